@@ -0,0 +1 @@
+CREATE INDEX idx_executions_task_executed ON executions(task_id, executed_at DESC, id DESC);
@@ -0,0 +1 @@
+CREATE INDEX idx_executions_executed_at ON executions(executed_at, id);
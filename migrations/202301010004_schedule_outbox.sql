@@ -0,0 +1,8 @@
+-- Schedule changes written in the same transaction as the task mutation that caused them.
+-- The scheduler consumes (and deletes) rows in order; a NULL trigger_at means the task was removed.
+CREATE TABLE schedule_outbox (
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+    task_id TEXT NOT NULL,
+    trigger_at DATETIME,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
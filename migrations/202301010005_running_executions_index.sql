@@ -0,0 +1,2 @@
+-- Finds executions left in the running state by a crash without scanning the whole history.
+CREATE INDEX idx_executions_running ON executions(executed_at, id) WHERE status = 'running';
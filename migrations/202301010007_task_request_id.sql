@@ -0,0 +1,2 @@
+-- The API request that created a task, propagated to its webhook calls for log correlation.
+ALTER TABLE tasks ADD COLUMN request_id TEXT;
@@ -0,0 +1,3 @@
+-- Per-task execution retention, enforced by the pruning job. NULL keeps everything.
+ALTER TABLE tasks ADD COLUMN keep_executions INTEGER;
+ALTER TABLE tasks ADD COLUMN keep_days INTEGER;
@@ -0,0 +1,5 @@
+-- Per-task webhook signing secrets. During a rotation the previous secret keeps signing
+-- until previous_secret_expires_at, so receivers can switch over without dropping requests.
+ALTER TABLE tasks ADD COLUMN signing_secret TEXT;
+ALTER TABLE tasks ADD COLUMN previous_signing_secret TEXT;
+ALTER TABLE tasks ADD COLUMN previous_secret_expires_at DATETIME;
@@ -0,0 +1,4 @@
+-- The API key that created a task, which is the tenant its quota counts against.
+ALTER TABLE tasks ADD COLUMN api_key_id BLOB;
+
+CREATE INDEX idx_tasks_api_key_active ON tasks(api_key_id) WHERE deleted_at IS NULL;
@@ -0,0 +1,11 @@
+-- Single-use links that fire a task without an API key. The URL carries an HMAC of the
+-- link under the task's signing secret; used_at is set when the link is redeemed.
+CREATE TABLE trigger_links (
+    id TEXT PRIMARY KEY NOT NULL,
+    task_id TEXT NOT NULL,
+    payload TEXT,
+    expires_at DATETIME NOT NULL,
+    used_at DATETIME,
+    created_at DATETIME NOT NULL,
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
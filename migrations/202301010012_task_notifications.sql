@@ -0,0 +1,2 @@
+-- Per-task notification policy as JSON; tasks without one send no notifications.
+ALTER TABLE tasks ADD COLUMN notifications TEXT;
@@ -0,0 +1,12 @@
+-- Groups organise tasks; tasks of a paused group are not dispatched.
+CREATE TABLE task_groups (
+    id BLOB PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT,
+    paused_at TEXT,
+    created_at TEXT NOT NULL
+);
+
+ALTER TABLE tasks ADD COLUMN group_id BLOB REFERENCES task_groups(id);
+
+CREATE INDEX idx_tasks_group ON tasks(group_id) WHERE group_id IS NOT NULL;
@@ -0,0 +1,3 @@
+-- Labels select tasks for bulk operations; paused tasks are not dispatched.
+ALTER TABLE tasks ADD COLUMN labels TEXT;
+ALTER TABLE tasks ADD COLUMN paused_at TEXT;
@@ -0,0 +1,4 @@
+-- Executions keep the payload they ran with so they can be replayed; replays point back at
+-- the execution they repeat.
+ALTER TABLE executions ADD COLUMN payload TEXT;
+ALTER TABLE executions ADD COLUMN replay_of BLOB REFERENCES executions(id);
@@ -0,0 +1,2 @@
+-- How long the executor took, for spotting runs that take far longer than usual.
+ALTER TABLE executions ADD COLUMN duration_ms INTEGER;
@@ -0,0 +1,2 @@
+-- Cron expression of `cron` tasks, which are rescheduled to its next occurrence.
+ALTER TABLE tasks ADD COLUMN cron_expr TEXT;
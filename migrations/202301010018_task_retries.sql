@@ -0,0 +1,3 @@
+-- Retry policy for failed executions, and how many retries the current run has used.
+ALTER TABLE tasks ADD COLUMN retry_policy TEXT;
+ALTER TABLE tasks ADD COLUMN retry_attempt INTEGER NOT NULL DEFAULT 0;
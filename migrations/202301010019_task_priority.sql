@@ -0,0 +1,2 @@
+-- Dispatch order among tasks that are due at the same time; higher runs first.
+ALTER TABLE tasks ADD COLUMN priority INTEGER NOT NULL DEFAULT 0;
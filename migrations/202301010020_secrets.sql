@@ -0,0 +1,8 @@
+-- Named secrets for payload placeholders, encrypted with SECRETS_KEY; the plaintext is
+-- never stored.
+CREATE TABLE secrets (
+    name TEXT PRIMARY KEY NOT NULL,
+    ciphertext BLOB NOT NULL,
+    created_at DATETIME NOT NULL,
+    updated_at DATETIME NOT NULL
+);
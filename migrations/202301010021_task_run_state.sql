@@ -0,0 +1,2 @@
+-- What a task's runs are doing: pending, running, completed or failed.
+ALTER TABLE tasks ADD COLUMN run_state TEXT NOT NULL DEFAULT 'pending';
@@ -0,0 +1,7 @@
+-- Named leases held by one instance at a time, such as the scheduler's leadership. A
+-- holder renews its lease before it expires; anyone may take an expired one.
+CREATE TABLE leases (
+    name TEXT PRIMARY KEY NOT NULL,
+    holder TEXT NOT NULL,
+    expires_at DATETIME NOT NULL
+);
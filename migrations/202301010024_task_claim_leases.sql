@@ -0,0 +1,4 @@
+-- The instance holding a task's claim and when the claim lapses unless it renews it, so a
+-- claim left by a crashed instance can be reclaimed by the others.
+ALTER TABLE tasks ADD COLUMN claimed_by TEXT;
+ALTER TABLE tasks ADD COLUMN claim_expires_at TEXT;
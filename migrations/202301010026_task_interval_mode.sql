@@ -0,0 +1,3 @@
+-- Whether an interval task's next trigger follows its last run's end (fixed_delay) or its
+-- last trigger (fixed_rate).
+ALTER TABLE tasks ADD COLUMN interval_mode TEXT NOT NULL DEFAULT 'fixed_delay';
@@ -0,0 +1,4 @@
+-- When a recurring task stops: after a date, after a number of scheduled runs, or both.
+ALTER TABLE tasks ADD COLUMN ends_at TEXT;
+ALTER TABLE tasks ADD COLUMN max_executions INTEGER;
+ALTER TABLE tasks ADD COLUMN run_count INTEGER NOT NULL DEFAULT 0;
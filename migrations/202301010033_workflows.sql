@@ -0,0 +1,21 @@
+-- Named graphs of tasks, and the runs advancing through them. Nodes are stored as JSON in
+-- dependency order; a run keeps each node's state by key.
+CREATE TABLE workflows (
+    id TEXT PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL,
+    nodes TEXT NOT NULL,
+    created_at DATETIME NOT NULL
+);
+
+CREATE TABLE workflow_runs (
+    id TEXT PRIMARY KEY NOT NULL,
+    workflow_id TEXT NOT NULL,
+    status TEXT NOT NULL,
+    nodes TEXT NOT NULL,
+    started_at DATETIME NOT NULL,
+    finished_at DATETIME,
+    FOREIGN KEY (workflow_id) REFERENCES workflows(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_workflow_runs_workflow ON workflow_runs(workflow_id, started_at);
+CREATE INDEX idx_workflow_runs_running ON workflow_runs(status) WHERE status = 'running';
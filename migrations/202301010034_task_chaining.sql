@@ -0,0 +1,2 @@
+-- Task started with the output of each successful run.
+ALTER TABLE tasks ADD COLUMN next_task_id BLOB;
@@ -0,0 +1,2 @@
+-- Condition on the output of the task a task depends on.
+ALTER TABLE tasks ADD COLUMN run_if TEXT;
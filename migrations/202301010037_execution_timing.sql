@@ -0,0 +1,4 @@
+-- When each run was due, and when it started and finished.
+ALTER TABLE executions ADD COLUMN scheduled_for TEXT;
+ALTER TABLE executions ADD COLUMN started_at TEXT;
+ALTER TABLE executions ADD COLUMN finished_at TEXT;
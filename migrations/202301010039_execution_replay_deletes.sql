@@ -0,0 +1,9 @@
+-- Deleting a replayed execution keeps its replays and forgets what they repeat, the way
+-- ON DELETE SET NULL would; SQLite cannot change the constraint of an existing column.
+CREATE INDEX idx_executions_replay_of ON executions(replay_of) WHERE replay_of IS NOT NULL;
+
+CREATE TRIGGER executions_replay_of_set_null
+BEFORE DELETE ON executions
+BEGIN
+    UPDATE executions SET replay_of = NULL WHERE replay_of = OLD.id;
+END;
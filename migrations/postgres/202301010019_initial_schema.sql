@@ -0,0 +1,83 @@
+-- PostgreSQL schema, equivalent to the SQLite migrations up to and including 202301010019.
+-- Later schema changes get a migration of the same version in both directories.
+CREATE TABLE task_groups (
+    id UUID PRIMARY KEY,
+    name TEXT NOT NULL,
+    description TEXT,
+    paused_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE TABLE tasks (
+    id UUID PRIMARY KEY,
+    name TEXT NOT NULL,
+    task_type TEXT NOT NULL,
+    trigger_at TIMESTAMPTZ NOT NULL,
+    interval_seconds BIGINT,
+    payload JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    deleted_at TIMESTAMPTZ,
+    request_id TEXT,
+    keep_executions BIGINT,
+    keep_days BIGINT,
+    signing_secret TEXT,
+    previous_signing_secret TEXT,
+    previous_secret_expires_at TIMESTAMPTZ,
+    api_key_id UUID,
+    notifications JSONB,
+    group_id UUID REFERENCES task_groups(id),
+    labels JSONB,
+    paused_at TIMESTAMPTZ,
+    cron_expr TEXT,
+    retry_policy JSONB,
+    retry_attempt BIGINT NOT NULL DEFAULT 0,
+    priority BIGINT NOT NULL DEFAULT 0
+);
+
+CREATE INDEX idx_tasks_trigger_at ON tasks(trigger_at);
+CREATE INDEX idx_tasks_api_key_active ON tasks(api_key_id) WHERE deleted_at IS NULL;
+CREATE INDEX idx_tasks_group ON tasks(group_id) WHERE group_id IS NOT NULL;
+
+CREATE TABLE executions (
+    id UUID PRIMARY KEY,
+    task_id UUID NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    executed_at TIMESTAMPTZ NOT NULL,
+    output JSONB NOT NULL,
+    status TEXT NOT NULL,
+    payload JSONB,
+    replay_of UUID REFERENCES executions(id),
+    duration_ms BIGINT
+);
+
+CREATE INDEX idx_executions_task_executed ON executions(task_id, executed_at DESC, id DESC);
+CREATE INDEX idx_executions_executed_at ON executions(executed_at, id);
+CREATE INDEX idx_executions_running ON executions(executed_at, id) WHERE status = 'running';
+
+-- Schedule changes written in the same transaction as the task mutation that caused them.
+-- The scheduler consumes (and deletes) rows in order; a NULL trigger_at means the task was removed.
+CREATE TABLE schedule_outbox (
+    seq BIGSERIAL PRIMARY KEY,
+    task_id UUID NOT NULL,
+    trigger_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- API keys are stored as SHA-256 hashes; the plaintext is only shown once, on creation.
+CREATE TABLE api_keys (
+    id UUID PRIMARY KEY,
+    name TEXT NOT NULL,
+    key_hash TEXT NOT NULL UNIQUE,
+    scope TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ
+);
+
+-- Single-use links that fire a task without an API key.
+CREATE TABLE trigger_links (
+    id UUID PRIMARY KEY,
+    task_id UUID NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    payload JSONB,
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL
+);
@@ -0,0 +1,8 @@
+-- Named secrets for payload placeholders, encrypted with SECRETS_KEY; the plaintext is
+-- never stored.
+CREATE TABLE secrets (
+    name TEXT PRIMARY KEY,
+    ciphertext BYTEA NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL
+);
@@ -0,0 +1,2 @@
+-- When the current run claimed the task, so a claim left by a crashed process can expire.
+ALTER TABLE tasks ADD COLUMN claimed_at TIMESTAMPTZ;
@@ -0,0 +1,2 @@
+-- What happens to runs a task missed while the scheduler was down: fire_once, fire_all or skip.
+ALTER TABLE tasks ADD COLUMN misfire_policy TEXT NOT NULL DEFAULT 'fire_once';
@@ -0,0 +1,4 @@
+-- When a recurring task stops: after a date, after a number of scheduled runs, or both.
+ALTER TABLE tasks ADD COLUMN ends_at TIMESTAMPTZ;
+ALTER TABLE tasks ADD COLUMN max_executions BIGINT;
+ALTER TABLE tasks ADD COLUMN run_count BIGINT NOT NULL DEFAULT 0;
@@ -0,0 +1,2 @@
+-- IANA time zone a cron task's expression is evaluated in; NULL means UTC.
+ALTER TABLE tasks ADD COLUMN timezone TEXT;
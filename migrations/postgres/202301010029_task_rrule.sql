@@ -0,0 +1,2 @@
+-- iCalendar recurrence rule of rrule tasks, including its DTSTART.
+ALTER TABLE tasks ADD COLUMN rrule TEXT;
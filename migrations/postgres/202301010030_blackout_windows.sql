@@ -0,0 +1,4 @@
+-- Periods a task's runs are held back in, and the original due time of deferred runs.
+ALTER TABLE tasks ADD COLUMN blackout_windows JSONB;
+ALTER TABLE tasks ADD COLUMN deferred_from TIMESTAMPTZ;
+ALTER TABLE executions ADD COLUMN deferred_from TIMESTAMPTZ;
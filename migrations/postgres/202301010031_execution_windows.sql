@@ -0,0 +1,2 @@
+-- Periods of the week a task may run in.
+ALTER TABLE tasks ADD COLUMN execution_windows JSONB;
@@ -0,0 +1,3 @@
+-- Task whose successful runs a task waits for.
+ALTER TABLE tasks ADD COLUMN depends_on UUID;
+CREATE INDEX idx_tasks_depends_on ON tasks(depends_on) WHERE depends_on IS NOT NULL;
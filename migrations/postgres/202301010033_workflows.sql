@@ -0,0 +1,20 @@
+-- Named graphs of tasks, and the runs advancing through them. Nodes are stored as JSON in
+-- dependency order; a run keeps each node's state by key.
+CREATE TABLE workflows (
+    id UUID PRIMARY KEY,
+    name TEXT NOT NULL,
+    nodes JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE TABLE workflow_runs (
+    id UUID PRIMARY KEY,
+    workflow_id UUID NOT NULL REFERENCES workflows(id) ON DELETE CASCADE,
+    status TEXT NOT NULL,
+    nodes JSONB NOT NULL,
+    started_at TIMESTAMPTZ NOT NULL,
+    finished_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_workflow_runs_workflow ON workflow_runs(workflow_id, started_at);
+CREATE INDEX idx_workflow_runs_running ON workflow_runs(status) WHERE status = 'running';
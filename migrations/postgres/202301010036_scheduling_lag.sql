@@ -0,0 +1,2 @@
+-- How late each scheduled run started after it was due.
+ALTER TABLE executions ADD COLUMN lag_ms BIGINT;
@@ -0,0 +1,3 @@
+-- Which try at a run each execution was, and why failed ones failed.
+ALTER TABLE executions ADD COLUMN attempt BIGINT;
+ALTER TABLE executions ADD COLUMN failure_kind TEXT;
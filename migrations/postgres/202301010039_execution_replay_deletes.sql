@@ -0,0 +1,5 @@
+-- Deleting a replayed execution keeps its replays and forgets what they repeat.
+ALTER TABLE executions DROP CONSTRAINT executions_replay_of_fkey;
+ALTER TABLE executions ADD CONSTRAINT executions_replay_of_fkey
+    FOREIGN KEY (replay_of) REFERENCES executions(id) ON DELETE SET NULL;
+CREATE INDEX idx_executions_replay_of ON executions(replay_of) WHERE replay_of IS NOT NULL;
@@ -0,0 +1,100 @@
+//! API key authentication and scope enforcement.
+//!
+//! Handlers declare the scope they need by taking an [`Authorized`] extractor, e.g.
+//! `Authorized<Operator>`. Keys are read from `Authorization: Bearer <key>` or `X-API-Key`.
+//! While authentication is disabled (no bootstrap key configured) every request passes.
+use crate::api::AppState;
+use crate::domain::{ApiKey, Scope};
+use crate::errors::AppError;
+use axum::extract::FromRequestParts;
+use axum::http::{header, request::Parts};
+use std::marker::PhantomData;
+
+/// Marker types naming the scope an [`Authorized`] extractor requires.
+pub trait RequiredScope {
+    const SCOPE: Scope;
+}
+
+pub struct ReadOnly;
+pub struct Operator;
+pub struct Admin;
+
+impl RequiredScope for ReadOnly {
+    const SCOPE: Scope = Scope::ReadOnly;
+}
+
+impl RequiredScope for Operator {
+    const SCOPE: Scope = Scope::Operator;
+}
+
+impl RequiredScope for Admin {
+    const SCOPE: Scope = Scope::Admin;
+}
+
+/// Extractor that rejects the request unless it carries a key with at least scope `S`.
+///
+/// Holds the authenticated key, or `None` while authentication is disabled.
+pub struct Authorized<S> {
+    pub key: Option<ApiKey>,
+    _scope: PhantomData<S>,
+}
+
+impl<S> FromRequestParts<AppState> for Authorized<S>
+where
+    S: RequiredScope + Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, AppError> {
+        if !state.service.config().auth_enabled() {
+            return Ok(Self {
+                key: None,
+                _scope: PhantomData,
+            });
+        }
+
+        let presented = presented_key(parts).ok_or(AppError::Unauthorized)?;
+        let key = state.service.authenticate(presented).await?;
+        if key.scope < S::SCOPE {
+            tracing::warn!(key_id = %key.id, have = ?key.scope, need = ?S::SCOPE, "Insufficient scope");
+            return Err(AppError::Forbidden);
+        }
+
+        Ok(Self {
+            key: Some(key),
+            _scope: PhantomData,
+        })
+    }
+}
+
+impl<S> Authorized<S> {
+    /// Checks that the request also holds `scope`, for handlers whose requirement depends on
+    /// the request itself. Always passes while authentication is disabled.
+    ///
+    /// # Errors
+    ///
+    /// * `AppError::Forbidden` - If the key's scope is below `scope`.
+    pub fn require(&self, scope: Scope) -> Result<(), AppError> {
+        match &self.key {
+            Some(key) if key.scope < scope => {
+                tracing::warn!(key_id = %key.id, have = ?key.scope, need = ?scope, "Insufficient scope");
+                Err(AppError::Forbidden)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The key sent as a bearer token or in the `X-API-Key` header.
+fn presented_key(parts: &Parts) -> Option<&str> {
+    let bearer = parts
+        .headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+
+    bearer
+        .or_else(|| parts.headers.get("x-api-key").and_then(|v| v.to_str().ok()))
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+}
@@ -1,3 +1,11 @@
+use crate::domain::{
+    ApiKey, BlackoutWindow, Dependency, Execution, ExecutionRetry, ExecutionStatus,
+    ExecutionSummary, FailureKind, GroupStats, IntervalMode, Labels, MisfirePolicy, Notifications,
+    Scope, StoredSecret, Task, TaskGroup, TaskRuns, TaskState, TaskSummary, TaskType, WorkflowNode,
+};
+use crate::executor::condition::RunCondition;
+use crate::schedule::{ExecutionWindow, ScheduleIssue};
+use crate::service::maintenance::MaintenanceWindow;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -7,12 +15,216 @@ use uuid::Uuid;
 #[derive(Deserialize)]
 pub struct CreateTaskReq {
     pub name: String,
+    /// `once`, `interval`, `cron` or `rrule`; may be omitted if `schedule` is given.
+    #[serde(default)]
     pub task_type: String,
+    /// When the task first runs; required unless `schedule` is given.
+    pub trigger_at: Option<DateTime<Utc>>,
+    /// A human-friendly schedule such as `every 15 minutes`, `daily at 09:30` or
+    /// `in 2 hours`, in place of `task_type`, `trigger_at` and the schedule fields.
+    pub schedule: Option<String>,
+    pub interval_seconds: Option<i64>,
+    /// Cron expression of cron tasks, e.g. `0 9 * * 1-5` (UTC).
+    pub cron_expr: Option<String>,
+    pub payload: Option<Value>,
+    /// Keep only this many of the task's most recent executions.
+    pub keep_executions: Option<i64>,
+    /// Keep the task's executions for this many days.
+    pub keep_days: Option<i64>,
+    /// Which events are sent to which channels; no notifications if omitted.
+    pub notifications: Option<Notifications>,
+    /// Group to file the task under.
+    pub group_id: Option<Uuid>,
+    /// Labels for selecting the task in bulk operations.
+    pub labels: Option<Labels>,
+    /// Retries of failed executions; failures are not retried if omitted.
+    pub retry_policy: Option<ExecutionRetry>,
+    /// Higher runs first when several tasks are due at once; defaults to 0.
+    pub priority: Option<i64>,
+    /// How runs missed during downtime are caught up: `fire_once` (default), `fire_all` or
+    /// `skip`. Recurring tasks only.
+    pub misfire_policy: Option<MisfirePolicy>,
+    /// `fixed_delay` (default) schedules the next run one interval after a run finishes,
+    /// `fixed_rate` one interval after its trigger. Interval tasks only.
+    pub interval_mode: Option<IntervalMode>,
+    /// The task completes instead of running after this time. Recurring tasks only.
+    pub ends_at: Option<DateTime<Utc>>,
+    /// The task completes after this many scheduled runs. Recurring tasks only.
+    pub max_executions: Option<i64>,
+    /// IANA time zone the cron expression or recurrence rule is evaluated in, e.g.
+    /// `Europe/Oslo`; UTC if omitted. Cron and rrule tasks only.
+    pub timezone: Option<String>,
+    /// iCalendar recurrence rule of rrule tasks, e.g. `FREQ=MONTHLY;BYDAY=2TU`. Without a
+    /// `DTSTART`, the rule starts at `trigger_at`.
+    pub rrule: Option<String>,
+    /// Periods the task does not run in, on top of the global `BLACKOUT_WINDOWS`.
+    pub blackout_windows: Option<Vec<BlackoutWindow>>,
+    /// Periods of the week the task may run in, in `timezone`, e.g.
+    /// `[{"days": ["mon", "tue", "wed", "thu", "fri"], "start": "08:00", "end": "18:00"}]`.
+    pub execution_windows: Option<Vec<ExecutionWindow>>,
+    /// Task whose successful runs this task waits for.
+    pub depends_on: Option<Uuid>,
+    /// Task started after each successful run. Its payload may use `{{previous.output}}`,
+    /// or a field of it such as `{{previous.output.body.id}}`, to receive this run's output.
+    pub next_task_id: Option<Uuid>,
+    /// Condition on the output of the `depends_on` task's latest successful run, e.g.
+    /// `{"path": "$.changed", "op": "eq", "value": true}`; runs that find it unmet are
+    /// recorded as `skipped`.
+    pub run_if: Option<RunCondition>,
+}
+
+/// Request DTO for previewing a schedule before creating a task with it. Takes the
+/// schedule fields of [`CreateTaskReq`].
+#[derive(Deserialize, Default)]
+pub struct PreviewScheduleReq {
+    #[serde(default)]
+    pub task_type: String,
+    /// When the schedule starts; defaults to now.
+    pub trigger_at: Option<DateTime<Utc>>,
+    pub schedule: Option<String>,
+    pub interval_seconds: Option<i64>,
+    pub cron_expr: Option<String>,
+    pub rrule: Option<String>,
+    pub timezone: Option<String>,
+    /// How many fire times to return; defaults to 10, at most 100.
+    pub count: Option<usize>,
+}
+
+/// Request DTO for checking cron expressions and recurrence rules; at least one of them
+/// is required.
+#[derive(Deserialize, Default)]
+pub struct ValidateScheduleReq {
+    pub cron_expr: Option<String>,
+    pub rrule: Option<String>,
+    pub timezone: Option<String>,
+}
+
+/// A problem found in a schedule, with the request field it was found in.
+#[derive(Serialize)]
+pub struct ScheduleIssueResponse {
+    pub field: &'static str,
+    #[serde(flatten)]
+    pub issue: ScheduleIssue,
+}
+
+/// Request DTO for replacing a task's definition. The task type cannot change.
+#[derive(Deserialize)]
+pub struct ReplaceTaskReq {
+    pub name: String,
     pub trigger_at: DateTime<Utc>,
+    /// Required for interval tasks, not allowed for others.
+    pub interval_seconds: Option<i64>,
+    /// Required for cron tasks, not allowed for others.
+    pub cron_expr: Option<String>,
+    /// Replaces the payload; an empty object if omitted.
+    pub payload: Option<Value>,
+}
+
+/// Request DTO for changing some of a task's fields; omitted fields are left as they are.
+#[derive(Deserialize, Default)]
+pub struct UpdateTaskReq {
+    pub name: Option<String>,
+    pub trigger_at: Option<DateTime<Utc>>,
+    /// Interval tasks only.
     pub interval_seconds: Option<i64>,
+    /// Cron tasks only.
+    pub cron_expr: Option<String>,
+    /// Replaces the payload.
+    pub payload: Option<Value>,
+}
+
+/// Request DTO for cloning a task. Omitted fields are copied from the source task.
+#[derive(Deserialize, Default)]
+pub struct CloneTaskReq {
+    pub name: Option<String>,
+    pub trigger_at: Option<DateTime<Utc>>,
+    /// Fields merged over the source payload; a non-object value replaces it outright.
+    pub payload: Option<Value>,
+}
+
+/// Request DTO for running a task immediately.
+#[derive(Deserialize, Default)]
+pub struct RunTaskReq {
+    /// Restart an interval task's cadence from this run instead of keeping its schedule.
+    #[serde(default)]
+    pub reset_cadence: bool,
+}
+
+/// Default and maximum overlap during which a rotated-out signing secret keeps signing
+/// requests.
+pub const DEFAULT_SECRET_OVERLAP_SECONDS: i64 = 24 * 60 * 60;
+pub const MAX_SECRET_OVERLAP_SECONDS: i64 = 30 * 24 * 60 * 60;
+
+/// Request DTO for rotating a task's signing secret.
+#[derive(Deserialize)]
+pub struct RotateSecretReq {
+    pub overlap_seconds: Option<i64>,
+}
+
+/// Response DTO for a rotated signing secret. The new secret is not shown again.
+#[derive(Serialize)]
+pub struct RotatedSecretResponse {
+    pub signing_secret: String,
+    /// Until then, requests also carry a signature made with the previous secret.
+    pub previous_secret_expires_at: DateTime<Utc>,
+}
+
+/// Default and maximum lifetime of a trigger link.
+pub const DEFAULT_TRIGGER_LINK_TTL_SECONDS: i64 = 24 * 60 * 60;
+pub const MAX_TRIGGER_LINK_TTL_SECONDS: i64 = 30 * 24 * 60 * 60;
+
+/// Request DTO for minting a trigger link.
+#[derive(Deserialize, Default)]
+pub struct CreateTriggerLinkReq {
+    pub ttl_seconds: Option<i64>,
+    /// Fields merged over the task's payload when the link fires.
     pub payload: Option<Value>,
 }
 
+/// Response DTO for a minted trigger link.
+#[derive(Serialize)]
+pub struct TriggerLinkResponse {
+    pub id: Uuid,
+    /// Absolute if `PUBLIC_BASE_URL` is configured, otherwise a path on the API.
+    pub url: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Default `Retry-After` sent on mutations rejected during maintenance.
+pub const DEFAULT_MAINTENANCE_RETRY_AFTER_SECONDS: u64 = 300;
+
+/// Request DTO for entering maintenance mode.
+#[derive(Deserialize, Default)]
+pub struct MaintenanceReq {
+    /// Also stop dispatching due tasks.
+    #[serde(default)]
+    pub pause_scheduler: bool,
+    pub retry_after_seconds: Option<u64>,
+    pub reason: Option<String>,
+}
+
+/// Response DTO for the maintenance status.
+#[derive(Serialize)]
+pub struct MaintenanceResponse {
+    pub enabled: bool,
+    #[serde(flatten)]
+    pub window: Option<MaintenanceWindow>,
+}
+
+/// Query parameters of `DELETE /tasks/{id}`.
+#[derive(Deserialize)]
+pub struct DeleteTaskQuery {
+    /// Remove the task and its executions for good instead of moving it to the recycle bin.
+    #[serde(default)]
+    pub hard: bool,
+}
+
+/// Query parameters of a trigger link URL.
+#[derive(Deserialize)]
+pub struct TriggerLinkQuery {
+    pub sig: String,
+}
+
 /// Response DTO for returning task details.
 #[derive(Serialize)]
 pub struct TaskResponse {
@@ -21,7 +233,39 @@ pub struct TaskResponse {
     pub task_type: String,
     pub trigger_at: DateTime<Utc>,
     pub interval_seconds: Option<i64>,
+    pub cron_expr: Option<String>,
     pub payload: Value,
+    pub retry_policy: Option<ExecutionRetry>,
+    pub priority: i64,
+    pub misfire_policy: MisfirePolicy,
+    pub interval_mode: IntervalMode,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub max_executions: Option<i64>,
+    pub run_count: i64,
+    pub timezone: Option<String>,
+    pub rrule: Option<String>,
+    pub blackout_windows: Option<Vec<BlackoutWindow>>,
+    pub execution_windows: Option<Vec<ExecutionWindow>>,
+    pub depends_on: Option<Uuid>,
+    pub next_task_id: Option<Uuid>,
+    pub run_if: Option<RunCondition>,
+    /// Whether the task's next run can go ahead; only on single-task responses.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dependency: Option<Dependency>,
+    /// When the pending run was due, if a blackout window deferred it.
+    pub deferred_from: Option<DateTime<Utc>>,
+    /// `active`, `paused` or `deleted`.
+    pub status: String,
+    /// `pending`, `running`, `completed` or `failed`.
+    pub run_state: String,
+    pub paused_at: Option<DateTime<Utc>>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub group_id: Option<Uuid>,
+    pub labels: Option<Labels>,
+    /// `None` while the task is deleted or paused.
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_status: Option<String>,
 }
 
 /// Response DTO for returning a summary of a task.
@@ -30,5 +274,313 @@ pub struct TaskSummaryResponse {
     pub id: Uuid,
     pub name: String,
     pub status: String,
+    pub run_state: String,
     pub deleted_at: Option<DateTime<Utc>>,
+    pub group_id: Option<Uuid>,
+    /// `None` while the task is deleted or paused.
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_status: Option<String>,
+}
+
+/// Query parameters for `GET /tasks`.
+#[derive(Deserialize)]
+pub struct ListTasksQuery {
+    /// `active` or `deleted`; both are listed when omitted.
+    pub state: Option<TaskState>,
+    /// Only list tasks of this group.
+    pub group_id: Option<Uuid>,
+}
+
+/// Request DTO for creating a task group.
+#[derive(Deserialize)]
+pub struct CreateGroupReq {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+/// Request DTO for creating a workflow.
+#[derive(Deserialize)]
+pub struct CreateWorkflowReq {
+    pub name: String,
+    /// The graph, e.g. `[{"key": "fetch", "task_id": "..."}, {"key": "report", "task_id":
+    /// "...", "after": ["fetch"], "compensate_with": "..."}]`.
+    pub nodes: Vec<WorkflowNode>,
+}
+
+/// Response DTO for a task group with its aggregated numbers.
+#[derive(Serialize)]
+pub struct GroupResponse {
+    #[serde(flatten)]
+    pub group: TaskGroup,
+    pub stats: GroupStats,
+}
+
+/// Request DTO for bulk pause and resume. Exactly one of the selectors must be given.
+#[derive(Deserialize)]
+pub struct BulkPauseReq {
+    /// Tasks carrying every one of these labels.
+    pub labels: Option<Labels>,
+    /// Tasks in this group.
+    pub group_id: Option<Uuid>,
+}
+
+/// Response DTO for bulk pause and resume.
+#[derive(Serialize)]
+pub struct BulkPauseResponse {
+    /// Tasks whose state changed.
+    pub affected: u64,
+}
+
+/// Query parameters for paginated execution listings.
+#[derive(Deserialize)]
+pub struct ExecutionPageQuery {
+    pub limit: Option<i64>,
+    pub cursor: Option<String>,
+    /// List full records, with their output, instead of summaries.
+    #[serde(default)]
+    pub include_output: bool,
+}
+
+/// Response DTO for returning an execution record.
+#[derive(Serialize)]
+pub struct ExecutionResponse {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub executed_at: DateTime<Utc>,
+    pub status: String,
+    pub output: Value,
+    /// The execution this one replays, if any.
+    pub replay_of: Option<Uuid>,
+    pub duration_ms: Option<i64>,
+    /// When the run was originally due, if a blackout window deferred it.
+    pub deferred_from: Option<DateTime<Utc>>,
+    /// How late the scheduler started the run, in milliseconds.
+    pub lag_ms: Option<i64>,
+    /// When the run was due, for scheduled runs.
+    pub scheduled_for: Option<DateTime<Utc>>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    /// 1 for the first try at a run, 2 for its first retry and so on.
+    pub attempt: Option<i64>,
+    /// Why the execution failed: `timeout`, `connection`, `http_status`, `validation` or
+    /// `other`.
+    pub failure_kind: Option<FailureKind>,
+}
+
+/// Response DTO for an execution in a list, without its output.
+#[derive(Serialize)]
+pub struct ExecutionSummaryResponse {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub executed_at: DateTime<Utc>,
+    pub status: String,
+}
+
+/// Response DTO for a page of executions.
+#[derive(Serialize)]
+pub struct ExecutionPageResponse<T = ExecutionSummaryResponse> {
+    pub items: Vec<T>,
+    /// Opaque cursor for the next page; absent on the last page.
+    pub next_cursor: Option<String>,
+}
+
+impl TaskResponse {
+    pub fn new(task: Task, runs: TaskRuns) -> Self {
+        let status = task.status().as_str().to_string();
+        Self {
+            id: task.id.to_string(),
+            name: task.name,
+            task_type: match task.task_type {
+                TaskType::Once => "once".to_string(),
+                TaskType::Interval => "interval".to_string(),
+                TaskType::Cron => "cron".to_string(),
+                TaskType::Rrule => "rrule".to_string(),
+            },
+            trigger_at: task.trigger_at,
+            interval_seconds: task.interval_seconds,
+            cron_expr: task.cron_expr,
+            payload: task.payload,
+            retry_policy: task.retry_policy,
+            priority: task.priority,
+            misfire_policy: task.misfire_policy,
+            interval_mode: task.interval_mode,
+            ends_at: task.ends_at,
+            max_executions: task.max_executions,
+            run_count: task.run_count,
+            timezone: task.timezone,
+            rrule: task.rrule,
+            blackout_windows: task.blackout_windows,
+            execution_windows: task.execution_windows,
+            depends_on: task.depends_on,
+            next_task_id: task.next_task_id,
+            run_if: task.run_if,
+            dependency: None,
+            deferred_from: task.deferred_from,
+            status,
+            run_state: task.run_state.as_str().to_string(),
+            paused_at: task.paused_at,
+            deleted_at: task.deleted_at,
+            group_id: task.group_id,
+            labels: task.labels,
+            next_run_at: runs.next_run_at,
+            last_run_at: runs.last_run_at,
+            last_status: runs.last_status.as_ref().map(status_label),
+        }
+    }
+}
+
+impl From<TaskSummary> for TaskSummaryResponse {
+    fn from(task: TaskSummary) -> Self {
+        let status = task.status().as_str().to_string();
+        Self {
+            id: task.id,
+            name: task.name,
+            status,
+            run_state: task.run_state.as_str().to_string(),
+            deleted_at: task.deleted_at,
+            group_id: task.group_id,
+            next_run_at: task.runs.next_run_at,
+            last_run_at: task.runs.last_run_at,
+            last_status: task.runs.last_status.as_ref().map(status_label),
+        }
+    }
+}
+
+impl From<Execution> for ExecutionResponse {
+    fn from(exec: Execution) -> Self {
+        Self {
+            id: exec.id,
+            task_id: exec.task_id,
+            executed_at: exec.executed_at,
+            status: status_label(&exec.status),
+            output: exec.output,
+            replay_of: exec.replay_of,
+            duration_ms: exec.duration_ms,
+            deferred_from: exec.deferred_from,
+            lag_ms: exec.lag_ms,
+            scheduled_for: exec.scheduled_for,
+            started_at: exec.started_at,
+            finished_at: exec.finished_at,
+            attempt: exec.attempt,
+            failure_kind: exec.failure_kind,
+        }
+    }
+}
+
+impl From<ExecutionSummary> for ExecutionSummaryResponse {
+    fn from(exec: ExecutionSummary) -> Self {
+        Self {
+            id: exec.id,
+            task_id: exec.task_id,
+            executed_at: exec.executed_at,
+            status: status_label(&exec.status),
+        }
+    }
+}
+
+fn status_label(status: &ExecutionStatus) -> String {
+    status.as_str().to_string()
+}
+
+/// Output format for execution exports.
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Ndjson,
+    Csv,
+}
+
+/// Query parameters for `GET /executions/export`.
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    #[serde(default)]
+    pub format: ExportFormat,
+    pub since: Option<DateTime<Utc>>,
+}
+
+/// Query parameters for `GET /admin/snapshot`.
+#[derive(Deserialize, Default)]
+pub struct SnapshotQuery {
+    #[serde(default)]
+    pub include_executions: bool,
+    #[serde(default)]
+    pub include_secrets: bool,
+}
+
+/// Response DTO for a restored snapshot: how much of each kind was written.
+#[derive(Serialize)]
+pub struct RestoreSnapshotResponse {
+    pub groups: usize,
+    pub tasks: usize,
+    pub workflows: usize,
+    pub executions: usize,
+}
+
+/// Request DTO for creating an API key.
+#[derive(Deserialize)]
+pub struct CreateApiKeyReq {
+    pub name: String,
+    pub scope: Scope,
+}
+
+/// Request DTO for changing an API key's scope.
+#[derive(Deserialize)]
+pub struct UpdateApiKeyReq {
+    pub scope: Scope,
+}
+
+/// Response DTO for an API key's metadata.
+#[derive(Serialize)]
+pub struct ApiKeyResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub scope: Scope,
+    pub created_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+/// Response DTO for a newly created API key, the only time its plaintext is returned.
+#[derive(Serialize)]
+pub struct CreatedApiKeyResponse {
+    #[serde(flatten)]
+    pub metadata: ApiKeyResponse,
+    pub key: String,
+}
+
+impl From<ApiKey> for ApiKeyResponse {
+    fn from(key: ApiKey) -> Self {
+        Self {
+            id: key.id,
+            name: key.name,
+            scope: key.scope,
+            created_at: key.created_at,
+            revoked_at: key.revoked_at,
+        }
+    }
+}
+
+/// Request DTO for storing a secret's value.
+#[derive(Deserialize)]
+pub struct PutSecretReq {
+    pub value: String,
+}
+
+/// Response DTO for a secret's metadata; its value is never returned.
+#[derive(Serialize)]
+pub struct SecretResponse {
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<StoredSecret> for SecretResponse {
+    fn from(secret: StoredSecret) -> Self {
+        Self {
+            name: secret.name,
+            created_at: secret.created_at,
+            updated_at: secret.updated_at,
+        }
+    }
 }
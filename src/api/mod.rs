@@ -1,14 +1,34 @@
+pub mod auth;
 pub mod dto;
 
-use crate::api::dto::{CreateTaskReq, TaskSummaryResponse};
+use crate::api::auth::{Admin, Authorized, Operator, ReadOnly};
+use crate::api::dto::{
+    ApiKeyResponse, BulkPauseReq, BulkPauseResponse, CloneTaskReq, CreateApiKeyReq, CreateGroupReq,
+    CreateTaskReq, CreateTriggerLinkReq, CreateWorkflowReq, CreatedApiKeyResponse,
+    DEFAULT_MAINTENANCE_RETRY_AFTER_SECONDS, DEFAULT_SECRET_OVERLAP_SECONDS,
+    DEFAULT_TRIGGER_LINK_TTL_SECONDS, DeleteTaskQuery, ExecutionPageQuery, ExecutionPageResponse,
+    ExecutionResponse, ExecutionSummaryResponse, ExportFormat, ExportQuery, GroupResponse,
+    ListTasksQuery, MAX_SECRET_OVERLAP_SECONDS, MAX_TRIGGER_LINK_TTL_SECONDS, MaintenanceReq,
+    MaintenanceResponse, PreviewScheduleReq, PutSecretReq, ReplaceTaskReq, RestoreSnapshotResponse,
+    RotateSecretReq, RotatedSecretResponse, RunTaskReq, ScheduleIssueResponse, SecretResponse,
+    SnapshotQuery, TaskResponse, TaskSummaryResponse, TriggerLinkQuery, TriggerLinkResponse,
+    UpdateApiKeyReq, UpdateTaskReq, ValidateScheduleReq,
+};
+use crate::domain::{
+    ApiKey, Scope, Snapshot, TaskFilter, TaskGroup, TaskSelector, TaskState, Workflow, WorkflowRun,
+};
 use crate::errors::AppError;
-use crate::service::TaskService;
+use crate::service::{RequestContext, TaskService, trigger_links};
 use axum::{
-    Json, Router,
-    extract::{Path, Request, State},
-    http::{HeaderValue, StatusCode},
-    routing::{delete, post},
+    Extension, Json, Router,
+    body::{Body, Bytes},
+    extract::{DefaultBodyLimit, Path, Query, Request, State},
+    http::{HeaderValue, Method, StatusCode, header},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{delete, get, patch, post, put},
 };
+use futures_util::{StreamExt, stream};
 use serde_json::{Value, json};
 use tower_http::services::ServeDir;
 use tower_http::{
@@ -18,6 +38,9 @@ use tower_http::{
 
 use uuid::Uuid;
 
+/// Largest snapshot accepted for restoring, in bytes.
+const MAX_SNAPSHOT_BYTES: usize = 256 * 1024 * 1024;
+
 #[cfg(test)]
 mod tests;
 #[derive(Clone)]
@@ -48,14 +71,117 @@ impl MakeRequestId for MakeUuidRequest {
 /// # Returns
 /// * `Router` - The configured Axum router
 pub fn router(service: TaskService) -> Router {
-    let state = AppState { service };
+    with_middleware(public_routes().merge(admin_routes()), service)
+}
 
-    let x_request_id = "x-request-id".parse::<axum::http::HeaderName>().unwrap();
+/// Build the router for the public listener when admin routes are served on their own
+/// listener: tasks, executions, health checks and the dashboard.
+///
+/// # Arguments
+///
+/// * `service` - An instance of TaskService to handle business logic
+pub fn public_router(service: TaskService) -> Router {
+    with_middleware(public_routes(), service)
+}
+
+/// Build the router for a dedicated admin listener: key management and health checks.
+///
+/// # Arguments
+///
+/// * `service` - An instance of TaskService to handle business logic
+pub fn admin_router(service: TaskService) -> Router {
+    with_middleware(admin_routes().merge(health_routes()), service)
+}
 
+fn public_routes() -> Router<AppState> {
     Router::new()
         .fallback_service(ServeDir::new("static"))
         .route("/tasks", post(create_task).get(list_tasks))
-        .route("/tasks/{id}", delete(delete_task))
+        .route("/tasks/bulk/pause", post(bulk_pause))
+        .route("/tasks/bulk/resume", post(bulk_resume))
+        .route(
+            "/tasks/{id}",
+            get(get_task)
+                .put(update_task)
+                .patch(patch_task)
+                .delete(delete_task),
+        )
+        .route("/tasks/{id}/purge", delete(purge_task))
+        .route("/tasks/{id}/clone", post(clone_task))
+        .route("/tasks/{id}/run", post(run_task))
+        .route("/tasks/{id}/skip-next", post(skip_next))
+        .route("/schedules/preview", post(preview_schedule))
+        .route("/schedules/validate", post(validate_schedule))
+        .route("/tasks/{id}/pause", post(pause_task))
+        .route("/tasks/{id}/resume", post(resume_task))
+        .route("/tasks/{id}/rotate-secret", post(rotate_secret))
+        .route("/tasks/{id}/trigger-links", post(create_trigger_link))
+        .route(
+            "/trigger/{link_id}",
+            get(fire_trigger_link).post(fire_trigger_link),
+        )
+        .route("/tasks/{id}/executions", get(list_executions))
+        .route("/groups", post(create_group).get(list_groups))
+        .route("/groups/{id}", get(get_group))
+        .route("/groups/{id}/pause", post(pause_group))
+        .route("/groups/{id}/resume", post(resume_group))
+        .route("/workflows", post(create_workflow).get(list_workflows))
+        .route("/workflows/{id}", get(get_workflow))
+        .route(
+            "/workflows/{id}/runs",
+            post(start_workflow).get(list_workflow_runs),
+        )
+        .route("/workflow-runs/{id}", get(get_workflow_run))
+        .route("/executions/export", get(export_executions))
+        .route("/executions/{id}", get(get_execution))
+        .route("/executions/{id}/replay", post(replay_execution))
+        .route("/executions/{id}/cancel", post(cancel_execution))
+        .merge(health_routes())
+}
+
+fn admin_routes() -> Router<AppState> {
+    Router::new()
+        .route("/admin/api-keys", post(create_api_key).get(list_api_keys))
+        .route(
+            "/admin/api-keys/{id}",
+            patch(update_api_key).delete(revoke_api_key),
+        )
+        .route("/admin/secrets", get(list_secrets))
+        .route(
+            "/admin/secrets/{name}",
+            put(put_secret).delete(delete_secret),
+        )
+        .route("/admin/snapshot", get(export_snapshot))
+        .route(
+            "/admin/snapshot/restore",
+            post(restore_snapshot).layer(DefaultBodyLimit::max(MAX_SNAPSHOT_BYTES)),
+        )
+        .route(
+            "/admin/maintenance",
+            get(get_maintenance)
+                .put(enter_maintenance)
+                .delete(exit_maintenance),
+        )
+}
+
+fn health_routes() -> Router<AppState> {
+    Router::new()
+        .route("/health/live", get(liveness))
+        .route("/health/ready", get(readiness))
+        .route("/metrics", get(metrics))
+}
+
+/// Attaches the state plus request-id and tracing layers shared by every listener.
+fn with_middleware(routes: Router<AppState>, service: TaskService) -> Router {
+    let state = AppState { service };
+
+    let x_request_id = "x-request-id".parse::<axum::http::HeaderName>().unwrap();
+
+    routes
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            reject_writes_when_read_only,
+        ))
         .with_state(state)
         .layer(
             TraceLayer::new_for_http()
@@ -80,73 +206,1164 @@ pub fn router(service: TaskService) -> Router {
         .layer(SetRequestIdLayer::new(x_request_id, MakeUuidRequest))
 }
 
+/// Rejects mutations on read-only followers (403) and while maintenance mode is active
+/// (503). Reads keep working, and during maintenance admin routes stay open so it can be
+/// ended. Trigger links are mutations even when fetched with GET, and schedule previews
+/// and checks are reads even though they are POSTed.
+async fn reject_writes_when_read_only(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let path = request.uri().path();
+    let read_only = (matches!(
+        *request.method(),
+        Method::GET | Method::HEAD | Method::OPTIONS
+    ) && !path.starts_with("/trigger/"))
+        || path == "/schedules/preview"
+        || path == "/schedules/validate";
+
+    if !read_only && state.service.config().follower {
+        return AppError::ReadOnlyInstance.into_response();
+    }
+
+    if !read_only
+        && !path.starts_with("/admin/")
+        && let Some(window) = state.service.maintenance().current()
+    {
+        return AppError::Maintenance {
+            retry_after_secs: window.retry_after_secs,
+        }
+        .into_response();
+    }
+
+    next.run(request).await
+}
+
+/// Combines the authenticated key and the request's `x-request-id` for the service.
+fn request_context(
+    api_key: Option<ApiKey>,
+    request_id: Option<Extension<RequestId>>,
+) -> RequestContext {
+    RequestContext {
+        request_id: request_id
+            .and_then(|Extension(id)| id.header_value().to_str().ok().map(str::to_string)),
+        api_key,
+    }
+}
+
 /// Handler to create a new task
 ///
 /// # Arguments
 ///
 /// * `State(state)` - Application state containing the TaskService
+/// * `request_id` - The request's `x-request-id`, stored on the task for correlation
 /// * `Json(payload)` - JSON payload containing task creation details
 ///
 /// # Errors
 ///
 /// * `AppError` - If task creation fails (see TaskService::create_task for details)
 async fn create_task(
+    auth: Authorized<Operator>,
     State(state): State<AppState>,
+    request_id: Option<Extension<RequestId>>,
     Json(payload): Json<CreateTaskReq>,
 ) -> Result<Json<Value>, AppError> {
-    let task_id = state.service.create_task(payload).await?;
+    let ctx = request_context(auth.key, request_id);
+    let task = state.service.create_task(payload, ctx).await?;
+
+    tracing::info!(task_id = %task.id, "Task Created Successfully");
+
+    // The signing secret is only ever returned here and on rotation
+    Ok(Json(json!({
+        "status": "created",
+        "id": task.id,
+        "trigger_at": task.trigger_at,
+        "signing_secret": task.signing_secret
+    })))
+}
+
+/// Handler to replace a task's name, schedule and payload
+///
+/// # Arguments
+///
+/// * `State(state)` - Application state containing the TaskService
+/// * `Path(task_id)` - Path parameter containing the UUID of the task to update
+/// * `Json(payload)` - JSON body with the task's new `name`, `trigger_at`, `interval_seconds`
+///   or `cron_expr`, and `payload`
+///
+/// # Errors
+///
+/// * `AppError` - If the task is missing or the update is invalid (see TaskService::update_task)
+async fn update_task(
+    _auth: Authorized<Operator>,
+    State(state): State<AppState>,
+    Path(task_id): Path<Uuid>,
+    Json(payload): Json<ReplaceTaskReq>,
+) -> Result<Json<TaskResponse>, AppError> {
+    state.service.update_task(task_id, payload).await?;
+    let (task, runs) = state.service.get_task(task_id).await?;
+
+    Ok(Json(TaskResponse::new(task, runs)))
+}
+
+/// Handler to change some of a task's fields
+///
+/// # Arguments
+///
+/// * `State(state)` - Application state containing the TaskService
+/// * `Path(task_id)` - Path parameter containing the UUID of the task to update
+/// * `Json(payload)` - JSON body with the fields to change; omitted fields are kept
+///
+/// # Errors
+///
+/// * `AppError` - If the task is missing or the change is invalid (see TaskService::patch_task)
+async fn patch_task(
+    _auth: Authorized<Operator>,
+    State(state): State<AppState>,
+    Path(task_id): Path<Uuid>,
+    Json(payload): Json<UpdateTaskReq>,
+) -> Result<Json<TaskResponse>, AppError> {
+    state.service.patch_task(task_id, payload).await?;
+    let (task, runs) = state.service.get_task(task_id).await?;
+
+    Ok(Json(TaskResponse::new(task, runs)))
+}
+
+/// Handler to create a copy of an existing task
+///
+/// # Arguments
+///
+/// * `State(state)` - Application state containing the TaskService
+/// * `request_id` - The request's `x-request-id`, stored on the new task for correlation
+/// * `Path(task_id)` - Path parameter containing the UUID of the task to copy
+/// * `payload` - Optional JSON body with `name`, `trigger_at` and `payload` overrides
+///
+/// # Errors
+///
+/// * `AppError` - If the source task is missing or cloning fails (see TaskService::clone_task)
+async fn clone_task(
+    auth: Authorized<Operator>,
+    State(state): State<AppState>,
+    request_id: Option<Extension<RequestId>>,
+    Path(task_id): Path<Uuid>,
+    payload: Option<Json<CloneTaskReq>>,
+) -> Result<Json<Value>, AppError> {
+    let ctx = request_context(auth.key, request_id);
+    let overrides = payload.map(|Json(req)| req).unwrap_or_default();
+    let task = state.service.clone_task(task_id, overrides, ctx).await?;
+
+    tracing::info!(source = %task_id, task_id = %task.id, "Task Cloned Successfully");
+
+    Ok(Json(json!({
+        "status": "created",
+        "id": task.id,
+        "trigger_at": task.trigger_at,
+        "signing_secret": task.signing_secret
+    })))
+}
+
+/// Handler to run a task immediately, outside its schedule
+///
+/// # Arguments
+///
+/// * `State(state)` - Application state containing the TaskService
+/// * `Path(task_id)` - Path parameter containing the UUID of the task
+/// * `payload` - Optional JSON body with `reset_cadence`, to restart an interval task's
+///   cadence from this run
+///
+/// # Errors
+///
+/// * `AppError` - If the task is missing or the run cannot be recorded
+///   (see TaskService::run_task)
+async fn run_task(
+    _auth: Authorized<Operator>,
+    State(state): State<AppState>,
+    Path(task_id): Path<Uuid>,
+    payload: Option<Json<RunTaskReq>>,
+) -> Result<Json<ExecutionResponse>, AppError> {
+    let req = payload.map(|Json(req)| req).unwrap_or_default();
+    let execution = state.service.run_task(task_id, req.reset_cadence).await?;
+
+    Ok(Json(ExecutionResponse::from(execution)))
+}
+
+/// Handler to skip the next run of an interval task
+///
+/// # Arguments
+///
+/// * `State(state)` - Application state containing the TaskService
+/// * `Path(task_id)` - Path parameter containing the UUID of the task
+///
+/// # Errors
+///
+/// * `AppError` - If the task is missing or not an interval task (see TaskService::skip_next)
+async fn skip_next(
+    _auth: Authorized<Operator>,
+    State(state): State<AppState>,
+    Path(task_id): Path<Uuid>,
+) -> Result<Json<Value>, AppError> {
+    let task = state.service.skip_next(task_id).await?;
+
+    Ok(Json(
+        json!({ "id": task.id, "trigger_at": task.trigger_at }),
+    ))
+}
+
+/// Handler to list the next fire times of a schedule without creating a task
+///
+/// # Arguments
+///
+/// * `State(state)` - Application state containing the TaskService
+/// * `Json(payload)` - The schedule, as it would be sent to create a task, and a count
+///
+/// # Errors
+///
+/// * `AppError` - If the schedule is invalid (see TaskService::preview_schedule)
+async fn preview_schedule(
+    _auth: Authorized<ReadOnly>,
+    State(state): State<AppState>,
+    Json(payload): Json<PreviewScheduleReq>,
+) -> Result<Json<Value>, AppError> {
+    let occurrences = state.service.preview_schedule(payload)?;
+
+    Ok(Json(json!({ "occurrences": occurrences })))
+}
+
+/// Handler to check cron expressions and recurrence rules without creating a task
+///
+/// Always answers 200; problems are listed in `errors` with the field, part and kind of
+/// each.
+///
+/// # Arguments
+///
+/// * `State(state)` - Application state containing the TaskService
+/// * `Json(payload)` - The expressions to check
+///
+/// # Errors
+///
+/// * `AppError` - If there is nothing to check (see TaskService::check_schedule)
+async fn validate_schedule(
+    _auth: Authorized<ReadOnly>,
+    State(state): State<AppState>,
+    Json(payload): Json<ValidateScheduleReq>,
+) -> Result<Json<Value>, AppError> {
+    let errors: Vec<ScheduleIssueResponse> = state
+        .service
+        .check_schedule(payload)?
+        .into_iter()
+        .map(|(field, issue)| ScheduleIssueResponse { field, issue })
+        .collect();
+
+    Ok(Json(
+        json!({ "valid": errors.is_empty(), "errors": errors }),
+    ))
+}
+
+/// Handler to rotate a task's webhook signing secret (admin only)
+///
+/// # Arguments
+///
+/// * `State(state)` - Application state containing the TaskService
+/// * `Path(task_id)` - Path parameter containing the UUID of the task
+/// * `payload` - Optional JSON body with `overlap_seconds`, how long the old secret keeps
+///   signing requests (default 24 hours, at most 30 days)
+///
+/// # Errors
+///
+/// * `AppError::ValidationError` - If `overlap_seconds` is negative or over 30 days
+/// * `AppError` - If the task is missing or rotation fails (see TaskService::rotate_signing_secret)
+async fn rotate_secret(
+    _auth: Authorized<Admin>,
+    State(state): State<AppState>,
+    Path(task_id): Path<Uuid>,
+    payload: Option<Json<RotateSecretReq>>,
+) -> Result<Json<RotatedSecretResponse>, AppError> {
+    let overlap_seconds = payload
+        .and_then(|Json(req)| req.overlap_seconds)
+        .unwrap_or(DEFAULT_SECRET_OVERLAP_SECONDS);
+    if !(0..=MAX_SECRET_OVERLAP_SECONDS).contains(&overlap_seconds) {
+        return Err(AppError::ValidationError(format!(
+            "overlap_seconds must be between 0 and {}",
+            MAX_SECRET_OVERLAP_SECONDS
+        )));
+    }
 
-    tracing::info!(%task_id, "Task Created Successfully");
+    let (signing_secret, previous_secret_expires_at) = state
+        .service
+        .rotate_signing_secret(task_id, chrono::Duration::seconds(overlap_seconds))
+        .await?;
 
-    Ok(Json(json!({ "status": "created","id": task_id })))
+    Ok(Json(RotatedSecretResponse {
+        signing_secret,
+        previous_secret_expires_at,
+    }))
+}
+
+/// Handler to mint a single-use, time-limited link that fires a task without an API key
+///
+/// # Arguments
+///
+/// * `State(state)` - Application state containing the TaskService
+/// * `Path(task_id)` - Path parameter containing the UUID of the task
+/// * `payload` - Optional JSON body with `ttl_seconds` (default 24 hours, at most 30 days)
+///   and `payload` overrides for the triggered run
+///
+/// # Errors
+///
+/// * `AppError` - If the task is missing or has no signing secret
+///   (see TaskService::create_trigger_link)
+async fn create_trigger_link(
+    _auth: Authorized<Operator>,
+    State(state): State<AppState>,
+    Path(task_id): Path<Uuid>,
+    payload: Option<Json<CreateTriggerLinkReq>>,
+) -> Result<(StatusCode, Json<TriggerLinkResponse>), AppError> {
+    let req = payload.map(|Json(req)| req).unwrap_or_default();
+    let ttl_seconds = req.ttl_seconds.unwrap_or(DEFAULT_TRIGGER_LINK_TTL_SECONDS);
+    if !(1..=MAX_TRIGGER_LINK_TTL_SECONDS).contains(&ttl_seconds) {
+        return Err(AppError::ValidationError(format!(
+            "ttl_seconds must be between 1 and {}",
+            MAX_TRIGGER_LINK_TTL_SECONDS
+        )));
+    }
+
+    let (link, signature) = state
+        .service
+        .create_trigger_link(task_id, chrono::Duration::seconds(ttl_seconds), req.payload)
+        .await?;
+
+    let path = trigger_links::link_path(link.id, &signature);
+    let url = match &state.service.config().public_base_url {
+        Some(base) => format!("{}{}", base, path),
+        None => path,
+    };
+
+    Ok((
+        StatusCode::CREATED,
+        Json(TriggerLinkResponse {
+            id: link.id,
+            url,
+            expires_at: link.expires_at,
+        }),
+    ))
+}
+
+/// Handler that redeems a trigger link and runs its task once. Needs no API key; the
+/// link's signature is the credential.
+///
+/// # Arguments
+///
+/// * `State(state)` - Application state containing the TaskService
+/// * `Path(link_id)` - Path parameter containing the UUID of the link
+/// * `Query(query)` - The link's `sig`
+///
+/// # Errors
+///
+/// * `AppError` - 404 for unknown, expired, used or tampered links
+///   (see TaskService::fire_trigger_link)
+async fn fire_trigger_link(
+    State(state): State<AppState>,
+    Path(link_id): Path<Uuid>,
+    Query(query): Query<TriggerLinkQuery>,
+) -> Result<Json<ExecutionResponse>, AppError> {
+    let execution = state.service.fire_trigger_link(link_id, &query.sig).await?;
+
+    Ok(Json(ExecutionResponse::from(execution)))
 }
 
 /// Handler to delete a task by its ID
 ///
+/// The task is moved to the recycle bin, unless `?hard=true` asks for it and its executions
+/// to be removed for good, e.g. to honour an erasure request.
+///
 /// # Arguments
 ///
 /// * `State(state)` - Application state containing the TaskService
 /// * `Path(task_id)` - Path parameter containing the UUID of the task to delete
+/// * `Query(query)` - Whether to hard delete the task
 ///
 /// # Errors
 ///
-/// * `AppError` - If task deletion fails (see TaskService::delete_task for details)
+/// * `AppError` - If task deletion fails (see TaskService::delete_task and
+///   TaskService::purge_task for details)
 async fn delete_task(
+    _auth: Authorized<Admin>,
     State(state): State<AppState>,
     Path(task_id): Path<Uuid>,
+    Query(query): Query<DeleteTaskQuery>,
 ) -> Result<StatusCode, AppError> {
+    if query.hard {
+        state.service.purge_task(task_id).await?;
+        tracing::info!(%task_id, "Task hard deleted");
+        return Ok(StatusCode::NO_CONTENT);
+    }
     state.service.delete_task(task_id).await?;
 
     Ok(StatusCode::NO_CONTENT)
 }
 
-/// Handler to list all tasks
+/// Handler to permanently delete a task and its executions (admin only)
+///
+/// # Arguments
+///
+/// * `State(state)` - Application state containing the TaskService
+/// * `Path(task_id)` - Path parameter containing the UUID of the task to purge
+///
+/// # Errors
+///
+/// * `AppError` - If the task does not exist or purging fails (see TaskService::purge_task)
+async fn purge_task(
+    _auth: Authorized<Admin>,
+    State(state): State<AppState>,
+    Path(task_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    state.service.purge_task(task_id).await?;
+
+    tracing::info!(%task_id, "Task purged");
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Handler to fetch a single task
+///
+/// Tasks in the recycle bin are only shown to admin keys.
+///
+/// # Arguments
+///
+/// * `State(state)` - Application state containing the TaskService
+/// * `Path(task_id)` - The UUID of the task
+///
+/// # Errors
+///
+/// * `AppError` - If the task is missing (see TaskService::get_task for details)
+async fn get_task(
+    auth: Authorized<ReadOnly>,
+    State(state): State<AppState>,
+    Path(task_id): Path<Uuid>,
+) -> Result<Json<TaskResponse>, AppError> {
+    let (task, runs) = state.service.get_task(task_id).await?;
+    if task.deleted_at.is_some() {
+        auth.require(Scope::Admin)?;
+    }
+    let dependency = state.service.dependency(&task).await?;
+
+    Ok(Json(TaskResponse {
+        dependency,
+        ..TaskResponse::new(task, runs)
+    }))
+}
+
+/// Handler to list tasks
+///
+/// Reviewing the recycle bin (`state=deleted`) requires the admin scope.
 ///
 /// # Arguments
 ///
 /// * `State(state)` - Application state containing the TaskService
+/// * `Query(query)` - Optional `state` filter (`active` or `deleted`) and `group_id`
 ///
 /// # Errors
 ///
 /// * `AppError` - If listing tasks fails (see TaskService::list_tasks for details)
 async fn list_tasks(
+    auth: Authorized<ReadOnly>,
     State(state): State<AppState>,
+    Query(query): Query<ListTasksQuery>,
 ) -> Result<Json<Vec<TaskSummaryResponse>>, AppError> {
-    let tasks = state.service.list_tasks().await?;
+    if query.state == Some(TaskState::Deleted) {
+        auth.require(Scope::Admin)?;
+    }
 
-    let response: Vec<TaskSummaryResponse> = tasks
-        .into_iter()
-        .map(|task| TaskSummaryResponse {
-            id: task.id,
-            name: task.name,
-            status: if task.deleted_at.is_some() {
-                "deleted".to_string()
-            } else {
-                "active".to_string()
-            },
-            deleted_at: task.deleted_at,
+    let tasks = state
+        .service
+        .list_tasks(TaskFilter {
+            state: query.state,
+            group_id: query.group_id,
         })
-        .collect();
+        .await?;
+
+    let response: Vec<TaskSummaryResponse> =
+        tasks.into_iter().map(TaskSummaryResponse::from).collect();
 
     Ok(Json(response))
 }
+
+/// Handler to pause every task matching a label selector or group
+///
+/// # Errors
+///
+/// * `AppError` - If the selector is invalid or the update fails (see TaskService::pause_tasks)
+async fn bulk_pause(
+    _auth: Authorized<Operator>,
+    State(state): State<AppState>,
+    Json(req): Json<BulkPauseReq>,
+) -> Result<Json<BulkPauseResponse>, AppError> {
+    let affected = state.service.pause_tasks(&bulk_selector(req)?).await?;
+
+    Ok(Json(BulkPauseResponse { affected }))
+}
+
+/// Handler to resume every task matching a label selector or group
+///
+/// # Errors
+///
+/// * `AppError` - If the selector is invalid or the update fails (see TaskService::resume_tasks)
+async fn bulk_resume(
+    _auth: Authorized<Operator>,
+    State(state): State<AppState>,
+    Json(req): Json<BulkPauseReq>,
+) -> Result<Json<BulkPauseResponse>, AppError> {
+    let affected = state.service.resume_tasks(&bulk_selector(req)?).await?;
+
+    Ok(Json(BulkPauseResponse { affected }))
+}
+
+fn bulk_selector(req: BulkPauseReq) -> Result<TaskSelector, AppError> {
+    match (req.labels, req.group_id) {
+        (Some(labels), None) => Ok(TaskSelector::Labels(labels)),
+        (None, Some(group_id)) => Ok(TaskSelector::Group(group_id)),
+        _ => Err(AppError::ValidationError(
+            "Give either 'labels' or 'group_id'".into(),
+        )),
+    }
+}
+
+/// Handler to create a task group
+///
+/// # Errors
+///
+/// * `AppError` - If the name is blank or the insert fails (see TaskService::create_group)
+async fn create_group(
+    _auth: Authorized<Operator>,
+    State(state): State<AppState>,
+    Json(payload): Json<CreateGroupReq>,
+) -> Result<(StatusCode, Json<TaskGroup>), AppError> {
+    let group = state
+        .service
+        .create_group(payload.name, payload.description)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(group)))
+}
+
+/// Handler to list task groups
+///
+/// # Errors
+///
+/// * `AppError` - If listing groups fails (see TaskService::list_groups for details)
+async fn list_groups(
+    _auth: Authorized<ReadOnly>,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<TaskGroup>>, AppError> {
+    let groups = state.service.list_groups().await?;
+
+    Ok(Json(groups))
+}
+
+/// Handler to show a task group with its task and execution counts
+///
+/// # Errors
+///
+/// * `AppError` - If the group is missing (see TaskService::get_group for details)
+async fn get_group(
+    _auth: Authorized<ReadOnly>,
+    State(state): State<AppState>,
+    Path(group_id): Path<Uuid>,
+) -> Result<Json<GroupResponse>, AppError> {
+    let (group, stats) = state.service.get_group(group_id).await?;
+
+    Ok(Json(GroupResponse { group, stats }))
+}
+
+/// Handler to pause a single task
+///
+/// # Errors
+///
+/// * `AppError` - If the task is missing (see TaskService::pause_task for details)
+async fn pause_task(
+    _auth: Authorized<Operator>,
+    State(state): State<AppState>,
+    Path(task_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    state.service.pause_task(task_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Handler to resume a paused task
+///
+/// # Errors
+///
+/// * `AppError` - If the task is missing (see TaskService::resume_task for details)
+async fn resume_task(
+    _auth: Authorized<Operator>,
+    State(state): State<AppState>,
+    Path(task_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    state.service.resume_task(task_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Handler to pause every task of a group
+///
+/// # Errors
+///
+/// * `AppError` - If the group is missing (see TaskService::pause_group for details)
+async fn pause_group(
+    _auth: Authorized<Operator>,
+    State(state): State<AppState>,
+    Path(group_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    state.service.pause_group(group_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Handler to resume a paused group
+///
+/// # Errors
+///
+/// * `AppError` - If the group is missing (see TaskService::resume_group for details)
+async fn resume_group(
+    _auth: Authorized<Operator>,
+    State(state): State<AppState>,
+    Path(group_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    state.service.resume_group(group_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Handler to create a workflow
+///
+/// # Errors
+///
+/// * `AppError` - If the graph is invalid or the insert fails (see
+///   TaskService::create_workflow)
+async fn create_workflow(
+    _auth: Authorized<Operator>,
+    State(state): State<AppState>,
+    Json(payload): Json<CreateWorkflowReq>,
+) -> Result<(StatusCode, Json<Workflow>), AppError> {
+    let workflow = state
+        .service
+        .create_workflow(payload.name, payload.nodes)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(workflow)))
+}
+
+/// Handler to list workflows
+///
+/// # Errors
+///
+/// * `AppError` - If listing workflows fails (see TaskService::list_workflows for details)
+async fn list_workflows(
+    _auth: Authorized<ReadOnly>,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<Workflow>>, AppError> {
+    let workflows = state.service.list_workflows().await?;
+
+    Ok(Json(workflows))
+}
+
+/// Handler to show a workflow
+///
+/// # Errors
+///
+/// * `AppError` - If the workflow is missing (see TaskService::get_workflow for details)
+async fn get_workflow(
+    _auth: Authorized<ReadOnly>,
+    State(state): State<AppState>,
+    Path(workflow_id): Path<Uuid>,
+) -> Result<Json<Workflow>, AppError> {
+    let workflow = state.service.get_workflow(workflow_id).await?;
+
+    Ok(Json(workflow))
+}
+
+/// Handler to start a run of a workflow, which continues in the background
+///
+/// # Errors
+///
+/// * `AppError` - If the workflow is missing (see TaskService::start_workflow for details)
+async fn start_workflow(
+    _auth: Authorized<Operator>,
+    State(state): State<AppState>,
+    Path(workflow_id): Path<Uuid>,
+) -> Result<(StatusCode, Json<WorkflowRun>), AppError> {
+    let run = state.service.start_workflow(workflow_id).await?;
+
+    Ok((StatusCode::ACCEPTED, Json(run)))
+}
+
+/// Handler to list a workflow's latest runs
+///
+/// # Errors
+///
+/// * `AppError` - If the workflow is missing (see TaskService::list_workflow_runs for
+///   details)
+async fn list_workflow_runs(
+    _auth: Authorized<ReadOnly>,
+    State(state): State<AppState>,
+    Path(workflow_id): Path<Uuid>,
+) -> Result<Json<Vec<WorkflowRun>>, AppError> {
+    let runs = state.service.list_workflow_runs(workflow_id).await?;
+
+    Ok(Json(runs))
+}
+
+/// Handler to show a workflow run and the state of each of its nodes
+///
+/// # Errors
+///
+/// * `AppError` - If the run is missing (see TaskService::get_workflow_run for details)
+async fn get_workflow_run(
+    _auth: Authorized<ReadOnly>,
+    State(state): State<AppState>,
+    Path(run_id): Path<Uuid>,
+) -> Result<Json<WorkflowRun>, AppError> {
+    let run = state.service.get_workflow_run(run_id).await?;
+
+    Ok(Json(run))
+}
+
+/// Handler to replay a past execution with the payload it ran with
+///
+/// # Arguments
+///
+/// * `State(state)` - Application state containing the TaskService
+/// * `Path(execution_id)` - Path parameter containing the UUID of the execution to replay
+///
+/// # Errors
+///
+/// * `AppError` - If the execution is missing, still running or has no recorded payload
+///   (see TaskService::replay_execution)
+async fn replay_execution(
+    _auth: Authorized<Operator>,
+    State(state): State<AppState>,
+    Path(execution_id): Path<Uuid>,
+) -> Result<Json<ExecutionResponse>, AppError> {
+    let execution = state.service.replay_execution(execution_id).await?;
+
+    Ok(Json(ExecutionResponse::from(execution)))
+}
+
+/// Handler to cancel a running execution, which is recorded as cancelled once its executor
+/// has stopped
+///
+/// # Arguments
+///
+/// * `State(state)` - Application state containing the TaskService
+/// * `Path(execution_id)` - Path parameter containing the UUID of the execution to cancel
+///
+/// # Errors
+///
+/// * `AppError` - If the execution is missing or not running in this instance
+///   (see TaskService::cancel_execution)
+async fn cancel_execution(
+    _auth: Authorized<Operator>,
+    State(state): State<AppState>,
+    Path(execution_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    state.service.cancel_execution(execution_id).await?;
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+/// Handler to fetch a single execution, e.g. one a failure notification links to
+///
+/// # Arguments
+///
+/// * `State(state)` - Application state containing the TaskService
+/// * `Path(execution_id)` - Path parameter containing the UUID of the execution
+///
+/// # Errors
+///
+/// * `AppError` - If the execution is missing (see TaskService::get_execution for details)
+async fn get_execution(
+    _auth: Authorized<ReadOnly>,
+    State(state): State<AppState>,
+    Path(execution_id): Path<Uuid>,
+) -> Result<Json<ExecutionResponse>, AppError> {
+    let execution = state.service.get_execution(execution_id).await?;
+
+    Ok(Json(ExecutionResponse::from(execution)))
+}
+
+/// Handler to list a task's executions, newest first, with cursor pagination
+///
+/// # Arguments
+///
+/// * `State(state)` - Application state containing the TaskService
+/// * `Path(task_id)` - Path parameter containing the UUID of the task
+/// * `Query(query)` - Optional `limit`, opaque `cursor` from a previous page, and
+///   `include_output` to list full records instead of summaries
+///
+/// # Errors
+///
+/// * `AppError` - If listing executions fails (see TaskService::list_executions for details)
+async fn list_executions(
+    _auth: Authorized<ReadOnly>,
+    State(state): State<AppState>,
+    Path(task_id): Path<Uuid>,
+    Query(query): Query<ExecutionPageQuery>,
+) -> Result<Response, AppError> {
+    if query.include_output {
+        let (executions, next_cursor) = state
+            .service
+            .list_execution_records(task_id, query.limit, query.cursor.as_deref())
+            .await?;
+
+        return Ok(Json(ExecutionPageResponse {
+            items: executions
+                .into_iter()
+                .map(ExecutionResponse::from)
+                .collect(),
+            next_cursor,
+        })
+        .into_response());
+    }
+
+    let (executions, next_cursor) = state
+        .service
+        .list_executions(task_id, query.limit, query.cursor.as_deref())
+        .await?;
+
+    Ok(Json(ExecutionPageResponse {
+        items: executions
+            .into_iter()
+            .map(ExecutionSummaryResponse::from)
+            .collect(),
+        next_cursor,
+    })
+    .into_response())
+}
+
+/// Handler to stream all executions as NDJSON or CSV
+///
+/// Rows are streamed straight from a database cursor, so exports of any size are never
+/// buffered in memory.
+///
+/// # Arguments
+///
+/// * `State(state)` - Application state containing the TaskService
+/// * `Query(query)` - `format` (`ndjson` or `csv`, default `ndjson`) and optional `since` timestamp
+async fn export_executions(
+    _auth: Authorized<ReadOnly>,
+    State(state): State<AppState>,
+    Query(query): Query<ExportQuery>,
+) -> Response {
+    let format = query.format;
+    let receiver = state.service.export_executions(query.since);
+
+    let (content_type, header_row) = match format {
+        ExportFormat::Ndjson => ("application/x-ndjson", None),
+        ExportFormat::Csv => (
+            "text/csv",
+            Some(Bytes::from_static(
+                b"id,task_id,executed_at,status,output\n",
+            )),
+        ),
+    };
+
+    let rows = stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|row| (row, receiver))
+    })
+    .map(move |row| {
+        row.map(|exec| encode_export_row(format, ExecutionResponse::from(exec)))
+            .inspect_err(|e| tracing::error!("Execution export failed: {:?}", e))
+    });
+
+    let body = Body::from_stream(stream::iter(header_row.map(Ok)).chain(rows));
+
+    ([(header::CONTENT_TYPE, content_type)], body).into_response()
+}
+
+/// Encodes a single execution as one NDJSON line or CSV record.
+fn encode_export_row(format: ExportFormat, exec: ExecutionResponse) -> Bytes {
+    match format {
+        ExportFormat::Ndjson => {
+            let mut line = serde_json::to_vec(&exec).unwrap_or_default();
+            line.push(b'\n');
+            Bytes::from(line)
+        }
+        ExportFormat::Csv => {
+            let fields = [
+                exec.id.to_string(),
+                exec.task_id.to_string(),
+                exec.executed_at.to_rfc3339(),
+                exec.status,
+                exec.output.to_string(),
+            ];
+            let record: Vec<String> = fields.iter().map(|f| csv_escape(f)).collect();
+            Bytes::from(record.join(",") + "\n")
+        }
+    }
+}
+
+/// Quotes a CSV field if it contains a delimiter, quote or newline.
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Handler to create an API key (admin only)
+///
+/// # Arguments
+///
+/// * `State(state)` - Application state containing the TaskService
+/// * `Json(payload)` - Name and scope of the new key
+///
+/// # Errors
+///
+/// * `AppError` - If key creation fails (see TaskService::create_api_key for details)
+async fn create_api_key(
+    _auth: Authorized<Admin>,
+    State(state): State<AppState>,
+    Json(payload): Json<CreateApiKeyReq>,
+) -> Result<(StatusCode, Json<CreatedApiKeyResponse>), AppError> {
+    let (key, plaintext) = state
+        .service
+        .create_api_key(payload.name, payload.scope)
+        .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreatedApiKeyResponse {
+            metadata: key.into(),
+            key: plaintext,
+        }),
+    ))
+}
+
+/// Handler to list API keys without their secrets (admin only)
+///
+/// # Errors
+///
+/// * `AppError` - If listing keys fails (see TaskService::list_api_keys for details)
+async fn list_api_keys(
+    _auth: Authorized<Admin>,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<ApiKeyResponse>>, AppError> {
+    let keys = state.service.list_api_keys().await?;
+
+    Ok(Json(keys.into_iter().map(ApiKeyResponse::from).collect()))
+}
+
+/// Handler to change an API key's scope (admin only)
+///
+/// # Errors
+///
+/// * `AppError` - If the key is missing or revoked (see TaskService::set_api_key_scope)
+async fn update_api_key(
+    _auth: Authorized<Admin>,
+    State(state): State<AppState>,
+    Path(key_id): Path<Uuid>,
+    Json(payload): Json<UpdateApiKeyReq>,
+) -> Result<StatusCode, AppError> {
+    state
+        .service
+        .set_api_key_scope(key_id, payload.scope)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Handler to revoke an API key (admin only)
+///
+/// # Errors
+///
+/// * `AppError` - If the key is missing or already revoked (see TaskService::revoke_api_key)
+async fn revoke_api_key(
+    _auth: Authorized<Admin>,
+    State(state): State<AppState>,
+    Path(key_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    state.service.revoke_api_key(key_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Handler to store a secret's value (admin only)
+///
+/// # Errors
+///
+/// * `AppError` - If the name or value is invalid or storing fails (see
+///   TaskService::put_secret for details)
+async fn put_secret(
+    _auth: Authorized<Admin>,
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(payload): Json<PutSecretReq>,
+) -> Result<Json<SecretResponse>, AppError> {
+    let secret = state.service.put_secret(name, &payload.value).await?;
+
+    Ok(Json(secret.into()))
+}
+
+/// Handler to list secrets without their values (admin only)
+///
+/// # Errors
+///
+/// * `AppError` - If listing secrets fails (see TaskService::list_secrets for details)
+async fn list_secrets(
+    _auth: Authorized<Admin>,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<SecretResponse>>, AppError> {
+    let secrets = state.service.list_secrets().await?;
+
+    Ok(Json(
+        secrets.into_iter().map(SecretResponse::from).collect(),
+    ))
+}
+
+/// Handler to delete a secret (admin only)
+///
+/// # Errors
+///
+/// * `AppError` - If the secret is missing (see TaskService::delete_secret)
+async fn delete_secret(
+    _auth: Authorized<Admin>,
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, AppError> {
+    state.service.delete_secret(&name).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Handler to export a snapshot of every group, task and workflow (admin only)
+///
+/// # Arguments
+///
+/// * `State(state)` - Application state containing the TaskService
+/// * `Query(query)` - Whether to include the execution history and signing secrets
+///
+/// # Errors
+///
+/// * `AppError` - If reading the state fails (see TaskService::export_snapshot for details)
+async fn export_snapshot(
+    _auth: Authorized<Admin>,
+    State(state): State<AppState>,
+    Query(query): Query<SnapshotQuery>,
+) -> Result<Json<Snapshot>, AppError> {
+    let snapshot = state
+        .service
+        .export_snapshot(query.include_executions, query.include_secrets)
+        .await?;
+
+    Ok(Json(snapshot))
+}
+
+/// Handler to restore a snapshot into an empty instance (admin only)
+///
+/// # Arguments
+///
+/// * `State(state)` - Application state containing the TaskService
+/// * `Json(snapshot)` - A snapshot exported from another instance
+///
+/// # Errors
+///
+/// * `AppError` - If the snapshot cannot be restored (see TaskService::restore_snapshot)
+async fn restore_snapshot(
+    _auth: Authorized<Admin>,
+    State(state): State<AppState>,
+    Json(snapshot): Json<Snapshot>,
+) -> Result<(StatusCode, Json<RestoreSnapshotResponse>), AppError> {
+    let counts = RestoreSnapshotResponse {
+        groups: snapshot.groups.len(),
+        tasks: snapshot.tasks.len(),
+        workflows: snapshot.workflows.len(),
+        executions: snapshot.executions.as_ref().map_or(0, Vec::len),
+    };
+    state.service.restore_snapshot(snapshot).await?;
+
+    Ok((StatusCode::CREATED, Json(counts)))
+}
+
+/// Handler to show whether maintenance mode is active (admin only)
+async fn get_maintenance(
+    _auth: Authorized<Admin>,
+    State(state): State<AppState>,
+) -> Json<MaintenanceResponse> {
+    let window = state.service.maintenance().current();
+
+    Json(MaintenanceResponse {
+        enabled: window.is_some(),
+        window,
+    })
+}
+
+/// Handler to enter maintenance mode, or change its settings (admin only)
+///
+/// # Arguments
+///
+/// * `State(state)` - Application state containing the TaskService
+/// * `payload` - Optional settings; by default the scheduler keeps dispatching
+async fn enter_maintenance(
+    _auth: Authorized<Admin>,
+    State(state): State<AppState>,
+    payload: Option<Json<MaintenanceReq>>,
+) -> Json<MaintenanceResponse> {
+    let Json(payload) = payload.unwrap_or_default();
+    let window = state.service.enter_maintenance(
+        payload.pause_scheduler,
+        payload
+            .retry_after_seconds
+            .unwrap_or(DEFAULT_MAINTENANCE_RETRY_AFTER_SECONDS),
+        payload.reason,
+    );
+
+    Json(MaintenanceResponse {
+        enabled: true,
+        window: Some(window),
+    })
+}
+
+/// Handler to leave maintenance mode (admin only)
+async fn exit_maintenance(_auth: Authorized<Admin>, State(state): State<AppState>) -> StatusCode {
+    state.service.exit_maintenance();
+
+    StatusCode::NO_CONTENT
+}
+
+/// Handler for the liveness probe: the process is up and serving requests.
+async fn liveness() -> Json<Value> {
+    Json(json!({ "status": "ok" }))
+}
+
+/// Handler for `GET /metrics`: the scheduler's metrics in the Prometheus text format.
+/// Unauthenticated like the health checks, so scrapers need no key.
+async fn metrics(State(state): State<AppState>) -> Response {
+    (
+        [(
+            axum::http::header::CONTENT_TYPE,
+            "text/plain; version=0.0.4",
+        )],
+        state.service.metrics().render(),
+    )
+        .into_response()
+}
+
+/// Handler for the readiness probe.
+///
+/// Responds with 503 while the scheduler is in degraded mode (database unreachable), so
+/// load balancers can take the instance out of rotation until it recovers.
+async fn readiness(State(state): State<AppState>) -> Response {
+    let health = state.service.health();
+    if health.is_ready() {
+        return Json(json!({ "status": "ready" })).into_response();
+    }
+
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        Json(json!({ "status": "degraded", "reason": health.reason() })),
+    )
+        .into_response()
+}
@@ -1,4 +1,5 @@
 use crate::api::router;
+use crate::config::Config;
 use crate::service::TaskService;
 use axum::body::Body;
 use axum::http::{Request, StatusCode};
@@ -41,6 +42,43 @@ async fn test_create_task_success(pool: SqlitePool) -> sqlx::Result<()> {
 
     Ok(())
 }
+
+#[sqlx::test]
+async fn test_get_task_returns_the_full_task(pool: SqlitePool) -> sqlx::Result<()> {
+    let (tx, _rx) = mpsc::channel(1);
+    let app = router(TaskService::new(pool.clone(), tx));
+    let get = |uri: String| {
+        app.clone()
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+    };
+
+    let task = crate::domain::Task::new_interval(
+        "heartbeat",
+        chrono::Utc::now(),
+        60,
+        json!({ "url": "http://localhost/ping" }),
+    );
+    crate::db::queries::TaskRepository::new(&pool)
+        .create_task(&task)
+        .await?;
+
+    let response = get(format!("/tasks/{}", task.id)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body: Value = from_slice(&body).unwrap();
+    assert_eq!(body["task_type"], "interval");
+    assert_eq!(body["interval_seconds"], 60);
+    assert_eq!(body["payload"]["url"], "http://localhost/ping");
+    assert_eq!(body["status"], "active");
+    assert!(body["deleted_at"].is_null());
+
+    let response = get(format!("/tasks/{}", uuid::Uuid::new_v4()))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    Ok(())
+}
 #[sqlx::test]
 async fn test_create_task_validation_error(pool: SqlitePool) -> sqlx::Result<()> {
     let (tx, _rx) = mpsc::channel(1);
@@ -96,3 +134,807 @@ async fn test_create_task_rejects_bad_interval(pool: SqlitePool) -> sqlx::Result
 
     Ok(())
 }
+
+#[sqlx::test]
+async fn test_list_executions_paginates_with_cursor(pool: SqlitePool) -> sqlx::Result<()> {
+    use crate::db::queries::TaskRepository;
+    use crate::domain::{Execution, ExecutionStatus, Task};
+
+    let repo = TaskRepository::new(&pool);
+    let task = Task::new_interval("paged", chrono::Utc::now(), 60, json!({}));
+    repo.create_task(&task).await?;
+    for _ in 0..3 {
+        let exec = Execution::new(task.id, json!({}), ExecutionStatus::Success);
+        TaskRepository::insert_execution_with_executor(&pool, &exec).await?;
+    }
+
+    let (tx, _rx) = mpsc::channel(1);
+    let app = router(TaskService::new(pool.clone(), tx));
+
+    let req = Request::builder()
+        .uri(format!("/tasks/{}/executions?limit=2", task.id))
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(req).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let first_page: Value = from_slice(&body_bytes).unwrap();
+    assert_eq!(first_page["items"].as_array().unwrap().len(), 2);
+    let cursor = first_page["next_cursor"]
+        .as_str()
+        .expect("Should have a next page");
+
+    // Full records continue from the same cursor
+    let req = Request::builder()
+        .uri(format!(
+            "/tasks/{}/executions?limit=2&cursor={}&include_output=true",
+            task.id, cursor
+        ))
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(req).await.unwrap();
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let second_page: Value = from_slice(&body_bytes).unwrap();
+    assert_eq!(second_page["items"].as_array().unwrap().len(), 1);
+    assert!(second_page["items"][0]["output"].is_object());
+    assert!(second_page["next_cursor"].is_null());
+
+    let req = Request::builder()
+        .uri(format!("/tasks/{}/executions?cursor=garbage", task.id))
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(req).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_get_execution_by_id(pool: SqlitePool) -> sqlx::Result<()> {
+    use crate::db::queries::TaskRepository;
+    use crate::domain::{Execution, ExecutionStatus, Task};
+
+    let repo = TaskRepository::new(&pool);
+    let task = Task::new_once("alerting", chrono::Utc::now(), json!({}));
+    repo.create_task(&task).await?;
+    let exec = Execution::new(
+        task.id,
+        json!({ "error": "boom" }),
+        ExecutionStatus::Failure,
+    );
+    TaskRepository::insert_execution_with_executor(&pool, &exec).await?;
+
+    let (tx, _rx) = mpsc::channel(1);
+    let app = router(TaskService::new(pool.clone(), tx));
+    let get = |uri: String| {
+        app.clone()
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+    };
+
+    let response = get(format!("/executions/{}", exec.id)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let body: Value = from_slice(&body_bytes).unwrap();
+    assert_eq!(body["task_id"], task.id.to_string());
+    assert_eq!(body["status"], "failure");
+    assert_eq!(body["output"]["error"], "boom");
+
+    let response = get(format!("/executions/{}", uuid::Uuid::new_v4()))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_export_executions_streams_ndjson_and_csv(pool: SqlitePool) -> sqlx::Result<()> {
+    use crate::db::queries::TaskRepository;
+    use crate::domain::{Execution, ExecutionStatus, Task};
+
+    let repo = TaskRepository::new(&pool);
+    let task = Task::new_interval("exported", chrono::Utc::now(), 60, json!({}));
+    repo.create_task(&task).await?;
+
+    let mut old = Execution::new(task.id, json!({ "msg": "a,b" }), ExecutionStatus::Failure);
+    old.executed_at = chrono::Utc::now() - chrono::Duration::days(2);
+    TaskRepository::insert_execution_with_executor(&pool, &old).await?;
+    let recent = Execution::new(task.id, json!({}), ExecutionStatus::Success);
+    TaskRepository::insert_execution_with_executor(&pool, &recent).await?;
+
+    let (tx, _rx) = mpsc::channel(1);
+    let app = router(TaskService::new(pool.clone(), tx));
+
+    let req = Request::builder()
+        .uri("/executions/export?format=csv")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(req).await.unwrap();
+    assert_eq!(response.headers()["content-type"], "text/csv");
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let csv = String::from_utf8(body_bytes.to_vec()).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 3, "Header plus one line per execution");
+    assert!(
+        lines[1].contains("\"{\"\"msg\"\":\"\"a,b\"\"}\""),
+        "Output should be quoted"
+    );
+
+    let since = (chrono::Utc::now() - chrono::Duration::days(1))
+        .to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    let req = Request::builder()
+        .uri(format!("/executions/export?format=ndjson&since={}", since))
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(req).await.unwrap();
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let rows: Vec<Value> = String::from_utf8(body_bytes.to_vec())
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0]["id"], recent.id.to_string());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_readiness_reflects_degraded_mode() {
+    let store = std::sync::Arc::new(crate::db::memory::MemoryTaskStore::new());
+    let (tx, _rx) = mpsc::channel(1);
+    let service = TaskService::with_store(store.clone(), tx);
+    let app = router(service.clone());
+
+    let ready = || Request::get("/health/ready").body(Body::empty()).unwrap();
+
+    let response = app.clone().oneshot(ready()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    service.health().mark_degraded("database is locked");
+    let response = app.clone().oneshot(ready()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body: Value = from_slice(&body).unwrap();
+    assert_eq!(body["reason"], "database is locked");
+
+    // Transient store failures surface as 503 rather than 500
+    store.fail_next(sqlx::Error::PoolTimedOut);
+    let response = app
+        .oneshot(Request::get("/tasks").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[sqlx::test]
+async fn test_api_key_scopes_are_enforced(pool: SqlitePool) -> sqlx::Result<()> {
+    let (tx, _rx) = mpsc::channel(1);
+    let service = TaskService::new(pool.clone(), tx).with_config(Config {
+        admin_api_key: Some("bootstrap-secret".into()),
+        ..Config::default()
+    });
+    let app = router(service.clone());
+
+    let call = |method: &str, uri: &str, key: Option<&str>, body: Option<Value>| {
+        let mut req = Request::builder().method(method).uri(uri);
+        if let Some(key) = key {
+            req = req.header("Authorization", format!("Bearer {}", key));
+        }
+        let body = match body {
+            Some(body) => {
+                req = req.header("Content-Type", "application/json");
+                Body::from(body.to_string())
+            }
+            None => Body::empty(),
+        };
+        app.clone().oneshot(req.body(body).unwrap())
+    };
+
+    let mut keys = Vec::new();
+    for scope in ["read_only", "operator"] {
+        let response = call(
+            "POST",
+            "/admin/api-keys",
+            Some("bootstrap-secret"),
+            Some(json!({ "name": scope, "scope": scope })),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let body: Value = from_slice(&body).unwrap();
+        keys.push(body["key"].as_str().unwrap().to_string());
+    }
+    let (reader, operator) = (keys[0].as_str(), keys[1].as_str());
+
+    let task = json!({
+        "name": "scoped",
+        "task_type": "once",
+        "trigger_at": chrono::Utc::now().to_rfc3339(),
+    });
+
+    let status = |r: Result<axum::response::Response, _>| r.unwrap().status();
+    assert_eq!(
+        status(call("GET", "/tasks", None, None).await),
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        status(call("GET", "/tasks", Some("wrong"), None).await),
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        status(call("GET", "/tasks", Some(reader), None).await),
+        StatusCode::OK
+    );
+    assert_eq!(
+        status(call("POST", "/tasks", Some(reader), Some(task.clone())).await),
+        StatusCode::FORBIDDEN
+    );
+
+    let response = call("POST", "/tasks", Some(operator), Some(task))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let task_id = from_slice::<Value>(&body).unwrap()["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    assert_eq!(
+        status(
+            call(
+                "DELETE",
+                &format!("/tasks/{}", task_id),
+                Some(operator),
+                None
+            )
+            .await
+        ),
+        StatusCode::FORBIDDEN,
+        "Operators cannot delete"
+    );
+    assert_eq!(
+        status(call("GET", "/admin/api-keys", Some(operator), None).await),
+        StatusCode::FORBIDDEN
+    );
+
+    // Promote the reader, then revoke it
+    let reader_id = service.authenticate(reader).await.unwrap().id;
+    let key_uri = format!("/admin/api-keys/{}", reader_id);
+    assert_eq!(
+        status(
+            call(
+                "PATCH",
+                &key_uri,
+                Some("bootstrap-secret"),
+                Some(json!({ "scope": "admin" }))
+            )
+            .await
+        ),
+        StatusCode::NO_CONTENT
+    );
+    assert_eq!(
+        status(call("DELETE", &format!("/tasks/{}", task_id), Some(reader), None).await),
+        StatusCode::NO_CONTENT
+    );
+    assert_eq!(
+        status(call("DELETE", &key_uri, Some("bootstrap-secret"), None).await),
+        StatusCode::NO_CONTENT
+    );
+    assert_eq!(
+        status(call("GET", "/tasks", Some(reader), None).await),
+        StatusCode::UNAUTHORIZED,
+        "Revoked keys no longer authenticate"
+    );
+
+    let stored: Vec<String> = sqlx::query_scalar("SELECT key_hash FROM api_keys")
+        .fetch_all(&pool)
+        .await?;
+    assert!(stored.iter().all(|hash| hash != reader && hash != operator));
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_recycle_bin_listing_and_purge_are_admin_only(pool: SqlitePool) -> sqlx::Result<()> {
+    use crate::db::queries::TaskRepository;
+    use crate::domain::{Execution, ExecutionStatus, Scope, Task};
+
+    let repo = TaskRepository::new(&pool);
+    let kept = Task::new_interval("kept", chrono::Utc::now(), 60, json!({}));
+    let binned = Task::new_interval("binned", chrono::Utc::now(), 60, json!({}));
+    repo.create_task(&kept).await?;
+    repo.create_task(&binned).await?;
+    let exec = Execution::new(binned.id, json!({}), ExecutionStatus::Success);
+    TaskRepository::insert_execution_with_executor(&pool, &exec).await?;
+    repo.delete_task(binned.id).await?;
+
+    let (tx, _rx) = mpsc::channel(1);
+    let service = TaskService::new(pool.clone(), tx).with_config(Config {
+        admin_api_key: Some("bootstrap-secret".into()),
+        ..Config::default()
+    });
+    let (_, operator) = service
+        .create_api_key("ops".into(), Scope::Operator)
+        .await
+        .unwrap();
+    let app = router(service);
+
+    let call = |method: &str, uri: &str, key: &str| {
+        let req = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("Authorization", format!("Bearer {}", key))
+            .body(Body::empty())
+            .unwrap();
+        app.clone().oneshot(req)
+    };
+
+    let response = call("GET", "/tasks?state=deleted", &operator)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let response = call("GET", "/tasks?state=active", &operator).await.unwrap();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let active: Value = from_slice(&body).unwrap();
+    assert_eq!(active.as_array().unwrap().len(), 1);
+    assert_eq!(active[0]["id"], kept.id.to_string());
+
+    let response = call("GET", "/tasks?state=deleted", "bootstrap-secret")
+        .await
+        .unwrap();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let deleted: Value = from_slice(&body).unwrap();
+    assert_eq!(deleted.as_array().unwrap().len(), 1);
+    assert_eq!(deleted[0]["id"], binned.id.to_string());
+    assert_eq!(deleted[0]["status"], "deleted");
+
+    let purge_uri = format!("/tasks/{}/purge", binned.id);
+    let response = call("DELETE", &purge_uri, &operator).await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let response = call("DELETE", &purge_uri, "bootstrap-secret")
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let response = call("DELETE", &purge_uri, "bootstrap-secret")
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    assert!(repo.get_task(binned.id).await?.is_none());
+    let executions: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM executions")
+        .fetch_one(&pool)
+        .await?;
+    assert_eq!(executions, 0, "Executions are purged with their task");
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_hard_delete_removes_the_task_and_its_executions(
+    pool: SqlitePool,
+) -> sqlx::Result<()> {
+    use crate::db::queries::TaskRepository;
+    use crate::domain::{Execution, ExecutionStatus, Task};
+
+    let repo = TaskRepository::new(&pool);
+    let soft = Task::new_interval("soft", chrono::Utc::now(), 60, json!({}));
+    let hard = Task::new_interval("hard", chrono::Utc::now(), 60, json!({}));
+    for task in [&soft, &hard] {
+        repo.create_task(task).await?;
+        let exec = Execution::new(task.id, json!({}), ExecutionStatus::Success);
+        TaskRepository::insert_execution_with_executor(&pool, &exec).await?;
+    }
+
+    let (tx, _rx) = mpsc::channel(1);
+    let app = router(TaskService::new(pool.clone(), tx));
+    let delete = |uri: String| {
+        let req = Request::builder()
+            .method("DELETE")
+            .uri(uri)
+            .body(Body::empty())
+            .unwrap();
+        app.clone().oneshot(req)
+    };
+
+    let response = delete(format!("/tasks/{}", soft.id)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let response = delete(format!("/tasks/{}?hard=true", hard.id))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let response = delete(format!("/tasks/{}?hard=true", hard.id))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    assert!(repo.get_task(soft.id).await?.unwrap().deleted_at.is_some());
+    assert!(repo.get_task(hard.id).await?.is_none());
+    let remaining: Vec<uuid::Uuid> = sqlx::query_scalar("SELECT task_id FROM executions")
+        .fetch_all(&pool)
+        .await?;
+    assert_eq!(
+        remaining,
+        vec![soft.id],
+        "Only the hard delete takes executions"
+    );
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_task_quota_is_enforced_per_api_key(pool: SqlitePool) -> sqlx::Result<()> {
+    use crate::domain::Scope;
+
+    let (tx, _rx) = mpsc::channel(1);
+    let service = TaskService::new(pool.clone(), tx).with_config(Config {
+        admin_api_key: Some("bootstrap-secret".into()),
+        max_tasks_per_key: Some(1),
+        ..Config::default()
+    });
+    let (_, first) = service
+        .create_api_key("first".into(), Scope::Operator)
+        .await
+        .unwrap();
+    let (_, second) = service
+        .create_api_key("second".into(), Scope::Operator)
+        .await
+        .unwrap();
+    let app = router(service);
+
+    let create = |key: &str| {
+        let body = json!({
+            "name": "quota",
+            "task_type": "once",
+            "trigger_at": chrono::Utc::now().to_rfc3339(),
+        });
+        let req = Request::builder()
+            .method("POST")
+            .uri("/tasks")
+            .header("Authorization", format!("Bearer {}", key))
+            .header("Content-Type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        app.clone().oneshot(req)
+    };
+
+    let response = create(&first).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let task_id = from_slice::<Value>(&body).unwrap()["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let response = create(&first).await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let error = from_slice::<Value>(&body).unwrap()["error"].to_string();
+    assert!(error.contains("limit 1"), "Unexpected error: {}", error);
+
+    assert_eq!(create(&second).await.unwrap().status(), StatusCode::OK);
+    assert_eq!(
+        create("bootstrap-secret").await.unwrap().status(),
+        StatusCode::OK,
+        "The bootstrap key is not limited"
+    );
+
+    // Deleted tasks no longer count
+    let req = Request::builder()
+        .method("DELETE")
+        .uri(format!("/tasks/{}", task_id))
+        .header("Authorization", "Bearer bootstrap-secret")
+        .body(Body::empty())
+        .unwrap();
+    app.clone().oneshot(req).await.unwrap();
+    assert_eq!(create(&first).await.unwrap().status(), StatusCode::OK);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_maintenance_mode_rejects_writes_with_retry_after() {
+    let store = std::sync::Arc::new(crate::db::memory::MemoryTaskStore::new());
+    let (tx, _rx) = mpsc::channel(8);
+    let service = TaskService::with_store(store, tx);
+    let app = router(service.clone());
+
+    let create = || {
+        let body = json!({
+            "name": "during-maintenance",
+            "task_type": "once",
+            "trigger_at": chrono::Utc::now().to_rfc3339(),
+        });
+        Request::post("/tasks")
+            .header("Content-Type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    };
+
+    let enable = Request::put("/admin/maintenance")
+        .header("Content-Type", "application/json")
+        .body(Body::from(
+            json!({ "pause_scheduler": true, "retry_after_seconds": 120 }).to_string(),
+        ))
+        .unwrap();
+    let response = app.clone().oneshot(enable).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(service.maintenance().pauses_dispatch());
+
+    let response = app.clone().oneshot(create()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(response.headers()["retry-after"], "120");
+
+    let response = app
+        .clone()
+        .oneshot(Request::get("/tasks").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK, "Reads keep working");
+
+    let disable = Request::delete("/admin/maintenance")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(disable).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert_eq!(
+        app.oneshot(create()).await.unwrap().status(),
+        StatusCode::OK
+    );
+}
+
+#[tokio::test]
+async fn test_follower_serves_reads_and_rejects_all_writes() {
+    let store = std::sync::Arc::new(crate::db::memory::MemoryTaskStore::new());
+    let (tx, _rx) = mpsc::channel(1);
+    let service = TaskService::with_store(store, tx).with_config(Config {
+        follower: true,
+        ..Config::default()
+    });
+    let app = router(service);
+
+    let response = app
+        .clone()
+        .oneshot(Request::get("/tasks").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    for uri in ["/tasks", "/admin/api-keys"] {
+        let req = Request::post(uri)
+            .header("Content-Type", "application/json")
+            .body(Body::from("{}"))
+            .unwrap();
+        let response = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(
+            response.status(),
+            StatusCode::FORBIDDEN,
+            "{} is a write",
+            uri
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_error_responses_carry_a_code() {
+    let store = std::sync::Arc::new(crate::db::memory::MemoryTaskStore::new());
+    let (tx, _rx) = mpsc::channel(8);
+    let app = router(TaskService::with_store(store, tx));
+
+    let send = |method: &str, uri: String, body: Option<Value>| {
+        let req = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("Content-Type", "application/json")
+            .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+            .unwrap();
+        app.clone().oneshot(req)
+    };
+    let code = |response: axum::response::Response| async move {
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (status, from_slice::<Value>(&body).unwrap()["code"].clone())
+    };
+
+    let task = |task_type: &str, interval_seconds: i64| {
+        json!({
+            "name": "codes",
+            "task_type": task_type,
+            "trigger_at": chrono::Utc::now().to_rfc3339(),
+            "interval_seconds": interval_seconds,
+        })
+    };
+    let response = send("POST", "/tasks".into(), Some(task("interval", 0)))
+        .await
+        .unwrap();
+    assert_eq!(
+        code(response).await,
+        (StatusCode::BAD_REQUEST, json!("INTERVAL_TOO_SMALL"))
+    );
+    let response = send("POST", "/tasks".into(), Some(task("weekly", 60)))
+        .await
+        .unwrap();
+    assert_eq!(
+        code(response).await,
+        (StatusCode::BAD_REQUEST, json!("VALIDATION_FAILED"))
+    );
+    let response = send("DELETE", format!("/tasks/{}", uuid::Uuid::new_v4()), None)
+        .await
+        .unwrap();
+    assert_eq!(
+        code(response).await,
+        (StatusCode::NOT_FOUND, json!("NOT_FOUND"))
+    );
+    let response = send(
+        "POST",
+        format!("/tasks/{}/rotate-secret", uuid::Uuid::new_v4()),
+        Some(json!({ "overlap_seconds": i64::MAX })),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        code(response).await,
+        (StatusCode::BAD_REQUEST, json!("VALIDATION_FAILED"))
+    );
+}
+
+#[tokio::test]
+async fn test_group_lifecycle_and_listing() {
+    let store = std::sync::Arc::new(crate::db::memory::MemoryTaskStore::new());
+    let (tx, _rx) = mpsc::channel(8);
+    let app = router(TaskService::with_store(store, tx));
+
+    let send = |method: &str, uri: String, body: Option<Value>| {
+        let req = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("Content-Type", "application/json")
+            .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+            .unwrap();
+        app.clone().oneshot(req)
+    };
+    let read = |response: axum::response::Response| async move {
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        from_slice::<Value>(&body).unwrap()
+    };
+
+    let response = send("POST", "/groups".into(), Some(json!({ "name": "billing" })))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let group_id = read(response).await["id"].as_str().unwrap().to_string();
+
+    let task = |group_id: &str| {
+        json!({
+            "name": "invoice-run",
+            "task_type": "once",
+            "trigger_at": chrono::Utc::now().to_rfc3339(),
+            "group_id": group_id,
+        })
+    };
+    let response = send("POST", "/tasks".into(), Some(task(&group_id)))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = send(
+        "POST",
+        "/tasks".into(),
+        Some(task(&uuid::Uuid::new_v4().to_string())),
+    )
+    .await
+    .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST, "Unknown group");
+
+    let response = send("GET", format!("/tasks?group_id={}", group_id), None)
+        .await
+        .unwrap();
+    let tasks = read(response).await;
+    assert_eq!(tasks.as_array().unwrap().len(), 1);
+    assert_eq!(tasks[0]["group_id"], group_id.as_str());
+
+    let response = send("POST", format!("/groups/{}/pause", group_id), None)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let response = send("GET", format!("/groups/{}", group_id), None)
+        .await
+        .unwrap();
+    let group = read(response).await;
+    assert!(group["paused_at"].is_string());
+    assert_eq!(group["stats"]["task_count"], 1);
+}
+
+#[sqlx::test]
+async fn test_paused_tasks_are_kept_but_not_picked(pool: SqlitePool) -> sqlx::Result<()> {
+    let (tx, _rx) = mpsc::channel(8);
+    let app = router(TaskService::new(pool.clone(), tx));
+    let repo = crate::db::queries::TaskRepository::new(&pool);
+
+    let send = |uri: String| {
+        let req = Request::post(uri).body(Body::empty()).unwrap();
+        app.clone().oneshot(req)
+    };
+    let status_of = |id: uuid::Uuid| {
+        let req = Request::get("/tasks").body(Body::empty()).unwrap();
+        let app = app.clone();
+        async move {
+            let body = app.oneshot(req).await.unwrap().into_body();
+            let tasks: Value = from_slice(&body.collect().await.unwrap().to_bytes()).unwrap();
+            let task = tasks
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|t| t["id"] == id.to_string());
+            task.unwrap()["status"].clone()
+        }
+    };
+
+    let task = crate::domain::Task::new_interval("hourly", chrono::Utc::now(), 3600, json!({}));
+    repo.create_task(&task).await?;
+
+    let response = send(format!("/tasks/{}/pause", task.id)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert_eq!(status_of(task.id).await, "paused");
+    assert!(repo.get_next_pending_task().await?.is_none());
+
+    let response = send(format!("/tasks/{}/resume", task.id)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert_eq!(status_of(task.id).await, "active");
+    assert_eq!(
+        repo.get_next_pending_task().await?.map(|t| t.id),
+        Some(task.id)
+    );
+
+    let response = send(format!("/tasks/{}/pause", uuid::Uuid::new_v4()))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_admin_routes_move_to_the_admin_listener() {
+    let store = std::sync::Arc::new(crate::db::memory::MemoryTaskStore::new());
+    let (tx, _rx) = mpsc::channel(1);
+    let service = TaskService::with_store(store, tx);
+
+    let get = |uri: &str| Request::get(uri).body(Body::empty()).unwrap();
+    let public = crate::api::public_router(service.clone());
+    let admin = crate::api::admin_router(service);
+
+    let status = |r: axum::response::Response| r.status();
+    assert_eq!(
+        status(public.clone().oneshot(get("/tasks")).await.unwrap()),
+        StatusCode::OK
+    );
+    assert_eq!(
+        status(
+            public
+                .clone()
+                .oneshot(get("/admin/api-keys"))
+                .await
+                .unwrap()
+        ),
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        status(public.oneshot(get("/health/live")).await.unwrap()),
+        StatusCode::OK
+    );
+
+    assert_eq!(
+        status(admin.clone().oneshot(get("/admin/api-keys")).await.unwrap()),
+        StatusCode::OK
+    );
+    assert_eq!(
+        status(admin.clone().oneshot(get("/health/ready")).await.unwrap()),
+        StatusCode::OK
+    );
+    assert_eq!(
+        status(admin.oneshot(get("/tasks")).await.unwrap()),
+        StatusCode::NOT_FOUND
+    );
+}
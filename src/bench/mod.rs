@@ -0,0 +1,283 @@
+//! Built-in load-testing harness, run with `task-scheduler bench [OPTIONS]`.
+//!
+//! Seeds synthetic tasks into a throwaway SQLite database, runs the real scheduler against
+//! them and reports scheduling latency (`executed_at - trigger_at`) and throughput.
+use crate::config::Config;
+use crate::db::{self, queries::TaskRepository};
+use crate::domain::Task;
+use crate::errors::AppError;
+use crate::scheduler;
+use crate::service::TaskService;
+use chrono::{DateTime, Duration, Utc};
+use serde_json::json;
+use sqlx::sqlite::SqlitePoolOptions;
+use std::fmt;
+use std::str::FromStr;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+#[cfg(test)]
+mod tests;
+
+/// How seeded trigger times are spread over the seeding window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Distribution {
+    /// Trigger times evenly spaced across the window.
+    Uniform,
+    /// Every task due at the start of the window.
+    Burst,
+}
+
+impl FromStr for Distribution {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "uniform" => Ok(Distribution::Uniform),
+            "burst" => Ok(Distribution::Burst),
+            _ => Err(AppError::Config(format!(
+                "Unknown distribution '{}'. Use 'uniform' or 'burst'",
+                s
+            ))),
+        }
+    }
+}
+
+/// Parameters for a benchmark run.
+#[derive(Debug, Clone)]
+pub struct BenchOptions {
+    /// Number of tasks to seed.
+    pub tasks: usize,
+    /// Width of the window trigger times are spread over, in seconds.
+    pub spread_secs: i64,
+    /// How trigger times are spread over the window.
+    pub distribution: Distribution,
+    /// Optional webhook target; without it executions fail fast and only scheduler
+    /// overhead is measured.
+    pub url: Option<String>,
+    /// Give up waiting for executions after this many seconds.
+    pub timeout_secs: u64,
+}
+
+impl Default for BenchOptions {
+    fn default() -> Self {
+        Self {
+            tasks: 1000,
+            spread_secs: 10,
+            distribution: Distribution::Uniform,
+            url: None,
+            timeout_secs: 300,
+        }
+    }
+}
+
+impl BenchOptions {
+    /// Parses `--tasks N --spread-secs S --distribution uniform|burst --url URL --timeout-secs T`.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::Config' for unknown flags or unparsable values.
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, AppError> {
+        let mut options = BenchOptions::default();
+        let mut args = args.into_iter();
+
+        while let Some(flag) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| AppError::Config(format!("Missing value for '{}'", flag)))?;
+            let invalid = || AppError::Config(format!("Invalid value '{}' for '{}'", value, flag));
+
+            match flag.as_str() {
+                "--tasks" => options.tasks = value.parse().map_err(|_| invalid())?,
+                "--spread-secs" => options.spread_secs = value.parse().map_err(|_| invalid())?,
+                "--distribution" => options.distribution = value.parse()?,
+                "--url" => options.url = Some(value),
+                "--timeout-secs" => options.timeout_secs = value.parse().map_err(|_| invalid())?,
+                _ => return Err(AppError::Config(format!("Unknown bench flag '{}'", flag))),
+            }
+        }
+
+        Ok(options)
+    }
+
+    fn trigger_at(&self, start: DateTime<Utc>, index: usize) -> DateTime<Utc> {
+        match self.distribution {
+            Distribution::Burst => start,
+            Distribution::Uniform => {
+                let step_ms = self.spread_secs * 1000 / self.tasks.max(1) as i64;
+                start + Duration::milliseconds(step_ms * index as i64)
+            }
+        }
+    }
+}
+
+/// Results of a benchmark run.
+#[derive(Debug, Clone)]
+pub struct BenchReport {
+    /// The parameters the run was started with.
+    pub options: BenchOptions,
+    pub seeded: usize,
+    pub executed: usize,
+    pub wall_time: std::time::Duration,
+    pub throughput_per_sec: f64,
+    pub latency_p50_ms: i64,
+    pub latency_p95_ms: i64,
+    pub latency_p99_ms: i64,
+    pub latency_max_ms: i64,
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Benchmark report")?;
+        match self.options.distribution {
+            Distribution::Uniform => writeln!(
+                f,
+                "  distribution:     uniform over {} s",
+                self.options.spread_secs
+            )?,
+            Distribution::Burst => writeln!(f, "  distribution:     burst")?,
+        }
+        writeln!(
+            f,
+            "  target:           {}",
+            self.options.url.as_deref().unwrap_or("none")
+        )?;
+        writeln!(f, "  tasks seeded:     {}", self.seeded)?;
+        writeln!(f, "  tasks executed:   {}", self.executed)?;
+        writeln!(f, "  wall time:        {:.2?}", self.wall_time)?;
+        writeln!(
+            f,
+            "  throughput:       {:.1} exec/s",
+            self.throughput_per_sec
+        )?;
+        writeln!(f, "  latency p50:      {} ms", self.latency_p50_ms)?;
+        writeln!(f, "  latency p95:      {} ms", self.latency_p95_ms)?;
+        writeln!(f, "  latency p99:      {} ms", self.latency_p99_ms)?;
+        write!(f, "  latency max:      {} ms", self.latency_max_ms)
+    }
+}
+
+/// Returns the value at percentile `p` (0-100) of an ascending slice.
+pub fn percentile(sorted: &[i64], p: f64) -> i64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = ((p / 100.0) * (sorted.len() - 1) as f64).round() as usize;
+    sorted[rank.min(sorted.len() - 1)]
+}
+
+/// Seeds tasks into a temporary database, runs the scheduler and measures the results.
+///
+/// # Arguments
+///
+/// * `options` - The benchmark parameters.
+/// * `config` - Base configuration; the database URL is replaced by a temporary file.
+///
+/// # Errors
+///
+/// * Returns 'AppError::Database' if seeding or measuring fails.
+pub async fn run(options: BenchOptions, config: Config) -> Result<BenchReport, AppError> {
+    let db_path = std::env::temp_dir().join(format!("scheduler-bench-{}.db", uuid::Uuid::new_v4()));
+    let config = Config {
+        db_url: format!("sqlite:{}", db_path.display()),
+        ..config
+    };
+
+    let pool = SqlitePoolOptions::new()
+        .max_connections(5)
+        .connect_with(db::connect_options(&config)?)
+        .await?;
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .map_err(|e| AppError::Database(e.into()))?;
+
+    let payload = match &options.url {
+        Some(url) => json!({ "url": url, "method": "GET" }),
+        None => json!({}),
+    };
+
+    let start = Utc::now() + Duration::seconds(1);
+    let repo = TaskRepository::new(&pool);
+    for index in 0..options.tasks {
+        let task = Task::new_once(
+            format!("bench-{}", index),
+            options.trigger_at(start, index),
+            payload.clone(),
+        );
+        repo.create_task(&task).await?;
+    }
+    tracing::info!(tasks = options.tasks, "Seeded benchmark tasks");
+
+    let (tx, rx) = mpsc::channel(100);
+    let token = CancellationToken::new();
+    let service = TaskService::new(pool.clone(), tx).with_config(config);
+    let scheduler_token = token.clone();
+    let handle = tokio::spawn(async move {
+        scheduler::run_scheduler(service, rx, scheduler_token).await;
+    });
+
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(options.timeout_secs);
+    let mut executed = 0usize;
+    while std::time::Instant::now() < deadline {
+        let count: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM executions WHERE status != 'running'")
+                .fetch_one(&pool)
+                .await?;
+        executed = count as usize;
+        if executed >= options.tasks {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+
+    token.cancel();
+    let _ = handle.await;
+
+    let rows: Vec<(DateTime<Utc>, DateTime<Utc>)> = sqlx::query_as(
+        r#"
+        SELECT t.trigger_at, e.executed_at
+        FROM executions e
+        JOIN tasks t ON t.id = e.task_id
+        WHERE e.status != 'running'
+        "#,
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    pool.close().await;
+    for suffix in ["", "-shm", "-wal"] {
+        let _ = std::fs::remove_file(format!("{}{}", db_path.display(), suffix));
+    }
+
+    let mut latencies: Vec<i64> = rows
+        .iter()
+        .map(|(trigger_at, executed_at)| (*executed_at - *trigger_at).num_milliseconds().max(0))
+        .collect();
+    latencies.sort_unstable();
+
+    let wall_time = rows
+        .iter()
+        .map(|(_, executed_at)| *executed_at)
+        .max()
+        .map(|last| (last - start).to_std().unwrap_or_default())
+        .unwrap_or_default();
+
+    let throughput_per_sec = if wall_time.as_secs_f64() > 0.0 {
+        executed as f64 / wall_time.as_secs_f64()
+    } else {
+        executed as f64
+    };
+
+    Ok(BenchReport {
+        seeded: options.tasks,
+        options,
+        executed,
+        wall_time,
+        throughput_per_sec,
+        latency_p50_ms: percentile(&latencies, 50.0),
+        latency_p95_ms: percentile(&latencies, 95.0),
+        latency_p99_ms: percentile(&latencies, 99.0),
+        latency_max_ms: latencies.last().copied().unwrap_or(0),
+    })
+}
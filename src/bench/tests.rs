@@ -0,0 +1,49 @@
+use crate::bench::{BenchOptions, Distribution, percentile, run};
+use crate::config::Config;
+
+#[test]
+fn test_options_parse_flags() {
+    let args = [
+        "--tasks",
+        "50",
+        "--distribution",
+        "burst",
+        "--spread-secs",
+        "2",
+    ]
+    .map(String::from);
+
+    let options = BenchOptions::from_args(args).unwrap();
+
+    assert_eq!(options.tasks, 50);
+    assert_eq!(options.distribution, Distribution::Burst);
+    assert_eq!(options.spread_secs, 2);
+    assert!(BenchOptions::from_args(["--bogus".to_string(), "1".to_string()]).is_err());
+}
+
+#[test]
+fn test_percentile() {
+    let sorted = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+
+    assert_eq!(percentile(&sorted, 0.0), 1);
+    assert_eq!(percentile(&sorted, 50.0), 6);
+    assert_eq!(percentile(&sorted, 100.0), 10);
+    assert_eq!(percentile(&[], 99.0), 0);
+}
+
+#[tokio::test]
+async fn test_small_burst_run_executes_every_task() {
+    let options = BenchOptions {
+        tasks: 20,
+        distribution: Distribution::Burst,
+        timeout_secs: 30,
+        ..BenchOptions::default()
+    };
+
+    let report = run(options, Config::default()).await.unwrap();
+
+    assert_eq!(report.seeded, 20);
+    assert_eq!(report.executed, 20);
+    assert!(report.latency_p50_ms <= report.latency_max_ms);
+    assert!(report.to_string().contains("distribution:     burst"));
+}
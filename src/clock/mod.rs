@@ -0,0 +1,121 @@
+//! Time source used by the scheduler and service.
+//!
+//! Production code runs on [`SystemClock`]; tests inject a [`TestClock`] and move time forward
+//! explicitly instead of sleeping for real.
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use std::fmt::Debug;
+use std::time::Instant;
+use tokio::sync::watch;
+
+#[cfg(test)]
+mod tests;
+
+/// Longest single sleep before the wall-clock deadline is re-validated.
+const MAX_SLEEP_SLICE: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Disagreement between wall-clock and monotonic elapsed time treated as a clock jump.
+const CLOCK_JUMP_THRESHOLD: Duration = Duration::seconds(2);
+
+/// A source of the current time that can also wait for a point in time.
+#[async_trait]
+pub trait Clock: Debug + Send + Sync {
+    /// The current time.
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Resolves once `now()` is at or after `deadline`, or earlier if the clock jumped and the
+    /// caller should re-plan. Callers must re-check the time after waking.
+    async fn sleep_until(&self, deadline: DateTime<Utc>);
+}
+
+/// Wall-clock time backed by `Utc::now()` and the Tokio timer.
+///
+/// Tokio timers run on the monotonic clock, which does not follow wall-clock adjustments and
+/// stops while the host is suspended. Sleeps are therefore cut into short slices and the
+/// deadline is re-validated against the wall clock after each one; if the two clocks
+/// disagree by more than [`CLOCK_JUMP_THRESHOLD`], the jump is logged and the sleep ends early
+/// so the scheduler re-plans instead of oversleeping for hours.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+#[async_trait]
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    async fn sleep_until(&self, deadline: DateTime<Utc>) {
+        loop {
+            let wall_start = Utc::now();
+            let Ok(remaining) = (deadline - wall_start).to_std() else {
+                return;
+            };
+            if remaining.is_zero() {
+                return;
+            }
+
+            let monotonic_start = Instant::now();
+            tokio::time::sleep(remaining.min(MAX_SLEEP_SLICE)).await;
+
+            if let Some(jump) = detect_jump(Utc::now() - wall_start, monotonic_start.elapsed()) {
+                tracing::warn!(
+                    jump_ms = jump.num_milliseconds(),
+                    %deadline,
+                    "Wall clock jumped (NTP step or suspend); re-planning"
+                );
+                return;
+            }
+        }
+    }
+}
+
+/// Returns how far the wall clock moved relative to the monotonic clock over the same
+/// interval, if that exceeds [`CLOCK_JUMP_THRESHOLD`] in either direction.
+pub fn detect_jump(
+    wall_elapsed: Duration,
+    monotonic_elapsed: std::time::Duration,
+) -> Option<Duration> {
+    let monotonic_elapsed = Duration::from_std(monotonic_elapsed).unwrap_or(Duration::MAX);
+    let jump = wall_elapsed - monotonic_elapsed;
+    (jump.abs() > CLOCK_JUMP_THRESHOLD).then_some(jump)
+}
+
+/// Manually driven clock for deterministic tests.
+///
+/// Time only moves when [`advance`](TestClock::advance) or [`set`](TestClock::set) is called,
+/// which wakes every pending `sleep_until` whose deadline has been reached.
+#[derive(Debug)]
+pub struct TestClock {
+    now: watch::Sender<DateTime<Utc>>,
+}
+
+impl TestClock {
+    pub fn new(start: DateTime<Utc>) -> Self {
+        Self {
+            now: watch::Sender::new(start),
+        }
+    }
+
+    /// Moves the clock forward (or backward, for a negative duration).
+    pub fn advance(&self, by: Duration) {
+        self.now.send_modify(|now| *now += by);
+    }
+
+    /// Jumps the clock to an absolute time.
+    pub fn set(&self, to: DateTime<Utc>) {
+        self.now.send_replace(to);
+    }
+}
+
+#[async_trait]
+impl Clock for TestClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.borrow()
+    }
+
+    async fn sleep_until(&self, deadline: DateTime<Utc>) {
+        let mut rx = self.now.subscribe();
+        // The sender lives as long as `self`, so this only returns once the deadline passes
+        let _ = rx.wait_for(|now| *now >= deadline).await;
+    }
+}
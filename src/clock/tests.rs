@@ -0,0 +1,54 @@
+use crate::clock::{Clock, SystemClock, TestClock, detect_jump};
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+
+#[test]
+fn test_detect_jump_ignores_normal_drift() {
+    let slept = std::time::Duration::from_millis(1000);
+    assert_eq!(detect_jump(Duration::milliseconds(1003), slept), None);
+    assert_eq!(detect_jump(Duration::milliseconds(998), slept), None);
+}
+
+#[test]
+fn test_detect_jump_reports_forward_and_backward_steps() {
+    let slept = std::time::Duration::from_secs(1);
+
+    // Suspend or NTP step forward: an hour passed on the wall clock during a 1s sleep
+    assert_eq!(
+        detect_jump(Duration::seconds(3601), slept),
+        Some(Duration::hours(1))
+    );
+    // Step backward: the wall clock went back 10 minutes
+    assert_eq!(
+        detect_jump(Duration::seconds(1) - Duration::minutes(10), slept),
+        Some(-Duration::minutes(10))
+    );
+}
+
+#[tokio::test]
+async fn test_system_clock_returns_immediately_for_past_deadlines() {
+    let started = std::time::Instant::now();
+    SystemClock
+        .sleep_until(Utc::now() - Duration::hours(1))
+        .await;
+    assert!(started.elapsed() < std::time::Duration::from_millis(100));
+}
+
+#[tokio::test]
+async fn test_test_clock_wakes_sleepers_on_advance() {
+    let start = Utc::now();
+    let clock = Arc::new(TestClock::new(start));
+
+    let sleeper = tokio::spawn({
+        let clock = clock.clone();
+        async move { clock.sleep_until(start + Duration::minutes(5)).await }
+    });
+
+    clock.advance(Duration::minutes(4));
+    tokio::task::yield_now().await;
+    assert!(!sleeper.is_finished());
+
+    clock.advance(Duration::minutes(1));
+    sleeper.await.unwrap();
+    assert_eq!(clock.now(), start + Duration::minutes(5));
+}
@@ -0,0 +1,118 @@
+use crate::domain::{ApiKey, Scope};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+pub struct ApiKeyRepository<'a> {
+    pub pool: &'a SqlitePool,
+}
+
+impl<'a> ApiKeyRepository<'a> {
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Stores a new API key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key's metadata.
+    /// * `key_hash` - Hash of the plaintext key; the plaintext itself is never stored.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<()>` - Result indicating success or failure of the operation.
+    pub async fn create(&self, key: &ApiKey, key_hash: &str) -> sqlx::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO api_keys (id, name, key_hash, scope, created_at)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(key.id)
+        .bind(&key.name)
+        .bind(key_hash)
+        .bind(key.scope)
+        .bind(key.created_at)
+        .execute(self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Looks up a live (not revoked) key by the hash of its plaintext.
+    ///
+    /// # Arguments
+    ///
+    /// * `key_hash` - Hash of the presented key.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<Option<ApiKey>>` - The key if it exists and has not been revoked.
+    pub async fn find_by_hash(&self, key_hash: &str) -> sqlx::Result<Option<ApiKey>> {
+        sqlx::query_as::<_, ApiKey>(
+            r#"
+            SELECT id, name, scope, created_at, revoked_at
+            FROM api_keys
+            WHERE key_hash = ? AND revoked_at IS NULL
+            "#,
+        )
+        .bind(key_hash)
+        .fetch_optional(self.pool)
+        .await
+    }
+
+    /// Retrieves every key, revoked ones included, oldest first.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<Vec<ApiKey>>` - All stored keys.
+    pub async fn list(&self) -> sqlx::Result<Vec<ApiKey>> {
+        sqlx::query_as::<_, ApiKey>(
+            r#"
+            SELECT id, name, scope, created_at, revoked_at
+            FROM api_keys
+            ORDER BY created_at ASC, id ASC
+            "#,
+        )
+        .fetch_all(self.pool)
+        .await
+    }
+
+    /// Changes the scope of a live key.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The UUID of the key.
+    /// * `scope` - The new scope.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<u64>` - Result containing the number of rows affected.
+    pub async fn update_scope(&self, id: Uuid, scope: Scope) -> sqlx::Result<u64> {
+        let result =
+            sqlx::query("UPDATE api_keys SET scope = ? WHERE id = ? AND revoked_at IS NULL")
+                .bind(scope)
+                .bind(id)
+                .execute(self.pool)
+                .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Revokes a key; it can no longer authenticate.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The UUID of the key.
+    /// * `at` - Revocation timestamp.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<u64>` - Result containing the number of rows affected.
+    pub async fn revoke(&self, id: Uuid, at: DateTime<Utc>) -> sqlx::Result<u64> {
+        let result =
+            sqlx::query("UPDATE api_keys SET revoked_at = ? WHERE id = ? AND revoked_at IS NULL")
+                .bind(at)
+                .bind(id)
+                .execute(self.pool)
+                .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,192 @@
+use crate::domain::{GroupStats, TaskGroup};
+use chrono::{DateTime, Utc};
+use sqlx::{Executor, Sqlite, SqlitePool};
+use uuid::Uuid;
+
+pub struct GroupRepository<'a> {
+    pub pool: &'a SqlitePool,
+}
+
+impl<'a> GroupRepository<'a> {
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Stores a new task group.
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The group to store.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<()>` - Result indicating success or failure of the operation.
+    pub async fn create(&self, group: &TaskGroup) -> sqlx::Result<()> {
+        Self::create_with_executor(self.pool, group).await
+    }
+
+    /// Stores a new task group using the provided executor.
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - An executor that can execute the query (e.g., a connection or transaction).
+    /// * `group` - The group to store.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<()>` - Result indicating success or failure of the operation.
+    pub async fn create_with_executor<'c, E>(executor: E, group: &TaskGroup) -> sqlx::Result<()>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        sqlx::query(
+            r#"
+            INSERT INTO task_groups (id, name, description, paused_at, created_at)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(group.id)
+        .bind(&group.name)
+        .bind(&group.description)
+        .bind(group.paused_at)
+        .bind(group.created_at)
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Retrieves a group by its ID.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The UUID of the group.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<Option<TaskGroup>>` - The group if found.
+    pub async fn find(&self, id: Uuid) -> sqlx::Result<Option<TaskGroup>> {
+        sqlx::query_as::<_, TaskGroup>(
+            r#"
+            SELECT id, name, description, paused_at, created_at
+            FROM task_groups
+            WHERE id = ?
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(self.pool)
+        .await
+    }
+
+    /// Retrieves every group, ordered by name.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<Vec<TaskGroup>>` - The groups.
+    pub async fn list(&self) -> sqlx::Result<Vec<TaskGroup>> {
+        Self::list_with_executor(self.pool).await
+    }
+
+    /// Retrieves every group, ordered by name, using the provided executor.
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - An executor that can execute the query (e.g., a connection or transaction).
+    ///
+    /// # Returns
+    /// * `sqlx::Result<Vec<TaskGroup>>` - The groups.
+    pub async fn list_with_executor<'c, E>(executor: E) -> sqlx::Result<Vec<TaskGroup>>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        sqlx::query_as::<_, TaskGroup>(
+            r#"
+            SELECT id, name, description, paused_at, created_at
+            FROM task_groups
+            ORDER BY name ASC
+            "#,
+        )
+        .fetch_all(executor)
+        .await
+    }
+
+    /// Pauses a group at `paused_at`, or resumes it if `None`, using the provided executor.
+    /// Pausing a paused group keeps its original pause time.
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - An executor that can execute the query (e.g., a connection or transaction).
+    /// * `id` - The UUID of the group.
+    /// * `paused_at` - The pause time, or `None` to resume.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<u64>` - The number of rows affected.
+    pub async fn set_paused_with_executor<'c, E>(
+        executor: E,
+        id: Uuid,
+        paused_at: Option<DateTime<Utc>>,
+    ) -> sqlx::Result<u64>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let result = sqlx::query(
+            r#"
+            UPDATE task_groups
+            SET paused_at = CASE WHEN ?1 IS NULL THEN NULL ELSE COALESCE(paused_at, ?1) END
+            WHERE id = ?2
+            "#,
+        )
+        .bind(paused_at)
+        .bind(id)
+        .execute(executor)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Retrieves the trigger times of a group's live tasks using the provided executor.
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - An executor that can execute the query (e.g., a connection or transaction).
+    /// * `id` - The UUID of the group.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<Vec<(Uuid, DateTime<Utc>)>>` - Task ids with their trigger times.
+    pub async fn task_triggers_with_executor<'c, E>(
+        executor: E,
+        id: Uuid,
+    ) -> sqlx::Result<Vec<(Uuid, DateTime<Utc>)>>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        sqlx::query_as("SELECT id, trigger_at FROM tasks WHERE group_id = ? AND deleted_at IS NULL")
+            .bind(id)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// Aggregates the task and execution counts of a group.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The UUID of the group.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<GroupStats>` - The group's numbers; all zero for an empty group.
+    pub async fn stats(&self, id: Uuid) -> sqlx::Result<GroupStats> {
+        sqlx::query_as::<_, GroupStats>(
+            r#"
+            SELECT
+                (SELECT COUNT(*) FROM tasks WHERE group_id = ?1 AND deleted_at IS NULL)
+                    AS task_count,
+                COUNT(e.id) AS execution_count,
+                COALESCE(SUM(e.status = 'success'), 0) AS success_count,
+                COALESCE(SUM(e.status IN ('failure', 'interrupted')), 0) AS failure_count,
+                MAX(e.executed_at) AS last_executed_at
+            FROM executions e
+            JOIN tasks t ON t.id = e.task_id
+            WHERE t.group_id = ?1
+            "#,
+        )
+        .bind(id)
+        .fetch_one(self.pool)
+        .await
+    }
+}
@@ -0,0 +1,71 @@
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+pub struct LeaseRepository<'a> {
+    pub pool: &'a SqlitePool,
+}
+
+impl<'a> LeaseRepository<'a> {
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Takes or renews a lease in a single statement, so two instances can never both
+    /// hold it.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the lease.
+    /// * `holder` - The instance taking it.
+    /// * `now` - Leases that expired before this time are free.
+    /// * `expires_at` - When the lease lapses unless it is renewed.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<bool>` - True if `holder` now holds the lease, false if another
+    ///   instance does.
+    pub async fn acquire(
+        &self,
+        name: &str,
+        holder: &str,
+        now: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> sqlx::Result<bool> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO leases (name, holder, expires_at)
+            VALUES (?, ?, ?)
+            ON CONFLICT(name) DO UPDATE SET
+                holder = excluded.holder,
+                expires_at = excluded.expires_at
+            WHERE leases.holder = excluded.holder OR leases.expires_at < ?
+            "#,
+        )
+        .bind(name)
+        .bind(holder)
+        .bind(expires_at)
+        .bind(now)
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Gives up a lease, if `holder` still holds it.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the lease.
+    /// * `holder` - The instance giving it up.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<u64>` - Result containing the number of rows affected.
+    pub async fn release(&self, name: &str, holder: &str) -> sqlx::Result<u64> {
+        let result = sqlx::query("DELETE FROM leases WHERE name = ? AND holder = ?")
+            .bind(name)
+            .bind(holder)
+            .execute(self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
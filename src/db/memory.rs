@@ -0,0 +1,1068 @@
+//! In-memory [`TaskStore`] for unit tests, available outside this crate with the `test-util`
+//! feature.
+//!
+//! It mirrors the SQLite store's semantics closely enough for service-level tests (soft
+//! deletes, keyset pagination, skipped writes for vanished tasks, the schedule outbox) and can
+//! be told to fail upcoming calls to exercise error paths.
+use crate::db::store::{
+    Claim, ExecutionWrite, FollowUp, TaskStore, WriteOutcome, restored_task_is_scheduled,
+};
+use crate::domain::{
+    ApiKey, Execution, ExecutionCursor, ExecutionStatus, ExecutionSummary, GroupStats, RunState,
+    SNAPSHOT_VERSION, Scope, Snapshot, StoredSecret, Task, TaskFilter, TaskGroup, TaskPatch,
+    TaskRuns, TaskSelector, TaskState, TaskSummary, TriggerLink, Workflow, WorkflowRun,
+    WorkflowRunStatus,
+};
+use crate::scheduler::ScheduleEvent;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::stream::{self, BoxStream, StreamExt};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, MutexGuard};
+use uuid::Uuid;
+
+#[derive(Debug, Default)]
+struct State {
+    /// Tasks in insertion order.
+    tasks: Vec<Task>,
+    executions: Vec<Execution>,
+    outbox: VecDeque<ScheduleEvent>,
+    /// API keys with the hashes of their plaintext.
+    api_keys: Vec<(ApiKey, String)>,
+    trigger_links: Vec<TriggerLink>,
+    groups: Vec<TaskGroup>,
+    workflows: Vec<Workflow>,
+    /// Workflow runs in start order.
+    workflow_runs: Vec<WorkflowRun>,
+    /// Secrets ordered by name.
+    secrets: Vec<StoredSecret>,
+    /// Holder and expiry of each lease.
+    leases: HashMap<String, (String, DateTime<Utc>)>,
+    /// The latest claim on each task.
+    claims: HashMap<Uuid, Claim>,
+    /// Scripted outcomes of upcoming calls; `None` lets a call through.
+    failures: VecDeque<Option<sqlx::Error>>,
+}
+
+#[derive(Debug, Default)]
+pub struct MemoryTaskStore {
+    state: Mutex<State>,
+}
+
+impl MemoryTaskStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes the next store call fail with `error`. Queued errors are returned in order.
+    pub fn fail_next(&self, error: sqlx::Error) {
+        self.lock().failures.push_back(Some(error));
+    }
+
+    /// Lets the next store call succeed before any queued failures apply, to target a later
+    /// call in a sequence.
+    pub fn pass_next(&self) {
+        self.lock().failures.push_back(None);
+    }
+
+    /// Removes a task outright, as if it had been hard deleted behind the service's back.
+    pub fn remove_task(&self, id: Uuid) {
+        self.lock().tasks.retain(|t| t.id != id);
+    }
+
+    /// Snapshot of every recorded execution, oldest first.
+    pub fn executions(&self) -> Vec<Execution> {
+        self.lock().executions.clone()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Locks the state, or returns the next injected failure.
+    fn begin(&self) -> sqlx::Result<MutexGuard<'_, State>> {
+        let mut state = self.lock();
+        match state.failures.pop_front() {
+            Some(Some(error)) => Err(error),
+            _ => Ok(state),
+        }
+    }
+}
+
+impl State {
+    /// Removes the given executions; their replays are kept and forget what they repeat.
+    fn remove_executions(&mut self, ids: &[Uuid]) {
+        self.executions.retain(|e| !ids.contains(&e.id));
+        for execution in &mut self.executions {
+            if execution.replay_of.is_some_and(|id| ids.contains(&id)) {
+                execution.replay_of = None;
+            }
+        }
+    }
+
+    /// IDs of up to `limit` tasks soft deleted before `cutoff`, longest deleted first.
+    fn deleted_task_ids(&self, cutoff: DateTime<Utc>, limit: i64) -> Vec<Uuid> {
+        let mut deleted: Vec<(DateTime<Utc>, Uuid)> = self
+            .tasks
+            .iter()
+            .filter_map(|t| t.deleted_at.filter(|at| *at < cutoff).map(|at| (at, t.id)))
+            .collect();
+        deleted.sort();
+        deleted.truncate(limit.max(0) as usize);
+        deleted.into_iter().map(|(_, id)| id).collect()
+    }
+
+    /// IDs of up to `limit` finished executions outside their task's retention settings,
+    /// oldest first; see [`TaskStore::prune_executions`].
+    fn expired_executions(
+        &self,
+        now: DateTime<Utc>,
+        default_keep_days: Option<i64>,
+        limit: i64,
+    ) -> Vec<Uuid> {
+        let mut expired = Vec::new();
+        for task in &self.tasks {
+            let mut finished: Vec<&Execution> = self
+                .executions
+                .iter()
+                .filter(|e| e.task_id == task.id && e.status != ExecutionStatus::Running)
+                .collect();
+            finished.sort_by_key(|e| std::cmp::Reverse((e.executed_at, e.id)));
+
+            for (recency, exec) in finished.into_iter().enumerate() {
+                let over_count = task
+                    .keep_executions
+                    .is_some_and(|keep| recency as i64 >= keep);
+                let too_old = task
+                    .keep_days
+                    .or(default_keep_days)
+                    .is_some_and(|days| exec.executed_at < now - chrono::Duration::days(days));
+                if over_count || too_old {
+                    expired.push((exec.executed_at, exec.id));
+                }
+            }
+        }
+
+        expired.sort();
+        expired.truncate(limit.max(0) as usize);
+        expired.into_iter().map(|(_, id)| id).collect()
+    }
+
+    /// True if the task is live and neither it nor its group is paused.
+    fn is_scheduled(&self, task: &Task) -> bool {
+        task.deleted_at.is_none()
+            && task.paused_at.is_none()
+            && !task.group_id.is_some_and(|group_id| {
+                self.groups
+                    .iter()
+                    .any(|g| g.id == group_id && g.paused_at.is_some())
+            })
+    }
+
+    fn is_due(&self, task: &Task, now: DateTime<Utc>) -> bool {
+        self.is_scheduled(task) && task.trigger_at <= now
+    }
+
+    fn runs(&self, task: &Task) -> TaskRuns {
+        let last_run = self
+            .executions
+            .iter()
+            .filter(|e| e.task_id == task.id && e.status != ExecutionStatus::Running)
+            .max_by_key(|e| (e.executed_at, e.id));
+        TaskRuns {
+            next_run_at: self.is_scheduled(task).then_some(task.trigger_at),
+            last_run_at: last_run.map(|e| e.executed_at),
+            last_status: last_run.map(|e| e.status.clone()),
+        }
+    }
+}
+
+/// Replaces the execution with the same ID, or appends it.
+fn upsert_execution(executions: &mut Vec<Execution>, execution: &Execution) {
+    match executions.iter_mut().find(|e| e.id == execution.id) {
+        Some(existing) => *existing = execution.clone(),
+        None => executions.push(execution.clone()),
+    }
+}
+
+#[async_trait]
+impl TaskStore for MemoryTaskStore {
+    async fn create_task(&self, task: &Task) -> sqlx::Result<()> {
+        let mut state = self.begin()?;
+        if state.tasks.iter().any(|t| t.id == task.id) {
+            return Err(sqlx::Error::Protocol(format!(
+                "duplicate task id {}",
+                task.id
+            )));
+        }
+        state.tasks.push(task.clone());
+        state.outbox.push_back(ScheduleEvent::Upserted {
+            id: task.id,
+            trigger_at: task.trigger_at,
+        });
+        Ok(())
+    }
+
+    async fn get_task(&self, id: Uuid) -> sqlx::Result<Option<Task>> {
+        let state = self.begin()?;
+        Ok(state.tasks.iter().find(|t| t.id == id).cloned())
+    }
+
+    async fn delete_task(&self, id: Uuid) -> sqlx::Result<u64> {
+        let mut state = self.begin()?;
+        match state.tasks.iter_mut().find(|t| t.id == id) {
+            Some(task) => {
+                task.deleted_at = Some(Utc::now());
+                state.outbox.push_back(ScheduleEvent::Removed { id });
+                Ok(1)
+            }
+            None => Ok(0),
+        }
+    }
+
+    async fn purge_task(&self, id: Uuid) -> sqlx::Result<u64> {
+        let mut state = self.begin()?;
+        let before = state.tasks.len();
+        state.tasks.retain(|t| t.id != id);
+        if state.tasks.len() == before {
+            return Ok(0);
+        }
+
+        state.executions.retain(|e| e.task_id != id);
+        state.trigger_links.retain(|l| l.task_id != id);
+        state.outbox.push_back(ScheduleEvent::Removed { id });
+        Ok(1)
+    }
+
+    async fn purge_deleted_tasks(&self, cutoff: DateTime<Utc>, limit: i64) -> sqlx::Result<u64> {
+        let mut state = self.begin()?;
+        let ids = state.deleted_task_ids(cutoff, limit);
+        state.tasks.retain(|t| !ids.contains(&t.id));
+        state.executions.retain(|e| !ids.contains(&e.task_id));
+        state.trigger_links.retain(|l| !ids.contains(&l.task_id));
+        Ok(ids.len() as u64)
+    }
+
+    async fn deleted_task_ids(&self, cutoff: DateTime<Utc>, limit: i64) -> sqlx::Result<Vec<Uuid>> {
+        Ok(self.begin()?.deleted_task_ids(cutoff, limit))
+    }
+
+    async fn count_active_tasks(&self, api_key_id: Uuid) -> sqlx::Result<i64> {
+        let state = self.begin()?;
+        Ok(state
+            .tasks
+            .iter()
+            .filter(|t| t.api_key_id == Some(api_key_id) && t.deleted_at.is_none())
+            .count() as i64)
+    }
+
+    async fn rotate_signing_secret(
+        &self,
+        id: Uuid,
+        secret: &str,
+        previous_expires_at: DateTime<Utc>,
+    ) -> sqlx::Result<u64> {
+        let mut state = self.begin()?;
+        match state
+            .tasks
+            .iter_mut()
+            .find(|t| t.id == id && t.deleted_at.is_none())
+        {
+            Some(task) => {
+                task.previous_signing_secret = task.signing_secret.replace(secret.to_string());
+                task.previous_secret_expires_at = Some(previous_expires_at);
+                Ok(1)
+            }
+            None => Ok(0),
+        }
+    }
+
+    async fn drain_outbox(&self, limit: i64) -> sqlx::Result<Vec<ScheduleEvent>> {
+        let mut state = self.begin()?;
+        let take = state.outbox.len().min(limit.max(0) as usize);
+        Ok(state.outbox.drain(..take).collect())
+    }
+
+    /// Highest priority first, then oldest; the SQLite store's per-type interleaving is not
+    /// replicated.
+    async fn get_due_tasks(&self, now: DateTime<Utc>, limit: i64) -> sqlx::Result<Vec<Task>> {
+        let state = self.begin()?;
+        let mut due: Vec<Task> = state
+            .tasks
+            .iter()
+            .filter(|t| state.is_due(t, now))
+            .cloned()
+            .collect();
+        due.sort_by_key(|t| (std::cmp::Reverse(t.priority), t.trigger_at));
+        due.truncate(limit.max(0) as usize);
+        Ok(due)
+    }
+
+    async fn count_due_tasks(&self, now: DateTime<Utc>) -> sqlx::Result<i64> {
+        let state = self.begin()?;
+        Ok(state.tasks.iter().filter(|t| state.is_due(t, now)).count() as i64)
+    }
+
+    async fn get_upcoming_triggers(&self, limit: i64) -> sqlx::Result<Vec<(Uuid, DateTime<Utc>)>> {
+        let state = self.begin()?;
+        let mut triggers: Vec<_> = state
+            .tasks
+            .iter()
+            .filter(|t| state.is_scheduled(t))
+            .map(|t| (t.id, t.trigger_at))
+            .collect();
+        triggers.sort_by_key(|(_, trigger_at)| *trigger_at);
+        triggers.truncate(limit.max(0) as usize);
+        Ok(triggers)
+    }
+
+    async fn get_task_summaries(&self, filter: &TaskFilter) -> sqlx::Result<Vec<TaskSummary>> {
+        let state = self.begin()?;
+        Ok(state
+            .tasks
+            .iter()
+            .rev()
+            .filter(|t| match filter.state {
+                None => true,
+                Some(TaskState::Active) => t.deleted_at.is_none(),
+                Some(TaskState::Deleted) => t.deleted_at.is_some(),
+            })
+            .filter(|t| filter.group_id.is_none() || t.group_id == filter.group_id)
+            .map(|t| TaskSummary {
+                id: t.id,
+                name: t.name.clone(),
+                task_type: t.task_type.clone(),
+                trigger_at: t.trigger_at,
+                interval_seconds: t.interval_seconds,
+                cron_expr: t.cron_expr.clone(),
+                deleted_at: t.deleted_at,
+                group_id: t.group_id,
+                paused_at: t.paused_at,
+                run_state: t.run_state,
+                runs: state.runs(t),
+            })
+            .collect())
+    }
+
+    async fn get_task_runs(&self, id: Uuid) -> sqlx::Result<TaskRuns> {
+        let state = self.begin()?;
+        Ok(state
+            .tasks
+            .iter()
+            .find(|t| t.id == id)
+            .map(|t| state.runs(t))
+            .unwrap_or_default())
+    }
+
+    async fn list_executions(
+        &self,
+        task_id: Uuid,
+        after: Option<&ExecutionCursor>,
+        limit: i64,
+    ) -> sqlx::Result<Vec<ExecutionSummary>> {
+        let page = self.list_execution_records(task_id, after, limit).await?;
+        Ok(page
+            .into_iter()
+            .map(|e| ExecutionSummary {
+                id: e.id,
+                task_id: e.task_id,
+                executed_at: e.executed_at,
+                status: e.status,
+            })
+            .collect())
+    }
+
+    async fn list_execution_records(
+        &self,
+        task_id: Uuid,
+        after: Option<&ExecutionCursor>,
+        limit: i64,
+    ) -> sqlx::Result<Vec<Execution>> {
+        let state = self.begin()?;
+        let mut page: Vec<Execution> = state
+            .executions
+            .iter()
+            .filter(|e| e.task_id == task_id)
+            .filter(|e| after.is_none_or(|c| (e.executed_at, e.id) < (c.executed_at, c.id)))
+            .cloned()
+            .collect();
+        page.sort_by_key(|e| std::cmp::Reverse((e.executed_at, e.id)));
+        page.truncate(limit.max(0) as usize);
+        Ok(page)
+    }
+
+    fn stream_executions(
+        &self,
+        since: Option<DateTime<Utc>>,
+    ) -> BoxStream<'_, sqlx::Result<Execution>> {
+        let rows = match self.begin() {
+            Ok(state) => {
+                let mut rows: Vec<Execution> = state
+                    .executions
+                    .iter()
+                    .filter(|e| since.is_none_or(|since| e.executed_at >= since))
+                    .cloned()
+                    .collect();
+                rows.sort_by_key(|e| (e.executed_at, e.id));
+                rows.into_iter().map(Ok).collect()
+            }
+            Err(error) => vec![Err(error)],
+        };
+        stream::iter(rows).boxed()
+    }
+
+    async fn begin_executions(
+        &self,
+        executions: &[Execution],
+        claim: &Claim,
+    ) -> sqlx::Result<Vec<WriteOutcome>> {
+        let mut state = self.begin()?;
+        let State {
+            tasks,
+            executions: records,
+            claims,
+            ..
+        } = &mut *state;
+        let mut outcomes = Vec::with_capacity(executions.len());
+
+        for execution in executions {
+            let Some(task) = tasks.iter_mut().find(|t| t.id == execution.task_id) else {
+                outcomes.push(WriteOutcome::TaskMissing);
+                continue;
+            };
+            let held = task.run_state == RunState::Running
+                && claims.get(&task.id).is_some_and(|held| {
+                    held.expires_at >= execution.executed_at
+                        || records.iter().any(|e| {
+                            e.task_id == task.id
+                                && e.status == ExecutionStatus::Running
+                                && e.id != execution.id
+                        })
+                });
+            if held {
+                outcomes.push(WriteOutcome::AlreadyRunning);
+                continue;
+            }
+            task.run_state = RunState::Running;
+            claims.insert(task.id, claim.clone());
+            upsert_execution(records, execution);
+            outcomes.push(WriteOutcome::Recorded);
+        }
+
+        Ok(outcomes)
+    }
+
+    async fn renew_claims(&self, task_ids: &[Uuid], claim: &Claim) -> sqlx::Result<u64> {
+        let mut state = self.begin()?;
+        let State { tasks, claims, .. } = &mut *state;
+        let mut renewed = 0;
+        for task in tasks.iter().filter(|t| task_ids.contains(&t.id)) {
+            if let Some(held) = claims.get_mut(&task.id)
+                && task.run_state == RunState::Running
+                && held.owner == claim.owner
+            {
+                held.expires_at = claim.expires_at;
+                renewed += 1;
+            }
+        }
+        Ok(renewed)
+    }
+
+    async fn get_execution(&self, id: Uuid) -> sqlx::Result<Option<Execution>> {
+        let state = self.begin()?;
+        Ok(state.executions.iter().find(|e| e.id == id).cloned())
+    }
+
+    async fn recent_durations(
+        &self,
+        task_id: Uuid,
+        exclude: Uuid,
+        limit: i64,
+    ) -> sqlx::Result<Vec<i64>> {
+        let state = self.begin()?;
+        let mut executions: Vec<&Execution> = state
+            .executions
+            .iter()
+            .filter(|e| {
+                e.task_id == task_id && e.id != exclude && e.status == ExecutionStatus::Success
+            })
+            .filter(|e| e.duration_ms.is_some())
+            .collect();
+        executions.sort_by_key(|e| std::cmp::Reverse((e.executed_at, e.id)));
+        Ok(executions
+            .into_iter()
+            .take(limit.max(0) as usize)
+            .filter_map(|e| e.duration_ms)
+            .collect())
+    }
+
+    async fn last_success(&self, task_id: Uuid) -> sqlx::Result<Option<Execution>> {
+        let state = self.begin()?;
+        Ok(state
+            .executions
+            .iter()
+            .filter(|e| e.task_id == task_id && e.status == ExecutionStatus::Success)
+            .max_by_key(|e| e.executed_at)
+            .cloned())
+    }
+
+    async fn get_dependents(&self, task_id: Uuid) -> sqlx::Result<Vec<Task>> {
+        let state = self.begin()?;
+        Ok(state
+            .tasks
+            .iter()
+            .filter(|t| t.depends_on == Some(task_id) && t.deleted_at.is_none())
+            .cloned()
+            .collect())
+    }
+
+    async fn running_executions(&self) -> sqlx::Result<Vec<Execution>> {
+        let state = self.begin()?;
+        let mut running: Vec<Execution> = state
+            .executions
+            .iter()
+            .filter(|e| e.status == ExecutionStatus::Running)
+            .cloned()
+            .collect();
+        running.sort_by_key(|e| (e.executed_at, e.id));
+        Ok(running)
+    }
+
+    async fn orphaned_executions(
+        &self,
+        owner: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> sqlx::Result<Vec<Execution>> {
+        let state = self.begin()?;
+        let abandoned = |task_id: Uuid| {
+            let Some(task) = state.tasks.iter().find(|t| t.id == task_id) else {
+                return false;
+            };
+            task.run_state != RunState::Running
+                || state.claims.get(&task_id).is_none_or(|claim| {
+                    claim.expires_at < now || Some(claim.owner.as_str()) == owner
+                })
+        };
+        let mut orphaned: Vec<Execution> = state
+            .executions
+            .iter()
+            .filter(|e| e.status == ExecutionStatus::Running && abandoned(e.task_id))
+            .cloned()
+            .collect();
+        orphaned.sort_by_key(|e| (e.executed_at, e.id));
+        Ok(orphaned)
+    }
+
+    async fn prune_executions(
+        &self,
+        now: DateTime<Utc>,
+        default_keep_days: Option<i64>,
+        limit: i64,
+    ) -> sqlx::Result<u64> {
+        let mut state = self.begin()?;
+        let expired = state.expired_executions(now, default_keep_days, limit);
+        state.remove_executions(&expired);
+        Ok(expired.len() as u64)
+    }
+
+    async fn expired_executions(
+        &self,
+        now: DateTime<Utc>,
+        default_keep_days: Option<i64>,
+        limit: i64,
+    ) -> sqlx::Result<Vec<Execution>> {
+        let state = self.begin()?;
+        let expired = state.expired_executions(now, default_keep_days, limit);
+        Ok(expired
+            .iter()
+            .filter_map(|id| state.executions.iter().find(|e| e.id == *id).cloned())
+            .collect())
+    }
+
+    async fn delete_executions(&self, ids: &[Uuid]) -> sqlx::Result<u64> {
+        let mut state = self.begin()?;
+        let before = state.executions.len();
+        state.remove_executions(ids);
+        Ok((before - state.executions.len()) as u64)
+    }
+
+    async fn record_executions(
+        &self,
+        writes: &[ExecutionWrite],
+    ) -> sqlx::Result<Vec<WriteOutcome>> {
+        let mut state = self.begin()?;
+        let mut outcomes = Vec::with_capacity(writes.len());
+
+        for write in writes {
+            let Some(task) = state
+                .tasks
+                .iter_mut()
+                .find(|t| t.id == write.execution.task_id)
+            else {
+                outcomes.push(WriteOutcome::TaskMissing);
+                continue;
+            };
+
+            match write.follow_up {
+                FollowUp::Delete => task.deleted_at = Some(write.execution.executed_at),
+                FollowUp::Reschedule(next_trigger) => {
+                    task.trigger_at = next_trigger;
+                    task.retry_attempt = 0;
+                    task.deferred_from = None;
+                }
+                FollowUp::Retry { at, attempt } => {
+                    task.trigger_at = at;
+                    task.retry_attempt = attempt;
+                }
+                FollowUp::Keep => {}
+            }
+            task.run_state = write.run_state();
+            if write.finishes_run() {
+                task.run_count += 1;
+            }
+            upsert_execution(&mut state.executions, &write.execution);
+            outcomes.push(WriteOutcome::Recorded);
+        }
+
+        Ok(outcomes)
+    }
+
+    async fn create_api_key(&self, key: &ApiKey, key_hash: &str) -> sqlx::Result<()> {
+        let mut state = self.begin()?;
+        state.api_keys.push((key.clone(), key_hash.to_string()));
+        Ok(())
+    }
+
+    async fn find_api_key(&self, key_hash: &str) -> sqlx::Result<Option<ApiKey>> {
+        let state = self.begin()?;
+        Ok(state
+            .api_keys
+            .iter()
+            .find(|(key, hash)| hash == key_hash && key.revoked_at.is_none())
+            .map(|(key, _)| key.clone()))
+    }
+
+    async fn list_api_keys(&self) -> sqlx::Result<Vec<ApiKey>> {
+        let state = self.begin()?;
+        Ok(state.api_keys.iter().map(|(key, _)| key.clone()).collect())
+    }
+
+    async fn update_api_key_scope(&self, id: Uuid, scope: Scope) -> sqlx::Result<u64> {
+        let mut state = self.begin()?;
+        match state
+            .api_keys
+            .iter_mut()
+            .find(|(key, _)| key.id == id && key.revoked_at.is_none())
+        {
+            Some((key, _)) => {
+                key.scope = scope;
+                Ok(1)
+            }
+            None => Ok(0),
+        }
+    }
+
+    async fn revoke_api_key(&self, id: Uuid, at: DateTime<Utc>) -> sqlx::Result<u64> {
+        let mut state = self.begin()?;
+        match state
+            .api_keys
+            .iter_mut()
+            .find(|(key, _)| key.id == id && key.revoked_at.is_none())
+        {
+            Some((key, _)) => {
+                key.revoked_at = Some(at);
+                Ok(1)
+            }
+            None => Ok(0),
+        }
+    }
+
+    async fn put_secret(&self, secret: &StoredSecret) -> sqlx::Result<()> {
+        let mut state = self.begin()?;
+        match state.secrets.iter_mut().find(|s| s.name == secret.name) {
+            Some(stored) => {
+                stored.ciphertext = secret.ciphertext.clone();
+                stored.updated_at = secret.updated_at;
+            }
+            None => {
+                state.secrets.push(secret.clone());
+                state.secrets.sort_by(|a, b| a.name.cmp(&b.name));
+            }
+        }
+        Ok(())
+    }
+
+    async fn get_secret(&self, name: &str) -> sqlx::Result<Option<StoredSecret>> {
+        let state = self.begin()?;
+        Ok(state.secrets.iter().find(|s| s.name == name).cloned())
+    }
+
+    async fn list_secrets(&self) -> sqlx::Result<Vec<StoredSecret>> {
+        let state = self.begin()?;
+        Ok(state.secrets.clone())
+    }
+
+    async fn delete_secret(&self, name: &str) -> sqlx::Result<u64> {
+        let mut state = self.begin()?;
+        let before = state.secrets.len();
+        state.secrets.retain(|s| s.name != name);
+        Ok((before - state.secrets.len()) as u64)
+    }
+
+    async fn acquire_lease(
+        &self,
+        name: &str,
+        holder: &str,
+        now: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> sqlx::Result<bool> {
+        let mut state = self.begin()?;
+        let taken = state
+            .leases
+            .get(name)
+            .is_some_and(|(current, until)| current != holder && *until >= now);
+        if !taken {
+            state
+                .leases
+                .insert(name.to_string(), (holder.to_string(), expires_at));
+        }
+        Ok(!taken)
+    }
+
+    async fn release_lease(&self, name: &str, holder: &str) -> sqlx::Result<u64> {
+        let mut state = self.begin()?;
+        match state.leases.get(name) {
+            Some((current, _)) if current == holder => {
+                state.leases.remove(name);
+                Ok(1)
+            }
+            _ => Ok(0),
+        }
+    }
+
+    async fn create_trigger_link(&self, link: &TriggerLink) -> sqlx::Result<()> {
+        let mut state = self.begin()?;
+        state.trigger_links.push(link.clone());
+        Ok(())
+    }
+
+    async fn get_trigger_link(&self, id: Uuid) -> sqlx::Result<Option<TriggerLink>> {
+        let state = self.begin()?;
+        Ok(state.trigger_links.iter().find(|l| l.id == id).cloned())
+    }
+
+    async fn redeem_trigger_link(&self, id: Uuid, now: DateTime<Utc>) -> sqlx::Result<bool> {
+        let mut state = self.begin()?;
+        match state
+            .trigger_links
+            .iter_mut()
+            .find(|l| l.id == id && l.used_at.is_none() && l.expires_at > now)
+        {
+            Some(link) => {
+                link.used_at = Some(now);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn create_workflow(&self, workflow: &Workflow) -> sqlx::Result<()> {
+        let mut state = self.begin()?;
+        state.workflows.push(workflow.clone());
+        Ok(())
+    }
+
+    async fn get_workflow(&self, id: Uuid) -> sqlx::Result<Option<Workflow>> {
+        let state = self.begin()?;
+        Ok(state.workflows.iter().find(|w| w.id == id).cloned())
+    }
+
+    async fn list_workflows(&self) -> sqlx::Result<Vec<Workflow>> {
+        let state = self.begin()?;
+        let mut workflows = state.workflows.clone();
+        workflows.sort_by(|a, b| (&a.name, a.id).cmp(&(&b.name, b.id)));
+        Ok(workflows)
+    }
+
+    async fn create_workflow_run(&self, run: &WorkflowRun) -> sqlx::Result<()> {
+        let mut state = self.begin()?;
+        state.workflow_runs.push(run.clone());
+        Ok(())
+    }
+
+    async fn get_workflow_run(&self, id: Uuid) -> sqlx::Result<Option<WorkflowRun>> {
+        let state = self.begin()?;
+        Ok(state.workflow_runs.iter().find(|r| r.id == id).cloned())
+    }
+
+    async fn list_workflow_runs(
+        &self,
+        workflow_id: Uuid,
+        limit: i64,
+    ) -> sqlx::Result<Vec<WorkflowRun>> {
+        let state = self.begin()?;
+        Ok(state
+            .workflow_runs
+            .iter()
+            .rev()
+            .filter(|r| r.workflow_id == workflow_id)
+            .take(limit.max(0) as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn unfinished_workflow_runs(&self) -> sqlx::Result<Vec<WorkflowRun>> {
+        let state = self.begin()?;
+        Ok(state
+            .workflow_runs
+            .iter()
+            .filter(|r| r.status == WorkflowRunStatus::Running)
+            .cloned()
+            .collect())
+    }
+
+    async fn update_workflow_run(&self, run: &WorkflowRun) -> sqlx::Result<u64> {
+        let mut state = self.begin()?;
+        match state.workflow_runs.iter_mut().find(|r| r.id == run.id) {
+            Some(stored) => {
+                *stored = run.clone();
+                Ok(1)
+            }
+            None => Ok(0),
+        }
+    }
+
+    async fn create_group(&self, group: &TaskGroup) -> sqlx::Result<()> {
+        let mut state = self.begin()?;
+        state.groups.push(group.clone());
+        Ok(())
+    }
+
+    async fn get_group(&self, id: Uuid) -> sqlx::Result<Option<TaskGroup>> {
+        let state = self.begin()?;
+        Ok(state.groups.iter().find(|g| g.id == id).cloned())
+    }
+
+    async fn list_groups(&self) -> sqlx::Result<Vec<TaskGroup>> {
+        let state = self.begin()?;
+        let mut groups = state.groups.clone();
+        groups.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(groups)
+    }
+
+    async fn set_group_paused(
+        &self,
+        id: Uuid,
+        paused_at: Option<DateTime<Utc>>,
+    ) -> sqlx::Result<u64> {
+        let mut state = self.begin()?;
+        let Some(group) = state.groups.iter_mut().find(|g| g.id == id) else {
+            return Ok(0);
+        };
+        group.paused_at = paused_at.map(|at| group.paused_at.unwrap_or(at));
+
+        let events: Vec<ScheduleEvent> = state
+            .tasks
+            .iter()
+            .filter(|t| t.group_id == Some(id) && t.deleted_at.is_none())
+            .map(|t| match paused_at {
+                Some(_) => ScheduleEvent::Removed { id: t.id },
+                None => ScheduleEvent::Upserted {
+                    id: t.id,
+                    trigger_at: t.trigger_at,
+                },
+            })
+            .collect();
+        state.outbox.extend(events);
+        Ok(1)
+    }
+
+    async fn reschedule_task(&self, id: Uuid, trigger_at: DateTime<Utc>) -> sqlx::Result<u64> {
+        let mut state = self.begin()?;
+        let Some(task) = state.tasks.iter_mut().find(|t| t.id == id) else {
+            return Ok(0);
+        };
+        task.trigger_at = trigger_at;
+        task.retry_attempt = 0;
+        task.deferred_from = None;
+        state
+            .outbox
+            .push_back(ScheduleEvent::Upserted { id, trigger_at });
+        Ok(1)
+    }
+
+    async fn defer_task(
+        &self,
+        id: Uuid,
+        until: DateTime<Utc>,
+        deferred_from: DateTime<Utc>,
+    ) -> sqlx::Result<u64> {
+        let mut state = self.begin()?;
+        let Some(task) = state
+            .tasks
+            .iter_mut()
+            .find(|t| t.id == id && t.deleted_at.is_none())
+        else {
+            return Ok(0);
+        };
+        task.trigger_at = until;
+        task.deferred_from.get_or_insert(deferred_from);
+        state.outbox.push_back(ScheduleEvent::Upserted {
+            id,
+            trigger_at: until,
+        });
+        Ok(1)
+    }
+
+    async fn update_task(&self, task: &Task) -> sqlx::Result<u64> {
+        let mut state = self.begin()?;
+        let Some(stored) = state
+            .tasks
+            .iter_mut()
+            .find(|t| t.id == task.id && t.deleted_at.is_none())
+        else {
+            return Ok(0);
+        };
+        stored.name = task.name.clone();
+        stored.trigger_at = task.trigger_at;
+        stored.interval_seconds = task.interval_seconds;
+        stored.cron_expr = task.cron_expr.clone();
+        stored.payload = task.payload.clone();
+        stored.retry_attempt = 0;
+        state.outbox.push_back(ScheduleEvent::Upserted {
+            id: task.id,
+            trigger_at: task.trigger_at,
+        });
+        Ok(1)
+    }
+
+    async fn patch_task(&self, id: Uuid, patch: &TaskPatch) -> sqlx::Result<u64> {
+        let mut state = self.begin()?;
+        let Some(stored) = state
+            .tasks
+            .iter_mut()
+            .find(|t| t.id == id && t.deleted_at.is_none())
+        else {
+            return Ok(0);
+        };
+        *stored = patch.apply(stored);
+        let trigger_at = stored.trigger_at;
+        state
+            .outbox
+            .push_back(ScheduleEvent::Upserted { id, trigger_at });
+        Ok(1)
+    }
+
+    async fn set_tasks_paused(
+        &self,
+        selector: &TaskSelector,
+        paused_at: Option<DateTime<Utc>>,
+    ) -> sqlx::Result<u64> {
+        let mut state = self.begin()?;
+        let mut events = Vec::new();
+        for task in state.tasks.iter_mut().filter(|t| {
+            t.deleted_at.is_none()
+                && t.paused_at.is_some() != paused_at.is_some()
+                && selector.matches(t.id, t.labels.as_ref(), t.group_id)
+        }) {
+            task.paused_at = paused_at;
+            events.push(match paused_at {
+                Some(_) => ScheduleEvent::Removed { id: task.id },
+                None => ScheduleEvent::Upserted {
+                    id: task.id,
+                    trigger_at: task.trigger_at,
+                },
+            });
+        }
+
+        let affected = events.len() as u64;
+        state.outbox.extend(events);
+        Ok(affected)
+    }
+
+    async fn group_stats(&self, id: Uuid) -> sqlx::Result<GroupStats> {
+        let state = self.begin()?;
+        let task_ids: Vec<Uuid> = state
+            .tasks
+            .iter()
+            .filter(|t| t.group_id == Some(id))
+            .map(|t| t.id)
+            .collect();
+        let executions: Vec<&Execution> = state
+            .executions
+            .iter()
+            .filter(|e| task_ids.contains(&e.task_id))
+            .collect();
+
+        Ok(GroupStats {
+            task_count: state
+                .tasks
+                .iter()
+                .filter(|t| t.group_id == Some(id) && t.deleted_at.is_none())
+                .count() as i64,
+            execution_count: executions.len() as i64,
+            success_count: executions
+                .iter()
+                .filter(|e| e.status == ExecutionStatus::Success)
+                .count() as i64,
+            failure_count: executions
+                .iter()
+                .filter(|e| {
+                    matches!(
+                        e.status,
+                        ExecutionStatus::Failure | ExecutionStatus::Interrupted
+                    )
+                })
+                .count() as i64,
+            last_executed_at: executions.iter().map(|e| e.executed_at).max(),
+        })
+    }
+
+    async fn export_snapshot(
+        &self,
+        taken_at: DateTime<Utc>,
+        include_executions: bool,
+    ) -> sqlx::Result<Snapshot> {
+        let state = self.begin()?;
+        let mut groups = state.groups.clone();
+        groups.sort_by(|a, b| a.name.cmp(&b.name));
+        let executions = include_executions.then(|| {
+            let mut executions = state.executions.clone();
+            executions.sort_by_key(|e| (e.executed_at, e.id));
+            executions
+        });
+
+        let mut workflows = state.workflows.clone();
+        workflows.sort_by(|a, b| (&a.name, a.id).cmp(&(&b.name, b.id)));
+
+        Ok(Snapshot {
+            version: SNAPSHOT_VERSION,
+            taken_at,
+            groups,
+            tasks: state.tasks.iter().rev().cloned().collect(),
+            workflows,
+            executions,
+        })
+    }
+
+    async fn restore_snapshot(&self, snapshot: &Snapshot) -> sqlx::Result<bool> {
+        let mut state = self.begin()?;
+        if !state.tasks.is_empty() || !state.groups.is_empty() || !state.workflows.is_empty() {
+            return Ok(false);
+        }
+
+        state.groups = snapshot.groups.clone();
+        state.tasks = snapshot.tasks.clone();
+        for task in &snapshot.tasks {
+            if restored_task_is_scheduled(snapshot, task) {
+                state.outbox.push_back(ScheduleEvent::Upserted {
+                    id: task.id,
+                    trigger_at: task.trigger_at,
+                });
+            }
+        }
+        state.workflows = snapshot.workflows.clone();
+        state.executions = snapshot.executions.clone().unwrap_or_default();
+        Ok(true)
+    }
+}
@@ -1,9 +1,25 @@
+use crate::config::Config;
+use crate::db::store::{SqliteTaskStore, TaskStore};
+use crate::errors::AppError;
 use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions};
 use sqlx::{Pool, Sqlite};
 use std::str::FromStr;
+use std::sync::Arc;
 use std::time::Duration;
 
+pub mod api_keys;
+pub mod groups;
+pub mod leases;
+#[cfg(any(test, feature = "test-util"))]
+pub mod memory;
+#[cfg(feature = "postgres")]
+pub mod postgres;
 pub mod queries;
+pub mod retry;
+pub mod secrets;
+pub mod store;
+pub mod trigger_links;
+pub mod workflows;
 
 #[cfg(test)]
 mod tests;
@@ -38,3 +54,77 @@ pub async fn init_pool(database_url: &str) -> Pool<Sqlite> {
 
     pool
 }
+
+/// Build SQLite connection options from the configured tuning parameters.
+///
+/// # Arguments
+///
+/// * `config` - The application configuration.
+///
+/// # Returns
+/// * `Result<SqliteConnectOptions, AppError>` - The connection options, or a configuration error
+///   if the database URL is invalid.
+pub fn connect_options(config: &Config) -> Result<SqliteConnectOptions, AppError> {
+    let options = SqliteConnectOptions::from_str(&config.db_url)
+        .map_err(|e| AppError::Config(format!("Invalid DATABASE_URL: {}", e)))?
+        .create_if_missing(true)
+        .journal_mode(SqliteJournalMode::Wal)
+        .foreign_keys(true)
+        .synchronous(config.sqlite_synchronous)
+        .busy_timeout(Duration::from_millis(config.sqlite_busy_timeout_ms))
+        .pragma(
+            "wal_autocheckpoint",
+            config.sqlite_wal_autocheckpoint.to_string(),
+        )
+        .pragma("mmap_size", config.sqlite_mmap_size.to_string());
+
+    Ok(options)
+}
+
+/// True if `database_url` points at a PostgreSQL server rather than a SQLite file.
+pub fn is_postgres_url(database_url: &str) -> bool {
+    database_url.starts_with("postgres://") || database_url.starts_with("postgresql://")
+}
+
+/// Connects to the database `DATABASE_URL` points at: PostgreSQL for `postgres://` URLs,
+/// SQLite otherwise. The schema is migrated unless the instance is a read-only follower,
+/// which must not change it underneath the writer.
+///
+/// # Arguments
+///
+/// * `config` - The application configuration.
+///
+/// # Errors
+///
+/// * Returns 'AppError::Config' if the URL is invalid, or points at PostgreSQL in a build
+///   without the `postgres` feature.
+/// * Returns 'AppError::Database' if the database cannot be reached or migrated.
+pub async fn open_store(config: &Config) -> Result<Arc<dyn TaskStore>, AppError> {
+    if is_postgres_url(&config.db_url) {
+        #[cfg(feature = "postgres")]
+        {
+            let pool = postgres::connect(config).await?;
+            if !config.follower {
+                postgres::migrate(&pool).await?;
+            }
+            return Ok(Arc::new(postgres::PostgresTaskStore::new(pool)));
+        }
+        #[cfg(not(feature = "postgres"))]
+        return Err(AppError::Config(
+            "DATABASE_URL points at PostgreSQL, but this build lacks the `postgres` feature".into(),
+        ));
+    }
+
+    let pool = SqlitePoolOptions::new()
+        .max_connections(5)
+        .connect_with(connect_options(config)?)
+        .await?;
+    if !config.follower {
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .map_err(|e| AppError::Database(e.into()))?;
+    }
+
+    Ok(Arc::new(SqliteTaskStore::new(pool)))
+}
@@ -0,0 +1,1581 @@
+//! [`TaskStore`] backed by PostgreSQL, available with the `postgres` feature.
+//!
+//! It keeps the SQLite store's semantics (soft deletes, the schedule outbox, savepoints
+//! around each execution write) on the schema in `migrations/postgres`.
+use crate::config::Config;
+use crate::db::store::{
+    Claim, ExecutionWrite, FollowUp, TaskStore, WriteOutcome, restored_task_is_scheduled,
+};
+use crate::domain::{
+    ApiKey, Execution, ExecutionCursor, ExecutionSummary, GroupStats, Labels, RunState,
+    SNAPSHOT_VERSION, Scope, Snapshot, StoredSecret, Task, TaskFilter, TaskGroup, TaskPatch,
+    TaskRuns, TaskSelector, TaskState, TaskSummary, TriggerLink, Workflow, WorkflowRun,
+};
+use crate::errors::AppError;
+use crate::scheduler::ScheduleEvent;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::stream::BoxStream;
+use sqlx::postgres::{PgConnectOptions, PgPool, PgPoolOptions};
+use sqlx::{Acquire, Executor, Postgres, Row, types::Json};
+use std::str::FromStr;
+use uuid::Uuid;
+
+#[cfg(test)]
+mod tests;
+
+/// Columns of a [`Task`], in the order of [`TaskStore::create_task`]'s insert.
+const TASK_COLUMNS: &str = r#"
+    id, name, task_type, trigger_at, interval_seconds, payload, deleted_at, request_id,
+    keep_executions, keep_days, signing_secret, previous_signing_secret,
+    previous_secret_expires_at, api_key_id, notifications, group_id, labels, paused_at,
+    cron_expr, retry_policy, retry_attempt, priority, run_state, misfire_policy,
+    interval_mode, ends_at, max_executions, run_count, timezone, rrule, blackout_windows,
+    deferred_from, execution_windows, depends_on, next_task_id,
+    run_if
+"#;
+
+const WORKFLOW_RUN_COLUMNS: &str = "id, workflow_id, status, nodes, started_at, finished_at";
+
+const EXECUTION_COLUMNS: &str = "id, task_id, executed_at, output, status, payload, replay_of, \
+    duration_ms, deferred_from, lag_ms, scheduled_for, started_at, finished_at, attempt, \
+    failure_kind";
+
+/// IDs of up to `$3` finished executions outside their task's retention settings at `$1`,
+/// oldest first, with `$2` as the `keep_days` of tasks without their own.
+const EXPIRED_EXECUTION_IDS: &str = r#"
+    SELECT id FROM (
+        SELECT e.id, e.executed_at, t.keep_executions,
+            COALESCE(t.keep_days, $2) AS keep_days,
+            ROW_NUMBER() OVER (
+                PARTITION BY e.task_id ORDER BY e.executed_at DESC, e.id DESC
+            ) AS recency
+        FROM executions e
+        JOIN tasks t ON t.id = e.task_id
+        WHERE e.status != 'running'
+          AND (t.keep_executions IS NOT NULL OR COALESCE(t.keep_days, $2) IS NOT NULL)
+    ) ranked
+    WHERE recency > keep_executions
+       OR executed_at < $1 - keep_days * INTERVAL '1 day'
+    ORDER BY executed_at, id
+    LIMIT $3
+"#;
+
+/// `TaskRuns` columns of a task aliased `t`; needs [`LAST_RUN_JOIN`].
+const TASK_RUNS_COLUMNS: &str = r#"
+    CASE WHEN t.deleted_at IS NULL AND t.paused_at IS NULL AND NOT EXISTS (
+        SELECT 1 FROM task_groups g WHERE g.id = t.group_id AND g.paused_at IS NOT NULL
+    ) THEN t.trigger_at END AS next_run_at,
+    last_run.executed_at AS last_run_at,
+    last_run.status AS last_status
+"#;
+
+/// Joins the latest finished execution of a task aliased `t` as `last_run`.
+const LAST_RUN_JOIN: &str = r#"
+    LEFT JOIN LATERAL (
+        SELECT e.executed_at, e.status FROM executions e
+        WHERE e.task_id = t.id AND e.status != 'running'
+        ORDER BY e.executed_at DESC, e.id DESC
+        LIMIT 1
+    ) last_run ON TRUE
+"#;
+
+/// Live tasks that the scheduler may dispatch: neither they nor their group are paused.
+const SCHEDULABLE: &str = r#"
+    deleted_at IS NULL AND paused_at IS NULL
+    AND (group_id IS NULL OR group_id NOT IN (
+        SELECT id FROM task_groups WHERE paused_at IS NOT NULL
+    ))
+"#;
+
+/// Opens a PostgreSQL connection pool for the configured `DATABASE_URL`.
+///
+/// # Arguments
+///
+/// * `config` - The application configuration.
+///
+/// # Errors
+///
+/// * Returns 'AppError::Config' if the database URL is invalid.
+/// * Returns 'AppError::Database' if the server cannot be reached.
+pub async fn connect(config: &Config) -> Result<PgPool, AppError> {
+    let options = PgConnectOptions::from_str(&config.db_url)
+        .map_err(|e| AppError::Config(format!("Invalid DATABASE_URL: {}", e)))?;
+
+    Ok(PgPoolOptions::new()
+        .max_connections(10)
+        .connect_with(options)
+        .await?)
+}
+
+/// Applies the PostgreSQL migrations.
+///
+/// # Errors
+///
+/// * Returns 'AppError::Database' if a migration fails.
+pub async fn migrate(pool: &PgPool) -> Result<(), AppError> {
+    sqlx::migrate!("./migrations/postgres")
+        .run(pool)
+        .await
+        .map_err(|e| AppError::Database(e.into()))
+}
+
+/// [`TaskStore`] backed by a PostgreSQL pool.
+///
+/// Like [`SqliteTaskStore`](crate::db::store::SqliteTaskStore), it leaves retries to the
+/// caller.
+#[derive(Debug, Clone)]
+pub struct PostgresTaskStore {
+    pool: PgPool,
+}
+
+impl PostgresTaskStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+}
+
+async fn insert_task<'c, E>(executor: E, task: &Task) -> sqlx::Result<()>
+where
+    E: Executor<'c, Database = Postgres>,
+{
+    sqlx::query(&format!(
+        r#"
+        INSERT INTO tasks ({})
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18,
+            $19, $20, $21, $22, $23, $24, $25, $26, $27, $28, $29, $30, $31, $32,
+            $33, $34, $35, $36)
+        "#,
+        TASK_COLUMNS
+    ))
+    .bind(task.id)
+    .bind(&task.name)
+    .bind(task.task_type.clone())
+    .bind(task.trigger_at)
+    .bind(task.interval_seconds)
+    .bind(Json(&task.payload))
+    .bind(task.deleted_at)
+    .bind(&task.request_id)
+    .bind(task.keep_executions)
+    .bind(task.keep_days)
+    .bind(&task.signing_secret)
+    .bind(&task.previous_signing_secret)
+    .bind(task.previous_secret_expires_at)
+    .bind(task.api_key_id)
+    .bind(task.notifications.as_ref().map(Json))
+    .bind(task.group_id)
+    .bind(task.labels.as_ref().map(Json))
+    .bind(task.paused_at)
+    .bind(&task.cron_expr)
+    .bind(task.retry_policy.as_ref().map(Json))
+    .bind(task.retry_attempt)
+    .bind(task.priority)
+    .bind(task.run_state)
+    .bind(task.misfire_policy)
+    .bind(task.interval_mode)
+    .bind(task.ends_at)
+    .bind(task.max_executions)
+    .bind(task.run_count)
+    .bind(&task.timezone)
+    .bind(&task.rrule)
+    .bind(task.blackout_windows.as_ref().map(Json))
+    .bind(task.deferred_from)
+    .bind(task.execution_windows.as_ref().map(Json))
+    .bind(task.depends_on)
+    .bind(task.next_task_id)
+    .bind(task.run_if.as_ref().map(Json))
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Inserts an execution, or finalizes the `running` record of the same ID.
+async fn upsert_execution<'c, E>(executor: E, exec: &Execution) -> sqlx::Result<()>
+where
+    E: Executor<'c, Database = Postgres>,
+{
+    sqlx::query(&format!(
+        r#"
+        INSERT INTO executions ({})
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+        ON CONFLICT (id) DO UPDATE SET
+            executed_at = excluded.executed_at,
+            output = excluded.output,
+            status = excluded.status,
+            payload = excluded.payload,
+            replay_of = excluded.replay_of,
+            duration_ms = excluded.duration_ms,
+            deferred_from = excluded.deferred_from,
+            lag_ms = excluded.lag_ms,
+            scheduled_for = excluded.scheduled_for,
+            started_at = excluded.started_at,
+            finished_at = excluded.finished_at,
+            attempt = excluded.attempt,
+            failure_kind = excluded.failure_kind
+        "#,
+        EXECUTION_COLUMNS
+    ))
+    .bind(exec.id)
+    .bind(exec.task_id)
+    .bind(exec.executed_at)
+    .bind(Json(&exec.output))
+    .bind(exec.status.clone())
+    .bind(exec.payload.as_ref().map(Json))
+    .bind(exec.replay_of)
+    .bind(exec.duration_ms)
+    .bind(exec.deferred_from)
+    .bind(exec.lag_ms)
+    .bind(exec.scheduled_for)
+    .bind(exec.started_at)
+    .bind(exec.finished_at)
+    .bind(exec.attempt)
+    .bind(exec.failure_kind)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+async fn enqueue<'c, E>(executor: E, event: &ScheduleEvent) -> sqlx::Result<()>
+where
+    E: Executor<'c, Database = Postgres>,
+{
+    let (task_id, trigger_at) = match event {
+        ScheduleEvent::Upserted { id, trigger_at } => (*id, Some(*trigger_at)),
+        ScheduleEvent::Removed { id } => (*id, None),
+    };
+
+    sqlx::query("INSERT INTO schedule_outbox (task_id, trigger_at) VALUES ($1, $2)")
+        .bind(task_id)
+        .bind(trigger_at)
+        .execute(executor)
+        .await?;
+
+    Ok(())
+}
+
+async fn soft_delete<'c, E>(executor: E, id: Uuid) -> sqlx::Result<u64>
+where
+    E: Executor<'c, Database = Postgres>,
+{
+    let result = sqlx::query("UPDATE tasks SET deleted_at = $1 WHERE id = $2")
+        .bind(Utc::now())
+        .bind(id)
+        .execute(executor)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+async fn update_trigger<'c, E>(
+    executor: E,
+    id: Uuid,
+    trigger_at: DateTime<Utc>,
+) -> sqlx::Result<u64>
+where
+    E: Executor<'c, Database = Postgres>,
+{
+    let result = sqlx::query(
+        "UPDATE tasks SET trigger_at = $1, retry_attempt = 0, deferred_from = NULL WHERE id = $2",
+    )
+    .bind(trigger_at)
+    .bind(id)
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+async fn set_run_state<'c, E>(executor: E, id: Uuid, run_state: RunState) -> sqlx::Result<u64>
+where
+    E: Executor<'c, Database = Postgres>,
+{
+    let result = sqlx::query("UPDATE tasks SET run_state = $1 WHERE id = $2")
+        .bind(run_state)
+        .bind(id)
+        .execute(executor)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+#[async_trait]
+impl TaskStore for PostgresTaskStore {
+    async fn create_task(&self, task: &Task) -> sqlx::Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        insert_task(&mut *tx, task).await?;
+        let event = ScheduleEvent::Upserted {
+            id: task.id,
+            trigger_at: task.trigger_at,
+        };
+        enqueue(&mut *tx, &event).await?;
+
+        tx.commit().await
+    }
+
+    async fn get_task(&self, id: Uuid) -> sqlx::Result<Option<Task>> {
+        sqlx::query_as::<_, Task>(&format!("SELECT {} FROM tasks WHERE id = $1", TASK_COLUMNS))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    async fn delete_task(&self, id: Uuid) -> sqlx::Result<u64> {
+        let mut tx = self.pool.begin().await?;
+
+        let rows = soft_delete(&mut *tx, id).await?;
+        if rows > 0 {
+            enqueue(&mut *tx, &ScheduleEvent::Removed { id }).await?;
+        }
+
+        tx.commit().await?;
+        Ok(rows)
+    }
+
+    async fn purge_task(&self, id: Uuid) -> sqlx::Result<u64> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM executions WHERE task_id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        let rows = sqlx::query("DELETE FROM tasks WHERE id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        if rows > 0 {
+            enqueue(&mut *tx, &ScheduleEvent::Removed { id }).await?;
+        }
+
+        tx.commit().await?;
+        Ok(rows)
+    }
+
+    async fn purge_deleted_tasks(&self, cutoff: DateTime<Utc>, limit: i64) -> sqlx::Result<u64> {
+        let mut tx = self.pool.begin().await?;
+
+        let ids: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            SELECT id FROM tasks
+            WHERE deleted_at IS NOT NULL AND deleted_at < $1
+            ORDER BY deleted_at
+            LIMIT $2
+            FOR UPDATE
+            "#,
+        )
+        .bind(cutoff)
+        .bind(limit)
+        .fetch_all(&mut *tx)
+        .await?;
+        sqlx::query("DELETE FROM executions WHERE task_id = ANY($1)")
+            .bind(&ids)
+            .execute(&mut *tx)
+            .await?;
+        let rows = sqlx::query("DELETE FROM tasks WHERE id = ANY($1)")
+            .bind(&ids)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        tx.commit().await?;
+        Ok(rows)
+    }
+
+    async fn deleted_task_ids(&self, cutoff: DateTime<Utc>, limit: i64) -> sqlx::Result<Vec<Uuid>> {
+        sqlx::query_scalar(
+            r#"
+            SELECT id FROM tasks
+            WHERE deleted_at IS NOT NULL AND deleted_at < $1
+            ORDER BY deleted_at
+            LIMIT $2
+            "#,
+        )
+        .bind(cutoff)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    async fn count_active_tasks(&self, api_key_id: Uuid) -> sqlx::Result<i64> {
+        sqlx::query_scalar(
+            "SELECT COUNT(*) FROM tasks WHERE api_key_id = $1 AND deleted_at IS NULL",
+        )
+        .bind(api_key_id)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    async fn rotate_signing_secret(
+        &self,
+        id: Uuid,
+        secret: &str,
+        previous_expires_at: DateTime<Utc>,
+    ) -> sqlx::Result<u64> {
+        let result = sqlx::query(
+            r#"
+            UPDATE tasks
+            SET previous_signing_secret = signing_secret,
+                previous_secret_expires_at = $1,
+                signing_secret = $2
+            WHERE id = $3 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(previous_expires_at)
+        .bind(secret)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Deletes exactly the rows it returns, so an event committed late by a concurrent
+    /// transaction (with a lower sequence number) is picked up by the next drain.
+    async fn drain_outbox(&self, limit: i64) -> sqlx::Result<Vec<ScheduleEvent>> {
+        let mut rows: Vec<(i64, Uuid, Option<DateTime<Utc>>)> = sqlx::query_as(
+            r#"
+            DELETE FROM schedule_outbox
+            WHERE seq IN (SELECT seq FROM schedule_outbox ORDER BY seq ASC LIMIT $1)
+            RETURNING seq, task_id, trigger_at
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.sort_by_key(|(seq, _, _)| *seq);
+
+        Ok(rows
+            .into_iter()
+            .map(|(_, id, trigger_at)| match trigger_at {
+                Some(trigger_at) => ScheduleEvent::Upserted { id, trigger_at },
+                None => ScheduleEvent::Removed { id },
+            })
+            .collect())
+    }
+
+    async fn get_due_tasks(&self, now: DateTime<Utc>, limit: i64) -> sqlx::Result<Vec<Task>> {
+        sqlx::query_as::<_, Task>(&format!(
+            r#"
+            SELECT {}
+            FROM (
+                SELECT *,
+                    ROW_NUMBER() OVER (
+                        PARTITION BY priority, task_type ORDER BY trigger_at
+                    ) AS lane_rank
+                FROM tasks
+                WHERE trigger_at <= $1 AND {}
+            ) due
+            ORDER BY priority DESC, lane_rank ASC, trigger_at ASC
+            LIMIT $2
+            "#,
+            TASK_COLUMNS, SCHEDULABLE
+        ))
+        .bind(now)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    async fn count_due_tasks(&self, now: DateTime<Utc>) -> sqlx::Result<i64> {
+        sqlx::query_scalar(&format!(
+            "SELECT COUNT(*) FROM tasks WHERE trigger_at <= $1 AND {}",
+            SCHEDULABLE
+        ))
+        .bind(now)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    async fn get_upcoming_triggers(&self, limit: i64) -> sqlx::Result<Vec<(Uuid, DateTime<Utc>)>> {
+        sqlx::query_as(&format!(
+            r#"
+            SELECT id, trigger_at
+            FROM tasks
+            WHERE {}
+            ORDER BY trigger_at ASC
+            LIMIT $1
+            "#,
+            SCHEDULABLE
+        ))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    async fn get_task_summaries(&self, filter: &TaskFilter) -> sqlx::Result<Vec<TaskSummary>> {
+        let state = match filter.state {
+            None => "",
+            Some(TaskState::Active) => "AND t.deleted_at IS NULL",
+            Some(TaskState::Deleted) => "AND t.deleted_at IS NOT NULL",
+        };
+
+        sqlx::query_as::<_, TaskSummary>(&format!(
+            r#"
+            SELECT t.id, t.name, t.task_type, t.trigger_at, t.interval_seconds, t.cron_expr,
+                t.deleted_at, t.group_id, t.paused_at, t.run_state, {}
+            FROM tasks t
+            {}
+            WHERE ($1::uuid IS NULL OR t.group_id = $1) {}
+            ORDER BY t.created_at DESC
+            "#,
+            TASK_RUNS_COLUMNS, LAST_RUN_JOIN, state
+        ))
+        .bind(filter.group_id)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    async fn get_task_runs(&self, id: Uuid) -> sqlx::Result<TaskRuns> {
+        let runs = sqlx::query_as::<_, TaskRuns>(&format!(
+            "SELECT {} FROM tasks t {} WHERE t.id = $1",
+            TASK_RUNS_COLUMNS, LAST_RUN_JOIN
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(runs.unwrap_or_default())
+    }
+
+    async fn list_executions(
+        &self,
+        task_id: Uuid,
+        after: Option<&ExecutionCursor>,
+        limit: i64,
+    ) -> sqlx::Result<Vec<ExecutionSummary>> {
+        sqlx::query_as::<_, ExecutionSummary>(
+            r#"
+            SELECT id, task_id, executed_at, status
+            FROM executions
+            WHERE task_id = $1
+                AND ($2::timestamptz IS NULL OR (executed_at, id) < ($2, $3))
+            ORDER BY executed_at DESC, id DESC
+            LIMIT $4
+            "#,
+        )
+        .bind(task_id)
+        .bind(after.map(|c| c.executed_at))
+        .bind(after.map(|c| c.id))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    async fn list_execution_records(
+        &self,
+        task_id: Uuid,
+        after: Option<&ExecutionCursor>,
+        limit: i64,
+    ) -> sqlx::Result<Vec<Execution>> {
+        sqlx::query_as::<_, Execution>(&format!(
+            r#"
+            SELECT {}
+            FROM executions
+            WHERE task_id = $1
+                AND ($2::timestamptz IS NULL OR (executed_at, id) < ($2, $3))
+            ORDER BY executed_at DESC, id DESC
+            LIMIT $4
+            "#,
+            EXECUTION_COLUMNS
+        ))
+        .bind(task_id)
+        .bind(after.map(|c| c.executed_at))
+        .bind(after.map(|c| c.id))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    fn stream_executions(
+        &self,
+        since: Option<DateTime<Utc>>,
+    ) -> BoxStream<'_, sqlx::Result<Execution>> {
+        sqlx::query_as::<_, Execution>(
+            r#"
+            SELECT id, task_id, executed_at, output, status, payload, replay_of, duration_ms,
+                deferred_from, lag_ms, scheduled_for, started_at, finished_at,
+                attempt, failure_kind
+            FROM executions
+            WHERE $1::timestamptz IS NULL OR executed_at >= $1
+            ORDER BY executed_at ASC, id ASC
+            "#,
+        )
+        .bind(since)
+        .fetch(&self.pool)
+    }
+
+    async fn begin_executions(
+        &self,
+        executions: &[Execution],
+        claim: &Claim,
+    ) -> sqlx::Result<Vec<WriteOutcome>> {
+        let mut tx = self.pool.begin().await?;
+        let mut outcomes = Vec::with_capacity(executions.len());
+
+        for execution in executions {
+            let mut savepoint = tx.begin().await?;
+            match upsert_execution(&mut *savepoint, execution).await {
+                Ok(()) => {
+                    let claimed = sqlx::query(
+                        r#"
+                        UPDATE tasks
+                        SET run_state = 'running', claimed_at = $1, claimed_by = $2,
+                            claim_expires_at = $3
+                        WHERE id = $4
+                            AND (run_state != 'running' OR claim_expires_at IS NULL OR (
+                                claim_expires_at < $1 AND NOT EXISTS (
+                                    SELECT 1 FROM executions
+                                    WHERE task_id = tasks.id AND status = 'running'
+                                        AND id != $5
+                                )
+                            ))
+                        "#,
+                    )
+                    .bind(execution.executed_at)
+                    .bind(&claim.owner)
+                    .bind(claim.expires_at)
+                    .bind(execution.task_id)
+                    .bind(execution.id)
+                    .execute(&mut *savepoint)
+                    .await?
+                    .rows_affected();
+                    if claimed == 0 {
+                        savepoint.rollback().await?;
+                        outcomes.push(WriteOutcome::AlreadyRunning);
+                        continue;
+                    }
+                    savepoint.commit().await?;
+                    outcomes.push(WriteOutcome::Recorded);
+                }
+                Err(sqlx::Error::Database(e)) if e.is_foreign_key_violation() => {
+                    savepoint.rollback().await?;
+                    outcomes.push(WriteOutcome::TaskMissing);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        tx.commit().await?;
+        Ok(outcomes)
+    }
+
+    async fn renew_claims(&self, task_ids: &[Uuid], claim: &Claim) -> sqlx::Result<u64> {
+        let result = sqlx::query(
+            r#"
+            UPDATE tasks
+            SET claim_expires_at = $1
+            WHERE id = ANY($2) AND claimed_by = $3 AND run_state = 'running'
+            "#,
+        )
+        .bind(claim.expires_at)
+        .bind(task_ids)
+        .bind(&claim.owner)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn get_execution(&self, id: Uuid) -> sqlx::Result<Option<Execution>> {
+        sqlx::query_as::<_, Execution>(&format!(
+            "SELECT {} FROM executions WHERE id = $1",
+            EXECUTION_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn recent_durations(
+        &self,
+        task_id: Uuid,
+        exclude: Uuid,
+        limit: i64,
+    ) -> sqlx::Result<Vec<i64>> {
+        sqlx::query_scalar(
+            r#"
+            SELECT duration_ms
+            FROM executions
+            WHERE task_id = $1 AND id != $2 AND status = 'success' AND duration_ms IS NOT NULL
+            ORDER BY executed_at DESC, id DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(task_id)
+        .bind(exclude)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    async fn last_success(&self, task_id: Uuid) -> sqlx::Result<Option<Execution>> {
+        sqlx::query_as::<_, Execution>(&format!(
+            r#"
+            SELECT {}
+            FROM executions
+            WHERE task_id = $1 AND status = 'success'
+            ORDER BY executed_at DESC
+            LIMIT 1
+            "#,
+            EXECUTION_COLUMNS
+        ))
+        .bind(task_id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn get_dependents(&self, task_id: Uuid) -> sqlx::Result<Vec<Task>> {
+        sqlx::query_as::<_, Task>(&format!(
+            "SELECT {} FROM tasks WHERE depends_on = $1 AND deleted_at IS NULL",
+            TASK_COLUMNS
+        ))
+        .bind(task_id)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    async fn running_executions(&self) -> sqlx::Result<Vec<Execution>> {
+        sqlx::query_as::<_, Execution>(&format!(
+            r#"
+            SELECT {}
+            FROM executions
+            WHERE status = 'running'
+            ORDER BY executed_at ASC, id ASC
+            "#,
+            EXECUTION_COLUMNS
+        ))
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    async fn orphaned_executions(
+        &self,
+        owner: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> sqlx::Result<Vec<Execution>> {
+        sqlx::query_as::<_, Execution>(&format!(
+            r#"
+            SELECT {}
+            FROM executions
+            WHERE status = 'running' AND task_id IN (
+                SELECT id FROM tasks
+                WHERE run_state != 'running' OR claim_expires_at IS NULL
+                    OR claim_expires_at < $1 OR claimed_by = $2
+            )
+            ORDER BY executed_at ASC, id ASC
+            "#,
+            EXECUTION_COLUMNS
+        ))
+        .bind(now)
+        .bind(owner)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    async fn prune_executions(
+        &self,
+        now: DateTime<Utc>,
+        default_keep_days: Option<i64>,
+        limit: i64,
+    ) -> sqlx::Result<u64> {
+        let result = sqlx::query(&format!(
+            "DELETE FROM executions WHERE id IN ({})",
+            EXPIRED_EXECUTION_IDS
+        ))
+        .bind(now)
+        .bind(default_keep_days)
+        .bind(limit)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn expired_executions(
+        &self,
+        now: DateTime<Utc>,
+        default_keep_days: Option<i64>,
+        limit: i64,
+    ) -> sqlx::Result<Vec<Execution>> {
+        sqlx::query_as::<_, Execution>(&format!(
+            "SELECT {} FROM executions WHERE id IN ({}) ORDER BY executed_at, id",
+            EXECUTION_COLUMNS, EXPIRED_EXECUTION_IDS
+        ))
+        .bind(now)
+        .bind(default_keep_days)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    async fn delete_executions(&self, ids: &[Uuid]) -> sqlx::Result<u64> {
+        let result = sqlx::query("DELETE FROM executions WHERE id = ANY($1)")
+            .bind(ids)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Writes the whole group in one transaction, each write inside its own savepoint so a
+    /// foreign key violation (task deleted during execution) only rolls back that write.
+    async fn record_executions(
+        &self,
+        writes: &[ExecutionWrite],
+    ) -> sqlx::Result<Vec<WriteOutcome>> {
+        let mut tx = self.pool.begin().await?;
+        let mut outcomes = Vec::with_capacity(writes.len());
+
+        for write in writes {
+            let task_id = write.execution.task_id;
+            let mut savepoint = tx.begin().await?;
+
+            match upsert_execution(&mut *savepoint, &write.execution).await {
+                Ok(()) => {}
+                Err(sqlx::Error::Database(e)) if e.is_foreign_key_violation() => {
+                    savepoint.rollback().await?;
+                    outcomes.push(WriteOutcome::TaskMissing);
+                    continue;
+                }
+                Err(e) => return Err(e),
+            }
+
+            match write.follow_up {
+                FollowUp::Delete => {
+                    soft_delete(&mut *savepoint, task_id).await?;
+                }
+                FollowUp::Reschedule(next_trigger) => {
+                    update_trigger(&mut *savepoint, task_id, next_trigger).await?;
+                }
+                FollowUp::Retry { at, attempt } => {
+                    sqlx::query(
+                        "UPDATE tasks SET trigger_at = $1, retry_attempt = $2 WHERE id = $3",
+                    )
+                    .bind(at)
+                    .bind(attempt)
+                    .bind(task_id)
+                    .execute(&mut *savepoint)
+                    .await?;
+                }
+                FollowUp::Keep => {}
+            }
+            set_run_state(&mut *savepoint, task_id, write.run_state()).await?;
+            if write.finishes_run() {
+                sqlx::query("UPDATE tasks SET run_count = run_count + 1 WHERE id = $1")
+                    .bind(task_id)
+                    .execute(&mut *savepoint)
+                    .await?;
+            }
+
+            savepoint.commit().await?;
+            outcomes.push(WriteOutcome::Recorded);
+        }
+
+        tx.commit().await?;
+        Ok(outcomes)
+    }
+
+    async fn create_api_key(&self, key: &ApiKey, key_hash: &str) -> sqlx::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO api_keys (id, name, key_hash, scope, created_at)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(key.id)
+        .bind(&key.name)
+        .bind(key_hash)
+        .bind(key.scope)
+        .bind(key.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn find_api_key(&self, key_hash: &str) -> sqlx::Result<Option<ApiKey>> {
+        sqlx::query_as::<_, ApiKey>(
+            r#"
+            SELECT id, name, scope, created_at, revoked_at
+            FROM api_keys
+            WHERE key_hash = $1 AND revoked_at IS NULL
+            "#,
+        )
+        .bind(key_hash)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn list_api_keys(&self) -> sqlx::Result<Vec<ApiKey>> {
+        sqlx::query_as::<_, ApiKey>(
+            r#"
+            SELECT id, name, scope, created_at, revoked_at
+            FROM api_keys
+            ORDER BY created_at ASC, id ASC
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    async fn update_api_key_scope(&self, id: Uuid, scope: Scope) -> sqlx::Result<u64> {
+        let result =
+            sqlx::query("UPDATE api_keys SET scope = $1 WHERE id = $2 AND revoked_at IS NULL")
+                .bind(scope)
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn revoke_api_key(&self, id: Uuid, at: DateTime<Utc>) -> sqlx::Result<u64> {
+        let result =
+            sqlx::query("UPDATE api_keys SET revoked_at = $1 WHERE id = $2 AND revoked_at IS NULL")
+                .bind(at)
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn put_secret(&self, secret: &StoredSecret) -> sqlx::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO secrets (name, ciphertext, created_at, updated_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (name) DO UPDATE SET
+                ciphertext = EXCLUDED.ciphertext,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(&secret.name)
+        .bind(&secret.ciphertext)
+        .bind(secret.created_at)
+        .bind(secret.updated_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_secret(&self, name: &str) -> sqlx::Result<Option<StoredSecret>> {
+        sqlx::query_as::<_, StoredSecret>(
+            "SELECT name, ciphertext, created_at, updated_at FROM secrets WHERE name = $1",
+        )
+        .bind(name)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn list_secrets(&self) -> sqlx::Result<Vec<StoredSecret>> {
+        sqlx::query_as::<_, StoredSecret>(
+            "SELECT name, ciphertext, created_at, updated_at FROM secrets ORDER BY name ASC",
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    async fn delete_secret(&self, name: &str) -> sqlx::Result<u64> {
+        let result = sqlx::query("DELETE FROM secrets WHERE name = $1")
+            .bind(name)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn acquire_lease(
+        &self,
+        name: &str,
+        holder: &str,
+        now: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> sqlx::Result<bool> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO leases (name, holder, expires_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (name) DO UPDATE SET
+                holder = EXCLUDED.holder,
+                expires_at = EXCLUDED.expires_at
+            WHERE leases.holder = EXCLUDED.holder OR leases.expires_at < $4
+            "#,
+        )
+        .bind(name)
+        .bind(holder)
+        .bind(expires_at)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn release_lease(&self, name: &str, holder: &str) -> sqlx::Result<u64> {
+        let result = sqlx::query("DELETE FROM leases WHERE name = $1 AND holder = $2")
+            .bind(name)
+            .bind(holder)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn create_trigger_link(&self, link: &TriggerLink) -> sqlx::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO trigger_links (id, task_id, payload, expires_at, used_at, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(link.id)
+        .bind(link.task_id)
+        .bind(link.payload.as_ref().map(Json))
+        .bind(link.expires_at)
+        .bind(link.used_at)
+        .bind(link.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_trigger_link(&self, id: Uuid) -> sqlx::Result<Option<TriggerLink>> {
+        sqlx::query_as::<_, TriggerLink>(
+            r#"
+            SELECT id, task_id, payload, expires_at, used_at, created_at
+            FROM trigger_links
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn redeem_trigger_link(&self, id: Uuid, now: DateTime<Utc>) -> sqlx::Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE trigger_links
+            SET used_at = $1
+            WHERE id = $2 AND used_at IS NULL AND expires_at > $1
+            "#,
+        )
+        .bind(now)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn create_workflow(&self, workflow: &Workflow) -> sqlx::Result<()> {
+        insert_workflow(&self.pool, workflow).await
+    }
+
+    async fn get_workflow(&self, id: Uuid) -> sqlx::Result<Option<Workflow>> {
+        sqlx::query_as::<_, Workflow>(
+            "SELECT id, name, nodes, created_at FROM workflows WHERE id = $1",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn list_workflows(&self) -> sqlx::Result<Vec<Workflow>> {
+        list_workflows(&self.pool).await
+    }
+
+    async fn create_workflow_run(&self, run: &WorkflowRun) -> sqlx::Result<()> {
+        sqlx::query(&format!(
+            "INSERT INTO workflow_runs ({}) VALUES ($1, $2, $3, $4, $5, $6)",
+            WORKFLOW_RUN_COLUMNS
+        ))
+        .bind(run.id)
+        .bind(run.workflow_id)
+        .bind(run.status)
+        .bind(Json(&run.nodes))
+        .bind(run.started_at)
+        .bind(run.finished_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_workflow_run(&self, id: Uuid) -> sqlx::Result<Option<WorkflowRun>> {
+        sqlx::query_as::<_, WorkflowRun>(&format!(
+            "SELECT {} FROM workflow_runs WHERE id = $1",
+            WORKFLOW_RUN_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn list_workflow_runs(
+        &self,
+        workflow_id: Uuid,
+        limit: i64,
+    ) -> sqlx::Result<Vec<WorkflowRun>> {
+        sqlx::query_as::<_, WorkflowRun>(&format!(
+            r#"
+            SELECT {}
+            FROM workflow_runs
+            WHERE workflow_id = $1
+            ORDER BY started_at DESC, id DESC
+            LIMIT $2
+            "#,
+            WORKFLOW_RUN_COLUMNS
+        ))
+        .bind(workflow_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    async fn unfinished_workflow_runs(&self) -> sqlx::Result<Vec<WorkflowRun>> {
+        sqlx::query_as::<_, WorkflowRun>(&format!(
+            "SELECT {} FROM workflow_runs WHERE status = 'running' ORDER BY started_at, id",
+            WORKFLOW_RUN_COLUMNS
+        ))
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    async fn update_workflow_run(&self, run: &WorkflowRun) -> sqlx::Result<u64> {
+        let result = sqlx::query(
+            "UPDATE workflow_runs SET status = $1, nodes = $2, finished_at = $3 WHERE id = $4",
+        )
+        .bind(run.status)
+        .bind(Json(&run.nodes))
+        .bind(run.finished_at)
+        .bind(run.id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn create_group(&self, group: &TaskGroup) -> sqlx::Result<()> {
+        insert_group(&self.pool, group).await
+    }
+
+    async fn get_group(&self, id: Uuid) -> sqlx::Result<Option<TaskGroup>> {
+        sqlx::query_as::<_, TaskGroup>(
+            r#"
+            SELECT id, name, description, paused_at, created_at
+            FROM task_groups
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn list_groups(&self) -> sqlx::Result<Vec<TaskGroup>> {
+        list_groups(&self.pool).await
+    }
+
+    async fn set_group_paused(
+        &self,
+        id: Uuid,
+        paused_at: Option<DateTime<Utc>>,
+    ) -> sqlx::Result<u64> {
+        let mut tx = self.pool.begin().await?;
+
+        let rows = sqlx::query(
+            r#"
+            UPDATE task_groups
+            SET paused_at = CASE WHEN $1::timestamptz IS NULL THEN NULL
+                ELSE COALESCE(paused_at, $1) END
+            WHERE id = $2
+            "#,
+        )
+        .bind(paused_at)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        if rows > 0 {
+            // Paused tasks leave the scheduler's cache and come back when resumed
+            let triggers: Vec<(Uuid, DateTime<Utc>)> = sqlx::query_as(
+                "SELECT id, trigger_at FROM tasks WHERE group_id = $1 AND deleted_at IS NULL",
+            )
+            .bind(id)
+            .fetch_all(&mut *tx)
+            .await?;
+            for (task_id, trigger_at) in triggers {
+                let event = match paused_at {
+                    Some(_) => ScheduleEvent::Removed { id: task_id },
+                    None => ScheduleEvent::Upserted {
+                        id: task_id,
+                        trigger_at,
+                    },
+                };
+                enqueue(&mut *tx, &event).await?;
+            }
+        }
+
+        tx.commit().await?;
+        Ok(rows)
+    }
+
+    async fn reschedule_task(&self, id: Uuid, trigger_at: DateTime<Utc>) -> sqlx::Result<u64> {
+        let mut tx = self.pool.begin().await?;
+
+        let rows = update_trigger(&mut *tx, id, trigger_at).await?;
+        if rows > 0 {
+            enqueue(&mut *tx, &ScheduleEvent::Upserted { id, trigger_at }).await?;
+        }
+
+        tx.commit().await?;
+        Ok(rows)
+    }
+
+    async fn defer_task(
+        &self,
+        id: Uuid,
+        until: DateTime<Utc>,
+        deferred_from: DateTime<Utc>,
+    ) -> sqlx::Result<u64> {
+        let mut tx = self.pool.begin().await?;
+
+        let rows = sqlx::query(
+            r#"
+            UPDATE tasks
+            SET trigger_at = $1, deferred_from = COALESCE(deferred_from, $2)
+            WHERE id = $3 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(until)
+        .bind(deferred_from)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        if rows > 0 {
+            let event = ScheduleEvent::Upserted {
+                id,
+                trigger_at: until,
+            };
+            enqueue(&mut *tx, &event).await?;
+        }
+
+        tx.commit().await?;
+        Ok(rows)
+    }
+
+    async fn update_task(&self, task: &Task) -> sqlx::Result<u64> {
+        let mut tx = self.pool.begin().await?;
+
+        let rows = sqlx::query(
+            r#"
+            UPDATE tasks
+            SET name = $1, trigger_at = $2, interval_seconds = $3, cron_expr = $4, payload = $5,
+                retry_attempt = 0
+            WHERE id = $6 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(&task.name)
+        .bind(task.trigger_at)
+        .bind(task.interval_seconds)
+        .bind(&task.cron_expr)
+        .bind(Json(&task.payload))
+        .bind(task.id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        if rows > 0 {
+            let event = ScheduleEvent::Upserted {
+                id: task.id,
+                trigger_at: task.trigger_at,
+            };
+            enqueue(&mut *tx, &event).await?;
+        }
+
+        tx.commit().await?;
+        Ok(rows)
+    }
+
+    async fn patch_task(&self, id: Uuid, patch: &TaskPatch) -> sqlx::Result<u64> {
+        let mut tx = self.pool.begin().await?;
+
+        let trigger_at: Option<DateTime<Utc>> = sqlx::query_scalar(
+            r#"
+            UPDATE tasks
+            SET name = COALESCE($1, name),
+                trigger_at = COALESCE($2, trigger_at),
+                interval_seconds = COALESCE($3, interval_seconds),
+                cron_expr = COALESCE($4, cron_expr),
+                payload = COALESCE($5, payload),
+                retry_attempt = CASE WHEN $6 THEN 0 ELSE retry_attempt END
+            WHERE id = $7 AND deleted_at IS NULL
+            RETURNING trigger_at
+            "#,
+        )
+        .bind(&patch.name)
+        .bind(patch.trigger_at)
+        .bind(patch.interval_seconds)
+        .bind(&patch.cron_expr)
+        .bind(patch.payload.as_ref().map(Json))
+        .bind(patch.reschedules())
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(trigger_at) = trigger_at else {
+            return Ok(0);
+        };
+        enqueue(&mut *tx, &ScheduleEvent::Upserted { id, trigger_at }).await?;
+
+        tx.commit().await?;
+        Ok(1)
+    }
+
+    async fn set_tasks_paused(
+        &self,
+        selector: &TaskSelector,
+        paused_at: Option<DateTime<Utc>>,
+    ) -> sqlx::Result<u64> {
+        let mut tx = self.pool.begin().await?;
+
+        // Label matching happens here; the group or task is narrowed down in the query
+        let (group_id, task_id) = match selector {
+            TaskSelector::Group(id) => (Some(*id), None),
+            TaskSelector::Task(id) => (None, Some(*id)),
+            TaskSelector::Labels(_) => (None, None),
+        };
+        let rows = sqlx::query(
+            r#"
+            SELECT id, trigger_at, labels, group_id
+            FROM tasks
+            WHERE deleted_at IS NULL AND ($1::uuid IS NULL OR group_id = $1)
+                AND ($2::uuid IS NULL OR id = $2)
+            "#,
+        )
+        .bind(group_id)
+        .bind(task_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut affected = 0;
+        for row in rows {
+            let id: Uuid = row.try_get("id")?;
+            let labels = row.try_get::<Option<Json<Labels>>, _>("labels")?;
+            if !selector.matches(id, labels.as_deref(), row.try_get("group_id")?) {
+                continue;
+            }
+
+            let changed = sqlx::query(
+                r#"
+                UPDATE tasks
+                SET paused_at = $1
+                WHERE id = $2 AND deleted_at IS NULL
+                    AND (paused_at IS NULL) = ($1::timestamptz IS NOT NULL)
+                "#,
+            )
+            .bind(paused_at)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+            if changed == 0 {
+                continue;
+            }
+
+            let event = match paused_at {
+                Some(_) => ScheduleEvent::Removed { id },
+                None => ScheduleEvent::Upserted {
+                    id,
+                    trigger_at: row.try_get("trigger_at")?,
+                },
+            };
+            enqueue(&mut *tx, &event).await?;
+            affected += 1;
+        }
+
+        tx.commit().await?;
+        Ok(affected)
+    }
+
+    async fn group_stats(&self, id: Uuid) -> sqlx::Result<GroupStats> {
+        sqlx::query_as::<_, GroupStats>(
+            r#"
+            SELECT
+                (SELECT COUNT(*) FROM tasks WHERE group_id = $1 AND deleted_at IS NULL)
+                    AS task_count,
+                COUNT(e.id) AS execution_count,
+                COUNT(*) FILTER (WHERE e.status = 'success') AS success_count,
+                COUNT(*) FILTER (WHERE e.status IN ('failure', 'interrupted')) AS failure_count,
+                MAX(e.executed_at) AS last_executed_at
+            FROM executions e
+            JOIN tasks t ON t.id = e.task_id
+            WHERE t.group_id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    async fn export_snapshot(
+        &self,
+        taken_at: DateTime<Utc>,
+        include_executions: bool,
+    ) -> sqlx::Result<Snapshot> {
+        // Every read sees the same database state
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ READ ONLY")
+            .execute(&mut *tx)
+            .await?;
+
+        let groups = list_groups(&mut *tx).await?;
+        let tasks = sqlx::query_as::<_, Task>(&format!(
+            "SELECT {} FROM tasks ORDER BY created_at DESC",
+            TASK_COLUMNS
+        ))
+        .fetch_all(&mut *tx)
+        .await?;
+        let workflows = list_workflows(&mut *tx).await?;
+        let executions = match include_executions {
+            true => Some(
+                sqlx::query_as::<_, Execution>(&format!(
+                    "SELECT {} FROM executions ORDER BY executed_at ASC, id ASC",
+                    EXECUTION_COLUMNS
+                ))
+                .fetch_all(&mut *tx)
+                .await?,
+            ),
+            false => None,
+        };
+
+        tx.commit().await?;
+        Ok(Snapshot {
+            version: SNAPSHOT_VERSION,
+            taken_at,
+            groups,
+            tasks,
+            workflows,
+            executions,
+        })
+    }
+
+    async fn restore_snapshot(&self, snapshot: &Snapshot) -> sqlx::Result<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        let empty: bool = sqlx::query_scalar(
+            r#"
+            SELECT NOT EXISTS (SELECT 1 FROM tasks)
+                AND NOT EXISTS (SELECT 1 FROM task_groups)
+                AND NOT EXISTS (SELECT 1 FROM workflows)
+            "#,
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        if !empty {
+            return Ok(false);
+        }
+        for group in &snapshot.groups {
+            insert_group(&mut *tx, group).await?;
+        }
+        for task in &snapshot.tasks {
+            insert_task(&mut *tx, task).await?;
+            if restored_task_is_scheduled(snapshot, task) {
+                let event = ScheduleEvent::Upserted {
+                    id: task.id,
+                    trigger_at: task.trigger_at,
+                };
+                enqueue(&mut *tx, &event).await?;
+            }
+        }
+        for workflow in &snapshot.workflows {
+            insert_workflow(&mut *tx, workflow).await?;
+        }
+        for execution in snapshot.executions.iter().flatten() {
+            upsert_execution(&mut *tx, execution).await?;
+        }
+
+        tx.commit().await?;
+        Ok(true)
+    }
+}
+
+async fn insert_group<'c, E>(executor: E, group: &TaskGroup) -> sqlx::Result<()>
+where
+    E: Executor<'c, Database = Postgres>,
+{
+    sqlx::query(
+        r#"
+        INSERT INTO task_groups (id, name, description, paused_at, created_at)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+    )
+    .bind(group.id)
+    .bind(&group.name)
+    .bind(&group.description)
+    .bind(group.paused_at)
+    .bind(group.created_at)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+async fn insert_workflow<'c, E>(executor: E, workflow: &Workflow) -> sqlx::Result<()>
+where
+    E: Executor<'c, Database = Postgres>,
+{
+    sqlx::query(
+        r#"
+        INSERT INTO workflows (id, name, nodes, created_at)
+        VALUES ($1, $2, $3, $4)
+        "#,
+    )
+    .bind(workflow.id)
+    .bind(&workflow.name)
+    .bind(Json(&workflow.nodes))
+    .bind(workflow.created_at)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+async fn list_workflows<'c, E>(executor: E) -> sqlx::Result<Vec<Workflow>>
+where
+    E: Executor<'c, Database = Postgres>,
+{
+    sqlx::query_as::<_, Workflow>(
+        "SELECT id, name, nodes, created_at FROM workflows ORDER BY name, id",
+    )
+    .fetch_all(executor)
+    .await
+}
+
+async fn list_groups<'c, E>(executor: E) -> sqlx::Result<Vec<TaskGroup>>
+where
+    E: Executor<'c, Database = Postgres>,
+{
+    sqlx::query_as::<_, TaskGroup>(
+        r#"
+        SELECT id, name, description, paused_at, created_at
+        FROM task_groups
+        ORDER BY name ASC
+        "#,
+    )
+    .fetch_all(executor)
+    .await
+}
@@ -0,0 +1,149 @@
+//! Run against a PostgreSQL server with
+//! `DATABASE_URL=postgres://... cargo test --features postgres`.
+use crate::db::postgres::PostgresTaskStore;
+use crate::db::store::{Claim, ExecutionWrite, FollowUp, TaskStore, WriteOutcome};
+use crate::domain::{Execution, ExecutionRetry, ExecutionStatus, Task, TaskFilter, TaskSelector};
+use crate::scheduler::ScheduleEvent;
+use chrono::{Duration, DurationRound, Utc};
+use serde_json::json;
+use sqlx::PgPool;
+use std::collections::BTreeMap;
+
+#[sqlx::test(migrations = "./migrations/postgres")]
+async fn test_tasks_round_trip_through_the_outbox(pool: PgPool) -> sqlx::Result<()> {
+    let store = PostgresTaskStore::new(pool);
+    // Postgres keeps microseconds
+    let now = Utc::now()
+        .duration_trunc(Duration::microseconds(1))
+        .unwrap();
+
+    let task = Task {
+        labels: Some(BTreeMap::from([("team".into(), "billing".into())])),
+        retry_policy: Some(ExecutionRetry {
+            max_retries: 3,
+            initial_delay_seconds: 30,
+            backoff_multiplier: 2.0,
+        }),
+        priority: 5,
+        ..Task::new_interval(
+            "invoice-run",
+            now - Duration::minutes(1),
+            60,
+            json!({"a": 1}),
+        )
+    };
+    let later = Task::new_once("later", now + Duration::hours(1), json!({}));
+    store.create_task(&task).await?;
+    store.create_task(&later).await?;
+
+    assert_eq!(store.get_task(task.id).await?, Some(task.clone()));
+    assert_eq!(store.get_due_tasks(now, 10).await?, vec![task.clone()]);
+    assert_eq!(store.count_due_tasks(now).await?, 1);
+
+    let paused = store
+        .set_tasks_paused(&TaskSelector::Task(task.id), Some(now))
+        .await?;
+    assert_eq!(paused, 1);
+    assert!(store.get_due_tasks(now, 10).await?.is_empty());
+    store.delete_task(later.id).await?;
+
+    assert_eq!(
+        store.drain_outbox(10).await?,
+        vec![
+            ScheduleEvent::Upserted {
+                id: task.id,
+                trigger_at: task.trigger_at
+            },
+            ScheduleEvent::Upserted {
+                id: later.id,
+                trigger_at: later.trigger_at
+            },
+            ScheduleEvent::Removed { id: task.id },
+            ScheduleEvent::Removed { id: later.id },
+        ]
+    );
+    assert!(store.drain_outbox(10).await?.is_empty());
+
+    let summaries = store.get_task_summaries(&TaskFilter::default()).await?;
+    assert_eq!(summaries.len(), 2);
+
+    assert_eq!(store.purge_deleted_tasks(now, 10).await?, 0);
+    let cutoff = Utc::now() + Duration::minutes(1);
+    assert_eq!(store.purge_deleted_tasks(cutoff, 10).await?, 1);
+    assert!(store.get_task(later.id).await?.is_none());
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "./migrations/postgres")]
+async fn test_execution_writes_skip_vanished_tasks(pool: PgPool) -> sqlx::Result<()> {
+    let store = PostgresTaskStore::new(pool);
+    let now = Utc::now();
+    let task = Task {
+        keep_executions: Some(1),
+        ..Task::new_interval("heartbeat", now, 60, json!({}))
+    };
+    store.create_task(&task).await?;
+
+    let running = Execution::new(task.id, json!({}), ExecutionStatus::Running);
+    let gone = Execution::new(uuid::Uuid::new_v4(), json!({}), ExecutionStatus::Running);
+    let claim = Claim {
+        owner: "test".into(),
+        expires_at: now + Duration::minutes(1),
+    };
+    assert_eq!(
+        store
+            .begin_executions(&[running.clone(), gone], &claim)
+            .await?,
+        vec![WriteOutcome::Recorded, WriteOutcome::TaskMissing]
+    );
+    assert_eq!(store.running_executions().await?.len(), 1);
+    assert_eq!(store.renew_claims(&[task.id], &claim).await?, 1);
+    assert!(store.orphaned_executions(None, now).await?.is_empty());
+    assert_eq!(store.orphaned_executions(Some("test"), now).await?.len(), 1);
+
+    let next = now + Duration::minutes(1);
+    let finished = Execution {
+        status: ExecutionStatus::Success,
+        duration_ms: Some(12),
+        ..running
+    };
+    let older = Execution {
+        executed_at: now - Duration::days(1),
+        ..Execution::new(task.id, json!({}), ExecutionStatus::Failure)
+    };
+    let outcomes = store
+        .record_executions(&[
+            ExecutionWrite {
+                execution: finished.clone(),
+                follow_up: FollowUp::Reschedule(next),
+            },
+            ExecutionWrite {
+                execution: older,
+                follow_up: FollowUp::Keep,
+            },
+        ])
+        .await?;
+    assert_eq!(outcomes, vec![WriteOutcome::Recorded; 2]);
+    assert!(store.running_executions().await?.is_empty());
+
+    let runs = store.get_task_runs(task.id).await?;
+    assert_eq!(runs.last_status, Some(ExecutionStatus::Success));
+    assert!(runs.next_run_at.is_some());
+
+    let later = now + Duration::minutes(1);
+    let expired = store.expired_executions(later, Some(0), 1000).await?;
+    assert_eq!(
+        expired.len(),
+        2,
+        "Both finished executions are older than zero days"
+    );
+    assert_eq!(store.delete_executions(&[]).await?, 0);
+    assert_eq!(store.prune_executions(now, None, 1000).await?, 1);
+    let records = store.list_execution_records(task.id, None, 10).await?;
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].id, finished.id);
+    assert_eq!(records[0].duration_ms, Some(12));
+
+    Ok(())
+}
@@ -1,16 +1,76 @@
-use crate::domain::Task;
-use chrono::Utc;
+use crate::db::retry::{RetryPolicy, with_retry};
+use crate::db::store::Claim;
+use crate::domain::{
+    BlackoutWindow, Execution, ExecutionCursor, ExecutionRetry, ExecutionSummary, Labels,
+    Notifications, RunState, Task, TaskFilter, TaskPatch, TaskRuns, TaskSelector, TaskState,
+    TaskSummary,
+};
+use crate::executor::condition::RunCondition;
+use crate::schedule::ExecutionWindow;
+use crate::scheduler::ScheduleEvent;
+use chrono::{DateTime, Utc};
+use futures_util::stream::BoxStream;
 use serde_json::Value;
 use sqlx::{Executor, Row, Sqlite, SqlitePool, types::Json};
 use uuid::Uuid;
 
+/// `TaskRuns` columns of a task aliased `t`; needs [`LAST_RUN_JOIN`].
+const TASK_RUNS_COLUMNS: &str = r#"
+    CASE WHEN t.deleted_at IS NULL AND t.paused_at IS NULL AND NOT EXISTS (
+        SELECT 1 FROM task_groups g WHERE g.id = t.group_id AND g.paused_at IS NOT NULL
+    ) THEN t.trigger_at END AS next_run_at,
+    last_run.executed_at AS last_run_at,
+    last_run.status AS last_status
+"#;
+
+/// IDs of up to `?3` finished executions outside their task's retention settings at `?1`,
+/// oldest first, with `?2` as the `keep_days` of tasks without their own.
+const EXPIRED_EXECUTION_IDS: &str = r#"
+    SELECT id FROM (
+        SELECT e.id, e.executed_at, t.keep_executions,
+            COALESCE(t.keep_days, ?2) AS keep_days,
+            ROW_NUMBER() OVER (
+                PARTITION BY e.task_id ORDER BY e.executed_at DESC, e.id DESC
+            ) AS recency
+        FROM executions e
+        JOIN tasks t ON t.id = e.task_id
+        WHERE e.status != 'running'
+          AND (t.keep_executions IS NOT NULL OR COALESCE(t.keep_days, ?2) IS NOT NULL)
+    )
+    WHERE recency > keep_executions
+       OR julianday(executed_at) < julianday(?1) - keep_days
+    ORDER BY executed_at, id
+    LIMIT ?3
+"#;
+
+/// Joins the latest finished execution of a task aliased `t` as `last_run`, through the
+/// `(task_id, executed_at, id)` index.
+const LAST_RUN_JOIN: &str = r#"
+    LEFT JOIN executions last_run ON last_run.id = (
+        SELECT e.id FROM executions e
+        WHERE e.task_id = t.id AND e.status != 'running'
+        ORDER BY e.executed_at DESC, e.id DESC
+        LIMIT 1
+    )
+"#;
+
 pub struct TaskRepository<'a> {
     pub pool: &'a SqlitePool,
+    retry: RetryPolicy,
 }
 
 impl<'a> TaskRepository<'a> {
     pub fn new(pool: &'a SqlitePool) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    /// Sets the retry policy applied to write operations that hit `SQLITE_BUSY`.
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
     }
 
     /// Creates a new task in the database.
@@ -22,10 +82,38 @@ impl<'a> TaskRepository<'a> {
     /// # Returns
     /// * `sqlx::Result<()>` - Result indicating success or failure of the operation.
     pub async fn create_task(&self, task: &Task) -> sqlx::Result<()> {
+        with_retry(&self.retry, || {
+            Self::create_task_with_executor(self.pool, task)
+        })
+        .await
+    }
+
+    /// Inserts a task using the provided executor.
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - An executor that can execute the query (e.g., a connection or transaction).
+    /// * `task` - A reference to the Task entity to be created.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<()>` - Result indicating success or failure of the operation.
+    pub async fn create_task_with_executor<'c, E>(executor: E, task: &Task) -> sqlx::Result<()>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
         sqlx::query(
             r#"
-            INSERT INTO tasks (id, name, task_type, trigger_at, interval_seconds, payload)
-            VALUES (?, ?, ?, ?, ?, ?)
+            INSERT INTO tasks (
+                id, name, task_type, trigger_at, interval_seconds, payload, deleted_at,
+                request_id, keep_executions, keep_days, signing_secret, previous_signing_secret,
+                previous_secret_expires_at, api_key_id, notifications, group_id, labels, paused_at,
+                cron_expr, retry_policy, retry_attempt, priority, run_state, misfire_policy,
+                interval_mode, ends_at, max_executions, run_count, timezone, rrule,
+                blackout_windows, deferred_from, execution_windows, depends_on,
+                next_task_id, run_if
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?,
+                ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(task.id)
@@ -34,7 +122,37 @@ impl<'a> TaskRepository<'a> {
         .bind(task.trigger_at)
         .bind(task.interval_seconds)
         .bind(Json(&task.payload))
-        .execute(self.pool)
+        .bind(task.deleted_at)
+        .bind(&task.request_id)
+        .bind(task.keep_executions)
+        .bind(task.keep_days)
+        .bind(&task.signing_secret)
+        .bind(&task.previous_signing_secret)
+        .bind(task.previous_secret_expires_at)
+        .bind(task.api_key_id)
+        .bind(task.notifications.as_ref().map(Json))
+        .bind(task.group_id)
+        .bind(task.labels.as_ref().map(Json))
+        .bind(task.paused_at)
+        .bind(&task.cron_expr)
+        .bind(task.retry_policy.as_ref().map(Json))
+        .bind(task.retry_attempt)
+        .bind(task.priority)
+        .bind(task.run_state)
+        .bind(task.misfire_policy)
+        .bind(task.interval_mode)
+        .bind(task.ends_at)
+        .bind(task.max_executions)
+        .bind(task.run_count)
+        .bind(&task.timezone)
+        .bind(&task.rrule)
+        .bind(task.blackout_windows.as_ref().map(Json))
+        .bind(task.deferred_from)
+        .bind(task.execution_windows.as_ref().map(Json))
+        .bind(task.depends_on)
+        .bind(task.next_task_id)
+        .bind(task.run_if.as_ref().map(Json))
+        .execute(executor)
         .await?;
 
         Ok(())
@@ -51,7 +169,13 @@ impl<'a> TaskRepository<'a> {
     pub async fn get_task(&self, id: Uuid) -> sqlx::Result<Option<Task>> {
         let row = sqlx::query(
             r#"
-            SELECT id, name, task_type, trigger_at, interval_seconds, payload, deleted_at
+            SELECT id, name, task_type, trigger_at, interval_seconds, payload, deleted_at, request_id,
+                keep_executions, keep_days, signing_secret, previous_signing_secret,
+                previous_secret_expires_at, api_key_id, notifications, group_id, labels, paused_at,
+                cron_expr, retry_policy, retry_attempt, priority, run_state, misfire_policy,
+                interval_mode, ends_at, max_executions, run_count, timezone, rrule,
+                blackout_windows, deferred_from, execution_windows, depends_on,
+                next_task_id, run_if
             FROM tasks
             WHERE id = ?
             "#,
@@ -72,11 +196,55 @@ impl<'a> TaskRepository<'a> {
             interval_seconds: row.try_get("interval_seconds")?,
             payload: row.try_get::<Json<Value>, _>("payload")?.0,
             deleted_at: row.try_get("deleted_at")?,
+            request_id: row.try_get("request_id")?,
+            keep_executions: row.try_get("keep_executions")?,
+            keep_days: row.try_get("keep_days")?,
+            signing_secret: row.try_get("signing_secret")?,
+            previous_signing_secret: row.try_get("previous_signing_secret")?,
+            previous_secret_expires_at: row.try_get("previous_secret_expires_at")?,
+            api_key_id: row.try_get("api_key_id")?,
+            notifications: row
+                .try_get::<Option<Json<Notifications>>, _>("notifications")?
+                .map(|json| json.0),
+            group_id: row.try_get("group_id")?,
+            labels: row
+                .try_get::<Option<Json<Labels>>, _>("labels")?
+                .map(|json| json.0),
+            paused_at: row.try_get("paused_at")?,
+            cron_expr: row.try_get("cron_expr")?,
+            retry_policy: row
+                .try_get::<Option<Json<ExecutionRetry>>, _>("retry_policy")?
+                .map(|json| json.0),
+            retry_attempt: row.try_get("retry_attempt")?,
+            priority: row.try_get("priority")?,
+            run_state: row.try_get("run_state")?,
+            misfire_policy: row.try_get("misfire_policy")?,
+            interval_mode: row.try_get("interval_mode")?,
+            ends_at: row.try_get("ends_at")?,
+            max_executions: row.try_get("max_executions")?,
+            run_count: row.try_get("run_count")?,
+            timezone: row.try_get("timezone")?,
+            rrule: row.try_get("rrule")?,
+            blackout_windows: row
+                .try_get::<Option<Json<Vec<BlackoutWindow>>>, _>("blackout_windows")?
+                .map(|json| json.0),
+            deferred_from: row.try_get("deferred_from")?,
+            execution_windows: row
+                .try_get::<Option<Json<Vec<ExecutionWindow>>>, _>("execution_windows")?
+                .map(|json| json.0),
+            depends_on: row.try_get("depends_on")?,
+            next_task_id: row.try_get("next_task_id")?,
+            run_if: row
+                .try_get::<Option<Json<RunCondition>>, _>("run_if")?
+                .map(|json| json.0),
         }))
     }
 
     pub async fn delete_task(&self, id: Uuid) -> sqlx::Result<u64> {
-        Self::delete_task_with_executor(self.pool, id).await
+        with_retry(&self.retry, || {
+            Self::delete_task_with_executor(self.pool, id)
+        })
+        .await
     }
 
     /// Soft deletes a task by setting its deleted_at timestamp.
@@ -102,10 +270,222 @@ impl<'a> TaskRepository<'a> {
         Ok(result.rows_affected())
     }
 
+    /// Permanently deletes a task row, whether or not it was soft deleted.
+    ///
+    /// Its executions must be removed first (see `delete_executions_for_task_with_executor`).
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - An executor that can execute the query (e.g., a connection or transaction).
+    /// * `id` - The UUID of the task to purge.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<u64>` - Result containing the number of rows affected.
+    pub async fn purge_task_with_executor<'c, E>(executor: E, id: Uuid) -> sqlx::Result<u64>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let result = sqlx::query("DELETE FROM tasks WHERE id = ?")
+            .bind(id)
+            .execute(executor)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Lists tasks soft deleted before a cutoff, longest deleted first.
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - An executor that can execute the query (e.g., a connection or transaction).
+    /// * `cutoff` - Only tasks deleted strictly before this time are listed.
+    /// * `limit` - Maximum number of tasks to list.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<Vec<Uuid>>` - The IDs of the tasks.
+    pub async fn deleted_task_ids_with_executor<'c, E>(
+        executor: E,
+        cutoff: DateTime<Utc>,
+        limit: i64,
+    ) -> sqlx::Result<Vec<Uuid>>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        sqlx::query_scalar(
+            r#"
+            SELECT id FROM tasks
+            WHERE deleted_at IS NOT NULL AND deleted_at < ?
+            ORDER BY deleted_at
+            LIMIT ?
+            "#,
+        )
+        .bind(cutoff)
+        .bind(limit)
+        .fetch_all(executor)
+        .await
+    }
+
+    /// Permanently deletes every execution of a task.
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - An executor that can execute the query (e.g., a connection or transaction).
+    /// * `task_id` - The UUID of the task whose executions are removed.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<u64>` - Result containing the number of executions deleted.
+    pub async fn delete_executions_for_task_with_executor<'c, E>(
+        executor: E,
+        task_id: Uuid,
+    ) -> sqlx::Result<u64>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let result = sqlx::query("DELETE FROM executions WHERE task_id = ?")
+            .bind(task_id)
+            .execute(executor)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Counts the live tasks created with an API key.
+    ///
+    /// # Arguments
+    ///
+    /// * `api_key_id` - The UUID of the API key.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<i64>` - The number of tasks that are not deleted.
+    pub async fn count_active_tasks_for_key(&self, api_key_id: Uuid) -> sqlx::Result<i64> {
+        sqlx::query_scalar("SELECT COUNT(*) FROM tasks WHERE api_key_id = ? AND deleted_at IS NULL")
+            .bind(api_key_id)
+            .fetch_one(self.pool)
+            .await
+    }
+
+    /// Replaces a live task's signing secret, keeping the current one as the previous secret
+    /// until `previous_expires_at`.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The UUID of the task.
+    /// * `secret` - The new signing secret.
+    /// * `previous_expires_at` - When the outgoing secret stops signing requests.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<u64>` - Result containing the number of rows affected.
+    pub async fn rotate_signing_secret(
+        &self,
+        id: Uuid,
+        secret: &str,
+        previous_expires_at: DateTime<Utc>,
+    ) -> sqlx::Result<u64> {
+        let result = sqlx::query(
+            r#"
+            UPDATE tasks
+            SET previous_signing_secret = signing_secret,
+                previous_secret_expires_at = ?,
+                signing_secret = ?
+            WHERE id = ? AND deleted_at IS NULL
+            "#,
+        )
+        .bind(previous_expires_at)
+        .bind(secret)
+        .bind(id)
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Retrieves the ids and trigger times of the live tasks a selector matches, using the
+    /// provided executor.
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - An executor that can execute the query (e.g., a connection or transaction).
+    /// * `selector` - The labels or group the tasks must match.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<Vec<(Uuid, DateTime<Utc>)>>` - Task ids with their trigger times.
+    pub async fn select_tasks_with_executor<'c, E>(
+        executor: E,
+        selector: &TaskSelector,
+    ) -> sqlx::Result<Vec<(Uuid, DateTime<Utc>)>>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        // Label matching happens here; the group or task is narrowed down in the query
+        let (group_id, task_id) = match selector {
+            TaskSelector::Group(id) => (Some(*id), None),
+            TaskSelector::Task(id) => (None, Some(*id)),
+            TaskSelector::Labels(_) => (None, None),
+        };
+        let rows = sqlx::query(
+            r#"
+            SELECT id, trigger_at, labels, group_id
+            FROM tasks
+            WHERE deleted_at IS NULL AND (?1 IS NULL OR group_id = ?1) AND (?2 IS NULL OR id = ?2)
+            "#,
+        )
+        .bind(group_id)
+        .bind(task_id)
+        .fetch_all(executor)
+        .await?;
+
+        let mut selected = Vec::new();
+        for row in rows {
+            let labels = row.try_get::<Option<Json<Labels>>, _>("labels")?;
+            if selector.matches(
+                row.try_get("id")?,
+                labels.as_deref(),
+                row.try_get("group_id")?,
+            ) {
+                selected.push((row.try_get("id")?, row.try_get("trigger_at")?));
+            }
+        }
+        Ok(selected)
+    }
+
+    /// Pauses a live task at `paused_at`, or resumes it if `None`, using the provided
+    /// executor. Tasks already in the requested state are left alone.
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - An executor that can execute the query (e.g., a connection or transaction).
+    /// * `id` - The UUID of the task.
+    /// * `paused_at` - The pause time, or `None` to resume.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<u64>` - Result containing the number of rows affected.
+    pub async fn set_paused_with_executor<'c, E>(
+        executor: E,
+        id: Uuid,
+        paused_at: Option<DateTime<Utc>>,
+    ) -> sqlx::Result<u64>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let result = sqlx::query(
+            r#"
+            UPDATE tasks
+            SET paused_at = ?1
+            WHERE id = ?2 AND deleted_at IS NULL AND (paused_at IS NULL) = (?1 IS NOT NULL)
+            "#,
+        )
+        .bind(paused_at)
+        .bind(id)
+        .execute(executor)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
     pub async fn update_trigger_with_executor<'c, E>(
         executor: E,
         id: Uuid,
-        new_trigger_at: chrono::DateTime<Utc>,
+        new_trigger_at: DateTime<Utc>,
     ) -> sqlx::Result<u64>
     where
         E: Executor<'c, Database = Sqlite>,
@@ -113,7 +493,7 @@ impl<'a> TaskRepository<'a> {
         let result = sqlx::query(
             r#"
             UPDATE tasks
-            SET trigger_at = ?
+            SET trigger_at = ?, retry_attempt = 0, deferred_from = NULL
             WHERE id = ?
             "#,
         )
@@ -125,44 +505,1099 @@ impl<'a> TaskRepository<'a> {
         Ok(result.rows_affected())
     }
 
-    pub async fn get_next_pending_task(&self) -> sqlx::Result<Option<Task>> {
-        let row = sqlx::query(
+    /// Holds a live task's run back until the end of a blackout window, using the provided
+    /// executor. The first deferral of a run records when it was due.
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - An executor that can execute the query (e.g., a connection or transaction).
+    /// * `id` - The UUID of the task.
+    /// * `until` - When the run may fire.
+    /// * `deferred_from` - When the run was due.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<u64>` - Result containing the number of rows affected.
+    pub async fn defer_with_executor<'c, E>(
+        executor: E,
+        id: Uuid,
+        until: DateTime<Utc>,
+        deferred_from: DateTime<Utc>,
+    ) -> sqlx::Result<u64>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let result = sqlx::query(
             r#"
-            SELECT id, name, task_type, trigger_at, interval_seconds, payload, deleted_at
-            FROM tasks
-            WHERE deleted_at IS NULL
-            ORDER BY trigger_at ASC
-            LIMIT 1
+            UPDATE tasks
+            SET trigger_at = ?, deferred_from = COALESCE(deferred_from, ?)
+            WHERE id = ? AND deleted_at IS NULL
             "#,
         )
-        .fetch_optional(self.pool)
+        .bind(until)
+        .bind(deferred_from)
+        .bind(id)
+        .execute(executor)
         .await?;
 
-        let row = match row {
-            Some(row) => row,
-            None => return Ok(None),
-        };
+        Ok(result.rows_affected())
+    }
 
-        Ok(Some(Task {
-            id: row.try_get("id")?,
-            name: row.try_get("name")?,
-            task_type: row.try_get("task_type")?,
-            trigger_at: row.try_get("trigger_at")?,
-            interval_seconds: row.try_get("interval_seconds")?,
-            payload: row.try_get::<Json<Value>, _>("payload")?.0,
-            deleted_at: row.try_get("deleted_at")?,
-        }))
+    /// Replaces a live task's name, schedule and payload, using the provided executor. Any
+    /// pending retry is dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - An executor that can execute the query (e.g., a connection or transaction).
+    /// * `task` - The task with its new definition.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<u64>` - Result containing the number of rows affected.
+    pub async fn update_definition_with_executor<'c, E>(
+        executor: E,
+        task: &Task,
+    ) -> sqlx::Result<u64>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let result = sqlx::query(
+            r#"
+            UPDATE tasks
+            SET name = ?, trigger_at = ?, interval_seconds = ?, cron_expr = ?, payload = ?,
+                retry_attempt = 0
+            WHERE id = ? AND deleted_at IS NULL
+            "#,
+        )
+        .bind(&task.name)
+        .bind(task.trigger_at)
+        .bind(task.interval_seconds)
+        .bind(&task.cron_expr)
+        .bind(Json(&task.payload))
+        .bind(task.id)
+        .execute(executor)
+        .await?;
+
+        Ok(result.rows_affected())
     }
 
-    pub async fn get_all_tasks(&self) -> sqlx::Result<Vec<Task>> {
-        sqlx::query_as::<_, Task>(
+    /// Updates only the fields a patch sets on a live task, using the provided executor.
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - An executor that can execute the query (e.g., a connection or transaction).
+    /// * `id` - The UUID of the task.
+    /// * `patch` - The fields to change.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<Option<DateTime<Utc>>>` - The task's trigger time after the update, or
+    ///   `None` if no live task was updated.
+    pub async fn patch_with_executor<'c, E>(
+        executor: E,
+        id: Uuid,
+        patch: &TaskPatch,
+    ) -> sqlx::Result<Option<DateTime<Utc>>>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        sqlx::query_scalar(
             r#"
-            SELECT id, name, task_type, trigger_at, interval_seconds, payload, deleted_at
-            FROM tasks
-            ORDER BY created_at DESC
+            UPDATE tasks
+            SET name = COALESCE(?1, name),
+                trigger_at = COALESCE(?2, trigger_at),
+                interval_seconds = COALESCE(?3, interval_seconds),
+                cron_expr = COALESCE(?4, cron_expr),
+                payload = COALESCE(?5, payload),
+                retry_attempt = CASE WHEN ?6 THEN 0 ELSE retry_attempt END
+            WHERE id = ?7 AND deleted_at IS NULL
+            RETURNING trigger_at
             "#,
         )
-        .fetch_all(self.pool)
+        .bind(&patch.name)
+        .bind(patch.trigger_at)
+        .bind(patch.interval_seconds)
+        .bind(&patch.cron_expr)
+        .bind(patch.payload.as_ref().map(Json))
+        .bind(patch.reschedules())
+        .bind(id)
+        .fetch_optional(executor)
+        .await
+    }
+
+    /// Moves a task's trigger to a retry of its failed run, using the provided executor.
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - An executor that can execute the query (e.g., a connection or transaction).
+    /// * `id` - The UUID of the task.
+    /// * `retry_at` - When the retry runs.
+    /// * `attempt` - Which retry of the run this is, starting at 1.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<u64>` - Result containing the number of rows affected.
+    pub async fn schedule_retry_with_executor<'c, E>(
+        executor: E,
+        id: Uuid,
+        retry_at: DateTime<Utc>,
+        attempt: i64,
+    ) -> sqlx::Result<u64>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let result = sqlx::query(
+            r#"
+            UPDATE tasks
+            SET trigger_at = ?, retry_attempt = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(retry_at)
+        .bind(attempt)
+        .bind(id)
+        .execute(executor)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Sets a task's run state, using the provided executor.
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - An executor that can execute the query (e.g., a connection or transaction).
+    /// * `id` - The UUID of the task.
+    /// * `run_state` - The new run state.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<u64>` - Result containing the number of rows affected.
+    pub async fn set_run_state_with_executor<'c, E>(
+        executor: E,
+        id: Uuid,
+        run_state: RunState,
+    ) -> sqlx::Result<u64>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let result = sqlx::query("UPDATE tasks SET run_state = ? WHERE id = ?")
+            .bind(run_state)
+            .bind(id)
+            .execute(executor)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Adds a finished scheduled run to a task's `run_count`, using the provided executor.
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - An executor that can execute the query (e.g., a connection or transaction).
+    /// * `id` - The UUID of the task.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<u64>` - Result containing the number of rows affected.
+    pub async fn count_run_with_executor<'c, E>(executor: E, id: Uuid) -> sqlx::Result<u64>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let result = sqlx::query("UPDATE tasks SET run_count = run_count + 1 WHERE id = ?")
+            .bind(id)
+            .execute(executor)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Claims a task for the run `execution` starts, using the provided executor. Another
+    /// run's claim is only taken over once it has expired and no other execution of the task
+    /// is still running.
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - An executor that can execute the query (e.g., a connection or transaction).
+    /// * `execution` - The `running` record of the run, already written.
+    /// * `claim` - The claim to hold the task with.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<u64>` - 1 if the task was claimed, 0 if another run holds it.
+    pub async fn claim_with_executor<'c, E>(
+        executor: E,
+        execution: &Execution,
+        claim: &Claim,
+    ) -> sqlx::Result<u64>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let result = sqlx::query(
+            r#"
+            UPDATE tasks
+            SET run_state = 'running', claimed_at = ?1, claimed_by = ?2, claim_expires_at = ?3
+            WHERE id = ?4
+                AND (run_state != 'running' OR claim_expires_at IS NULL OR (
+                    claim_expires_at < ?1 AND NOT EXISTS (
+                        SELECT 1 FROM executions
+                        WHERE task_id = tasks.id AND status = 'running' AND id != ?5
+                    )
+                ))
+            "#,
+        )
+        .bind(execution.executed_at)
+        .bind(&claim.owner)
+        .bind(claim.expires_at)
+        .bind(execution.task_id)
+        .bind(execution.id)
+        .execute(executor)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Extends the claims `claim.owner` holds on running tasks, using the provided executor.
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - An executor that can execute the query (e.g., a connection or transaction).
+    /// * `id` - The UUID of the task.
+    /// * `claim` - The owner and new expiry of the claim.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<u64>` - 1 if the claim was renewed, 0 if it is no longer held.
+    pub async fn renew_claim_with_executor<'c, E>(
+        executor: E,
+        id: Uuid,
+        claim: &Claim,
+    ) -> sqlx::Result<u64>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let result = sqlx::query(
+            r#"
+            UPDATE tasks
+            SET claim_expires_at = ?
+            WHERE id = ? AND claimed_by = ? AND run_state = 'running'
+            "#,
+        )
+        .bind(claim.expires_at)
+        .bind(id)
+        .bind(&claim.owner)
+        .execute(executor)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    pub async fn get_next_pending_task(&self) -> sqlx::Result<Option<Task>> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, name, task_type, trigger_at, interval_seconds, payload, deleted_at, request_id,
+                keep_executions, keep_days, signing_secret, previous_signing_secret,
+                previous_secret_expires_at, api_key_id, notifications, group_id, labels, paused_at,
+                cron_expr, retry_policy, retry_attempt, priority, run_state, misfire_policy,
+                interval_mode, ends_at, max_executions, run_count, timezone, rrule,
+                blackout_windows, deferred_from, execution_windows, depends_on,
+                next_task_id, run_if
+            FROM tasks
+            WHERE deleted_at IS NULL AND paused_at IS NULL
+                AND (group_id IS NULL OR group_id NOT IN (
+                    SELECT id FROM task_groups WHERE paused_at IS NOT NULL
+                ))
+            ORDER BY priority DESC, trigger_at ASC
+            LIMIT 1
+            "#,
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        let row = match row {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+
+        Ok(Some(Task {
+            id: row.try_get("id")?,
+            name: row.try_get("name")?,
+            task_type: row.try_get("task_type")?,
+            trigger_at: row.try_get("trigger_at")?,
+            interval_seconds: row.try_get("interval_seconds")?,
+            payload: row.try_get::<Json<Value>, _>("payload")?.0,
+            deleted_at: row.try_get("deleted_at")?,
+            request_id: row.try_get("request_id")?,
+            keep_executions: row.try_get("keep_executions")?,
+            keep_days: row.try_get("keep_days")?,
+            signing_secret: row.try_get("signing_secret")?,
+            previous_signing_secret: row.try_get("previous_signing_secret")?,
+            previous_secret_expires_at: row.try_get("previous_secret_expires_at")?,
+            api_key_id: row.try_get("api_key_id")?,
+            notifications: row
+                .try_get::<Option<Json<Notifications>>, _>("notifications")?
+                .map(|json| json.0),
+            group_id: row.try_get("group_id")?,
+            labels: row
+                .try_get::<Option<Json<Labels>>, _>("labels")?
+                .map(|json| json.0),
+            paused_at: row.try_get("paused_at")?,
+            cron_expr: row.try_get("cron_expr")?,
+            retry_policy: row
+                .try_get::<Option<Json<ExecutionRetry>>, _>("retry_policy")?
+                .map(|json| json.0),
+            retry_attempt: row.try_get("retry_attempt")?,
+            priority: row.try_get("priority")?,
+            run_state: row.try_get("run_state")?,
+            misfire_policy: row.try_get("misfire_policy")?,
+            interval_mode: row.try_get("interval_mode")?,
+            ends_at: row.try_get("ends_at")?,
+            max_executions: row.try_get("max_executions")?,
+            run_count: row.try_get("run_count")?,
+            timezone: row.try_get("timezone")?,
+            rrule: row.try_get("rrule")?,
+            blackout_windows: row
+                .try_get::<Option<Json<Vec<BlackoutWindow>>>, _>("blackout_windows")?
+                .map(|json| json.0),
+            deferred_from: row.try_get("deferred_from")?,
+            execution_windows: row
+                .try_get::<Option<Json<Vec<ExecutionWindow>>>, _>("execution_windows")?
+                .map(|json| json.0),
+            depends_on: row.try_get("depends_on")?,
+            next_task_id: row.try_get("next_task_id")?,
+            run_if: row
+                .try_get::<Option<Json<RunCondition>>, _>("run_if")?
+                .map(|json| json.0),
+        }))
+    }
+
+    /// Retrieves up to `limit` non-deleted tasks whose trigger time is at or before `now`,
+    /// leaving out paused tasks and tasks of paused groups.
+    ///
+    /// Higher-priority tasks come first. Within a priority, due tasks are interleaved
+    /// round-robin across fairness lanes (currently the task type), oldest first within each
+    /// lane, so a flood of overdue tasks of one kind cannot starve the others while the
+    /// scheduler catches up in chunks.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The cut-off timestamp for due tasks.
+    /// * `limit` - Maximum number of tasks to return.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<Vec<Task>>` - The due tasks.
+    pub async fn get_due_tasks(&self, now: DateTime<Utc>, limit: i64) -> sqlx::Result<Vec<Task>> {
+        sqlx::query_as::<_, Task>(
+            r#"
+            SELECT id, name, task_type, trigger_at, interval_seconds, payload, deleted_at, request_id,
+                keep_executions, keep_days, signing_secret, previous_signing_secret,
+                previous_secret_expires_at, api_key_id, notifications, group_id, labels, paused_at,
+                cron_expr, retry_policy, retry_attempt, priority, run_state, misfire_policy,
+                interval_mode, ends_at, max_executions, run_count, timezone, rrule,
+                blackout_windows, deferred_from, execution_windows, depends_on,
+                next_task_id, run_if
+            FROM (
+                SELECT *,
+                    ROW_NUMBER() OVER (
+                        PARTITION BY priority, task_type ORDER BY trigger_at
+                    ) AS lane_rank
+                FROM tasks
+                WHERE deleted_at IS NULL AND paused_at IS NULL AND trigger_at <= ?
+                    AND (group_id IS NULL OR group_id NOT IN (
+                        SELECT id FROM task_groups WHERE paused_at IS NOT NULL
+                    ))
+            )
+            ORDER BY priority DESC, lane_rank ASC, trigger_at ASC
+            LIMIT ?
+            "#,
+        )
+        .bind(now)
+        .bind(limit)
+        .fetch_all(self.pool)
+        .await
+    }
+
+    /// Counts non-deleted tasks whose trigger time is at or before `now`, leaving out paused
+    /// tasks and tasks of paused groups.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The cut-off timestamp for due tasks.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<i64>` - The number of due tasks.
+    pub async fn count_due_tasks(&self, now: DateTime<Utc>) -> sqlx::Result<i64> {
+        sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*)
+            FROM tasks
+            WHERE deleted_at IS NULL AND paused_at IS NULL AND trigger_at <= ?
+                AND (group_id IS NULL OR group_id NOT IN (
+                    SELECT id FROM task_groups WHERE paused_at IS NOT NULL
+                ))
+            "#,
+        )
+        .bind(now)
+        .fetch_one(self.pool)
+        .await
+    }
+
+    /// Inserts an execution record, or finalizes it if a `running` record with the same ID
+    /// was written before the executor was invoked.
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - An executor that can execute the query (e.g., a connection or transaction).
+    /// * `exec` - The execution to insert or update.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<()>` - Result indicating success or failure of the operation.
+    pub async fn insert_execution_with_executor<'c, E>(
+        executor: E,
+        exec: &Execution,
+    ) -> sqlx::Result<()>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        sqlx::query(
+            r#"
+            INSERT INTO executions (
+                id, task_id, executed_at, output, status, payload, replay_of, duration_ms,
+                deferred_from, lag_ms, scheduled_for, started_at, finished_at,
+                attempt, failure_kind
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
+            ON CONFLICT(id) DO UPDATE SET
+                executed_at = excluded.executed_at,
+                output = excluded.output,
+                status = excluded.status,
+                payload = excluded.payload,
+                replay_of = excluded.replay_of,
+                duration_ms = excluded.duration_ms,
+                deferred_from = excluded.deferred_from,
+                lag_ms = excluded.lag_ms,
+                scheduled_for = excluded.scheduled_for,
+                started_at = excluded.started_at,
+                finished_at = excluded.finished_at,
+                attempt = excluded.attempt,
+                failure_kind = excluded.failure_kind
+            "#,
+        )
+        .bind(exec.id)
+        .bind(exec.task_id)
+        .bind(exec.executed_at)
+        .bind(Json(&exec.output))
+        .bind(exec.status.clone())
+        .bind(exec.payload.as_ref().map(Json))
+        .bind(exec.replay_of)
+        .bind(exec.duration_ms)
+        .bind(exec.deferred_from)
+        .bind(exec.lag_ms)
+        .bind(exec.scheduled_for)
+        .bind(exec.started_at)
+        .bind(exec.finished_at)
+        .bind(exec.attempt)
+        .bind(exec.failure_kind)
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Retrieves an execution by its ID.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The UUID of the execution.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<Option<Execution>>` - The execution if found.
+    pub async fn get_execution(&self, id: Uuid) -> sqlx::Result<Option<Execution>> {
+        sqlx::query_as::<_, Execution>(
+            r#"
+            SELECT id, task_id, executed_at, output, status, payload, replay_of, duration_ms,
+                deferred_from, lag_ms, scheduled_for, started_at, finished_at,
+                attempt, failure_kind
+            FROM executions
+            WHERE id = ?
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(self.pool)
+        .await
+    }
+
+    /// Retrieves the durations of a task's most recent successful executions, newest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `task_id` - The UUID of the task.
+    /// * `exclude` - An execution to leave out, normally the one being compared.
+    /// * `limit` - Maximum number of durations to return.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<Vec<i64>>` - The durations in milliseconds.
+    pub async fn recent_durations(
+        &self,
+        task_id: Uuid,
+        exclude: Uuid,
+        limit: i64,
+    ) -> sqlx::Result<Vec<i64>> {
+        sqlx::query_scalar(
+            r#"
+            SELECT duration_ms
+            FROM executions
+            WHERE task_id = ? AND id != ? AND status = 'success' AND duration_ms IS NOT NULL
+            ORDER BY executed_at DESC, id DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(task_id)
+        .bind(exclude)
+        .bind(limit)
+        .fetch_all(self.pool)
+        .await
+    }
+
+    /// Retrieves a task's latest successful execution.
+    ///
+    /// # Arguments
+    ///
+    /// * `task_id` - The UUID of the task.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<Option<Execution>>` - The execution, or `None` if the task never
+    ///   succeeded.
+    pub async fn last_success(&self, task_id: Uuid) -> sqlx::Result<Option<Execution>> {
+        sqlx::query_as::<_, Execution>(
+            r#"
+            SELECT id, task_id, executed_at, output, status, payload, replay_of, duration_ms,
+                deferred_from, lag_ms, scheduled_for, started_at, finished_at,
+                attempt, failure_kind
+            FROM executions
+            WHERE task_id = ? AND status = 'success'
+            ORDER BY executed_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(task_id)
+        .fetch_optional(self.pool)
+        .await
+    }
+
+    /// Retrieves the non-deleted tasks that depend on a task.
+    ///
+    /// # Arguments
+    ///
+    /// * `task_id` - The UUID of the task depended on.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<Vec<Task>>` - The dependent tasks.
+    pub async fn get_dependents(&self, task_id: Uuid) -> sqlx::Result<Vec<Task>> {
+        sqlx::query_as::<_, Task>(
+            r#"
+            SELECT id, name, task_type, trigger_at, interval_seconds, payload, deleted_at, request_id,
+                keep_executions, keep_days, signing_secret, previous_signing_secret,
+                previous_secret_expires_at, api_key_id, notifications, group_id, labels, paused_at,
+                cron_expr, retry_policy, retry_attempt, priority, run_state, misfire_policy,
+                interval_mode, ends_at, max_executions, run_count, timezone, rrule,
+                blackout_windows, deferred_from, execution_windows, depends_on,
+                next_task_id, run_if
+            FROM tasks
+            WHERE depends_on = ? AND deleted_at IS NULL
+            "#,
+        )
+        .bind(task_id)
+        .fetch_all(self.pool)
+        .await
+    }
+
+    /// Retrieves every execution still marked as running, oldest first.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<Vec<Execution>>` - The unfinished executions.
+    pub async fn get_running_executions(&self) -> sqlx::Result<Vec<Execution>> {
+        sqlx::query_as::<_, Execution>(
+            r#"
+            SELECT id, task_id, executed_at, output, status, payload, replay_of, duration_ms,
+                deferred_from, lag_ms, scheduled_for, started_at, finished_at,
+                attempt, failure_kind
+            FROM executions
+            WHERE status = 'running'
+            ORDER BY executed_at ASC, id ASC
+            "#,
+        )
+        .fetch_all(self.pool)
+        .await
+    }
+
+    /// Retrieves running executions whose task's claim expired before `now`, or is held by
+    /// `owner`.
+    ///
+    /// # Arguments
+    ///
+    /// * `owner` - An instance whose claims count as abandoned, normally the caller's own
+    ///   on start.
+    /// * `now` - Claims that expired before this time are abandoned.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<Vec<Execution>>` - The abandoned executions, oldest first.
+    pub async fn get_orphaned_executions(
+        &self,
+        owner: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> sqlx::Result<Vec<Execution>> {
+        sqlx::query_as::<_, Execution>(
+            r#"
+            SELECT e.id, e.task_id, e.executed_at, e.output, e.status, e.payload, e.replay_of,
+                e.duration_ms, e.deferred_from, e.lag_ms,
+                e.scheduled_for, e.started_at, e.finished_at, e.attempt, e.failure_kind
+            FROM executions e
+            JOIN tasks t ON t.id = e.task_id
+            WHERE e.status = 'running'
+                AND (t.run_state != 'running' OR t.claim_expires_at IS NULL
+                    OR t.claim_expires_at < ? OR t.claimed_by = ?)
+            ORDER BY e.executed_at ASC, e.id ASC
+            "#,
+        )
+        .bind(now)
+        .bind(owner)
+        .fetch_all(self.pool)
+        .await
+    }
+
+    /// Deletes a batch of finished executions that fall outside their task's retention
+    /// settings, oldest first.
+    ///
+    /// An execution is pruned if it is not among the task's `keep_executions` most recent
+    /// finished executions, or if it is more than `keep_days` days older than `now`. Tasks
+    /// without `keep_days` fall back to `default_keep_days`; tasks without either setting
+    /// are left alone.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The reference time for `keep_days`.
+    /// * `default_keep_days` - Retention in days for tasks that do not set their own.
+    /// * `limit` - Maximum number of executions to delete.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<u64>` - The number of executions deleted.
+    pub async fn prune_executions(
+        &self,
+        now: DateTime<Utc>,
+        default_keep_days: Option<i64>,
+        limit: i64,
+    ) -> sqlx::Result<u64> {
+        let result = sqlx::query(&format!(
+            "DELETE FROM executions WHERE id IN ({})",
+            EXPIRED_EXECUTION_IDS
+        ))
+        .bind(now)
+        .bind(default_keep_days)
+        .bind(limit)
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Retrieves a batch of the finished executions that [`Self::prune_executions`] would
+    /// delete, oldest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The reference time for `keep_days`.
+    /// * `default_keep_days` - Retention in days for tasks that do not set their own.
+    /// * `limit` - Maximum number of executions to return.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<Vec<Execution>>` - The expired executions.
+    pub async fn expired_executions(
+        &self,
+        now: DateTime<Utc>,
+        default_keep_days: Option<i64>,
+        limit: i64,
+    ) -> sqlx::Result<Vec<Execution>> {
+        sqlx::query_as::<_, Execution>(&format!(
+            r#"
+            SELECT id, task_id, executed_at, output, status, payload, replay_of, duration_ms,
+                deferred_from, lag_ms, scheduled_for, started_at, finished_at,
+                attempt, failure_kind
+            FROM executions
+            WHERE id IN ({})
+            ORDER BY executed_at, id
+            "#,
+            EXPIRED_EXECUTION_IDS
+        ))
+        .bind(now)
+        .bind(default_keep_days)
+        .bind(limit)
+        .fetch_all(self.pool)
+        .await
+    }
+
+    /// Permanently deletes the given executions in one statement.
+    ///
+    /// # Arguments
+    ///
+    /// * `ids` - The UUIDs of the executions to delete, bound as one JSON array.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<u64>` - Result containing the number of rows affected.
+    pub async fn delete_executions(&self, ids: &[Uuid]) -> sqlx::Result<u64> {
+        // UUIDs are stored as blobs, so they travel as hex strings and are decoded in SQL
+        let ids: Vec<String> = ids.iter().map(|id| id.simple().to_string()).collect();
+        let result = sqlx::query(
+            "DELETE FROM executions WHERE id IN (SELECT unhex(value) FROM json_each(?))",
+        )
+        .bind(Json(ids))
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Retrieves a page of a task's full execution records, newest first, using keyset
+    /// pagination.
+    ///
+    /// # Arguments
+    ///
+    /// * `task_id` - The UUID of the task whose executions to list.
+    /// * `after` - Optional cursor; only executions strictly older than it are returned.
+    /// * `limit` - Maximum number of executions to return.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<Vec<Execution>>` - The executions ordered by `(executed_at, id)`
+    ///   descending.
+    pub async fn list_execution_records(
+        &self,
+        task_id: Uuid,
+        after: Option<&ExecutionCursor>,
+        limit: i64,
+    ) -> sqlx::Result<Vec<Execution>> {
+        sqlx::query_as::<_, Execution>(
+            r#"
+            SELECT id, task_id, executed_at, output, status, payload, replay_of, duration_ms,
+                deferred_from, lag_ms, scheduled_for, started_at, finished_at,
+                attempt, failure_kind
+            FROM executions
+            WHERE task_id = ?1 AND (?2 IS NULL OR (executed_at, id) < (?2, ?3))
+            ORDER BY executed_at DESC, id DESC
+            LIMIT ?4
+            "#,
+        )
+        .bind(task_id)
+        .bind(after.map(|c| c.executed_at))
+        .bind(after.map(|c| c.id))
+        .bind(limit)
+        .fetch_all(self.pool)
+        .await
+    }
+
+    /// Retrieves a page of a task's executions, newest first, using keyset pagination.
+    ///
+    /// # Arguments
+    ///
+    /// * `task_id` - The UUID of the task whose executions to list.
+    /// * `after` - Optional cursor; only executions strictly older than it are returned.
+    /// * `limit` - Maximum number of executions to return.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<Vec<ExecutionSummary>>` - The executions (without output) ordered by
+    ///   `(executed_at, id)` descending.
+    pub async fn list_executions(
+        &self,
+        task_id: Uuid,
+        after: Option<&ExecutionCursor>,
+        limit: i64,
+    ) -> sqlx::Result<Vec<ExecutionSummary>> {
+        match after {
+            Some(cursor) => {
+                sqlx::query_as::<_, ExecutionSummary>(
+                    r#"
+                    SELECT id, task_id, executed_at, status
+                    FROM executions
+                    WHERE task_id = ? AND (executed_at, id) < (?, ?)
+                    ORDER BY executed_at DESC, id DESC
+                    LIMIT ?
+                    "#,
+                )
+                .bind(task_id)
+                .bind(cursor.executed_at)
+                .bind(cursor.id)
+                .bind(limit)
+                .fetch_all(self.pool)
+                .await
+            }
+            None => {
+                sqlx::query_as::<_, ExecutionSummary>(
+                    r#"
+                    SELECT id, task_id, executed_at, status
+                    FROM executions
+                    WHERE task_id = ?
+                    ORDER BY executed_at DESC, id DESC
+                    LIMIT ?
+                    "#,
+                )
+                .bind(task_id)
+                .bind(limit)
+                .fetch_all(self.pool)
+                .await
+            }
+        }
+    }
+
+    /// Streams every execution (optionally only those at or after `since`) in chronological
+    /// order, without buffering the result set.
+    ///
+    /// # Arguments
+    ///
+    /// * `since` - Optional lower bound for `executed_at`.
+    ///
+    /// # Returns
+    /// * `BoxStream<sqlx::Result<Execution>>` - A stream of executions.
+    pub fn stream_executions(
+        &self,
+        since: Option<DateTime<Utc>>,
+    ) -> BoxStream<'a, sqlx::Result<Execution>> {
+        sqlx::query_as::<_, Execution>(
+            r#"
+            SELECT id, task_id, executed_at, output, status, payload, replay_of, duration_ms,
+                deferred_from, lag_ms, scheduled_for, started_at, finished_at,
+                attempt, failure_kind
+            FROM executions
+            WHERE ?1 IS NULL OR executed_at >= ?1
+            ORDER BY executed_at ASC, id ASC
+            "#,
+        )
+        .bind(since)
+        .fetch(self.pool)
+    }
+
+    /// Retrieves summaries of the tasks matching `filter` (no payload), newest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - Lifecycle state and group to restrict the listing to.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<Vec<TaskSummary>>` - The task summaries.
+    pub async fn get_task_summaries(&self, filter: &TaskFilter) -> sqlx::Result<Vec<TaskSummary>> {
+        let state = match filter.state {
+            None => "",
+            Some(TaskState::Active) => "AND t.deleted_at IS NULL",
+            Some(TaskState::Deleted) => "AND t.deleted_at IS NOT NULL",
+        };
+
+        sqlx::query_as::<_, TaskSummary>(&format!(
+            r#"
+            SELECT t.id, t.name, t.task_type, t.trigger_at, t.interval_seconds, t.cron_expr,
+                t.deleted_at, t.group_id, t.paused_at, t.run_state, {}
+            FROM tasks t
+            {}
+            WHERE (?1 IS NULL OR t.group_id = ?1) {}
+            ORDER BY t.created_at DESC
+            "#,
+            TASK_RUNS_COLUMNS, LAST_RUN_JOIN, state
+        ))
+        .bind(filter.group_id)
+        .fetch_all(self.pool)
+        .await
+    }
+
+    /// Computes when a task runs next and how its latest finished run went.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The UUID of the task.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<TaskRuns>` - The run times; all empty for an unknown task.
+    pub async fn get_task_runs(&self, id: Uuid) -> sqlx::Result<TaskRuns> {
+        let runs = sqlx::query_as::<_, TaskRuns>(&format!(
+            r#"
+            SELECT {}
+            FROM tasks t
+            {}
+            WHERE t.id = ?
+            "#,
+            TASK_RUNS_COLUMNS, LAST_RUN_JOIN
+        ))
+        .bind(id)
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(runs.unwrap_or_default())
+    }
+
+    /// Retrieves the `limit` earliest trigger times of non-deleted, unpaused tasks outside
+    /// paused groups.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - Maximum number of triggers to return.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<Vec<(Uuid, DateTime<Utc>)>>` - Task ids with their trigger times, earliest first.
+    pub async fn get_upcoming_triggers(
+        &self,
+        limit: i64,
+    ) -> sqlx::Result<Vec<(Uuid, DateTime<Utc>)>> {
+        sqlx::query_as(
+            r#"
+            SELECT id, trigger_at
+            FROM tasks
+            WHERE deleted_at IS NULL AND paused_at IS NULL
+                AND (group_id IS NULL OR group_id NOT IN (
+                    SELECT id FROM task_groups WHERE paused_at IS NOT NULL
+                ))
+            ORDER BY trigger_at ASC
+            LIMIT ?
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(self.pool)
+        .await
+    }
+
+    /// Appends a schedule change to the outbox using the provided executor, normally the
+    /// transaction that made the change.
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - An executor that can execute the query (e.g., a connection or transaction).
+    /// * `event` - The schedule change to record.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<()>` - Result indicating success or failure of the operation.
+    pub async fn enqueue_schedule_event_with_executor<'c, E>(
+        executor: E,
+        event: &ScheduleEvent,
+    ) -> sqlx::Result<()>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        let (task_id, trigger_at) = match event {
+            ScheduleEvent::Upserted { id, trigger_at } => (*id, Some(*trigger_at)),
+            ScheduleEvent::Removed { id } => (*id, None),
+        };
+
+        sqlx::query("INSERT INTO schedule_outbox (task_id, trigger_at) VALUES (?, ?)")
+            .bind(task_id)
+            .bind(trigger_at)
+            .execute(executor)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Removes and returns up to `limit` of the oldest outbox entries, in write order.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - Maximum number of entries to consume.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<Vec<ScheduleEvent>>` - The consumed schedule changes.
+    pub async fn drain_schedule_events(&self, limit: i64) -> sqlx::Result<Vec<ScheduleEvent>> {
+        let mut tx = self.pool.begin().await?;
+
+        let rows: Vec<(i64, Uuid, Option<DateTime<Utc>>)> = sqlx::query_as(
+            "SELECT seq, task_id, trigger_at FROM schedule_outbox ORDER BY seq ASC LIMIT ?",
+        )
+        .bind(limit)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        if let Some((last_seq, _, _)) = rows.last() {
+            sqlx::query("DELETE FROM schedule_outbox WHERE seq <= ?")
+                .bind(last_seq)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(_, id, trigger_at)| match trigger_at {
+                Some(trigger_at) => ScheduleEvent::Upserted { id, trigger_at },
+                None => ScheduleEvent::Removed { id },
+            })
+            .collect())
+    }
+
+    pub async fn get_all_tasks(&self) -> sqlx::Result<Vec<Task>> {
+        Self::get_all_tasks_with_executor(self.pool).await
+    }
+
+    /// Retrieves every task, deleted ones included, newest first, using the provided executor.
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - An executor that can execute the query (e.g., a connection or transaction).
+    ///
+    /// # Returns
+    /// * `sqlx::Result<Vec<Task>>` - The tasks.
+    pub async fn get_all_tasks_with_executor<'c, E>(executor: E) -> sqlx::Result<Vec<Task>>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        sqlx::query_as::<_, Task>(
+            r#"
+            SELECT id, name, task_type, trigger_at, interval_seconds, payload, deleted_at, request_id,
+                keep_executions, keep_days, signing_secret, previous_signing_secret,
+                previous_secret_expires_at, api_key_id, notifications, group_id, labels, paused_at,
+                cron_expr, retry_policy, retry_attempt, priority, run_state, misfire_policy,
+                interval_mode, ends_at, max_executions, run_count, timezone, rrule,
+                blackout_windows, deferred_from, execution_windows, depends_on,
+                next_task_id, run_if
+            FROM tasks
+            ORDER BY created_at DESC
+            "#,
+        )
+        .fetch_all(executor)
+        .await
+    }
+
+    /// Retrieves every execution, oldest first, using the provided executor.
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - An executor that can execute the query (e.g., a connection or transaction).
+    ///
+    /// # Returns
+    /// * `sqlx::Result<Vec<Execution>>` - The executions.
+    pub async fn get_all_executions_with_executor<'c, E>(
+        executor: E,
+    ) -> sqlx::Result<Vec<Execution>>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        sqlx::query_as::<_, Execution>(
+            r#"
+            SELECT id, task_id, executed_at, output, status, payload, replay_of, duration_ms,
+                deferred_from, lag_ms, scheduled_for, started_at, finished_at,
+                attempt, failure_kind
+            FROM executions
+            ORDER BY executed_at ASC, id ASC
+            "#,
+        )
+        .fetch_all(executor)
+        .await
+    }
+
+    /// True if the database holds no tasks, groups or workflows, using the provided executor.
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - An executor that can execute the query (e.g., a connection or transaction).
+    ///
+    /// # Returns
+    /// * `sqlx::Result<bool>` - Whether the instance is empty.
+    pub async fn is_empty_with_executor<'c, E>(executor: E) -> sqlx::Result<bool>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        sqlx::query_scalar(
+            r#"
+            SELECT NOT EXISTS (SELECT 1 FROM tasks)
+                AND NOT EXISTS (SELECT 1 FROM task_groups)
+                AND NOT EXISTS (SELECT 1 FROM workflows)
+            "#,
+        )
+        .fetch_one(executor)
         .await
     }
 }
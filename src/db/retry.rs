@@ -0,0 +1,139 @@
+use std::future::Future;
+use std::time::Duration;
+
+/// SQLite primary result codes that indicate lock contention rather than a real failure.
+const SQLITE_BUSY: i64 = 5;
+const SQLITE_LOCKED: i64 = 6;
+/// Primary result codes for a database file that cannot currently be read or opened.
+const SQLITE_IOERR: i64 = 10;
+const SQLITE_CANTOPEN: i64 = 14;
+
+/// Retry-with-backoff strategy for `SQLITE_BUSY`/`SQLITE_LOCKED` errors.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Number of retries after the first attempt (0 disables retrying).
+    pub max_retries: u32,
+    /// Delay before the first retry; doubled on every subsequent retry.
+    pub base_delay: Duration,
+    /// Upper bound for a single backoff delay.
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(50),
+            max_delay: Duration::from_secs(2),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Backoff delay before retry number `attempt` (1-based).
+    pub fn delay_for(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+}
+
+/// Returns true if the error is a transient SQLite lock error worth retrying.
+pub fn is_busy(err: &sqlx::Error) -> bool {
+    let Some(db_err) = err.as_database_error() else {
+        return false;
+    };
+
+    // SQLite reports extended result codes; the primary code lives in the low byte
+    db_err
+        .code()
+        .and_then(|code| code.parse::<i64>().ok())
+        .map(|code| matches!(code & 0xff, SQLITE_BUSY | SQLITE_LOCKED))
+        .unwrap_or(false)
+}
+
+/// Returns true if the error suggests the database is temporarily unreachable (lock
+/// contention, I/O failure, exhausted pool) rather than rejecting the statement itself.
+pub fn is_transient(err: &sqlx::Error) -> bool {
+    if is_busy(err) {
+        return true;
+    }
+
+    match err {
+        sqlx::Error::Io(_) | sqlx::Error::PoolTimedOut | sqlx::Error::WorkerCrashed => true,
+        sqlx::Error::Database(db_err) => db_err
+            .code()
+            .and_then(|code| code.parse::<i64>().ok())
+            .map(|code| matches!(code & 0xff, SQLITE_IOERR | SQLITE_CANTOPEN))
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+/// Runs `op`, retrying with exponential backoff while it fails with a busy/locked error.
+///
+/// # Arguments
+///
+/// * `policy` - The retry policy to apply.
+/// * `op` - Closure producing a fresh attempt of the operation.
+///
+/// # Returns
+/// * `sqlx::Result<T>` - The first successful result, or the last error.
+pub async fn with_retry<T, F, Fut>(policy: &RetryPolicy, mut op: F) -> sqlx::Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = sqlx::Result<T>>,
+{
+    let mut attempt = 0;
+
+    loop {
+        match op().await {
+            Err(e) if attempt < policy.max_retries && is_busy(&e) => {
+                attempt += 1;
+                let delay = policy.delay_for(attempt);
+                tracing::warn!(attempt, ?delay, "Database busy, retrying: {}", e);
+                tokio::time::sleep(delay).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Exponential backoff with jitter for waiting out a database outage.
+///
+/// Each delay is drawn uniformly from the upper half of the current exponential step, so
+/// several instances recovering from the same outage do not hammer the database in lockstep.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    base: Duration,
+    max: Duration,
+    attempt: u32,
+}
+
+impl Backoff {
+    pub fn new(base: Duration, max: Duration) -> Self {
+        Self {
+            base,
+            max,
+            attempt: 0,
+        }
+    }
+
+    /// Number of consecutive failures since the last reset.
+    pub fn attempts(&self) -> u32 {
+        self.attempt
+    }
+
+    /// Delay before the next attempt; grows with every call until `reset`.
+    pub fn next_delay(&mut self) -> Duration {
+        let factor = 2u32.saturating_pow(self.attempt);
+        let step = self.base.saturating_mul(factor).min(self.max);
+        self.attempt = self.attempt.saturating_add(1);
+
+        let half = step / 2;
+        half + half.mul_f64(rand::random::<f64>())
+    }
+
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+    }
+}
@@ -0,0 +1,95 @@
+use crate::domain::StoredSecret;
+use sqlx::SqlitePool;
+
+pub struct SecretRepository<'a> {
+    pub pool: &'a SqlitePool,
+}
+
+impl<'a> SecretRepository<'a> {
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Stores a secret, replacing the value of an existing one with the same name while
+    /// keeping its creation time.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret` - The encrypted secret.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<()>` - Result indicating success or failure of the operation.
+    pub async fn put(&self, secret: &StoredSecret) -> sqlx::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO secrets (name, ciphertext, created_at, updated_at)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(name) DO UPDATE SET
+                ciphertext = excluded.ciphertext,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&secret.name)
+        .bind(&secret.ciphertext)
+        .bind(secret.created_at)
+        .bind(secret.updated_at)
+        .execute(self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Retrieves a secret by its name.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the secret.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<Option<StoredSecret>>` - The secret if found.
+    pub async fn find(&self, name: &str) -> sqlx::Result<Option<StoredSecret>> {
+        sqlx::query_as::<_, StoredSecret>(
+            r#"
+            SELECT name, ciphertext, created_at, updated_at
+            FROM secrets
+            WHERE name = ?
+            "#,
+        )
+        .bind(name)
+        .fetch_optional(self.pool)
+        .await
+    }
+
+    /// Retrieves every secret, ordered by name.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<Vec<StoredSecret>>` - All stored secrets.
+    pub async fn list(&self) -> sqlx::Result<Vec<StoredSecret>> {
+        sqlx::query_as::<_, StoredSecret>(
+            r#"
+            SELECT name, ciphertext, created_at, updated_at
+            FROM secrets
+            ORDER BY name ASC
+            "#,
+        )
+        .fetch_all(self.pool)
+        .await
+    }
+
+    /// Deletes a secret.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the secret.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<u64>` - Result containing the number of rows affected.
+    pub async fn delete(&self, name: &str) -> sqlx::Result<u64> {
+        let result = sqlx::query("DELETE FROM secrets WHERE name = ?")
+            .bind(name)
+            .execute(self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,1026 @@
+use crate::db::api_keys::ApiKeyRepository;
+use crate::db::groups::GroupRepository;
+use crate::db::leases::LeaseRepository;
+use crate::db::queries::TaskRepository;
+use crate::db::retry::RetryPolicy;
+use crate::db::secrets::SecretRepository;
+use crate::db::trigger_links::TriggerLinkRepository;
+use crate::db::workflows::WorkflowRepository;
+use crate::domain::{
+    ApiKey, Execution, ExecutionCursor, ExecutionStatus, ExecutionSummary, GroupStats, RunState,
+    SNAPSHOT_VERSION, Scope, Snapshot, StoredSecret, Task, TaskFilter, TaskGroup, TaskPatch,
+    TaskRuns, TaskSelector, TaskSummary, TriggerLink, Workflow, WorkflowRun,
+};
+use crate::scheduler::ScheduleEvent;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::stream::BoxStream;
+use sqlx::{Acquire, SqlitePool};
+use uuid::Uuid;
+
+/// What happens to a task once its execution has been recorded.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FollowUp {
+    /// Soft delete the task (one-off tasks).
+    Delete,
+    /// Move the task's trigger to the given time (recurring tasks), ending any retries.
+    Reschedule(DateTime<Utc>),
+    /// Retry the failed run at the given time; `attempt` counts the run's retries from 1.
+    Retry { at: DateTime<Utc>, attempt: i64 },
+    /// Leave the task as it is.
+    Keep,
+}
+
+/// An execution record together with the schedule change it implies.
+#[derive(Debug, Clone)]
+pub struct ExecutionWrite {
+    pub execution: Execution,
+    pub follow_up: FollowUp,
+}
+
+impl ExecutionWrite {
+    /// The run state the write leaves its task in.
+    pub fn run_state(&self) -> RunState {
+        match (&self.execution.status, &self.follow_up) {
+            (ExecutionStatus::Running, _) => RunState::Running,
+            (_, FollowUp::Retry { .. }) => RunState::Pending,
+            (
+                ExecutionStatus::Success | ExecutionStatus::Skipped | ExecutionStatus::Cancelled,
+                FollowUp::Delete,
+            ) => RunState::Completed,
+            (
+                ExecutionStatus::Success | ExecutionStatus::Skipped | ExecutionStatus::Cancelled,
+                _,
+            ) => RunState::Pending,
+            (ExecutionStatus::Failure | ExecutionStatus::Interrupted, _) => RunState::Failed,
+        }
+    }
+
+    /// Whether the write finishes one of the task's scheduled runs, counting towards its
+    /// `max_executions`.
+    pub fn finishes_run(&self) -> bool {
+        matches!(self.follow_up, FollowUp::Reschedule(_) | FollowUp::Delete)
+    }
+}
+
+/// A scheduler instance's hold on the tasks it runs, renewed for as long as they run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Claim {
+    /// The instance holding the claim, see `INSTANCE_ID`.
+    pub owner: String,
+    /// When the claim lapses unless it is renewed.
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Result of persisting a single [`ExecutionWrite`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteOutcome {
+    /// The execution and its follow-up were written.
+    Recorded,
+    /// The task no longer exists (deleted while executing); nothing was written for it.
+    TaskMissing,
+    /// Another run holds the task's claim; nothing was written for it.
+    AlreadyRunning,
+}
+
+/// Persistence operations the service and scheduler depend on.
+///
+/// [`SqliteTaskStore`] is the default implementation, with a PostgreSQL one behind the
+/// `postgres` feature; an in-memory store is available to tests and, with the `test-util`
+/// feature, to downstream crates.
+#[async_trait]
+pub trait TaskStore: Send + Sync {
+    /// Inserts a task and queues an `Upserted` schedule event for it in the same write.
+    async fn create_task(&self, task: &Task) -> sqlx::Result<()>;
+
+    async fn get_task(&self, id: Uuid) -> sqlx::Result<Option<Task>>;
+
+    /// Soft deletes a task, returning the number of rows affected. A `Removed` schedule event
+    /// is queued in the same write if the task existed.
+    async fn delete_task(&self, id: Uuid) -> sqlx::Result<u64>;
+
+    /// Permanently deletes a task and its executions, returning the number of tasks removed.
+    /// A `Removed` schedule event is queued in the same write if the task existed.
+    async fn purge_task(&self, id: Uuid) -> sqlx::Result<u64>;
+
+    /// Permanently deletes up to `limit` tasks soft deleted before `cutoff`, longest deleted
+    /// first, together with their executions. Returns the number of tasks removed.
+    async fn purge_deleted_tasks(&self, cutoff: DateTime<Utc>, limit: i64) -> sqlx::Result<u64>;
+
+    /// The tasks [`TaskStore::purge_deleted_tasks`] would remove, longest deleted first, so
+    /// their executions can be archived before [`TaskStore::purge_task`] removes each.
+    async fn deleted_task_ids(&self, cutoff: DateTime<Utc>, limit: i64) -> sqlx::Result<Vec<Uuid>>;
+
+    /// Number of live tasks created with the given API key.
+    async fn count_active_tasks(&self, api_key_id: Uuid) -> sqlx::Result<i64>;
+
+    /// Replaces a live task's signing secret, keeping the current one valid until
+    /// `previous_expires_at`. Returns the number of rows affected.
+    async fn rotate_signing_secret(
+        &self,
+        id: Uuid,
+        secret: &str,
+        previous_expires_at: DateTime<Utc>,
+    ) -> sqlx::Result<u64>;
+
+    /// Consumes up to `limit` queued schedule events, oldest first.
+    async fn drain_outbox(&self, limit: i64) -> sqlx::Result<Vec<ScheduleEvent>>;
+
+    /// Up to `limit` live tasks due at `now`, interleaved fairly across task types. Tasks of
+    /// paused groups are never due.
+    async fn get_due_tasks(&self, now: DateTime<Utc>, limit: i64) -> sqlx::Result<Vec<Task>>;
+
+    async fn count_due_tasks(&self, now: DateTime<Utc>) -> sqlx::Result<i64>;
+
+    /// The `limit` earliest live triggers outside paused groups, ordered by trigger time.
+    async fn get_upcoming_triggers(&self, limit: i64) -> sqlx::Result<Vec<(Uuid, DateTime<Utc>)>>;
+
+    /// Summaries of the tasks matching `filter`, newest first.
+    async fn get_task_summaries(&self, filter: &TaskFilter) -> sqlx::Result<Vec<TaskSummary>>;
+
+    /// When a task runs next and how its latest finished run went.
+    async fn get_task_runs(&self, id: Uuid) -> sqlx::Result<TaskRuns>;
+
+    /// A page of a task's executions, newest first, strictly after `after` if given.
+    async fn list_executions(
+        &self,
+        task_id: Uuid,
+        after: Option<&ExecutionCursor>,
+        limit: i64,
+    ) -> sqlx::Result<Vec<ExecutionSummary>>;
+
+    /// Like [`TaskStore::list_executions`], but with the executions' full records.
+    async fn list_execution_records(
+        &self,
+        task_id: Uuid,
+        after: Option<&ExecutionCursor>,
+        limit: i64,
+    ) -> sqlx::Result<Vec<Execution>>;
+
+    /// Every execution at or after `since`, oldest first.
+    fn stream_executions(
+        &self,
+        since: Option<DateTime<Utc>>,
+    ) -> BoxStream<'_, sqlx::Result<Execution>>;
+
+    /// Writes `running` records for executions that are about to start, atomically, returning
+    /// one outcome per execution.
+    ///
+    /// Each task is claimed along with its record, so no other run starts it at the same
+    /// time. A claim is only taken over once it has expired and its run has been finalized
+    /// (see [`TaskStore::orphaned_executions`]). An execution whose task has disappeared or
+    /// is claimed by another run is skipped and must not be run.
+    async fn begin_executions(
+        &self,
+        executions: &[Execution],
+        claim: &Claim,
+    ) -> sqlx::Result<Vec<WriteOutcome>>;
+
+    /// Extends the claims `claim.owner` holds on running tasks to `claim.expires_at`,
+    /// returning how many were renewed.
+    async fn renew_claims(&self, task_ids: &[Uuid], claim: &Claim) -> sqlx::Result<u64>;
+
+    async fn get_execution(&self, id: Uuid) -> sqlx::Result<Option<Execution>>;
+
+    /// Durations in milliseconds of a task's latest successful executions other than
+    /// `exclude`, newest first.
+    async fn recent_durations(
+        &self,
+        task_id: Uuid,
+        exclude: Uuid,
+        limit: i64,
+    ) -> sqlx::Result<Vec<i64>>;
+
+    /// The task's latest successful execution, if it ever succeeded.
+    async fn last_success(&self, task_id: Uuid) -> sqlx::Result<Option<Execution>>;
+
+    /// Live tasks that depend on the task.
+    async fn get_dependents(&self, task_id: Uuid) -> sqlx::Result<Vec<Task>>;
+
+    /// Executions still marked as running, oldest first.
+    async fn running_executions(&self) -> sqlx::Result<Vec<Execution>>;
+
+    /// Running executions nobody is running anymore, oldest first: those whose task's claim
+    /// expired before `now`, and those claimed by `owner` if given.
+    async fn orphaned_executions(
+        &self,
+        owner: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> sqlx::Result<Vec<Execution>>;
+
+    /// Deletes up to `limit` finished executions outside their task's retention settings,
+    /// oldest first, returning how many were removed. Tasks without `keep_days` keep their
+    /// executions for `default_keep_days`, if given.
+    async fn prune_executions(
+        &self,
+        now: DateTime<Utc>,
+        default_keep_days: Option<i64>,
+        limit: i64,
+    ) -> sqlx::Result<u64>;
+
+    /// The executions [`TaskStore::prune_executions`] would delete, oldest first, so they
+    /// can be archived before [`TaskStore::delete_executions`] removes them.
+    async fn expired_executions(
+        &self,
+        now: DateTime<Utc>,
+        default_keep_days: Option<i64>,
+        limit: i64,
+    ) -> sqlx::Result<Vec<Execution>>;
+
+    /// Permanently deletes the given executions in one write, returning how many existed.
+    async fn delete_executions(&self, ids: &[Uuid]) -> sqlx::Result<u64>;
+
+    /// Persists executions and their follow-ups atomically, returning one outcome per write.
+    /// A write replaces the `running` record of the same execution, if any.
+    ///
+    /// A write whose task has disappeared is skipped without failing the rest of the group.
+    async fn record_executions(&self, writes: &[ExecutionWrite])
+    -> sqlx::Result<Vec<WriteOutcome>>;
+    /// Stores a new API key under the hash of its plaintext.
+    async fn create_api_key(&self, key: &ApiKey, key_hash: &str) -> sqlx::Result<()>;
+
+    /// The live (not revoked) key with the given hash, if any.
+    async fn find_api_key(&self, key_hash: &str) -> sqlx::Result<Option<ApiKey>>;
+
+    /// Every key, revoked ones included, oldest first.
+    async fn list_api_keys(&self) -> sqlx::Result<Vec<ApiKey>>;
+
+    /// Changes a live key's scope, returning the number of rows affected.
+    async fn update_api_key_scope(&self, id: Uuid, scope: Scope) -> sqlx::Result<u64>;
+
+    /// Revokes a live key, returning the number of rows affected.
+    async fn revoke_api_key(&self, id: Uuid, at: DateTime<Utc>) -> sqlx::Result<u64>;
+
+    /// Stores a secret, replacing the value of one with the same name but keeping its
+    /// creation time.
+    async fn put_secret(&self, secret: &StoredSecret) -> sqlx::Result<()>;
+
+    async fn get_secret(&self, name: &str) -> sqlx::Result<Option<StoredSecret>>;
+
+    /// Every secret, ordered by name.
+    async fn list_secrets(&self) -> sqlx::Result<Vec<StoredSecret>>;
+
+    /// Deletes a secret, returning the number of rows affected.
+    async fn delete_secret(&self, name: &str) -> sqlx::Result<u64>;
+
+    /// Takes the lease `name` for `holder` until `expires_at`, or renews it. Succeeds if the
+    /// lease is free, expired before `now` or already held by `holder`; returns false if
+    /// another holder has it.
+    async fn acquire_lease(
+        &self,
+        name: &str,
+        holder: &str,
+        now: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> sqlx::Result<bool>;
+
+    /// Gives up a lease `holder` holds, returning the number of rows affected.
+    async fn release_lease(&self, name: &str, holder: &str) -> sqlx::Result<u64>;
+
+    async fn create_trigger_link(&self, link: &TriggerLink) -> sqlx::Result<()>;
+
+    async fn get_trigger_link(&self, id: Uuid) -> sqlx::Result<Option<TriggerLink>>;
+
+    /// Marks an unused, unexpired link as used. Returns false if it was already used or has
+    /// expired, so each link is redeemed at most once.
+    async fn redeem_trigger_link(&self, id: Uuid, now: DateTime<Utc>) -> sqlx::Result<bool>;
+
+    async fn create_workflow(&self, workflow: &Workflow) -> sqlx::Result<()>;
+
+    async fn get_workflow(&self, id: Uuid) -> sqlx::Result<Option<Workflow>>;
+
+    /// Every workflow, ordered by name.
+    async fn list_workflows(&self) -> sqlx::Result<Vec<Workflow>>;
+
+    async fn create_workflow_run(&self, run: &WorkflowRun) -> sqlx::Result<()>;
+
+    async fn get_workflow_run(&self, id: Uuid) -> sqlx::Result<Option<WorkflowRun>>;
+
+    /// A workflow's latest `limit` runs, newest first.
+    async fn list_workflow_runs(
+        &self,
+        workflow_id: Uuid,
+        limit: i64,
+    ) -> sqlx::Result<Vec<WorkflowRun>>;
+
+    /// Runs still in progress, oldest first.
+    async fn unfinished_workflow_runs(&self) -> sqlx::Result<Vec<WorkflowRun>>;
+
+    /// Saves a run's status, node states and finish time, returning the number of rows
+    /// affected.
+    async fn update_workflow_run(&self, run: &WorkflowRun) -> sqlx::Result<u64>;
+
+    async fn create_group(&self, group: &TaskGroup) -> sqlx::Result<()>;
+
+    async fn get_group(&self, id: Uuid) -> sqlx::Result<Option<TaskGroup>>;
+
+    /// Every group, ordered by name.
+    async fn list_groups(&self) -> sqlx::Result<Vec<TaskGroup>>;
+
+    /// Pauses a group at `paused_at`, or resumes it if `None`, returning the number of rows
+    /// affected. `Removed` or `Upserted` schedule events for the group's live tasks are queued
+    /// in the same write.
+    async fn set_group_paused(
+        &self,
+        id: Uuid,
+        paused_at: Option<DateTime<Utc>>,
+    ) -> sqlx::Result<u64>;
+
+    /// Moves a task's trigger and queues an `Upserted` schedule event for it in the same
+    /// write, returning the number of rows affected.
+    async fn reschedule_task(&self, id: Uuid, trigger_at: DateTime<Utc>) -> sqlx::Result<u64>;
+
+    /// Moves a live task's trigger to `until`, the end of a blackout window, recording
+    /// `deferred_from` as the time the run was due unless an earlier deferral already did.
+    /// Queues an `Upserted` schedule event in the same write and returns the number of rows
+    /// affected.
+    async fn defer_task(
+        &self,
+        id: Uuid,
+        until: DateTime<Utc>,
+        deferred_from: DateTime<Utc>,
+    ) -> sqlx::Result<u64>;
+
+    /// Replaces a live task's name, schedule and payload with those of `task` and queues an
+    /// `Upserted` schedule event for it in the same write, returning the number of rows
+    /// affected.
+    async fn update_task(&self, task: &Task) -> sqlx::Result<u64>;
+
+    /// Changes only the fields a patch sets on a live task and queues an `Upserted` schedule
+    /// event for it in the same write, returning the number of rows affected.
+    async fn patch_task(&self, id: Uuid, patch: &TaskPatch) -> sqlx::Result<u64>;
+
+    /// Pauses the live tasks a selector matches at `paused_at`, or resumes them if `None`,
+    /// returning how many changed state. Their schedule events are queued in the same write.
+    async fn set_tasks_paused(
+        &self,
+        selector: &TaskSelector,
+        paused_at: Option<DateTime<Utc>>,
+    ) -> sqlx::Result<u64>;
+
+    /// Task and execution counts of a group.
+    async fn group_stats(&self, id: Uuid) -> sqlx::Result<GroupStats>;
+
+    /// Reads every group, task and workflow, and the execution history if asked to, from
+    /// one consistent view of the store.
+    async fn export_snapshot(
+        &self,
+        taken_at: DateTime<Utc>,
+        include_executions: bool,
+    ) -> sqlx::Result<Snapshot>;
+
+    /// Writes a snapshot's groups, tasks, workflows and executions in one write, queuing
+    /// `Upserted` schedule events for the tasks that are due to run. Returns false without
+    /// writing anything if the store already holds tasks, groups or workflows.
+    async fn restore_snapshot(&self, snapshot: &Snapshot) -> sqlx::Result<bool>;
+}
+
+/// True if a restored task should be handed to the scheduler: it is live and neither it nor
+/// its group is paused.
+pub(crate) fn restored_task_is_scheduled(snapshot: &Snapshot, task: &Task) -> bool {
+    task.deleted_at.is_none()
+        && task.paused_at.is_none()
+        && !task.group_id.is_some_and(|group_id| {
+            snapshot
+                .groups
+                .iter()
+                .any(|g| g.id == group_id && g.paused_at.is_some())
+        })
+}
+
+/// [`TaskStore`] backed by a SQLite pool.
+///
+/// Busy retries are left to the caller so that they apply uniformly across stores; the
+/// underlying repository is therefore built with retrying disabled.
+#[derive(Debug, Clone)]
+pub struct SqliteTaskStore {
+    pool: SqlitePool,
+}
+
+impl SqliteTaskStore {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    fn repo(&self) -> TaskRepository<'_> {
+        TaskRepository::new(&self.pool).with_retry(RetryPolicy {
+            max_retries: 0,
+            ..RetryPolicy::default()
+        })
+    }
+}
+
+#[async_trait]
+impl TaskStore for SqliteTaskStore {
+    async fn create_task(&self, task: &Task) -> sqlx::Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        TaskRepository::create_task_with_executor(&mut *tx, task).await?;
+        let event = ScheduleEvent::Upserted {
+            id: task.id,
+            trigger_at: task.trigger_at,
+        };
+        TaskRepository::enqueue_schedule_event_with_executor(&mut *tx, &event).await?;
+
+        tx.commit().await
+    }
+
+    async fn get_task(&self, id: Uuid) -> sqlx::Result<Option<Task>> {
+        self.repo().get_task(id).await
+    }
+
+    async fn delete_task(&self, id: Uuid) -> sqlx::Result<u64> {
+        let mut tx = self.pool.begin().await?;
+
+        let rows = TaskRepository::delete_task_with_executor(&mut *tx, id).await?;
+        if rows > 0 {
+            TaskRepository::enqueue_schedule_event_with_executor(
+                &mut *tx,
+                &ScheduleEvent::Removed { id },
+            )
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(rows)
+    }
+
+    async fn purge_task(&self, id: Uuid) -> sqlx::Result<u64> {
+        let mut tx = self.pool.begin().await?;
+
+        TaskRepository::delete_executions_for_task_with_executor(&mut *tx, id).await?;
+        let rows = TaskRepository::purge_task_with_executor(&mut *tx, id).await?;
+        if rows > 0 {
+            TaskRepository::enqueue_schedule_event_with_executor(
+                &mut *tx,
+                &ScheduleEvent::Removed { id },
+            )
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(rows)
+    }
+
+    async fn purge_deleted_tasks(&self, cutoff: DateTime<Utc>, limit: i64) -> sqlx::Result<u64> {
+        let mut tx = self.pool.begin().await?;
+
+        let ids = TaskRepository::deleted_task_ids_with_executor(&mut *tx, cutoff, limit).await?;
+        let mut rows = 0;
+        for id in ids {
+            TaskRepository::delete_executions_for_task_with_executor(&mut *tx, id).await?;
+            rows += TaskRepository::purge_task_with_executor(&mut *tx, id).await?;
+        }
+
+        tx.commit().await?;
+        Ok(rows)
+    }
+
+    async fn deleted_task_ids(&self, cutoff: DateTime<Utc>, limit: i64) -> sqlx::Result<Vec<Uuid>> {
+        TaskRepository::deleted_task_ids_with_executor(&self.pool, cutoff, limit).await
+    }
+
+    async fn count_active_tasks(&self, api_key_id: Uuid) -> sqlx::Result<i64> {
+        self.repo().count_active_tasks_for_key(api_key_id).await
+    }
+
+    async fn rotate_signing_secret(
+        &self,
+        id: Uuid,
+        secret: &str,
+        previous_expires_at: DateTime<Utc>,
+    ) -> sqlx::Result<u64> {
+        self.repo()
+            .rotate_signing_secret(id, secret, previous_expires_at)
+            .await
+    }
+
+    async fn drain_outbox(&self, limit: i64) -> sqlx::Result<Vec<ScheduleEvent>> {
+        self.repo().drain_schedule_events(limit).await
+    }
+
+    async fn get_due_tasks(&self, now: DateTime<Utc>, limit: i64) -> sqlx::Result<Vec<Task>> {
+        self.repo().get_due_tasks(now, limit).await
+    }
+
+    async fn count_due_tasks(&self, now: DateTime<Utc>) -> sqlx::Result<i64> {
+        self.repo().count_due_tasks(now).await
+    }
+
+    async fn get_upcoming_triggers(&self, limit: i64) -> sqlx::Result<Vec<(Uuid, DateTime<Utc>)>> {
+        self.repo().get_upcoming_triggers(limit).await
+    }
+
+    async fn get_task_summaries(&self, filter: &TaskFilter) -> sqlx::Result<Vec<TaskSummary>> {
+        self.repo().get_task_summaries(filter).await
+    }
+
+    async fn get_task_runs(&self, id: Uuid) -> sqlx::Result<TaskRuns> {
+        self.repo().get_task_runs(id).await
+    }
+
+    async fn list_executions(
+        &self,
+        task_id: Uuid,
+        after: Option<&ExecutionCursor>,
+        limit: i64,
+    ) -> sqlx::Result<Vec<ExecutionSummary>> {
+        self.repo().list_executions(task_id, after, limit).await
+    }
+
+    async fn list_execution_records(
+        &self,
+        task_id: Uuid,
+        after: Option<&ExecutionCursor>,
+        limit: i64,
+    ) -> sqlx::Result<Vec<Execution>> {
+        self.repo()
+            .list_execution_records(task_id, after, limit)
+            .await
+    }
+
+    fn stream_executions(
+        &self,
+        since: Option<DateTime<Utc>>,
+    ) -> BoxStream<'_, sqlx::Result<Execution>> {
+        self.repo().stream_executions(since)
+    }
+
+    async fn begin_executions(
+        &self,
+        executions: &[Execution],
+        claim: &Claim,
+    ) -> sqlx::Result<Vec<WriteOutcome>> {
+        let mut tx = self.pool.begin().await?;
+        let mut outcomes = Vec::with_capacity(executions.len());
+
+        for execution in executions {
+            let mut savepoint = tx.begin().await?;
+            match TaskRepository::insert_execution_with_executor(&mut *savepoint, execution).await {
+                Ok(()) => {
+                    let claimed =
+                        TaskRepository::claim_with_executor(&mut *savepoint, execution, claim)
+                            .await?;
+                    if claimed == 0 {
+                        savepoint.rollback().await?;
+                        outcomes.push(WriteOutcome::AlreadyRunning);
+                        continue;
+                    }
+                    savepoint.commit().await?;
+                    outcomes.push(WriteOutcome::Recorded);
+                }
+                Err(sqlx::Error::Database(e)) if e.is_foreign_key_violation() => {
+                    savepoint.rollback().await?;
+                    outcomes.push(WriteOutcome::TaskMissing);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        tx.commit().await?;
+
+        Ok(outcomes)
+    }
+
+    async fn renew_claims(&self, task_ids: &[Uuid], claim: &Claim) -> sqlx::Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        let mut renewed = 0;
+        for id in task_ids {
+            renewed += TaskRepository::renew_claim_with_executor(&mut *tx, *id, claim).await?;
+        }
+        tx.commit().await?;
+
+        Ok(renewed)
+    }
+
+    async fn get_execution(&self, id: Uuid) -> sqlx::Result<Option<Execution>> {
+        self.repo().get_execution(id).await
+    }
+
+    async fn recent_durations(
+        &self,
+        task_id: Uuid,
+        exclude: Uuid,
+        limit: i64,
+    ) -> sqlx::Result<Vec<i64>> {
+        self.repo().recent_durations(task_id, exclude, limit).await
+    }
+
+    async fn last_success(&self, task_id: Uuid) -> sqlx::Result<Option<Execution>> {
+        self.repo().last_success(task_id).await
+    }
+
+    async fn get_dependents(&self, task_id: Uuid) -> sqlx::Result<Vec<Task>> {
+        self.repo().get_dependents(task_id).await
+    }
+
+    async fn running_executions(&self) -> sqlx::Result<Vec<Execution>> {
+        self.repo().get_running_executions().await
+    }
+
+    async fn orphaned_executions(
+        &self,
+        owner: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> sqlx::Result<Vec<Execution>> {
+        self.repo().get_orphaned_executions(owner, now).await
+    }
+
+    async fn prune_executions(
+        &self,
+        now: DateTime<Utc>,
+        default_keep_days: Option<i64>,
+        limit: i64,
+    ) -> sqlx::Result<u64> {
+        self.repo()
+            .prune_executions(now, default_keep_days, limit)
+            .await
+    }
+
+    async fn expired_executions(
+        &self,
+        now: DateTime<Utc>,
+        default_keep_days: Option<i64>,
+        limit: i64,
+    ) -> sqlx::Result<Vec<Execution>> {
+        self.repo()
+            .expired_executions(now, default_keep_days, limit)
+            .await
+    }
+
+    async fn delete_executions(&self, ids: &[Uuid]) -> sqlx::Result<u64> {
+        self.repo().delete_executions(ids).await
+    }
+
+    /// Writes the whole group in one transaction, each write inside its own savepoint so a
+    /// foreign key violation (task deleted during execution) only rolls back that write.
+    async fn record_executions(
+        &self,
+        writes: &[ExecutionWrite],
+    ) -> sqlx::Result<Vec<WriteOutcome>> {
+        let mut tx = self.pool.begin().await?;
+        let mut outcomes = Vec::with_capacity(writes.len());
+
+        for write in writes {
+            let task_id = write.execution.task_id;
+            let mut savepoint = tx.begin().await?;
+
+            let db_result =
+                TaskRepository::insert_execution_with_executor(&mut *savepoint, &write.execution)
+                    .await;
+
+            match db_result {
+                Ok(()) => {}
+                // Catch foreign key violation if task was deleted during processing here
+                Err(sqlx::Error::Database(e)) if e.is_foreign_key_violation() => {
+                    savepoint.rollback().await?;
+                    outcomes.push(WriteOutcome::TaskMissing);
+                    continue;
+                }
+                Err(e) => return Err(e),
+            }
+
+            match write.follow_up {
+                FollowUp::Delete => {
+                    TaskRepository::delete_task_with_executor(&mut *savepoint, task_id).await?;
+                }
+                FollowUp::Reschedule(next_trigger) => {
+                    TaskRepository::update_trigger_with_executor(
+                        &mut *savepoint,
+                        task_id,
+                        next_trigger,
+                    )
+                    .await?;
+                }
+                FollowUp::Retry { at, attempt } => {
+                    TaskRepository::schedule_retry_with_executor(
+                        &mut *savepoint,
+                        task_id,
+                        at,
+                        attempt,
+                    )
+                    .await?;
+                }
+                FollowUp::Keep => {}
+            }
+            TaskRepository::set_run_state_with_executor(
+                &mut *savepoint,
+                task_id,
+                write.run_state(),
+            )
+            .await?;
+            if write.finishes_run() {
+                TaskRepository::count_run_with_executor(&mut *savepoint, task_id).await?;
+            }
+
+            savepoint.commit().await?;
+            outcomes.push(WriteOutcome::Recorded);
+        }
+
+        tx.commit().await?;
+
+        Ok(outcomes)
+    }
+
+    async fn create_api_key(&self, key: &ApiKey, key_hash: &str) -> sqlx::Result<()> {
+        ApiKeyRepository::new(&self.pool)
+            .create(key, key_hash)
+            .await
+    }
+
+    async fn find_api_key(&self, key_hash: &str) -> sqlx::Result<Option<ApiKey>> {
+        ApiKeyRepository::new(&self.pool)
+            .find_by_hash(key_hash)
+            .await
+    }
+
+    async fn list_api_keys(&self) -> sqlx::Result<Vec<ApiKey>> {
+        ApiKeyRepository::new(&self.pool).list().await
+    }
+
+    async fn update_api_key_scope(&self, id: Uuid, scope: Scope) -> sqlx::Result<u64> {
+        ApiKeyRepository::new(&self.pool)
+            .update_scope(id, scope)
+            .await
+    }
+
+    async fn revoke_api_key(&self, id: Uuid, at: DateTime<Utc>) -> sqlx::Result<u64> {
+        ApiKeyRepository::new(&self.pool).revoke(id, at).await
+    }
+
+    async fn put_secret(&self, secret: &StoredSecret) -> sqlx::Result<()> {
+        SecretRepository::new(&self.pool).put(secret).await
+    }
+
+    async fn get_secret(&self, name: &str) -> sqlx::Result<Option<StoredSecret>> {
+        SecretRepository::new(&self.pool).find(name).await
+    }
+
+    async fn list_secrets(&self) -> sqlx::Result<Vec<StoredSecret>> {
+        SecretRepository::new(&self.pool).list().await
+    }
+
+    async fn delete_secret(&self, name: &str) -> sqlx::Result<u64> {
+        SecretRepository::new(&self.pool).delete(name).await
+    }
+
+    async fn acquire_lease(
+        &self,
+        name: &str,
+        holder: &str,
+        now: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> sqlx::Result<bool> {
+        LeaseRepository::new(&self.pool)
+            .acquire(name, holder, now, expires_at)
+            .await
+    }
+
+    async fn release_lease(&self, name: &str, holder: &str) -> sqlx::Result<u64> {
+        LeaseRepository::new(&self.pool).release(name, holder).await
+    }
+
+    async fn create_trigger_link(&self, link: &TriggerLink) -> sqlx::Result<()> {
+        TriggerLinkRepository::new(&self.pool).create(link).await
+    }
+
+    async fn get_trigger_link(&self, id: Uuid) -> sqlx::Result<Option<TriggerLink>> {
+        TriggerLinkRepository::new(&self.pool).find(id).await
+    }
+
+    async fn redeem_trigger_link(&self, id: Uuid, now: DateTime<Utc>) -> sqlx::Result<bool> {
+        TriggerLinkRepository::new(&self.pool).redeem(id, now).await
+    }
+
+    async fn create_workflow(&self, workflow: &Workflow) -> sqlx::Result<()> {
+        WorkflowRepository::new(&self.pool).create(workflow).await
+    }
+
+    async fn get_workflow(&self, id: Uuid) -> sqlx::Result<Option<Workflow>> {
+        WorkflowRepository::new(&self.pool).find(id).await
+    }
+
+    async fn list_workflows(&self) -> sqlx::Result<Vec<Workflow>> {
+        WorkflowRepository::new(&self.pool).list().await
+    }
+
+    async fn create_workflow_run(&self, run: &WorkflowRun) -> sqlx::Result<()> {
+        WorkflowRepository::new(&self.pool).create_run(run).await
+    }
+
+    async fn get_workflow_run(&self, id: Uuid) -> sqlx::Result<Option<WorkflowRun>> {
+        WorkflowRepository::new(&self.pool).find_run(id).await
+    }
+
+    async fn list_workflow_runs(
+        &self,
+        workflow_id: Uuid,
+        limit: i64,
+    ) -> sqlx::Result<Vec<WorkflowRun>> {
+        WorkflowRepository::new(&self.pool)
+            .list_runs(workflow_id, limit)
+            .await
+    }
+
+    async fn unfinished_workflow_runs(&self) -> sqlx::Result<Vec<WorkflowRun>> {
+        WorkflowRepository::new(&self.pool).unfinished_runs().await
+    }
+
+    async fn update_workflow_run(&self, run: &WorkflowRun) -> sqlx::Result<u64> {
+        WorkflowRepository::new(&self.pool).update_run(run).await
+    }
+
+    async fn create_group(&self, group: &TaskGroup) -> sqlx::Result<()> {
+        GroupRepository::new(&self.pool).create(group).await
+    }
+
+    async fn get_group(&self, id: Uuid) -> sqlx::Result<Option<TaskGroup>> {
+        GroupRepository::new(&self.pool).find(id).await
+    }
+
+    async fn list_groups(&self) -> sqlx::Result<Vec<TaskGroup>> {
+        GroupRepository::new(&self.pool).list().await
+    }
+
+    async fn set_group_paused(
+        &self,
+        id: Uuid,
+        paused_at: Option<DateTime<Utc>>,
+    ) -> sqlx::Result<u64> {
+        let mut tx = self.pool.begin().await?;
+
+        let rows = GroupRepository::set_paused_with_executor(&mut *tx, id, paused_at).await?;
+        if rows > 0 {
+            // Paused tasks leave the scheduler's cache and come back when resumed
+            let triggers = GroupRepository::task_triggers_with_executor(&mut *tx, id).await?;
+            for (task_id, trigger_at) in triggers {
+                let event = match paused_at {
+                    Some(_) => ScheduleEvent::Removed { id: task_id },
+                    None => ScheduleEvent::Upserted {
+                        id: task_id,
+                        trigger_at,
+                    },
+                };
+                TaskRepository::enqueue_schedule_event_with_executor(&mut *tx, &event).await?;
+            }
+        }
+
+        tx.commit().await?;
+        Ok(rows)
+    }
+
+    async fn reschedule_task(&self, id: Uuid, trigger_at: DateTime<Utc>) -> sqlx::Result<u64> {
+        let mut tx = self.pool.begin().await?;
+
+        let rows = TaskRepository::update_trigger_with_executor(&mut *tx, id, trigger_at).await?;
+        if rows > 0 {
+            let event = ScheduleEvent::Upserted { id, trigger_at };
+            TaskRepository::enqueue_schedule_event_with_executor(&mut *tx, &event).await?;
+        }
+
+        tx.commit().await?;
+        Ok(rows)
+    }
+
+    async fn defer_task(
+        &self,
+        id: Uuid,
+        until: DateTime<Utc>,
+        deferred_from: DateTime<Utc>,
+    ) -> sqlx::Result<u64> {
+        let mut tx = self.pool.begin().await?;
+
+        let rows = TaskRepository::defer_with_executor(&mut *tx, id, until, deferred_from).await?;
+        if rows > 0 {
+            let event = ScheduleEvent::Upserted {
+                id,
+                trigger_at: until,
+            };
+            TaskRepository::enqueue_schedule_event_with_executor(&mut *tx, &event).await?;
+        }
+
+        tx.commit().await?;
+        Ok(rows)
+    }
+
+    async fn update_task(&self, task: &Task) -> sqlx::Result<u64> {
+        let mut tx = self.pool.begin().await?;
+
+        let rows = TaskRepository::update_definition_with_executor(&mut *tx, task).await?;
+        if rows > 0 {
+            let event = ScheduleEvent::Upserted {
+                id: task.id,
+                trigger_at: task.trigger_at,
+            };
+            TaskRepository::enqueue_schedule_event_with_executor(&mut *tx, &event).await?;
+        }
+
+        tx.commit().await?;
+        Ok(rows)
+    }
+
+    async fn patch_task(&self, id: Uuid, patch: &TaskPatch) -> sqlx::Result<u64> {
+        let mut tx = self.pool.begin().await?;
+
+        let Some(trigger_at) = TaskRepository::patch_with_executor(&mut *tx, id, patch).await?
+        else {
+            return Ok(0);
+        };
+        let event = ScheduleEvent::Upserted { id, trigger_at };
+        TaskRepository::enqueue_schedule_event_with_executor(&mut *tx, &event).await?;
+
+        tx.commit().await?;
+        Ok(1)
+    }
+
+    async fn set_tasks_paused(
+        &self,
+        selector: &TaskSelector,
+        paused_at: Option<DateTime<Utc>>,
+    ) -> sqlx::Result<u64> {
+        let mut tx = self.pool.begin().await?;
+
+        let mut affected = 0;
+        for (id, trigger_at) in
+            TaskRepository::select_tasks_with_executor(&mut *tx, selector).await?
+        {
+            if TaskRepository::set_paused_with_executor(&mut *tx, id, paused_at).await? == 0 {
+                continue;
+            }
+            let event = match paused_at {
+                Some(_) => ScheduleEvent::Removed { id },
+                None => ScheduleEvent::Upserted { id, trigger_at },
+            };
+            TaskRepository::enqueue_schedule_event_with_executor(&mut *tx, &event).await?;
+            affected += 1;
+        }
+
+        tx.commit().await?;
+        Ok(affected)
+    }
+
+    async fn group_stats(&self, id: Uuid) -> sqlx::Result<GroupStats> {
+        GroupRepository::new(&self.pool).stats(id).await
+    }
+
+    async fn export_snapshot(
+        &self,
+        taken_at: DateTime<Utc>,
+        include_executions: bool,
+    ) -> sqlx::Result<Snapshot> {
+        // Every read sees the same database state
+        let mut tx = self.pool.begin().await?;
+
+        let groups = GroupRepository::list_with_executor(&mut *tx).await?;
+        let tasks = TaskRepository::get_all_tasks_with_executor(&mut *tx).await?;
+        let workflows = WorkflowRepository::list_with_executor(&mut *tx).await?;
+        let executions = match include_executions {
+            true => Some(TaskRepository::get_all_executions_with_executor(&mut *tx).await?),
+            false => None,
+        };
+
+        tx.commit().await?;
+        Ok(Snapshot {
+            version: SNAPSHOT_VERSION,
+            taken_at,
+            groups,
+            tasks,
+            workflows,
+            executions,
+        })
+    }
+
+    async fn restore_snapshot(&self, snapshot: &Snapshot) -> sqlx::Result<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        if !TaskRepository::is_empty_with_executor(&mut *tx).await? {
+            return Ok(false);
+        }
+        for group in &snapshot.groups {
+            GroupRepository::create_with_executor(&mut *tx, group).await?;
+        }
+        for task in &snapshot.tasks {
+            TaskRepository::create_task_with_executor(&mut *tx, task).await?;
+            if restored_task_is_scheduled(snapshot, task) {
+                let event = ScheduleEvent::Upserted {
+                    id: task.id,
+                    trigger_at: task.trigger_at,
+                };
+                TaskRepository::enqueue_schedule_event_with_executor(&mut *tx, &event).await?;
+            }
+        }
+        for workflow in &snapshot.workflows {
+            WorkflowRepository::create_with_executor(&mut *tx, workflow).await?;
+        }
+        for execution in snapshot.executions.iter().flatten() {
+            TaskRepository::insert_execution_with_executor(&mut *tx, execution).await?;
+        }
+
+        tx.commit().await?;
+        Ok(true)
+    }
+}
@@ -1,6 +1,8 @@
 use crate::db::queries::TaskRepository;
-use crate::domain::{Task, TaskType};
-use chrono::{Duration, Utc};
+use crate::db::store::{Claim, ExecutionWrite, FollowUp, SqliteTaskStore, TaskStore, WriteOutcome};
+use crate::domain::{Execution, ExecutionStatus, RunState, Task, TaskType};
+use crate::scheduler::ScheduleEvent;
+use chrono::{DateTime, Duration, Utc};
 use serde_json::json;
 use sqlx::SqlitePool;
 
@@ -87,6 +89,38 @@ async fn test_get_next_pending_task_logic(pool: SqlitePool) -> sqlx::Result<()>
     Ok(())
 }
 
+#[sqlx::test]
+async fn test_higher_priority_tasks_are_dispatched_first(pool: SqlitePool) -> sqlx::Result<()> {
+    let repo = TaskRepository::new(&pool);
+    let now = Utc::now();
+
+    let old = Task::new_once("old", now - Duration::hours(2), json!({}));
+    let critical = Task {
+        priority: 10,
+        ..Task::new_once("critical", now - Duration::minutes(1), json!({}))
+    };
+    let background = Task {
+        priority: -1,
+        ..Task::new_once("background", now - Duration::hours(3), json!({}))
+    };
+    for task in [&old, &critical, &background] {
+        repo.create_task(task).await?;
+    }
+
+    let pending = repo.get_next_pending_task().await?.unwrap();
+    assert_eq!(pending.id, critical.id);
+
+    let due: Vec<_> = repo
+        .get_due_tasks(now, 10)
+        .await?
+        .into_iter()
+        .map(|t| t.id)
+        .collect();
+    assert_eq!(due, vec![critical.id, old.id, background.id]);
+
+    Ok(())
+}
+
 #[sqlx::test]
 async fn test_interval_persistence(pool: SqlitePool) -> sqlx::Result<()> {
     let repo = TaskRepository::new(&pool);
@@ -107,3 +141,844 @@ async fn test_interval_persistence(pool: SqlitePool) -> sqlx::Result<()> {
 
     Ok(())
 }
+
+#[derive(Debug)]
+struct FakeDbError(&'static str);
+
+impl std::fmt::Display for FakeDbError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "fake error {}", self.0)
+    }
+}
+
+impl std::error::Error for FakeDbError {}
+
+impl sqlx::error::DatabaseError for FakeDbError {
+    fn message(&self) -> &str {
+        "fake error"
+    }
+
+    fn code(&self) -> Option<std::borrow::Cow<'_, str>> {
+        Some(self.0.into())
+    }
+
+    fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+        self
+    }
+
+    fn kind(&self) -> sqlx::error::ErrorKind {
+        sqlx::error::ErrorKind::Other
+    }
+}
+
+#[tokio::test]
+async fn test_busy_errors_are_retried_with_backoff() {
+    use crate::db::retry::{RetryPolicy, is_busy, with_retry};
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    // 517 = SQLITE_BUSY_SNAPSHOT, an extended busy code
+    assert!(is_busy(&sqlx::Error::database(FakeDbError("517"))));
+    assert!(!is_busy(&sqlx::Error::database(FakeDbError("787"))));
+
+    let policy = RetryPolicy {
+        max_retries: 3,
+        base_delay: std::time::Duration::from_millis(1),
+        ..RetryPolicy::default()
+    };
+    let attempts = AtomicU32::new(0);
+
+    let result = with_retry(&policy, || async {
+        if attempts.fetch_add(1, Ordering::SeqCst) < 2 {
+            Err(sqlx::Error::database(FakeDbError("5")))
+        } else {
+            Ok(42)
+        }
+    })
+    .await;
+
+    assert_eq!(result.unwrap(), 42);
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+    // Non-busy errors surface immediately
+    attempts.store(0, Ordering::SeqCst);
+    let result: sqlx::Result<()> = with_retry(&policy, || async {
+        attempts.fetch_add(1, Ordering::SeqCst);
+        Err(sqlx::Error::database(FakeDbError("787")))
+    })
+    .await;
+
+    assert!(result.is_err());
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+}
+
+#[sqlx::test]
+async fn test_list_executions_keyset_pagination(pool: SqlitePool) -> sqlx::Result<()> {
+    use crate::domain::{Execution, ExecutionCursor, ExecutionStatus};
+
+    let repo = TaskRepository::new(&pool);
+    let task = Task::new_interval("paged", Utc::now(), 60, json!({}));
+    repo.create_task(&task).await?;
+
+    // Three executions share a timestamp so ordering has to fall back to the id
+    let tied_at = Utc::now() - Duration::minutes(5);
+    for i in 0..5 {
+        let mut exec = Execution::new(task.id, json!({ "run": i }), ExecutionStatus::Success);
+        exec.executed_at = if i < 3 {
+            tied_at
+        } else {
+            tied_at + Duration::minutes(i)
+        };
+        TaskRepository::insert_execution_with_executor(&pool, &exec).await?;
+    }
+
+    let mut seen = Vec::new();
+    let mut cursor: Option<ExecutionCursor> = None;
+    loop {
+        let page = repo.list_executions(task.id, cursor.as_ref(), 2).await?;
+        if page.is_empty() {
+            break;
+        }
+        cursor = page.last().map(ExecutionCursor::after);
+        seen.extend(page);
+    }
+
+    assert_eq!(
+        seen.len(),
+        5,
+        "Every execution should be returned exactly once"
+    );
+    for pair in seen.windows(2) {
+        assert!(
+            (pair[0].executed_at, pair[0].id) > (pair[1].executed_at, pair[1].id),
+            "Executions should be strictly ordered newest first"
+        );
+    }
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_task_summaries_skip_payload(pool: SqlitePool) -> sqlx::Result<()> {
+    let repo = TaskRepository::new(&pool);
+
+    let task = Task::new_once("summary", Utc::now(), json!({ "blob": "x".repeat(10_000) }));
+    repo.create_task(&task).await?;
+    repo.delete_task(task.id).await?;
+
+    let summaries = repo.get_task_summaries(&Default::default()).await?;
+
+    assert_eq!(summaries.len(), 1);
+    assert_eq!(summaries[0].id, task.id);
+    assert_eq!(summaries[0].name, "summary");
+    assert!(summaries[0].deleted_at.is_some());
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_due_tasks_are_chunked_fairly_across_lanes(pool: SqlitePool) -> sqlx::Result<()> {
+    let repo = TaskRepository::new(&pool);
+    let now = Utc::now();
+
+    // A large backlog of overdue one-off tasks, plus a single interval task due last
+    for i in 0..10 {
+        let task = Task::new_once(
+            format!("backlog-{}", i),
+            now - Duration::hours(2) + Duration::seconds(i),
+            json!({}),
+        );
+        repo.create_task(&task).await?;
+    }
+    let heartbeat = Task::new_interval("heartbeat", now - Duration::minutes(1), 60, json!({}));
+    repo.create_task(&heartbeat).await?;
+
+    assert_eq!(repo.count_due_tasks(now).await?, 11);
+
+    let chunk = repo.get_due_tasks(now, 4).await?;
+    assert_eq!(chunk.len(), 4);
+    assert!(
+        chunk.iter().any(|t| t.id == heartbeat.id),
+        "Interval task should not be starved by the one-off backlog"
+    );
+    assert_eq!(chunk[0].name, "backlog-0", "Lanes are served oldest first");
+
+    Ok(())
+}
+
+#[test]
+fn test_outage_backoff_grows_with_jitter_and_resets() {
+    use crate::db::retry::Backoff;
+    use std::time::Duration as StdDuration;
+
+    let mut backoff = Backoff::new(StdDuration::from_millis(100), StdDuration::from_secs(1));
+
+    for step in [100u64, 200, 400, 800, 1000, 1000] {
+        let delay = backoff.next_delay();
+        let step = StdDuration::from_millis(step);
+        assert!(
+            delay >= step / 2 && delay <= step,
+            "{:?} outside [{:?}, {:?}]",
+            delay,
+            step / 2,
+            step
+        );
+    }
+    assert_eq!(backoff.attempts(), 6);
+
+    backoff.reset();
+    assert!(backoff.next_delay() <= StdDuration::from_millis(100));
+}
+
+#[sqlx::test]
+async fn test_outbox_records_mutations_in_order(pool: SqlitePool) -> sqlx::Result<()> {
+    let store = SqliteTaskStore::new(pool);
+    let task = Task::new_once("outboxed", Utc::now(), json!({}));
+
+    store.create_task(&task).await?;
+    store.delete_task(task.id).await?;
+    // Deleting a missing task is not a schedule change
+    store.delete_task(uuid::Uuid::new_v4()).await?;
+
+    assert_eq!(store.drain_outbox(1).await?.len(), 1);
+    assert_eq!(
+        store.drain_outbox(10).await?,
+        vec![ScheduleEvent::Removed { id: task.id }]
+    );
+    assert!(
+        store.drain_outbox(10).await?.is_empty(),
+        "Drained entries are consumed"
+    );
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_running_records_are_finalized_in_place(pool: SqlitePool) -> sqlx::Result<()> {
+    let store = SqliteTaskStore::new(pool.clone());
+    let task = Task::new_once("write_ahead", Utc::now(), json!({}));
+    store.create_task(&task).await?;
+
+    let running = Execution::new(task.id, json!({}), ExecutionStatus::Running);
+    let gone = Execution::new(uuid::Uuid::new_v4(), json!({}), ExecutionStatus::Running);
+    let outcomes = store
+        .begin_executions(&[running.clone(), gone], &claim("test", in_a_minute()))
+        .await?;
+    assert_eq!(
+        outcomes,
+        vec![WriteOutcome::Recorded, WriteOutcome::TaskMissing]
+    );
+    assert_eq!(store.running_executions().await?.len(), 1);
+
+    let finished = Execution {
+        output: json!({ "ok": true }),
+        status: ExecutionStatus::Success,
+        ..running
+    };
+    store
+        .record_executions(&[ExecutionWrite {
+            execution: finished,
+            follow_up: FollowUp::Delete,
+        }])
+        .await?;
+
+    assert!(store.running_executions().await?.is_empty());
+    let statuses: Vec<String> = sqlx::query_scalar("SELECT status FROM executions")
+        .fetch_all(&pool)
+        .await?;
+    assert_eq!(statuses, vec!["success"]);
+
+    Ok(())
+}
+
+fn claim(owner: &str, expires_at: DateTime<Utc>) -> Claim {
+    Claim {
+        owner: owner.into(),
+        expires_at,
+    }
+}
+
+fn in_a_minute() -> DateTime<Utc> {
+    Utc::now() + Duration::minutes(1)
+}
+
+#[sqlx::test]
+async fn test_tasks_are_claimed_by_one_run_at_a_time(pool: SqlitePool) -> sqlx::Result<()> {
+    let store = SqliteTaskStore::new(pool);
+    let task = Task::new_interval("claimed", Utc::now(), 60, json!({}));
+    store.create_task(&task).await?;
+    let begin = async |owner: &str| {
+        let running = Execution::new(task.id, json!({}), ExecutionStatus::Running);
+        let outcomes = store
+            .begin_executions(std::slice::from_ref(&running), &claim(owner, in_a_minute()))
+            .await
+            .unwrap();
+        (running, outcomes[0])
+    };
+
+    let (first, outcome) = begin("a").await;
+    assert_eq!(outcome, WriteOutcome::Recorded);
+    let (_, outcome) = begin("b").await;
+    assert_eq!(outcome, WriteOutcome::AlreadyRunning);
+    assert_eq!(store.running_executions().await?.len(), 1);
+
+    // Only the owner renews a claim; this one lapses
+    let lapsed = Utc::now() - Duration::seconds(1);
+    assert_eq!(
+        store.renew_claims(&[task.id], &claim("b", lapsed)).await?,
+        0
+    );
+    assert_eq!(
+        store.renew_claims(&[task.id], &claim("a", lapsed)).await?,
+        1
+    );
+    assert!(
+        store
+            .orphaned_executions(Some("b"), lapsed)
+            .await?
+            .is_empty()
+    );
+
+    // An expired claim is only taken over once its run has been finalized
+    assert_eq!(store.orphaned_executions(None, Utc::now()).await?.len(), 1);
+    let (_, outcome) = begin("b").await;
+    assert_eq!(outcome, WriteOutcome::AlreadyRunning);
+    store
+        .record_executions(&[ExecutionWrite {
+            execution: Execution {
+                status: ExecutionStatus::Interrupted,
+                ..first
+            },
+            follow_up: FollowUp::Keep,
+        }])
+        .await?;
+    let (_, outcome) = begin("b").await;
+    assert_eq!(outcome, WriteOutcome::Recorded);
+
+    // A live claim only counts as abandoned for its own owner, after a restart
+    assert!(
+        store
+            .orphaned_executions(None, Utc::now())
+            .await?
+            .is_empty()
+    );
+    assert_eq!(
+        store
+            .orphaned_executions(Some("b"), Utc::now())
+            .await?
+            .len(),
+        1
+    );
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_run_state_follows_executions(pool: SqlitePool) -> sqlx::Result<()> {
+    let store = SqliteTaskStore::new(pool);
+    let task = Task::new_interval("report", Utc::now(), 60, json!({}));
+    store.create_task(&task).await?;
+    let run_state = async |store: &SqliteTaskStore| {
+        let task = store.get_task(task.id).await.unwrap().unwrap();
+        task.run_state
+    };
+    assert_eq!(run_state(&store).await, RunState::Pending);
+
+    for (status, follow_up, expected) in [
+        (
+            ExecutionStatus::Failure,
+            FollowUp::Retry {
+                at: Utc::now(),
+                attempt: 1,
+            },
+            RunState::Pending,
+        ),
+        (
+            ExecutionStatus::Failure,
+            FollowUp::Reschedule(Utc::now()),
+            RunState::Failed,
+        ),
+        (
+            ExecutionStatus::Success,
+            FollowUp::Reschedule(Utc::now()),
+            RunState::Pending,
+        ),
+        (
+            ExecutionStatus::Success,
+            FollowUp::Delete,
+            RunState::Completed,
+        ),
+    ] {
+        let running = Execution::new(task.id, json!({}), ExecutionStatus::Running);
+        store
+            .begin_executions(
+                std::slice::from_ref(&running),
+                &claim("test", in_a_minute()),
+            )
+            .await?;
+        assert_eq!(run_state(&store).await, RunState::Running);
+
+        let execution = Execution { status, ..running };
+        store
+            .record_executions(&[ExecutionWrite {
+                execution,
+                follow_up,
+            }])
+            .await?;
+        assert_eq!(run_state(&store).await, expected);
+    }
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_prune_executions_applies_per_task_retention(pool: SqlitePool) -> sqlx::Result<()> {
+    let store = SqliteTaskStore::new(pool.clone());
+    let now = Utc::now();
+
+    let heartbeat = Task {
+        keep_executions: Some(2),
+        ..Task::new_interval("heartbeat", now, 60, json!({}))
+    };
+    let audit = Task {
+        keep_days: Some(30),
+        ..Task::new_interval("audit", now, 86_400, json!({}))
+    };
+    let unlimited = Task::new_interval("unlimited", now, 60, json!({}));
+    for task in [&heartbeat, &audit, &unlimited] {
+        store.create_task(task).await?;
+        for days_ago in [0, 10, 40, 400] {
+            let exec = Execution {
+                executed_at: now - Duration::days(days_ago),
+                ..Execution::new(task.id, json!({}), ExecutionStatus::Success)
+            };
+            TaskRepository::insert_execution_with_executor(&pool, &exec).await?;
+        }
+    }
+    // Unfinished executions are never pruned
+    let running = Execution {
+        executed_at: now - Duration::days(500),
+        ..Execution::new(heartbeat.id, json!({}), ExecutionStatus::Running)
+    };
+    TaskRepository::insert_execution_with_executor(&pool, &running).await?;
+
+    assert_eq!(store.prune_executions(now, None, 1000).await?, 4);
+
+    let remaining = |id: uuid::Uuid| {
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM executions WHERE task_id = ?")
+            .bind(id)
+            .fetch_one(&pool)
+    };
+    assert_eq!(
+        remaining(heartbeat.id).await?,
+        3,
+        "Two newest plus the running one"
+    );
+    assert_eq!(remaining(audit.id).await?, 2);
+    assert_eq!(remaining(unlimited.id).await?, 4);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_prune_executions_applies_the_default_retention_in_batches(
+    pool: SqlitePool,
+) -> sqlx::Result<()> {
+    let store = SqliteTaskStore::new(pool.clone());
+    let now = Utc::now();
+
+    let defaulted = Task::new_interval("defaulted", now, 60, json!({}));
+    let own = Task {
+        keep_days: Some(365),
+        ..Task::new_interval("own", now, 60, json!({}))
+    };
+    for task in [&defaulted, &own] {
+        store.create_task(task).await?;
+        for days_ago in [0, 10, 40, 400] {
+            let exec = Execution {
+                executed_at: now - Duration::days(days_ago),
+                ..Execution::new(task.id, json!({}), ExecutionStatus::Success)
+            };
+            TaskRepository::insert_execution_with_executor(&pool, &exec).await?;
+        }
+    }
+
+    // Oldest first: the 400 day old executions of both tasks, then the 40 day old one
+    assert_eq!(store.prune_executions(now, Some(30), 2).await?, 2);
+    assert_eq!(store.prune_executions(now, Some(30), 2).await?, 1);
+    assert_eq!(store.prune_executions(now, Some(30), 2).await?, 0);
+
+    let remaining = |id: uuid::Uuid| {
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM executions WHERE task_id = ?")
+            .bind(id)
+            .fetch_one(&pool)
+    };
+    assert_eq!(remaining(defaulted.id).await?, 2);
+    assert_eq!(remaining(own.id).await?, 3, "The task's own keep_days wins");
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_replayed_executions_can_be_pruned(pool: SqlitePool) -> sqlx::Result<()> {
+    let store = SqliteTaskStore::new(pool.clone());
+    let now = Utc::now();
+
+    let task = Task::new_interval("replayed", now, 60, json!({}));
+    store.create_task(&task).await?;
+    let original = Execution {
+        executed_at: now - Duration::days(40),
+        ..Execution::new(task.id, json!({}), ExecutionStatus::Failure)
+    };
+    let replay = Execution {
+        replay_of: Some(original.id),
+        ..Execution::new(task.id, json!({}), ExecutionStatus::Success)
+    };
+    for exec in [&original, &replay] {
+        TaskRepository::insert_execution_with_executor(&pool, exec).await?;
+    }
+
+    // The replay outlives the execution it repeated, and forgets it
+    assert_eq!(store.prune_executions(now, Some(30), 10).await?, 1);
+    let kept = store.get_execution(replay.id).await?.unwrap();
+    assert_eq!(kept.replay_of, None);
+
+    let older = Execution {
+        executed_at: now - Duration::days(40),
+        ..Execution::new(task.id, json!({}), ExecutionStatus::Failure)
+    };
+    let again = Execution {
+        replay_of: Some(older.id),
+        ..Execution::new(task.id, json!({}), ExecutionStatus::Success)
+    };
+    for exec in [&older, &again] {
+        TaskRepository::insert_execution_with_executor(&pool, exec).await?;
+    }
+    assert_eq!(store.delete_executions(&[older.id]).await?, 1);
+    let kept = store.get_execution(again.id).await?.unwrap();
+    assert_eq!(kept.replay_of, None);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_expired_executions_are_deleted_by_id(pool: SqlitePool) -> sqlx::Result<()> {
+    let store = SqliteTaskStore::new(pool.clone());
+    let now = Utc::now();
+
+    let task = Task::new_interval("archived", now, 60, json!({}));
+    store.create_task(&task).await?;
+    for days_ago in [0, 40, 400] {
+        let exec = Execution {
+            executed_at: now - Duration::days(days_ago),
+            ..Execution::new(task.id, json!({}), ExecutionStatus::Success)
+        };
+        TaskRepository::insert_execution_with_executor(&pool, &exec).await?;
+    }
+
+    let expired = store.expired_executions(now, Some(30), 10).await?;
+    assert_eq!(expired.len(), 2);
+    let mut ids: Vec<uuid::Uuid> = expired.iter().map(|e| e.id).collect();
+    ids.push(uuid::Uuid::new_v4());
+    assert_eq!(store.delete_executions(&ids).await?, 2);
+    assert!(
+        store
+            .expired_executions(now, Some(30), 10)
+            .await?
+            .is_empty()
+    );
+    assert_eq!(store.delete_executions(&[]).await?, 0);
+
+    let remaining = store.list_execution_records(task.id, None, 10).await?;
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].executed_at, now);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_purge_deleted_tasks_removes_long_deleted_tasks_and_their_executions(
+    pool: SqlitePool,
+) -> sqlx::Result<()> {
+    let store = SqliteTaskStore::new(pool.clone());
+    let now = Utc::now();
+
+    let live = Task::new_interval("live", now, 60, json!({}));
+    let recently_deleted = Task::new_interval("recently deleted", now, 60, json!({}));
+    let long_deleted = Task::new_interval("long deleted", now, 60, json!({}));
+    for task in [&live, &recently_deleted, &long_deleted] {
+        store.create_task(task).await?;
+        let exec = Execution::new(task.id, json!({}), ExecutionStatus::Success);
+        TaskRepository::insert_execution_with_executor(&pool, &exec).await?;
+    }
+    store.delete_task(recently_deleted.id).await?;
+    store.delete_task(long_deleted.id).await?;
+    sqlx::query("UPDATE tasks SET deleted_at = ? WHERE id = ?")
+        .bind(now - Duration::days(40))
+        .bind(long_deleted.id)
+        .execute(&pool)
+        .await?;
+
+    assert_eq!(
+        store
+            .purge_deleted_tasks(now - Duration::days(30), 10)
+            .await?,
+        1
+    );
+
+    assert!(store.get_task(long_deleted.id).await?.is_none());
+    assert!(store.get_task(recently_deleted.id).await?.is_some());
+    assert!(store.get_task(live.id).await?.is_some());
+    let executions: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM executions")
+        .fetch_one(&pool)
+        .await?;
+    assert_eq!(executions, 2, "The purged task's execution goes with it");
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_trigger_links_are_redeemed_once(pool: SqlitePool) -> sqlx::Result<()> {
+    use crate::domain::TriggerLink;
+
+    let store = SqliteTaskStore::new(pool.clone());
+    let now = Utc::now();
+    let task = Task::new_once("linked", now, json!({}));
+    store.create_task(&task).await?;
+
+    let link = TriggerLink {
+        id: uuid::Uuid::new_v4(),
+        task_id: task.id,
+        payload: Some(json!({ "body": { "source": "email" } })),
+        expires_at: now + Duration::hours(1),
+        used_at: None,
+        created_at: now,
+    };
+    store.create_trigger_link(&link).await?;
+    assert_eq!(store.get_trigger_link(link.id).await?, Some(link.clone()));
+
+    assert!(
+        !store
+            .redeem_trigger_link(link.id, now + Duration::hours(2))
+            .await?,
+        "Expired links cannot be redeemed"
+    );
+    assert!(store.redeem_trigger_link(link.id, now).await?);
+    assert!(!store.redeem_trigger_link(link.id, now).await?);
+
+    let stored = store.get_trigger_link(link.id).await?.unwrap();
+    assert_eq!(stored.used_at, Some(now));
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_workflow_runs_track_their_nodes(pool: SqlitePool) -> sqlx::Result<()> {
+    use crate::domain::{NodeState, Workflow, WorkflowNode, WorkflowRun, WorkflowRunStatus};
+
+    let store = SqliteTaskStore::new(pool.clone());
+    let now = Utc::now();
+    let task = Task::new_once("step", now, json!({}));
+    store.create_task(&task).await?;
+    let workflow = Workflow {
+        id: uuid::Uuid::new_v4(),
+        name: "pipeline".into(),
+        nodes: vec![WorkflowNode {
+            key: "step".into(),
+            task_id: task.id,
+            after: vec![],
+            compensate_with: None,
+        }],
+        created_at: now,
+    };
+    store.create_workflow(&workflow).await?;
+    assert_eq!(
+        store.get_workflow(workflow.id).await?,
+        Some(workflow.clone())
+    );
+
+    let mut run = WorkflowRun::new(&workflow, now);
+    store.create_workflow_run(&run).await?;
+    assert_eq!(store.unfinished_workflow_runs().await?, vec![run.clone()]);
+
+    run.nodes.get_mut("step").unwrap().state = NodeState::Succeeded;
+    run.status = WorkflowRunStatus::Succeeded;
+    run.finished_at = Some(now);
+    assert_eq!(store.update_workflow_run(&run).await?, 1);
+    assert_eq!(store.get_workflow_run(run.id).await?, Some(run.clone()));
+    assert!(store.unfinished_workflow_runs().await?.is_empty());
+    assert_eq!(store.list_workflow_runs(workflow.id, 10).await?, vec![run]);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_paused_groups_hold_back_their_tasks(pool: SqlitePool) -> sqlx::Result<()> {
+    use crate::domain::{TaskFilter, TaskGroup};
+
+    let store = SqliteTaskStore::new(pool.clone());
+    let now = Utc::now();
+    let group = TaskGroup {
+        id: uuid::Uuid::new_v4(),
+        name: "nightly".into(),
+        description: None,
+        paused_at: None,
+        created_at: now,
+    };
+    store.create_group(&group).await?;
+
+    let grouped = Task {
+        group_id: Some(group.id),
+        ..Task::new_interval("grouped", now - Duration::minutes(1), 60, json!({}))
+    };
+    let loose = Task::new_once("loose", now - Duration::minutes(1), json!({}));
+    store.create_task(&grouped).await?;
+    store.create_task(&loose).await?;
+    store.drain_outbox(10).await?;
+
+    let write = ExecutionWrite {
+        execution: Execution::new(grouped.id, json!({}), ExecutionStatus::Failure),
+        follow_up: FollowUp::Keep,
+    };
+    store.record_executions(&[write]).await?;
+
+    assert_eq!(store.set_group_paused(group.id, Some(now)).await?, 1);
+    assert_eq!(
+        store.drain_outbox(10).await?,
+        vec![ScheduleEvent::Removed { id: grouped.id }]
+    );
+    let due = store.get_due_tasks(now, 10).await?;
+    assert_eq!(due.iter().map(|t| t.id).collect::<Vec<_>>(), vec![loose.id]);
+    assert_eq!(store.count_due_tasks(now).await?, 1);
+    assert_eq!(store.get_upcoming_triggers(10).await?.len(), 1);
+
+    let filter = TaskFilter {
+        group_id: Some(group.id),
+        ..TaskFilter::default()
+    };
+    let listed = store.get_task_summaries(&filter).await?;
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].id, grouped.id);
+
+    let stats = store.group_stats(group.id).await?;
+    assert_eq!(stats.task_count, 1);
+    assert_eq!(stats.execution_count, 1);
+    assert_eq!(stats.failure_count, 1);
+    assert!(stats.last_executed_at.is_some());
+
+    store.set_group_paused(group.id, None).await?;
+    assert_eq!(
+        store.drain_outbox(10).await?,
+        vec![ScheduleEvent::Upserted {
+            id: grouped.id,
+            trigger_at: grouped.trigger_at
+        }]
+    );
+    assert_eq!(store.count_due_tasks(now).await?, 2);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_bulk_pause_by_label_is_counted_and_reversible(pool: SqlitePool) -> sqlx::Result<()> {
+    use crate::domain::{Labels, TaskSelector};
+
+    let store = SqliteTaskStore::new(pool.clone());
+    let now = Utc::now();
+    let labelled = |name: &str, team: &str| Task {
+        labels: Some(Labels::from([
+            ("team".to_string(), team.to_string()),
+            ("tier".to_string(), "batch".to_string()),
+        ])),
+        ..Task::new_once(name, now - Duration::minutes(1), json!({}))
+    };
+    let billing = labelled("invoices", "billing");
+    let search = labelled("reindex", "search");
+    store.create_task(&billing).await?;
+    store.create_task(&search).await?;
+    store.drain_outbox(10).await?;
+
+    let selector =
+        TaskSelector::Labels(Labels::from([("team".to_string(), "billing".to_string())]));
+    assert_eq!(store.set_tasks_paused(&selector, Some(now)).await?, 1);
+    assert_eq!(
+        store.set_tasks_paused(&selector, Some(now)).await?,
+        0,
+        "Already paused tasks are not counted again"
+    );
+    assert_eq!(
+        store.drain_outbox(10).await?,
+        vec![ScheduleEvent::Removed { id: billing.id }]
+    );
+    let due = store.get_due_tasks(now, 10).await?;
+    assert_eq!(
+        due.iter().map(|t| t.id).collect::<Vec<_>>(),
+        vec![search.id]
+    );
+    assert!(
+        store
+            .get_task(billing.id)
+            .await?
+            .unwrap()
+            .paused_at
+            .is_some()
+    );
+
+    assert_eq!(store.set_tasks_paused(&selector, None).await?, 1);
+    assert_eq!(store.count_due_tasks(now).await?, 2);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_task_runs_are_computed_from_schedule_and_history(
+    pool: SqlitePool,
+) -> sqlx::Result<()> {
+    let store = SqliteTaskStore::new(pool.clone());
+    let now = Utc::now();
+    let task = Task::new_interval("hourly", now + Duration::minutes(5), 3600, json!({}));
+    store.create_task(&task).await?;
+
+    let runs = store.get_task_runs(task.id).await?;
+    assert_eq!(runs.next_run_at, Some(task.trigger_at));
+    assert_eq!(runs.last_run_at, None);
+
+    let finished = Execution {
+        executed_at: now - Duration::minutes(10),
+        ..Execution::new(task.id, json!({}), ExecutionStatus::Failure)
+    };
+    let running = Execution {
+        executed_at: now,
+        ..Execution::new(task.id, json!({}), ExecutionStatus::Running)
+    };
+    store
+        .begin_executions(&[finished.clone(), running], &claim("test", in_a_minute()))
+        .await?;
+    let runs = store.get_task_runs(task.id).await?;
+    assert_eq!(runs.last_run_at, Some(finished.executed_at));
+    assert_eq!(
+        runs.last_status,
+        Some(ExecutionStatus::Failure),
+        "Running executions are not the last run yet"
+    );
+
+    store.delete_task(task.id).await?;
+    let summaries = store.get_task_summaries(&Default::default()).await?;
+    assert_eq!(
+        summaries[0].runs.next_run_at, None,
+        "Deleted tasks never run"
+    );
+    assert_eq!(
+        summaries[0].runs.last_status,
+        Some(ExecutionStatus::Failure)
+    );
+
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use crate::domain::TriggerLink;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use sqlx::types::Json;
+use uuid::Uuid;
+
+pub struct TriggerLinkRepository<'a> {
+    pub pool: &'a SqlitePool,
+}
+
+impl<'a> TriggerLinkRepository<'a> {
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Stores a new trigger link.
+    ///
+    /// # Arguments
+    ///
+    /// * `link` - The link to store.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<()>` - Result indicating success or failure of the operation.
+    pub async fn create(&self, link: &TriggerLink) -> sqlx::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO trigger_links (id, task_id, payload, expires_at, used_at, created_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(link.id)
+        .bind(link.task_id)
+        .bind(link.payload.as_ref().map(Json))
+        .bind(link.expires_at)
+        .bind(link.used_at)
+        .bind(link.created_at)
+        .execute(self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Retrieves a trigger link by its ID, used or not.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The UUID of the link.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<Option<TriggerLink>>` - The link if found.
+    pub async fn find(&self, id: Uuid) -> sqlx::Result<Option<TriggerLink>> {
+        sqlx::query_as::<_, TriggerLink>(
+            r#"
+            SELECT id, task_id, payload, expires_at, used_at, created_at
+            FROM trigger_links
+            WHERE id = ?
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(self.pool)
+        .await
+    }
+
+    /// Marks a link as used if it is still unused and unexpired at `now`. The check and the
+    /// update are one statement, so a link can only be redeemed once.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The UUID of the link.
+    /// * `now` - The redemption time.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<bool>` - True if this call redeemed the link.
+    pub async fn redeem(&self, id: Uuid, now: DateTime<Utc>) -> sqlx::Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE trigger_links
+            SET used_at = ?1
+            WHERE id = ?2 AND used_at IS NULL AND expires_at > ?1
+            "#,
+        )
+        .bind(now)
+        .bind(id)
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+}
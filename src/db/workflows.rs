@@ -0,0 +1,223 @@
+use crate::domain::{Workflow, WorkflowRun};
+use sqlx::types::Json;
+use sqlx::{Executor, Sqlite, SqlitePool};
+use uuid::Uuid;
+
+pub struct WorkflowRepository<'a> {
+    pub pool: &'a SqlitePool,
+}
+
+impl<'a> WorkflowRepository<'a> {
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Stores a new workflow.
+    ///
+    /// # Arguments
+    ///
+    /// * `workflow` - The workflow to store.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<()>` - Result indicating success or failure of the operation.
+    pub async fn create(&self, workflow: &Workflow) -> sqlx::Result<()> {
+        Self::create_with_executor(self.pool, workflow).await
+    }
+
+    /// Stores a new workflow using the provided executor.
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - An executor that can execute the query (e.g., a connection or transaction).
+    /// * `workflow` - The workflow to store.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<()>` - Result indicating success or failure of the operation.
+    pub async fn create_with_executor<'c, E>(executor: E, workflow: &Workflow) -> sqlx::Result<()>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        sqlx::query(
+            r#"
+            INSERT INTO workflows (id, name, nodes, created_at)
+            VALUES (?, ?, ?, ?)
+            "#,
+        )
+        .bind(workflow.id)
+        .bind(&workflow.name)
+        .bind(Json(&workflow.nodes))
+        .bind(workflow.created_at)
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Retrieves a workflow by its ID.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The UUID of the workflow.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<Option<Workflow>>` - The workflow if found.
+    pub async fn find(&self, id: Uuid) -> sqlx::Result<Option<Workflow>> {
+        sqlx::query_as::<_, Workflow>(
+            r#"
+            SELECT id, name, nodes, created_at
+            FROM workflows
+            WHERE id = ?
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(self.pool)
+        .await
+    }
+
+    /// Retrieves every workflow, ordered by name.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<Vec<Workflow>>` - The workflows.
+    pub async fn list(&self) -> sqlx::Result<Vec<Workflow>> {
+        Self::list_with_executor(self.pool).await
+    }
+
+    /// Retrieves every workflow, ordered by name, using the provided executor.
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - An executor that can execute the query (e.g., a connection or transaction).
+    ///
+    /// # Returns
+    /// * `sqlx::Result<Vec<Workflow>>` - The workflows.
+    pub async fn list_with_executor<'c, E>(executor: E) -> sqlx::Result<Vec<Workflow>>
+    where
+        E: Executor<'c, Database = Sqlite>,
+    {
+        sqlx::query_as::<_, Workflow>(
+            r#"
+            SELECT id, name, nodes, created_at
+            FROM workflows
+            ORDER BY name, id
+            "#,
+        )
+        .fetch_all(executor)
+        .await
+    }
+
+    /// Stores a new workflow run.
+    ///
+    /// # Arguments
+    ///
+    /// * `run` - The run to store.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<()>` - Result indicating success or failure of the operation.
+    pub async fn create_run(&self, run: &WorkflowRun) -> sqlx::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO workflow_runs (id, workflow_id, status, nodes, started_at, finished_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(run.id)
+        .bind(run.workflow_id)
+        .bind(run.status)
+        .bind(Json(&run.nodes))
+        .bind(run.started_at)
+        .bind(run.finished_at)
+        .execute(self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Retrieves a workflow run by its ID.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The UUID of the run.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<Option<WorkflowRun>>` - The run if found.
+    pub async fn find_run(&self, id: Uuid) -> sqlx::Result<Option<WorkflowRun>> {
+        sqlx::query_as::<_, WorkflowRun>(
+            r#"
+            SELECT id, workflow_id, status, nodes, started_at, finished_at
+            FROM workflow_runs
+            WHERE id = ?
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(self.pool)
+        .await
+    }
+
+    /// Retrieves a workflow's most recent runs, newest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `workflow_id` - The UUID of the workflow.
+    /// * `limit` - Maximum number of runs to return.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<Vec<WorkflowRun>>` - The runs.
+    pub async fn list_runs(&self, workflow_id: Uuid, limit: i64) -> sqlx::Result<Vec<WorkflowRun>> {
+        sqlx::query_as::<_, WorkflowRun>(
+            r#"
+            SELECT id, workflow_id, status, nodes, started_at, finished_at
+            FROM workflow_runs
+            WHERE workflow_id = ?
+            ORDER BY started_at DESC, id DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(workflow_id)
+        .bind(limit)
+        .fetch_all(self.pool)
+        .await
+    }
+
+    /// Retrieves the runs still in progress, oldest first.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<Vec<WorkflowRun>>` - The unfinished runs.
+    pub async fn unfinished_runs(&self) -> sqlx::Result<Vec<WorkflowRun>> {
+        sqlx::query_as::<_, WorkflowRun>(
+            r#"
+            SELECT id, workflow_id, status, nodes, started_at, finished_at
+            FROM workflow_runs
+            WHERE status = 'running'
+            ORDER BY started_at, id
+            "#,
+        )
+        .fetch_all(self.pool)
+        .await
+    }
+
+    /// Saves a run's status, node states and finish time.
+    ///
+    /// # Arguments
+    ///
+    /// * `run` - The run as it now stands.
+    ///
+    /// # Returns
+    /// * `sqlx::Result<u64>` - Result containing the number of rows affected.
+    pub async fn update_run(&self, run: &WorkflowRun) -> sqlx::Result<u64> {
+        let result = sqlx::query(
+            r#"
+            UPDATE workflow_runs
+            SET status = ?, nodes = ?, finished_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(run.status)
+        .bind(Json(&run.nodes))
+        .bind(run.finished_at)
+        .bind(run.id)
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
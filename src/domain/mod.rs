@@ -1,29 +1,255 @@
+use crate::executor::condition::RunCondition;
+use crate::schedule::{ExecutionWindow, Recurrence};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
 use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use sqlx::{FromRow, Type};
+use std::collections::BTreeMap;
+use std::str::FromStr;
 use uuid::Uuid;
 
+/// Key/value labels attached to a task, e.g. `team=billing`.
+pub type Labels = BTreeMap<String, String>;
+
 // Enums
 
 /// Represents execution mode of a task.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
-#[sqlx(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
 pub enum TaskType {
     /// Task that runs only once at a specified time.
     Once,
     /// Task that runs at regular intervals.
     Interval,
+    /// Task that runs whenever its cron expression matches.
+    Cron,
+    /// Task that runs on the occurrences of an iCalendar recurrence rule.
+    Rrule,
 }
 
 /// Represents the status of a task execution.
-#[derive(Debug, Clone, Serialize, Deserialize, Type)]
-#[sqlx(rename_all = "lowercase")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
 pub enum ExecutionStatus {
     /// Execution completed successfully.
     Success,
     /// Execution failed.
     Failure,
+    /// The executor has been invoked and has not finished yet.
+    Running,
+    /// The process stopped while the execution was running; its outcome is unknown.
+    Interrupted,
+    /// Not run, because the task's `run_if` condition was not met.
+    Skipped,
+    /// Stopped through the API before it finished.
+    Cancelled,
+}
+
+impl ExecutionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExecutionStatus::Success => "success",
+            ExecutionStatus::Failure => "failure",
+            ExecutionStatus::Running => "running",
+            ExecutionStatus::Interrupted => "interrupted",
+            ExecutionStatus::Skipped => "skipped",
+            ExecutionStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// Where a task is in its lifecycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskStatus {
+    /// Dispatched whenever it comes due.
+    Active,
+    /// Kept, but not dispatched until resumed.
+    Paused,
+    /// Soft deleted.
+    Deleted,
+}
+
+impl TaskStatus {
+    fn of(deleted_at: Option<DateTime<Utc>>, paused_at: Option<DateTime<Utc>>) -> Self {
+        if deleted_at.is_some() {
+            TaskStatus::Deleted
+        } else if paused_at.is_some() {
+            TaskStatus::Paused
+        } else {
+            TaskStatus::Active
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskStatus::Active => "active",
+            TaskStatus::Paused => "paused",
+            TaskStatus::Deleted => "deleted",
+        }
+    }
+}
+
+/// Why an execution failed, for querying failures without parsing their error messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum FailureKind {
+    /// The target did not answer in time.
+    Timeout,
+    /// The target could not be reached.
+    Connection,
+    /// The target answered with a status the task does not accept.
+    HttpStatus,
+    /// The payload could not be run, e.g. it is malformed or names an unknown type.
+    Validation,
+    /// Any other failure, e.g. a non-zero exit status or an unmet response condition.
+    Other,
+}
+
+/// What a task's runs are doing, persisted as runs start and finish.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum RunState {
+    /// Waiting for its next run.
+    #[default]
+    Pending,
+    /// A run is in progress.
+    Running,
+    /// Its last run succeeded and it will not run again.
+    Completed,
+    /// Its last run failed or was interrupted, and no retry is scheduled.
+    Failed,
+}
+
+impl RunState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RunState::Pending => "pending",
+            RunState::Running => "running",
+            RunState::Completed => "completed",
+            RunState::Failed => "failed",
+        }
+    }
+}
+
+/// What the scheduler does with a recurring task's runs that are overdue by more than the
+/// missed-run grace period, e.g. after downtime.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum MisfirePolicy {
+    /// Run once, then carry on from now.
+    #[default]
+    FireOnce,
+    /// Run once for every missed occurrence, back to back, until caught up.
+    FireAll,
+    /// Run none of them and carry on with the next occurrence after now.
+    Skip,
+}
+
+/// How an interval task's next trigger follows from the run that just finished.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum IntervalMode {
+    /// One interval after the run finished, so the schedule drifts by each run's latency.
+    #[default]
+    FixedDelay,
+    /// One interval after the previous trigger, so runs keep their phase.
+    FixedRate,
+}
+
+/// Lifecycle filter for task listings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskState {
+    /// Not deleted.
+    Active,
+    /// Soft deleted and waiting in the recycle bin.
+    Deleted,
+}
+
+/// Something that happened to a task and may be worth telling someone about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationEvent {
+    /// An execution failed or was interrupted.
+    Failure,
+    /// An execution succeeded after the previous one had not.
+    Recovery,
+    /// The task started later than the missed-run grace period after its trigger time.
+    MissedRun,
+    /// An execution took far longer than the task's recent runs.
+    SlowRun,
+    /// The task was disabled automatically. Nothing disables tasks on its own yet, so
+    /// routes subscribing to it stay silent until something does.
+    AutoDisable,
+}
+
+/// Where a notification is delivered.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NotificationChannel {
+    /// A Slack incoming webhook.
+    Slack { webhook_url: String },
+    /// Email, sent through the relay configured with `EMAIL_RELAY_URL`.
+    Email { to: Vec<String> },
+    /// A JSON `POST` to any URL.
+    Callback { url: String },
+}
+
+/// Sends the selected events of a task to one channel.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NotificationRoute {
+    /// Defaults to failures and automatic disabling, the events that need a human.
+    #[serde(default = "NotificationRoute::default_events")]
+    pub events: Vec<NotificationEvent>,
+    #[serde(flatten)]
+    pub channel: NotificationChannel,
+}
+
+/// A task's notification policy.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Notifications {
+    pub routes: Vec<NotificationRoute>,
+}
+
+/// Retries of a failed execution, with exponentially growing delays.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionRetry {
+    /// Retries after the first failed attempt before the run counts as failed for good.
+    pub max_retries: u32,
+    /// Delay before the first retry.
+    pub initial_delay_seconds: i64,
+    /// Factor each further delay grows by.
+    #[serde(default = "ExecutionRetry::default_multiplier")]
+    pub backoff_multiplier: f64,
+}
+
+/// A period during which runs are held back, e.g. a maintenance window or a holiday. Runs
+/// that come due inside it are deferred to its end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlackoutWindow {
+    pub starts_at: DateTime<Utc>,
+    /// First instant after the window; deferred runs fire at this time.
+    pub ends_at: DateTime<Utc>,
+}
+
+/// Access level of an API key. Each scope includes everything the lower ones allow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum Scope {
+    /// Read tasks and executions, e.g. for dashboards.
+    ReadOnly,
+    /// Additionally create and control tasks, but not delete them.
+    Operator,
+    /// Full access, including deletions, secrets, settings and key management.
+    Admin,
 }
 
 // Structs
@@ -40,6 +266,28 @@ pub struct Execution {
     pub output: Value,
     /// Status of the execution.
     pub status: ExecutionStatus,
+    /// Payload the task ran with. Not recorded for executions from before replays existed.
+    pub payload: Option<Value>,
+    /// The execution this one replays, if any.
+    pub replay_of: Option<Uuid>,
+    /// How long the executor took, in milliseconds. Not recorded while running.
+    pub duration_ms: Option<i64>,
+    /// When the run was originally due, if a blackout window deferred it.
+    pub deferred_from: Option<DateTime<Utc>>,
+    /// How long after its `trigger_at` the scheduler started the run, in milliseconds. Not
+    /// recorded for runs started on demand, such as run-now and replays.
+    pub lag_ms: Option<i64>,
+    /// When the run was due: the task's `trigger_at` for scheduled runs, `None` for runs
+    /// started on demand.
+    pub scheduled_for: Option<DateTime<Utc>>,
+    /// When the executor was invoked. Not recorded for executions from before timings were.
+    pub started_at: Option<DateTime<Utc>>,
+    /// When the run finished. Not recorded while running.
+    pub finished_at: Option<DateTime<Utc>>,
+    /// 1 for a task's first try at a run, 2 for its first retry and so on.
+    pub attempt: Option<i64>,
+    /// Why the execution failed; `None` unless its status is `failure`.
+    pub failure_kind: Option<FailureKind>,
 }
 /// Represents a scheduled task.
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow, PartialEq)]
@@ -58,6 +306,381 @@ pub struct Task {
     pub payload: Value,
     /// If set, indicates the task is deleted and execution is skipped.
     pub deleted_at: Option<DateTime<Utc>>,
+    /// ID of the API request that created the task, if any.
+    pub request_id: Option<String>,
+    /// Keep only this many of the task's most recent executions.
+    pub keep_executions: Option<i64>,
+    /// Keep the task's executions for this many days.
+    pub keep_days: Option<i64>,
+    /// Secret for signing the task's webhook requests. Tasks created before signing was
+    /// introduced have none and are sent unsigned.
+    pub signing_secret: Option<String>,
+    /// The secret replaced by the last rotation, still signing until it expires.
+    pub previous_signing_secret: Option<String>,
+    pub previous_secret_expires_at: Option<DateTime<Utc>>,
+    /// API key that created the task; its tenant's task quota counts the task.
+    pub api_key_id: Option<Uuid>,
+    /// Which events are sent where; tasks without a policy notify nobody.
+    #[sqlx(json(nullable))]
+    pub notifications: Option<Notifications>,
+    /// Group the task is filed under, if any.
+    pub group_id: Option<Uuid>,
+    #[sqlx(json(nullable))]
+    pub labels: Option<Labels>,
+    /// If set, the task is not dispatched until it is resumed.
+    pub paused_at: Option<DateTime<Utc>>,
+    /// Cron expression of cron tasks, evaluated in `timezone`.
+    pub cron_expr: Option<String>,
+    /// How failed executions are retried; failures are not retried without one.
+    #[sqlx(json(nullable))]
+    pub retry_policy: Option<ExecutionRetry>,
+    /// Retries the current run has used so far; 0 outside of a retry sequence.
+    pub retry_attempt: i64,
+    /// Tasks with a higher priority are dispatched first when several are due at once.
+    #[serde(default)]
+    pub priority: i64,
+    /// What the task's runs are doing; kept up to date by the store.
+    #[serde(default)]
+    pub run_state: RunState,
+    /// How runs missed by more than the grace period are caught up.
+    #[serde(default)]
+    pub misfire_policy: MisfirePolicy,
+    /// Whether an interval task's runs drift with their latency or keep their phase.
+    #[serde(default)]
+    pub interval_mode: IntervalMode,
+    /// A recurring task completes instead of running after this time.
+    pub ends_at: Option<DateTime<Utc>>,
+    /// A recurring task completes after this many scheduled runs.
+    pub max_executions: Option<i64>,
+    /// Scheduled runs that have finished so far; manual runs and retries do not count.
+    #[serde(default)]
+    pub run_count: i64,
+    /// IANA time zone a cron or recurrence rule task's schedule is evaluated in; UTC if
+    /// unset.
+    pub timezone: Option<String>,
+    /// iCalendar recurrence rule of recurrence rule tasks, with its `DTSTART`.
+    pub rrule: Option<String>,
+    /// Periods the task's runs are held back in, on top of the global ones.
+    #[sqlx(json(nullable))]
+    pub blackout_windows: Option<Vec<BlackoutWindow>>,
+    /// When the pending run was originally due, if a blackout window deferred it. Cleared
+    /// once the task's trigger moves on.
+    pub deferred_from: Option<DateTime<Utc>>,
+    /// Periods of the week the task may run in, in `timezone`; triggers outside them move
+    /// to the next opening. Any time if unset.
+    #[sqlx(json(nullable))]
+    pub execution_windows: Option<Vec<ExecutionWindow>>,
+    /// Task whose successful runs this task waits for; a due run is held back until that
+    /// task has succeeded since this one last ran.
+    pub depends_on: Option<Uuid>,
+    /// Task started after each successful run, with that run's output available to its
+    /// payload as `{{previous.output}}`.
+    pub next_task_id: Option<Uuid>,
+    /// Condition on the output of the `depends_on` task's latest successful run; runs that
+    /// find it unmet are recorded as skipped instead of running.
+    #[sqlx(json(nullable))]
+    pub run_if: Option<RunCondition>,
+}
+
+/// Lightweight projection of a task for list views, without the payload.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, PartialEq)]
+pub struct TaskSummary {
+    pub id: Uuid,
+    pub name: String,
+    pub task_type: TaskType,
+    pub trigger_at: DateTime<Utc>,
+    pub interval_seconds: Option<i64>,
+    pub cron_expr: Option<String>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub group_id: Option<Uuid>,
+    pub paused_at: Option<DateTime<Utc>>,
+    pub run_state: RunState,
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub runs: TaskRuns,
+}
+
+/// When a task runs next and how its latest finished run went.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, FromRow, PartialEq)]
+pub struct TaskRuns {
+    /// `None` while the task is deleted or paused, directly or through its group.
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_status: Option<ExecutionStatus>,
+}
+
+/// Whether a dependent task's next run can go ahead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DependencyState {
+    /// The task depended on has succeeded since the dependent last ran.
+    Ready,
+    /// The dependent's runs are held back until the task depended on succeeds.
+    Waiting,
+}
+
+/// Where a dependent task stands with the task it depends on.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Dependency {
+    pub task_id: Uuid,
+    pub state: DependencyState,
+    /// When the task depended on last succeeded, if ever.
+    pub last_succeeded_at: Option<DateTime<Utc>>,
+}
+
+/// Which tasks a listing includes.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TaskFilter {
+    /// Only active or only deleted tasks; both if `None`.
+    pub state: Option<TaskState>,
+    /// Only tasks in this group.
+    pub group_id: Option<Uuid>,
+}
+
+/// Picks the tasks a bulk operation applies to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TaskSelector {
+    /// Tasks carrying every one of these labels.
+    Labels(Labels),
+    /// Tasks in this group.
+    Group(Uuid),
+    /// A single task.
+    Task(Uuid),
+}
+
+/// Changes to some of a task's fields; `None` leaves a field as it is.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TaskPatch {
+    pub name: Option<String>,
+    pub trigger_at: Option<DateTime<Utc>>,
+    pub interval_seconds: Option<i64>,
+    pub cron_expr: Option<String>,
+    pub payload: Option<Value>,
+}
+
+impl TaskPatch {
+    /// True if the patch moves the task's schedule, which drops any pending retry.
+    pub fn reschedules(&self) -> bool {
+        self.trigger_at.is_some() || self.interval_seconds.is_some() || self.cron_expr.is_some()
+    }
+
+    /// Applies the patch to a copy of `task`.
+    pub fn apply(&self, task: &Task) -> Task {
+        let mut task = task.clone();
+        if let Some(name) = &self.name {
+            task.name = name.clone();
+        }
+        if let Some(trigger_at) = self.trigger_at {
+            task.trigger_at = trigger_at;
+        }
+        if let Some(seconds) = self.interval_seconds {
+            task.interval_seconds = Some(seconds);
+        }
+        if let Some(expr) = &self.cron_expr {
+            task.cron_expr = Some(expr.clone());
+        }
+        if let Some(payload) = &self.payload {
+            task.payload = payload.clone();
+        }
+        if self.reschedules() {
+            task.retry_attempt = 0;
+        }
+        task
+    }
+}
+
+/// A named group of tasks, e.g. a team's or a pipeline's jobs.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, PartialEq)]
+pub struct TaskGroup {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    /// If set, none of the group's tasks are dispatched.
+    pub paused_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Format version of [`Snapshot`]s written by this build.
+pub const SNAPSHOT_VERSION: u32 = 2;
+
+/// The scheduler's state at one point in time, for restoring into a fresh instance.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub version: u32,
+    pub taken_at: DateTime<Utc>,
+    pub groups: Vec<TaskGroup>,
+    /// Every task, including those in the recycle bin. Signing secrets are left out unless
+    /// requested.
+    pub tasks: Vec<Task>,
+    /// Every workflow, ordered by name.
+    pub workflows: Vec<Workflow>,
+    /// Execution history, oldest first; only included on request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub executions: Option<Vec<Execution>>,
+}
+
+/// Aggregated numbers of a group's tasks and their executions.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, FromRow, PartialEq)]
+pub struct GroupStats {
+    /// Live (not deleted) tasks in the group.
+    pub task_count: i64,
+    pub execution_count: i64,
+    pub success_count: i64,
+    /// Failed and interrupted executions.
+    pub failure_count: i64,
+    pub last_executed_at: Option<DateTime<Utc>>,
+}
+
+/// Lightweight projection of an execution for list views, without the output.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ExecutionSummary {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub executed_at: DateTime<Utc>,
+    pub status: ExecutionStatus,
+}
+
+/// An API key's metadata. The key itself is only known to its holder.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, PartialEq)]
+pub struct ApiKey {
+    pub id: Uuid,
+    /// Human-readable label, e.g. the dashboard or team using the key.
+    pub name: String,
+    pub scope: Scope,
+    pub created_at: DateTime<Utc>,
+    /// If set, the key no longer authenticates.
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+/// A named secret as it is stored. Its value is only decrypted while a task runs.
+#[derive(Debug, Clone, FromRow, PartialEq)]
+pub struct StoredSecret {
+    pub name: String,
+    /// Nonce, AES-256-GCM ciphertext and tag of the value.
+    pub ciphertext: Vec<u8>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A single-use link that fires a task without an API key.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, PartialEq)]
+pub struct TriggerLink {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    /// Fields merged over the task's payload for the triggered run.
+    pub payload: Option<Value>,
+    pub expires_at: DateTime<Utc>,
+    /// If set, the link has been redeemed and no longer works.
+    pub used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A step of a workflow: a task run once the steps it follows have succeeded.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WorkflowNode {
+    /// Names the node within its workflow.
+    pub key: String,
+    pub task_id: Uuid,
+    /// Keys of the nodes that must succeed before this one runs.
+    #[serde(default)]
+    pub after: Vec<String>,
+    /// Task run to undo this node's work if the workflow fails after the node succeeded.
+    pub compensate_with: Option<Uuid>,
+}
+
+/// A named graph of tasks, run node by node along its edges.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, PartialEq)]
+pub struct Workflow {
+    pub id: Uuid,
+    pub name: String,
+    /// The nodes in an order where each comes after the nodes it follows.
+    #[sqlx(json)]
+    pub nodes: Vec<WorkflowNode>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// How a workflow run went, or that it is still going.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum WorkflowRunStatus {
+    Running,
+    /// Every node succeeded.
+    Succeeded,
+    /// A node failed; the nodes after it did not run.
+    Failed,
+}
+
+/// Where one node of a workflow run stands.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeState {
+    /// Waiting for the nodes it follows.
+    #[default]
+    Pending,
+    Running,
+    Succeeded,
+    Failed,
+    /// Not run because a node it follows failed.
+    UpstreamFailed,
+    /// Succeeded, then undone by its compensating task after the workflow failed.
+    Compensated,
+    /// Succeeded, but its compensating task failed too.
+    CompensationFailed,
+}
+
+/// One node of a workflow run, with the executions it produced.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NodeRun {
+    pub state: NodeState,
+    pub execution_id: Option<Uuid>,
+    pub compensation_id: Option<Uuid>,
+}
+
+/// One run of a workflow through its graph.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, PartialEq)]
+pub struct WorkflowRun {
+    pub id: Uuid,
+    pub workflow_id: Uuid,
+    pub status: WorkflowRunStatus,
+    /// Each node's progress, by key.
+    #[sqlx(json)]
+    pub nodes: BTreeMap<String, NodeRun>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl WorkflowRun {
+    /// A run of `workflow` starting at `now`, with every node pending.
+    pub fn new(workflow: &Workflow, now: DateTime<Utc>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            workflow_id: workflow.id,
+            status: WorkflowRunStatus::Running,
+            nodes: workflow
+                .nodes
+                .iter()
+                .map(|node| (node.key.clone(), NodeRun::default()))
+                .collect(),
+            started_at: now,
+            finished_at: None,
+        }
+    }
+
+    /// The state of the node with `key`; pending if the run does not know it.
+    pub fn state(&self, key: &str) -> NodeState {
+        self.nodes
+            .get(key)
+            .map(|node| node.state)
+            .unwrap_or_default()
+    }
+}
+
+/// Keyset position in a task's execution history, ordered by `(executed_at, id)` descending.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecutionCursor {
+    pub executed_at: DateTime<Utc>,
+    pub id: Uuid,
 }
 
 // Implementations
@@ -72,6 +695,35 @@ impl Task {
             interval_seconds: None,
             payload,
             deleted_at: None,
+            request_id: None,
+            keep_executions: None,
+            keep_days: None,
+            signing_secret: None,
+            previous_signing_secret: None,
+            previous_secret_expires_at: None,
+            api_key_id: None,
+            notifications: None,
+            group_id: None,
+            labels: None,
+            paused_at: None,
+            cron_expr: None,
+            retry_policy: None,
+            retry_attempt: 0,
+            priority: 0,
+            run_state: RunState::Pending,
+            misfire_policy: MisfirePolicy::FireOnce,
+            interval_mode: IntervalMode::FixedDelay,
+            ends_at: None,
+            max_executions: None,
+            run_count: 0,
+            timezone: None,
+            rrule: None,
+            blackout_windows: None,
+            deferred_from: None,
+            execution_windows: None,
+            depends_on: None,
+            next_task_id: None,
+            run_if: None,
         }
     }
 
@@ -89,6 +741,176 @@ impl Task {
             interval_seconds: Some(interval_seconds),
             payload,
             deleted_at: None,
+            request_id: None,
+            keep_executions: None,
+            keep_days: None,
+            signing_secret: None,
+            previous_signing_secret: None,
+            previous_secret_expires_at: None,
+            api_key_id: None,
+            notifications: None,
+            group_id: None,
+            labels: None,
+            paused_at: None,
+            cron_expr: None,
+            retry_policy: None,
+            retry_attempt: 0,
+            priority: 0,
+            run_state: RunState::Pending,
+            misfire_policy: MisfirePolicy::FireOnce,
+            interval_mode: IntervalMode::FixedDelay,
+            ends_at: None,
+            max_executions: None,
+            run_count: 0,
+            timezone: None,
+            rrule: None,
+            blackout_windows: None,
+            deferred_from: None,
+            execution_windows: None,
+            depends_on: None,
+            next_task_id: None,
+            run_if: None,
+        }
+    }
+
+    /// A cron task first triggering at `trigger_at`, which should be an occurrence of
+    /// `cron_expr`.
+    pub fn new_cron(
+        name: impl Into<String>,
+        trigger_at: DateTime<Utc>,
+        cron_expr: impl Into<String>,
+        payload: Value,
+    ) -> Self {
+        Self {
+            task_type: TaskType::Cron,
+            cron_expr: Some(cron_expr.into()),
+            ..Self::new_once(name, trigger_at, payload)
+        }
+    }
+
+    /// A recurrence rule task first triggering at `trigger_at`, which should be an
+    /// occurrence of `rrule`.
+    pub fn new_rrule(
+        name: impl Into<String>,
+        trigger_at: DateTime<Utc>,
+        rrule: impl Into<String>,
+        payload: Value,
+    ) -> Self {
+        Self {
+            task_type: TaskType::Rrule,
+            rrule: Some(rrule.into()),
+            ..Self::new_once(name, trigger_at, payload)
+        }
+    }
+
+    /// The next occurrence of a cron or recurrence rule task's schedule after `after`;
+    /// `None` for other tasks or if the schedule has no further occurrence.
+    pub fn next_calendar_trigger(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self.task_type {
+            TaskType::Cron | TaskType::Rrule => self.recurrence()?.following(after),
+            TaskType::Once | TaskType::Interval => None,
+        }
+    }
+
+    /// The task's schedule, or `None` if its schedule fields are missing or invalid.
+    pub fn recurrence(&self) -> Option<Recurrence> {
+        let tz = self.tz();
+        Some(match self.task_type {
+            TaskType::Once => Recurrence::Once,
+            TaskType::Interval => Recurrence::Interval(self.interval_seconds?),
+            TaskType::Cron => Recurrence::Cron {
+                expr: self.cron_expr.clone()?,
+                tz,
+            },
+            TaskType::Rrule => Recurrence::Rrule {
+                rule: crate::schedule::parse_rrule(self.rrule.as_deref()?, self.trigger_at, tz)
+                    .ok()?,
+                tz,
+            },
+        })
+    }
+
+    /// The first time at or after `at` the task's execution windows allow it to run; `at`
+    /// itself if it has no windows.
+    pub fn within_windows(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        let windows = self.execution_windows.as_deref().unwrap_or_default();
+        crate::schedule::next_open(windows, at, self.tz()).unwrap_or(at)
+    }
+
+    /// The time zone the task's calendar schedule is evaluated in; UTC unless it has one.
+    pub fn tz(&self) -> Tz {
+        self.timezone
+            .as_deref()
+            .and_then(|name| name.parse().ok())
+            .unwrap_or(Tz::UTC)
+    }
+
+    /// Whether the task is active, paused or deleted. Tasks of a paused group stay active.
+    pub fn status(&self) -> TaskStatus {
+        TaskStatus::of(self.deleted_at, self.paused_at)
+    }
+}
+
+impl TaskSummary {
+    /// Whether the task is active, paused or deleted.
+    pub fn status(&self) -> TaskStatus {
+        TaskStatus::of(self.deleted_at, self.paused_at)
+    }
+}
+
+/// Upper bound for a single retry delay, however far the backoff has grown.
+pub const MAX_RETRY_DELAY_SECONDS: i64 = 24 * 60 * 60;
+
+impl ExecutionRetry {
+    fn default_multiplier() -> f64 {
+        2.0
+    }
+
+    /// Delay before retry number `attempt`, starting at 1:
+    /// `initial_delay_seconds * backoff_multiplier^(attempt - 1)`, capped at
+    /// [`MAX_RETRY_DELAY_SECONDS`].
+    pub fn delay(&self, attempt: u32) -> chrono::Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let seconds = self.initial_delay_seconds as f64 * self.backoff_multiplier.powi(exponent);
+        chrono::Duration::seconds(seconds.min(MAX_RETRY_DELAY_SECONDS as f64) as i64)
+    }
+}
+
+impl NotificationRoute {
+    fn default_events() -> Vec<NotificationEvent> {
+        vec![NotificationEvent::Failure, NotificationEvent::AutoDisable]
+    }
+}
+
+impl Notifications {
+    /// True if any route is subscribed to `event`.
+    pub fn wants(&self, event: NotificationEvent) -> bool {
+        self.routes
+            .iter()
+            .any(|route| route.events.contains(&event))
+    }
+
+    /// Channels subscribed to `event`.
+    pub fn channels_for(
+        &self,
+        event: NotificationEvent,
+    ) -> impl Iterator<Item = &NotificationChannel> {
+        self.routes
+            .iter()
+            .filter(move |route| route.events.contains(&event))
+            .map(|route| &route.channel)
+    }
+}
+
+impl TaskSelector {
+    /// True if the task `id` with these labels and group is selected.
+    pub fn matches(&self, id: Uuid, labels: Option<&Labels>, group_id: Option<Uuid>) -> bool {
+        match self {
+            TaskSelector::Labels(selector) => selector
+                .iter()
+                .all(|(key, value)| labels.and_then(|l| l.get(key)) == Some(value)),
+            TaskSelector::Group(group) => group_id == Some(*group),
+            TaskSelector::Task(task) => id == *task,
         }
     }
 }
@@ -101,6 +923,97 @@ impl Execution {
             executed_at: Utc::now(),
             output,
             status,
+            payload: None,
+            replay_of: None,
+            duration_ms: None,
+            deferred_from: None,
+            lag_ms: None,
+            scheduled_for: None,
+            started_at: None,
+            finished_at: None,
+            attempt: None,
+            failure_kind: None,
         }
     }
 }
+
+impl BlackoutWindow {
+    /// True if `at` falls inside the window.
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        self.starts_at <= at && at < self.ends_at
+    }
+
+    /// When runs due at `at` may fire: the end of the window containing it, or of the last
+    /// of several overlapping or adjoining windows. `None` if no window contains `at`.
+    pub fn release<'a>(
+        windows: impl IntoIterator<Item = &'a BlackoutWindow>,
+        at: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        let windows: Vec<&BlackoutWindow> = windows.into_iter().collect();
+        let mut release = None;
+        while let Some(end) = windows
+            .iter()
+            .filter(|window| window.contains(release.unwrap_or(at)))
+            .map(|window| window.ends_at)
+            .max()
+        {
+            release = Some(end);
+        }
+        release
+    }
+}
+
+impl FromStr for BlackoutWindow {
+    type Err = String;
+
+    /// Parses an RFC 3339 interval such as `2024-12-24T00:00:00Z/2024-12-27T00:00:00Z`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "'{}' is not a blackout window such as 2024-12-24T00:00:00Z/2024-12-27T00:00:00Z",
+                s
+            )
+        };
+        let (starts_at, ends_at) = s.split_once('/').ok_or_else(invalid)?;
+        let window = BlackoutWindow {
+            starts_at: DateTime::parse_from_rfc3339(starts_at.trim())
+                .map_err(|_| invalid())?
+                .with_timezone(&Utc),
+            ends_at: DateTime::parse_from_rfc3339(ends_at.trim())
+                .map_err(|_| invalid())?
+                .with_timezone(&Utc),
+        };
+        if window.ends_at <= window.starts_at {
+            return Err(format!("Blackout window '{}' ends before it starts", s));
+        }
+        Ok(window)
+    }
+}
+
+impl ExecutionCursor {
+    /// Cursor pointing just past the given execution.
+    pub fn after(exec: &ExecutionSummary) -> Self {
+        Self {
+            executed_at: exec.executed_at,
+            id: exec.id,
+        }
+    }
+
+    /// Encodes the cursor as an opaque, URL-safe token.
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(format!("{}|{}", self.executed_at.to_rfc3339(), self.id))
+    }
+
+    /// Decodes a token produced by [`ExecutionCursor::encode`], returning `None` if malformed.
+    pub fn decode(token: &str) -> Option<Self> {
+        let raw = String::from_utf8(URL_SAFE_NO_PAD.decode(token).ok()?).ok()?;
+        let (executed_at, id) = raw.split_once('|')?;
+
+        Some(Self {
+            executed_at: DateTime::parse_from_rfc3339(executed_at)
+                .ok()?
+                .with_timezone(&Utc),
+            id: Uuid::parse_str(id).ok()?,
+        })
+    }
+}
@@ -1,12 +1,34 @@
 use axum::{
     Json,
-    http::StatusCode,
+    http::{StatusCode, header},
     response::{IntoResponse, Response},
 };
 
+use crate::db::retry::is_transient;
+use serde::Serialize;
 use serde_json::json;
 use thiserror::Error;
 
+/// Stable, machine-readable identifier of an error, sent as `code` in every error response
+/// so clients can branch on it instead of on the message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    InternalError,
+    /// The database is busy or unreachable; retrying later may succeed.
+    ServiceUnavailable,
+    NotFound,
+    ValidationFailed,
+    IntervalTooSmall,
+    Unauthorized,
+    Forbidden,
+    QuotaExceeded,
+    ReadOnlyInstance,
+    MaintenanceMode,
+    /// The request clashes with the resource's current state, e.g. a task that is running.
+    Conflict,
+}
+
 #[derive(Error, Debug)]
 pub enum AppError {
     #[error("Database Error: {0}")]
@@ -20,11 +42,74 @@ pub enum AppError {
 
     #[error("Validation Error: {0}")]
     ValidationError(String),
+
+    #[error("interval_seconds must be at least {0} second")]
+    IntervalTooSmall(i64),
+
+    #[error("Missing or invalid API key")]
+    Unauthorized,
+
+    #[error("API key lacks the required scope")]
+    Forbidden,
+
+    #[error("Quota exceeded: {0}")]
+    QuotaExceeded(String),
+
+    #[error("This instance is a read-only follower; send writes to the primary")]
+    ReadOnlyInstance,
+
+    #[error("The API is in maintenance mode and read-only")]
+    Maintenance { retry_after_secs: u64 },
+
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    #[error("Archive Error: {0}")]
+    Archive(String),
+}
+
+impl AppError {
+    /// The code clients see for this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            AppError::Database(e) if is_transient(e) => ErrorCode::ServiceUnavailable,
+            AppError::Database(_) | AppError::Config(_) | AppError::Archive(_) => {
+                ErrorCode::InternalError
+            }
+            AppError::NotFound => ErrorCode::NotFound,
+            AppError::ValidationError(_) => ErrorCode::ValidationFailed,
+            AppError::IntervalTooSmall(_) => ErrorCode::IntervalTooSmall,
+            AppError::Unauthorized => ErrorCode::Unauthorized,
+            AppError::Forbidden => ErrorCode::Forbidden,
+            AppError::QuotaExceeded(_) => ErrorCode::QuotaExceeded,
+            AppError::ReadOnlyInstance => ErrorCode::ReadOnlyInstance,
+            AppError::Maintenance { .. } => ErrorCode::MaintenanceMode,
+            AppError::Conflict(_) => ErrorCode::Conflict,
+        }
+    }
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
+        let code = self.code();
+        if let AppError::Maintenance { retry_after_secs } = self {
+            let body = Json(json!({ "error": self.to_string(), "code": code }));
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                [(header::RETRY_AFTER, retry_after_secs.to_string())],
+                body,
+            )
+                .into_response();
+        }
+
         let (status, message) = match &self {
+            AppError::Database(e) if is_transient(e) => {
+                tracing::warn!("Database unavailable: {:?}", e);
+                (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "Service temporarily unavailable, retry later".to_string(),
+                )
+            }
             AppError::Database(e) => {
                 tracing::error!("Database error: {:?}", e);
                 (
@@ -34,9 +119,23 @@ impl IntoResponse for AppError {
             }
             AppError::NotFound => (StatusCode::NOT_FOUND, "Resource Not Found".to_string()),
             AppError::ValidationError(msg) => (StatusCode::BAD_REQUEST, msg.clone()),
+            AppError::IntervalTooSmall(_) => (StatusCode::BAD_REQUEST, self.to_string()),
+            AppError::Unauthorized => (StatusCode::UNAUTHORIZED, self.to_string()),
+            AppError::Forbidden => (StatusCode::FORBIDDEN, self.to_string()),
+            AppError::QuotaExceeded(_) => (StatusCode::FORBIDDEN, self.to_string()),
+            AppError::ReadOnlyInstance => (StatusCode::FORBIDDEN, self.to_string()),
             AppError::Config(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg.clone()),
+            AppError::Archive(msg) => {
+                tracing::error!("Archive error: {}", msg);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Internal Server Error".to_string(),
+                )
+            }
+            AppError::Conflict(msg) => (StatusCode::CONFLICT, msg.clone()),
+            AppError::Maintenance { .. } => unreachable!("handled above"),
         };
 
-        (status, Json(json!({"error":     message}))).into_response()
+        (status, Json(json!({ "error": message, "code": code }))).into_response()
     }
 }
@@ -0,0 +1,193 @@
+use crate::config::Config;
+use crate::domain::Task;
+use crate::executor::{ExecError, ExecutionContext, Executor};
+use async_trait::async_trait;
+use lapin::options::{BasicPublishOptions, ConfirmSelectOptions};
+use lapin::publisher_confirm::Confirmation;
+use lapin::{BasicProperties, Channel, Connection, ConnectionProperties};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tokio::sync::Mutex;
+
+/// Payload type of RabbitMQ tasks.
+pub const AMQP_TYPE: &str = "amqp";
+
+/// AMQP delivery mode of messages that survive a broker restart.
+const PERSISTENT: u8 = 2;
+
+/// What an AMQP task publishes.
+#[derive(Debug, Deserialize)]
+struct AmqpPayload {
+    /// `""` is the default exchange, which routes by queue name.
+    #[serde(default)]
+    exchange: String,
+    routing_key: String,
+    /// Strings are sent as `text/plain`, anything else as JSON; empty if unset.
+    #[serde(default)]
+    body: Value,
+    #[serde(default = "default_persistent")]
+    persistent: bool,
+    /// Fail the run if no queue is bound to the routing key.
+    #[serde(default)]
+    mandatory: bool,
+}
+
+fn default_persistent() -> bool {
+    true
+}
+
+impl AmqpPayload {
+    fn content(&self) -> (&'static str, Vec<u8>) {
+        match &self.body {
+            Value::Null => ("application/octet-stream", Vec::new()),
+            Value::String(text) => ("text/plain", text.clone().into_bytes()),
+            body => ("application/json", body.to_string().into_bytes()),
+        }
+    }
+}
+
+/// An open connection with a channel in confirm mode.
+struct Link {
+    // Kept so the connection lives as long as its channel
+    _connection: Connection,
+    channel: Channel,
+}
+
+/// Publishes a message to RabbitMQ:
+/// `{"type": "amqp", "exchange": "events", "routing_key": "orders.created", "body": {...}}`.
+///
+/// The executor keeps one connection to `AMQP_URL` for all tasks. It is opened on the first
+/// run and reopened whenever it is found closed, so a broker restart costs at most the run
+/// that noticed it. Publishes wait for the broker's confirmation.
+pub struct AmqpExecutor {
+    url: String,
+    link: Mutex<Option<Link>>,
+}
+
+impl AmqpExecutor {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            link: Mutex::new(None),
+        }
+    }
+
+    /// An executor for the broker in the config; `None` if `AMQP_URL` is unset.
+    pub fn from_config(config: &Config) -> Option<Self> {
+        config.amqp_url.as_ref().map(Self::new)
+    }
+
+    /// The open channel, reconnecting if there is none or it was closed.
+    async fn channel(&self) -> Result<Channel, ExecError> {
+        let mut link = self.link.lock().await;
+        if let Some(open) = link
+            .as_ref()
+            .filter(|open| open.channel.status().connected())
+        {
+            return Ok(open.channel.clone());
+        }
+
+        let connect = async {
+            let connection =
+                Connection::connect(&self.url, ConnectionProperties::default()).await?;
+            let channel = connection.create_channel().await?;
+            channel
+                .confirm_select(ConfirmSelectOptions::default())
+                .await?;
+            Ok::<_, lapin::Error>(Link {
+                _connection: connection,
+                channel,
+            })
+        };
+        let open = connect
+            .await
+            .map_err(|e| ExecError::Failed(format!("Failed to connect to RabbitMQ: {}", e)))?;
+        let channel = open.channel.clone();
+        *link = Some(open);
+        Ok(channel)
+    }
+
+    /// Drops the connection, so the next run opens a new one.
+    async fn disconnect(&self) {
+        self.link.lock().await.take();
+    }
+
+    fn parse(payload: &Value) -> Result<AmqpPayload, ExecError> {
+        let message = AmqpPayload::deserialize(payload)
+            .map_err(|e| ExecError::InvalidPayload(format!("Invalid AMQP payload: {}", e)))?;
+        if message.exchange.is_empty() && message.routing_key.is_empty() {
+            return Err(ExecError::InvalidPayload(
+                "'routing_key' must name a queue when no 'exchange' is given".into(),
+            ));
+        }
+        Ok(message)
+    }
+
+    async fn publish(
+        channel: &Channel,
+        message: &AmqpPayload,
+    ) -> Result<Confirmation, lapin::Error> {
+        let (content_type, body) = message.content();
+        let mut properties = BasicProperties::default().with_content_type(content_type.into());
+        if message.persistent {
+            properties = properties.with_delivery_mode(PERSISTENT);
+        }
+        let options = BasicPublishOptions {
+            mandatory: message.mandatory,
+            ..BasicPublishOptions::default()
+        };
+
+        channel
+            .basic_publish(
+                &message.exchange,
+                &message.routing_key,
+                options,
+                &body,
+                properties,
+            )
+            .await?
+            .await
+    }
+}
+
+#[async_trait]
+impl Executor for AmqpExecutor {
+    /// Publishes the message and waits for the broker to confirm it.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'ExecError::InvalidPayload' if the payload is malformed.
+    /// * Returns 'ExecError::Failed' if the broker cannot be reached, rejects the message,
+    ///   or returns a `mandatory` message as unroutable.
+    ///
+    /// Returns the exchange and routing key on success.
+    async fn execute(&self, task: &Task, _ctx: &ExecutionContext<'_>) -> Result<Value, ExecError> {
+        let message = Self::parse(&task.payload)?;
+        let channel = self.channel().await?;
+
+        let confirmation = match Self::publish(&channel, &message).await {
+            Ok(confirmation) => confirmation,
+            Err(e) => {
+                // The channel is unusable after most errors; start over on the next run
+                self.disconnect().await;
+                return Err(ExecError::Failed(format!("AMQP publish failed: {}", e)));
+            }
+        };
+        match confirmation {
+            Confirmation::Ack(None) | Confirmation::NotRequested => Ok(json!({
+                "exchange": message.exchange,
+                "routing_key": message.routing_key,
+                "confirmation": "ack",
+            })),
+            Confirmation::Ack(Some(_)) => Err(ExecError::Failed(format!(
+                "No queue is bound to routing key '{}'",
+                message.routing_key
+            ))),
+            Confirmation::Nack(_) => Err(ExecError::Failed("RabbitMQ rejected the message".into())),
+        }
+    }
+
+    fn validate(&self, payload: &Value) -> Result<(), ExecError> {
+        Self::parse(payload).map(|_| ())
+    }
+}
@@ -0,0 +1,138 @@
+use crate::config::Config;
+use crate::domain::Task;
+use crate::executor::{ExecError, ExecutionContext, Executor};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+
+/// Payload type of command tasks.
+pub const COMMAND_TYPE: &str = "command";
+
+/// What a command task runs.
+#[derive(Debug, Deserialize)]
+struct CommandPayload {
+    program: String,
+    #[serde(default)]
+    args: Vec<String>,
+    /// Overrides `COMMAND_TIMEOUT_SECS` for this task.
+    timeout_seconds: Option<u64>,
+}
+
+/// Runs a local program: `{"type": "command", "program": "backup", "args": ["--full"]}`.
+///
+/// Only programs on the configured allow-list can be run, and never through a shell, so
+/// arguments are passed as they are. The exit code, stdout and stderr are recorded as the
+/// execution output; a non-zero exit code or a timeout fails the run.
+pub struct CommandExecutor {
+    programs: Vec<String>,
+    timeout: Duration,
+}
+
+impl CommandExecutor {
+    pub fn new(programs: Vec<String>, timeout: Duration) -> Self {
+        Self { programs, timeout }
+    }
+
+    /// An executor for the programs and timeout in the config.
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(
+            config.command_programs.clone(),
+            Duration::from_secs(config.command_timeout_secs),
+        )
+    }
+
+    /// Parses a payload and checks its program against the allow-list.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'ExecError::InvalidPayload' if the payload is malformed or the program is
+    ///   not allowed.
+    fn parse(&self, payload: &Value) -> Result<CommandPayload, ExecError> {
+        let command = CommandPayload::deserialize(payload)
+            .map_err(|e| ExecError::InvalidPayload(format!("Invalid command payload: {}", e)))?;
+        if !self.programs.contains(&command.program) {
+            return Err(ExecError::InvalidPayload(format!(
+                "Program '{}' is not in COMMAND_ALLOWED_PROGRAMS",
+                command.program
+            )));
+        }
+        if command.timeout_seconds == Some(0) {
+            return Err(ExecError::InvalidPayload(
+                "'timeout_seconds' must be positive".into(),
+            ));
+        }
+        Ok(command)
+    }
+}
+
+#[async_trait]
+impl Executor for CommandExecutor {
+    /// Runs the program and waits for it to exit.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'ExecError::InvalidPayload' if the payload is malformed or the program is
+    ///   not allowed.
+    /// * Returns 'ExecError::Failed' if the program cannot be started.
+    /// * Returns 'ExecError::Partial' if it exits with a non-zero code, with the exit code and
+    ///   captured output as on success.
+    /// * Returns 'ExecError::Timeout' if it runs past its timeout; the process is killed.
+    ///
+    /// Returns the exit code and the captured output on success.
+    async fn execute(&self, task: &Task, _ctx: &ExecutionContext<'_>) -> Result<Value, ExecError> {
+        let command = self.parse(&task.payload)?;
+        let timeout = command
+            .timeout_seconds
+            .map(Duration::from_secs)
+            .unwrap_or(self.timeout);
+
+        let child = Command::new(&command.program)
+            .args(&command.args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| {
+                ExecError::Failed(format!("Failed to start '{}': {}", command.program, e))
+            })?;
+
+        // Dropping the unfinished future kills the process
+        let output = tokio::time::timeout(timeout, child.wait_with_output())
+            .await
+            .map_err(|_| {
+                ExecError::Timeout(format!(
+                    "Command timed out after {} seconds",
+                    timeout.as_secs()
+                ))
+            })?
+            .map_err(|e| ExecError::Failed(format!("Failed to wait for command: {}", e)))?;
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let captured = json!({
+            "exit_code": output.status.code(),
+            "stdout": String::from_utf8_lossy(&output.stdout),
+            "stderr": stderr,
+        });
+        if output.status.success() {
+            Ok(captured)
+        } else {
+            // No exit code if the process was killed by a signal
+            let code = output
+                .status
+                .code()
+                .map_or("a signal".to_string(), |code| code.to_string());
+            Err(ExecError::Partial {
+                message: format!("Command exited with {}: {}", code, stderr),
+                output: captured,
+            })
+        }
+    }
+
+    fn validate(&self, payload: &Value) -> Result<(), ExecError> {
+        self.parse(payload).map(|_| ())
+    }
+}
@@ -0,0 +1,150 @@
+//! Conditions on the output of the task a task depends on, deciding whether a run goes ahead.
+//!
+//! Paths, here and wherever else a JSONPath picks a value out of an output, are a small
+//! JSONPath subset: `$` for the whole output, followed by `.field`, `['field']` and
+//! `[index]` steps, e.g. `$.items[0].status`.
+use crate::errors::AppError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// How the value at a condition's path is compared with the condition's value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConditionOp {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    /// A string containing a substring, an array containing an element or an object
+    /// containing a key.
+    Contains,
+    /// The path is present; takes no value.
+    Exists,
+}
+
+/// A check against the output of the task a task depends on, e.g.
+/// `{"path": "$.status", "op": "eq", "value": "changed"}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunCondition {
+    pub path: String,
+    pub op: ConditionOp,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<Value>,
+}
+
+/// One step of a parsed path.
+#[derive(Debug, PartialEq)]
+enum Step {
+    Field(String),
+    Index(usize),
+}
+
+impl RunCondition {
+    /// Checks that the path parses and that the operator has the value it needs.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::ValidationError' if the path is not a supported JSONPath, or the
+    ///   value is missing for an operator other than `exists`.
+    pub fn validate(&self) -> Result<(), AppError> {
+        if !is_valid_path(&self.path) {
+            return Err(AppError::ValidationError(format!(
+                "'{}' is not a supported JSONPath",
+                self.path
+            )));
+        }
+        if self.value.is_none() && self.op != ConditionOp::Exists {
+            return Err(AppError::ValidationError(
+                "run_if needs a value to compare with".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Whether `output` meets the condition. A missing path meets no condition; values of
+    /// different types are only ever unequal.
+    pub fn matches(&self, output: &Value) -> bool {
+        let Some(actual) = select(output, &self.path) else {
+            return false;
+        };
+        let expected = self.value.as_ref().unwrap_or(&Value::Null);
+        match self.op {
+            ConditionOp::Exists => true,
+            ConditionOp::Eq => equal(actual, expected),
+            ConditionOp::Ne => !equal(actual, expected),
+            ConditionOp::Gt => compare(actual, expected).is_some_and(|o| o.is_gt()),
+            ConditionOp::Gte => compare(actual, expected).is_some_and(|o| o.is_ge()),
+            ConditionOp::Lt => compare(actual, expected).is_some_and(|o| o.is_lt()),
+            ConditionOp::Lte => compare(actual, expected).is_some_and(|o| o.is_le()),
+            ConditionOp::Contains => match (actual, expected) {
+                (Value::String(text), Value::String(part)) => text.contains(part.as_str()),
+                (Value::Array(items), _) => items.iter().any(|item| equal(item, expected)),
+                (Value::Object(fields), Value::String(key)) => fields.contains_key(key),
+                _ => false,
+            },
+        }
+    }
+}
+
+/// Splits a path into its steps, or `None` if it is not one this module supports.
+fn parse_path(path: &str) -> Option<Vec<Step>> {
+    let mut rest = path.trim().strip_prefix('$')?;
+    let mut steps = Vec::new();
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            if end == 0 {
+                return None;
+            }
+            steps.push(Step::Field(after[..end].to_string()));
+            rest = &after[end..];
+        } else {
+            let after = rest.strip_prefix('[')?;
+            let end = after.find(']')?;
+            let inner = after[..end].trim();
+            let quoted = ['\'', '"']
+                .iter()
+                .find_map(|q| inner.strip_prefix(*q).and_then(|i| i.strip_suffix(*q)));
+            steps.push(match quoted {
+                Some(field) => Step::Field(field.to_string()),
+                None => Step::Index(inner.parse().ok()?),
+            });
+            rest = &after[end + 1..];
+        }
+    }
+    Some(steps)
+}
+
+/// Whether `path` is a JSONPath this module supports.
+pub fn is_valid_path(path: &str) -> bool {
+    parse_path(path).is_some()
+}
+
+/// The value at `path` in `value`, or `None` if the path is unsupported or leads nowhere.
+pub fn select<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    parse_path(path)?
+        .iter()
+        .try_fold(value, |current, step| match step {
+            Step::Field(field) => current.get(field),
+            Step::Index(index) => current.get(index),
+        })
+}
+
+/// JSON equality, except that numbers compare by value, so `1` equals `1.0`.
+fn equal(a: &Value, b: &Value) -> bool {
+    match (a.as_f64(), b.as_f64()) {
+        (Some(a), Some(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Orders two numbers or two strings; other values are not ordered.
+fn compare(a: &Value, b: &Value) -> Option<std::cmp::Ordering> {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64()?.partial_cmp(&b.as_f64()?),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        _ => None,
+    }
+}
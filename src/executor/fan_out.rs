@@ -0,0 +1,229 @@
+use crate::domain::Task;
+use crate::executor::condition;
+use crate::executor::registry::payload_type;
+use crate::executor::template;
+use crate::executor::{ExecError, ExecutionContext, Executor};
+use async_trait::async_trait;
+use futures_util::{StreamExt, stream};
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+
+/// Payload type of fan-out tasks.
+pub const FAN_OUT_TYPE: &str = "fan_out";
+
+/// Children of a fan-out task that run at once, unless its payload says otherwise.
+pub const DEFAULT_FAN_OUT_PARALLELISM: usize = 4;
+
+/// Children a single fan-out run may start.
+pub const MAX_FAN_OUT_ITEMS: usize = 1000;
+
+/// What a fan-out task runs.
+#[derive(Debug, Deserialize)]
+struct FanOutPayload {
+    /// The items, given up front.
+    items: Option<Vec<Value>>,
+    /// Where to fetch the items from instead.
+    items_from: Option<ItemSource>,
+    /// Payload of each child, with `{{item}}` and `{{index}}` placeholders.
+    task: Value,
+    max_parallel: Option<usize>,
+}
+
+/// A payload whose output holds the items, e.g. a webhook listing them.
+#[derive(Debug, Deserialize)]
+struct ItemSource {
+    payload: Value,
+    /// JSONPath of the array in the output, e.g. `$.response.items`.
+    path: String,
+}
+
+/// Runs one child per item of an array, e.g.
+/// `{"type": "fan_out", "items": [1, 2], "task": {"url": "https://x.test/{{item}}"}}`.
+///
+/// The items are either given as `items` or fetched by running `items_from.payload` and
+/// taking the array at `items_from.path` in its output; a webhook's `response` body is read
+/// as JSON for this. Each child runs `task` with the item filled in, through the executor
+/// its type names, at most `max_parallel` at a time. The run succeeds if every child does,
+/// and its output lists each child's item and outcome.
+pub struct FanOutExecutor;
+
+/// The payloads a fan-out runs besides its own: the child template and the item source, if
+/// any. A child whose type is filled in per item is left out, as its executor is only known
+/// at run time.
+pub fn nested_payloads(payload: &Value) -> Vec<&Value> {
+    let child = payload
+        .get("task")
+        .filter(|child| !payload_type(child).contains("{{"));
+    let source = payload
+        .get("items_from")
+        .and_then(|source| source.get("payload"));
+    child.into_iter().chain(source).collect()
+}
+
+impl FanOutExecutor {
+    /// Parses a payload.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'ExecError::InvalidPayload' if the payload is malformed, gives both or
+    ///   neither of `items` and `items_from`, has an unsupported path or too many items,
+    ///   sets `max_parallel` to 0, or its child or item source is itself a fan-out.
+    fn parse(payload: &Value) -> Result<FanOutPayload, ExecError> {
+        let fan_out = FanOutPayload::deserialize(payload)
+            .map_err(|e| ExecError::InvalidPayload(format!("Invalid fan_out payload: {}", e)))?;
+        if fan_out.items.is_some() == fan_out.items_from.is_some() {
+            return Err(ExecError::InvalidPayload(
+                "Set exactly one of 'items' and 'items_from'".into(),
+            ));
+        }
+        // Nesting would multiply the children of a single run
+        let nested = std::iter::once(&fan_out.task)
+            .chain(fan_out.items_from.as_ref().map(|source| &source.payload));
+        for payload in nested {
+            if payload_type(payload) == FAN_OUT_TYPE {
+                return Err(ExecError::InvalidPayload(
+                    "A fan-out cannot run another fan-out".into(),
+                ));
+            }
+        }
+        if let Some(source) = &fan_out.items_from
+            && !condition::is_valid_path(&source.path)
+        {
+            return Err(ExecError::InvalidPayload(format!(
+                "'{}' is not a supported JSONPath",
+                source.path
+            )));
+        }
+        if fan_out
+            .items
+            .as_ref()
+            .is_some_and(|items| items.len() > MAX_FAN_OUT_ITEMS)
+        {
+            return Err(ExecError::InvalidPayload(format!(
+                "A fan-out runs at most {} items",
+                MAX_FAN_OUT_ITEMS
+            )));
+        }
+        if fan_out.max_parallel == Some(0) {
+            return Err(ExecError::InvalidPayload(
+                "'max_parallel' must be positive".into(),
+            ));
+        }
+        Ok(fan_out)
+    }
+
+    /// Runs the item source and picks the array out of its output.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'ExecError::Failed' if the source fails, or its output has no array at the
+    ///   path or one with too many items.
+    async fn fetch_items(
+        task: &Task,
+        source: ItemSource,
+        ctx: &ExecutionContext<'_>,
+    ) -> Result<Vec<Value>, ExecError> {
+        let source_task = Task {
+            payload: source.payload,
+            ..task.clone()
+        };
+        let mut output = ctx
+            .executors
+            .execute(&source_task, ctx)
+            .await
+            .map_err(|e| ExecError::Failed(format!("Fetching the items failed: {}", e)))?;
+        if let Some(Value::String(body)) = output.get("response")
+            && let Ok(body) = serde_json::from_str::<Value>(body)
+        {
+            output["response"] = body;
+        }
+
+        match condition::select(&output, &source.path) {
+            Some(Value::Array(items)) if items.len() <= MAX_FAN_OUT_ITEMS => Ok(items.clone()),
+            Some(Value::Array(_)) => Err(ExecError::Failed(format!(
+                "A fan-out runs at most {} items",
+                MAX_FAN_OUT_ITEMS
+            ))),
+            _ => Err(ExecError::Failed(format!(
+                "No array at '{}' in the fetched output",
+                source.path
+            ))),
+        }
+    }
+}
+
+#[async_trait]
+impl Executor for FanOutExecutor {
+    /// Runs a child for each item and collects their outcomes.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'ExecError::InvalidPayload' if the payload is malformed.
+    /// * Returns 'ExecError::Failed' if the items cannot be fetched.
+    /// * Returns 'ExecError::Partial' if any child fails, with every child's outcome.
+    ///
+    /// Returns every child's item and output on success.
+    async fn execute(&self, task: &Task, ctx: &ExecutionContext<'_>) -> Result<Value, ExecError> {
+        let fan_out = Self::parse(&task.payload)?;
+        let items = match (fan_out.items, fan_out.items_from) {
+            (Some(items), _) => items,
+            (None, Some(source)) => Self::fetch_items(task, source, ctx).await?,
+            (None, None) => Vec::new(),
+        };
+
+        let template = &fan_out.task;
+        let children: Vec<Value> = stream::iter(items.into_iter().enumerate())
+            .map(|(index, item)| async move {
+                let mut payload = template.clone();
+                template::render_item(&mut payload, &item, index);
+                let child = Task {
+                    payload,
+                    ..task.clone()
+                };
+                let mut outcome = Map::new();
+                outcome.insert("index".into(), json!(index));
+                outcome.insert("item".into(), item);
+                let result = match payload_type(&child.payload) {
+                    FAN_OUT_TYPE => Err(ExecError::InvalidPayload(
+                        "A fan-out cannot run another fan-out".into(),
+                    )),
+                    _ => ctx.executors.execute(&child, ctx).await,
+                };
+                match result {
+                    Ok(output) => {
+                        outcome.insert("status".into(), json!("success"));
+                        outcome.insert("output".into(), output);
+                    }
+                    Err(e) => {
+                        outcome.insert("status".into(), json!("failure"));
+                        outcome.insert("error".into(), json!(e.to_string()));
+                    }
+                }
+                Value::Object(outcome)
+            })
+            .buffered(fan_out.max_parallel.unwrap_or(DEFAULT_FAN_OUT_PARALLELISM))
+            .collect()
+            .await;
+
+        let failed = children
+            .iter()
+            .filter(|child| child["status"] == "failure")
+            .count();
+        let output = json!({
+            "succeeded": children.len() - failed,
+            "failed": failed,
+            "children": children,
+        });
+        if failed > 0 {
+            return Err(ExecError::Partial {
+                message: format!("{} of {} children failed", failed, children.len()),
+                output,
+            });
+        }
+        Ok(output)
+    }
+
+    fn validate(&self, payload: &Value) -> Result<(), ExecError> {
+        Self::parse(payload).map(|_| ())
+    }
+}
@@ -0,0 +1,172 @@
+use reqwest::Url;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+/// Which hosts webhooks may call.
+///
+/// Host patterns are names like `api.example.com`, or `*.example.com` for any subdomain.
+/// A denied host is rejected even if it is also allowed; with an allow-list, every other
+/// host is rejected.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HostPolicy {
+    allowed: Vec<String>,
+    denied: Vec<String>,
+    /// Reject loopback, private, link-local and other non-public addresses, whether given
+    /// in the URL or resolved from its host name.
+    block_private: bool,
+}
+
+impl HostPolicy {
+    pub fn new<A, D>(allowed: A, denied: D, block_private: bool) -> Self
+    where
+        A: IntoIterator,
+        A::Item: AsRef<str>,
+        D: IntoIterator,
+        D::Item: AsRef<str>,
+    {
+        Self {
+            allowed: patterns(allowed),
+            denied: patterns(denied),
+            block_private,
+        }
+    }
+
+    /// True if addresses must be checked as host names are resolved.
+    pub fn blocks_private(&self) -> bool {
+        self.block_private
+    }
+
+    /// Checks a webhook URL before it is called. Addresses behind a host name are checked
+    /// when the name is resolved, see [`GuardedResolver`].
+    ///
+    /// # Errors
+    ///
+    /// * Returns an error message if the URL is not HTTP(S), or its host is denied, not
+    ///   allowed, or a blocked address.
+    pub fn check_url(&self, url: &Url) -> Result<(), String> {
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(format!(
+                "Webhook URL scheme '{}' is not allowed",
+                url.scheme()
+            ));
+        }
+        let host = url
+            .host_str()
+            .ok_or("Webhook URL has no host")?
+            .trim_end_matches('.')
+            .to_ascii_lowercase();
+        // IPv6 hosts are bracketed in URLs
+        if let Ok(ip) = host.trim_start_matches('[').trim_end_matches(']').parse() {
+            self.check_ip(ip)?;
+        }
+        self.check_host(&host)
+    }
+
+    fn check_host(&self, host: &str) -> Result<(), String> {
+        if self.denied.iter().any(|pattern| matches(pattern, host)) {
+            return Err(format!("Webhook host '{}' is denied", host));
+        }
+        if !self.allowed.is_empty() && !self.allowed.iter().any(|pattern| matches(pattern, host)) {
+            return Err(format!(
+                "Webhook host '{}' is not in WEBHOOK_ALLOWED_HOSTS",
+                host
+            ));
+        }
+        Ok(())
+    }
+
+    fn check_ip(&self, ip: IpAddr) -> Result<(), String> {
+        if self.block_private && !is_public(ip) {
+            return Err(format!("Webhook address {} is not public", ip));
+        }
+        Ok(())
+    }
+}
+
+fn patterns<I>(hosts: I) -> Vec<String>
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    hosts
+        .into_iter()
+        .map(|host| {
+            host.as_ref()
+                .trim()
+                .trim_end_matches('.')
+                .to_ascii_lowercase()
+        })
+        .filter(|host| !host.is_empty())
+        .collect()
+}
+
+fn matches(pattern: &str, host: &str) -> bool {
+    match pattern.strip_prefix("*.") {
+        Some(domain) => host
+            .strip_suffix(domain)
+            .is_some_and(|sub| sub.ends_with('.') && sub.len() > 1),
+        None => pattern == host,
+    }
+}
+
+/// False for addresses that reach the scheduler's own host or network rather than the
+/// internet.
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(mapped) => is_public_v4(mapped),
+            None => is_public_v6(ip),
+        },
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    // 100.64.0.0/10 is carrier-grade NAT, shared by the provider's customers
+    let shared = a == 100 && (64..128).contains(&b);
+    !(ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || shared)
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    !(ip.is_loopback()
+        || ip.is_unspecified()
+        || ip.is_unique_local()
+        || ip.is_unicast_link_local()
+        || ip.is_multicast())
+}
+
+/// Resolves host names like the system resolver, failing the request if a name resolves to
+/// an address the policy blocks. Checking at connection time, rather than when the task is
+/// created, also covers names that are changed to point inside the network later.
+pub struct GuardedResolver {
+    policy: HostPolicy,
+}
+
+impl GuardedResolver {
+    pub fn new(policy: HostPolicy) -> Self {
+        Self { policy }
+    }
+}
+
+impl Resolve for GuardedResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let policy = self.policy.clone();
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> =
+                tokio::net::lookup_host((name.as_str(), 0)).await?.collect();
+            for addr in &addrs {
+                policy
+                    .check_ip(addr.ip())
+                    .map_err(|e| format!("{} ('{}' resolves to it)", e, name.as_str()))?;
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
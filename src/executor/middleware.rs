@@ -0,0 +1,82 @@
+use crate::domain::Task;
+use async_trait::async_trait;
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Outcome of a single executor invocation: the output on success, or an error message.
+pub type ExecutionOutcome = Result<Value, String>;
+
+/// A hook wrapped around every executor invocation.
+///
+/// Middlewares are composed into a [`MiddlewareChain`] at startup. `before` hooks run in
+/// registration order and may mutate the task (e.g. inject secrets into the payload);
+/// `after` hooks run in reverse order and may inspect or rewrite the outcome.
+#[async_trait]
+pub trait ExecutionMiddleware: Send + Sync {
+    /// Called before the executor runs.
+    ///
+    /// # Errors
+    ///
+    /// * Returning an error short-circuits the execution; the error is recorded as the outcome.
+    async fn before(&self, _task: &mut Task) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Called after the executor returns (or after a `before` hook aborted the execution).
+    async fn after(&self, _task: &Task, _outcome: &mut ExecutionOutcome) {}
+}
+
+/// Ordered collection of middlewares applied around executor invocations.
+#[derive(Clone, Default)]
+pub struct MiddlewareChain {
+    layers: Vec<Arc<dyn ExecutionMiddleware>>,
+}
+
+impl MiddlewareChain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a middleware to the end of the chain.
+    pub fn with(mut self, middleware: impl ExecutionMiddleware + 'static) -> Self {
+        self.layers.push(Arc::new(middleware));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    /// Runs every `before` hook in order, stopping at the first error.
+    pub async fn before(&self, task: &mut Task) -> Result<(), String> {
+        for layer in &self.layers {
+            layer.before(task).await?;
+        }
+        Ok(())
+    }
+
+    /// Runs every `after` hook in reverse order.
+    pub async fn after(&self, task: &Task, outcome: &mut ExecutionOutcome) {
+        for layer in self.layers.iter().rev() {
+            layer.after(task, outcome).await;
+        }
+    }
+}
+
+/// Logs the start and outcome of every execution.
+pub struct LoggingMiddleware;
+
+#[async_trait]
+impl ExecutionMiddleware for LoggingMiddleware {
+    async fn before(&self, task: &mut Task) -> Result<(), String> {
+        tracing::debug!(task_id = %task.id, name = %task.name, "Invoking executor");
+        Ok(())
+    }
+
+    async fn after(&self, task: &Task, outcome: &mut ExecutionOutcome) {
+        match outcome {
+            Ok(_) => tracing::debug!(task_id = %task.id, "Executor succeeded"),
+            Err(e) => tracing::warn!(task_id = %task.id, error = %e, "Executor failed"),
+        }
+    }
+}
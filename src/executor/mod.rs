@@ -0,0 +1,135 @@
+//! Task execution plumbing shared by the service and the scheduler.
+//!
+//! Executors run a task's payload. The [`ExecutorRegistry`] picks one by the payload's
+//! `type` field, so new kinds of tasks plug in without touching the service.
+use crate::config::Config;
+use crate::domain::{FailureKind, Task};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use thiserror::Error;
+use uuid::Uuid;
+
+#[cfg(feature = "amqp")]
+pub mod amqp;
+pub mod command;
+pub mod condition;
+pub mod fan_out;
+pub mod hosts;
+pub mod middleware;
+#[cfg(feature = "nats")]
+pub mod nats;
+pub mod oauth;
+#[cfg(feature = "redis")]
+pub mod redis;
+pub mod registry;
+#[cfg(feature = "scripting")]
+pub mod script;
+pub mod template;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod webhook;
+pub mod webhook_log;
+
+pub use command::CommandExecutor;
+pub use fan_out::FanOutExecutor;
+pub use hosts::HostPolicy;
+pub use middleware::{ExecutionMiddleware, LoggingMiddleware, MiddlewareChain};
+pub use registry::ExecutorRegistry;
+pub use webhook::WebhookExecutor;
+pub use webhook_log::{DebugOptions, Redaction};
+
+/// Why an executor did not produce an output. The message is recorded as the execution's
+/// error.
+#[derive(Debug, Clone, Error, PartialEq)]
+pub enum ExecError {
+    /// The payload does not describe something the executor can run.
+    #[error("{0}")]
+    InvalidPayload(String),
+    /// No executor is registered for the payload's type.
+    #[error("No executor is registered for type '{0}'")]
+    UnknownType(String),
+    /// The executor ran and the invocation failed.
+    #[error("{0}")]
+    Failed(String),
+    /// The invocation did not finish in time.
+    #[error("{0}")]
+    Timeout(String),
+    /// The target of the invocation could not be reached.
+    #[error("{0}")]
+    Connection(String),
+    /// The target answered with a status the task does not accept.
+    #[error("{0}")]
+    HttpStatus(String),
+    /// The invocation failed part way; `output` holds what it produced before failing.
+    #[error("{message}")]
+    Partial { message: String, output: Value },
+}
+
+impl ExecError {
+    /// Short machine-readable cause recorded next to the error message, for failures worth
+    /// telling apart.
+    pub fn reason(&self) -> Option<&'static str> {
+        match self {
+            ExecError::Timeout(_) => Some("timeout"),
+            _ => None,
+        }
+    }
+
+    /// The category the failure is recorded under.
+    pub fn kind(&self) -> FailureKind {
+        match self {
+            ExecError::InvalidPayload(_) | ExecError::UnknownType(_) => FailureKind::Validation,
+            ExecError::Timeout(_) => FailureKind::Timeout,
+            ExecError::Connection(_) => FailureKind::Connection,
+            ExecError::HttpStatus(_) => FailureKind::HttpStatus,
+            ExecError::Failed(_) | ExecError::Partial { .. } => FailureKind::Other,
+        }
+    }
+
+    /// What the invocation produced before failing, recorded next to the error.
+    pub fn output(&self) -> Option<&Value> {
+        match self {
+            ExecError::Partial { output, .. } => Some(output),
+            _ => None,
+        }
+    }
+}
+
+/// What an executor knows about the invocation besides the task itself.
+#[derive(Debug, Clone, Copy)]
+pub struct ExecutionContext<'a> {
+    /// The execution record the invocation belongs to.
+    pub execution_id: Uuid,
+    /// When the invocation started, by the service's clock.
+    pub started_at: DateTime<Utc>,
+    pub config: &'a Config,
+    /// The service's HTTP client, shared so runs reuse pooled connections.
+    pub http: &'a reqwest::Client,
+    /// Values of the secrets filled into the payload, to be kept out of logs.
+    pub secrets: &'a [String],
+    /// The service's executors, for executors that run other payloads.
+    pub executors: &'a ExecutorRegistry,
+}
+
+/// Runs tasks of one payload type.
+#[async_trait]
+pub trait Executor: Send + Sync {
+    /// Runs the task, returning the output recorded for a successful execution.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'ExecError::InvalidPayload' if the payload is malformed.
+    /// * Returns 'ExecError::Failed' if the invocation failed.
+    async fn execute(&self, task: &Task, ctx: &ExecutionContext<'_>) -> Result<Value, ExecError>;
+
+    /// Checks a payload when a task is created or changed, so malformed tasks are rejected
+    /// up front instead of failing every run.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'ExecError::InvalidPayload' if the payload cannot be run.
+    fn validate(&self, _payload: &Value) -> Result<(), ExecError> {
+        Ok(())
+    }
+}
@@ -0,0 +1,134 @@
+use crate::config::Config;
+use crate::domain::Task;
+use crate::executor::{ExecError, ExecutionContext, Executor};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tokio::sync::OnceCell;
+
+/// Payload type of NATS tasks.
+pub const NATS_TYPE: &str = "nats";
+
+/// What a NATS task publishes.
+#[derive(Debug, Deserialize)]
+struct NatsPayload {
+    subject: String,
+    /// Strings are sent as they are, anything else as JSON; empty if unset.
+    #[serde(default)]
+    body: Value,
+    /// Publish to a JetStream stream and wait for its acknowledgement.
+    #[serde(default)]
+    jetstream: bool,
+}
+
+impl NatsPayload {
+    fn bytes(&self) -> Vec<u8> {
+        match &self.body {
+            Value::Null => Vec::new(),
+            Value::String(text) => text.clone().into_bytes(),
+            body => body.to_string().into_bytes(),
+        }
+    }
+}
+
+/// Publishes a message: `{"type": "nats", "subject": "orders.created", "body": {...}}`.
+///
+/// Connects to `NATS_URL` on the first run and reuses the connection afterwards; a failed
+/// connection is retried by the next run. Core NATS publishes are not acknowledged, so the
+/// execution only records that the message was flushed to the server; with
+/// `"jetstream": true` the stream's acknowledgement is recorded.
+pub struct NatsExecutor {
+    url: String,
+    token: Option<String>,
+    client: OnceCell<async_nats::Client>,
+}
+
+impl NatsExecutor {
+    pub fn new(url: impl Into<String>, token: Option<String>) -> Self {
+        Self {
+            url: url.into(),
+            token,
+            client: OnceCell::new(),
+        }
+    }
+
+    /// An executor for the server in the config; `None` if `NATS_URL` is unset.
+    pub fn from_config(config: &Config) -> Option<Self> {
+        let url = config.nats_url.as_ref()?;
+        Some(Self::new(url, config.nats_token.clone()))
+    }
+
+    async fn client(&self) -> Result<&async_nats::Client, ExecError> {
+        self.client
+            .get_or_try_init(|| async {
+                let mut options = async_nats::ConnectOptions::new();
+                if let Some(token) = &self.token {
+                    options = options.token(token.clone());
+                }
+                options.connect(self.url.as_str()).await
+            })
+            .await
+            .map_err(|e| ExecError::Failed(format!("Failed to connect to NATS: {}", e)))
+    }
+
+    fn parse(payload: &Value) -> Result<NatsPayload, ExecError> {
+        let message = NatsPayload::deserialize(payload)
+            .map_err(|e| ExecError::InvalidPayload(format!("Invalid NATS payload: {}", e)))?;
+        if message.subject.is_empty() {
+            return Err(ExecError::InvalidPayload(
+                "'subject' must not be empty".into(),
+            ));
+        }
+        Ok(message)
+    }
+}
+
+#[async_trait]
+impl Executor for NatsExecutor {
+    /// Publishes the message and waits until the server has it.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'ExecError::InvalidPayload' if the payload is malformed.
+    /// * Returns 'ExecError::Failed' if the server cannot be reached or JetStream does not
+    ///   acknowledge the message.
+    ///
+    /// Returns the subject and the acknowledgement (`null` for core NATS) on success.
+    async fn execute(&self, task: &Task, _ctx: &ExecutionContext<'_>) -> Result<Value, ExecError> {
+        let message = Self::parse(&task.payload)?;
+        let client = self.client().await?;
+        let body = message.bytes();
+
+        let ack = if message.jetstream {
+            let ack = async_nats::jetstream::new(client.clone())
+                .publish(message.subject.clone(), body.into())
+                .await
+                .map_err(|e| ExecError::Failed(format!("NATS publish failed: {}", e)))?
+                .await
+                .map_err(|e| {
+                    ExecError::Failed(format!("NATS publish was not acknowledged: {}", e))
+                })?;
+            json!({
+                "stream": ack.stream,
+                "sequence": ack.sequence,
+                "duplicate": ack.duplicate,
+            })
+        } else {
+            client
+                .publish(message.subject.clone(), body.into())
+                .await
+                .map_err(|e| ExecError::Failed(format!("NATS publish failed: {}", e)))?;
+            client
+                .flush()
+                .await
+                .map_err(|e| ExecError::Failed(format!("NATS flush failed: {}", e)))?;
+            Value::Null
+        };
+
+        Ok(json!({ "subject": message.subject, "ack": ack }))
+    }
+
+    fn validate(&self, payload: &Value) -> Result<(), ExecError> {
+        Self::parse(payload).map(|_| ())
+    }
+}
@@ -0,0 +1,111 @@
+use crate::executor::ExecError;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// How long before its expiry a cached token is replaced, so it does not expire in flight.
+const EXPIRY_MARGIN: Duration = Duration::from_secs(30);
+
+/// OAuth2 client credentials of a webhook, from the payload's `auth` object:
+/// `{"type": "oauth2", "token_url": "...", "client_id": "...", "client_secret": "..."}`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClientCredentials {
+    pub token_url: String,
+    pub client_id: String,
+    pub client_secret: String,
+    /// Space-separated scopes to request; the server's default if unset.
+    pub scope: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: Option<u64>,
+}
+
+/// Access tokens by the credentials they were issued for, reused until shortly before they
+/// expire. Tokens without an expiry are not cached, and expired ones are dropped once found.
+#[derive(Default)]
+pub struct TokenCache {
+    tokens: Mutex<HashMap<ClientCredentials, (String, Instant)>>,
+}
+
+impl TokenCache {
+    /// A valid access token for the credentials, fetching a new one if none is cached.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'ExecError::Failed' if the token endpoint cannot be reached, refuses the
+    ///   credentials or returns no token.
+    /// * Returns 'ExecError::Timeout' if it does not respond within `timeout`.
+    pub async fn token(
+        &self,
+        client: &reqwest::Client,
+        credentials: &ClientCredentials,
+        timeout: Duration,
+    ) -> Result<String, ExecError> {
+        {
+            let mut tokens = self.lock_tokens();
+            match tokens.get(credentials) {
+                Some((token, expires_at)) if Instant::now() < *expires_at => {
+                    return Ok(token.clone());
+                }
+                Some(_) => {
+                    tokens.remove(credentials);
+                }
+                None => {}
+            }
+        }
+
+        let mut form = vec![("grant_type", "client_credentials")];
+        if let Some(scope) = &credentials.scope {
+            form.push(("scope", scope));
+        }
+        let response = client
+            .post(&credentials.token_url)
+            .basic_auth(&credentials.client_id, Some(&credentials.client_secret))
+            .form(&form)
+            .timeout(timeout)
+            .send()
+            .await
+            .map_err(|e| {
+                if e.is_timeout() {
+                    ExecError::Timeout(format!(
+                        "OAuth2 token request timed out after {} seconds",
+                        timeout.as_secs()
+                    ))
+                } else {
+                    ExecError::Failed(format!("OAuth2 token request failed: {:?}", e))
+                }
+            })?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(ExecError::Failed(format!(
+                "OAuth2 token endpoint returned HTTP {}",
+                status.as_u16()
+            )));
+        }
+        let issued: TokenResponse = response
+            .json()
+            .await
+            .map_err(|e| ExecError::Failed(format!("Invalid OAuth2 token response: {}", e)))?;
+
+        if let Some(expires_in) = issued.expires_in {
+            let lifetime = Duration::from_secs(expires_in).saturating_sub(EXPIRY_MARGIN);
+            let now = Instant::now();
+            let mut tokens = self.lock_tokens();
+            tokens.retain(|_, (_, expires_at)| now < *expires_at);
+            tokens.insert(
+                credentials.clone(),
+                (issued.access_token.clone(), now + lifetime),
+            );
+        }
+        Ok(issued.access_token)
+    }
+
+    fn lock_tokens(&self) -> MutexGuard<'_, HashMap<ClientCredentials, (String, Instant)>> {
+        self.tokens.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,143 @@
+use crate::config::Config;
+use crate::domain::Task;
+use crate::executor::{ExecError, ExecutionContext, Executor};
+use async_trait::async_trait;
+use redis::aio::{ConnectionManager, ConnectionManagerConfig};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::time::Duration;
+use tokio::sync::OnceCell;
+
+/// Payload type of Redis tasks.
+pub const REDIS_TYPE: &str = "redis";
+
+/// What a Redis task issues.
+#[derive(Debug, Deserialize)]
+struct RedisPayload {
+    command: String,
+    /// Strings and numbers are sent as they are, anything else as JSON.
+    #[serde(default)]
+    args: Vec<Value>,
+}
+
+/// Issues a Redis command: `{"type": "redis", "command": "LPUSH", "args": ["jobs", {...}]}`.
+///
+/// Only commands on the allow-list can be issued. The connection to `REDIS_URL` is opened on
+/// the first run and reconnects on its own after the server drops it. The reply is recorded
+/// as the execution output.
+pub struct RedisExecutor {
+    url: String,
+    commands: Vec<String>,
+    connection: OnceCell<ConnectionManager>,
+}
+
+impl RedisExecutor {
+    pub fn new(url: impl Into<String>, commands: Vec<String>) -> Self {
+        Self {
+            url: url.into(),
+            commands: commands.iter().map(|c| c.to_uppercase()).collect(),
+            connection: OnceCell::new(),
+        }
+    }
+
+    /// An executor for the server in the config; `None` if `REDIS_URL` is unset.
+    pub fn from_config(config: &Config) -> Option<Self> {
+        let url = config.redis_url.as_ref()?;
+        Some(Self::new(url, config.redis_commands.clone()))
+    }
+
+    async fn connection(&self) -> Result<ConnectionManager, ExecError> {
+        self.connection
+            .get_or_try_init(|| async {
+                let client = redis::Client::open(self.url.as_str())?;
+                // Fail the run quickly instead of backing off for minutes; the next run
+                // tries again
+                let config = ConnectionManagerConfig::new()
+                    .set_number_of_retries(2)
+                    .set_max_delay(500)
+                    .set_connection_timeout(Duration::from_secs(5));
+                ConnectionManager::new_with_config(client, config).await
+            })
+            .await
+            .cloned()
+            .map_err(|e| ExecError::Failed(format!("Failed to connect to Redis: {}", e)))
+    }
+
+    fn parse(&self, payload: &Value) -> Result<RedisPayload, ExecError> {
+        let mut command = RedisPayload::deserialize(payload)
+            .map_err(|e| ExecError::InvalidPayload(format!("Invalid Redis payload: {}", e)))?;
+        command.command = command.command.to_uppercase();
+        if !self.commands.contains(&command.command) {
+            return Err(ExecError::InvalidPayload(format!(
+                "Command '{}' is not in REDIS_ALLOWED_COMMANDS",
+                command.command
+            )));
+        }
+        Ok(command)
+    }
+}
+
+/// Renders an argument the way a user would type it in `redis-cli`.
+fn argument(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Converts a reply to JSON. Binary strings that are not UTF-8 are replaced lossily.
+fn reply(value: redis::Value) -> Value {
+    match value {
+        redis::Value::Nil => Value::Null,
+        redis::Value::Int(n) => json!(n),
+        redis::Value::Double(n) => json!(n),
+        redis::Value::Boolean(b) => json!(b),
+        redis::Value::Okay => json!("OK"),
+        redis::Value::SimpleString(text) => json!(text),
+        redis::Value::VerbatimString { text, .. } => json!(text),
+        redis::Value::BulkString(bytes) => json!(String::from_utf8_lossy(&bytes)),
+        redis::Value::Array(items) | redis::Value::Set(items) => {
+            Value::Array(items.into_iter().map(reply).collect())
+        }
+        redis::Value::Map(pairs) => Value::Array(
+            pairs
+                .into_iter()
+                .map(|(k, v)| json!([reply(k), reply(v)]))
+                .collect(),
+        ),
+        other => json!(format!("{:?}", other)),
+    }
+}
+
+#[async_trait]
+impl Executor for RedisExecutor {
+    /// Issues the command and waits for its reply.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'ExecError::InvalidPayload' if the payload is malformed or the command is
+    ///   not allowed.
+    /// * Returns 'ExecError::Failed' if the server cannot be reached or replies with an
+    ///   error.
+    ///
+    /// Returns the command's reply on success.
+    async fn execute(&self, task: &Task, _ctx: &ExecutionContext<'_>) -> Result<Value, ExecError> {
+        let command = self.parse(&task.payload)?;
+        let mut connection = self.connection().await?;
+
+        let mut cmd = redis::cmd(&command.command);
+        for arg in &command.args {
+            cmd.arg(argument(arg));
+        }
+        let value: redis::Value = cmd
+            .query_async(&mut connection)
+            .await
+            .map_err(|e| ExecError::Failed(format!("Redis {} failed: {}", command.command, e)))?;
+
+        Ok(json!({ "command": command.command, "reply": reply(value) }))
+    }
+
+    fn validate(&self, payload: &Value) -> Result<(), ExecError> {
+        self.parse(payload).map(|_| ())
+    }
+}
@@ -0,0 +1,118 @@
+use crate::domain::Task;
+use crate::executor::fan_out::{self, FAN_OUT_TYPE};
+use crate::executor::{ExecError, ExecutionContext, Executor, FanOutExecutor, WebhookExecutor};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Payload key naming the executor that runs a task.
+pub const TYPE_KEY: &str = "type";
+
+/// Executor of payloads without a `type`.
+pub const DEFAULT_TYPE: &str = "webhook";
+
+/// The payload type naming a task's executor, e.g. for logs; malformed types are passed
+/// through as `invalid`, for [`ExecutorRegistry::resolve`] to reject.
+pub fn payload_type(payload: &Value) -> &str {
+    match payload.get(TYPE_KEY) {
+        None => DEFAULT_TYPE,
+        Some(Value::String(name)) => name.as_str(),
+        Some(_) => "invalid",
+    }
+}
+
+/// Executors keyed by the payload type they run.
+#[derive(Clone)]
+pub struct ExecutorRegistry {
+    executors: HashMap<String, Arc<dyn Executor>>,
+}
+
+impl std::fmt::Debug for ExecutorRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExecutorRegistry")
+            .field("types", &self.types())
+            .finish()
+    }
+}
+
+impl Default for ExecutorRegistry {
+    /// A registry with the built-in executors.
+    fn default() -> Self {
+        Self::empty()
+            .with(DEFAULT_TYPE, WebhookExecutor::default())
+            .with(FAN_OUT_TYPE, FanOutExecutor)
+    }
+}
+
+impl ExecutorRegistry {
+    /// A registry without any executors, not even the webhook one.
+    pub fn empty() -> Self {
+        Self {
+            executors: HashMap::new(),
+        }
+    }
+
+    /// Registers an executor for payloads of type `name`, replacing any previous one.
+    pub fn with(mut self, name: impl Into<String>, executor: impl Executor + 'static) -> Self {
+        self.executors.insert(name.into(), Arc::new(executor));
+        self
+    }
+
+    /// Registered payload types, sorted.
+    pub fn types(&self) -> Vec<&str> {
+        let mut types: Vec<&str> = self.executors.keys().map(String::as_str).collect();
+        types.sort_unstable();
+        types
+    }
+
+    /// The executor for a payload.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'ExecError::InvalidPayload' if `type` is not a string.
+    /// * Returns 'ExecError::UnknownType' if no executor is registered for it.
+    pub fn resolve(&self, payload: &Value) -> Result<&Arc<dyn Executor>, ExecError> {
+        let name = match payload.get(TYPE_KEY) {
+            None => DEFAULT_TYPE,
+            Some(Value::String(name)) => name.as_str(),
+            Some(_) => {
+                return Err(ExecError::InvalidPayload(format!(
+                    "'{}' must be a string",
+                    TYPE_KEY
+                )));
+            }
+        };
+        self.executors
+            .get(name)
+            .ok_or_else(|| ExecError::UnknownType(name.to_string()))
+    }
+
+    /// Checks that a payload has a registered type and that its executor accepts it, along
+    /// with the payloads a fan-out runs.
+    ///
+    /// # Errors
+    ///
+    /// * Returns the errors of [`ExecutorRegistry::resolve`] and of the executor's check.
+    pub fn validate(&self, payload: &Value) -> Result<(), ExecError> {
+        self.resolve(payload)?.validate(payload)?;
+        if payload_type(payload) == FAN_OUT_TYPE {
+            for nested in fan_out::nested_payloads(payload) {
+                self.validate(nested)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs a task with the executor registered for its payload type.
+    ///
+    /// # Errors
+    ///
+    /// * Returns the errors of [`ExecutorRegistry::resolve`] and of the executor.
+    pub async fn execute(
+        &self,
+        task: &Task,
+        ctx: &ExecutionContext<'_>,
+    ) -> Result<Value, ExecError> {
+        self.resolve(&task.payload)?.execute(task, ctx).await
+    }
+}
@@ -0,0 +1,131 @@
+use crate::config::Config;
+use crate::domain::Task;
+use crate::executor::{ExecError, ExecutionContext, Executor};
+use async_trait::async_trait;
+use rhai::{Dynamic, Engine, EvalAltResult, Scope};
+use serde_json::{Value, json};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Payload type of script tasks.
+pub const SCRIPT_TYPE: &str = "script";
+
+/// Largest string, array or map a script may build.
+const MAX_DATA_SIZE: usize = 1 << 20;
+
+/// Runs a [Rhai](https://rhai.rs) script: `{"type": "script", "script": "payload.n * 2", "n": 21}`.
+///
+/// The script sees the whole task payload as `payload`, and its return value is recorded as
+/// the execution output, next to anything it `print`ed. Rhai has no access to files, the
+/// network or the process, and each run is cut off after `SCRIPT_MAX_OPERATIONS` operations
+/// or `SCRIPT_TIMEOUT_MS`, whichever comes first.
+pub struct ScriptExecutor {
+    max_operations: u64,
+    timeout: Duration,
+}
+
+impl ScriptExecutor {
+    pub fn new(max_operations: u64, timeout: Duration) -> Self {
+        Self {
+            max_operations,
+            timeout,
+        }
+    }
+
+    /// An executor with the budget in the config.
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(
+            config.script_max_operations,
+            Duration::from_millis(config.script_timeout_ms),
+        )
+    }
+
+    /// A sandboxed engine enforcing the budget.
+    fn engine(&self) -> Engine {
+        let mut engine = Engine::new();
+        engine
+            .set_max_operations(self.max_operations)
+            .set_max_call_levels(32)
+            .set_max_expr_depths(64, 32)
+            .set_max_string_size(MAX_DATA_SIZE)
+            .set_max_array_size(MAX_DATA_SIZE)
+            .set_max_map_size(MAX_DATA_SIZE)
+            .on_debug(|_, _, _| {});
+        engine
+    }
+
+    fn script(payload: &Value) -> Result<&str, ExecError> {
+        payload
+            .get("script")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ExecError::InvalidPayload("Missing 'script' in payload".into()))
+    }
+
+    /// Runs a script to completion on the current thread.
+    fn run(&self, script: &str, payload: &Value) -> Result<Value, ExecError> {
+        let mut engine = self.engine();
+        let log = Arc::new(Mutex::new(Vec::<String>::new()));
+        let printed = log.clone();
+        engine.on_print(move |line| printed.lock().unwrap().push(line.to_string()));
+        let deadline = Instant::now() + self.timeout;
+        engine.on_progress(move |_| (Instant::now() > deadline).then_some(Dynamic::UNIT));
+
+        let ast = engine
+            .compile(script)
+            .map_err(|e| ExecError::InvalidPayload(format!("Invalid script: {}", e)))?;
+        let mut scope = Scope::new();
+        let payload = rhai::serde::to_dynamic(payload)
+            .map_err(|e| ExecError::InvalidPayload(format!("Invalid payload: {}", e)))?;
+        scope.push_constant("payload", payload);
+
+        let result = engine
+            .eval_ast_with_scope::<Dynamic>(&mut scope, &ast)
+            .map_err(|e| match *e {
+                EvalAltResult::ErrorTooManyOperations(_) => ExecError::Failed(format!(
+                    "Script exceeded {} operations",
+                    self.max_operations
+                )),
+                EvalAltResult::ErrorTerminated(..) => ExecError::Timeout(format!(
+                    "Script timed out after {} ms",
+                    self.timeout.as_millis()
+                )),
+                e => ExecError::Failed(format!("Script failed: {}", e)),
+            })?;
+        let result: Value = rhai::serde::from_dynamic(&result).map_err(|e| {
+            ExecError::Failed(format!("Script returned an unsupported value: {}", e))
+        })?;
+
+        let log = std::mem::take(&mut *log.lock().unwrap());
+        Ok(json!({ "result": result, "log": log }))
+    }
+}
+
+#[async_trait]
+impl Executor for ScriptExecutor {
+    /// Runs the script on a blocking thread, so a busy script never stalls the scheduler.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'ExecError::InvalidPayload' if the script is missing or does not compile.
+    /// * Returns 'ExecError::Failed' if the script throws, exceeds its operations or returns
+    ///   a value that has no JSON form.
+    /// * Returns 'ExecError::Timeout' if it runs past `SCRIPT_TIMEOUT_MS`.
+    ///
+    /// Returns the script's result and printed lines on success.
+    async fn execute(&self, task: &Task, _ctx: &ExecutionContext<'_>) -> Result<Value, ExecError> {
+        let script = Self::script(&task.payload)?.to_string();
+        let payload = task.payload.clone();
+        let runner = Self::new(self.max_operations, self.timeout);
+        tokio::task::spawn_blocking(move || runner.run(&script, &payload))
+            .await
+            .map_err(|e| ExecError::Failed(format!("Script panicked: {}", e)))?
+    }
+
+    /// Compiles the script, so syntax errors are rejected up front.
+    fn validate(&self, payload: &Value) -> Result<(), ExecError> {
+        self.engine()
+            .compile(Self::script(payload)?)
+            .map_err(|e| ExecError::InvalidPayload(format!("Invalid script: {}", e)))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,172 @@
+//! Execution-context placeholders in webhook URLs and bodies.
+//!
+//! `{{task.id}}`, `{{task.name}}`, `{{execution.id}}`, `{{execution.scheduled_at}}`,
+//! `{{execution.started_at}}` and `{{now}}` are replaced when the request is built.
+//! Unknown placeholders are left as they are.
+//!
+//! The payload of a chained task can also use `{{previous.output}}` (or a field of it, such
+//! as `{{previous.output.items.0.id}}`), `{{previous.execution_id}}`,
+//! `{{previous.task_id}}` and `{{previous.executed_at}}`, filled from the run that started
+//! it before it is dispatched, and the payload a fan-out task runs for each of its items
+//! can use `{{item}}` (or a field of it, such as `{{item.user.id}}`) and `{{index}}`.
+use crate::domain::{Execution, Task};
+use crate::executor::ExecutionContext;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::Value;
+
+/// Values of the placeholders for one run.
+pub struct Variables {
+    values: Vec<(&'static str, String)>,
+}
+
+impl Variables {
+    /// The variables of a run of `task`, with `{{now}}` rendered as `now`.
+    pub fn new(task: &Task, ctx: &ExecutionContext<'_>, now: DateTime<Utc>) -> Self {
+        Self {
+            values: vec![
+                ("task.id", task.id.to_string()),
+                ("task.name", task.name.clone()),
+                ("execution.id", ctx.execution_id.to_string()),
+                ("execution.scheduled_at", timestamp(task.trigger_at)),
+                ("execution.started_at", timestamp(ctx.started_at)),
+                ("now", timestamp(now)),
+            ],
+        }
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|(known, _)| *known == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Replaces the placeholders in `text`, percent-encoding the values if it is a URL.
+    pub fn render(&self, text: &str, url: bool) -> String {
+        replace_placeholders(text, |name| {
+            self.get(name).map(|value| match url {
+                true => percent_encode(value),
+                false => value.to_string(),
+            })
+        })
+    }
+
+    /// Replaces the placeholders in every string of a JSON body.
+    pub fn render_value(&self, value: &mut Value) {
+        match value {
+            Value::String(text) => *text = self.render(text, false),
+            Value::Array(items) => items.iter_mut().for_each(|item| self.render_value(item)),
+            Value::Object(fields) => fields
+                .values_mut()
+                .for_each(|field| self.render_value(field)),
+            _ => {}
+        }
+    }
+}
+
+/// Fills the `{{previous.*}}` placeholders in every string of a chained task's payload
+/// from the run that started it. A string that is nothing but one placeholder becomes the
+/// value itself, so objects and numbers keep their type; elsewhere the value is written as
+/// text. Other placeholders are left for the run itself.
+pub fn render_previous(value: &mut Value, previous: &Execution) {
+    render_json(value, &|name| previous_value(name, previous));
+}
+
+/// Fills `{{item}}`, a field of it such as `{{item.user.id}}`, and `{{index}}` in every
+/// string of a fan-out child's payload, the way [`render_previous`] does.
+pub fn render_item(value: &mut Value, item: &Value, index: usize) {
+    render_json(value, &|name| match name {
+        "index" => Some(Value::from(index)),
+        _ => lookup_path(item, name.strip_prefix("item")?).cloned(),
+    });
+}
+
+/// Replaces the placeholders `lookup` knows in every string of `value`.
+fn render_json(value: &mut Value, lookup: &dyn Fn(&str) -> Option<Value>) {
+    match value {
+        Value::String(text) => {
+            let whole = text
+                .trim()
+                .strip_prefix("{{")
+                .and_then(|rest| rest.strip_suffix("}}"))
+                .filter(|name| !name.contains("{{"))
+                .and_then(|name| lookup(name.trim()));
+            *value = match whole {
+                Some(whole) => whole,
+                None => Value::String(replace_placeholders(text, |name| {
+                    lookup(name).map(|value| match value {
+                        Value::String(text) => text,
+                        other => other.to_string(),
+                    })
+                })),
+            };
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| render_json(item, lookup)),
+        Value::Object(fields) => fields
+            .values_mut()
+            .for_each(|field| render_json(field, lookup)),
+        _ => {}
+    }
+}
+
+/// Follows a path of `.field` and `.index` steps into `value`; an empty path is `value`
+/// itself.
+fn lookup_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    if path.is_empty() {
+        return Some(value);
+    }
+    path.strip_prefix('.')?
+        .split('.')
+        .try_fold(value, |current, field| match current {
+            Value::Array(items) => items.get(field.parse::<usize>().ok()?),
+            _ => current.get(field),
+        })
+}
+
+/// The value of a `previous.*` placeholder, or `None` if it names nothing in `previous`.
+fn previous_value(name: &str, previous: &Execution) -> Option<Value> {
+    match name.strip_prefix("previous.")? {
+        "execution_id" => Some(Value::String(previous.id.to_string())),
+        "task_id" => Some(Value::String(previous.task_id.to_string())),
+        "executed_at" => Some(Value::String(timestamp(previous.executed_at))),
+        path => lookup_path(&previous.output, path.strip_prefix("output")?).cloned(),
+    }
+}
+
+/// Replaces each `{{name}}` in `text` with what `lookup` gives for it, leaving the ones it
+/// does not know.
+fn replace_placeholders(text: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    let mut rendered = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}").map(|end| start + end) else {
+            break;
+        };
+        rendered.push_str(&rest[..start]);
+        match lookup(rest[start + 2..end].trim()) {
+            Some(value) => rendered.push_str(&value),
+            None => rendered.push_str(&rest[start..end + 2]),
+        }
+        rest = &rest[end + 2..];
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+/// Formats a timestamp the way the API serializes them.
+fn timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+/// Encodes everything but unreserved characters, so values are safe in any part of a URL.
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
@@ -0,0 +1,219 @@
+use crate::config::Config;
+use crate::domain::Task;
+use crate::executor::{ExecError, ExecutionContext, Executor};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use wasmtime::{Engine, Instance, Module, Store, StoreLimits, StoreLimitsBuilder, Trap};
+
+/// Payload type of plugin tasks.
+pub const WASM_TYPE: &str = "wasm";
+
+/// How often the engine's epoch advances; run timeouts are rounded up to it.
+const TICK: Duration = Duration::from_millis(10);
+
+/// Linear memory a plugin may grow to.
+const MAX_MEMORY: usize = 64 << 20;
+
+/// Runs a WebAssembly plugin from the plugins directory: `{"type": "wasm", "plugin": "resize"}`
+/// runs `<PLUGINS_DIR>/resize.wasm`.
+///
+/// A plugin is a core module without imports that exports:
+///
+/// * `memory`, its linear memory;
+/// * `alloc(len: i32) -> i32`, returning a buffer of `len` bytes;
+/// * `run(ptr: i32, len: i32) -> i64`, taking the task payload as JSON in the buffer and
+///   returning where its JSON output is, as `ptr << 32 | len`.
+///
+/// Modules are compiled on first use and recompiled when their file changes, so plugins can
+/// be added or replaced without a restart. Each run gets a fresh instance with
+/// `WASM_MAX_FUEL` fuel and `WASM_TIMEOUT_MS` of wall-clock time.
+#[derive(Clone)]
+pub struct WasmExecutor {
+    engine: Engine,
+    dir: PathBuf,
+    fuel: u64,
+    timeout: Duration,
+    modules: Arc<Mutex<HashMap<String, (SystemTime, Module)>>>,
+}
+
+impl WasmExecutor {
+    /// # Errors
+    ///
+    /// * Returns 'ExecError::Failed' if the engine cannot be created on this platform.
+    pub fn new(dir: impl Into<PathBuf>, fuel: u64, timeout: Duration) -> Result<Self, ExecError> {
+        let mut config = wasmtime::Config::new();
+        config.consume_fuel(true).epoch_interruption(true);
+        let engine = Engine::new(&config)
+            .map_err(|e| ExecError::Failed(format!("Failed to create WASM engine: {}", e)))?;
+
+        // Advances the epoch that run timeouts are measured in, until the engine is dropped
+        let weak = engine.weak();
+        std::thread::spawn(move || {
+            while let Some(engine) = weak.upgrade() {
+                engine.increment_epoch();
+                drop(engine);
+                std::thread::sleep(TICK);
+            }
+        });
+
+        Ok(Self {
+            engine,
+            dir: dir.into(),
+            fuel,
+            timeout,
+            modules: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    /// An executor for the plugins directory in the config; `None` if `PLUGINS_DIR` is
+    /// unset.
+    ///
+    /// # Errors
+    ///
+    /// * Returns the errors of [`WasmExecutor::new`].
+    pub fn from_config(config: &Config) -> Result<Option<Self>, ExecError> {
+        let Some(dir) = &config.plugins_dir else {
+            return Ok(None);
+        };
+        Self::new(
+            dir,
+            config.wasm_max_fuel,
+            Duration::from_millis(config.wasm_timeout_ms),
+        )
+        .map(Some)
+    }
+
+    /// The plugin's file, checking that its name cannot escape the plugins directory.
+    fn path(&self, payload: &Value) -> Result<(String, PathBuf), ExecError> {
+        let name = payload
+            .get("plugin")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ExecError::InvalidPayload("Missing 'plugin' in payload".into()))?;
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(ExecError::InvalidPayload(format!(
+                "Invalid plugin name '{}': use letters, digits, '-' and '_'",
+                name
+            )));
+        }
+        Ok((name.to_string(), self.dir.join(format!("{}.wasm", name))))
+    }
+
+    /// The compiled module, recompiling it if the file changed since it was last loaded.
+    fn module(&self, name: &str, path: &PathBuf) -> Result<Module, ExecError> {
+        let modified = std::fs::metadata(path)
+            .and_then(|meta| meta.modified())
+            .map_err(|e| ExecError::Failed(format!("Plugin '{}' is not available: {}", name, e)))?;
+        if let Some((loaded, module)) = self.modules.lock().unwrap().get(name)
+            && *loaded == modified
+        {
+            return Ok(module.clone());
+        }
+
+        let module = Module::from_file(&self.engine, path)
+            .map_err(|e| ExecError::Failed(format!("Plugin '{}' does not compile: {}", name, e)))?;
+        self.modules
+            .lock()
+            .unwrap()
+            .insert(name.to_string(), (modified, module.clone()));
+        Ok(module)
+    }
+
+    /// Runs a plugin to completion on the current thread.
+    fn run(&self, name: &str, module: &Module, payload: &Value) -> Result<Value, ExecError> {
+        let failed = |e: wasmtime::Error| match e.downcast_ref::<Trap>() {
+            Some(Trap::OutOfFuel) => {
+                ExecError::Failed(format!("Plugin '{}' ran out of fuel", name))
+            }
+            Some(Trap::Interrupt) => ExecError::Timeout(format!(
+                "Plugin '{}' timed out after {} ms",
+                name,
+                self.timeout.as_millis()
+            )),
+            _ => ExecError::Failed(format!("Plugin '{}' failed: {:#}", name, e)),
+        };
+
+        let limits = StoreLimitsBuilder::new().memory_size(MAX_MEMORY).build();
+        let mut store: Store<StoreLimits> = Store::new(&self.engine, limits);
+        store.limiter(|limits| limits);
+        store.set_fuel(self.fuel).map_err(failed)?;
+        store.set_epoch_deadline(self.timeout.as_millis().div_ceil(TICK.as_millis()) as u64);
+
+        let instance = Instance::new(&mut store, module, &[]).map_err(failed)?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| ExecError::Failed(format!("Plugin '{}' exports no memory", name)))?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&mut store, "alloc")
+            .map_err(failed)?;
+        let run = instance
+            .get_typed_func::<(i32, i32), i64>(&mut store, "run")
+            .map_err(failed)?;
+
+        let input = payload.to_string().into_bytes();
+        let len = i32::try_from(input.len())
+            .map_err(|_| ExecError::InvalidPayload("Payload is too large for a plugin".into()))?;
+        let ptr = alloc.call(&mut store, len).map_err(failed)?;
+        memory
+            .write(&mut store, ptr as u32 as usize, &input)
+            .map_err(|e| failed(e.into()))?;
+
+        let packed = run.call(&mut store, (ptr, len)).map_err(failed)? as u64;
+        let (out_ptr, out_len) = ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize);
+        if out_len > MAX_MEMORY {
+            return Err(ExecError::Failed(format!(
+                "Plugin '{}' returned {} bytes of output",
+                name, out_len
+            )));
+        }
+        let mut output = vec![0; out_len];
+        memory
+            .read(&store, out_ptr, &mut output)
+            .map_err(|e| failed(e.into()))?;
+
+        // `run` returning 0 means no output
+        if output.is_empty() {
+            return Ok(Value::Null);
+        }
+        serde_json::from_slice(&output).map_err(|e| {
+            ExecError::Failed(format!("Plugin '{}' returned invalid JSON: {}", name, e))
+        })
+    }
+}
+
+#[async_trait]
+impl Executor for WasmExecutor {
+    /// Runs the plugin on a blocking thread, so a busy plugin never stalls the scheduler.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'ExecError::InvalidPayload' if the plugin name is missing or invalid.
+    /// * Returns 'ExecError::Failed' if the plugin is missing, does not compile, traps, runs
+    ///   out of fuel, or returns something other than JSON.
+    /// * Returns 'ExecError::Timeout' if it runs past `WASM_TIMEOUT_MS`.
+    ///
+    /// Returns the plugin's JSON output on success.
+    async fn execute(&self, task: &Task, _ctx: &ExecutionContext<'_>) -> Result<Value, ExecError> {
+        let (name, path) = self.path(&task.payload)?;
+        let payload = task.payload.clone();
+        let runner = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let module = runner.module(&name, &path)?;
+            runner.run(&name, &module, &payload)
+        })
+        .await
+        .map_err(|e| ExecError::Failed(format!("Plugin panicked: {}", e)))?
+    }
+
+    /// Checks the plugin name only; a plugin may be dropped in after the task is created.
+    fn validate(&self, payload: &Value) -> Result<(), ExecError> {
+        self.path(payload).map(|_| ())
+    }
+}
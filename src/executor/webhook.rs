@@ -0,0 +1,546 @@
+use crate::config::Config;
+use crate::domain::Task;
+use crate::executor::condition;
+use crate::executor::hosts::{GuardedResolver, HostPolicy};
+use crate::executor::oauth::{ClientCredentials, TokenCache};
+use crate::executor::template::Variables;
+use crate::executor::{DebugOptions, ExecError, ExecutionContext, Executor, webhook_log};
+use crate::service::{
+    CORRELATION_ID_HEADER, EXECUTION_ID_HEADER, REQUEST_ID_HEADER, correlation_id, signing,
+};
+use crate::telemetry::{self, TRACEPARENT_HEADER};
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Headers the scheduler sets on every request, which payloads may not override.
+const SCHEDULER_HEADERS: &[&str] = &[
+    signing::SIGNATURE_HEADER,
+    CORRELATION_ID_HEADER,
+    EXECUTION_ID_HEADER,
+    REQUEST_ID_HEADER,
+    TRACEPARENT_HEADER,
+];
+
+/// Credentials sent with a webhook request, from the payload's `auth` object.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase", deny_unknown_fields)]
+enum Auth {
+    Basic {
+        username: String,
+        password: Option<String>,
+    },
+    Bearer {
+        token: String,
+    },
+    /// A token fetched with the client credentials grant, sent as a bearer token.
+    #[serde(rename = "oauth2")]
+    OAuth2(ClientCredentials),
+}
+
+impl Auth {
+    fn secrets(&self) -> Vec<String> {
+        match self {
+            Auth::Basic { password, .. } => password.iter().cloned().collect(),
+            Auth::Bearer { token } => vec![token.clone()],
+            Auth::OAuth2(credentials) => vec![credentials.client_secret.clone()],
+        }
+    }
+}
+
+/// Extra headers and credentials of a webhook request.
+#[derive(Debug, Default)]
+struct RequestOptions {
+    headers: HeaderMap,
+    auth: Option<Auth>,
+}
+
+impl RequestOptions {
+    /// Reads the payload's `headers` map and `auth` object.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'ExecError::InvalidPayload' if either is malformed, or a header would
+    ///   replace one the scheduler sets itself.
+    fn from_payload(payload: &Value) -> Result<Self, ExecError> {
+        let invalid = |e: String| ExecError::InvalidPayload(e);
+        let mut options = RequestOptions::default();
+
+        if let Some(raw) = payload.get("headers") {
+            let raw: BTreeMap<String, String> = serde_json::from_value(raw.clone())
+                .map_err(|e| invalid(format!("Invalid 'headers': {}", e)))?;
+            for (name, value) in raw {
+                let header = HeaderName::from_bytes(name.as_bytes())
+                    .map_err(|_| invalid(format!("Invalid header name '{}'", name)))?;
+                if SCHEDULER_HEADERS
+                    .iter()
+                    .any(|reserved| header.as_str().eq_ignore_ascii_case(reserved))
+                {
+                    return Err(invalid(format!(
+                        "Header '{}' is set by the scheduler",
+                        name
+                    )));
+                }
+                let value = HeaderValue::from_str(&value)
+                    .map_err(|_| invalid(format!("Invalid value for header '{}'", name)))?;
+                options.headers.insert(header, value);
+            }
+        }
+        if let Some(raw) = payload.get("auth") {
+            options.auth = Some(
+                serde_json::from_value(raw.clone())
+                    .map_err(|e| invalid(format!("Invalid 'auth': {}", e)))?,
+            );
+        }
+
+        Ok(options)
+    }
+}
+
+/// When a response counts as a success, from the payload's `success` object:
+/// `{"status": [200, 202], "body_contains": "OK", "json_path": "$.error", "equals": false}`.
+/// Every condition given must hold; without any, a 2xx status is enough.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SuccessCriteria {
+    /// Accepted status codes, instead of any 2xx.
+    #[serde(default)]
+    status: Vec<u16>,
+    body_contains: Option<String>,
+    body_not_contains: Option<String>,
+    /// Dot-separated path to a field of a JSON body, optionally prefixed with `$.`; array
+    /// elements are addressed by index, e.g. `items.0.state`.
+    json_path: Option<String>,
+    /// The value the field at `json_path` must have.
+    equals: Option<Value>,
+}
+
+impl SuccessCriteria {
+    /// Reads the payload's `success` object; the default criteria if it has none.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'ExecError::InvalidPayload' if it is malformed, or `json_path` and
+    ///   `equals` are not given together.
+    fn from_payload(payload: &Value) -> Result<Self, ExecError> {
+        let Some(raw) = payload.get("success") else {
+            return Ok(Self::default());
+        };
+        let criteria: SuccessCriteria = serde_json::from_value(raw.clone())
+            .map_err(|e| ExecError::InvalidPayload(format!("Invalid 'success': {}", e)))?;
+        if criteria.json_path.is_some() != criteria.equals.is_some() {
+            return Err(ExecError::InvalidPayload(
+                "'success' needs both 'json_path' and 'equals', or neither".into(),
+            ));
+        }
+        if let Some(path) = &criteria.json_path {
+            json_path(path)?;
+        }
+        if criteria
+            .status
+            .iter()
+            .any(|code| !(100..600).contains(code))
+        {
+            return Err(ExecError::InvalidPayload(
+                "'success.status' must hold HTTP status codes".into(),
+            ));
+        }
+        Ok(criteria)
+    }
+
+    /// Whether the status is one the task accepts.
+    fn accepts(&self, status: reqwest::StatusCode) -> bool {
+        if self.status.is_empty() {
+            status.is_success()
+        } else {
+            self.status.contains(&status.as_u16())
+        }
+    }
+
+    /// Checks a response; `Err` tells which condition it missed.
+    fn check(&self, status: reqwest::StatusCode, body: &str) -> Result<(), String> {
+        if !self.accepts(status) {
+            return Err(format!("HTTP Error {}", status.as_u16()));
+        }
+        if let Some(needle) = &self.body_contains
+            && !body.contains(needle.as_str())
+        {
+            return Err(format!("Response does not contain '{}'", needle));
+        }
+        if let Some(needle) = &self.body_not_contains
+            && body.contains(needle.as_str())
+        {
+            return Err(format!("Response contains '{}'", needle));
+        }
+        if let (Some(path), Some(expected)) = (&self.json_path, &self.equals) {
+            let segments = json_path(path).map_err(|e| e.to_string())?;
+            let body: Value = serde_json::from_str(body)
+                .map_err(|_| format!("Response is not JSON, cannot check '{}'", path))?;
+            let found = segments
+                .iter()
+                .try_fold(&body, |value, segment| match value {
+                    Value::Array(items) => segment.parse::<usize>().ok().and_then(|i| items.get(i)),
+                    value => value.get(segment.as_str()),
+                });
+            match found {
+                Some(actual) if actual == expected => {}
+                Some(actual) => {
+                    return Err(format!(
+                        "Response field '{}' is {}, expected {}",
+                        path, actual, expected
+                    ));
+                }
+                None => return Err(format!("Response has no field '{}'", path)),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Splits a `json_path` into its fields.
+fn json_path(path: &str) -> Result<Vec<String>, ExecError> {
+    let trimmed = path.trim();
+    let trimmed = trimmed.strip_prefix("$.").unwrap_or(trimmed);
+    let segments: Vec<String> = trimmed.split('.').map(str::to_string).collect();
+    if segments.iter().any(String::is_empty) {
+        return Err(ExecError::InvalidPayload(format!(
+            "'{}' is not a valid 'json_path'",
+            path
+        )));
+    }
+    Ok(segments)
+}
+
+/// Reads a payload's `timeout_seconds`.
+///
+/// # Errors
+///
+/// * Returns 'ExecError::InvalidPayload' if it is not a positive integer.
+fn timeout_seconds(raw: &Value) -> Result<u64, ExecError> {
+    raw.as_u64().filter(|secs| *secs > 0).ok_or_else(|| {
+        ExecError::InvalidPayload("'timeout_seconds' must be a positive integer".into())
+    })
+}
+
+/// Reads a payload's `response_fields`: JSONPaths of the parts of a JSON response to record
+/// instead of the whole body, e.g. `["$.id", "$.items[0].state"]`.
+///
+/// # Errors
+///
+/// * Returns 'ExecError::InvalidPayload' if it is not a list of supported JSONPaths.
+fn response_fields(payload: &Value) -> Result<Option<Vec<String>>, ExecError> {
+    let Some(raw) = payload.get("response_fields") else {
+        return Ok(None);
+    };
+    let paths: Vec<String> = serde_json::from_value(raw.clone()).map_err(|_| {
+        ExecError::InvalidPayload("'response_fields' must be a list of JSONPaths".into())
+    })?;
+    if let Some(path) = paths.iter().find(|path| !condition::is_valid_path(path)) {
+        return Err(ExecError::InvalidPayload(format!(
+            "'{}' is not a supported JSONPath",
+            path
+        )));
+    }
+    Ok(Some(paths))
+}
+
+/// Redirects a webhook may follow, like reqwest's default.
+const MAX_REDIRECTS: usize = 10;
+
+/// Builds the HTTP client shared by all webhook runs, so connections and TLS sessions are
+/// reused between them. Timeouts of whole requests are set per request.
+///
+/// Redirects are only followed to hosts `WEBHOOK_*_HOSTS` allow, and with
+/// `WEBHOOK_BLOCK_PRIVATE_IPS` host names are resolved by a [`GuardedResolver`].
+pub fn http_client(config: &Config) -> reqwest::Client {
+    let hosts = config.webhook_hosts.clone();
+    let redirects = reqwest::redirect::Policy::custom(move |attempt| {
+        if attempt.previous().len() >= MAX_REDIRECTS {
+            return attempt.error("too many redirects");
+        }
+        match hosts.check_url(attempt.url()) {
+            Ok(()) => attempt.follow(),
+            Err(e) => attempt.error(e),
+        }
+    });
+    let mut builder = reqwest::Client::builder()
+        .user_agent("TaskScheduler/1.0")
+        .connect_timeout(Duration::from_secs(config.webhook_connect_timeout_secs))
+        .pool_idle_timeout(Duration::from_secs(config.webhook_pool_idle_timeout_secs))
+        .pool_max_idle_per_host(config.webhook_pool_max_idle_per_host)
+        .redirect(redirects);
+    if config.webhook_hosts.blocks_private() {
+        builder =
+            builder.dns_resolver(Arc::new(GuardedResolver::new(config.webhook_hosts.clone())));
+    }
+    builder
+        .build()
+        // Only fails if the TLS backend cannot be initialized, like `reqwest::Client::new`
+        .expect("Failed to build HTTP client")
+}
+
+/// Calls the HTTP endpoint in the payload:
+/// `{"url": "...", "method": "POST", "body": {...}}`. `method` defaults to `GET`; `body` is
+/// only sent with `POST` and `PUT`.
+///
+/// Requests time out after `timeout_seconds`, or `WEBHOOK_TIMEOUT_SECS` if unset.
+///
+/// Requests may carry extra `headers` (`{"X-Tenant": "acme"}`) and credentials in `auth`:
+/// `{"type": "basic", "username": "...", "password": "..."}` or
+/// `{"type": "bearer", "token": "..."}`. With
+/// `{"type": "oauth2", "token_url": "...", "client_id": "...", "client_secret": "..."}`, an
+/// access token is fetched from the token endpoint and reused until it expires. Credentials
+/// are hidden from logs and from the recorded response, even if the endpoint echoes them
+/// back.
+///
+/// A 2xx response is a success unless the payload sets stricter `success` criteria, see
+/// [`SuccessCriteria`]. The response body is recorded as text, or with `response_fields`
+/// only the values at those JSONPaths of a JSON body, keyed by path.
+///
+/// The URL and the strings of the body may contain execution-context placeholders such as
+/// `{{task.id}}` or `{{execution.scheduled_at}}`, see [`template`](super::template).
+///
+/// URLs are checked against the [`HostPolicy`] when the task is created and again when it
+/// runs.
+#[derive(Default)]
+pub struct WebhookExecutor {
+    hosts: HostPolicy,
+    tokens: TokenCache,
+}
+
+impl WebhookExecutor {
+    pub fn new(hosts: HostPolicy) -> Self {
+        Self {
+            hosts,
+            tokens: TokenCache::default(),
+        }
+    }
+
+    /// An executor for the host policy in the config.
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(config.webhook_hosts.clone())
+    }
+
+    /// Parses a webhook URL and checks it against the host policy.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'ExecError::InvalidPayload' if the URL is invalid or not allowed.
+    fn url(&self, raw: &str) -> Result<reqwest::Url, ExecError> {
+        let url = reqwest::Url::parse(raw)
+            .map_err(|e| ExecError::InvalidPayload(format!("Invalid 'url': {}", e)))?;
+        self.hosts
+            .check_url(&url)
+            .map_err(ExecError::InvalidPayload)?;
+        Ok(url)
+    }
+}
+
+#[async_trait]
+impl Executor for WebhookExecutor {
+    /// Executes the HTTP webhook defined in the task payload.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'ExecError::InvalidPayload' if the `url` is missing or not allowed, or the
+    ///   debug options, headers, credentials or success criteria are malformed.
+    /// * Returns 'ExecError::Timeout' if the endpoint does not respond in time.
+    /// * Returns 'ExecError::Connection' if the host cannot be reached.
+    /// * Returns 'ExecError::HttpStatus' if the response status is not accepted.
+    /// * Returns 'ExecError::Failed' if the HTTP request fails, e.g. because the host
+    ///   resolves to a blocked address, or the response body does not meet the success
+    ///   criteria.
+    ///
+    /// Returns the HTTP response as JSON on success.
+    async fn execute(&self, task: &Task, ctx: &ExecutionContext<'_>) -> Result<Value, ExecError> {
+        let url = task
+            .payload
+            .get("url")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ExecError::InvalidPayload("Missing 'url' in payload".into()))?;
+        let variables = Variables::new(task, ctx, chrono::Utc::now());
+        let url = self.url(&variables.render(url, true))?;
+
+        let method = task
+            .payload
+            .get("method")
+            .and_then(|v| v.as_str())
+            .unwrap_or("GET")
+            .to_uppercase();
+
+        let debug = DebugOptions::from_payload(&task.payload)
+            .map_err(ExecError::InvalidPayload)?
+            .unwrap_or_default();
+        let log_level = debug.level.or(ctx.config.webhook_log_level);
+        let options = RequestOptions::from_payload(&task.payload)?;
+        let criteria = SuccessCriteria::from_payload(&task.payload)?;
+        let fields = response_fields(&task.payload)?;
+        let redaction = ctx
+            .config
+            .webhook_redaction
+            .merge(&debug.redaction().map_err(ExecError::InvalidPayload)?)
+            .with_secrets(options.auth.iter().flat_map(Auth::secrets))
+            .with_secrets(ctx.secrets.iter().cloned());
+        if let Some(Auth::OAuth2(credentials)) = &options.auth {
+            self.url(&credentials.token_url)?;
+        }
+
+        let mut body = task
+            .payload
+            .get("body")
+            .cloned()
+            .unwrap_or_else(|| json!({}));
+        variables.render_value(&mut body);
+        // Serialized up front so the signature covers exactly the bytes that are sent
+        let body = match method.as_str() {
+            "POST" | "PUT" => serde_json::to_vec(&body).map_err(|e| {
+                ExecError::InvalidPayload(format!("Failed to serialize body: {}", e))
+            })?,
+            _ => Vec::new(),
+        };
+
+        let timeout = match task.payload.get("timeout_seconds") {
+            Some(raw) => timeout_seconds(raw)?,
+            None => ctx.config.webhook_timeout_secs,
+        };
+
+        let client = ctx.http;
+        let token = match &options.auth {
+            Some(Auth::OAuth2(credentials)) => Some(
+                self.tokens
+                    .token(client, credentials, Duration::from_secs(timeout))
+                    .await?,
+            ),
+            _ => None,
+        };
+        let redaction = redaction.with_secrets(token.clone());
+
+        let mut builder = match method.as_str() {
+            "POST" => client.post(url),
+            "PUT" => client.put(url),
+            "DELETE" => client.delete(url),
+            _ => client.get(url),
+        };
+        if !body.is_empty() {
+            builder = builder
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(body.clone());
+        }
+        builder = builder
+            .timeout(Duration::from_secs(timeout))
+            .headers(options.headers);
+        builder = match &options.auth {
+            Some(Auth::Basic { username, password }) => {
+                builder.basic_auth(username, password.as_ref())
+            }
+            Some(Auth::Bearer { token }) => builder.bearer_auth(token),
+            Some(Auth::OAuth2(_)) => builder.bearer_auth(token.unwrap_or_default()),
+            None => builder,
+        };
+        if let Some(signature) = signing::signature_header(
+            task,
+            ctx.config.webhook_signing_secret.as_deref(),
+            ctx.started_at,
+            &body,
+        ) {
+            builder = builder.header(signing::SIGNATURE_HEADER, signature);
+        }
+
+        if let Some(request_id) = &task.request_id {
+            builder = builder.header(REQUEST_ID_HEADER, request_id);
+        }
+        let request = builder
+            .header(
+                CORRELATION_ID_HEADER,
+                correlation_id(task, ctx.execution_id),
+            )
+            .header(EXECUTION_ID_HEADER, ctx.execution_id.to_string())
+            .header(TRACEPARENT_HEADER, telemetry::traceparent(ctx.execution_id))
+            .build()
+            .map_err(|e| {
+                ExecError::InvalidPayload(format!("Failed to build HTTP request: {}", e))
+            })?;
+        if let Some(level) = log_level {
+            let exchange = json!({
+                "method": request.method().as_str(),
+                "url": request.url().as_str(),
+                "headers": redaction.headers(request.headers()),
+                "body": redaction.text(&String::from_utf8_lossy(&body)),
+            });
+            webhook_log::log_exchange(level, task.id, "Webhook request", &exchange);
+        }
+
+        let response = client.execute(request).await.map_err(|e| {
+            if e.is_timeout() {
+                ExecError::Timeout(format!("Webhook timed out after {} seconds", timeout))
+            } else if e.is_connect() {
+                ExecError::Connection(format!("HTTP request failed: {:?}", e))
+            } else {
+                ExecError::Failed(format!("HTTP request failed: {:?}", e))
+            }
+        })?;
+
+        let status = response.status();
+        let headers = response.headers().clone();
+        let raw = response.text().await.unwrap_or_default();
+        let verdict = criteria.check(status, &raw);
+        // Redacted before it is logged or recorded as the execution output
+        let text = redaction.text(&raw);
+        if let Some(level) = log_level {
+            let exchange = json!({
+                "status": status.as_u16(),
+                "headers": redaction.headers(&headers),
+                "body": text,
+            });
+            webhook_log::log_exchange(level, task.id, "Webhook response", &exchange);
+        }
+
+        let response = match (fields, serde_json::from_str::<Value>(&text)) {
+            (Some(paths), Ok(body)) => Value::Object(
+                paths
+                    .into_iter()
+                    .map(|path| {
+                        let value = condition::select(&body, &path).cloned();
+                        (path, value.unwrap_or(Value::Null))
+                    })
+                    .collect(),
+            ),
+            _ => Value::String(text.clone()),
+        };
+        match verdict {
+            Ok(()) => Ok(json!({ "status": status.as_u16(), "response": response })),
+            Err(reason) if !criteria.accepts(status) => {
+                Err(ExecError::HttpStatus(format!("{}: {}", reason, text)))
+            }
+            Err(reason) => Err(ExecError::Failed(format!("{}: {}", reason, text))),
+        }
+    }
+
+    /// Checks the URL against the host policy, and the debug options, headers, credentials
+    /// and success criteria; a missing or malformed `url` is reported by the run.
+    fn validate(&self, payload: &Value) -> Result<(), ExecError> {
+        if let Some(url) = payload
+            .get("url")
+            .and_then(|v| v.as_str())
+            .and_then(|raw| reqwest::Url::parse(raw).ok())
+        {
+            self.hosts
+                .check_url(&url)
+                .map_err(ExecError::InvalidPayload)?;
+        }
+        DebugOptions::from_payload(payload).map_err(ExecError::InvalidPayload)?;
+        if let Some(Auth::OAuth2(credentials)) = RequestOptions::from_payload(payload)?.auth {
+            self.url(&credentials.token_url)?;
+        }
+        SuccessCriteria::from_payload(payload)?;
+        response_fields(payload)?;
+        if let Some(raw) = payload.get("timeout_seconds") {
+            timeout_seconds(raw)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,235 @@
+use crate::service::signing::SIGNATURE_HEADER;
+use reqwest::header::HeaderMap;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::str::FromStr;
+use tracing::Level;
+
+/// Payload key holding a task's webhook debug options.
+pub const DEBUG_KEY: &str = "debug";
+
+/// Replacement for redacted header values and JSON fields.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Headers that are redacted even without any configured rule.
+const ALWAYS_REDACTED: [&str; 4] = [
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+];
+
+/// Which header values and JSON fields are hidden from webhook logs and execution output.
+///
+/// JSON paths are dot-separated field names, optionally prefixed with `$.`; `*` matches any
+/// field or array element, e.g. `data.*.token`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Redaction {
+    headers: Vec<String>,
+    paths: Vec<Vec<String>>,
+    /// Values hidden wherever they appear, e.g. credentials a response might echo back.
+    secrets: Vec<String>,
+}
+
+impl Redaction {
+    /// Builds a rule set from header names and JSON paths.
+    ///
+    /// # Errors
+    ///
+    /// * Returns an error message if a path is empty or has an empty segment.
+    pub fn new<H, P>(headers: H, paths: P) -> Result<Self, String>
+    where
+        H: IntoIterator,
+        H::Item: AsRef<str>,
+        P: IntoIterator,
+        P::Item: AsRef<str>,
+    {
+        let headers = headers
+            .into_iter()
+            .map(|name| name.as_ref().trim().to_ascii_lowercase())
+            .filter(|name| !name.is_empty())
+            .collect();
+        let paths = paths
+            .into_iter()
+            .map(|path| parse_path(path.as_ref()))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            headers,
+            paths,
+            secrets: Vec::new(),
+        })
+    }
+
+    /// Also hides these values wherever they appear in headers and bodies.
+    pub fn with_secrets(mut self, secrets: impl IntoIterator<Item = String>) -> Self {
+        self.secrets
+            .extend(secrets.into_iter().filter(|secret| !secret.is_empty()));
+        self
+    }
+
+    /// Rules of both sets.
+    pub fn merge(&self, other: &Redaction) -> Redaction {
+        Redaction {
+            headers: [self.headers.as_slice(), other.headers.as_slice()].concat(),
+            paths: [self.paths.as_slice(), other.paths.as_slice()].concat(),
+            secrets: [self.secrets.as_slice(), other.secrets.as_slice()].concat(),
+        }
+    }
+
+    /// True if the value of header `name` must not be shown.
+    pub fn hides_header(&self, name: &str) -> bool {
+        let name = name.to_ascii_lowercase();
+        ALWAYS_REDACTED.contains(&name.as_str())
+            || name.eq_ignore_ascii_case(SIGNATURE_HEADER)
+            || self.headers.contains(&name)
+    }
+
+    /// Headers as a JSON object, with hidden values replaced.
+    pub fn headers(&self, headers: &HeaderMap) -> Value {
+        let mut redacted = Map::new();
+        for (name, value) in headers {
+            let value = String::from_utf8_lossy(value.as_bytes());
+            let value = if self.hides_header(name.as_str()) {
+                REDACTED.to_string()
+            } else {
+                self.scrub(&value)
+            };
+            redacted.insert(name.to_string(), Value::String(value));
+        }
+        Value::Object(redacted)
+    }
+
+    /// Replaces every field matched by a path.
+    pub fn value(&self, value: &mut Value) {
+        for path in &self.paths {
+            redact_path(value, path);
+        }
+    }
+
+    /// Redacts a body: JSON is redacted field by field, anything else is returned as is.
+    /// Secrets are hidden in either.
+    pub fn text(&self, text: &str) -> String {
+        if self.paths.is_empty() {
+            return self.scrub(text);
+        }
+        match serde_json::from_str::<Value>(text) {
+            Ok(mut value) => {
+                self.value(&mut value);
+                self.scrub(&value.to_string())
+            }
+            Err(_) => self.scrub(text),
+        }
+    }
+
+    /// Replaces every occurrence of a secret.
+    fn scrub(&self, text: &str) -> String {
+        self.secrets.iter().fold(text.to_string(), |text, secret| {
+            text.replace(secret, REDACTED)
+        })
+    }
+}
+
+/// Per-task webhook debug options, read from the payload's `debug` object:
+/// `{"level": "debug", "redact_headers": [...], "redact_paths": [...]}`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DebugOptions {
+    /// Level the task's requests and responses are logged at; falls back to
+    /// `WEBHOOK_LOG_LEVEL`.
+    #[serde(default, deserialize_with = "deserialize_level")]
+    pub level: Option<Level>,
+    /// Added to the global header rules.
+    #[serde(default)]
+    pub redact_headers: Vec<String>,
+    /// Added to the global JSON path rules.
+    #[serde(default)]
+    pub redact_paths: Vec<String>,
+}
+
+impl DebugOptions {
+    /// Reads the debug options of a payload; `None` if it has none.
+    ///
+    /// # Errors
+    ///
+    /// * Returns an error message if the options are malformed.
+    pub fn from_payload(payload: &Value) -> Result<Option<Self>, String> {
+        let Some(raw) = payload.get(DEBUG_KEY) else {
+            return Ok(None);
+        };
+        let options: DebugOptions = serde_json::from_value(raw.clone())
+            .map_err(|e| format!("Invalid '{}' options: {}", DEBUG_KEY, e))?;
+        options.redaction()?;
+
+        Ok(Some(options))
+    }
+
+    /// The task's own redaction rules.
+    ///
+    /// # Errors
+    ///
+    /// * Returns an error message if a path is malformed.
+    pub fn redaction(&self) -> Result<Redaction, String> {
+        Redaction::new(&self.redact_headers, &self.redact_paths)
+    }
+}
+
+/// Logs a webhook request or response at `level`.
+pub fn log_exchange(level: Level, task_id: uuid::Uuid, message: &str, exchange: &Value) {
+    macro_rules! at {
+        ($macro:ident) => {
+            tracing::$macro!(task_id = %task_id, exchange = %exchange, "{}", message)
+        };
+    }
+    match level {
+        Level::TRACE => at!(trace),
+        Level::DEBUG => at!(debug),
+        Level::INFO => at!(info),
+        Level::WARN => at!(warn),
+        Level::ERROR => at!(error),
+    }
+}
+
+fn parse_path(path: &str) -> Result<Vec<String>, String> {
+    let trimmed = path.trim();
+    let trimmed = trimmed.strip_prefix("$.").unwrap_or(trimmed);
+    let segments: Vec<String> = trimmed.split('.').map(str::to_string).collect();
+    if segments.iter().any(String::is_empty) {
+        return Err(format!("'{}' is not a valid redaction path", path));
+    }
+    Ok(segments)
+}
+
+fn redact_path(value: &mut Value, path: &[String]) {
+    let Some((segment, rest)) = path.split_first() else {
+        return;
+    };
+    let children: Vec<&mut Value> = match value {
+        Value::Object(fields) if segment == "*" => fields.values_mut().collect(),
+        Value::Object(fields) => fields.get_mut(segment).into_iter().collect(),
+        Value::Array(items) if segment == "*" => items.iter_mut().collect(),
+        Value::Array(items) => segment
+            .parse::<usize>()
+            .ok()
+            .and_then(|index| items.get_mut(index))
+            .into_iter()
+            .collect(),
+        _ => Vec::new(),
+    };
+    for child in children {
+        if rest.is_empty() {
+            *child = Value::String(REDACTED.to_string());
+        } else {
+            redact_path(child, rest);
+        }
+    }
+}
+
+fn deserialize_level<'de, D>(deserializer: D) -> Result<Option<Level>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|raw| Level::from_str(&raw).map_err(serde::de::Error::custom))
+        .transpose()
+}
@@ -0,0 +1,495 @@
+//! Opt-in fault injection for chaos testing.
+//!
+//! When any `FAULT_*` probability is configured the scheduler deliberately misbehaves: store
+//! calls are delayed, executor invocations fail and scheduler wake-ups are dropped, so the
+//! busy-retry, failure-recording and outbox-polling paths can be exercised under load.
+//! Everything is off by default.
+use crate::db::store::{Claim, ExecutionWrite, TaskStore, WriteOutcome};
+use crate::domain::{
+    ApiKey, Execution, ExecutionCursor, ExecutionSummary, GroupStats, Scope, Snapshot,
+    StoredSecret, Task, TaskFilter, TaskGroup, TaskPatch, TaskRuns, TaskSelector, TaskSummary,
+    TriggerLink, Workflow, WorkflowRun,
+};
+use crate::executor::ExecutionMiddleware;
+use crate::scheduler::ScheduleEvent;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::stream::BoxStream;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+#[cfg(test)]
+mod tests;
+
+/// Probabilities (0.0 to 1.0) of each injected fault.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FaultConfig {
+    /// Chance that a store call is delayed by `db_delay_ms` first.
+    pub db_delay_probability: f64,
+    pub db_delay_ms: u64,
+    /// Chance that an executor invocation fails without running.
+    pub executor_failure_probability: f64,
+    /// Chance that a wake-up signal to the scheduler is dropped.
+    pub notification_drop_probability: f64,
+}
+
+impl FaultConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.db_delay_probability > 0.0
+            || self.executor_failure_probability > 0.0
+            || self.notification_drop_probability > 0.0
+    }
+}
+
+/// Returns true with the given probability.
+pub fn roll(probability: f64) -> bool {
+    probability > 0.0 && rand::random_bool(probability.min(1.0))
+}
+
+/// [`TaskStore`] decorator that randomly delays calls to the wrapped store.
+pub struct FaultyStore {
+    inner: Arc<dyn TaskStore>,
+    delay_probability: f64,
+    delay: Duration,
+}
+
+impl FaultyStore {
+    pub fn new(inner: Arc<dyn TaskStore>, config: &FaultConfig) -> Self {
+        Self {
+            inner,
+            delay_probability: config.db_delay_probability,
+            delay: Duration::from_millis(config.db_delay_ms),
+        }
+    }
+
+    async fn maybe_delay(&self) {
+        if roll(self.delay_probability) {
+            tracing::debug!(delay = ?self.delay, "Injecting store delay");
+            tokio::time::sleep(self.delay).await;
+        }
+    }
+}
+
+#[async_trait]
+impl TaskStore for FaultyStore {
+    async fn create_task(&self, task: &Task) -> sqlx::Result<()> {
+        self.maybe_delay().await;
+        self.inner.create_task(task).await
+    }
+
+    async fn get_task(&self, id: Uuid) -> sqlx::Result<Option<Task>> {
+        self.maybe_delay().await;
+        self.inner.get_task(id).await
+    }
+
+    async fn delete_task(&self, id: Uuid) -> sqlx::Result<u64> {
+        self.maybe_delay().await;
+        self.inner.delete_task(id).await
+    }
+
+    async fn purge_task(&self, id: Uuid) -> sqlx::Result<u64> {
+        self.maybe_delay().await;
+        self.inner.purge_task(id).await
+    }
+
+    async fn purge_deleted_tasks(&self, cutoff: DateTime<Utc>, limit: i64) -> sqlx::Result<u64> {
+        self.maybe_delay().await;
+        self.inner.purge_deleted_tasks(cutoff, limit).await
+    }
+
+    async fn deleted_task_ids(&self, cutoff: DateTime<Utc>, limit: i64) -> sqlx::Result<Vec<Uuid>> {
+        self.maybe_delay().await;
+        self.inner.deleted_task_ids(cutoff, limit).await
+    }
+
+    async fn count_active_tasks(&self, api_key_id: Uuid) -> sqlx::Result<i64> {
+        self.maybe_delay().await;
+        self.inner.count_active_tasks(api_key_id).await
+    }
+
+    async fn rotate_signing_secret(
+        &self,
+        id: Uuid,
+        secret: &str,
+        previous_expires_at: DateTime<Utc>,
+    ) -> sqlx::Result<u64> {
+        self.maybe_delay().await;
+        self.inner
+            .rotate_signing_secret(id, secret, previous_expires_at)
+            .await
+    }
+
+    async fn drain_outbox(&self, limit: i64) -> sqlx::Result<Vec<ScheduleEvent>> {
+        self.maybe_delay().await;
+        self.inner.drain_outbox(limit).await
+    }
+
+    async fn get_due_tasks(&self, now: DateTime<Utc>, limit: i64) -> sqlx::Result<Vec<Task>> {
+        self.maybe_delay().await;
+        self.inner.get_due_tasks(now, limit).await
+    }
+
+    async fn count_due_tasks(&self, now: DateTime<Utc>) -> sqlx::Result<i64> {
+        self.maybe_delay().await;
+        self.inner.count_due_tasks(now).await
+    }
+
+    async fn get_upcoming_triggers(&self, limit: i64) -> sqlx::Result<Vec<(Uuid, DateTime<Utc>)>> {
+        self.maybe_delay().await;
+        self.inner.get_upcoming_triggers(limit).await
+    }
+
+    async fn get_task_summaries(&self, filter: &TaskFilter) -> sqlx::Result<Vec<TaskSummary>> {
+        self.maybe_delay().await;
+        self.inner.get_task_summaries(filter).await
+    }
+
+    async fn get_task_runs(&self, id: Uuid) -> sqlx::Result<TaskRuns> {
+        self.maybe_delay().await;
+        self.inner.get_task_runs(id).await
+    }
+
+    async fn list_executions(
+        &self,
+        task_id: Uuid,
+        after: Option<&ExecutionCursor>,
+        limit: i64,
+    ) -> sqlx::Result<Vec<ExecutionSummary>> {
+        self.maybe_delay().await;
+        self.inner.list_executions(task_id, after, limit).await
+    }
+
+    async fn list_execution_records(
+        &self,
+        task_id: Uuid,
+        after: Option<&ExecutionCursor>,
+        limit: i64,
+    ) -> sqlx::Result<Vec<Execution>> {
+        self.maybe_delay().await;
+        self.inner
+            .list_execution_records(task_id, after, limit)
+            .await
+    }
+
+    /// Streams are passed through undelayed.
+    fn stream_executions(
+        &self,
+        since: Option<DateTime<Utc>>,
+    ) -> BoxStream<'_, sqlx::Result<Execution>> {
+        self.inner.stream_executions(since)
+    }
+
+    async fn begin_executions(
+        &self,
+        executions: &[Execution],
+        claim: &Claim,
+    ) -> sqlx::Result<Vec<WriteOutcome>> {
+        self.maybe_delay().await;
+        self.inner.begin_executions(executions, claim).await
+    }
+
+    async fn renew_claims(&self, task_ids: &[Uuid], claim: &Claim) -> sqlx::Result<u64> {
+        self.maybe_delay().await;
+        self.inner.renew_claims(task_ids, claim).await
+    }
+
+    async fn get_execution(&self, id: Uuid) -> sqlx::Result<Option<Execution>> {
+        self.maybe_delay().await;
+        self.inner.get_execution(id).await
+    }
+
+    async fn recent_durations(
+        &self,
+        task_id: Uuid,
+        exclude: Uuid,
+        limit: i64,
+    ) -> sqlx::Result<Vec<i64>> {
+        self.maybe_delay().await;
+        self.inner.recent_durations(task_id, exclude, limit).await
+    }
+
+    async fn last_success(&self, task_id: Uuid) -> sqlx::Result<Option<Execution>> {
+        self.maybe_delay().await;
+        self.inner.last_success(task_id).await
+    }
+
+    async fn get_dependents(&self, task_id: Uuid) -> sqlx::Result<Vec<Task>> {
+        self.maybe_delay().await;
+        self.inner.get_dependents(task_id).await
+    }
+
+    async fn running_executions(&self) -> sqlx::Result<Vec<Execution>> {
+        self.maybe_delay().await;
+        self.inner.running_executions().await
+    }
+
+    async fn orphaned_executions(
+        &self,
+        owner: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> sqlx::Result<Vec<Execution>> {
+        self.maybe_delay().await;
+        self.inner.orphaned_executions(owner, now).await
+    }
+
+    async fn prune_executions(
+        &self,
+        now: DateTime<Utc>,
+        default_keep_days: Option<i64>,
+        limit: i64,
+    ) -> sqlx::Result<u64> {
+        self.maybe_delay().await;
+        self.inner
+            .prune_executions(now, default_keep_days, limit)
+            .await
+    }
+
+    async fn expired_executions(
+        &self,
+        now: DateTime<Utc>,
+        default_keep_days: Option<i64>,
+        limit: i64,
+    ) -> sqlx::Result<Vec<Execution>> {
+        self.maybe_delay().await;
+        self.inner
+            .expired_executions(now, default_keep_days, limit)
+            .await
+    }
+
+    async fn delete_executions(&self, ids: &[Uuid]) -> sqlx::Result<u64> {
+        self.maybe_delay().await;
+        self.inner.delete_executions(ids).await
+    }
+
+    async fn record_executions(
+        &self,
+        writes: &[ExecutionWrite],
+    ) -> sqlx::Result<Vec<WriteOutcome>> {
+        self.maybe_delay().await;
+        self.inner.record_executions(writes).await
+    }
+
+    async fn create_api_key(&self, key: &ApiKey, key_hash: &str) -> sqlx::Result<()> {
+        self.maybe_delay().await;
+        self.inner.create_api_key(key, key_hash).await
+    }
+
+    async fn find_api_key(&self, key_hash: &str) -> sqlx::Result<Option<ApiKey>> {
+        self.maybe_delay().await;
+        self.inner.find_api_key(key_hash).await
+    }
+
+    async fn list_api_keys(&self) -> sqlx::Result<Vec<ApiKey>> {
+        self.maybe_delay().await;
+        self.inner.list_api_keys().await
+    }
+
+    async fn update_api_key_scope(&self, id: Uuid, scope: Scope) -> sqlx::Result<u64> {
+        self.maybe_delay().await;
+        self.inner.update_api_key_scope(id, scope).await
+    }
+
+    async fn revoke_api_key(&self, id: Uuid, at: DateTime<Utc>) -> sqlx::Result<u64> {
+        self.maybe_delay().await;
+        self.inner.revoke_api_key(id, at).await
+    }
+
+    async fn put_secret(&self, secret: &StoredSecret) -> sqlx::Result<()> {
+        self.maybe_delay().await;
+        self.inner.put_secret(secret).await
+    }
+
+    async fn get_secret(&self, name: &str) -> sqlx::Result<Option<StoredSecret>> {
+        self.maybe_delay().await;
+        self.inner.get_secret(name).await
+    }
+
+    async fn list_secrets(&self) -> sqlx::Result<Vec<StoredSecret>> {
+        self.maybe_delay().await;
+        self.inner.list_secrets().await
+    }
+
+    async fn delete_secret(&self, name: &str) -> sqlx::Result<u64> {
+        self.maybe_delay().await;
+        self.inner.delete_secret(name).await
+    }
+
+    async fn acquire_lease(
+        &self,
+        name: &str,
+        holder: &str,
+        now: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> sqlx::Result<bool> {
+        self.maybe_delay().await;
+        self.inner
+            .acquire_lease(name, holder, now, expires_at)
+            .await
+    }
+
+    async fn release_lease(&self, name: &str, holder: &str) -> sqlx::Result<u64> {
+        self.maybe_delay().await;
+        self.inner.release_lease(name, holder).await
+    }
+
+    async fn create_trigger_link(&self, link: &TriggerLink) -> sqlx::Result<()> {
+        self.maybe_delay().await;
+        self.inner.create_trigger_link(link).await
+    }
+
+    async fn get_trigger_link(&self, id: Uuid) -> sqlx::Result<Option<TriggerLink>> {
+        self.maybe_delay().await;
+        self.inner.get_trigger_link(id).await
+    }
+
+    async fn redeem_trigger_link(&self, id: Uuid, now: DateTime<Utc>) -> sqlx::Result<bool> {
+        self.maybe_delay().await;
+        self.inner.redeem_trigger_link(id, now).await
+    }
+
+    async fn create_workflow(&self, workflow: &Workflow) -> sqlx::Result<()> {
+        self.maybe_delay().await;
+        self.inner.create_workflow(workflow).await
+    }
+
+    async fn get_workflow(&self, id: Uuid) -> sqlx::Result<Option<Workflow>> {
+        self.maybe_delay().await;
+        self.inner.get_workflow(id).await
+    }
+
+    async fn list_workflows(&self) -> sqlx::Result<Vec<Workflow>> {
+        self.maybe_delay().await;
+        self.inner.list_workflows().await
+    }
+
+    async fn create_workflow_run(&self, run: &WorkflowRun) -> sqlx::Result<()> {
+        self.maybe_delay().await;
+        self.inner.create_workflow_run(run).await
+    }
+
+    async fn get_workflow_run(&self, id: Uuid) -> sqlx::Result<Option<WorkflowRun>> {
+        self.maybe_delay().await;
+        self.inner.get_workflow_run(id).await
+    }
+
+    async fn list_workflow_runs(
+        &self,
+        workflow_id: Uuid,
+        limit: i64,
+    ) -> sqlx::Result<Vec<WorkflowRun>> {
+        self.maybe_delay().await;
+        self.inner.list_workflow_runs(workflow_id, limit).await
+    }
+
+    async fn unfinished_workflow_runs(&self) -> sqlx::Result<Vec<WorkflowRun>> {
+        self.maybe_delay().await;
+        self.inner.unfinished_workflow_runs().await
+    }
+
+    async fn update_workflow_run(&self, run: &WorkflowRun) -> sqlx::Result<u64> {
+        self.maybe_delay().await;
+        self.inner.update_workflow_run(run).await
+    }
+
+    async fn create_group(&self, group: &TaskGroup) -> sqlx::Result<()> {
+        self.maybe_delay().await;
+        self.inner.create_group(group).await
+    }
+
+    async fn get_group(&self, id: Uuid) -> sqlx::Result<Option<TaskGroup>> {
+        self.maybe_delay().await;
+        self.inner.get_group(id).await
+    }
+
+    async fn list_groups(&self) -> sqlx::Result<Vec<TaskGroup>> {
+        self.maybe_delay().await;
+        self.inner.list_groups().await
+    }
+
+    async fn set_group_paused(
+        &self,
+        id: Uuid,
+        paused_at: Option<DateTime<Utc>>,
+    ) -> sqlx::Result<u64> {
+        self.maybe_delay().await;
+        self.inner.set_group_paused(id, paused_at).await
+    }
+
+    async fn reschedule_task(&self, id: Uuid, trigger_at: DateTime<Utc>) -> sqlx::Result<u64> {
+        self.maybe_delay().await;
+        self.inner.reschedule_task(id, trigger_at).await
+    }
+
+    async fn defer_task(
+        &self,
+        id: Uuid,
+        until: DateTime<Utc>,
+        deferred_from: DateTime<Utc>,
+    ) -> sqlx::Result<u64> {
+        self.maybe_delay().await;
+        self.inner.defer_task(id, until, deferred_from).await
+    }
+
+    async fn update_task(&self, task: &Task) -> sqlx::Result<u64> {
+        self.maybe_delay().await;
+        self.inner.update_task(task).await
+    }
+
+    async fn patch_task(&self, id: Uuid, patch: &TaskPatch) -> sqlx::Result<u64> {
+        self.maybe_delay().await;
+        self.inner.patch_task(id, patch).await
+    }
+
+    async fn set_tasks_paused(
+        &self,
+        selector: &TaskSelector,
+        paused_at: Option<DateTime<Utc>>,
+    ) -> sqlx::Result<u64> {
+        self.maybe_delay().await;
+        self.inner.set_tasks_paused(selector, paused_at).await
+    }
+
+    async fn group_stats(&self, id: Uuid) -> sqlx::Result<GroupStats> {
+        self.maybe_delay().await;
+        self.inner.group_stats(id).await
+    }
+
+    async fn export_snapshot(
+        &self,
+        taken_at: DateTime<Utc>,
+        include_executions: bool,
+    ) -> sqlx::Result<Snapshot> {
+        self.maybe_delay().await;
+        self.inner
+            .export_snapshot(taken_at, include_executions)
+            .await
+    }
+
+    async fn restore_snapshot(&self, snapshot: &Snapshot) -> sqlx::Result<bool> {
+        self.maybe_delay().await;
+        self.inner.restore_snapshot(snapshot).await
+    }
+}
+
+/// Middleware that randomly fails executor invocations before they run.
+pub struct FaultInjectionMiddleware {
+    probability: f64,
+}
+
+impl FaultInjectionMiddleware {
+    pub fn new(probability: f64) -> Self {
+        Self { probability }
+    }
+}
+
+#[async_trait]
+impl ExecutionMiddleware for FaultInjectionMiddleware {
+    async fn before(&self, task: &mut Task) -> Result<(), String> {
+        if roll(self.probability) {
+            tracing::debug!(task_id = %task.id, "Injecting executor failure");
+            return Err("Injected fault".into());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+use crate::config::Config;
+use crate::db::memory::MemoryTaskStore;
+use crate::db::store::TaskStore;
+use crate::domain::{ExecutionStatus, Task};
+use crate::executor::MiddlewareChain;
+use crate::fault::{FaultConfig, FaultInjectionMiddleware, FaultyStore, roll};
+use crate::scheduler::ScheduleEvent;
+use crate::service::TaskService;
+use chrono::Utc;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+#[test]
+fn test_roll_respects_bounds() {
+    assert!(!roll(0.0));
+    assert!(roll(1.0));
+    assert!(roll(7.0), "Probabilities above 1.0 are clamped");
+}
+
+#[tokio::test]
+async fn test_faulty_store_delays_calls() {
+    let faults = FaultConfig {
+        db_delay_probability: 1.0,
+        db_delay_ms: 50,
+        ..FaultConfig::default()
+    };
+    let store = FaultyStore::new(Arc::new(MemoryTaskStore::new()), &faults);
+
+    let started = Instant::now();
+    store.get_task_summaries(&Default::default()).await.unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(50));
+}
+
+#[tokio::test]
+async fn test_injected_executor_failures_are_recorded() {
+    let store = Arc::new(MemoryTaskStore::new());
+    let (tx, _rx) = mpsc::channel(10);
+    let service = TaskService::with_store(store.clone(), tx)
+        .with_middleware(MiddlewareChain::new().with(FaultInjectionMiddleware::new(1.0)));
+
+    let task = Task::new_once("chaos", Utc::now(), json!({}));
+    store.create_task(&task).await.unwrap();
+    service.process_task(task).await.unwrap();
+
+    let executions = store.executions();
+    assert_eq!(executions.len(), 1);
+    assert!(matches!(executions[0].status, ExecutionStatus::Failure));
+    assert_eq!(executions[0].output["error"], "Injected fault");
+}
+
+#[tokio::test]
+async fn test_dropped_wake_ups_leave_changes_in_the_outbox() {
+    let (tx, mut rx) = mpsc::channel(10);
+    let service =
+        TaskService::with_store(Arc::new(MemoryTaskStore::new()), tx).with_config(Config {
+            faults: FaultConfig {
+                notification_drop_probability: 1.0,
+                ..FaultConfig::default()
+            },
+            ..Config::default()
+        });
+
+    let task = Task::new_once("dropped", Utc::now(), json!({}));
+    service.store().create_task(&task).await.unwrap();
+    service.delete_task(task.id).await.unwrap();
+
+    assert!(rx.try_recv().is_err(), "Wake-up should have been dropped");
+    assert_eq!(
+        service.store().drain_outbox(10).await.unwrap(),
+        vec![
+            ScheduleEvent::Upserted {
+                id: task.id,
+                trigger_at: task.trigger_at,
+            },
+            ScheduleEvent::Removed { id: task.id },
+        ]
+    );
+}
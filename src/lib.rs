@@ -2,10 +2,22 @@
 //!
 //! A Rust-based, persistent task scheduler built with Axum, SQLx, and Tokio.
 pub mod api;
+pub mod bench;
+pub mod clock;
 pub mod config;
 pub mod db;
 pub mod domain;
 pub mod errors;
+pub mod executor;
+pub mod fault;
+pub mod notify;
+pub mod retention;
+pub mod schedule;
 pub mod scheduler;
+pub mod secrets;
 pub mod service;
+pub mod telemetry;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
 pub mod tests;
+pub mod tls;
@@ -1,26 +1,45 @@
-use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions};
-use std::str::FromStr;
+use axum::Router;
+use std::net::SocketAddr;
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::{net::TcpListener, signal, sync::mpsc};
 use tokio_util::sync::CancellationToken;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use task_scheduler::{api, config::Config, service::TaskService};
+use task_scheduler::{
+    api, bench,
+    config::Config,
+    db,
+    executor::{LoggingMiddleware, MiddlewareChain},
+    fault::{FaultInjectionMiddleware, FaultyStore},
+    retention,
+    service::TaskService,
+    telemetry::Telemetry,
+    tls::{self, TlsConfig},
+};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let config = Config::from_env()?;
 
+    let mut args = std::env::args().skip(1);
+    if args.next().as_deref() == Some("bench") {
+        return run_bench(config, args.collect()).await;
+    }
+
     let app_env = std::env::var("APP_ENV").unwrap_or_else(|_| "development".into());
     let filter = tracing_subscriber::EnvFilter::new(&config.rust_log);
+    let (telemetry, otel_layer) = Telemetry::init(&config)?;
 
     if app_env.eq_ignore_ascii_case("production") {
         tracing_subscriber::registry()
+            .with(otel_layer)
             .with(filter)
             .with(tracing_subscriber::fmt::layer().json())
             .init();
     } else {
         tracing_subscriber::registry()
+            .with(otel_layer)
             .with(filter)
             .with(tracing_subscriber::fmt::layer().pretty())
             .init();
@@ -28,51 +47,184 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     tracing::info!("Starting Task Scheduler in {} mode", app_env);
 
-    let connection_options = SqliteConnectOptions::from_str(&config.db_url)?
-        .create_if_missing(true)
-        .journal_mode(SqliteJournalMode::Wal)
-        .foreign_keys(true)
-        .busy_timeout(Duration::from_secs(30));
-
-    let pool = SqlitePoolOptions::new()
-        .max_connections(5)
-        .connect_with(connection_options)
-        .await?;
+    let mut store = db::open_store(&config).await?;
 
     tracing::info!("Database connection pool established.");
 
-    sqlx::migrate!("./migrations").run(&pool).await?;
-    tracing::info!("Migrations applied successfully.");
+    // The writer owns the schema; a follower must not migrate it underneath it
+    if config.follower {
+        tracing::info!("Running as a read-only follower, skipping migrations.");
+    } else {
+        tracing::info!("Migrations applied successfully.");
+    }
 
-    let (scheduler_tx, scheduler_rx) = mpsc::channel::<()>(100);
+    let (scheduler_tx, scheduler_rx) = mpsc::channel(100);
 
     tracing::info!("Created scheduler channels.");
 
     let cancel_token = CancellationToken::new();
 
-    let service = TaskService::new(pool.clone(), scheduler_tx);
+    let mut middleware = MiddlewareChain::new().with(LoggingMiddleware);
+
+    if config.faults.is_enabled() {
+        tracing::warn!(faults = ?config.faults, "Fault injection is enabled");
+        store = Arc::new(FaultyStore::new(store, &config.faults));
+        middleware = middleware.with(FaultInjectionMiddleware::new(
+            config.faults.executor_failure_probability,
+        ));
+    }
+
+    if !config.auth_enabled() {
+        tracing::warn!("ADMIN_API_KEY is not set, the API is open to anyone who can reach it");
+    }
+
+    let service = TaskService::with_store(store, scheduler_tx)
+        .with_config(config.clone())
+        .with_middleware(middleware);
+
+    if config.follower {
+        tracing::info!("Follower mode: the scheduler and background jobs are not started.");
+    } else {
+        spawn_background_jobs(&service, scheduler_rx, &config, &cancel_token);
+    }
+    tracing::info!("Task service initialized.");
+
+    // With a dedicated admin listener, admin routes are not exposed publicly
+    let app = match config.admin_addr {
+        Some(_) => api::public_router(service.clone()),
+        None => api::router(service.clone()),
+    };
+
+    tokio::spawn(shutdown_signal(cancel_token.clone()));
+
+    let admin = async {
+        if let Some(admin_addr) = config.admin_addr {
+            let listener = TcpListener::bind(admin_addr).await?;
+            tracing::info!("Admin server listening on http://{}", admin_addr);
+
+            axum::serve(listener, api::admin_router(service.clone()))
+                .with_graceful_shutdown(cancel_token.clone().cancelled_owned())
+                .await?;
+        }
+        Ok::<(), Box<dyn std::error::Error>>(())
+    };
+    let public = serve_public(
+        app,
+        config.bind_addr(),
+        config.tls.clone(),
+        cancel_token.clone(),
+    );
+
+    // Runs still going when the grace period ends are aborted, including those of requests
+    // the servers are waiting on
+    let drain = async {
+        cancel_token.cancelled().await;
+        service.shutdown().await;
+        Ok(())
+    };
+
+    tokio::try_join!(public, admin, drain)?;
+
+    tracing::info!("Application shut down gracefully.");
+    tokio::task::spawn_blocking(move || telemetry.shutdown()).await?;
+
+    Ok(())
+}
+
+/// Starts the scheduler loop and the pruning job, which only the writer instance runs. With
+/// leader election, they only run while this instance holds the leader lease.
+fn spawn_background_jobs(
+    service: &TaskService,
+    scheduler_rx: mpsc::Receiver<()>,
+    config: &Config,
+    token: &CancellationToken,
+) {
+    if config.leader_election {
+        tracing::info!(
+            instance_id = %config.instance_id,
+            "Leader election enabled, standing by until the leader lease is acquired."
+        );
+        service
+            .tracker()
+            .spawn(task_scheduler::scheduler::leader::run_elected(
+                service.clone(),
+                scheduler_rx,
+                token.clone(),
+            ));
+        return;
+    }
 
     let scheduler_service = service.clone();
-    let scheduler_token = cancel_token.clone();
+    let scheduler_token = token.clone();
 
-    tokio::spawn(async move {
+    service.tracker().spawn(async move {
         tracing::info!("Scheduler background task started.");
         task_scheduler::scheduler::run_scheduler(scheduler_service, scheduler_rx, scheduler_token)
             .await;
     });
-    tracing::info!("Task service initialized.");
+    if config.prune_interval_secs > 0 {
+        service.tracker().spawn(retention::run_pruner(
+            service.clone(),
+            Duration::from_secs(config.prune_interval_secs),
+            token.clone(),
+        ));
+    }
+}
 
-    let app = api::router(service);
-    let addr = format!("0.0.0.0:{}", config.server_port);
-    let listener = TcpListener::bind(&addr).await?;
+/// Serves the public API over HTTPS if TLS is configured, plain HTTP otherwise, until the
+/// token is cancelled.
+async fn serve_public(
+    app: Router,
+    addr: SocketAddr,
+    tls: Option<TlsConfig>,
+    token: CancellationToken,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match tls {
+        Some(tls_config) => {
+            let mutual = tls_config.client_ca_path.is_some();
+            let (rustls_config, _reloader) = tls::watch(tls_config, token.clone())?;
 
-    tracing::info!("API Server listening on {}", addr);
+            let handle = axum_server::Handle::new();
+            let shutdown = handle.clone();
+            tokio::spawn(async move {
+                token.cancelled().await;
+                shutdown.graceful_shutdown(None);
+            });
 
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal(cancel_token))
-        .await?;
+            tracing::info!(
+                mutual_tls = mutual,
+                "API Server listening on https://{}",
+                addr
+            );
 
-    tracing::info!("Application shut down gracefully.");
+            axum_server::bind_rustls(addr, rustls_config)
+                .handle(handle)
+                .serve(app.into_make_service())
+                .await?;
+        }
+        None => {
+            let listener = TcpListener::bind(addr).await?;
+
+            tracing::info!("API Server listening on http://{}", addr);
+
+            axum::serve(listener, app)
+                .with_graceful_shutdown(token.cancelled_owned())
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs the `bench` subcommand and prints its report.
+async fn run_bench(config: Config, args: Vec<String>) -> Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::fmt()
+        .with_env_filter(std::env::var("RUST_LOG").unwrap_or_else(|_| "warn".into()))
+        .init();
+
+    let options = bench::BenchOptions::from_args(args)?;
+    let report = bench::run(options, config).await?;
+    println!("{}", report);
 
     Ok(())
 }
@@ -0,0 +1,111 @@
+//! Delivery of task notifications to the channels of a task's notification policy.
+use crate::domain::{NotificationChannel, NotificationEvent};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::json;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// A single notification, sent as-is to callback URLs.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Notification {
+    pub event: NotificationEvent,
+    pub task_id: Uuid,
+    pub task_name: String,
+    /// The execution that caused the notification, if any.
+    pub execution_id: Option<Uuid>,
+    pub message: String,
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl Notification {
+    /// One-line summary used for Slack messages and email subjects.
+    pub fn summary(&self) -> String {
+        let event = match self.event {
+            NotificationEvent::Failure => "failed",
+            NotificationEvent::Recovery => "recovered",
+            NotificationEvent::MissedRun => "missed its scheduled time",
+            NotificationEvent::SlowRun => "ran far longer than usual",
+            NotificationEvent::AutoDisable => "was disabled automatically",
+        };
+        format!("Task '{}' {}", self.task_name, event)
+    }
+}
+
+/// Delivers notifications to a channel.
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    /// Sends `notification` to `channel`.
+    ///
+    /// # Errors
+    ///
+    /// * Returns an error string if the channel could not be reached or rejected the message.
+    async fn send(
+        &self,
+        channel: &NotificationChannel,
+        notification: &Notification,
+    ) -> Result<(), String>;
+}
+
+/// Delivers notifications over HTTP: Slack incoming webhooks, callback URLs, and email
+/// through an HTTP relay.
+pub struct HttpNotifier {
+    email_relay_url: Option<String>,
+}
+
+impl HttpNotifier {
+    /// Creates a notifier. Without `email_relay_url`, email channels cannot be delivered.
+    pub fn new(email_relay_url: Option<String>) -> Self {
+        Self { email_relay_url }
+    }
+}
+
+#[async_trait]
+impl Notifier for HttpNotifier {
+    async fn send(
+        &self,
+        channel: &NotificationChannel,
+        notification: &Notification,
+    ) -> Result<(), String> {
+        let (url, body) = match channel {
+            NotificationChannel::Slack { webhook_url } => (
+                webhook_url.as_str(),
+                json!({ "text": format!("{}: {}", notification.summary(), notification.message) }),
+            ),
+            NotificationChannel::Callback { url } => (url.as_str(), json!(notification)),
+            NotificationChannel::Email { to } => {
+                let relay = self
+                    .email_relay_url
+                    .as_deref()
+                    .ok_or("EMAIL_RELAY_URL is not set, cannot send email")?;
+                let body = json!({
+                    "to": to,
+                    "subject": notification.summary(),
+                    "text": notification.message,
+                });
+                (relay, body)
+            }
+        };
+
+        let client = reqwest::Client::builder()
+            .user_agent("TaskScheduler/1.0")
+            .timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+
+        let response = client
+            .post(url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| format!("HTTP request failed: {:?}", e))?;
+
+        let status = response.status();
+        if status.is_success() {
+            Ok(())
+        } else {
+            Err(format!("HTTP Error {}", status.as_u16()))
+        }
+    }
+}
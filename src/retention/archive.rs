@@ -0,0 +1,154 @@
+//! Archive of the executions the pruning job and task purges remove, kept as
+//! newline-delimited JSON files in a local directory or an S3 bucket, so history outlives
+//! the database cheaply.
+use crate::domain::Execution;
+use crate::errors::AppError;
+use chrono::{DateTime, Utc};
+use std::path::PathBuf;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Where archived executions are written.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArchiveTarget {
+    /// A local directory, e.g. `/var/lib/scheduler/archive` or `file:///var/lib/...`.
+    Local(PathBuf),
+    /// A bucket and key prefix, e.g. `s3://history/scheduler`. Needs a build with the `s3`
+    /// feature.
+    S3 { bucket: String, prefix: String },
+}
+
+impl FromStr for ArchiveTarget {
+    type Err = String;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        if let Some(location) = raw.strip_prefix("s3://") {
+            let (bucket, prefix) = location.split_once('/').unwrap_or((location, ""));
+            if bucket.is_empty() {
+                return Err(format!("'{}' names no bucket", raw));
+            }
+            return Ok(Self::S3 {
+                bucket: bucket.to_string(),
+                prefix: prefix.trim_matches('/').to_string(),
+            });
+        }
+        let path = raw.strip_prefix("file://").unwrap_or(raw);
+        if path.is_empty() {
+            return Err("the archive path is empty".into());
+        }
+        Ok(Self::Local(PathBuf::from(path)))
+    }
+}
+
+/// Writes batches of executions to an [`ArchiveTarget`], one JSON Lines file per batch under
+/// a `YYYY/MM/DD` directory of the day it was archived.
+///
+/// Credentials and region of an S3 target come from the usual `AWS_*` environment variables
+/// (`AWS_ENDPOINT` for S3-compatible stores). The client is built on the first write, and a
+/// failed build is retried by the next one.
+pub struct ExecutionArchive {
+    target: ArchiveTarget,
+    #[cfg(feature = "s3")]
+    s3: tokio::sync::OnceCell<object_store::aws::AmazonS3>,
+}
+
+impl ExecutionArchive {
+    pub fn new(target: ArchiveTarget) -> Self {
+        Self {
+            target,
+            #[cfg(feature = "s3")]
+            s3: tokio::sync::OnceCell::new(),
+        }
+    }
+
+    /// Writes executions to a new file, which is complete once this returns.
+    ///
+    /// # Arguments
+    ///
+    /// * `executions` - The executions to archive, one per line.
+    /// * `archived_at` - When they are archived, which names the file and its directory.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::Archive' if the file cannot be written.
+    /// * Returns 'AppError::Config' for an S3 target in a build without the `s3` feature.
+    ///
+    /// # Returns
+    ///
+    /// The location of the file written.
+    pub async fn write(
+        &self,
+        executions: &[Execution],
+        archived_at: DateTime<Utc>,
+    ) -> Result<String, AppError> {
+        let mut lines = Vec::new();
+        for execution in executions {
+            serde_json::to_writer(&mut lines, execution)
+                .map_err(|e| AppError::Archive(format!("Failed to encode execution: {}", e)))?;
+            lines.push(b'\n');
+        }
+        let key = format!(
+            "{}/executions-{}-{}.jsonl",
+            archived_at.format("%Y/%m/%d"),
+            archived_at.format("%Y%m%dT%H%M%S%.3fZ"),
+            Uuid::new_v4().simple()
+        );
+
+        match &self.target {
+            ArchiveTarget::Local(dir) => write_local(dir, &key, lines).await,
+            #[cfg(feature = "s3")]
+            ArchiveTarget::S3 { bucket, prefix } => {
+                let key = if prefix.is_empty() {
+                    key
+                } else {
+                    format!("{}/{}", prefix, key)
+                };
+                self.write_s3(bucket, &key, lines).await
+            }
+            #[cfg(not(feature = "s3"))]
+            ArchiveTarget::S3 { .. } => Err(AppError::Config(
+                "An S3 archive needs a build with the `s3` feature".into(),
+            )),
+        }
+    }
+
+    #[cfg(feature = "s3")]
+    async fn write_s3(&self, bucket: &str, key: &str, lines: Vec<u8>) -> Result<String, AppError> {
+        use object_store::ObjectStore;
+        use object_store::aws::AmazonS3Builder;
+
+        let client = self
+            .s3
+            .get_or_try_init(|| async {
+                AmazonS3Builder::from_env()
+                    .with_bucket_name(bucket)
+                    .build()
+                    .map_err(|e| AppError::Archive(format!("Invalid S3 archive: {}", e)))
+            })
+            .await?;
+        client
+            .put(&object_store::path::Path::from(key), lines.into())
+            .await
+            .map_err(|e| {
+                AppError::Archive(format!("Failed to upload s3://{}/{}: {}", bucket, key, e))
+            })?;
+
+        Ok(format!("s3://{}/{}", bucket, key))
+    }
+}
+
+/// Writes the file under a temporary name first, so a crash never leaves a partial file
+/// that looks complete.
+async fn write_local(dir: &std::path::Path, key: &str, lines: Vec<u8>) -> Result<String, AppError> {
+    let path = dir.join(key);
+    let failed =
+        |e: std::io::Error| AppError::Archive(format!("Failed to write {}: {}", path.display(), e));
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await.map_err(failed)?;
+    }
+    let partial = path.with_extension("jsonl.partial");
+    tokio::fs::write(&partial, lines).await.map_err(failed)?;
+    tokio::fs::rename(&partial, &path).await.map_err(failed)?;
+
+    Ok(path.display().to_string())
+}
@@ -0,0 +1,43 @@
+//! Background job enforcing execution and recycle bin retention.
+pub mod archive;
+
+use crate::service::TaskService;
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+use tokio_util::sync::CancellationToken;
+
+/// Periodically deletes executions outside their task's retention settings (see
+/// [`TaskService::prune_executions`]) and purges tasks that have been soft deleted for too
+/// long (see [`TaskService::purge_deleted_tasks`]) until the token is cancelled.
+///
+/// Failures are logged and retried on the next tick; pruning never blocks scheduling.
+///
+/// # Arguments
+///
+/// * `service` - The TaskService whose store is pruned.
+/// * `interval` - Time between pruning passes. The first pass runs immediately.
+/// * `token` - A cancellation token to stop the job.
+pub async fn run_pruner(service: TaskService, interval: Duration, token: CancellationToken) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            _ = token.cancelled() => break,
+            _ = ticker.tick() => {}
+        }
+
+        match service.prune_executions().await {
+            Ok(0) => {}
+            Ok(pruned) => tracing::info!(pruned, "Pruned expired executions"),
+            Err(e) => tracing::warn!("Execution pruning failed: {:?}", e),
+        }
+        match service.purge_deleted_tasks().await {
+            Ok(0) => {}
+            Ok(purged) => tracing::info!(purged, "Purged expired deleted tasks"),
+            Err(e) => tracing::warn!("Deleted task purging failed: {:?}", e),
+        }
+    }
+
+    tracing::info!("Pruner stopped.");
+}
@@ -0,0 +1,50 @@
+use crate::schedule::validate::{IssueKind, ScheduleIssue};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use croner::Cron;
+
+/// Parses a cron expression: five fields (minute, hour, day of month, month, day of week),
+/// or six with a leading seconds field.
+///
+/// # Errors
+///
+/// * Returns the problem if the expression is malformed or never matches.
+pub fn parse_cron(expr: &str) -> Result<Cron, ScheduleIssue> {
+    let cron = Cron::new(expr)
+        .with_seconds_optional()
+        .parse()
+        .map_err(|e| {
+            ScheduleIssue::whole(
+                IssueKind::Malformed,
+                format!("'{}' is not a valid cron expression: {}", expr, e),
+            )
+        })?;
+    // Catches expressions that parse but can never fire, e.g. February 30th
+    cron.find_next_occurrence(&Utc::now(), false).map_err(|_| {
+        ScheduleIssue::whole(
+            IssueKind::NeverMatches,
+            format!("Cron expression '{}' never matches", expr),
+        )
+    })?;
+
+    Ok(cron)
+}
+
+/// The first occurrence of a cron expression after `from`, or at `from` if `inclusive`.
+///
+/// The expression matches wall-clock times in `tz`. A time skipped when the clocks go
+/// forward runs when the gap ends, e.g. 02:30 runs at 03:00 when 02:00-03:00 is skipped, and
+/// a time repeated when they go back runs once, at the first of the two instants.
+///
+/// Returns `None` if the expression is malformed or has no further occurrence.
+pub fn next_cron(
+    expr: &str,
+    from: DateTime<Utc>,
+    inclusive: bool,
+    tz: Tz,
+) -> Option<DateTime<Utc>> {
+    let cron = Cron::new(expr).with_seconds_optional().parse().ok()?;
+    cron.find_next_occurrence(&from.with_timezone(&tz), inclusive)
+        .ok()
+        .map(|next| next.with_timezone(&Utc))
+}
@@ -0,0 +1,104 @@
+use chrono::{DateTime, Days, LocalResult, NaiveDateTime, NaiveTime, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// How a local time that does not exist (spring-forward gap) is handled.
+///
+/// Ambiguous local times (fall-back overlap) always run once, at the first of the two
+/// instants, so a daily 02:30 job does not fire twice on the night the clocks go back.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DstPolicy {
+    /// Skip the occurrence entirely; the next one is the following day.
+    Skip,
+    /// Run later by the length of the gap, e.g. 02:30 becomes 03:30 when 02:00-03:00 is skipped.
+    #[default]
+    ShiftForward,
+}
+
+impl FromStr for DstPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "skip" => Ok(DstPolicy::Skip),
+            "shift_forward" => Ok(DstPolicy::ShiftForward),
+            _ => Err(format!(
+                "Unknown DST policy '{}'. Use 'skip' or 'shift_forward'",
+                s
+            )),
+        }
+    }
+}
+
+/// Parses an IANA time zone name, e.g. `Europe/Oslo`.
+///
+/// # Errors
+///
+/// * Returns an error message if the name is not a known time zone.
+pub fn parse_timezone(name: &str) -> Result<Tz, String> {
+    name.parse()
+        .map_err(|_| format!("'{}' is not a known IANA time zone", name))
+}
+
+/// Resolves a local wall-clock time in `tz` to a UTC instant.
+///
+/// # Arguments
+///
+/// * `tz` - The time zone the local time is expressed in.
+/// * `local` - The local date and time.
+/// * `policy` - What to do if `local` falls into a DST gap.
+///
+/// # Returns
+/// * `Option<DateTime<Utc>>` - The instant, or `None` if the policy skips this occurrence.
+pub fn resolve_local(tz: Tz, local: NaiveDateTime, policy: DstPolicy) -> Option<DateTime<Utc>> {
+    match tz.from_local_datetime(&local) {
+        LocalResult::Single(t) => Some(t.with_timezone(&Utc)),
+        // Fall back: the wall time happens twice, run at the first instant only
+        LocalResult::Ambiguous(earliest, _) => Some(earliest.with_timezone(&Utc)),
+        // Spring forward: the wall time never happens
+        LocalResult::None => match policy {
+            DstPolicy::Skip => None,
+            DstPolicy::ShiftForward => shift_past_gap(tz, local),
+        },
+    }
+}
+
+/// Maps a nonexistent local time to the instant it would have had with the pre-transition
+/// offset, which lands the same distance past the gap (02:30 in a 02:00-03:00 gap → 03:30).
+fn shift_past_gap(tz: Tz, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+    // The offset in force just before the gap; gaps are at most a few hours long
+    let before = (1..=24).find_map(|hours| {
+        tz.from_local_datetime(&(local - chrono::Duration::hours(hours)))
+            .earliest()
+    })?;
+    let offset = before.offset().fix();
+    let shifted = local.and_local_timezone(offset).single()?;
+    Some(shifted.with_timezone(&Utc))
+}
+
+/// Computes the next daily occurrence of local time `at` in `tz` strictly after `after`.
+///
+/// # Arguments
+///
+/// * `after` - Occurrences at or before this instant are ignored.
+/// * `at` - The local time of day.
+/// * `tz` - The time zone `at` is expressed in.
+/// * `policy` - How to treat days on which `at` falls into a DST gap.
+///
+/// # Returns
+/// * `Option<DateTime<Utc>>` - The next trigger, or `None` if none exists within a year.
+pub fn next_daily(
+    after: DateTime<Utc>,
+    at: NaiveTime,
+    tz: Tz,
+    policy: DstPolicy,
+) -> Option<DateTime<Utc>> {
+    let start = after.with_timezone(&tz).date_naive();
+
+    (0..=366)
+        .filter_map(|offset| start.checked_add_days(Days::new(offset)))
+        .filter_map(|date| resolve_local(tz, date.and_time(at), policy))
+        .find(|candidate| *candidate > after)
+}
@@ -0,0 +1,109 @@
+use chrono::{Duration, NaiveTime, Timelike};
+
+/// Longest delay or interval a human-friendly schedule may name: ten years.
+pub const MAX_HUMAN_SCHEDULE_SECONDS: i64 = 10 * 365 * 86_400;
+
+/// A schedule written the way people say it, resolved to one of the task types.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HumanSchedule {
+    /// `in 2 hours`: run once, this long from now.
+    Once(Duration),
+    /// `every 15 minutes`: run every this many seconds.
+    Interval(i64),
+    /// `daily at 09:30`: run whenever this cron expression matches.
+    Cron(String),
+}
+
+/// Parses a human-friendly schedule. Case and extra whitespace are ignored.
+///
+/// * `in N <unit>` runs once, e.g. `in 2 hours`.
+/// * `every [N] <unit>` repeats, e.g. `every 15 minutes` or `every hour`; `hourly` is short
+///   for `every hour`.
+/// * `daily at HH:MM`, `every day at HH:MM`, `weekdays at HH:MM` and
+///   `every <weekday> at HH:MM` run at a time of day.
+///
+/// Units are seconds, minutes, hours, days or weeks, singular or plural.
+///
+/// # Errors
+///
+/// * Returns an error message naming the accepted forms if the schedule is not one of them.
+/// * Returns an error message if a delay or interval is longer than ten years.
+pub fn parse_human_schedule(text: &str) -> Result<HumanSchedule, String> {
+    let normalized = text.trim().to_ascii_lowercase();
+    let words: Vec<&str> = normalized.split_whitespace().collect();
+    let parsed = match words.as_slice() {
+        ["in", amount, unit] => duration(amount, unit).map(HumanSchedule::Once),
+        ["hourly"] => Some(HumanSchedule::Interval(3600)),
+        ["every", unit] => {
+            duration("1", unit).map(|every| HumanSchedule::Interval(every.num_seconds()))
+        }
+        ["every", amount, unit] => {
+            duration(amount, unit).map(|every| HumanSchedule::Interval(every.num_seconds()))
+        }
+        ["daily" | "everyday", "at", time] | ["every", "day", "at", time] => daily(time, "*"),
+        ["weekdays", "at", time] | ["every", "weekday", "at", time] => daily(time, "1-5"),
+        ["every", day, "at", time] => weekday(day).and_then(|day| daily(time, day)),
+        _ => None,
+    };
+
+    let parsed = parsed.ok_or_else(|| {
+        format!(
+            "Cannot understand schedule '{}'. Use e.g. 'in 2 hours', 'every 15 minutes', \
+             'daily at 09:30', 'weekdays at 09:30' or 'every monday at 09:30'",
+            text
+        )
+    })?;
+    let seconds = match &parsed {
+        HumanSchedule::Once(delay) => delay.num_seconds(),
+        HumanSchedule::Interval(seconds) => *seconds,
+        HumanSchedule::Cron(_) => 0,
+    };
+    if seconds > MAX_HUMAN_SCHEDULE_SECONDS {
+        return Err(format!(
+            "Schedule '{}' is too far out; delays and intervals may be at most {} days",
+            text,
+            MAX_HUMAN_SCHEDULE_SECONDS / 86_400
+        ));
+    }
+    Ok(parsed)
+}
+
+/// `amount` of `unit`, if both are understood, the amount is at least 1 and the result is
+/// within the range of a `Duration`.
+fn duration(amount: &str, unit: &str) -> Option<Duration> {
+    let amount: i64 = amount.parse().ok().filter(|amount| *amount >= 1)?;
+    let seconds = match unit.strip_suffix('s').unwrap_or(unit) {
+        "second" | "sec" => 1,
+        "minute" | "min" => 60,
+        "hour" | "hr" => 3600,
+        "day" => 86_400,
+        "week" => 604_800,
+        _ => return None,
+    };
+    amount.checked_mul(seconds).and_then(Duration::try_seconds)
+}
+
+/// A cron expression running at `time` (`HH:MM`) on the cron days of week `days`.
+fn daily(time: &str, days: &str) -> Option<HumanSchedule> {
+    let time = NaiveTime::parse_from_str(time, "%H:%M").ok()?;
+    Some(HumanSchedule::Cron(format!(
+        "{} {} * * {}",
+        time.minute(),
+        time.hour(),
+        days
+    )))
+}
+
+/// The cron day of week of a weekday name, singular or plural.
+fn weekday(name: &str) -> Option<&'static str> {
+    Some(match name.strip_suffix('s').unwrap_or(name) {
+        "monday" => "1",
+        "tuesday" => "2",
+        "wednesday" => "3",
+        "thursday" => "4",
+        "friday" => "5",
+        "saturday" => "6",
+        "sunday" => "0",
+        _ => return None,
+    })
+}
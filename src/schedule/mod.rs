@@ -0,0 +1,24 @@
+//! Calendar-based schedule computation.
+//!
+//! Recurring tasks expressed in local wall-clock time ("every day at 02:30 Europe/Oslo")
+//! are resolved to UTC trigger instants here, including the DST edge cases. Cron
+//! expressions, iCalendar recurrence rules and execution windows are evaluated here too, in
+//! UTC or a task's own time zone.
+pub mod cron;
+pub mod dst;
+pub mod human;
+pub mod recurrence;
+pub mod rrule;
+pub mod validate;
+pub mod window;
+
+#[cfg(test)]
+mod tests;
+
+pub use cron::{next_cron, parse_cron};
+pub use dst::{DstPolicy, next_daily, parse_timezone, resolve_local};
+pub use human::{HumanSchedule, parse_human_schedule};
+pub use recurrence::Recurrence;
+pub use rrule::{RRule, parse_rrule};
+pub use validate::{IssueKind, ScheduleIssue, check_cron};
+pub use window::{ExecutionWindow, next_open};
@@ -0,0 +1,47 @@
+use crate::schedule::{RRule, next_cron};
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
+
+/// A schedule on its own, so it can be evaluated before any task has it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Recurrence {
+    /// A single run.
+    Once,
+    /// Runs this many seconds apart.
+    Interval(i64),
+    /// Runs whenever a cron expression matches, in a time zone.
+    Cron { expr: String, tz: Tz },
+    /// Runs on the occurrences of a recurrence rule, in a time zone.
+    Rrule { rule: RRule, tz: Tz },
+}
+
+impl Recurrence {
+    /// The first run of the schedule when it starts at `at`: `at` itself for one-off and
+    /// interval schedules, the first occurrence at or after it for calendar ones.
+    pub fn first(&self, at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Recurrence::Once | Recurrence::Interval(_) => Some(at),
+            Recurrence::Cron { expr, tz } => next_cron(expr, at, true, *tz),
+            Recurrence::Rrule { rule, tz } => rule.next_after(at, true, *tz),
+        }
+    }
+
+    /// The run following one at `previous`, if the schedule has another.
+    pub fn following(&self, previous: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Recurrence::Once => None,
+            Recurrence::Interval(seconds) => {
+                previous.checked_add_signed(Duration::seconds(*seconds))
+            }
+            Recurrence::Cron { expr, tz } => next_cron(expr, previous, false, *tz),
+            Recurrence::Rrule { rule, tz } => rule.next_after(previous, false, *tz),
+        }
+    }
+
+    /// Up to `count` runs of the schedule when it starts at `at`, in order.
+    pub fn upcoming(&self, at: DateTime<Utc>, count: usize) -> Vec<DateTime<Utc>> {
+        std::iter::successors(self.first(at), |previous| self.following(*previous))
+            .take(count)
+            .collect()
+    }
+}
@@ -0,0 +1,510 @@
+use crate::schedule::dst::{DstPolicy, resolve_local};
+use crate::schedule::validate::{IssueKind, ScheduleIssue};
+use chrono::{
+    DateTime, Datelike, Days, Months, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Utc, Weekday,
+};
+use chrono_tz::Tz;
+
+/// Periods searched for the next occurrence before a rule counts as exhausted.
+const MAX_PERIODS: i64 = 4000;
+
+/// Format of `DTSTART` and `UNTIL` date-times, optionally followed by `Z` for UTC.
+const DATE_TIME_FORMAT: &str = "%Y%m%dT%H%M%S";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Until {
+    /// The last instant an occurrence may have.
+    At(DateTime<Utc>),
+    /// The last local date an occurrence may fall on.
+    On(NaiveDate),
+}
+
+/// A parsed iCalendar (RFC 5545) recurrence rule together with its start.
+///
+/// Supports `FREQ` of `DAILY`, `WEEKLY`, `MONTHLY` or `YEARLY` with `INTERVAL`, `COUNT`,
+/// `UNTIL`, `BYMONTH`, `BYMONTHDAY`, `BYDAY` (with ordinals such as `2TU` or `-1FR` in
+/// monthly and yearly rules), `BYSETPOS`, `BYHOUR`, `BYMINUTE` and `BYSECOND`. Weeks start
+/// on Monday. Parts the rule leaves out default to `DTSTART`'s, e.g. a monthly rule without
+/// `BYDAY` or `BYMONTHDAY` runs on `DTSTART`'s day of the month at its time of day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RRule {
+    /// First possible occurrence, as a local wall-clock time.
+    dtstart: NaiveDateTime,
+    freq: Frequency,
+    interval: u32,
+    count: Option<u32>,
+    until: Option<Until>,
+    by_month: Vec<u32>,
+    by_month_day: Vec<i32>,
+    by_day: Vec<(Option<i32>, Weekday)>,
+    by_set_pos: Vec<i32>,
+    by_hour: Vec<u32>,
+    by_minute: Vec<u32>,
+    by_second: Vec<u32>,
+}
+
+/// Parses a recurrence rule, either a bare `FREQ=...` rule or `RRULE:` and `DTSTART:` lines.
+///
+/// # Arguments
+///
+/// * `text` - The rule.
+/// * `default_start` - The start if the rule has no `DTSTART`.
+/// * `tz` - The time zone the rule's wall-clock times are in; a `DTSTART` in UTC (ending in
+///   `Z`) is converted to it.
+///
+/// # Errors
+///
+/// * Returns the first problem found if the rule is malformed or uses unsupported parts.
+pub fn parse_rrule(
+    text: &str,
+    default_start: DateTime<Utc>,
+    tz: Tz,
+) -> Result<RRule, ScheduleIssue> {
+    let mut dtstart = None;
+    let mut rule = None;
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        if let Some(value) = line.strip_prefix("DTSTART:") {
+            dtstart = Some(parse_date_time(value, tz)?);
+        } else if line.starts_with("DTSTART;") {
+            return Err(ScheduleIssue::part(
+                "DTSTART",
+                IssueKind::Unsupported,
+                "DTSTART parameters are not supported; set the task's timezone",
+            ));
+        } else if rule.is_none() {
+            rule = Some(line.strip_prefix("RRULE:").unwrap_or(line));
+        } else {
+            return Err(ScheduleIssue::whole(
+                IssueKind::Malformed,
+                format!("Unexpected line '{}' in recurrence rule", line),
+            ));
+        }
+    }
+    let rule = rule.ok_or_else(|| {
+        ScheduleIssue::part("RRULE", IssueKind::Missing, "Recurrence rule has no RRULE")
+    })?;
+    let dtstart = dtstart.unwrap_or_else(|| default_start.with_timezone(&tz).naive_local());
+
+    let mut parsed = RRule {
+        dtstart,
+        freq: Frequency::Daily,
+        interval: 1,
+        count: None,
+        until: None,
+        by_month: Vec::new(),
+        by_month_day: Vec::new(),
+        by_day: Vec::new(),
+        by_set_pos: Vec::new(),
+        by_hour: Vec::new(),
+        by_minute: Vec::new(),
+        by_second: Vec::new(),
+    };
+    let mut freq = None;
+    for part in rule.split(';').filter(|part| !part.is_empty()) {
+        let (key, value) = part.split_once('=').ok_or_else(|| {
+            ScheduleIssue::whole(
+                IssueKind::Malformed,
+                format!("'{}' is not a KEY=VALUE rule part", part),
+            )
+        })?;
+        match key.to_ascii_uppercase().as_str() {
+            "FREQ" => {
+                freq = Some(match value.to_ascii_uppercase().as_str() {
+                    "DAILY" => Frequency::Daily,
+                    "WEEKLY" => Frequency::Weekly,
+                    "MONTHLY" => Frequency::Monthly,
+                    "YEARLY" => Frequency::Yearly,
+                    _ => {
+                        return Err(ScheduleIssue::part(
+                            "FREQ",
+                            IssueKind::Unsupported,
+                            format!(
+                                "FREQ={} is not supported; use DAILY, WEEKLY, MONTHLY or YEARLY",
+                                value
+                            ),
+                        ));
+                    }
+                })
+            }
+            "INTERVAL" => parsed.interval = parse_number(key, value, 1, u32::MAX)?,
+            "COUNT" => parsed.count = Some(parse_number(key, value, 1, u32::MAX)?),
+            "UNTIL" => parsed.until = Some(parse_until(value)?),
+            "BYMONTH" => parsed.by_month = parse_list(key, value, 1, 12)?,
+            "BYMONTHDAY" => parsed.by_month_day = parse_signed_list(key, value, 31)?,
+            "BYDAY" => {
+                parsed.by_day = value
+                    .split(',')
+                    .map(parse_weekday)
+                    .collect::<Result<_, _>>()?
+            }
+            "BYSETPOS" => parsed.by_set_pos = parse_signed_list(key, value, 366)?,
+            "BYHOUR" => parsed.by_hour = parse_list(key, value, 0, 23)?,
+            "BYMINUTE" => parsed.by_minute = parse_list(key, value, 0, 59)?,
+            "BYSECOND" => parsed.by_second = parse_list(key, value, 0, 59)?,
+            "WKST" if value.eq_ignore_ascii_case("MO") => {}
+            _ => {
+                return Err(ScheduleIssue::part(
+                    key,
+                    IssueKind::Unsupported,
+                    format!("Rule part '{}' is not supported", part),
+                ));
+            }
+        }
+    }
+    parsed.freq = freq.ok_or_else(|| {
+        ScheduleIssue::part("FREQ", IssueKind::Missing, "Recurrence rule has no FREQ")
+    })?;
+    if parsed.count.is_some() && parsed.until.is_some() {
+        return Err(ScheduleIssue::part(
+            "UNTIL",
+            IssueKind::Conflict,
+            "COUNT and UNTIL cannot both be set",
+        ));
+    }
+    if !matches!(parsed.freq, Frequency::Monthly | Frequency::Yearly)
+        && parsed.by_day.iter().any(|(ordinal, _)| ordinal.is_some())
+    {
+        return Err(ScheduleIssue::part(
+            "BYDAY",
+            IssueKind::Conflict,
+            "BYDAY ordinals such as 2TU only apply to MONTHLY and YEARLY rules",
+        ));
+    }
+
+    Ok(parsed)
+}
+
+impl RRule {
+    /// The first occurrence after `after`, or at `after` if `inclusive`.
+    ///
+    /// Wall-clock times are resolved in `tz`; a time skipped when the clocks go forward runs
+    /// later by the length of the gap, and a repeated time runs at its first instant.
+    ///
+    /// Returns `None` once the rule is exhausted by `COUNT` or `UNTIL`, or if it has no
+    /// occurrence within the next few thousand periods.
+    pub fn next_after(
+        &self,
+        after: DateTime<Utc>,
+        inclusive: bool,
+        tz: Tz,
+    ) -> Option<DateTime<Utc>> {
+        // COUNT needs every occurrence from the start; otherwise start just before `after`
+        let first_period = match self.count {
+            Some(_) => 0,
+            None => (self.period_of(after.with_timezone(&tz).date_naive()) - 1).max(0),
+        };
+        let mut seen = 0;
+        for period in first_period..first_period + MAX_PERIODS {
+            for local in self.occurrences(period)? {
+                if local < self.dtstart {
+                    continue;
+                }
+                let Some(at) = resolve_local(tz, local, DstPolicy::ShiftForward) else {
+                    continue;
+                };
+                match self.until {
+                    Some(Until::At(until)) if at > until => return None,
+                    Some(Until::On(until)) if local.date() > until => return None,
+                    _ => {}
+                }
+                seen += 1;
+                if self.count.is_some_and(|count| seen > count) {
+                    return None;
+                }
+                if at > after || (inclusive && at == after) {
+                    return Some(at);
+                }
+            }
+        }
+        None
+    }
+
+    /// Index of the period `date` falls in, counted in `INTERVAL`s from `DTSTART`'s period.
+    fn period_of(&self, date: NaiveDate) -> i64 {
+        let start = self.dtstart.date();
+        let elapsed = match self.freq {
+            Frequency::Daily => (date - start).num_days(),
+            Frequency::Weekly => (week_start(date) - week_start(start)).num_days() / 7,
+            Frequency::Monthly => {
+                i64::from(date.year() - start.year()) * 12 + i64::from(date.month())
+                    - i64::from(start.month())
+            }
+            Frequency::Yearly => i64::from(date.year() - start.year()),
+        };
+        elapsed.div_euclid(i64::from(self.interval))
+    }
+
+    /// The sorted local occurrences in the `period`th period, or `None` past chrono's range.
+    fn occurrences(&self, period: i64) -> Option<Vec<NaiveDateTime>> {
+        let steps = u32::try_from(period).ok()?.checked_mul(self.interval)?;
+        let start = self.dtstart.date();
+        let ranges = match self.freq {
+            Frequency::Daily => vec![vec![start.checked_add_days(Days::new(steps.into()))?]],
+            Frequency::Weekly => {
+                let monday = week_start(start).checked_add_days(Days::new(7 * u64::from(steps)))?;
+                vec![days(monday, monday.checked_add_days(Days::new(7))?)]
+            }
+            Frequency::Monthly => {
+                let first = start.with_day(1)?.checked_add_months(Months::new(steps))?;
+                vec![days(first, first.checked_add_months(Months::new(1))?)]
+            }
+            Frequency::Yearly => {
+                let year = start.year().checked_add(i32::try_from(steps).ok()?)?;
+                let january = NaiveDate::from_ymd_opt(year, 1, 1)?;
+                if self.by_month.is_empty() {
+                    vec![days(january, NaiveDate::from_ymd_opt(year + 1, 1, 1)?)]
+                } else {
+                    let mut months = self.by_month.clone();
+                    months.sort_unstable();
+                    months
+                        .into_iter()
+                        .filter_map(|month| {
+                            let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+                            Some(days(first, first.checked_add_months(Months::new(1))?))
+                        })
+                        .collect()
+                }
+            }
+        };
+
+        let times = self.times();
+        let mut occurrences: Vec<NaiveDateTime> = ranges
+            .iter()
+            .flat_map(|range| range.iter().filter(|day| self.matches(**day, range)))
+            .flat_map(|day| times.iter().map(|time| day.and_time(*time)))
+            .collect();
+        occurrences.sort_unstable();
+        occurrences.dedup();
+
+        if !self.by_set_pos.is_empty() {
+            let len = occurrences.len() as i32;
+            let mut picked: Vec<NaiveDateTime> = self
+                .by_set_pos
+                .iter()
+                .filter_map(|&pos| {
+                    let index = if pos > 0 { pos - 1 } else { len + pos };
+                    usize::try_from(index)
+                        .ok()
+                        .and_then(|index| occurrences.get(index).copied())
+                })
+                .collect();
+            picked.sort_unstable();
+            picked.dedup();
+            occurrences = picked;
+        }
+        Some(occurrences)
+    }
+
+    /// Whether `day` of the dates in `range` (a week, month or year) is part of the rule.
+    fn matches(&self, day: NaiveDate, range: &[NaiveDate]) -> bool {
+        if !self.by_month.is_empty() && !self.by_month.contains(&day.month()) {
+            return false;
+        }
+        if !self.by_month_day.is_empty() {
+            let from_end = day.day() as i32 - days_in_month(day) as i32 - 1;
+            if !self
+                .by_month_day
+                .iter()
+                .any(|&wanted| wanted == day.day() as i32 || wanted == from_end)
+            {
+                return false;
+            }
+        }
+        if !self.by_day.is_empty() {
+            let same_weekday = || {
+                range
+                    .iter()
+                    .filter(|other| other.weekday() == day.weekday())
+            };
+            let nth = same_weekday().filter(|other| **other <= day).count() as i32;
+            let nth_last = -(same_weekday().filter(|other| **other >= day).count() as i32);
+            if !self.by_day.iter().any(|(ordinal, weekday)| {
+                *weekday == day.weekday()
+                    && ordinal.is_none_or(|ordinal| ordinal == nth || ordinal == nth_last)
+            }) {
+                return false;
+            }
+        }
+        if self.by_month_day.is_empty() && self.by_day.is_empty() {
+            let start = self.dtstart.date();
+            return match self.freq {
+                Frequency::Daily => true,
+                Frequency::Weekly => day.weekday() == start.weekday(),
+                Frequency::Monthly => day.day() == start.day(),
+                Frequency::Yearly => {
+                    day.day() == start.day()
+                        && (!self.by_month.is_empty() || day.month() == start.month())
+                }
+            };
+        }
+        true
+    }
+
+    /// The times of day of each occurring day, sorted.
+    fn times(&self) -> Vec<NaiveTime> {
+        let or_start = |values: &[u32], start: u32| match values {
+            [] => vec![start],
+            values => values.to_vec(),
+        };
+        let start = self.dtstart.time();
+        let mut times: Vec<NaiveTime> = or_start(&self.by_hour, start.hour())
+            .into_iter()
+            .flat_map(|hour| {
+                or_start(&self.by_minute, start.minute())
+                    .into_iter()
+                    .map(move |minute| (hour, minute))
+            })
+            .flat_map(|(hour, minute)| {
+                or_start(&self.by_second, start.second())
+                    .into_iter()
+                    .filter_map(move |second| NaiveTime::from_hms_opt(hour, minute, second))
+            })
+            .collect();
+        times.sort_unstable();
+        times.dedup();
+        times
+    }
+}
+
+/// The Monday starting the week of `date`.
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Days::new(u64::from(date.weekday().num_days_from_monday()))
+}
+
+/// The dates from `first` up to, but excluding, `end`.
+fn days(first: NaiveDate, end: NaiveDate) -> Vec<NaiveDate> {
+    first.iter_days().take_while(|day| *day < end).collect()
+}
+
+fn days_in_month(date: NaiveDate) -> u32 {
+    let first = date.with_day(1).expect("Every month has a first day");
+    first
+        .checked_add_months(Months::new(1))
+        .map_or(31, |next| (next - first).num_days() as u32)
+}
+
+fn parse_date_time(value: &str, tz: Tz) -> Result<NaiveDateTime, ScheduleIssue> {
+    let invalid = || {
+        ScheduleIssue::part(
+            "DTSTART",
+            IssueKind::Malformed,
+            format!("'{}' is not a date-time such as 20240102T090000Z", value),
+        )
+    };
+    match value.strip_suffix('Z') {
+        Some(utc) => NaiveDateTime::parse_from_str(utc, DATE_TIME_FORMAT)
+            .map(|at| at.and_utc().with_timezone(&tz).naive_local())
+            .map_err(|_| invalid()),
+        None => NaiveDateTime::parse_from_str(value, DATE_TIME_FORMAT)
+            .or_else(|_| {
+                NaiveDate::parse_from_str(value, "%Y%m%d").map(|date| date.and_time(NaiveTime::MIN))
+            })
+            .map_err(|_| invalid()),
+    }
+}
+
+fn parse_until(value: &str) -> Result<Until, ScheduleIssue> {
+    match value.strip_suffix('Z') {
+        Some(utc) => NaiveDateTime::parse_from_str(utc, DATE_TIME_FORMAT)
+            .map(|at| Until::At(at.and_utc())),
+        None => NaiveDate::parse_from_str(value, "%Y%m%d").map(Until::On),
+    }
+    .map_err(|_| {
+        ScheduleIssue::part(
+            "UNTIL",
+            IssueKind::Malformed,
+            format!(
+                "UNTIL '{}' must be a UTC date-time such as 20241231T235959Z or a date such as 20241231",
+                value
+            ),
+        )
+    })
+}
+
+fn parse_number(key: &str, value: &str, min: u32, max: u32) -> Result<u32, ScheduleIssue> {
+    let message = || format!("{} must be a number from {} to {}", key, min, max);
+    // Digits that do not fit a u32 are out of range too
+    if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(ScheduleIssue::part(key, IssueKind::Malformed, message()));
+    }
+    value
+        .parse()
+        .ok()
+        .filter(|n| (min..=max).contains(n))
+        .ok_or_else(|| ScheduleIssue::part(key, IssueKind::OutOfRange, message()))
+}
+
+fn parse_list(key: &str, value: &str, min: u32, max: u32) -> Result<Vec<u32>, ScheduleIssue> {
+    value
+        .split(',')
+        .map(|item| parse_number(key, item, min, max))
+        .collect()
+}
+
+/// Parses non-zero numbers from `-max` to `max`.
+fn parse_signed_list(key: &str, value: &str, max: i32) -> Result<Vec<i32>, ScheduleIssue> {
+    let issue = |kind| {
+        ScheduleIssue::part(
+            key,
+            kind,
+            format!(
+                "{} values must be non-zero numbers from -{} to {}",
+                key, max, max
+            ),
+        )
+    };
+    value
+        .split(',')
+        .map(|item| {
+            let n = item
+                .parse::<i32>()
+                .map_err(|_| issue(IssueKind::Malformed))?;
+            if n == 0 || n.abs() > max {
+                return Err(issue(IssueKind::OutOfRange));
+            }
+            Ok(n)
+        })
+        .collect()
+}
+
+/// Parses a `BYDAY` value such as `MO`, `2TU` or `-1FR`.
+fn parse_weekday(value: &str) -> Result<(Option<i32>, Weekday), ScheduleIssue> {
+    let invalid = || {
+        ScheduleIssue::part(
+            "BYDAY",
+            IssueKind::Malformed,
+            format!("'{}' is not a BYDAY value such as MO, 2TU or -1FR", value),
+        )
+    };
+    let split = value.len().checked_sub(2).ok_or_else(invalid)?;
+    let (ordinal, day) = value.split_at_checked(split).ok_or_else(invalid)?;
+    let weekday = match day.to_ascii_uppercase().as_str() {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return Err(invalid()),
+    };
+    let ordinal = match ordinal {
+        "" => None,
+        ordinal => Some(
+            ordinal
+                .parse::<i32>()
+                .ok()
+                .filter(|n| *n != 0 && n.abs() <= 53)
+                .ok_or_else(invalid)?,
+        ),
+    };
+    Ok((ordinal, weekday))
+}
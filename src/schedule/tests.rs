@@ -0,0 +1,130 @@
+use crate::schedule::{
+    DstPolicy, next_cron, next_daily, parse_rrule, parse_timezone, resolve_local,
+};
+use chrono::{DateTime, NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::Europe::Oslo;
+
+fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+}
+
+fn half_past_two() -> NaiveTime {
+    NaiveTime::from_hms_opt(2, 30, 0).unwrap()
+}
+
+#[test]
+fn test_regular_day_resolves_with_local_offset() {
+    // 2024-06-01 02:30 CEST (UTC+2)
+    let local = NaiveDate::from_ymd_opt(2024, 6, 1)
+        .unwrap()
+        .and_time(half_past_two());
+    assert_eq!(
+        resolve_local(Oslo, local, DstPolicy::Skip),
+        Some(utc(2024, 6, 1, 0, 30))
+    );
+}
+
+#[test]
+fn test_spring_forward_gap_is_skipped() {
+    // Clocks jump from 02:00 to 03:00 on 2024-03-31, so 02:30 never happens
+    let gap = NaiveDate::from_ymd_opt(2024, 3, 31)
+        .unwrap()
+        .and_time(half_past_two());
+    assert_eq!(resolve_local(Oslo, gap, DstPolicy::Skip), None);
+
+    // The day before ran at 02:30 CET; the next run is 02:30 CEST on April 1st
+    let previous = utc(2024, 3, 30, 1, 30);
+    assert_eq!(
+        next_daily(previous, half_past_two(), Oslo, DstPolicy::Skip),
+        Some(utc(2024, 4, 1, 0, 30))
+    );
+}
+
+#[test]
+fn test_spring_forward_gap_shifts_forward() {
+    let previous = utc(2024, 3, 30, 1, 30);
+    // 02:30 becomes 03:30 CEST, i.e. 01:30 UTC: exactly 24 hours after the previous run
+    assert_eq!(
+        next_daily(previous, half_past_two(), Oslo, DstPolicy::ShiftForward),
+        Some(utc(2024, 3, 31, 1, 30))
+    );
+}
+
+#[test]
+fn test_fall_back_overlap_runs_once() {
+    // 02:30 happens twice on 2024-10-27: at 00:30 UTC (CEST) and 01:30 UTC (CET)
+    let previous = utc(2024, 10, 26, 0, 30);
+    let first = next_daily(previous, half_past_two(), Oslo, DstPolicy::Skip).unwrap();
+    assert_eq!(first, utc(2024, 10, 27, 0, 30), "Runs at the first instant");
+
+    let second = next_daily(first, half_past_two(), Oslo, DstPolicy::Skip).unwrap();
+    assert_eq!(
+        second,
+        utc(2024, 10, 28, 1, 30),
+        "The repeated 02:30 is not run again"
+    );
+}
+
+#[test]
+fn test_cron_follows_local_time_across_dst() {
+    let next = |from| next_cron("30 2 * * *", from, false, Oslo);
+    assert_eq!(parse_timezone("Europe/Oslo"), Ok(Oslo));
+    assert!(parse_timezone("Europe/Atlantis").is_err());
+
+    // 02:30 CET, then 03:00 CEST once the clocks skip 02:00-03:00, then 02:30 CEST
+    let before_spring = utc(2024, 3, 30, 1, 30);
+    assert_eq!(next(before_spring), Some(utc(2024, 3, 31, 1, 0)));
+    assert_eq!(next(utc(2024, 3, 31, 1, 0)), Some(utc(2024, 4, 1, 0, 30)));
+
+    // The repeated 02:30 on 2024-10-27 runs once, then 02:30 CET
+    let before_fall = utc(2024, 10, 26, 0, 30);
+    let first = next(before_fall).unwrap();
+    assert_eq!(first, utc(2024, 10, 27, 0, 30));
+    assert_eq!(next(first), Some(utc(2024, 10, 28, 1, 30)));
+}
+
+#[test]
+fn test_rrules_find_calendar_occurrences() {
+    let start = utc(2024, 1, 1, 9, 0);
+    let occurrences = |rule: &str, count: usize| {
+        let rule = parse_rrule(rule, start, chrono_tz::UTC).unwrap();
+        std::iter::successors(rule.next_after(start, true, chrono_tz::UTC), |at| {
+            rule.next_after(*at, false, chrono_tz::UTC)
+        })
+        .take(count)
+        .collect::<Vec<_>>()
+    };
+
+    // Last business day of the month; March 2024 ends on a Sunday
+    assert_eq!(
+        occurrences("FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1", 3),
+        [
+            utc(2024, 1, 31, 9, 0),
+            utc(2024, 2, 29, 9, 0),
+            utc(2024, 3, 29, 9, 0)
+        ]
+    );
+    // Second Tuesday, every other month
+    assert_eq!(
+        occurrences("RRULE:FREQ=MONTHLY;INTERVAL=2;BYDAY=2TU", 3),
+        [
+            utc(2024, 1, 9, 9, 0),
+            utc(2024, 3, 12, 9, 0),
+            utc(2024, 5, 14, 9, 0)
+        ]
+    );
+    // Counted from DTSTART, at its time of day: only the last of four runs is still ahead
+    assert_eq!(
+        occurrences("DTSTART:20231230T070000Z\nRRULE:FREQ=DAILY;COUNT=4", 5),
+        [utc(2024, 1, 2, 7, 0)]
+    );
+
+    for unsupported in [
+        "FREQ=HOURLY",
+        "FREQ=WEEKLY;BYDAY=1MO",
+        "BYDAY=MO",
+        "FREQ=DAILY;BYWEEKNO=1",
+    ] {
+        assert!(parse_rrule(unsupported, start, chrono_tz::UTC).is_err());
+    }
+}
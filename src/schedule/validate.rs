@@ -0,0 +1,155 @@
+use crate::schedule::parse_cron;
+use serde::Serialize;
+use std::fmt;
+
+/// The cron fields in order, with the values each accepts. Five-field expressions start at
+/// `minute`.
+const CRON_FIELDS: [(&str, u32, u32); 6] = [
+    ("second", 0, 59),
+    ("minute", 0, 59),
+    ("hour", 0, 23),
+    ("day_of_month", 1, 31),
+    ("month", 1, 12),
+    ("day_of_week", 0, 7),
+];
+
+const MONTH_NAMES: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+
+const WEEKDAY_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+/// What is wrong with a schedule expression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    /// Not written the way the syntax requires.
+    Malformed,
+    /// A number outside the values its field accepts.
+    OutOfRange,
+    /// Valid syntax this scheduler does not implement.
+    Unsupported,
+    /// A required part is left out.
+    Missing,
+    /// Parts that cannot be combined.
+    Conflict,
+    /// A name, such as a time zone, that is not known.
+    Unknown,
+    /// Well-formed, but never produces a run.
+    NeverMatches,
+}
+
+/// A problem found in a cron expression or recurrence rule.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ScheduleIssue {
+    /// The cron field (e.g. `hour`) or rule part (e.g. `BYMONTHDAY`) at fault; `None` when
+    /// the problem is with the expression as a whole.
+    pub part: Option<String>,
+    pub kind: IssueKind,
+    pub message: String,
+}
+
+impl ScheduleIssue {
+    /// A problem with the expression as a whole.
+    pub fn whole(kind: IssueKind, message: impl Into<String>) -> Self {
+        Self {
+            part: None,
+            kind,
+            message: message.into(),
+        }
+    }
+
+    /// A problem with one field or part of the expression.
+    pub fn part(part: &str, kind: IssueKind, message: impl Into<String>) -> Self {
+        Self {
+            part: Some(part.into()),
+            kind,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ScheduleIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Checks a cron expression field by field, so every bad field is reported rather than
+/// only the first.
+///
+/// Returns the problems found, or nothing if the expression is valid.
+pub fn check_cron(expr: &str) -> Vec<ScheduleIssue> {
+    let fields: Vec<&str> = expr.split_whitespace().collect();
+    // Nicknames such as @daily are left to the parser
+    if !expr.trim_start().starts_with('@') {
+        let layout = match fields.len() {
+            5 => &CRON_FIELDS[1..],
+            6 => &CRON_FIELDS[..],
+            count => {
+                return vec![ScheduleIssue::whole(
+                    IssueKind::Malformed,
+                    format!("Cron expressions have 5 or 6 fields, not {}", count),
+                )];
+            }
+        };
+        let issues: Vec<_> = fields
+            .iter()
+            .zip(layout)
+            .filter_map(|(value, (name, min, max))| check_cron_field(name, value, *min, *max))
+            .collect();
+        if !issues.is_empty() {
+            return issues;
+        }
+    }
+
+    parse_cron(expr).err().into_iter().collect()
+}
+
+/// The first problem with one cron field, if it has any. Values made of digits and the
+/// `L`, `W` and `#` extensions, such as `5L` or `1#2`, are left to the parser.
+fn check_cron_field(name: &str, value: &str, min: u32, max: u32) -> Option<ScheduleIssue> {
+    let malformed = || {
+        ScheduleIssue::part(
+            name,
+            IssueKind::Malformed,
+            format!("'{}' is not a valid {} field", value, name),
+        )
+    };
+    let names: &[&str] = match name {
+        "month" => &MONTH_NAMES,
+        "day_of_week" => &WEEKDAY_NAMES,
+        _ => &[],
+    };
+
+    for item in value.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => (range, Some(step)),
+            None => (item, None),
+        };
+        if step.is_some_and(|step| !step.parse::<u32>().is_ok_and(|step| step >= 1)) {
+            return Some(malformed());
+        }
+        if range == "*" || range == "?" {
+            continue;
+        }
+        for bound in range.split('-') {
+            if let Ok(number) = bound.parse::<u32>() {
+                if !(min..=max).contains(&number) {
+                    return Some(ScheduleIssue::part(
+                        name,
+                        IssueKind::OutOfRange,
+                        format!("{} must be from {} to {}, not {}", name, min, max, number),
+                    ));
+                }
+            } else if !names.iter().any(|known| bound.eq_ignore_ascii_case(known))
+                && !bound
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || "LW#".contains(c.to_ascii_uppercase()))
+            {
+                return Some(malformed());
+            }
+        }
+    }
+    None
+}
@@ -0,0 +1,53 @@
+use crate::schedule::dst::{DstPolicy, resolve_local};
+use chrono::{DateTime, Datelike, Days, NaiveDate, NaiveTime, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+/// A recurring period of local time a task may run in, e.g. weekdays from 08:00 to 18:00.
+///
+/// A window whose `end` is not after its `start` runs past midnight, e.g. 22:00 to 06:00
+/// opens on each of its days and closes the following morning.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecutionWindow {
+    /// Days the window opens on, e.g. `["mon", "tue"]`; every day if empty.
+    #[serde(default)]
+    pub days: Vec<Weekday>,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl ExecutionWindow {
+    /// The instants the window is open for when it opens on `date`, or `None` if it does
+    /// not open that day.
+    fn on(&self, date: NaiveDate, tz: Tz) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        if !self.days.is_empty() && !self.days.contains(&date.weekday()) {
+            return None;
+        }
+        let end_date = if self.end > self.start {
+            date
+        } else {
+            date.checked_add_days(Days::new(1))?
+        };
+        let opens = resolve_local(tz, date.and_time(self.start), DstPolicy::ShiftForward)?;
+        let closes = resolve_local(tz, end_date.and_time(self.end), DstPolicy::ShiftForward)?;
+        Some((opens, closes))
+    }
+}
+
+/// The first instant at or after `at` that falls inside one of `windows`, evaluated in
+/// `tz`; `at` itself if it is inside one or there are no windows.
+///
+/// Returns `None` if none of the windows ever opens.
+pub fn next_open(windows: &[ExecutionWindow], at: DateTime<Utc>, tz: Tz) -> Option<DateTime<Utc>> {
+    if windows.is_empty() {
+        return Some(at);
+    }
+    let today = at.with_timezone(&tz).date_naive();
+    // Yesterday's overnight windows may still be open; a week ahead covers every weekday
+    (-1..=7)
+        .filter_map(|offset: i64| today.checked_add_signed(chrono::Duration::days(offset)))
+        .flat_map(|date| windows.iter().filter_map(move |window| window.on(date, tz)))
+        .filter(|(_, closes)| *closes > at)
+        .map(|(opens, _)| opens.max(at))
+        .min()
+}
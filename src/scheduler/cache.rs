@@ -0,0 +1,115 @@
+use crate::scheduler::ScheduleEvent;
+use chrono::{DateTime, Utc};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use uuid::Uuid;
+
+/// In-memory min-heap of upcoming trigger times.
+///
+/// The cache holds at most `capacity` of the earliest triggers loaded from the database and is
+/// kept current by schedule events. Superseded heap entries are dropped lazily when they reach
+/// the top. If the database held more tasks than fit, `horizon` marks the last loaded trigger:
+/// anything beyond it may be missing, so reaching it forces a reload.
+#[derive(Debug)]
+pub struct TriggerCache {
+    capacity: usize,
+    heap: BinaryHeap<Reverse<(DateTime<Utc>, Uuid)>>,
+    current: HashMap<Uuid, DateTime<Utc>>,
+    horizon: Option<DateTime<Utc>>,
+    warm: bool,
+    held: HashSet<Uuid>,
+}
+
+impl TriggerCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            heap: BinaryHeap::new(),
+            current: HashMap::new(),
+            horizon: None,
+            warm: false,
+            held: HashSet::new(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// True on cold start, after an invalidation, or once every cached trigger up to the
+    /// horizon has been consumed.
+    pub fn needs_reload(&mut self) -> bool {
+        if !self.warm {
+            return true;
+        }
+        match (self.peek(), self.horizon) {
+            (None, Some(_)) => true,
+            (Some((trigger_at, _)), Some(horizon)) => trigger_at > horizon,
+            _ => false,
+        }
+    }
+
+    /// Replaces the cache contents with triggers freshly read from the database.
+    pub fn load(&mut self, triggers: Vec<(Uuid, DateTime<Utc>)>) {
+        self.heap.clear();
+        self.current.clear();
+
+        self.horizon = if triggers.len() >= self.capacity {
+            triggers.last().map(|(_, trigger_at)| *trigger_at)
+        } else {
+            None
+        };
+
+        for (id, trigger_at) in triggers {
+            self.upsert(id, trigger_at);
+        }
+        self.warm = true;
+    }
+
+    /// Drops everything; the next `needs_reload` call returns true.
+    pub fn invalidate(&mut self) {
+        self.warm = false;
+    }
+
+    pub fn apply(&mut self, event: ScheduleEvent) {
+        match event {
+            ScheduleEvent::Upserted { id, trigger_at } => self.upsert(id, trigger_at),
+            ScheduleEvent::Removed { id } => {
+                self.current.remove(&id);
+            }
+        }
+    }
+
+    /// Keeps a task out of [`TriggerCache::peek`] while it is executing, even across
+    /// reloads, so its still-due trigger does not wake the scheduler again.
+    pub fn hold(&mut self, id: Uuid) {
+        self.held.insert(id);
+    }
+
+    /// Makes a held task's trigger visible again.
+    pub fn release(&mut self, id: Uuid) {
+        if self.held.remove(&id)
+            && let Some(trigger_at) = self.current.get(&id)
+        {
+            self.heap.push(Reverse((*trigger_at, id)));
+        }
+    }
+
+    /// Earliest live trigger of a task that is not held, discarding superseded entries on
+    /// the way.
+    pub fn peek(&mut self) -> Option<(DateTime<Utc>, Uuid)> {
+        while let Some(Reverse((trigger_at, id))) = self.heap.peek().copied() {
+            if self.current.get(&id) == Some(&trigger_at) && !self.held.contains(&id) {
+                return Some((trigger_at, id));
+            }
+            // Held entries come back with `release`
+            self.heap.pop();
+        }
+        None
+    }
+
+    fn upsert(&mut self, id: Uuid, trigger_at: DateTime<Utc>) {
+        self.current.insert(id, trigger_at);
+        self.heap.push(Reverse((trigger_at, id)));
+    }
+}
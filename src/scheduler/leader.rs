@@ -0,0 +1,144 @@
+//! Leader election between instances sharing a database.
+use crate::retention;
+use crate::scheduler::run_scheduler;
+use crate::service::TaskService;
+use chrono::{DateTime, Duration, Utc};
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
+
+/// Name of the lease held by the instance running the scheduler.
+pub const LEADER_LEASE: &str = "scheduler";
+
+/// Runs the scheduler and the pruning job only while this instance holds the leader lease,
+/// until the token is cancelled.
+///
+/// Every instance tries to take the lease; the one that gets it renews it every third of
+/// `leader_lease_secs` and the others stand by, serving the API but scheduling nothing. A
+/// leader that fails to renew before its lease runs out stops its jobs and stands by
+/// again, so a standby takes over at most one lease after the leader stops. On shutdown the
+/// lease is released so a standby can take over right away.
+///
+/// Changes made through a standby's API only reach the leader through the store's outbox,
+/// so the leader also wakes its scheduler on every renewal.
+///
+/// # Arguments
+///
+/// * `service` - The TaskService used to process tasks; its `instance_id` names the holder.
+/// * `rx` - A receiver channel woken whenever new schedule changes are in the outbox.
+/// * `token` - A cancellation token to gracefully shut down the jobs.
+pub async fn run_elected(
+    service: TaskService,
+    mut rx: mpsc::Receiver<()>,
+    token: CancellationToken,
+) {
+    let clock = service.clock().clone();
+    let renew_every = lease_duration(&service) / 3;
+
+    loop {
+        match acquire(&service).await {
+            Ok(Some(held_until)) => {
+                lead(&service, &mut rx, &token, held_until).await;
+                if token.is_cancelled() {
+                    break;
+                }
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Failed to acquire the leader lease: {:?}", e),
+        }
+
+        let retry_at = clock.now() + renew_every;
+        loop {
+            tokio::select! {
+                _ = token.cancelled() => {
+                    tracing::info!("Standby stopped.");
+                    return;
+                }
+                _ = clock.sleep_until(retry_at) => break,
+                // Nothing is scheduled here; the leader reads the changes from the outbox
+                Some(()) = rx.recv() => {}
+            }
+        }
+    }
+}
+
+/// Runs the jobs until the token is cancelled or the lease is lost.
+async fn lead(
+    service: &TaskService,
+    rx: &mut mpsc::Receiver<()>,
+    token: &CancellationToken,
+    mut held_until: DateTime<Utc>,
+) {
+    let clock = service.clock().clone();
+    let holder = &service.config().instance_id;
+    let renew_every = lease_duration(service) / 3;
+    tracing::info!(holder = %holder, "Acquired the leader lease, starting the scheduler.");
+
+    let term = token.child_token();
+    let (wake_tx, wake_rx) = mpsc::channel(1);
+    let mut jobs = JoinSet::new();
+    jobs.spawn(run_scheduler(service.clone(), wake_rx, term.clone()));
+    let prune_interval_secs = service.config().prune_interval_secs;
+    if prune_interval_secs > 0 {
+        jobs.spawn(retention::run_pruner(
+            service.clone(),
+            std::time::Duration::from_secs(prune_interval_secs),
+            term.clone(),
+        ));
+    }
+
+    let mut renew_at = clock.now() + renew_every;
+    loop {
+        tokio::select! {
+            _ = token.cancelled() => break,
+            Some(()) = rx.recv() => {
+                let _ = wake_tx.try_send(());
+            }
+            _ = clock.sleep_until(renew_at) => {
+                match acquire(service).await {
+                    Ok(Some(until)) => held_until = until,
+                    Ok(None) => {
+                        tracing::warn!("Leader lease taken over, stopping the scheduler.");
+                        break;
+                    }
+                    // Keep leading while the lease still holds; another attempt follows
+                    Err(e) if clock.now() + renew_every < held_until => {
+                        tracing::warn!("Failed to renew the leader lease: {:?}", e);
+                    }
+                    Err(e) => {
+                        tracing::warn!("Leader lease expired, stopping the scheduler: {:?}", e);
+                        break;
+                    }
+                }
+                renew_at = clock.now() + renew_every;
+                let _ = wake_tx.try_send(());
+            }
+        }
+    }
+
+    term.cancel();
+    while jobs.join_next().await.is_some() {}
+
+    if token.is_cancelled()
+        && let Err(e) = service.store().release_lease(LEADER_LEASE, holder).await
+    {
+        tracing::warn!("Failed to release the leader lease: {:?}", e);
+    }
+}
+
+/// Takes or renews the leader lease.
+///
+/// Returns when the lease now expires, or `None` if another instance holds it.
+async fn acquire(service: &TaskService) -> sqlx::Result<Option<DateTime<Utc>>> {
+    let now = service.clock().now();
+    let expires_at = now + lease_duration(service);
+    let acquired = service
+        .store()
+        .acquire_lease(LEADER_LEASE, &service.config().instance_id, now, expires_at)
+        .await?;
+    Ok(acquired.then_some(expires_at))
+}
+
+fn lease_duration(service: &TaskService) -> Duration {
+    Duration::seconds(service.config().leader_lease_secs as i64)
+}
@@ -1,50 +1,185 @@
-use std::time::Duration;
+use std::time::Instant;
 
-use crate::{db::queries::TaskRepository, service::TaskService};
-use chrono::Utc;
+use crate::db::retry::{Backoff, is_transient};
+use crate::errors::AppError;
+use crate::service::TaskService;
+use chrono::{DateTime, Duration, Utc};
 use tokio::sync::mpsc;
 use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+pub mod cache;
+pub mod leader;
+pub mod pool;
+
+#[cfg(test)]
+mod tests;
+
+use cache::TriggerCache;
+use pool::WorkerPool;
+
+/// Maximum number of upcoming triggers held in memory.
+const TRIGGER_CACHE_CAPACITY: usize = 1024;
+
+/// Number of outbox entries consumed per store round trip.
+const OUTBOX_DRAIN_BATCH: i64 = 256;
+
+/// A change to a task's schedule, delivered to the scheduler through the store's outbox so
+/// its trigger cache stays current.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScheduleEvent {
+    /// The task was created or its next trigger time changed.
+    Upserted { id: Uuid, trigger_at: DateTime<Utc> },
+    /// The task no longer needs to be scheduled.
+    Removed { id: Uuid },
+}
 
 /// Runs the task scheduler which continuously checks for pending tasks and processes them.
 ///
+/// Upcoming trigger times are kept in an in-memory [`TriggerCache`] fed by schedule events,
+/// so the database is only consulted on cold start, on cache misses and when tasks are due.
+/// Events are read from the store's outbox, which API mutations write in the same transaction
+/// as the change itself; `rx` only carries wake-ups, so a lost signal delays an event until
+/// the next pass but never drops it.
+/// All waiting and due checks go through the service's [`Clock`](crate::clock::Clock).
+///
+/// On start, executions a previous process left running are recorded as interrupted (see
+/// [`TaskService::recover_interrupted`]). Runs of other instances whose claims expired are
+/// reclaimed the same way every third of `claim_timeout_secs` (see
+/// [`TaskService::reclaim_expired`]), so several schedulers can share one database.
+///
+/// Due tasks run in a [`WorkerPool`] of at most `max_concurrent_executions` tasks, so a slow
+/// webhook only occupies its own slot while the scheduler keeps dispatching. On shutdown the
+/// scheduler waits up to `shutdown_grace_secs` for running tasks to finish so their results
+/// are recorded, then aborts the rest and records them as interrupted.
+///
+/// While maintenance mode pauses dispatch, due tasks stay due and run once it ends.
+///
+/// If the database becomes unreachable the scheduler marks the service degraded, backs off
+/// exponentially with jitter and stops dispatching; results of executions that already ran
+/// are buffered by the service and written first once the database is back.
+///
 /// # Arguments
 ///
 /// * `service` - The TaskService used to process tasks.
-/// * `rx` - A receiver channel to listen for new task notifications.
+/// * `rx` - A receiver channel woken whenever new schedule changes are in the outbox.
 /// * `token` - A cancellation token to gracefully shut down the scheduler.
 pub async fn run_scheduler(
     service: TaskService,
     mut rx: mpsc::Receiver<()>,
     token: CancellationToken,
 ) {
-    let repo = TaskRepository::new(&service.get_pool());
+    let store = service.store().clone();
+    let clock = service.clock().clone();
+    let limit = service.config().max_concurrent_executions;
+    let mut pool = WorkerPool::new(limit);
+    // Running tasks are held in the cache; it must always have room beyond them
+    let mut cache = TriggerCache::new(TRIGGER_CACHE_CAPACITY.max(2 * limit));
+    let mut catch_up: Option<CatchUp> = None;
+    let mut backoff = service.config().outage_backoff();
+    let reclaim_every = Duration::seconds(service.config().claim_timeout_secs as i64) / 3;
+    let mut reclaim_at = clock.now() + reclaim_every;
 
+    // Attempts cut short by a previous crash count as runs; settle them before dispatching
     loop {
-        let next_task = match repo.get_next_pending_task().await {
-            Ok(task) => task,
+        match service.recover_interrupted().await {
+            Ok(changes) => {
+                changes.into_iter().for_each(|c| cache.apply(c));
+                recovered(&service, &mut backoff);
+                break;
+            }
+            Err(e) if is_outage(&e) => {
+                if !ride_out_outage(&service, &mut backoff, &token, &e).await {
+                    return;
+                }
+            }
             Err(e) => {
-                tracing::error!("Failed to fetch next task: {:?}", e);
-                tokio::time::sleep(Duration::from_secs(5)).await;
-                continue;
+                tracing::error!("Failed to recover interrupted executions: {:?}", e);
+                break;
+            }
+        }
+    }
+
+    // Workflow runs cut short the same way pick up where they left off
+    if let Err(e) = service.resume_workflow_runs().await {
+        tracing::error!("Failed to resume workflow runs: {:?}", e);
+    }
+
+    loop {
+        // Results from an outage go first; nothing new is dispatched until they are stored
+        if service.has_unflushed() {
+            match service.flush_unflushed().await {
+                Ok(changes) => {
+                    changes.into_iter().for_each(|c| cache.apply(c));
+                    recovered(&service, &mut backoff);
+                }
+                Err(e) => {
+                    if !ride_out_outage(&service, &mut backoff, &token, &e).await {
+                        break;
+                    }
+                    continue;
+                }
             }
-        };
+        }
 
-        let sleep_duration = if let Some(ref task) = next_task {
-            let now = Utc::now();
+        if let Err(e) = drain_outbox(&service, &mut cache).await {
+            if !ride_out_outage(&service, &mut backoff, &token, &e.into()).await {
+                break;
+            }
+            continue;
+        }
 
-            if task.trigger_at <= now {
-                Duration::ZERO
-            } else {
-                (task.trigger_at - now).to_std().unwrap_or(Duration::ZERO)
+        // Tasks of a crashed instance stay claimed until its runs are finalized
+        if clock.now() >= reclaim_at {
+            match service.reclaim_expired().await {
+                Ok(changes) => {
+                    changes.into_iter().for_each(|c| cache.apply(c));
+                    reclaim_at = clock.now() + reclaim_every;
+                }
+                Err(e) => {
+                    if !ride_out_outage(&service, &mut backoff, &token, &e).await {
+                        break;
+                    }
+                    continue;
+                }
             }
-        } else {
-            Duration::from_secs(3600)
-        };
+        }
+
+        if cache.needs_reload() {
+            match store.get_upcoming_triggers(cache.capacity() as i64).await {
+                Ok(triggers) => {
+                    tracing::debug!(count = triggers.len(), "Loaded trigger cache from DB");
+                    cache.load(triggers);
+                    recovered(&service, &mut backoff);
+                }
+                Err(e) => {
+                    if !ride_out_outage(&service, &mut backoff, &token, &e.into()).await {
+                        break;
+                    }
+                    continue;
+                }
+            }
+        }
+
+        let next_trigger = cache.peek();
+        // Leaving maintenance wakes the scheduler, so a paused one just waits for that
+        let paused = service.maintenance().pauses_dispatch();
+
+        // A full pool wakes the scheduler when a batch finishes
+        let can_dispatch = !paused && pool.available() > 0;
+        let wake_at = match next_trigger {
+            Some((trigger_at, _)) if can_dispatch => trigger_at,
+            _ => clock.now() + Duration::hours(1),
+        }
+        .min(reclaim_at);
+        if paused {
+            tracing::info!("Dispatch paused for maintenance");
+        }
 
         tracing::info!(
-            "Scheduler sleeping for {:?}. Next task: {:?}",
-            sleep_duration,
-            next_task.as_ref().map(|t| &t.name)
+            "Scheduler sleeping until {}. Next task: {:?}",
+            wake_at,
+            next_trigger.map(|(_, id)| id)
         );
 
         tokio::select! {
@@ -54,20 +189,217 @@ pub async fn run_scheduler(
                 break;
             }
             // Timer elapsed
-            _ = tokio::time::sleep(sleep_duration) => {
-                if let Some(task) = next_task {
-                    if task.trigger_at <= Utc::now() {
-                        if let Err(e) = service.process_task(task).await {
-                        tracing::error!("Error processing task: {:?}", e);
+            _ = clock.sleep_until(wake_at) => {
+                if let Some((trigger_at, _)) = next_trigger
+                    && trigger_at <= clock.now()
+                    && can_dispatch
+                {
+                    match process_due_chunk(&service, &mut cache, &mut catch_up, &mut pool).await {
+                        Ok(()) => recovered(&service, &mut backoff),
+                        Err(e) if is_outage(&e) => {
+                            if !ride_out_outage(&service, &mut backoff, &token, &e).await {
+                                break;
+                            }
+                        }
+                        Err(_) => {}
+                    }
+                }
+            }
+            // A batch finished, freeing its slots
+            Some((ids, result)) = pool.join_next() => {
+                ids.into_iter().for_each(|id| cache.release(id));
+                match result {
+                    Ok(changes) => {
+                        changes.into_iter().for_each(|c| cache.apply(c));
+                        recovered(&service, &mut backoff);
+                    }
+                    Err(e) => {
+                        tracing::error!("Error processing tasks: {:?}", e);
+                        cache.invalidate();
+                        if is_outage(&e) && !ride_out_outage(&service, &mut backoff, &token, &e).await {
+                            break;
                         }
                     }
                 }
             }
-            // New task notification received
-            _ = rx.recv() => {
+            // New schedule changes are waiting in the outbox
+            Some(()) = rx.recv() => {
                 tracing::info!("Received new task notification.");
             }
         }
     }
+
+    if !pool.is_idle() {
+        tracing::info!(
+            running = pool.in_flight(),
+            "Waiting for running tasks to finish"
+        );
+    }
+    let grace = Duration::seconds(service.config().shutdown_grace_secs as i64);
+    let abort_at = clock.now() + grace;
+    let mut aborted = false;
+    loop {
+        tokio::select! {
+            joined = pool.join_next() => match joined {
+                Some((_, Err(e))) => tracing::error!("Error processing tasks: {:?}", e),
+                Some(_) => {}
+                None => break,
+            },
+            // Aborted runs still finish, recorded as interrupted
+            _ = clock.sleep_until(abort_at), if !aborted => {
+                aborted = true;
+                service.abort_executions();
+            }
+        }
+    }
+
+    if service.has_unflushed()
+        && let Err(e) = service.flush_unflushed().await
+    {
+        tracing::error!("Exiting with unsaved execution results: {:?}", e);
+    }
     tracing::info!("Scheduler exited cleanly!");
 }
+
+/// Applies every pending outbox entry to the cache, oldest first.
+async fn drain_outbox(service: &TaskService, cache: &mut TriggerCache) -> sqlx::Result<()> {
+    loop {
+        let events = service.store().drain_outbox(OUTBOX_DRAIN_BATCH).await?;
+        let drained = events.len() as i64;
+        events.into_iter().for_each(|e| cache.apply(e));
+
+        if drained < OUTBOX_DRAIN_BATCH {
+            return Ok(());
+        }
+    }
+}
+
+fn is_outage(error: &AppError) -> bool {
+    matches!(error, AppError::Database(e) if is_transient(e))
+}
+
+/// Marks the service degraded and waits out the next backoff step.
+///
+/// Returns false if the scheduler was cancelled while waiting.
+async fn ride_out_outage(
+    service: &TaskService,
+    backoff: &mut Backoff,
+    token: &CancellationToken,
+    error: &AppError,
+) -> bool {
+    service.health().mark_degraded(error.to_string());
+    let delay = backoff.next_delay();
+    tracing::warn!(
+        attempt = backoff.attempts(),
+        ?delay,
+        "Database unavailable, backing off"
+    );
+
+    let clock = service.clock();
+    let resume_at = clock.now() + Duration::from_std(delay).unwrap_or(Duration::MAX);
+    tokio::select! {
+        _ = token.cancelled() => false,
+        _ = clock.sleep_until(resume_at) => true,
+    }
+}
+
+/// Clears degraded mode after a successful round trip to the database.
+fn recovered(service: &TaskService, backoff: &mut Backoff) {
+    service.health().mark_ready();
+    backoff.reset();
+}
+
+/// Progress of an ongoing catch-up, started when a pass finds more due tasks than one chunk.
+#[derive(Debug)]
+struct CatchUp {
+    started: Instant,
+    processed: usize,
+}
+
+/// Claims one chunk of due tasks and hands it to the worker pool in write batches.
+///
+/// The chunk is bounded by `catch_up_chunk_size` and by the pool's free slots, so a large
+/// backlog (e.g. after downtime) is worked off over several passes instead of being loaded
+/// into memory at once. Tasks that are still running are skipped. Progress is logged while a
+/// backlog remains.
+async fn process_due_chunk(
+    service: &TaskService,
+    cache: &mut TriggerCache,
+    catch_up: &mut Option<CatchUp>,
+    pool: &mut WorkerPool,
+) -> Result<(), AppError> {
+    let config = service.config();
+    let store = service.store();
+    let chunk_size = config.catch_up_chunk_size.min(pool.available());
+    if chunk_size == 0 {
+        return Ok(());
+    }
+
+    // Running tasks are still due, so look past them
+    let limit = (chunk_size + pool.in_flight()) as i64;
+    let due = match store.get_due_tasks(service.clock().now(), limit).await {
+        Ok(due) => due,
+        Err(e) => {
+            tracing::error!("Failed to fetch due tasks: {:?}", e);
+            cache.invalidate();
+            return Err(e.into());
+        }
+    };
+    let due: Vec<_> = due
+        .into_iter()
+        .filter(|task| !pool.is_running(&task.id))
+        .take(chunk_size)
+        .collect();
+
+    // The cached trigger no longer matches the DB (e.g. changed elsewhere)
+    if due.is_empty() {
+        cache.invalidate();
+        return Ok(());
+    }
+
+    let claimed = due.len();
+    if claimed >= chunk_size && catch_up.is_none() {
+        tracing::warn!(
+            chunk_size,
+            "Due tasks exceed one chunk, catching up on backlog"
+        );
+        *catch_up = Some(CatchUp {
+            started: Instant::now(),
+            processed: 0,
+        });
+    }
+
+    let mut due = due.into_iter().peekable();
+    while due.peek().is_some() {
+        let batch: Vec<_> = due.by_ref().take(config.write_batch_size).collect();
+        batch.iter().for_each(|task| cache.hold(task.id));
+        pool.spawn(service, batch);
+    }
+    if let Some(progress) = catch_up.as_mut() {
+        progress.processed += claimed;
+    }
+
+    let Some(progress) = catch_up.as_ref() else {
+        return Ok(());
+    };
+
+    if claimed >= chunk_size {
+        match store.count_due_tasks(service.clock().now()).await {
+            Ok(remaining) => tracing::info!(
+                processed = progress.processed,
+                remaining,
+                "Catch-up progress"
+            ),
+            Err(e) => tracing::warn!("Failed to count remaining due tasks: {:?}", e),
+        }
+    } else {
+        tracing::info!(
+            processed = progress.processed,
+            elapsed = ?progress.started.elapsed(),
+            "Caught up on backlog"
+        );
+        *catch_up = None;
+    }
+
+    Ok(())
+}
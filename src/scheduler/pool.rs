@@ -0,0 +1,80 @@
+use crate::domain::Task;
+use crate::errors::AppError;
+use crate::scheduler::ScheduleEvent;
+use crate::service::TaskService;
+use std::collections::{HashMap, HashSet};
+use tokio::task::{Id, JoinSet};
+use uuid::Uuid;
+
+/// A finished batch: the tasks it ran and the resulting schedule changes.
+pub type BatchResult = (Vec<Uuid>, Result<Vec<ScheduleEvent>, AppError>);
+
+/// Runs batches of due tasks in the background, so the scheduler keeps dispatching while
+/// slow webhooks are still executing.
+///
+/// At most `limit` tasks execute at once. The pool tracks which tasks are in flight, so a
+/// task that is still due in the database while it executes is not dispatched twice.
+#[derive(Debug)]
+pub struct WorkerPool {
+    limit: usize,
+    jobs: JoinSet<Result<Vec<ScheduleEvent>, AppError>>,
+    batches: HashMap<Id, Vec<Uuid>>,
+    in_flight: HashSet<Uuid>,
+}
+
+impl WorkerPool {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            jobs: JoinSet::new(),
+            batches: HashMap::new(),
+            in_flight: HashSet::new(),
+        }
+    }
+
+    /// Number of tasks that can be dispatched before the pool is full.
+    pub fn available(&self) -> usize {
+        self.limit.saturating_sub(self.in_flight.len())
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    pub fn is_running(&self, id: &Uuid) -> bool {
+        self.in_flight.contains(id)
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    /// Processes a batch of due tasks in the background (see [`TaskService::process_batch`]).
+    pub fn spawn(&mut self, service: &TaskService, batch: Vec<Task>) {
+        let ids: Vec<Uuid> = batch.iter().map(|task| task.id).collect();
+        self.in_flight.extend(ids.iter().copied());
+
+        let service = service.clone();
+        let job = self
+            .jobs
+            .spawn(async move { service.process_batch(batch).await });
+        self.batches.insert(job.id(), ids);
+    }
+
+    /// Waits for the next batch to finish; `None` if nothing is running.
+    pub async fn join_next(&mut self) -> Option<BatchResult> {
+        let (id, result) = match self.jobs.join_next_with_id().await? {
+            Ok(finished) => finished,
+            // A panicked batch leaves its tasks due, so they are dispatched again
+            Err(e) => {
+                tracing::error!("Task batch did not complete: {:?}", e);
+                (e.id(), Ok(Vec::new()))
+            }
+        };
+        let ids = self.batches.remove(&id).unwrap_or_default();
+        for id in &ids {
+            self.in_flight.remove(id);
+        }
+        Some((ids, result))
+    }
+}
@@ -0,0 +1,456 @@
+use crate::api::dto::CreateTaskReq;
+use crate::clock::{Clock, TestClock};
+use crate::config::Config;
+use crate::db::queries::TaskRepository;
+use crate::db::store::TaskStore;
+use crate::domain::Task;
+use crate::fault::FaultConfig;
+use crate::scheduler::cache::TriggerCache;
+use crate::scheduler::leader::{LEADER_LEASE, run_elected};
+use crate::scheduler::pool::WorkerPool;
+use crate::scheduler::{ScheduleEvent, process_due_chunk, run_scheduler};
+use crate::service::TaskService;
+use chrono::{Duration, TimeZone, Utc};
+use serde_json::json;
+use sqlx::SqlitePool;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+#[test]
+fn test_cache_orders_and_supersedes_triggers() {
+    let mut cache = TriggerCache::new(10);
+    assert!(cache.needs_reload(), "Cold cache should load from the DB");
+
+    let now = Utc::now();
+    let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+    cache.load(vec![(a, now), (b, now + Duration::minutes(1))]);
+    assert!(!cache.needs_reload());
+    assert_eq!(cache.peek(), Some((now, a)));
+
+    // Rescheduling a past the other task moves it behind b
+    cache.apply(ScheduleEvent::Upserted {
+        id: a,
+        trigger_at: now + Duration::minutes(5),
+    });
+    assert_eq!(cache.peek(), Some((now + Duration::minutes(1), b)));
+
+    cache.apply(ScheduleEvent::Removed { id: b });
+    assert_eq!(cache.peek(), Some((now + Duration::minutes(5), a)));
+
+    cache.apply(ScheduleEvent::Removed { id: a });
+    assert_eq!(cache.peek(), None);
+    assert!(
+        !cache.needs_reload(),
+        "Cache held everything, nothing to miss"
+    );
+}
+
+#[test]
+fn test_cache_reloads_past_horizon() {
+    let mut cache = TriggerCache::new(2);
+    let now = Utc::now();
+    let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+
+    // A full load means more tasks may exist beyond the last loaded trigger
+    cache.load(vec![(a, now), (b, now + Duration::minutes(1))]);
+    cache.apply(ScheduleEvent::Removed { id: a });
+    cache.apply(ScheduleEvent::Removed { id: b });
+    assert!(cache.needs_reload(), "Exhausted partial cache is a miss");
+
+    cache.load(vec![(a, now)]);
+    cache.apply(ScheduleEvent::Upserted {
+        id: b,
+        trigger_at: now + Duration::hours(1),
+    });
+    assert!(!cache.needs_reload());
+}
+
+/// Waits for every dispatched batch to be recorded.
+async fn finish(workers: &mut WorkerPool) {
+    while let Some((_, result)) = workers.join_next().await {
+        result.unwrap();
+    }
+}
+
+#[sqlx::test]
+async fn test_backlog_is_worked_off_in_chunks(pool: SqlitePool) -> sqlx::Result<()> {
+    let clock = Arc::new(TestClock::new(
+        Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+    ));
+    let (tx, _rx) = mpsc::channel(100);
+    let service = TaskService::new(pool.clone(), tx)
+        .with_clock(clock.clone())
+        .with_config(Config {
+            catch_up_chunk_size: 3,
+            write_batch_size: 2,
+            ..Config::default()
+        });
+    let repo = TaskRepository::new(&pool);
+
+    for i in 0..5 {
+        let task = Task::new_once(
+            format!("overdue-{}", i),
+            clock.now() - Duration::hours(1),
+            json!({}),
+        );
+        repo.create_task(&task).await?;
+    }
+
+    let mut cache = TriggerCache::new(10);
+    let mut catch_up = None;
+    let mut workers = WorkerPool::new(10);
+
+    process_due_chunk(&service, &mut cache, &mut catch_up, &mut workers)
+        .await
+        .unwrap();
+    finish(&mut workers).await;
+    assert_eq!(
+        repo.count_due_tasks(clock.now()).await?,
+        2,
+        "Only one chunk is claimed per pass"
+    );
+    assert!(catch_up.is_some(), "A full chunk starts a catch-up");
+
+    process_due_chunk(&service, &mut cache, &mut catch_up, &mut workers)
+        .await
+        .unwrap();
+    finish(&mut workers).await;
+    assert_eq!(repo.count_due_tasks(clock.now()).await?, 0);
+    assert!(catch_up.is_none(), "A partial chunk ends the catch-up");
+
+    let executions: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM executions")
+        .fetch_one(&pool)
+        .await?;
+    assert_eq!(executions, 5);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_scheduler_fires_only_when_clock_reaches_trigger(
+    pool: SqlitePool,
+) -> sqlx::Result<()> {
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let clock = Arc::new(TestClock::new(start));
+    let (tx, rx) = mpsc::channel(100);
+    let service = TaskService::new(pool.clone(), tx).with_clock(clock.clone());
+    let repo = TaskRepository::new(&pool);
+
+    let task = Task::new_once("in-an-hour", start + Duration::hours(1), json!({}));
+    repo.create_task(&task).await?;
+
+    let token = CancellationToken::new();
+    let handle = tokio::spawn(run_scheduler(service, rx, token.clone()));
+
+    let executions = || async {
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM executions")
+            .fetch_one(&pool)
+            .await
+    };
+
+    // Nothing happens before the trigger, however long we actually wait
+    clock.advance(Duration::minutes(59));
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    assert_eq!(executions().await?, 0);
+
+    // Time travel past the trigger wakes the scheduler
+    clock.advance(Duration::minutes(1));
+    for _ in 0..50 {
+        if executions().await? == 1 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert_eq!(executions().await?, 1);
+
+    let executed_at: chrono::DateTime<Utc> =
+        sqlx::query_scalar("SELECT executed_at FROM executions")
+            .fetch_one(&pool)
+            .await?;
+    assert_eq!(executed_at, start + Duration::hours(1));
+
+    token.cancel();
+    handle.await.unwrap();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_standby_schedules_only_once_it_takes_over_the_lease() {
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let clock = Arc::new(TestClock::new(start));
+    let store = Arc::new(crate::db::memory::MemoryTaskStore::new());
+    let (tx, rx) = mpsc::channel(100);
+    let service = TaskService::with_store(store.clone(), tx)
+        .with_clock(clock.clone())
+        .with_config(Config {
+            leader_lease_secs: 15,
+            instance_id: "standby".into(),
+            ..Config::default()
+        });
+
+    let task = Task::new_once("due", start - Duration::minutes(1), json!({}));
+    store.create_task(&task).await.unwrap();
+    // Another instance leads and stops renewing
+    assert!(
+        store
+            .acquire_lease(LEADER_LEASE, "leader", start, start + Duration::seconds(15))
+            .await
+            .unwrap()
+    );
+
+    let token = CancellationToken::new();
+    let handle = tokio::spawn(run_elected(service, rx, token.clone()));
+
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    assert!(store.executions().is_empty(), "A standby schedules nothing");
+
+    clock.advance(Duration::seconds(16));
+    for _ in 0..50 {
+        if !store.executions().is_empty() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert_eq!(store.executions().len(), 1, "The new leader runs due tasks");
+    assert!(
+        !store
+            .acquire_lease(LEADER_LEASE, "leader", clock.now(), clock.now())
+            .await
+            .unwrap()
+    );
+
+    // Shutting down hands the lease back
+    token.cancel();
+    handle.await.unwrap();
+    assert!(
+        store
+            .acquire_lease(LEADER_LEASE, "leader", clock.now(), clock.now())
+            .await
+            .unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_scheduler_recovers_from_database_outage() {
+    let store = Arc::new(crate::db::memory::MemoryTaskStore::new());
+    let (tx, rx) = mpsc::channel(100);
+    let service = TaskService::with_store(store.clone(), tx).with_config(Config {
+        outage_backoff_base_ms: 1,
+        outage_backoff_max_ms: 10,
+        ..Config::default()
+    });
+
+    let task = Task::new_once("survivor", Utc::now() - Duration::minutes(1), json!({}));
+    store.create_task(&task).await.unwrap();
+
+    // The database is down for the first few calls
+    for _ in 0..4 {
+        store.fail_next(sqlx::Error::PoolTimedOut);
+    }
+
+    let token = CancellationToken::new();
+    let handle = tokio::spawn(run_scheduler(service.clone(), rx, token.clone()));
+
+    for _ in 0..100 {
+        if !store.executions().is_empty() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+
+    assert_eq!(store.executions().len(), 1, "Task runs once the DB is back");
+    assert!(service.health().is_ready(), "Degraded mode is cleared");
+
+    token.cancel();
+    handle.await.unwrap();
+}
+
+#[sqlx::test]
+async fn test_lost_wake_up_delays_but_does_not_drop_new_tasks(
+    pool: SqlitePool,
+) -> sqlx::Result<()> {
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let clock = Arc::new(TestClock::new(start));
+    let (tx, rx) = mpsc::channel(100);
+    let service = TaskService::new(pool.clone(), tx)
+        .with_clock(clock.clone())
+        .with_config(Config {
+            faults: FaultConfig {
+                notification_drop_probability: 1.0,
+                ..FaultConfig::default()
+            },
+            ..Config::default()
+        });
+
+    let token = CancellationToken::new();
+    let handle = tokio::spawn(run_scheduler(service.clone(), rx, token.clone()));
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+    let id = service
+        .create_task(
+            CreateTaskReq {
+                name: "unannounced".into(),
+                task_type: "once".into(),
+                trigger_at: Some(start),
+                schedule: None,
+                interval_seconds: None,
+                cron_expr: None,
+                payload: None,
+                keep_executions: None,
+                keep_days: None,
+                notifications: None,
+                group_id: None,
+                labels: None,
+                retry_policy: None,
+                priority: None,
+                misfire_policy: None,
+                interval_mode: None,
+                ends_at: None,
+                max_executions: None,
+                timezone: None,
+                rrule: None,
+                blackout_windows: None,
+                execution_windows: None,
+                depends_on: None,
+                next_task_id: None,
+                run_if: None,
+            },
+            Default::default(),
+        )
+        .await
+        .unwrap()
+        .id;
+
+    let executions = || async {
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM executions WHERE task_id = ?")
+            .bind(id)
+            .fetch_one(&pool)
+            .await
+    };
+
+    // Without a wake-up the scheduler keeps sleeping on its idle timer
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    assert_eq!(executions().await?, 0);
+
+    // The next pass finds the change in the outbox
+    clock.advance(Duration::hours(1));
+    for _ in 0..50 {
+        if executions().await? == 1 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert_eq!(executions().await?, 1);
+
+    token.cancel();
+    handle.await.unwrap();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_slow_webhook_does_not_hold_up_other_tasks() {
+    use crate::domain::ExecutionStatus;
+
+    let release = Arc::new(tokio::sync::Notify::new());
+    let hook = {
+        let release = release.clone();
+        axum::Router::new()
+            .route(
+                "/slow",
+                axum::routing::get(move || async move { release.notified().await }),
+            )
+            .route("/fast", axum::routing::get(|| async {}))
+    };
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, hook).await.unwrap() });
+
+    let store = Arc::new(crate::db::memory::MemoryTaskStore::new());
+    let (tx, rx) = mpsc::channel(100);
+    let service = TaskService::with_store(store.clone(), tx).with_config(Config {
+        write_batch_size: 1,
+        ..Config::default()
+    });
+    let slow = Task::new_once(
+        "slow",
+        Utc::now() - Duration::minutes(2),
+        json!({ "url": format!("{}/slow", base) }),
+    );
+    let fast = Task::new_once(
+        "fast",
+        Utc::now() - Duration::minutes(1),
+        json!({ "url": format!("{}/fast", base) }),
+    );
+    store.create_task(&slow).await.unwrap();
+    store.create_task(&fast).await.unwrap();
+
+    let token = CancellationToken::new();
+    let handle = tokio::spawn(run_scheduler(service, rx, token.clone()));
+
+    let status_of = |id: Uuid| {
+        store
+            .executions()
+            .into_iter()
+            .find(|e| e.task_id == id)
+            .map(|e| e.status)
+    };
+    for _ in 0..100 {
+        if status_of(fast.id) == Some(ExecutionStatus::Success) {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert_eq!(status_of(fast.id), Some(ExecutionStatus::Success));
+    assert_eq!(
+        status_of(slow.id),
+        Some(ExecutionStatus::Running),
+        "The slow task is still executing"
+    );
+
+    // Shutdown waits for the running task and records it
+    release.notify_one();
+    token.cancel();
+    handle.await.unwrap();
+    assert_eq!(status_of(slow.id), Some(ExecutionStatus::Success));
+}
+
+#[tokio::test]
+async fn test_shutdown_aborts_runs_that_outlast_the_grace_period() {
+    use crate::domain::ExecutionStatus;
+
+    let hook = axum::Router::new().route("/hang", axum::routing::get(std::future::pending::<()>));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hang", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, hook).await.unwrap() });
+
+    let store = Arc::new(crate::db::memory::MemoryTaskStore::new());
+    let (tx, rx) = mpsc::channel(100);
+    let service = TaskService::with_store(store.clone(), tx).with_config(Config {
+        shutdown_grace_secs: 1,
+        ..Config::default()
+    });
+    let task = Task::new_once(
+        "hang",
+        Utc::now() - Duration::minutes(1),
+        json!({ "url": url }),
+    );
+    store.create_task(&task).await.unwrap();
+
+    let token = CancellationToken::new();
+    let handle = tokio::spawn(run_scheduler(service, rx, token.clone()));
+    for _ in 0..100 {
+        if !store.executions().is_empty() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+
+    // The run is aborted once the grace period is over, and its record kept
+    token.cancel();
+    handle.await.unwrap();
+    let execution = store.executions().pop().unwrap();
+    assert_eq!(execution.status, ExecutionStatus::Interrupted);
+    assert_eq!(execution.output["error"], "Aborted at shutdown");
+}
@@ -0,0 +1,175 @@
+//! Named secrets referenced from task payloads.
+//!
+//! Payloads refer to a secret as `{{secret:NAME}}` in any string. Secrets are stored
+//! encrypted with AES-256-GCM under `SECRETS_KEY`, and only decrypted while a task runs;
+//! the stored payload and the recorded execution output never contain their values.
+use ring::aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+
+#[cfg(test)]
+mod tests;
+
+/// Opening marker of a secret placeholder.
+const PLACEHOLDER_START: &str = "{{secret:";
+
+/// Closing marker of a secret placeholder.
+const PLACEHOLDER_END: &str = "}}";
+
+/// Replacement for secret values found in execution output.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Length of `SECRETS_KEY`, decoded.
+pub const KEY_LEN: usize = 32;
+
+/// Encrypts and decrypts secret values. The secret's name is authenticated along with its
+/// value, so a stored ciphertext cannot be moved to another name.
+#[derive(Clone)]
+pub struct SecretCipher {
+    key: [u8; KEY_LEN],
+}
+
+impl std::fmt::Debug for SecretCipher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SecretCipher(..)")
+    }
+}
+
+impl SecretCipher {
+    /// # Errors
+    ///
+    /// * Returns an error message if the key is not 32 bytes long.
+    pub fn new(key: &[u8]) -> Result<Self, String> {
+        let key = key
+            .try_into()
+            .map_err(|_| format!("must be {} bytes, got {}", KEY_LEN, key.len()))?;
+        Ok(Self { key })
+    }
+
+    fn key(&self) -> LessSafeKey {
+        LessSafeKey::new(UnboundKey::new(&AES_256_GCM, &self.key).expect("Key length is checked"))
+    }
+
+    /// A random nonce followed by the encrypted value and its tag.
+    pub fn encrypt(&self, name: &str, value: &str) -> Vec<u8> {
+        let nonce: [u8; NONCE_LEN] = rand::random();
+        let mut sealed = value.as_bytes().to_vec();
+        self.key()
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(name.as_bytes()),
+                &mut sealed,
+            )
+            .expect("Secret values fit in a single AES-GCM message");
+        [nonce.as_slice(), &sealed].concat()
+    }
+
+    /// # Errors
+    ///
+    /// * Returns an error message if the ciphertext was not sealed by this key for `name`.
+    pub fn decrypt(&self, name: &str, ciphertext: &[u8]) -> Result<String, String> {
+        let undecryptable = || format!("Secret '{}' cannot be decrypted with SECRETS_KEY", name);
+        if ciphertext.len() < NONCE_LEN {
+            return Err(undecryptable());
+        }
+        let (nonce, sealed) = ciphertext.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| undecryptable())?;
+        let mut sealed = sealed.to_vec();
+        let plaintext = self
+            .key()
+            .open_in_place(nonce, Aad::from(name.as_bytes()), &mut sealed)
+            .map_err(|_| undecryptable())?;
+        String::from_utf8(plaintext.to_vec()).map_err(|_| undecryptable())
+    }
+}
+
+/// Checks a secret name: 1 to 128 letters, digits, `_`, `-` or `.`.
+///
+/// # Errors
+///
+/// * Returns an error message if the name is invalid.
+pub fn validate_name(name: &str) -> Result<(), String> {
+    let valid = (1..=128).contains(&name.len())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid secret name '{}': use up to 128 letters, digits, '_', '-' and '.'",
+            name
+        ))
+    }
+}
+
+/// Names of the secrets a payload refers to.
+pub fn placeholders(payload: &Value) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    visit_strings(payload, &mut |text| {
+        let mut rest = text;
+        while let Some(start) = rest.find(PLACEHOLDER_START) {
+            let after = &rest[start + PLACEHOLDER_START.len()..];
+            let Some(end) = after.find(PLACEHOLDER_END) else {
+                break;
+            };
+            names.insert(after[..end].to_string());
+            rest = &after[end + PLACEHOLDER_END.len()..];
+        }
+    });
+    names
+}
+
+/// Replaces every placeholder with its secret's value. Placeholders of secrets missing
+/// from `values` are left as they are.
+pub fn interpolate(payload: &mut Value, values: &HashMap<String, String>) {
+    visit_strings_mut(payload, &mut |text| {
+        for (name, value) in values {
+            let placeholder = format!("{}{}{}", PLACEHOLDER_START, name, PLACEHOLDER_END);
+            if text.contains(&placeholder) {
+                *text = text.replace(&placeholder, value);
+            }
+        }
+    });
+}
+
+/// Replaces every occurrence of a secret value in the strings of `output`.
+pub fn scrub(output: &mut Value, values: &[String]) {
+    visit_strings_mut(output, &mut |text| {
+        *text = scrub_text(text, values);
+    });
+}
+
+/// Replaces every occurrence of a secret value in `text`.
+pub fn scrub_text(text: &str, values: &[String]) -> String {
+    values
+        .iter()
+        .filter(|value| !value.is_empty())
+        .fold(text.to_string(), |text, value| {
+            text.replace(value, REDACTED)
+        })
+}
+
+fn visit_strings(value: &Value, visit: &mut impl FnMut(&str)) {
+    match value {
+        Value::String(text) => visit(text),
+        Value::Array(items) => items.iter().for_each(|item| visit_strings(item, visit)),
+        Value::Object(fields) => fields
+            .values()
+            .for_each(|field| visit_strings(field, visit)),
+        _ => {}
+    }
+}
+
+fn visit_strings_mut(value: &mut Value, visit: &mut impl FnMut(&mut String)) {
+    match value {
+        Value::String(text) => visit(text),
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|item| visit_strings_mut(item, visit)),
+        Value::Object(fields) => fields
+            .values_mut()
+            .for_each(|field| visit_strings_mut(field, visit)),
+        _ => {}
+    }
+}
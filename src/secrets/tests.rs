@@ -0,0 +1,31 @@
+use crate::secrets::{SecretCipher, interpolate, placeholders, scrub};
+use serde_json::json;
+use std::collections::HashMap;
+
+#[test]
+fn test_secrets_round_trip_only_under_their_own_name() {
+    let cipher = SecretCipher::new(&[7; 32]).unwrap();
+    let sealed = cipher.encrypt("api-token", "t0k3n");
+
+    assert_eq!(cipher.decrypt("api-token", &sealed).unwrap(), "t0k3n");
+    assert!(cipher.decrypt("other", &sealed).is_err());
+    assert!(
+        SecretCipher::new(&[7; 32])
+            .unwrap()
+            .decrypt("api-token", &sealed[1..])
+            .is_err()
+    );
+    assert!(SecretCipher::new(&[7; 16]).is_err());
+
+    let mut payload = json!({ "headers": { "X-Key": "Key {{secret:api-token}}" }, "n": 1 });
+    assert_eq!(
+        placeholders(&payload).into_iter().collect::<Vec<_>>(),
+        ["api-token"]
+    );
+    let values = HashMap::from([("api-token".to_string(), "t0k3n".to_string())]);
+    interpolate(&mut payload, &values);
+    assert_eq!(payload["headers"]["X-Key"], "Key t0k3n");
+
+    scrub(&mut payload, &["t0k3n".to_string()]);
+    assert_eq!(payload["headers"]["X-Key"], "Key [REDACTED]");
+}
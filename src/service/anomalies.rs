@@ -0,0 +1,83 @@
+use crate::domain::{Execution, ExecutionStatus, Task};
+use crate::service::TaskService;
+
+/// How many recent successful runs make up a task's baseline.
+const BASELINE_RUNS: i64 = 20;
+
+/// Fewer successful runs than this are too little history to judge by.
+const MIN_BASELINE_RUNS: usize = 5;
+
+/// Median of the given durations; `None` if there are none.
+fn median(mut durations: Vec<i64>) -> Option<f64> {
+    if durations.is_empty() {
+        return None;
+    }
+
+    durations.sort_unstable();
+    let mid = durations.len() / 2;
+    Some(if durations.len().is_multiple_of(2) {
+        (durations[mid - 1] + durations[mid]) as f64 / 2.0
+    } else {
+        durations[mid] as f64
+    })
+}
+
+impl TaskService {
+    /// Compares a finished execution's duration against the median of the task's recent
+    /// successful runs and logs a warning if it took more than `duration_anomaly_factor`
+    /// times as long.
+    ///
+    /// # Arguments
+    ///
+    /// * `task` - The task the execution belongs to.
+    /// * `execution` - The finished execution.
+    ///
+    /// # Returns
+    /// A description of the anomaly, or `None` if the run was within range, the check is off
+    /// or there is not enough history yet.
+    pub(super) async fn duration_anomaly(
+        &self,
+        task: &Task,
+        execution: &Execution,
+    ) -> Option<String> {
+        let factor = self.config.duration_anomaly_factor;
+        let duration = execution.duration_ms?;
+        if factor <= 0.0 || execution.status == ExecutionStatus::Running {
+            return None;
+        }
+
+        let recent = match self
+            .store
+            .recent_durations(task.id, execution.id, BASELINE_RUNS)
+            .await
+        {
+            Ok(recent) => recent,
+            Err(e) => {
+                tracing::warn!(task_id = %task.id, "Could not load recent durations: {}", e);
+                return None;
+            }
+        };
+        if recent.len() < MIN_BASELINE_RUNS {
+            return None;
+        }
+        let median = median(recent)?;
+        // A zero median (sub-millisecond runs) would flag everything
+        if duration as f64 <= factor * median.max(1.0) {
+            return None;
+        }
+
+        tracing::warn!(
+            task_id = %task.id,
+            execution_id = %execution.id,
+            duration_ms = duration,
+            median_ms = median,
+            "Execution took far longer than usual"
+        );
+        Some(format!(
+            "Took {} ms, {:.1}x the recent median of {} ms",
+            duration,
+            duration as f64 / median.max(1.0),
+            median
+        ))
+    }
+}
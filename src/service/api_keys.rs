@@ -0,0 +1,149 @@
+use crate::db::retry::with_retry;
+use crate::domain::{ApiKey, Scope};
+use crate::errors::AppError;
+use crate::service::TaskService;
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::DateTime;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+/// Prefix of generated keys, so leaked keys are easy to recognise in logs and scanners.
+const KEY_PREFIX: &str = "sk_";
+
+/// Generates a new random API key.
+pub fn generate_key() -> String {
+    let bytes: [u8; 32] = rand::random();
+    format!("{}{}", KEY_PREFIX, URL_SAFE_NO_PAD.encode(bytes))
+}
+
+/// Hashes a plaintext key for storage and lookup.
+pub fn hash_key(key: &str) -> String {
+    format!("{:x}", Sha256::digest(key.as_bytes()))
+}
+
+impl TaskService {
+    /// Resolves a presented API key to the key it belongs to.
+    ///
+    /// The configured bootstrap key authenticates as an admin key with a nil ID.
+    ///
+    /// # Arguments
+    ///
+    /// * `presented` - The plaintext key sent by the client.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::Unauthorized' if the key is unknown or revoked.
+    /// * Returns 'AppError::Database' if the lookup fails.
+    pub async fn authenticate(&self, presented: &str) -> Result<ApiKey, AppError> {
+        let presented_hash = hash_key(presented);
+
+        // Comparing hashes keeps the comparison independent of the secret's content
+        if let Some(bootstrap) = &self.config.admin_api_key
+            && hash_key(bootstrap) == presented_hash
+        {
+            return Ok(ApiKey {
+                id: Uuid::nil(),
+                name: "bootstrap".to_string(),
+                scope: Scope::Admin,
+                created_at: DateTime::UNIX_EPOCH,
+                revoked_at: None,
+            });
+        }
+
+        with_retry(&self.config.retry_policy(), || {
+            self.store.find_api_key(&presented_hash)
+        })
+        .await?
+        .ok_or(AppError::Unauthorized)
+    }
+
+    /// Creates an API key with the given scope.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Label for the key.
+    /// * `scope` - Access level granted to the key.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::ValidationError' if `name` is blank.
+    /// * Returns 'AppError::Database' if the insert fails.
+    ///
+    /// Returns the key's metadata and its plaintext, which is not stored and cannot be
+    /// retrieved again.
+    pub async fn create_api_key(
+        &self,
+        name: String,
+        scope: Scope,
+    ) -> Result<(ApiKey, String), AppError> {
+        if name.trim().is_empty() {
+            return Err(AppError::ValidationError("name must not be empty".into()));
+        }
+
+        let plaintext = generate_key();
+        let key = ApiKey {
+            id: Uuid::new_v4(),
+            name,
+            scope,
+            created_at: self.clock.now(),
+            revoked_at: None,
+        };
+
+        let key_hash = hash_key(&plaintext);
+        with_retry(&self.config.retry_policy(), || {
+            self.store.create_api_key(&key, &key_hash)
+        })
+        .await?;
+        tracing::info!(key_id = %key.id, ?scope, "API key created");
+
+        Ok((key, plaintext))
+    }
+
+    /// Lists every API key, revoked ones included.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::Database' if the query fails.
+    pub async fn list_api_keys(&self) -> Result<Vec<ApiKey>, AppError> {
+        Ok(with_retry(&self.config.retry_policy(), || self.store.list_api_keys()).await?)
+    }
+
+    /// Changes the scope of a live API key.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::NotFound' if the key does not exist or is revoked.
+    /// * Returns 'AppError::Database' if the update fails.
+    pub async fn set_api_key_scope(&self, id: Uuid, scope: Scope) -> Result<(), AppError> {
+        let rows = with_retry(&self.config.retry_policy(), || {
+            self.store.update_api_key_scope(id, scope)
+        })
+        .await?;
+        if rows == 0 {
+            return Err(AppError::NotFound);
+        }
+
+        tracing::info!(key_id = %id, ?scope, "API key scope changed");
+        Ok(())
+    }
+
+    /// Revokes an API key.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::NotFound' if the key does not exist or is already revoked.
+    /// * Returns 'AppError::Database' if the update fails.
+    pub async fn revoke_api_key(&self, id: Uuid) -> Result<(), AppError> {
+        let now = self.clock.now();
+        let rows = with_retry(&self.config.retry_policy(), || {
+            self.store.revoke_api_key(id, now)
+        })
+        .await?;
+        if rows == 0 {
+            return Err(AppError::NotFound);
+        }
+
+        tracing::info!(key_id = %id, "API key revoked");
+        Ok(())
+    }
+}
@@ -0,0 +1,86 @@
+use crate::db::retry::with_retry;
+use crate::domain::{BlackoutWindow, Task};
+use crate::errors::AppError;
+use crate::scheduler::ScheduleEvent;
+use crate::service::TaskService;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// A run held back by a blackout window or a dependency.
+pub(super) struct Deferral {
+    pub task_id: Uuid,
+    /// When the run was due.
+    pub due_at: DateTime<Utc>,
+    /// When the run fires instead.
+    pub until: DateTime<Utc>,
+}
+
+/// Checks that every window ends after it starts.
+///
+/// # Errors
+///
+/// * Returns 'AppError::ValidationError' if a window is empty or ends before it starts.
+pub fn validate_blackout_windows(windows: &[BlackoutWindow]) -> Result<(), AppError> {
+    if windows.iter().any(|w| w.ends_at <= w.starts_at) {
+        return Err(AppError::ValidationError(
+            "Blackout windows must end after they start".into(),
+        ));
+    }
+
+    Ok(())
+}
+
+impl TaskService {
+    /// Whether a run of `task` dispatched at `now` falls into a global or task blackout
+    /// window, and if so the deferral that holds it back until the window ends.
+    pub(super) fn blackout(&self, task: &Task, now: DateTime<Utc>) -> Option<Deferral> {
+        let windows = self
+            .config
+            .blackout_windows
+            .iter()
+            .chain(task.blackout_windows.iter().flatten());
+        BlackoutWindow::release(windows, now).map(|until| Deferral {
+            task_id: task.id,
+            due_at: task.trigger_at,
+            until: task.within_windows(until),
+        })
+    }
+
+    /// Moves runs held back by blackout windows or dependencies to when they may fire,
+    /// without running them. Each task keeps the time its run was first due, so the execution that finally
+    /// runs shows why it was late.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::Database' for any database operation failures.
+    ///
+    /// Returns the resulting schedule changes.
+    pub(super) async fn defer_runs(
+        &self,
+        deferrals: Vec<Deferral>,
+    ) -> Result<Vec<ScheduleEvent>, AppError> {
+        let mut changes = Vec::with_capacity(deferrals.len());
+        for deferral in deferrals {
+            let Deferral {
+                task_id: id,
+                due_at,
+                until,
+            } = deferral;
+            let rows_affected = with_retry(&self.config.retry_policy(), || {
+                self.store.defer_task(id, until, due_at)
+            })
+            .await?;
+
+            tracing::info!(task_id = %id, %due_at, %until, "Deferred run");
+            changes.push(if rows_affected == 0 {
+                ScheduleEvent::Removed { id }
+            } else {
+                ScheduleEvent::Upserted {
+                    id,
+                    trigger_at: until,
+                }
+            });
+        }
+        Ok(changes)
+    }
+}
@@ -0,0 +1,131 @@
+use crate::db::retry::with_retry;
+use crate::domain::{Execution, ExecutionStatus, Task};
+use crate::errors::AppError;
+use crate::service::TaskService;
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::MutexGuard;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+impl TaskService {
+    /// Cancels a running execution: its executor is stopped and the execution is recorded
+    /// as `cancelled` once it has wound down. The run is not retried and the task keeps its
+    /// schedule.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The UUID of the execution.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::NotFound' if the execution does not exist.
+    /// * Returns 'AppError::Conflict' if it is not running, or is running in another
+    ///   scheduler instance.
+    /// * Returns 'AppError::Database' for any database operation failures.
+    pub async fn cancel_execution(&self, id: Uuid) -> Result<(), AppError> {
+        let execution = with_retry(&self.config.retry_policy(), || self.store.get_execution(id))
+            .await?
+            .ok_or(AppError::NotFound)?;
+        if execution.status != ExecutionStatus::Running {
+            return Err(AppError::Conflict("Execution is not running".into()));
+        }
+
+        let Some(token) = self.lock_cancellations().get(&id).cloned() else {
+            return Err(AppError::Conflict(
+                "Execution is running in another scheduler instance".into(),
+            ));
+        };
+        token.cancel();
+        tracing::info!(execution_id = %id, task_id = %execution.task_id, "Execution cancelled");
+        Ok(())
+    }
+
+    /// Aborts every execution running in this process, e.g. once shutdown has waited long
+    /// enough for them. Each is recorded as interrupted when its executor has stopped, and
+    /// executions started afterwards are aborted right away.
+    pub fn abort_executions(&self) {
+        let running = self.lock_cancellations().len();
+        tracing::warn!(running, "Aborting running executions");
+        self.aborts.cancel();
+    }
+
+    /// Waits for the executions and other background work of this process to finish
+    /// before it exits. Whatever is still running after `shutdown_grace_secs` is aborted,
+    /// executions being recorded as interrupted, and given as long again to wind down.
+    pub async fn shutdown(&self) {
+        self.tracker.close();
+        if !self.tracker.is_empty() {
+            tracing::info!(
+                tasks = self.tracker.len(),
+                "Waiting for background work to finish"
+            );
+        }
+        let grace = Duration::from_secs(self.config.shutdown_grace_secs);
+        if tokio::time::timeout(grace, self.tracker.wait())
+            .await
+            .is_ok()
+        {
+            return;
+        }
+
+        self.abort_executions();
+        if tokio::time::timeout(grace, self.tracker.wait())
+            .await
+            .is_err()
+        {
+            tracing::error!(
+                tasks = self.tracker.len(),
+                "Exiting with background work still running"
+            );
+        }
+    }
+
+    /// Registers a starting execution, returning the token that cancels it.
+    pub(super) fn track_execution(&self, id: Uuid) -> CancellationToken {
+        let token = self.aborts.child_token();
+        self.lock_cancellations().insert(id, token.clone());
+        token
+    }
+
+    pub(super) fn untrack_execution(&self, id: Uuid) {
+        self.lock_cancellations().remove(&id);
+    }
+
+    /// The record of an execution cancelled before it finished: `cancelled`, or
+    /// `interrupted` if it was aborted at shutdown.
+    pub(super) fn cancelled_execution(
+        &self,
+        task: &Task,
+        id: Uuid,
+        started_at: DateTime<Utc>,
+    ) -> Execution {
+        let (output, status) = if self.aborts.is_cancelled() {
+            (
+                json!({ "error": "Aborted at shutdown" }),
+                ExecutionStatus::Interrupted,
+            )
+        } else {
+            (
+                json!({ "error": "Execution was cancelled" }),
+                ExecutionStatus::Cancelled,
+            )
+        };
+        let finished_at = self.clock.now();
+        Execution {
+            id,
+            executed_at: finished_at,
+            payload: Some(task.payload.clone()),
+            duration_ms: Some((finished_at - started_at).num_milliseconds()),
+            started_at: Some(started_at),
+            finished_at: Some(finished_at),
+            ..Execution::new(task.id, output, status)
+        }
+    }
+
+    fn lock_cancellations(&self) -> MutexGuard<'_, HashMap<Uuid, CancellationToken>> {
+        self.cancellations.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
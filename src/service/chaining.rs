@@ -0,0 +1,69 @@
+use crate::db::retry::with_retry;
+use crate::domain::{Execution, ExecutionStatus, Task};
+use crate::errors::AppError;
+use crate::executor::template;
+use crate::secrets;
+use crate::service::{PendingWrite, TaskService};
+use uuid::Uuid;
+
+impl TaskService {
+    /// Starts the next task of each task that just succeeded, in the background. The next
+    /// task runs outside its own schedule, with the `{{previous.*}}` placeholders of its
+    /// payload filled from the run before it.
+    pub(super) fn start_next_tasks(&self, writes: &[PendingWrite]) {
+        for write in writes
+            .iter()
+            .filter(|write| write.execution.status == ExecutionStatus::Success)
+        {
+            let Some(next_task_id) = write.task.next_task_id else {
+                continue;
+            };
+            let service = self.clone();
+            let previous = write.execution.clone();
+            self.tracker.spawn(async move {
+                if let Err(e) = service.run_next_task(next_task_id, &previous).await {
+                    tracing::warn!(
+                        task_id = %previous.task_id,
+                        %next_task_id,
+                        "Next task in the chain did not run: {:?}",
+                        e
+                    );
+                }
+            });
+        }
+    }
+
+    /// Runs a chained task with the output of the run before it.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::NotFound' if the task was deleted.
+    /// * Returns 'AppError::ValidationError' if the previous output would add secret
+    ///   placeholders the task's own payload does not use; they are never resolved.
+    /// * Returns 'AppError::Conflict' if the task is already running.
+    /// * Returns 'AppError::Database' for any database operation failures.
+    ///
+    /// Returns the finished execution.
+    async fn run_next_task(&self, id: Uuid, previous: &Execution) -> Result<Execution, AppError> {
+        let task = with_retry(&self.config.retry_policy(), || self.store.get_task(id))
+            .await?
+            .filter(|task| task.deleted_at.is_none())
+            .ok_or(AppError::NotFound)?;
+
+        let mut payload = task.payload.clone();
+        template::render_previous(&mut payload, previous);
+        // Output comes from outside, so it must not be able to ask for secrets
+        let allowed = secrets::placeholders(&task.payload);
+        if let Some(name) = secrets::placeholders(&payload)
+            .into_iter()
+            .find(|name| !allowed.contains(name))
+        {
+            return Err(AppError::ValidationError(format!(
+                "The previous output refers to secret '{}', which the task does not use",
+                name
+            )));
+        }
+        tracing::info!(task_id = %id, previous_execution_id = %previous.id, "Running next task in the chain");
+        self.run_now(&Task { payload, ..task }, false).await
+    }
+}
@@ -0,0 +1,184 @@
+use crate::db::retry::with_retry;
+use crate::domain::{Dependency, DependencyState, ExecutionStatus, Task};
+use crate::errors::AppError;
+use crate::scheduler::ScheduleEvent;
+use crate::service::blackouts::Deferral;
+use crate::service::{PendingWrite, TaskService};
+use chrono::{DateTime, Duration, Utc};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+/// How long a run waiting on a dependency waits before it is checked again, unless the
+/// task depended on succeeds first.
+pub const DEPENDENCY_RECHECK_SECS: i64 = 60;
+
+impl TaskService {
+    /// Where a task stands with the task it depends on, judged by their executions: it is
+    /// ready once the task depended on has succeeded since the task last ran.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::Database' for any database operation failures.
+    ///
+    /// Returns `None` if the task depends on nothing.
+    pub async fn dependency(&self, task: &Task) -> Result<Option<Dependency>, AppError> {
+        let Some(task_id) = task.depends_on else {
+            return Ok(None);
+        };
+        let last_succeeded_at = with_retry(&self.config.retry_policy(), || {
+            self.store.last_success(task_id)
+        })
+        .await?
+        .map(|execution| execution.executed_at);
+        let last_run_at = with_retry(&self.config.retry_policy(), || {
+            self.store.get_task_runs(task.id)
+        })
+        .await?
+        .last_run_at;
+
+        let ready = last_succeeded_at
+            .is_some_and(|succeeded| last_run_at.is_none_or(|ran| succeeded > ran));
+        Ok(Some(Dependency {
+            task_id,
+            state: if ready {
+                DependencyState::Ready
+            } else {
+                DependencyState::Waiting
+            },
+            last_succeeded_at,
+        }))
+    }
+
+    /// Evaluates a task's `run_if` condition against the output of the latest successful
+    /// run of the task it depends on. A task that never succeeded meets no condition.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::Database' for any database operation failures.
+    ///
+    /// Returns the output to record for a skipped run, or `None` if the run goes ahead.
+    pub(super) async fn unmet_condition(&self, task: &Task) -> Result<Option<Value>, AppError> {
+        let (Some(condition), Some(task_id)) = (&task.run_if, task.depends_on) else {
+            return Ok(None);
+        };
+        let parent = with_retry(&self.config.retry_policy(), || {
+            self.store.last_success(task_id)
+        })
+        .await?;
+        if parent
+            .as_ref()
+            .is_some_and(|parent| condition.matches(&parent.output))
+        {
+            return Ok(None);
+        }
+
+        Ok(Some(json!({
+            "skipped": "run_if condition not met",
+            "condition": condition,
+            "dependency_execution_id": parent.map(|parent| parent.id),
+        })))
+    }
+
+    /// Checks that a new task's dependency names a live task.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::ValidationError' if the task does not exist or is deleted.
+    /// * Returns 'AppError::Database' for any database operation failures.
+    pub(super) async fn check_dependency_exists(
+        &self,
+        depends_on: Option<Uuid>,
+    ) -> Result<(), AppError> {
+        let Some(task_id) = depends_on else {
+            return Ok(());
+        };
+
+        let task = with_retry(&self.config.retry_policy(), || self.store.get_task(task_id)).await?;
+        if task.is_none_or(|task| task.deleted_at.is_some()) {
+            return Err(AppError::ValidationError(format!(
+                "Task {} does not exist",
+                task_id
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Splits due tasks into those free to run and deferrals for those still waiting on the
+    /// task they depend on. A waiting run is checked again after
+    /// [`DEPENDENCY_RECHECK_SECS`], or as soon as that task succeeds. Retries are not held
+    /// back.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::Database' for any database operation failures.
+    pub(super) async fn hold_for_dependencies(
+        &self,
+        tasks: Vec<Task>,
+        now: DateTime<Utc>,
+    ) -> Result<(Vec<Task>, Vec<Deferral>), AppError> {
+        let mut ready = Vec::with_capacity(tasks.len());
+        let mut waiting = Vec::new();
+        for task in tasks {
+            let held = match self.dependency(&task).await? {
+                Some(dependency) if task.retry_attempt == 0 => {
+                    dependency.state == DependencyState::Waiting
+                }
+                _ => false,
+            };
+            if !held {
+                ready.push(task);
+                continue;
+            }
+
+            waiting.push(Deferral {
+                task_id: task.id,
+                due_at: task.trigger_at,
+                until: now + Duration::seconds(DEPENDENCY_RECHECK_SECS),
+            });
+        }
+        Ok((ready, waiting))
+    }
+
+    /// Brings runs waiting on the tasks that just succeeded forward to `now`.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::Database' for any database operation failures.
+    ///
+    /// Returns the resulting schedule changes.
+    pub(super) async fn release_dependents(
+        &self,
+        writes: &[PendingWrite],
+        now: DateTime<Utc>,
+    ) -> Result<Vec<ScheduleEvent>, AppError> {
+        let mut changes = Vec::new();
+        for write in writes
+            .iter()
+            .filter(|write| write.execution.status == ExecutionStatus::Success)
+        {
+            let dependents = with_retry(&self.config.retry_policy(), || {
+                self.store.get_dependents(write.task.id)
+            })
+            .await?;
+            for dependent in dependents
+                .into_iter()
+                .filter(|task| task.deferred_from.is_some() && task.trigger_at > now)
+            {
+                let rows_affected = with_retry(&self.config.retry_policy(), || {
+                    self.store
+                        .defer_task(dependent.id, now, dependent.trigger_at)
+                })
+                .await?;
+                if rows_affected > 0 {
+                    tracing::info!(task_id = %dependent.id, "Released run waiting on a dependency");
+                    changes.push(ScheduleEvent::Upserted {
+                        id: dependent.id,
+                        trigger_at: now,
+                    });
+                }
+            }
+        }
+        Ok(changes)
+    }
+}
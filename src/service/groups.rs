@@ -0,0 +1,142 @@
+use crate::db::retry::with_retry;
+use crate::domain::{GroupStats, TaskGroup};
+use crate::errors::AppError;
+use crate::service::TaskService;
+use uuid::Uuid;
+
+impl TaskService {
+    /// Creates a task group.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Display name of the group.
+    /// * `description` - Optional free-form description.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::ValidationError' if the name is blank.
+    /// * Returns 'AppError::Database' for any database operation failures.
+    ///
+    /// Returns the new group.
+    pub async fn create_group(
+        &self,
+        name: String,
+        description: Option<String>,
+    ) -> Result<TaskGroup, AppError> {
+        if name.trim().is_empty() {
+            return Err(AppError::ValidationError("Group name is required".into()));
+        }
+
+        let group = TaskGroup {
+            id: Uuid::new_v4(),
+            name,
+            description,
+            paused_at: None,
+            created_at: self.clock.now(),
+        };
+        with_retry(&self.config.retry_policy(), || {
+            self.store.create_group(&group)
+        })
+        .await?;
+
+        Ok(group)
+    }
+
+    /// Lists every task group, ordered by name.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::Database' for any database operation failures.
+    pub async fn list_groups(&self) -> Result<Vec<TaskGroup>, AppError> {
+        let groups = self.store.list_groups().await?;
+        Ok(groups)
+    }
+
+    /// Retrieves a group with its aggregated task and execution counts.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The UUID of the group.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::NotFound' if the group does not exist.
+    /// * Returns 'AppError::Database' for any database operation failures.
+    pub async fn get_group(&self, id: Uuid) -> Result<(TaskGroup, GroupStats), AppError> {
+        let group = self.store.get_group(id).await?.ok_or(AppError::NotFound)?;
+        let stats = self.store.group_stats(id).await?;
+
+        Ok((group, stats))
+    }
+
+    /// Pauses a group: none of its tasks are dispatched until it is resumed. Runs that come
+    /// due in the meantime are caught up on resume.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The UUID of the group.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::NotFound' if the group does not exist.
+    /// * Returns 'AppError::Database' for any database operation failures.
+    pub async fn pause_group(&self, id: Uuid) -> Result<(), AppError> {
+        let now = self.clock.now();
+        self.set_group_paused(id, Some(now)).await?;
+        tracing::info!(group_id = %id, "Group paused");
+        Ok(())
+    }
+
+    /// Resumes a paused group; resuming an active group is a no-op.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The UUID of the group.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::NotFound' if the group does not exist.
+    /// * Returns 'AppError::Database' for any database operation failures.
+    pub async fn resume_group(&self, id: Uuid) -> Result<(), AppError> {
+        self.set_group_paused(id, None).await?;
+        tracing::info!(group_id = %id, "Group resumed");
+        Ok(())
+    }
+
+    async fn set_group_paused(
+        &self,
+        id: Uuid,
+        paused_at: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<(), AppError> {
+        let rows_affected = with_retry(&self.config.retry_policy(), || {
+            self.store.set_group_paused(id, paused_at)
+        })
+        .await?;
+        if rows_affected == 0 {
+            return Err(AppError::NotFound);
+        }
+
+        self.wake_scheduler();
+        Ok(())
+    }
+
+    /// Rejects a `group_id` that does not name an existing group.
+    pub(super) async fn check_group_exists(&self, group_id: Option<Uuid>) -> Result<(), AppError> {
+        let Some(group_id) = group_id else {
+            return Ok(());
+        };
+
+        let group = with_retry(&self.config.retry_policy(), || {
+            self.store.get_group(group_id)
+        })
+        .await?;
+        if group.is_none() {
+            return Err(AppError::ValidationError(format!(
+                "Group {} does not exist",
+                group_id
+            )));
+        }
+
+        Ok(())
+    }
+}
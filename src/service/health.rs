@@ -0,0 +1,42 @@
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Readiness of the service, shared between the scheduler and the API.
+///
+/// The scheduler marks the service degraded when the database becomes unreachable and ready
+/// again once it recovers; the readiness endpoint reports the current state so load balancers
+/// can route around an instance that cannot persist anything.
+#[derive(Debug, Default)]
+pub struct Health {
+    degraded: AtomicBool,
+    reason: Mutex<Option<String>>,
+}
+
+impl Health {
+    pub fn is_ready(&self) -> bool {
+        !self.degraded.load(Ordering::Acquire)
+    }
+
+    /// Why the service is degraded, if it is.
+    pub fn reason(&self) -> Option<String> {
+        self.reason
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub fn mark_degraded(&self, reason: impl Into<String>) {
+        let reason = reason.into();
+        if !self.degraded.swap(true, Ordering::AcqRel) {
+            tracing::error!(%reason, "Entering degraded mode");
+        }
+        *self.reason.lock().unwrap_or_else(|e| e.into_inner()) = Some(reason);
+    }
+
+    pub fn mark_ready(&self) {
+        if self.degraded.swap(false, Ordering::AcqRel) {
+            tracing::info!("Database reachable again, leaving degraded mode");
+        }
+        *self.reason.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
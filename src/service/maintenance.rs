@@ -0,0 +1,84 @@
+use crate::service::TaskService;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::Mutex;
+
+/// Settings of an active maintenance window.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MaintenanceWindow {
+    /// Also stop dispatching due tasks; they run once maintenance ends.
+    pub pause_scheduler: bool,
+    /// Sent as `Retry-After` on rejected mutations.
+    pub retry_after_secs: u64,
+    pub reason: Option<String>,
+    pub since: DateTime<Utc>,
+}
+
+/// Maintenance mode, shared between the API and the scheduler.
+///
+/// While active the API only serves reads, so the database can be migrated or backed up
+/// without writes racing it. The state is per process and is not persisted.
+#[derive(Debug, Default)]
+pub struct Maintenance {
+    window: Mutex<Option<MaintenanceWindow>>,
+}
+
+impl Maintenance {
+    /// The active window, if any.
+    pub fn current(&self) -> Option<MaintenanceWindow> {
+        self.lock().clone()
+    }
+
+    /// True if the scheduler must not dispatch anything.
+    pub fn pauses_dispatch(&self) -> bool {
+        self.lock().as_ref().is_some_and(|w| w.pause_scheduler)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<MaintenanceWindow>> {
+        self.window.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl TaskService {
+    /// Enters maintenance mode, or updates the settings of the active window.
+    ///
+    /// # Arguments
+    ///
+    /// * `pause_scheduler` - Whether dispatching stops as well.
+    /// * `retry_after_secs` - Retry hint for clients whose mutations are rejected.
+    /// * `reason` - Optional note shown to operators.
+    ///
+    /// Returns the active window.
+    pub fn enter_maintenance(
+        &self,
+        pause_scheduler: bool,
+        retry_after_secs: u64,
+        reason: Option<String>,
+    ) -> MaintenanceWindow {
+        let mut window = self.maintenance.lock();
+        let since = window
+            .as_ref()
+            .map_or_else(|| self.clock.now(), |w| w.since);
+        let active = MaintenanceWindow {
+            pause_scheduler,
+            retry_after_secs,
+            reason,
+            since,
+        };
+        *window = Some(active.clone());
+        drop(window);
+
+        tracing::warn!(?active, "Maintenance mode enabled");
+        // Lets a paused scheduler resume, or a running one notice the pause
+        self.wake_scheduler();
+        active
+    }
+
+    /// Leaves maintenance mode; a paused scheduler resumes dispatching immediately.
+    pub fn exit_maintenance(&self) {
+        if self.maintenance.lock().take().is_some() {
+            tracing::warn!("Maintenance mode disabled");
+        }
+        self.wake_scheduler();
+    }
+}
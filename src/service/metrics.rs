@@ -0,0 +1,93 @@
+use super::TaskService;
+use crate::domain::Task;
+use chrono::{DateTime, Utc};
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Upper bounds of the scheduling lag histogram's buckets, in seconds.
+const LAG_BUCKETS_SECS: [f64; 9] = [0.05, 0.1, 0.5, 1.0, 5.0, 15.0, 60.0, 300.0, 900.0];
+
+/// Measurements of how well the scheduler keeps up, shared between the scheduler and the API
+/// and served in the Prometheus text format.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    /// Runs per lag bucket, not cumulative; slower runs than the last bucket only count
+    /// towards `lag_count`.
+    lag_buckets: [AtomicU64; LAG_BUCKETS_SECS.len()],
+    lag_count: AtomicU64,
+    lag_sum_ms: AtomicU64,
+    last_lag_ms: AtomicU64,
+}
+
+impl Metrics {
+    /// Records how late a scheduled run started, in milliseconds.
+    pub fn observe_lag(&self, lag_ms: i64) {
+        let lag_ms = lag_ms.max(0) as u64;
+        let seconds = lag_ms as f64 / 1000.0;
+        if let Some(bucket) = LAG_BUCKETS_SECS.iter().position(|bound| seconds <= *bound) {
+            self.lag_buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        }
+        self.lag_count.fetch_add(1, Ordering::Relaxed);
+        self.lag_sum_ms.fetch_add(lag_ms, Ordering::Relaxed);
+        self.last_lag_ms.store(lag_ms, Ordering::Relaxed);
+    }
+
+    /// The metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut text = String::new();
+        let _ = writeln!(
+            text,
+            "# HELP scheduler_lag_seconds How long after their trigger time scheduled runs started."
+        );
+        let _ = writeln!(text, "# TYPE scheduler_lag_seconds histogram");
+        let mut cumulative = 0;
+        for (bound, count) in LAG_BUCKETS_SECS.iter().zip(&self.lag_buckets) {
+            cumulative += count.load(Ordering::Relaxed);
+            let _ = writeln!(
+                text,
+                "scheduler_lag_seconds_bucket{{le=\"{}\"}} {}",
+                bound, cumulative
+            );
+        }
+        let count = self.lag_count.load(Ordering::Relaxed);
+        let _ = writeln!(
+            text,
+            "scheduler_lag_seconds_bucket{{le=\"+Inf\"}} {}",
+            count
+        );
+        let sum = self.lag_sum_ms.load(Ordering::Relaxed) as f64 / 1000.0;
+        let _ = writeln!(text, "scheduler_lag_seconds_sum {}", sum);
+        let _ = writeln!(text, "scheduler_lag_seconds_count {}", count);
+        let _ = writeln!(
+            text,
+            "# HELP scheduler_last_lag_seconds Lag of the most recently started scheduled run."
+        );
+        let _ = writeln!(text, "# TYPE scheduler_last_lag_seconds gauge");
+        let last = self.last_lag_ms.load(Ordering::Relaxed) as f64 / 1000.0;
+        let _ = writeln!(text, "scheduler_last_lag_seconds {}", last);
+        text
+    }
+}
+
+/// How long after it was due a scheduled run starts, in milliseconds. Runs started early,
+/// e.g. by a clock running ahead, count as on time.
+pub(super) fn scheduling_lag(task: &Task, started_at: DateTime<Utc>) -> i64 {
+    (started_at - task.trigger_at).num_milliseconds().max(0)
+}
+
+impl TaskService {
+    /// Records the lag of a scheduled run that is starting, warning if it is over
+    /// `SCHEDULER_LAG_WARN_SECS`: a sign that the instance cannot keep up with its load.
+    pub(super) fn observe_lag(&self, task: &Task, lag_ms: i64) {
+        self.metrics.observe_lag(lag_ms);
+        let threshold = self.config.lag_warn_threshold_secs;
+        if threshold > 0 && lag_ms > (threshold as i64).saturating_mul(1000) {
+            tracing::warn!(
+                task_id = %task.id,
+                lag_ms,
+                "Run started {}ms after it was due, the scheduler is falling behind",
+                lag_ms
+            );
+        }
+    }
+}
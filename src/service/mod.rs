@@ -1,41 +1,644 @@
-use crate::api::dto::CreateTaskReq;
-use crate::db::queries::TaskRepository;
-use crate::domain::{Execution, ExecutionStatus, Task, TaskType};
+use crate::api::dto::{CloneTaskReq, CreateTaskReq, ReplaceTaskReq, UpdateTaskReq};
+use crate::clock::{Clock, SystemClock};
+use crate::config::Config;
+use crate::db::retry::{is_transient, with_retry};
+use crate::db::store::{Claim, ExecutionWrite, FollowUp, SqliteTaskStore, TaskStore, WriteOutcome};
+use crate::domain::{
+    ApiKey, Execution, ExecutionCursor, ExecutionRetry, ExecutionStatus, ExecutionSummary,
+    FailureKind, IntervalMode, MisfirePolicy, Task, TaskFilter, TaskPatch, TaskRuns, TaskSummary,
+    TaskType,
+};
 use crate::errors::AppError;
-use serde_json::json;
-use sqlx::{SqlitePool, types::Json};
-use tokio::sync::mpsc::Sender;
+use crate::executor::command::COMMAND_TYPE;
+use crate::executor::registry::{self, DEFAULT_TYPE};
+use crate::executor::{
+    CommandExecutor, ExecError, ExecutionContext, Executor, ExecutorRegistry, MiddlewareChain,
+    WebhookExecutor, webhook,
+};
+use crate::fault;
+use crate::notify::Notifier;
+use crate::retention::archive::ExecutionArchive;
+use crate::schedule::{self, HumanSchedule};
+use crate::scheduler::ScheduleEvent;
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use futures_util::{StreamExt, future};
+use serde_json::{Value, json};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc::{self, Sender};
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+use tracing::Instrument;
 use uuid::Uuid;
 
+pub mod anomalies;
+pub mod api_keys;
+pub mod blackouts;
+pub mod cancellation;
+pub mod chaining;
+pub mod dependencies;
+pub mod groups;
+pub mod health;
+pub mod maintenance;
+pub mod metrics;
+pub mod notifications;
+pub mod output;
+pub mod pausing;
+pub mod preview;
+pub mod replays;
+pub mod secrets;
+pub mod signing;
+pub mod snapshots;
+pub mod trigger_links;
+pub mod workflows;
+
 #[cfg(test)]
 mod tests;
 
+pub use health::Health;
+pub use maintenance::Maintenance;
+pub use metrics::Metrics;
+
+/// Default and maximum number of executions returned per page.
+pub const DEFAULT_PAGE_SIZE: i64 = 50;
+pub const MAX_PAGE_SIZE: i64 = 200;
+
+/// Header carrying [`correlation_id`] on outgoing webhook calls.
+pub const CORRELATION_ID_HEADER: &str = "X-Scheduler-Correlation-Id";
+
+/// Header carrying the execution id on outgoing webhook calls.
+pub const EXECUTION_ID_HEADER: &str = "X-Scheduler-Execution-Id";
+
+/// Header carrying, on outgoing webhook calls, the `x-request-id` of the API call that
+/// created the task.
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+/// Identifies a webhook call as `<request id>:<execution id>`, tying it to both the API
+/// request that created the task and the execution record. Tasks created outside the API
+/// carry only the execution id.
+pub fn correlation_id(task: &Task, execution_id: Uuid) -> String {
+    match &task.request_id {
+        Some(request_id) => format!("{}:{}", request_id, execution_id),
+        None => execution_id.to_string(),
+    }
+}
+
+/// Applies payload overrides: fields of an object are merged over an object payload, any
+/// other value replaces the payload outright.
+pub fn merge_payload(mut payload: Value, overrides: Value) -> Value {
+    match (overrides, payload.as_object_mut()) {
+        (Value::Object(overrides), Some(fields)) => {
+            fields.extend(overrides);
+            payload
+        }
+        (replacement, _) => replacement,
+    }
+}
+
+/// Trims a page fetched with one extra row, returning it with the cursor for the next page
+/// if that row was there.
+fn next_page<T>(
+    mut items: Vec<T>,
+    limit: i64,
+    position: impl Fn(&T) -> (DateTime<Utc>, Uuid),
+) -> (Vec<T>, Option<String>) {
+    if items.len() as i64 <= limit {
+        return (items, None);
+    }
+    items.truncate(limit as usize);
+    let cursor = items.last().map(|item| {
+        let (executed_at, id) = position(item);
+        ExecutionCursor { executed_at, id }.encode()
+    });
+    (items, cursor)
+}
+
+/// Parses the `task_type` of a request.
+///
+/// # Errors
+///
+/// * Returns 'AppError::ValidationError' if it names no task type.
+fn parse_task_type(task_type: &str) -> Result<TaskType, AppError> {
+    match task_type {
+        "once" => Ok(TaskType::Once),
+        "interval" => Ok(TaskType::Interval),
+        "cron" => Ok(TaskType::Cron),
+        "rrule" => Ok(TaskType::Rrule),
+        _ => Err(AppError::ValidationError(
+            "Invalid task_type. Use 'once', 'interval', 'cron' or 'rrule'".into(),
+        )),
+    }
+}
+
+/// Checks that a task of `task_type` has exactly the schedule fields its type needs.
+///
+/// # Errors
+///
+/// * Returns 'AppError::IntervalTooSmall' if 'interval_seconds' is less than 1.
+/// * Returns 'AppError::ValidationError' if a field is missing or does not apply to the
+///   type, or the cron expression is not valid.
+fn validate_schedule(
+    task_type: &TaskType,
+    interval_seconds: Option<i64>,
+    cron_expr: Option<&str>,
+) -> Result<(), AppError> {
+    match (task_type, interval_seconds) {
+        // limit to at least 1 second to avoid loops
+        (TaskType::Interval, Some(seconds)) if seconds < 1 => {
+            return Err(AppError::IntervalTooSmall(1));
+        }
+        (TaskType::Interval, None) => {
+            return Err(AppError::ValidationError(
+                "interval_seconds is required for interval tasks".into(),
+            ));
+        }
+        _ => {}
+    }
+    match (task_type, cron_expr) {
+        (TaskType::Cron, Some(expr)) => {
+            schedule::parse_cron(expr).map_err(|issue| AppError::ValidationError(issue.message))?;
+        }
+        (TaskType::Cron, None) => {
+            return Err(AppError::ValidationError(
+                "cron_expr is required for cron tasks".into(),
+            ));
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Fills in the task type, first trigger and schedule fields of a task created from a
+/// human-friendly `schedule`.
+///
+/// # Errors
+///
+/// * Returns 'AppError::ValidationError' as [`resolve_human_schedule`] does.
+fn apply_human_schedule(
+    req: &mut CreateTaskReq,
+    schedule: &str,
+    now: DateTime<Utc>,
+) -> Result<(), AppError> {
+    let (resolved, first_run) = resolve_human_schedule(
+        schedule,
+        &req.task_type,
+        req.trigger_at,
+        req.interval_seconds,
+        req.cron_expr.as_deref(),
+        req.rrule.as_deref(),
+        now,
+    )?;
+    req.trigger_at = Some(first_run);
+    match resolved {
+        HumanSchedule::Once(_) => req.task_type = "once".into(),
+        HumanSchedule::Interval(seconds) => {
+            req.task_type = "interval".into();
+            req.interval_seconds = Some(seconds);
+        }
+        HumanSchedule::Cron(expr) => {
+            req.task_type = "cron".into();
+            req.cron_expr = Some(expr);
+        }
+    }
+    Ok(())
+}
+
+/// Resolves a human-friendly `schedule` against `now`, given the other schedule fields of
+/// the request it came with. Interval tasks first run one interval from `now`.
+///
+/// # Errors
+///
+/// * Returns 'AppError::ValidationError' if the schedule cannot be parsed, its first run is
+///   out of range, or the request also sets a task type, trigger time or schedule field.
+///
+/// Returns what the schedule runs as, and its first run.
+fn resolve_human_schedule(
+    schedule: &str,
+    task_type: &str,
+    trigger_at: Option<DateTime<Utc>>,
+    interval_seconds: Option<i64>,
+    cron_expr: Option<&str>,
+    rrule: Option<&str>,
+    now: DateTime<Utc>,
+) -> Result<(HumanSchedule, DateTime<Utc>), AppError> {
+    if !task_type.is_empty()
+        || trigger_at.is_some()
+        || interval_seconds.is_some()
+        || cron_expr.is_some()
+        || rrule.is_some()
+    {
+        return Err(AppError::ValidationError(
+            "schedule cannot be combined with task_type, trigger_at, interval_seconds, \
+             cron_expr or rrule"
+                .into(),
+        ));
+    }
+    let resolved = schedule::parse_human_schedule(schedule).map_err(AppError::ValidationError)?;
+    let delay = match &resolved {
+        HumanSchedule::Once(delay) => *delay,
+        HumanSchedule::Interval(seconds) => chrono::Duration::seconds(*seconds),
+        HumanSchedule::Cron(_) => chrono::Duration::zero(),
+    };
+    let first_run = now.checked_add_signed(delay).ok_or_else(|| {
+        AppError::ValidationError(format!("Schedule '{}' is too far out", schedule))
+    })?;
+    Ok((resolved, first_run))
+}
+
+/// The first run of a recurrence rule task: the rule's first occurrence in `tz` at or after
+/// the requested time.
+///
+/// # Errors
+///
+/// * Returns 'AppError::ValidationError' if the rule is malformed or has no occurrence.
+fn first_rrule_trigger(rule: &str, at: DateTime<Utc>, tz: Tz) -> Result<DateTime<Utc>, AppError> {
+    schedule::parse_rrule(rule, at, tz)
+        .map_err(|issue| AppError::ValidationError(issue.message))?
+        .next_after(at, true, tz)
+        .ok_or_else(|| {
+            AppError::ValidationError(format!("Recurrence rule '{}' never matches", rule))
+        })
+}
+
+/// The first run of a cron task: the first occurrence in `tz` at or after the requested time.
+///
+/// # Errors
+///
+/// * Returns 'AppError::ValidationError' if the expression never matches.
+fn first_cron_trigger(expr: &str, at: DateTime<Utc>, tz: Tz) -> Result<DateTime<Utc>, AppError> {
+    schedule::next_cron(expr, at, true, tz).ok_or_else(|| {
+        AppError::ValidationError(format!("Cron expression '{}' never matches", expr))
+    })
+}
+
+/// Checks that a retry policy waits between attempts and never shortens its delays.
+///
+/// # Errors
+///
+/// * Returns 'AppError::ValidationError' if the initial delay is below 1 second or the
+///   multiplier is below 1.
+fn validate_retry_policy(retry: &ExecutionRetry) -> Result<(), AppError> {
+    if retry.initial_delay_seconds < 1 {
+        return Err(AppError::ValidationError(
+            "retry_policy.initial_delay_seconds must be at least 1".into(),
+        ));
+    }
+    if !(retry.backoff_multiplier >= 1.0 && retry.backoff_multiplier.is_finite()) {
+        return Err(AppError::ValidationError(
+            "retry_policy.backoff_multiplier must be at least 1".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// What happens to a task after an execution that finished at `now`.
+///
+/// A failed run is retried while the task's retry policy has retries left. Otherwise one-off
+/// tasks are deleted and recurring tasks move to their next regular trigger, or to the next
+/// missed one under [`MisfirePolicy::FireAll`]. Retries and next triggers outside the task's
+/// execution windows move to the next opening.
+fn follow_up(task: &Task, execution: &Execution, now: DateTime<Utc>) -> FollowUp {
+    if execution.status == ExecutionStatus::Failure
+        && let Some(retry) = &task.retry_policy
+    {
+        let attempt = task.retry_attempt + 1;
+        if attempt <= i64::from(retry.max_retries) {
+            let at = task.within_windows(now + retry.delay(attempt as u32));
+            tracing::info!(task_id = %task.id, attempt, retry_at = %at, "Scheduling a retry");
+            return FollowUp::Retry { at, attempt };
+        }
+        if retry.max_retries > 0 {
+            tracing::warn!(
+                task_id = %task.id,
+                retries = retry.max_retries,
+                "Execution failed permanently after exhausting its retries"
+            );
+        }
+    }
+
+    let follow_up = match task.task_type {
+        // For once tasks, delete after execution
+        TaskType::Once => FollowUp::Delete,
+        // For interval tasks, calculate next trigger time
+        TaskType::Interval => match task.interval_seconds {
+            Some(seconds) => {
+                let interval = chrono::Duration::seconds(seconds);
+                let next = task.trigger_at + interval;
+                FollowUp::Reschedule(match (task.interval_mode, task.misfire_policy) {
+                    // Missed runs stay due until each has fired
+                    (_, MisfirePolicy::FireAll) if next <= now => next,
+                    (IntervalMode::FixedRate, _) if next <= now => {
+                        next_in_phase(task, seconds, now)
+                    }
+                    (IntervalMode::FixedRate, _) => next,
+                    (IntervalMode::FixedDelay, _) => now + interval,
+                })
+            }
+            None => FollowUp::Keep,
+        },
+        // For cron and rrule tasks, move to the schedule's next occurrence
+        TaskType::Cron | TaskType::Rrule => match (task.misfire_policy == MisfirePolicy::FireAll)
+            .then(|| task.next_calendar_trigger(task.trigger_at))
+            .flatten()
+            .filter(|missed| *missed <= now)
+            .or_else(|| task.next_calendar_trigger(now))
+        {
+            Some(trigger_at) => FollowUp::Reschedule(trigger_at),
+            None => {
+                tracing::warn!(
+                    task_id = %task.id,
+                    "Task's schedule has no further occurrence; deleting it"
+                );
+                FollowUp::Delete
+            }
+        },
+    };
+
+    match follow_up {
+        FollowUp::Reschedule(next) => {
+            // Outside its execution windows, the run waits for the next opening
+            let next = task.within_windows(next);
+            if has_ended(task, next) {
+                tracing::info!(task_id = %task.id, "Task reached its end condition; completing it");
+                return FollowUp::Delete;
+            }
+            FollowUp::Reschedule(next)
+        }
+        follow_up => follow_up,
+    }
+}
+
+/// Whether a recurring task is done instead of running again at `next`: its end time
+/// comes first, or the run that just finished was its last.
+fn has_ended(task: &Task, next: DateTime<Utc>) -> bool {
+    task.ends_at.is_some_and(|ends_at| next > ends_at)
+        || task
+            .max_executions
+            .is_some_and(|max| task.run_count + 1 >= max)
+}
+
+/// The first trigger of an interval task after `now` that is a whole number of intervals
+/// after its current one.
+fn next_in_phase(task: &Task, interval_seconds: i64, now: DateTime<Utc>) -> DateTime<Utc> {
+    let intervals = (now - task.trigger_at).num_seconds() / interval_seconds + 1;
+    task.trigger_at + chrono::Duration::seconds(intervals * interval_seconds)
+}
+
+/// Who is making an API call, and through which request.
+#[derive(Debug, Clone, Default)]
+pub struct RequestContext {
+    /// ID of the API request, propagated to webhook calls for log correlation.
+    pub request_id: Option<String>,
+    /// The authenticated key, `None` while authentication is disabled.
+    pub api_key: Option<ApiKey>,
+}
+
+impl RequestContext {
+    /// The key that owns tasks created in this context. The bootstrap key is not a stored
+    /// key and owns nothing.
+    pub fn owner(&self) -> Option<Uuid> {
+        self.api_key
+            .as_ref()
+            .map(|key| key.id)
+            .filter(|id| !id.is_nil())
+    }
+}
+
+/// An executed task whose execution record has not been persisted yet.
+struct PendingWrite {
+    task: Task,
+    execution: Execution,
+}
+
 #[derive(Clone)]
 pub struct TaskService {
-    db_pool: SqlitePool,
+    store: Arc<dyn TaskStore>,
+    /// Wake-up signal for the scheduler; the changes themselves travel through the outbox.
     scheduler_tx: Sender<()>,
+    middleware: MiddlewareChain,
+    /// Runs tasks by their payload type.
+    executors: ExecutorRegistry,
+    /// HTTP client of all executions, built from the config.
+    http: reqwest::Client,
+    config: Config,
+    clock: Arc<dyn Clock>,
+    health: Arc<Health>,
+    maintenance: Arc<Maintenance>,
+    metrics: Arc<Metrics>,
+    /// Delivers task notifications; an HTTP notifier built from the config if unset.
+    notifier: Option<Arc<dyn Notifier>>,
+    /// Receives expired executions before pruning deletes them, if the config names one.
+    archive: Option<Arc<ExecutionArchive>>,
+    /// Executed tasks whose results could not be written during a database outage.
+    unflushed: Arc<Mutex<Vec<PendingWrite>>>,
+    /// Cancellation tokens of the executions running in this process.
+    cancellations: Arc<Mutex<HashMap<Uuid, CancellationToken>>>,
+    /// Parent of every execution's cancellation token, cancelled to abort them all at
+    /// shutdown.
+    aborts: CancellationToken,
+    /// Background work that shutdown waits for, such as the scheduler loop, workflow runs
+    /// and notification deliveries.
+    tracker: TaskTracker,
 }
 
 impl TaskService {
     pub fn new(db_pool: SqlitePool, scheduler_tx: Sender<()>) -> Self {
+        Self::with_store(Arc::new(SqliteTaskStore::new(db_pool)), scheduler_tx)
+    }
+
+    /// Creates a service on top of any [`TaskStore`], e.g. an in-memory one in tests.
+    pub fn with_store(store: Arc<dyn TaskStore>, scheduler_tx: Sender<()>) -> Self {
         Self {
-            db_pool,
+            store,
             scheduler_tx,
+            middleware: MiddlewareChain::default(),
+            executors: ExecutorRegistry::default(),
+            http: webhook::http_client(&Config::default()),
+            config: Config::default(),
+            clock: Arc::new(SystemClock),
+            health: Arc::new(Health::default()),
+            maintenance: Arc::new(Maintenance::default()),
+            metrics: Arc::new(Metrics::default()),
+            notifier: None,
+            archive: None,
+            unflushed: Arc::new(Mutex::new(Vec::new())),
+            cancellations: Arc::new(Mutex::new(HashMap::new())),
+            aborts: CancellationToken::new(),
+            tracker: TaskTracker::new(),
+        }
+    }
+
+    /// Sets the runtime configuration used by the service and the scheduler, registering
+    /// the executors it turns on.
+    pub fn with_config(mut self, config: Config) -> Self {
+        self.executors = self
+            .executors
+            .with(DEFAULT_TYPE, WebhookExecutor::from_config(&config));
+        if config.command_executor_enabled {
+            self.executors = self
+                .executors
+                .with(COMMAND_TYPE, CommandExecutor::from_config(&config));
+        }
+        #[cfg(feature = "amqp")]
+        if let Some(executor) = crate::executor::amqp::AmqpExecutor::from_config(&config) {
+            self.executors = self
+                .executors
+                .with(crate::executor::amqp::AMQP_TYPE, executor);
+        }
+        #[cfg(feature = "nats")]
+        if let Some(executor) = crate::executor::nats::NatsExecutor::from_config(&config) {
+            self.executors = self
+                .executors
+                .with(crate::executor::nats::NATS_TYPE, executor);
+        }
+        #[cfg(feature = "redis")]
+        if let Some(executor) = crate::executor::redis::RedisExecutor::from_config(&config) {
+            self.executors = self
+                .executors
+                .with(crate::executor::redis::REDIS_TYPE, executor);
+        }
+        #[cfg(feature = "scripting")]
+        {
+            self.executors = self.executors.with(
+                crate::executor::script::SCRIPT_TYPE,
+                crate::executor::script::ScriptExecutor::from_config(&config),
+            );
+        }
+        #[cfg(feature = "wasm")]
+        match crate::executor::wasm::WasmExecutor::from_config(&config) {
+            Ok(Some(executor)) => {
+                self.executors = self
+                    .executors
+                    .with(crate::executor::wasm::WASM_TYPE, executor);
+            }
+            Ok(None) => {}
+            Err(e) => tracing::error!("WASM plugins are unavailable: {}", e),
+        }
+        self.http = webhook::http_client(&config);
+        self.archive = config
+            .execution_archive
+            .clone()
+            .map(|target| Arc::new(ExecutionArchive::new(target)));
+        self.config = config;
+        self
+    }
+
+    /// Sets the middleware chain wrapped around every executor invocation.
+    pub fn with_middleware(mut self, middleware: MiddlewareChain) -> Self {
+        self.middleware = middleware;
+        self
+    }
+
+    /// Registers an executor for payloads of type `name`, next to the built-in ones.
+    pub fn with_executor(
+        mut self,
+        name: impl Into<String>,
+        executor: impl Executor + 'static,
+    ) -> Self {
+        self.executors = self.executors.with(name, executor);
+        self
+    }
+
+    /// Sets the time source used for execution timestamps and scheduling decisions.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn store(&self) -> &Arc<dyn TaskStore> {
+        &self.store
+    }
+
+    pub fn executors(&self) -> &ExecutorRegistry {
+        &self.executors
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Tracks background work, so [`TaskService::shutdown`] waits for it.
+    pub fn tracker(&self) -> &TaskTracker {
+        &self.tracker
+    }
+
+    pub fn clock(&self) -> &Arc<dyn Clock> {
+        &self.clock
+    }
+
+    pub fn health(&self) -> &Arc<Health> {
+        &self.health
+    }
+
+    pub fn maintenance(&self) -> &Arc<Maintenance> {
+        &self.maintenance
+    }
+
+    pub fn metrics(&self) -> &Arc<Metrics> {
+        &self.metrics
+    }
+
+    /// Wakes the scheduler so it drains the schedule outbox promptly.
+    ///
+    /// The change itself was already committed to the outbox with the mutation, so a lost
+    /// signal only delays it until the scheduler's next wake-up. A full channel means a
+    /// wake-up is already pending. Fault injection can drop the signal at random.
+    fn wake_scheduler(&self) {
+        if fault::roll(self.config.faults.notification_drop_probability) {
+            tracing::debug!("Injecting dropped scheduler wake-up");
+            return;
         }
+
+        let _ = self.scheduler_tx.try_send(());
     }
 
-    pub fn get_pool(&self) -> &SqlitePool {
-        &self.db_pool
+    /// Checks that a payload names a registered executor and that the executor accepts it.
+    fn validate_payload(&self, payload: &Value) -> Result<(), AppError> {
+        self.executors
+            .validate(payload)
+            .map_err(|e: ExecError| AppError::ValidationError(e.to_string()))
     }
 
     pub async fn delete_task(&self, id: Uuid) -> Result<(), AppError> {
-        let repo = TaskRepository::new(&self.db_pool);
+        let rows_affected =
+            with_retry(&self.config.retry_policy(), || self.store.delete_task(id)).await?;
+        if rows_affected == 0 {
+            return Err(AppError::NotFound);
+        }
+
+        self.wake_scheduler();
+
+        Ok(())
+    }
 
-        let rows_affected = repo.delete_task(id).await?;
+    /// Permanently deletes a task and all of its executions, bypassing the recycle bin. With
+    /// `EXECUTION_ARCHIVE_URL` set, the executions are written to the archive first.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The UUID of the task to purge, deleted or not.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::NotFound' if no task with the given ID exists.
+    /// * Returns 'AppError::Database' for any database operation failures.
+    /// * Returns 'AppError::Archive' if the executions cannot be archived; the task is kept.
+    pub async fn purge_task(&self, id: Uuid) -> Result<(), AppError> {
+        if let Some(archive) = &self.archive {
+            self.archive_task_executions(archive, id, self.clock.now())
+                .await?;
+        }
+        let rows_affected =
+            with_retry(&self.config.retry_policy(), || self.store.purge_task(id)).await?;
         if rows_affected == 0 {
             return Err(AppError::NotFound);
         }
 
+        self.wake_scheduler();
+
         Ok(())
     }
 
@@ -44,216 +647,1504 @@ impl TaskService {
     /// # Arguments
     ///
     /// * `req` - A 'CreateTaskReq' containing task details.
+    /// * `ctx` - The calling key, which owns the task, and the request ID, which is
+    ///   propagated to the task's webhook calls for log correlation.
     ///
     /// # Errors
     ///
     /// * Returns 'AppError::ValidationError' if:
     /// * 'task_type' is invalid.
+    /// * 'schedule' cannot be parsed or is combined with explicit schedule fields, or
+    ///   'trigger_at' is missing without it.
     /// * 'Interval' task is missing 'interval_seconds'
-    /// * 'Interval' task has 'interval_seconds' less than 1.
+    /// * 'Cron' task is missing 'cron_expr', or it is not a valid cron expression.
+    /// * a notification route cannot be delivered.
+    /// * 'group_id' does not name an existing group.
+    /// * 'depends_on' or 'next_task_id' does not name a live task.
+    /// * 'run_if' is set without 'depends_on', its path is not a supported JSONPath, or it
+    ///   has no value to compare with.
+    /// * a label key is blank.
+    /// * the payload names no registered executor, or its executor rejects it.
+    /// * the retry policy has a delay below 1 second or a multiplier below 1.
+    /// * a 'Once' task sets a misfire policy other than 'fire_once'.
+    /// * a task other than an 'Interval' one sets an interval mode other than 'fixed_delay'.
+    /// * a 'Once' task sets 'ends_at' or 'max_executions', 'max_executions' is below 1, or
+    ///   'ends_at' is before the first run.
+    /// * an 'Rrule' task is missing 'rrule', another task sets it, or it is not a supported
+    ///   recurrence rule or never matches.
+    /// * a task other than a 'Cron' or 'Rrule' one sets 'timezone' without
+    ///   'execution_windows', or it is not an IANA time zone.
     ///
+    /// * Returns 'AppError::IntervalTooSmall' if an 'Interval' task has 'interval_seconds'
+    ///   less than 1.
+    /// * Returns 'AppError::QuotaExceeded' if the calling key already owns the maximum number
+    ///   of live tasks.
     /// * Returns AppError::Database if insert fails.
-    pub async fn create_task(&self, req: CreateTaskReq) -> Result<Uuid, AppError> {
-        let task_type = match req.task_type.as_str() {
-            "once" => TaskType::Once,
-            "interval" => TaskType::Interval,
-            _ => {
+    ///
+    /// Returns the created task, including its newly generated signing secret.
+    pub async fn create_task(
+        &self,
+        mut req: CreateTaskReq,
+        ctx: RequestContext,
+    ) -> Result<Task, AppError> {
+        if let Some(schedule) = req.schedule.take() {
+            apply_human_schedule(&mut req, &schedule, self.clock.now())?;
+        }
+        let trigger_at = req.trigger_at.ok_or_else(|| {
+            AppError::ValidationError("trigger_at is required unless a schedule is given".into())
+        })?;
+        let task_type = parse_task_type(&req.task_type)?;
+
+        validate_schedule(&task_type, req.interval_seconds, req.cron_expr.as_deref())?;
+
+        for (field, value) in [
+            ("keep_executions", req.keep_executions),
+            ("keep_days", req.keep_days),
+        ] {
+            if value.is_some_and(|v| v < 1) {
+                return Err(AppError::ValidationError(format!(
+                    "{} must be at least 1",
+                    field
+                )));
+            }
+        }
+
+        if let Some(policy) = &req.notifications {
+            notifications::validate_notifications(policy)?;
+        }
+        if let Some(labels) = &req.labels {
+            pausing::validate_labels(labels)?;
+        }
+        if let Some(windows) = &req.blackout_windows {
+            blackouts::validate_blackout_windows(windows)?;
+        }
+        if let Some(retry) = &req.retry_policy {
+            validate_retry_policy(retry)?;
+        }
+        if task_type == TaskType::Once
+            && req
+                .misfire_policy
+                .is_some_and(|policy| policy != MisfirePolicy::FireOnce)
+        {
+            return Err(AppError::ValidationError(
+                "misfire_policy only applies to recurring tasks".into(),
+            ));
+        }
+        if task_type == TaskType::Once && (req.ends_at.is_some() || req.max_executions.is_some()) {
+            return Err(AppError::ValidationError(
+                "ends_at and max_executions only apply to recurring tasks".into(),
+            ));
+        }
+        if req.max_executions.is_some_and(|max| max < 1) {
+            return Err(AppError::ValidationError(
+                "max_executions must be at least 1".into(),
+            ));
+        }
+        if !matches!(task_type, TaskType::Cron | TaskType::Rrule)
+            && req.execution_windows.is_none()
+            && req.timezone.is_some()
+        {
+            return Err(AppError::ValidationError(
+                "timezone only applies to cron and rrule tasks, or tasks with execution_windows"
+                    .into(),
+            ));
+        }
+        match (&task_type, &req.rrule) {
+            (TaskType::Rrule, None) => {
                 return Err(AppError::ValidationError(
-                    "Invalid task_type. Use 'once' or 'interval'".into(),
+                    "rrule is required for rrule tasks".into(),
                 ));
             }
-        };
-
-        if task_type == TaskType::Interval {
-            match req.interval_seconds {
-                Some(seconds) if seconds < 1 => {
-                    // limit to at least 1 second to avoid loops
-                    return Err(AppError::ValidationError(
-                        "interval_seconds must be at least 1 second".into(),
-                    ));
-                }
-                None => {
-                    return Err(AppError::ValidationError(
-                        "interval_seconds is required for interval tasks".into(),
-                    ));
-                }
-                _ => {} // valid
+            (TaskType::Rrule, Some(_)) | (_, None) => {}
+            (_, Some(_)) => {
+                return Err(AppError::ValidationError(
+                    "rrule only applies to rrule tasks".into(),
+                ));
             }
         }
+        let tz = match &req.timezone {
+            Some(name) => schedule::parse_timezone(name).map_err(AppError::ValidationError)?,
+            None => Tz::UTC,
+        };
+        if task_type != TaskType::Interval
+            && req
+                .interval_mode
+                .is_some_and(|mode| mode != IntervalMode::FixedDelay)
+        {
+            return Err(AppError::ValidationError(
+                "interval_mode only applies to interval tasks".into(),
+            ));
+        }
 
         // Map DTO to Domain Entity
         let payload = req.payload.unwrap_or(json!({}));
+        self.validate_payload(&payload)?;
 
         let task = match task_type {
-            TaskType::Once => Task::new_once(req.name, req.trigger_at, payload),
-            TaskType::Interval => Task::new_interval(
-                req.name,
-                req.trigger_at,
-                req.interval_seconds.unwrap(),
-                payload,
-            ),
+            TaskType::Once => Task::new_once(req.name, trigger_at, payload),
+            TaskType::Interval => {
+                Task::new_interval(req.name, trigger_at, req.interval_seconds.unwrap(), payload)
+            }
+            TaskType::Cron => {
+                let expr = req.cron_expr.unwrap();
+                let first = first_cron_trigger(&expr, trigger_at, tz)?;
+                Task::new_cron(req.name, first, expr, payload)
+            }
+            TaskType::Rrule => {
+                let mut rule = req.rrule.unwrap();
+                // Keep the start the rule counts its intervals and defaults from
+                if !rule.contains("DTSTART") {
+                    rule = format!("DTSTART:{}\n{}", trigger_at.format("%Y%m%dT%H%M%SZ"), rule);
+                }
+                let first = first_rrule_trigger(&rule, trigger_at, tz)?;
+                Task::new_rrule(req.name, first, rule, payload)
+            }
+        };
+        // A first run outside the execution windows waits for the next opening
+        let task = match &req.execution_windows {
+            Some(windows) => Task {
+                trigger_at: schedule::next_open(windows, task.trigger_at, tz).ok_or_else(|| {
+                    AppError::ValidationError("execution_windows never open".into())
+                })?,
+                ..task
+            },
+            None => task,
+        };
+        if req.ends_at.is_some_and(|ends_at| ends_at < task.trigger_at) {
+            return Err(AppError::ValidationError(
+                "ends_at must not be before the task's first run".into(),
+            ));
+        }
+        self.check_task_quota(&ctx).await?;
+        self.check_group_exists(req.group_id).await?;
+        self.check_dependency_exists(req.depends_on).await?;
+        self.check_dependency_exists(req.next_task_id).await?;
+        if let Some(condition) = &req.run_if {
+            if req.depends_on.is_none() {
+                return Err(AppError::ValidationError(
+                    "run_if requires depends_on".into(),
+                ));
+            }
+            condition.validate()?;
+        }
+
+        let task = Task {
+            request_id: ctx.request_id.clone(),
+            group_id: req.group_id,
+            labels: req.labels,
+            api_key_id: ctx.owner(),
+            keep_executions: req.keep_executions,
+            keep_days: req.keep_days,
+            notifications: req.notifications,
+            retry_policy: req.retry_policy,
+            priority: req.priority.unwrap_or_default(),
+            misfire_policy: req.misfire_policy.unwrap_or_default(),
+            interval_mode: req.interval_mode.unwrap_or_default(),
+            ends_at: req.ends_at,
+            max_executions: req.max_executions,
+            timezone: req.timezone,
+            blackout_windows: req.blackout_windows,
+            execution_windows: req.execution_windows,
+            depends_on: req.depends_on,
+            next_task_id: req.next_task_id,
+            run_if: req.run_if,
+            signing_secret: Some(signing::generate_secret()),
+            ..task
         };
 
-        // Save to DB
-        let repo = TaskRepository::new(&self.db_pool);
-        repo.create_task(&task).await?;
+        // Save to DB (with the scheduler's outbox entry)
+        with_retry(&self.config.retry_policy(), || {
+            self.store.create_task(&task)
+        })
+        .await?;
 
         // Notify scheduler
-        let _ = self.scheduler_tx.try_send(());
+        self.wake_scheduler();
 
-        Ok(task.id)
+        Ok(task)
     }
 
-    /// Processes a task: executes its logic, records execution, and updates/deletes the task as needed.
+    /// Replaces a task's name, schedule and payload, keeping its ID, type and everything
+    /// else about it. A pending retry is dropped.
     ///
     /// # Arguments
     ///
-    /// * `task` - The Task to be processed.
+    /// * `id` - The UUID of the task.
+    /// * `req` - The task's new definition.
     ///
     /// # Errors
     ///
+    /// * Returns 'AppError::NotFound' if the task does not exist or is deleted.
+    /// * Returns 'AppError::ValidationError' if:
+    /// * an 'Interval' task is missing 'interval_seconds'.
+    /// * a 'Cron' task is missing 'cron_expr', or it is not a valid cron expression.
+    /// * the payload names no registered executor, or its executor rejects it.
+    ///
+    /// * Returns 'AppError::IntervalTooSmall' if 'interval_seconds' is less than 1.
     /// * Returns 'AppError::Database' for any database operation failures.
     ///
-    /// Returns 'Ok(())' even if the task was deleted during processing.
-    pub async fn process_task(&self, task: Task) -> Result<(), AppError> {
-        tracing::info!(
-            task_id = %task.id,
-            name = %task.name,
-            "Processing Task"
-        );
+    /// Returns the updated task.
+    pub async fn update_task(&self, id: Uuid, req: ReplaceTaskReq) -> Result<Task, AppError> {
+        let policy = self.config.retry_policy();
+        let task = with_retry(&policy, || self.store.get_task(id))
+            .await?
+            .filter(|task| task.deleted_at.is_none())
+            .ok_or(AppError::NotFound)?;
 
-        let (output, status) = match self.execute_webhook(&task).await {
-            Ok(val) => (val, ExecutionStatus::Success),
-            Err(e) => (json!({ "error": e.to_string() }), ExecutionStatus::Failure),
+        validate_schedule(
+            &task.task_type,
+            req.interval_seconds,
+            req.cron_expr.as_deref(),
+        )?;
+        let payload = req.payload.unwrap_or(json!({}));
+        self.validate_payload(&payload)?;
+        // Schedule fields of other task types are ignored, as on creation
+        let interval_seconds = req
+            .interval_seconds
+            .filter(|_| task.task_type == TaskType::Interval);
+        let cron_expr = req.cron_expr.filter(|_| task.task_type == TaskType::Cron);
+        let trigger_at = match (&cron_expr, &task.rrule) {
+            (Some(expr), _) => first_cron_trigger(expr, req.trigger_at, task.tz())?,
+            (None, Some(rule)) => first_rrule_trigger(rule, req.trigger_at, task.tz())?,
+            (None, None) => req.trigger_at,
         };
 
-        let mut scheduler_tx = self.db_pool.begin().await?;
-
-        let exec = Execution::new(task.id, output, status);
-
-        let id = exec.id;
-        let task_id = exec.task_id;
-        let executed_at = exec.executed_at;
-        let output = Json(&exec.output);
-        let exec_status = exec.status;
-
-        let db_result = sqlx::query(
-            r#"
-            INSERT INTO executions (id, task_id, executed_at, output, status)
-            VALUES (?1, ?2, ?3, ?4, ?5)
-            "#,
-        )
-        .bind(id)
-        .bind(task_id)
-        .bind(executed_at)
-        .bind(output)
-        .bind(exec_status)
-        .execute(&mut *scheduler_tx)
-        .await;
-
-        match db_result {
-            Ok(_) => match task.task_type {
-                // For once tasks, delete after execution
-                TaskType::Once => {
-                    TaskRepository::delete_task_with_executor(&mut *scheduler_tx, task.id).await?;
-                }
-                // For interval tasks, calculate and update next trigger time
-                TaskType::Interval => {
-                    if let Some(seconds) = task.interval_seconds {
-                        let next_trigger = chrono::Utc::now() + chrono::Duration::seconds(seconds);
-
-                        TaskRepository::update_trigger_with_executor(
-                            &mut *scheduler_tx,
-                            task.id,
-                            next_trigger,
-                        )
-                        .await?;
-                    }
-                }
-            },
-            // Catch foreign key violation if task was deleted during processing here
-            //
-            Err(sqlx::Error::Database(e)) if e.is_foreign_key_violation() => {
-                tracing::warn!("Task {} was deleted during execution.", task.id);
-                scheduler_tx.rollback().await?;
-                return Ok(());
+        let task = Task {
+            name: req.name,
+            trigger_at,
+            interval_seconds,
+            cron_expr,
+            payload,
+            retry_attempt: 0,
+            ..task
+        };
+        let rows_affected = with_retry(&policy, || self.store.update_task(&task)).await?;
+        if rows_affected == 0 {
+            return Err(AppError::NotFound);
+        }
+
+        self.wake_scheduler();
+        tracing::info!(task_id = %id, trigger_at = %task.trigger_at, "Task updated");
+
+        Ok(task)
+    }
+
+    /// Changes some of a task's fields, leaving the rest as they are. A pending retry is
+    /// dropped if the schedule changes. Cron tasks move to the first occurrence of their
+    /// expression at or after the new trigger time, or after now if only the expression
+    /// changes.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The UUID of the task.
+    /// * `req` - The fields to change.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::NotFound' if the task does not exist or is deleted.
+    /// * Returns 'AppError::ValidationError' if:
+    /// * 'interval_seconds' is given for a task that is not an 'Interval' task.
+    /// * 'cron_expr' is given for a task that is not a 'Cron' task, or is not a valid cron
+    ///   expression.
+    /// * the payload names no registered executor, or its executor rejects it.
+    ///
+    /// * Returns 'AppError::IntervalTooSmall' if 'interval_seconds' is less than 1.
+    /// * Returns 'AppError::Database' for any database operation failures.
+    ///
+    /// Returns the updated task.
+    pub async fn patch_task(&self, id: Uuid, req: UpdateTaskReq) -> Result<Task, AppError> {
+        let policy = self.config.retry_policy();
+        let task = with_retry(&policy, || self.store.get_task(id))
+            .await?
+            .filter(|task| task.deleted_at.is_none())
+            .ok_or(AppError::NotFound)?;
+
+        for (field, given, task_type) in [
+            (
+                "interval_seconds",
+                req.interval_seconds.is_some(),
+                TaskType::Interval,
+            ),
+            ("cron_expr", req.cron_expr.is_some(), TaskType::Cron),
+        ] {
+            if given && task.task_type != task_type {
+                return Err(AppError::ValidationError(format!(
+                    "{} does not apply to this task's type",
+                    field
+                )));
             }
+        }
+        validate_schedule(
+            &task.task_type,
+            req.interval_seconds.or(task.interval_seconds),
+            req.cron_expr.as_deref().or(task.cron_expr.as_deref()),
+        )?;
+        if let Some(payload) = &req.payload {
+            self.validate_payload(payload)?;
+        }
 
-            Err(e) => return Err(AppError::Database(e)),
+        let trigger_at = match (&task.task_type, &req.cron_expr, req.trigger_at) {
+            (TaskType::Cron, None, None) => None,
+            (TaskType::Cron, expr, at) => {
+                let expr = expr.as_deref().or(task.cron_expr.as_deref()).unwrap();
+                Some(first_cron_trigger(
+                    expr,
+                    at.unwrap_or(self.clock.now()),
+                    task.tz(),
+                )?)
+            }
+            (TaskType::Rrule, _, Some(at)) => Some(first_rrule_trigger(
+                task.rrule.as_deref().unwrap_or_default(),
+                at,
+                task.tz(),
+            )?),
+            (_, _, at) => at,
+        };
+        let patch = TaskPatch {
+            name: req.name,
+            trigger_at,
+            interval_seconds: req.interval_seconds,
+            cron_expr: req.cron_expr,
+            payload: req.payload,
+        };
+        let rows_affected = with_retry(&policy, || self.store.patch_task(id, &patch)).await?;
+        if rows_affected == 0 {
+            return Err(AppError::NotFound);
         }
 
-        scheduler_tx.commit().await?;
-        tracing::info!("Task processed succesfully!");
+        self.wake_scheduler();
+        tracing::info!(task_id = %id, "Task patched");
 
-        Ok(())
+        Ok(patch.apply(&task))
     }
 
-    /// Executes the HTTP webhook defined in the task payload.
+    /// Creates a copy of an existing task, with optional overrides.
     ///
     /// # Arguments
     ///
-    /// * `task` - The Task containing the webhook details.
+    /// * `id` - The UUID of the task to copy.
+    /// * `req` - Name, trigger time and payload fields to override.
+    /// * `ctx` - The calling key, which owns the new task, and the request ID.
     ///
     /// # Errors
     ///
-    /// * Returns an error string if the HTTP request fails or if required fields are missing.
-    ///
-    /// Returns the HTTP response as JSON on success.
-    async fn execute_webhook(&self, task: &Task) -> Result<serde_json::Value, String> {
-        let url = task
-            .payload
-            .get("url")
-            .and_then(|v| v.as_str())
-            .ok_or("Missing 'url' in payload")?;
-
-        let method = task
-            .payload
-            .get("method")
-            .and_then(|v| v.as_str())
-            .unwrap_or("GET")
-            .to_uppercase();
-
-        let value = json!({});
-        let body = task.payload.get("body").unwrap_or(&value);
-
-        let client = reqwest::Client::builder()
-            .user_agent("TaskScheduler/1.0")
-            .timeout(std::time::Duration::from_secs(10))
-            .build()
-            .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
-
-        let builder = match method.as_str() {
-            "POST" => client.post(url).json(body),
-            "PUT" => client.put(url).json(body),
-            "DELETE" => client.delete(url),
-            _ => client.get(url),
+    /// * Returns 'AppError::NotFound' if the source task does not exist or is deleted.
+    /// * Returns 'AppError::ValidationError' if the merged payload names no registered
+    ///   executor, or its executor rejects it.
+    /// * Returns 'AppError::QuotaExceeded' if the calling key owns too many live tasks.
+    /// * Returns 'AppError::Database' for any database operation failures.
+    ///
+    /// Returns the new task on success. It gets a signing secret of its own.
+    pub async fn clone_task(
+        &self,
+        id: Uuid,
+        req: CloneTaskReq,
+        ctx: RequestContext,
+    ) -> Result<Task, AppError> {
+        let source = self
+            .store
+            .get_task(id)
+            .await?
+            .filter(|task| task.deleted_at.is_none())
+            .ok_or(AppError::NotFound)?;
+
+        self.check_task_quota(&ctx).await?;
+
+        let payload = match req.payload {
+            Some(overrides) => merge_payload(source.payload, overrides),
+            None => source.payload,
+        };
+        self.validate_payload(&payload)?;
+
+        let task = Task {
+            id: Uuid::new_v4(),
+            name: req.name.unwrap_or(source.name),
+            trigger_at: req.trigger_at.unwrap_or(source.trigger_at),
+            payload,
+            deleted_at: None,
+            request_id: ctx.request_id.clone(),
+            api_key_id: ctx.owner(),
+            signing_secret: Some(signing::generate_secret()),
+            previous_signing_secret: None,
+            previous_secret_expires_at: None,
+            paused_at: None,
+            retry_attempt: 0,
+            ..source
         };
 
-        let response = builder
-            .send()
-            .await
-            .map_err(|e| format!("HTTP request failed: {:?}", e))?;
+        with_retry(&self.config.retry_policy(), || {
+            self.store.create_task(&task)
+        })
+        .await?;
+
+        self.wake_scheduler();
+
+        Ok(task)
+    }
 
-        let status = response.status();
-        let text = response.text().await.unwrap_or_default();
+    /// Rejects task creation once the calling key owns `max_tasks_per_key` live tasks.
+    ///
+    /// The count is read before the insert, so concurrent creations by the same key can
+    /// overshoot the cap by a few tasks; it guards against runaway scripts, not races.
+    async fn check_task_quota(&self, ctx: &RequestContext) -> Result<(), AppError> {
+        let (Some(max), Some(owner)) = (self.config.max_tasks_per_key, ctx.owner()) else {
+            return Ok(());
+        };
 
-        if status.is_success() {
-            Ok(json!({ "status": status.as_u16(), "response": text }))
-        } else {
-            Err(format!("HTTP Error {}: {}", status.as_u16(), text))
+        let active = with_retry(&self.config.retry_policy(), || {
+            self.store.count_active_tasks(owner)
+        })
+        .await?;
+        if active >= max {
+            tracing::warn!(api_key_id = %owner, active, max, "Task quota exceeded");
+            return Err(AppError::QuotaExceeded(format!(
+                "API key already owns {} active tasks (limit {}); delete some before creating more",
+                active, max
+            )));
         }
+
+        Ok(())
+    }
+
+    /// Processes a task: executes its logic, records execution, and updates/deletes the task as needed.
+    ///
+    /// # Arguments
+    ///
+    /// * `task` - The Task to be processed.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::Database' for any database operation failures.
+    ///
+    /// Returns 'Ok(())' even if the task was deleted during processing.
+    pub async fn process_task(&self, task: Task) -> Result<(), AppError> {
+        self.process_batch(vec![task]).await?;
+        Ok(())
     }
 
-    /// Lists all tasks in the system.
+    /// Processes a group of due tasks: writes a `running` record for each, runs the executors
+    /// concurrently, then finalizes every execution and schedule update in a single
+    /// transaction.
+    ///
+    /// Grouping the writes keeps high-frequency tasks from serializing on SQLite's single
+    /// writer with one transaction per run. The running records are committed before any
+    /// executor is invoked, so if the process crashes mid-group the attempts are still on
+    /// record; [`TaskService::recover_interrupted`] marks them as interrupted on the next
+    /// start instead of firing the tasks again. Tasks that vanished before their record could
+    /// be written, or that another run has claimed, are not run.
+    ///
+    /// Tasks whose runs were missed under the `skip` misfire policy move on to their next
+    /// trigger, those dispatched inside a blackout window are deferred to its end, and those
+    /// whose dependency has not succeeded since they last ran wait for it, all without
+    /// running. Successful runs release the tasks waiting on them.
+    ///
+    /// # Arguments
+    ///
+    /// * `tasks` - The due tasks to process.
     ///
     /// # Errors
     ///
     /// * Returns 'AppError::Database' for any database operation failures.
     ///
-    /// Returns a vector of Tasks on success.
-    pub async fn list_tasks(&self) -> Result<Vec<Task>, AppError> {
-        let repo = TaskRepository::new(&self.db_pool);
-        let tasks = repo.get_all_tasks().await?;
-        Ok(tasks)
+    /// Returns the resulting schedule changes so the caller can update its trigger cache.
+    pub async fn process_batch(&self, tasks: Vec<Task>) -> Result<Vec<ScheduleEvent>, AppError> {
+        let started_at = self.clock.now();
+        let mut missed = Vec::new();
+        let mut tasks = tasks;
+        tasks.retain(|task| match self.skip_missed_run(task, started_at) {
+            Some(next) => {
+                missed.push((task.id, next));
+                false
+            }
+            None => true,
+        });
+        let mut deferrals = Vec::new();
+        tasks.retain(|task| match self.blackout(task, started_at) {
+            Some(deferral) => {
+                deferrals.push(deferral);
+                false
+            }
+            None => true,
+        });
+        let (tasks, waiting) = self.hold_for_dependencies(tasks, started_at).await?;
+        deferrals.extend(waiting);
+        let mut skipped = self.skip_missed_runs(missed).await?;
+        skipped.extend(self.defer_runs(deferrals).await?);
+
+        let running: Vec<Execution> = tasks
+            .iter()
+            .map(|task| Execution {
+                executed_at: started_at,
+                deferred_from: task.deferred_from,
+                lag_ms: Some(metrics::scheduling_lag(task, started_at)),
+                scheduled_for: Some(task.trigger_at),
+                started_at: Some(started_at),
+                attempt: Some(task.retry_attempt + 1),
+                ..Execution::new(task.id, json!({}), ExecutionStatus::Running)
+            })
+            .collect();
+
+        // Leave evidence of every attempt before it can have side effects
+        let claim = self.claim(started_at);
+        let outcomes = with_retry(&self.config.retry_policy(), || {
+            self.store.begin_executions(&running, &claim)
+        })
+        .await?;
+
+        let mut vanished = Vec::new();
+        let mut runnable = Vec::with_capacity(tasks.len());
+
+        for ((task, running), outcome) in tasks.into_iter().zip(running).zip(outcomes) {
+            match outcome {
+                WriteOutcome::Recorded => {
+                    self.observe_lag(&task, running.lag_ms.unwrap_or_default());
+                    runnable.push((task, running.id, running.lag_ms));
+                }
+                WriteOutcome::TaskMissing => {
+                    tracing::warn!("Task {} was deleted before execution.", task.id);
+                    vanished.push(ScheduleEvent::Removed { id: task.id });
+                }
+                WriteOutcome::AlreadyRunning => {
+                    // Whoever holds the claim reschedules the task when its run finishes
+                    tracing::warn!("Task {} is already running elsewhere.", task.id);
+                    vanished.push(ScheduleEvent::Removed { id: task.id });
+                }
+            }
+        }
+
+        // Executors run side by side, so a slow target only holds up the group's write
+        let claimed: Vec<Uuid> = runnable.iter().map(|(task, _, _)| task.id).collect();
+        let executions = self
+            .renewing_claims(
+                &claimed,
+                future::join_all(runnable.iter().map(|(task, id, _)| self.execute(task, *id))),
+            )
+            .await;
+        let writes: Vec<PendingWrite> = runnable
+            .into_iter()
+            .zip(executions)
+            .map(|((task, _, lag_ms), execution)| PendingWrite {
+                execution: Execution {
+                    deferred_from: task.deferred_from,
+                    lag_ms,
+                    scheduled_for: Some(task.trigger_at),
+                    attempt: Some(task.retry_attempt + 1),
+                    ..execution
+                },
+                task,
+            })
+            .collect();
+
+        vanished.extend(skipped);
+        if writes.is_empty() {
+            return Ok(vanished);
+        }
+
+        let mut changes = match self.record_batch(&writes).await {
+            Ok(changes) => changes,
+            Err(AppError::Database(e)) if is_transient(&e) => {
+                // Keep the results so the tasks are not executed a second time
+                tracing::warn!(count = writes.len(), "Buffering execution results: {}", e);
+                self.lock_unflushed().extend(writes);
+                return Err(AppError::Database(e));
+            }
+            Err(e) => return Err(e),
+        };
+        tracing::info!(count = writes.len(), "Tasks processed succesfully!");
+        self.notify_outcomes(&writes, Some(started_at)).await;
+        changes.extend(self.release_dependents(&writes, self.clock.now()).await?);
+        self.start_next_tasks(&writes);
+
+        changes.extend(vanished);
+        Ok(changes)
+    }
+
+    /// Runs a live task immediately, outside its schedule (see [`TaskService::run_now`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The UUID of the task.
+    /// * `reset_cadence` - Restart an interval task's cadence from this run.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::NotFound' if the task does not exist or is deleted.
+    /// * Returns 'AppError::ValidationError' if `reset_cadence` is set for a one-off task.
+    /// * Returns 'AppError::Conflict' if the task is already running.
+    /// * Returns 'AppError::Database' for any database operation failures.
+    ///
+    /// Returns the finished execution.
+    pub async fn run_task(&self, id: Uuid, reset_cadence: bool) -> Result<Execution, AppError> {
+        let task = with_retry(&self.config.retry_policy(), || self.store.get_task(id))
+            .await?
+            .filter(|task| task.deleted_at.is_none())
+            .ok_or(AppError::NotFound)?;
+
+        self.run_now(&task, reset_cadence).await
+    }
+
+    /// Runs a task immediately, outside its schedule. The run is recorded like any other
+    /// execution. The task's schedule stays as it is unless `reset_cadence` is set, in which
+    /// case an interval task's next run is one interval after this one.
+    ///
+    /// # Arguments
+    ///
+    /// * `task` - The task to run, possibly with a modified payload.
+    /// * `reset_cadence` - Restart the task's cadence from this run.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::NotFound' if the task was deleted.
+    /// * Returns 'AppError::ValidationError' if `reset_cadence` is set for a one-off task.
+    /// * Returns 'AppError::Conflict' if the task is already running.
+    /// * Returns 'AppError::Database' for any database operation failures.
+    ///
+    /// Returns the finished execution.
+    pub async fn run_now(&self, task: &Task, reset_cadence: bool) -> Result<Execution, AppError> {
+        let interval = match (reset_cadence, task.interval_seconds) {
+            (false, _) => None,
+            (true, Some(seconds)) if task.task_type == TaskType::Interval => Some(seconds),
+            (true, _) => {
+                return Err(AppError::ValidationError(
+                    "Only interval tasks have a cadence to reset".into(),
+                ));
+            }
+        };
+
+        let execution = self.run_unscheduled(task, None).await?;
+
+        if let Some(seconds) = interval {
+            let next = self.clock.now() + chrono::Duration::seconds(seconds);
+            with_retry(&self.config.retry_policy(), || {
+                self.store.reschedule_task(task.id, next)
+            })
+            .await?;
+            self.wake_scheduler();
+        }
+
+        let write = PendingWrite {
+            task: task.clone(),
+            execution,
+        };
+        self.notify_outcomes(std::slice::from_ref(&write), None)
+            .await;
+        self.start_next_tasks(std::slice::from_ref(&write));
+
+        Ok(write.execution)
+    }
+
+    /// Where a due task moves to instead of running, if it is overdue by more than the
+    /// missed-run grace period and its misfire policy skips such runs: its first occurrence
+    /// after `now`. Interval tasks keep their phase.
+    fn skip_missed_run(&self, task: &Task, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let grace = chrono::Duration::seconds(self.config.missed_run_grace_secs as i64);
+        if task.misfire_policy != MisfirePolicy::Skip || now - task.trigger_at <= grace {
+            return None;
+        }
+        let next = match (&task.task_type, task.interval_seconds) {
+            (TaskType::Interval, Some(seconds)) => Some(next_in_phase(task, seconds, now)),
+            (TaskType::Cron | TaskType::Rrule, _) => task.next_calendar_trigger(now),
+            _ => None,
+        };
+        next.map(|next| task.within_windows(next))
+    }
+
+    /// Moves tasks whose runs were missed to their next trigger without running them.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::Database' for any database operation failures.
+    ///
+    /// Returns the resulting schedule changes.
+    async fn skip_missed_runs(
+        &self,
+        missed: Vec<(Uuid, DateTime<Utc>)>,
+    ) -> Result<Vec<ScheduleEvent>, AppError> {
+        let mut changes = Vec::with_capacity(missed.len());
+        for (id, next) in missed {
+            let rows_affected = with_retry(&self.config.retry_policy(), || {
+                self.store.reschedule_task(id, next)
+            })
+            .await?;
+
+            tracing::info!(task_id = %id, %next, "Skipped missed runs");
+            changes.push(if rows_affected == 0 {
+                ScheduleEvent::Removed { id }
+            } else {
+                ScheduleEvent::Upserted {
+                    id,
+                    trigger_at: next,
+                }
+            });
+        }
+        Ok(changes)
+    }
+
+    /// This instance's claim on the tasks it starts at `now`, see `CLAIM_TIMEOUT_SECS`.
+    fn claim(&self, now: DateTime<Utc>) -> Claim {
+        Claim {
+            owner: self.config.instance_id.clone(),
+            expires_at: now + chrono::Duration::seconds(self.config.claim_timeout_secs as i64),
+        }
+    }
+
+    /// Drives `work` to completion, renewing this instance's claims on `task_ids` every
+    /// third of `CLAIM_TIMEOUT_SECS` so long runs are not reclaimed by other instances.
+    async fn renewing_claims<F: Future>(&self, task_ids: &[Uuid], work: F) -> F::Output {
+        let every = chrono::Duration::seconds(self.config.claim_timeout_secs as i64) / 3;
+        tokio::pin!(work);
+        loop {
+            let renew_at = self.clock.now() + every;
+            tokio::select! {
+                output = &mut work => return output,
+                _ = self.clock.sleep_until(renew_at) => {
+                    let claim = self.claim(self.clock.now());
+                    if let Err(e) = self.store.renew_claims(task_ids, &claim).await {
+                        tracing::warn!("Failed to renew task claims: {:?}", e);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Claims a task, records a `running` execution for it, runs it and records the
+    /// outcome, leaving the task's schedule alone. Notifications are up to the caller.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::Conflict' if the task is already running.
+    /// * Returns 'AppError::NotFound' if the task row is gone.
+    /// * Returns 'AppError::Database' for any database operation failures.
+    async fn run_unscheduled(
+        &self,
+        task: &Task,
+        replay_of: Option<Uuid>,
+    ) -> Result<Execution, AppError> {
+        let policy = self.config.retry_policy();
+        let now = self.clock.now();
+        let running = Execution {
+            executed_at: now,
+            started_at: Some(now),
+            attempt: Some(1),
+            replay_of,
+            ..Execution::new(task.id, json!({}), ExecutionStatus::Running)
+        };
+
+        let claim = self.claim(running.executed_at);
+        let outcomes = with_retry(&policy, || {
+            self.store
+                .begin_executions(std::slice::from_ref(&running), &claim)
+        })
+        .await?;
+        match outcomes.first() {
+            Some(WriteOutcome::Recorded) => {}
+            Some(WriteOutcome::AlreadyRunning) => {
+                return Err(AppError::Conflict("Task is already running".into()));
+            }
+            _ => return Err(AppError::NotFound),
+        }
+
+        let execution = Execution {
+            replay_of,
+            attempt: Some(1),
+            ..self
+                .renewing_claims(&[task.id], self.execute(task, running.id))
+                .await
+        };
+        let write = ExecutionWrite {
+            execution: execution.clone(),
+            follow_up: FollowUp::Keep,
+        };
+        let outcomes = with_retry(&policy, || {
+            self.store.record_executions(std::slice::from_ref(&write))
+        })
+        .await?;
+        if outcomes.first() != Some(&WriteOutcome::Recorded) {
+            tracing::warn!("Task {} was deleted during execution.", task.id);
+            return Err(AppError::NotFound);
+        }
+
+        Ok(execution)
+    }
+
+    /// Skips the next run of a recurring task by moving its trigger one interval later, or to
+    /// the cron expression's following occurrence, without executing it.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The UUID of the task.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::NotFound' if the task does not exist or is deleted.
+    /// * Returns 'AppError::ValidationError' if the task is a one-off task.
+    /// * Returns 'AppError::Database' for any database operation failures.
+    ///
+    /// Returns the task with its new trigger time.
+    pub async fn skip_next(&self, id: Uuid) -> Result<Task, AppError> {
+        let policy = self.config.retry_policy();
+        let task = with_retry(&policy, || self.store.get_task(id))
+            .await?
+            .filter(|task| task.deleted_at.is_none())
+            .ok_or(AppError::NotFound)?;
+        let trigger_at = match (&task.task_type, task.interval_seconds) {
+            (TaskType::Interval, Some(seconds)) => {
+                Some(task.trigger_at + chrono::Duration::seconds(seconds))
+            }
+            (TaskType::Cron | TaskType::Rrule, _) => task.next_calendar_trigger(task.trigger_at),
+            _ => None,
+        }
+        .map(|next| task.within_windows(next));
+        let Some(trigger_at) = trigger_at else {
+            return Err(AppError::ValidationError(
+                "Only recurring tasks have a next run to skip".into(),
+            ));
+        };
+
+        let rows_affected =
+            with_retry(&policy, || self.store.reschedule_task(id, trigger_at)).await?;
+        if rows_affected == 0 {
+            return Err(AppError::NotFound);
+        }
+
+        self.wake_scheduler();
+        tracing::info!(task_id = %id, %trigger_at, "Skipped the next run");
+
+        Ok(Task { trigger_at, ..task })
+    }
+
+    /// Finalizes executions left `running` by a previous process of this instance, or by
+    /// any instance whose claim has expired, as interrupted.
+    ///
+    /// The outcome of such an attempt is unknown, so it counts as the task's run: one-off
+    /// tasks are deleted and interval tasks move to their next trigger, rather than firing
+    /// again on restart. Must be called before the scheduler dispatches anything.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::Database' for any database operation failures.
+    ///
+    /// Returns the resulting schedule changes.
+    pub async fn recover_interrupted(&self) -> Result<Vec<ScheduleEvent>, AppError> {
+        self.finalize_orphans(Some(&self.config.instance_id)).await
+    }
+
+    /// Finalizes executions of other instances whose claims expired as interrupted, like
+    /// [`TaskService::recover_interrupted`] does on start, so the tasks of a crashed
+    /// instance are picked up again.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::Database' for any database operation failures.
+    ///
+    /// Returns the resulting schedule changes.
+    pub async fn reclaim_expired(&self) -> Result<Vec<ScheduleEvent>, AppError> {
+        self.finalize_orphans(None).await
+    }
+
+    async fn finalize_orphans(&self, owner: Option<&str>) -> Result<Vec<ScheduleEvent>, AppError> {
+        let policy = self.config.retry_policy();
+        let now = self.clock.now();
+        let running = with_retry(&policy, || self.store.orphaned_executions(owner, now)).await?;
+
+        let mut writes = Vec::with_capacity(running.len());
+        for execution in running {
+            let Some(task) = with_retry(&policy, || self.store.get_task(execution.task_id)).await?
+            else {
+                continue;
+            };
+
+            tracing::warn!(
+                task_id = %task.id,
+                execution_id = %execution.id,
+                "Execution was interrupted, not running it again"
+            );
+            writes.push(PendingWrite {
+                task,
+                execution: Execution {
+                    output: json!({ "error": "Interrupted before completion" }),
+                    status: ExecutionStatus::Interrupted,
+                    ..execution
+                },
+            });
+        }
+
+        if writes.is_empty() {
+            return Ok(Vec::new());
+        }
+        let changes = self.record_batch(&writes).await?;
+        self.notify_outcomes(&writes, None).await;
+
+        Ok(changes)
+    }
+
+    /// Deletes finished executions that fall outside their task's retention settings, or
+    /// older than `EXECUTION_RETENTION_DAYS` for tasks without `keep_days`.
+    ///
+    /// Executions are deleted oldest first in batches of `PRUNE_BATCH_SIZE`, each its own
+    /// statement, until none are left to prune. With `EXECUTION_ARCHIVE_URL` set, each batch
+    /// is written to the archive first and only deleted once the write succeeded.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::Database' for any database operation failures. Batches deleted
+    ///   before the failure stay deleted.
+    /// * Returns 'AppError::Archive' if a batch cannot be archived; it stays in the database
+    ///   for the next pass.
+    ///
+    /// Returns the number of executions deleted.
+    pub async fn prune_executions(&self) -> Result<u64, AppError> {
+        let now = self.clock.now();
+        let default_keep_days = self.config.execution_retention_days;
+        let batch_size = self.config.prune_batch_size;
+        let mut pruned = 0;
+        loop {
+            let deleted = match &self.archive {
+                Some(archive) => {
+                    self.archive_expired(archive, now, default_keep_days, batch_size)
+                        .await?
+                }
+                None => {
+                    with_retry(&self.config.retry_policy(), || {
+                        self.store
+                            .prune_executions(now, default_keep_days, batch_size)
+                    })
+                    .await?
+                }
+            };
+            pruned += deleted;
+            if deleted < batch_size as u64 {
+                return Ok(pruned);
+            }
+        }
+    }
+
+    /// Archives one batch of expired executions, then deletes it. Returns the batch's size.
+    async fn archive_expired(
+        &self,
+        archive: &ExecutionArchive,
+        now: DateTime<Utc>,
+        default_keep_days: Option<i64>,
+        batch_size: i64,
+    ) -> Result<u64, AppError> {
+        let expired = with_retry(&self.config.retry_policy(), || {
+            self.store
+                .expired_executions(now, default_keep_days, batch_size)
+        })
+        .await?;
+        if expired.is_empty() {
+            return Ok(0);
+        }
+
+        let location = archive.write(&expired, now).await?;
+        tracing::info!(archived = expired.len(), %location, "Archived expired executions");
+        let ids: Vec<Uuid> = expired.iter().map(|e| e.id).collect();
+        with_retry(&self.config.retry_policy(), || {
+            self.store.delete_executions(&ids)
+        })
+        .await?;
+
+        Ok(expired.len() as u64)
+    }
+
+    /// Permanently deletes tasks soft deleted more than `DELETED_TASK_RETENTION_DAYS` ago,
+    /// with their executions, so the recycle bin does not grow without bound. Tasks are
+    /// purged longest deleted first in batches of `PRUNE_BATCH_SIZE`. With
+    /// `EXECUTION_ARCHIVE_URL` set, each task's executions are archived before it is purged.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::Database' for any database operation failures. Batches purged
+    ///   before the failure stay purged.
+    /// * Returns 'AppError::Archive' if a task's executions cannot be archived; it stays in
+    ///   the recycle bin for the next pass.
+    ///
+    /// Returns the number of tasks purged; none if no retention is configured.
+    pub async fn purge_deleted_tasks(&self) -> Result<u64, AppError> {
+        let Some(days) = self.config.deleted_task_retention_days else {
+            return Ok(0);
+        };
+        let now = self.clock.now();
+        let cutoff = now - chrono::Duration::days(days);
+        let batch_size = self.config.prune_batch_size;
+        let mut purged = 0;
+        loop {
+            let deleted = match &self.archive {
+                Some(archive) => {
+                    self.archive_deleted(archive, now, cutoff, batch_size)
+                        .await?
+                }
+                None => {
+                    with_retry(&self.config.retry_policy(), || {
+                        self.store.purge_deleted_tasks(cutoff, batch_size)
+                    })
+                    .await?
+                }
+            };
+            purged += deleted;
+            if deleted < batch_size as u64 {
+                return Ok(purged);
+            }
+        }
+    }
+
+    /// Archives the executions of one batch of long deleted tasks, purging each task once
+    /// its executions are archived. Returns the batch's size.
+    async fn archive_deleted(
+        &self,
+        archive: &ExecutionArchive,
+        now: DateTime<Utc>,
+        cutoff: DateTime<Utc>,
+        batch_size: i64,
+    ) -> Result<u64, AppError> {
+        let ids = with_retry(&self.config.retry_policy(), || {
+            self.store.deleted_task_ids(cutoff, batch_size)
+        })
+        .await?;
+        for &id in &ids {
+            self.archive_task_executions(archive, id, now).await?;
+            with_retry(&self.config.retry_policy(), || self.store.purge_task(id)).await?;
+        }
+
+        Ok(ids.len() as u64)
+    }
+
+    /// Writes every execution of a task to the archive, one file per `PRUNE_BATCH_SIZE`
+    /// executions, newest first.
+    async fn archive_task_executions(
+        &self,
+        archive: &ExecutionArchive,
+        task_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<(), AppError> {
+        let batch_size = self.config.prune_batch_size;
+        let mut after = None;
+        loop {
+            let page = with_retry(&self.config.retry_policy(), || {
+                self.store
+                    .list_execution_records(task_id, after.as_ref(), batch_size)
+            })
+            .await?;
+            let Some(last) = page.last() else {
+                return Ok(());
+            };
+            let location = archive.write(&page, now).await?;
+            tracing::info!(archived = page.len(), %task_id, %location, "Archived purged executions");
+            if page.len() < batch_size as usize {
+                return Ok(());
+            }
+            after = Some(ExecutionCursor {
+                executed_at: last.executed_at,
+                id: last.id,
+            });
+        }
+    }
+
+    /// True while execution results from a database outage are waiting to be written.
+    pub fn has_unflushed(&self) -> bool {
+        !self.lock_unflushed().is_empty()
+    }
+
+    /// Writes execution results buffered during a database outage.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::Database' if the database is still unavailable; the results stay
+    ///   buffered for the next attempt. Results the database rejects outright are discarded.
+    ///
+    /// Returns the resulting schedule changes on success.
+    pub async fn flush_unflushed(&self) -> Result<Vec<ScheduleEvent>, AppError> {
+        let writes = std::mem::take(&mut *self.lock_unflushed());
+        if writes.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        match self.record_batch(&writes).await {
+            Ok(changes) => {
+                tracing::info!(count = writes.len(), "Flushed buffered execution results");
+                self.notify_outcomes(&writes, None).await;
+                self.start_next_tasks(&writes);
+                Ok(changes)
+            }
+            Err(AppError::Database(e)) if is_transient(&e) => {
+                let mut unflushed = self.lock_unflushed();
+                let newer = std::mem::replace(&mut *unflushed, writes);
+                unflushed.extend(newer);
+                Err(AppError::Database(e))
+            }
+            Err(e) => {
+                // The database is back but rejects the writes; the tasks stay due and re-fire
+                tracing::error!(count = writes.len(), "Discarding buffered results: {:?}", e);
+                Err(e)
+            }
+        }
+    }
+
+    fn lock_unflushed(&self) -> std::sync::MutexGuard<'_, Vec<PendingWrite>> {
+        self.unflushed.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Runs a task as the execution `id`, until it finishes, is cancelled (see
+    /// [`TaskService::cancel_execution`]) or is aborted at shutdown (see
+    /// [`TaskService::abort_executions`]).
+    ///
+    /// The run is traced as an `execution` span, which records its duration and outcome once
+    /// it finishes and is exported along with the rest when OTLP export is configured.
+    async fn execute(&self, task: &Task, id: Uuid) -> Execution {
+        let span = tracing::info_span!(
+            "execution",
+            task_id = %task.id,
+            execution_id = %id,
+            executor = registry::payload_type(&task.payload),
+            duration_ms = tracing::field::Empty,
+            outcome = tracing::field::Empty,
+        );
+        let token = self.track_execution(id);
+        let started_at = self.clock.now();
+        let execution = tokio::select! {
+            execution = self.invoke(task, id).instrument(span.clone()) => execution,
+            _ = token.cancelled() => self.cancelled_execution(task, id, started_at),
+        };
+        self.untrack_execution(id);
+        span.record(
+            "duration_ms",
+            (execution.executed_at - started_at).num_milliseconds(),
+        );
+        span.record("outcome", execution.status.as_str());
+        execution
+    }
+
+    /// Runs the middleware chain and executor for a task, producing the final record for the
+    /// execution `id`. A run whose `run_if` condition is not met is recorded as skipped
+    /// without running.
+    async fn invoke(&self, task: &Task, id: Uuid) -> Execution {
+        tracing::info!(
+            task_id = %task.id,
+            name = %task.name,
+            "Processing Task"
+        );
+
+        let started_at = self.clock.now();
+        match self.unmet_condition(task).await {
+            Ok(Some(output)) => {
+                tracing::info!(task_id = %task.id, "Skipping run, its condition is not met");
+                return Execution {
+                    id,
+                    executed_at: started_at,
+                    payload: Some(task.payload.clone()),
+                    duration_ms: Some(0),
+                    started_at: Some(started_at),
+                    finished_at: Some(started_at),
+                    ..Execution::new(task.id, output, ExecutionStatus::Skipped)
+                };
+            }
+            Ok(None) => {}
+            Err(e) => {
+                return Execution {
+                    id,
+                    executed_at: started_at,
+                    payload: Some(task.payload.clone()),
+                    started_at: Some(started_at),
+                    finished_at: Some(started_at),
+                    failure_kind: Some(FailureKind::Other),
+                    ..Execution::new(
+                        task.id,
+                        json!({ "error": e.to_string() }),
+                        ExecutionStatus::Failure,
+                    )
+                };
+            }
+        }
+
+        let mut exec_task = task.clone();
+        let mut reason = None;
+        let mut kind = FailureKind::Other;
+        let mut partial = None;
+        let mut secrets = Vec::new();
+        let mut outcome = match self.middleware.before(&mut exec_task).await {
+            Ok(()) => match self.resolve_secrets(&mut exec_task.payload).await {
+                Ok(values) => {
+                    secrets = values;
+                    let ctx = ExecutionContext {
+                        execution_id: id,
+                        started_at,
+                        config: &self.config,
+                        http: &self.http,
+                        secrets: &secrets,
+                        executors: &self.executors,
+                    };
+                    self.executors.execute(&exec_task, &ctx).await.map_err(|e| {
+                        reason = e.reason();
+                        kind = e.kind();
+                        partial = e.output().cloned();
+                        e.to_string()
+                    })
+                }
+                Err(e) => Err(e),
+            },
+            Err(e) => Err(e),
+        };
+        self.middleware.after(&exec_task, &mut outcome).await;
+
+        let (mut output, status, failure_kind) = match outcome {
+            Ok(val) => (val, ExecutionStatus::Success, None),
+            Err(e) => {
+                let mut output = json!({ "error": e });
+                if let Some(reason) = reason {
+                    output["reason"] = json!(reason);
+                }
+                if let Some(Value::Object(fields)) = partial {
+                    fields
+                        .into_iter()
+                        .for_each(|(key, value)| output[key] = value);
+                }
+                (output, ExecutionStatus::Failure, Some(kind))
+            }
+        };
+        crate::secrets::scrub(&mut output, &secrets);
+        if let Some(bytes) = output::limit_output(&mut output, self.config.max_output_bytes) {
+            tracing::warn!(
+                task_id = %task.id,
+                bytes,
+                "Truncated an output larger than MAX_OUTPUT_BYTES"
+            );
+        }
+
+        let finished_at = self.clock.now();
+        Execution {
+            id,
+            executed_at: finished_at,
+            payload: Some(task.payload.clone()),
+            duration_ms: Some((finished_at - started_at).num_milliseconds()),
+            started_at: Some(started_at),
+            finished_at: Some(finished_at),
+            failure_kind,
+            ..Execution::new(task.id, output, status)
+        }
+    }
+
+    /// Persists executions and follow-up schedule changes in one transaction.
+    ///
+    /// A task deleted during execution is skipped without rolling back the rest of the group.
+    /// The whole group is retried if the database reports itself as busy.
+    async fn record_batch(&self, writes: &[PendingWrite]) -> Result<Vec<ScheduleEvent>, AppError> {
+        let now = self.clock.now();
+        let execution_writes: Vec<ExecutionWrite> = writes
+            .iter()
+            .map(|write| ExecutionWrite {
+                execution: write.execution.clone(),
+                follow_up: follow_up(&write.task, &write.execution, now),
+            })
+            .collect();
+
+        let outcomes = with_retry(&self.config.retry_policy(), || {
+            self.store.record_executions(&execution_writes)
+        })
+        .await?;
+
+        let changes = execution_writes
+            .iter()
+            .zip(outcomes)
+            .filter_map(|(write, outcome)| {
+                let id = write.execution.task_id;
+                match (outcome, &write.follow_up) {
+                    (WriteOutcome::TaskMissing, _) => {
+                        tracing::warn!("Task {} was deleted during execution.", id);
+                        Some(ScheduleEvent::Removed { id })
+                    }
+                    (WriteOutcome::Recorded, FollowUp::Delete) => {
+                        Some(ScheduleEvent::Removed { id })
+                    }
+                    (WriteOutcome::Recorded, FollowUp::Reschedule(trigger_at))
+                    | (WriteOutcome::Recorded, FollowUp::Retry { at: trigger_at, .. }) => {
+                        Some(ScheduleEvent::Upserted {
+                            id,
+                            trigger_at: *trigger_at,
+                        })
+                    }
+                    (WriteOutcome::Recorded, FollowUp::Keep) => None,
+                    // Only reported when a run begins
+                    (WriteOutcome::AlreadyRunning, _) => None,
+                }
+            })
+            .collect();
+
+        Ok(changes)
+    }
+
+    /// Fetches a task, including one in the recycle bin, with its run times.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The UUID of the task.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::NotFound' if the task does not exist.
+    /// * Returns 'AppError::Database' for any database operation failures.
+    pub async fn get_task(&self, id: Uuid) -> Result<(Task, TaskRuns), AppError> {
+        let task = self.store.get_task(id).await?.ok_or(AppError::NotFound)?;
+        let runs = self.store.get_task_runs(id).await?;
+
+        Ok((task, runs))
+    }
+
+    /// Fetches a single execution record.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The UUID of the execution.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::NotFound' if the execution does not exist.
+    /// * Returns 'AppError::Database' for any database operation failures.
+    pub async fn get_execution(&self, id: Uuid) -> Result<Execution, AppError> {
+        let execution = with_retry(&self.config.retry_policy(), || self.store.get_execution(id))
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+        Ok(execution)
+    }
+
+    /// Lists tasks, optionally only active ones or only those in the recycle bin, and
+    /// optionally only those of one group.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - Lifecycle state and group to restrict the listing to.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::Database' for any database operation failures.
+    ///
+    /// Returns a vector of task summaries (without payloads) on success.
+    pub async fn list_tasks(&self, filter: TaskFilter) -> Result<Vec<TaskSummary>, AppError> {
+        let tasks = self.store.get_task_summaries(&filter).await?;
+        Ok(tasks)
+    }
+
+    /// Lists a task's executions newest first, one page at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `task_id` - The UUID of the task.
+    /// * `limit` - Page size (defaults to `DEFAULT_PAGE_SIZE`, capped at `MAX_PAGE_SIZE`).
+    /// * `cursor` - Opaque cursor returned by a previous call.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::NotFound' if the task does not exist.
+    /// * Returns 'AppError::ValidationError' if the cursor or limit is invalid.
+    /// * Returns 'AppError::Database' for any database operation failures.
+    ///
+    /// Returns the page and the cursor for the next page, if there is one.
+    pub async fn list_executions(
+        &self,
+        task_id: Uuid,
+        limit: Option<i64>,
+        cursor: Option<&str>,
+    ) -> Result<(Vec<ExecutionSummary>, Option<String>), AppError> {
+        let (limit, after) = self.page_bounds(task_id, limit, cursor).await?;
+
+        // Fetch one extra row to learn whether another page follows
+        let executions = self
+            .store
+            .list_executions(task_id, after.as_ref(), limit + 1)
+            .await?;
+
+        Ok(next_page(executions, limit, |e| (e.executed_at, e.id)))
+    }
+
+    /// Lists a task's full execution records, with their output, newest first, one page at
+    /// a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `task_id` - The UUID of the task.
+    /// * `limit` - Page size (defaults to `DEFAULT_PAGE_SIZE`, capped at `MAX_PAGE_SIZE`).
+    /// * `cursor` - Opaque cursor returned by a previous call to either listing.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::NotFound' if the task does not exist.
+    /// * Returns 'AppError::ValidationError' if the cursor or limit is invalid.
+    /// * Returns 'AppError::Database' for any database operation failures.
+    ///
+    /// Returns the page and the cursor for the next page, if there is one.
+    pub async fn list_execution_records(
+        &self,
+        task_id: Uuid,
+        limit: Option<i64>,
+        cursor: Option<&str>,
+    ) -> Result<(Vec<Execution>, Option<String>), AppError> {
+        let (limit, after) = self.page_bounds(task_id, limit, cursor).await?;
+
+        let executions = self
+            .store
+            .list_execution_records(task_id, after.as_ref(), limit + 1)
+            .await?;
+
+        Ok(next_page(executions, limit, |e| (e.executed_at, e.id)))
+    }
+
+    /// Validates an execution page request for an existing task.
+    async fn page_bounds(
+        &self,
+        task_id: Uuid,
+        limit: Option<i64>,
+        cursor: Option<&str>,
+    ) -> Result<(i64, Option<ExecutionCursor>), AppError> {
+        let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE);
+        if !(1..=MAX_PAGE_SIZE).contains(&limit) {
+            return Err(AppError::ValidationError(format!(
+                "limit must be between 1 and {}",
+                MAX_PAGE_SIZE
+            )));
+        }
+
+        let after = match cursor {
+            Some(token) => Some(
+                ExecutionCursor::decode(token)
+                    .ok_or_else(|| AppError::ValidationError("Invalid cursor".into()))?,
+            ),
+            None => None,
+        };
+
+        if self.store.get_task(task_id).await?.is_none() {
+            return Err(AppError::NotFound);
+        }
+
+        Ok((limit, after))
+    }
+
+    /// Streams executions for export, starting at `since` if given.
+    ///
+    /// Rows are read from a database cursor on a background task and handed over through a
+    /// bounded channel, so exports of any size run in constant memory. The stream ends early
+    /// if the receiver is dropped (e.g. the client disconnects).
+    ///
+    /// # Arguments
+    ///
+    /// * `since` - Optional lower bound for `executed_at`.
+    pub fn export_executions(
+        &self,
+        since: Option<DateTime<Utc>>,
+    ) -> mpsc::Receiver<Result<Execution, AppError>> {
+        let store = self.store.clone();
+        let (tx, rx) = mpsc::channel(256);
+
+        tokio::spawn(async move {
+            let mut rows = store.stream_executions(since);
+
+            while let Some(row) = rows.next().await {
+                if tx.send(row.map_err(AppError::from)).await.is_err() {
+                    tracing::debug!("Execution export aborted by receiver");
+                    break;
+                }
+            }
+        });
+
+        rx
     }
 }
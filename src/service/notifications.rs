@@ -0,0 +1,168 @@
+use crate::domain::{ExecutionStatus, NotificationChannel, NotificationEvent, Notifications, Task};
+use crate::errors::AppError;
+use crate::notify::{HttpNotifier, Notification, Notifier};
+use crate::service::{PendingWrite, TaskService};
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+
+/// Checks that every route of a notification policy can be delivered.
+///
+/// # Errors
+///
+/// * Returns 'AppError::ValidationError' if a route has no events, a URL is not an HTTP(S)
+///   URL, or an email route has no valid recipient.
+pub fn validate_notifications(policy: &Notifications) -> Result<(), AppError> {
+    for route in &policy.routes {
+        if route.events.is_empty() {
+            return Err(AppError::ValidationError(
+                "Notification routes must select at least one event".into(),
+            ));
+        }
+
+        match &route.channel {
+            NotificationChannel::Slack { webhook_url: url }
+            | NotificationChannel::Callback { url } => {
+                let valid = reqwest::Url::parse(url)
+                    .is_ok_and(|url| matches!(url.scheme(), "http" | "https"));
+                if !valid {
+                    return Err(AppError::ValidationError(format!(
+                        "'{}' is not a valid notification URL",
+                        url
+                    )));
+                }
+            }
+            NotificationChannel::Email { to } => {
+                if to.is_empty() || to.iter().any(|address| !address.contains('@')) {
+                    return Err(AppError::ValidationError(
+                        "Email notifications need at least one valid recipient".into(),
+                    ));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+impl TaskService {
+    /// Sets the notifier used for task notifications, replacing the HTTP one.
+    pub fn with_notifier(mut self, notifier: Arc<dyn Notifier>) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    /// Sends the notifications that recorded executions call for under their tasks' policies.
+    ///
+    /// Delivery runs in the background and failures are only logged, so a slow or broken
+    /// channel never holds up scheduling. Runs that take far longer than usual are logged
+    /// whether or not their task has a policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `writes` - The recorded executions with the tasks they belong to.
+    /// * `started_at` - When a scheduled group started; runs that started more than the
+    ///   missed-run grace period after their trigger time count as missed. `None` for runs
+    ///   outside the schedule.
+    pub(super) async fn notify_outcomes(
+        &self,
+        writes: &[PendingWrite],
+        started_at: Option<DateTime<Utc>>,
+    ) {
+        let grace = chrono::Duration::seconds(self.config.missed_run_grace_secs as i64);
+
+        for PendingWrite { task, execution } in writes {
+            // Logged even for tasks that notify nobody
+            let slow = self.duration_anomaly(task, execution).await;
+            let Some(policy) = &task.notifications else {
+                continue;
+            };
+
+            let mut events = Vec::new();
+            if let Some(message) = slow {
+                events.push((NotificationEvent::SlowRun, message));
+            }
+            if started_at.is_some_and(|started_at| started_at - task.trigger_at > grace) {
+                events.push((
+                    NotificationEvent::MissedRun,
+                    format!("Scheduled for {}, started late", task.trigger_at),
+                ));
+            }
+            match execution.status {
+                ExecutionStatus::Failure | ExecutionStatus::Interrupted => {
+                    let error = execution.output.get("error").and_then(|e| e.as_str());
+                    events.push((
+                        NotificationEvent::Failure,
+                        error.unwrap_or("Execution failed").to_string(),
+                    ));
+                }
+                ExecutionStatus::Success
+                    if policy.wants(NotificationEvent::Recovery)
+                        && self.previously_failed(task, execution.id).await =>
+                {
+                    events.push((
+                        NotificationEvent::Recovery,
+                        "Execution succeeded after a failure".to_string(),
+                    ));
+                }
+                _ => {}
+            }
+
+            for (event, message) in events {
+                self.dispatch(
+                    task,
+                    Notification {
+                        event,
+                        task_id: task.id,
+                        task_name: task.name.clone(),
+                        execution_id: Some(execution.id),
+                        message,
+                        occurred_at: execution.executed_at,
+                    },
+                );
+            }
+        }
+    }
+
+    /// True if the finished execution before `current` did not succeed.
+    async fn previously_failed(&self, task: &Task, current: uuid::Uuid) -> bool {
+        match self.store.list_executions(task.id, None, 3).await {
+            Ok(executions) => executions
+                .iter()
+                .find(|e| e.id != current && e.status != ExecutionStatus::Running)
+                .is_some_and(|e| e.status != ExecutionStatus::Success),
+            Err(e) => {
+                tracing::warn!(task_id = %task.id, "Could not check for a recovery: {}", e);
+                false
+            }
+        }
+    }
+
+    /// Sends a notification to every channel of the task subscribed to its event.
+    fn dispatch(&self, task: &Task, notification: Notification) {
+        let Some(policy) = &task.notifications else {
+            return;
+        };
+        let channels: Vec<NotificationChannel> =
+            policy.channels_for(notification.event).cloned().collect();
+        if channels.is_empty() {
+            return;
+        }
+
+        let notifier = self
+            .notifier
+            .clone()
+            .unwrap_or_else(|| Arc::new(HttpNotifier::new(self.config.email_relay_url.clone())));
+        self.tracker.spawn(async move {
+            for channel in channels {
+                if let Err(e) = notifier.send(&channel, &notification).await {
+                    tracing::warn!(
+                        task_id = %notification.task_id,
+                        event = ?notification.event,
+                        "Notification failed: {}",
+                        e
+                    );
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,80 @@
+use serde_json::{Map, Value, json};
+use std::cmp::Reverse;
+
+/// Appended to strings cut short to keep an output under the size limit.
+pub const TRUNCATION_MARKER: &str = "…[truncated]";
+
+/// Room left for the `truncated` and `original_bytes` fields added to a truncated output.
+const NOTE_BYTES: usize = 64;
+
+/// Shrinks an output whose JSON is larger than `max_bytes`, so a huge response body is not
+/// stored verbatim. The longest strings are cut first, each ending in
+/// [`TRUNCATION_MARKER`]; if that is not enough, e.g. for a huge array of numbers, only a
+/// prefix of the JSON is kept as `preview`. The output then carries `"truncated": true` and
+/// its size before truncation as `original_bytes`.
+///
+/// # Arguments
+///
+/// * `output` - The output to shrink in place.
+/// * `max_bytes` - Largest JSON size left alone; `0` for no limit.
+///
+/// # Returns
+///
+/// The size of the output's JSON before truncation, or `None` if it was small enough.
+pub fn limit_output(output: &mut Value, max_bytes: usize) -> Option<usize> {
+    let original = json_size(output);
+    if max_bytes == 0 || original <= max_bytes {
+        return None;
+    }
+
+    // Cutting a string by n bytes shrinks the JSON by at least n, escapes included
+    let mut excess = original - max_bytes + NOTE_BYTES;
+    let mut strings = Vec::new();
+    collect_strings(output, &mut strings);
+    strings.sort_by_key(|text| Reverse(text.len()));
+    for text in strings {
+        if excess == 0 || text.len() <= TRUNCATION_MARKER.len() * 2 {
+            break;
+        }
+        let keep =
+            text.floor_char_boundary(text.len().saturating_sub(excess + TRUNCATION_MARKER.len()));
+        excess = excess.saturating_sub(text.len() - keep - TRUNCATION_MARKER.len());
+        text.truncate(keep);
+        text.push_str(TRUNCATION_MARKER);
+    }
+
+    let mut fields = match std::mem::take(output) {
+        Value::Object(fields) => fields,
+        other => Map::from_iter([("output".to_string(), other)]),
+    };
+    if json_size(&Value::Object(fields.clone())) + NOTE_BYTES > max_bytes {
+        // Quotes in the preview are escaped when it is stored, so it gets half the room
+        let preview = serde_json::to_string(&fields).unwrap_or_default();
+        let keep = preview.floor_char_boundary(max_bytes.saturating_sub(NOTE_BYTES * 2) / 2);
+        fields = Map::from_iter([(
+            "preview".to_string(),
+            json!(format!("{}{}", &preview[..keep], TRUNCATION_MARKER)),
+        )]);
+    }
+    fields.insert("truncated".into(), json!(true));
+    fields.insert("original_bytes".into(), json!(original));
+    *output = Value::Object(fields);
+    Some(original)
+}
+
+fn json_size(value: &Value) -> usize {
+    serde_json::to_vec(value).map_or(0, |json| json.len())
+}
+
+fn collect_strings<'a>(value: &'a mut Value, strings: &mut Vec<&'a mut String>) {
+    match value {
+        Value::String(text) => strings.push(text),
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|item| collect_strings(item, strings)),
+        Value::Object(fields) => fields
+            .values_mut()
+            .for_each(|field| collect_strings(field, strings)),
+        _ => {}
+    }
+}
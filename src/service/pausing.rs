@@ -0,0 +1,134 @@
+use crate::db::retry::with_retry;
+use crate::domain::{Labels, TaskSelector};
+use crate::errors::AppError;
+use crate::service::TaskService;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// Checks that every label has a key.
+///
+/// # Errors
+///
+/// * Returns 'AppError::ValidationError' if a label key is blank.
+pub fn validate_labels(labels: &Labels) -> Result<(), AppError> {
+    if labels.keys().any(|key| key.trim().is_empty()) {
+        return Err(AppError::ValidationError(
+            "Label keys must not be blank".into(),
+        ));
+    }
+
+    Ok(())
+}
+
+impl TaskService {
+    /// Pauses a task: it keeps its schedule but is not dispatched until resumed. Runs that come
+    /// due in the meantime are caught up on resume; pausing a paused task is a no-op.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The UUID of the task.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::NotFound' if the task does not exist or is deleted.
+    /// * Returns 'AppError::Database' for any database operation failures.
+    pub async fn pause_task(&self, id: Uuid) -> Result<(), AppError> {
+        let now = self.clock.now();
+        self.set_tasks_paused(&TaskSelector::Task(id), Some(now))
+            .await?;
+        tracing::info!(task_id = %id, "Task paused");
+        Ok(())
+    }
+
+    /// Resumes a paused task; resuming an active task is a no-op.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The UUID of the task.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::NotFound' if the task does not exist or is deleted.
+    /// * Returns 'AppError::Database' for any database operation failures.
+    pub async fn resume_task(&self, id: Uuid) -> Result<(), AppError> {
+        self.set_tasks_paused(&TaskSelector::Task(id), None).await?;
+        tracing::info!(task_id = %id, "Task resumed");
+        Ok(())
+    }
+
+    /// Pauses every live task the selector matches, in one transaction. Runs that come due
+    /// while a task is paused are caught up when it is resumed.
+    ///
+    /// # Arguments
+    ///
+    /// * `selector` - The labels or group of the tasks to pause.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::ValidationError' if the label selector is empty.
+    /// * Returns 'AppError::NotFound' if the selected group does not exist.
+    /// * Returns 'AppError::Database' for any database operation failures.
+    ///
+    /// Returns the number of tasks paused; tasks that already were are not counted.
+    pub async fn pause_tasks(&self, selector: &TaskSelector) -> Result<u64, AppError> {
+        let now = self.clock.now();
+        let affected = self.set_tasks_paused(selector, Some(now)).await?;
+        tracing::info!(?selector, affected, "Tasks paused");
+        Ok(affected)
+    }
+
+    /// Resumes every paused task the selector matches, in one transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `selector` - The labels or group of the tasks to resume.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::ValidationError' if the label selector is empty.
+    /// * Returns 'AppError::NotFound' if the selected group does not exist.
+    /// * Returns 'AppError::Database' for any database operation failures.
+    ///
+    /// Returns the number of tasks resumed.
+    pub async fn resume_tasks(&self, selector: &TaskSelector) -> Result<u64, AppError> {
+        let affected = self.set_tasks_paused(selector, None).await?;
+        tracing::info!(?selector, affected, "Tasks resumed");
+        Ok(affected)
+    }
+
+    async fn set_tasks_paused(
+        &self,
+        selector: &TaskSelector,
+        paused_at: Option<DateTime<Utc>>,
+    ) -> Result<u64, AppError> {
+        match selector {
+            // An empty selector would match every task
+            TaskSelector::Labels(labels) if labels.is_empty() => {
+                return Err(AppError::ValidationError(
+                    "Label selector must name at least one label".into(),
+                ));
+            }
+            TaskSelector::Labels(_) => {}
+            TaskSelector::Group(id) => {
+                self.store.get_group(*id).await?.ok_or(AppError::NotFound)?;
+            }
+            TaskSelector::Task(id) => {
+                self.store
+                    .get_task(*id)
+                    .await?
+                    .filter(|task| task.deleted_at.is_none())
+                    .ok_or(AppError::NotFound)?;
+            }
+        }
+
+        let affected = with_retry(&self.config.retry_policy(), || {
+            self.store.set_tasks_paused(selector, paused_at)
+        })
+        .await?;
+        if affected > 0 {
+            self.wake_scheduler();
+        }
+
+        Ok(affected)
+    }
+}
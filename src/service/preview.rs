@@ -0,0 +1,152 @@
+use crate::api::dto::{PreviewScheduleReq, ValidateScheduleReq};
+use crate::domain::TaskType;
+use crate::errors::AppError;
+use crate::schedule::{self, HumanSchedule, IssueKind, Recurrence, ScheduleIssue};
+use crate::service::{TaskService, parse_task_type, resolve_human_schedule, validate_schedule};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+
+/// Fire times returned when a preview does not ask for a number.
+pub const DEFAULT_PREVIEW_COUNT: usize = 10;
+
+/// Most fire times a single preview returns.
+pub const MAX_PREVIEW_COUNT: usize = 100;
+
+impl TaskService {
+    /// Computes the next fire times of a schedule, exactly as a task created with it would
+    /// run, without creating anything.
+    ///
+    /// # Arguments
+    ///
+    /// * `req` - The schedule fields, as they would be sent to create the task.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::ValidationError' if the schedule is invalid, as on creation, or
+    ///   'count' is not between 1 and 100.
+    /// * Returns 'AppError::IntervalTooSmall' if 'interval_seconds' is less than 1.
+    ///
+    /// Returns the fire times in order; fewer than requested if the schedule ends first.
+    pub fn preview_schedule(
+        &self,
+        req: PreviewScheduleReq,
+    ) -> Result<Vec<DateTime<Utc>>, AppError> {
+        let count = req.count.unwrap_or(DEFAULT_PREVIEW_COUNT);
+        if !(1..=MAX_PREVIEW_COUNT).contains(&count) {
+            return Err(AppError::ValidationError(format!(
+                "count must be between 1 and {}",
+                MAX_PREVIEW_COUNT
+            )));
+        }
+        let tz = match &req.timezone {
+            Some(name) => schedule::parse_timezone(name).map_err(AppError::ValidationError)?,
+            None => Tz::UTC,
+        };
+        let now = self.clock.now();
+
+        let (recurrence, start) = match req.schedule {
+            Some(text) => {
+                let (resolved, start) = resolve_human_schedule(
+                    &text,
+                    &req.task_type,
+                    req.trigger_at,
+                    req.interval_seconds,
+                    req.cron_expr.as_deref(),
+                    req.rrule.as_deref(),
+                    now,
+                )?;
+                let recurrence = match resolved {
+                    HumanSchedule::Once(_) => Recurrence::Once,
+                    HumanSchedule::Interval(seconds) => Recurrence::Interval(seconds),
+                    HumanSchedule::Cron(expr) => Recurrence::Cron { expr, tz },
+                };
+                (recurrence, start)
+            }
+            None => {
+                let task_type = parse_task_type(&req.task_type)?;
+                validate_schedule(&task_type, req.interval_seconds, req.cron_expr.as_deref())?;
+                let start = req.trigger_at.unwrap_or(now);
+                let recurrence = match task_type {
+                    TaskType::Once => Recurrence::Once,
+                    TaskType::Interval => Recurrence::Interval(req.interval_seconds.unwrap()),
+                    TaskType::Cron => Recurrence::Cron {
+                        expr: req.cron_expr.unwrap(),
+                        tz,
+                    },
+                    TaskType::Rrule => {
+                        let rule = req.rrule.ok_or_else(|| {
+                            AppError::ValidationError("rrule is required for rrule tasks".into())
+                        })?;
+                        Recurrence::Rrule {
+                            rule: schedule::parse_rrule(&rule, start, tz)
+                                .map_err(|issue| AppError::ValidationError(issue.message))?,
+                            tz,
+                        }
+                    }
+                };
+                (recurrence, start)
+            }
+        };
+
+        Ok(recurrence.upcoming(start, count))
+    }
+
+    /// Checks a cron expression, a recurrence rule or both, reporting every problem found
+    /// instead of failing on the first, so clients can validate as the user types.
+    ///
+    /// # Arguments
+    ///
+    /// * `req` - The expressions to check, and the time zone rules are evaluated in.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::ValidationError' if neither 'cron_expr' nor 'rrule' is given.
+    ///
+    /// Returns the problems with the request field each is in; none if the schedule is valid.
+    pub fn check_schedule(
+        &self,
+        req: ValidateScheduleReq,
+    ) -> Result<Vec<(&'static str, ScheduleIssue)>, AppError> {
+        if req.cron_expr.is_none() && req.rrule.is_none() {
+            return Err(AppError::ValidationError(
+                "cron_expr or rrule is required".into(),
+            ));
+        }
+        let mut issues = Vec::new();
+        let tz = match req.timezone.as_deref().map(schedule::parse_timezone) {
+            Some(Ok(tz)) => tz,
+            Some(Err(message)) => {
+                issues.push((
+                    "timezone",
+                    ScheduleIssue::whole(IssueKind::Unknown, message),
+                ));
+                Tz::UTC
+            }
+            None => Tz::UTC,
+        };
+
+        if let Some(expr) = &req.cron_expr {
+            issues.extend(
+                schedule::check_cron(expr)
+                    .into_iter()
+                    .map(|issue| ("cron_expr", issue)),
+            );
+        }
+        if let Some(rule) = &req.rrule {
+            let now = self.clock.now();
+            match schedule::parse_rrule(rule, now, tz) {
+                Ok(parsed) if parsed.next_after(now, true, tz).is_none() => issues.push((
+                    "rrule",
+                    ScheduleIssue::whole(
+                        IssueKind::NeverMatches,
+                        "Recurrence rule has no upcoming occurrence",
+                    ),
+                )),
+                Ok(_) => {}
+                Err(issue) => issues.push(("rrule", issue)),
+            }
+        }
+
+        Ok(issues)
+    }
+}
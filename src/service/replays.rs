@@ -0,0 +1,59 @@
+use crate::db::retry::with_retry;
+use crate::domain::{Execution, ExecutionStatus, Task};
+use crate::errors::AppError;
+use crate::service::{PendingWrite, TaskService};
+use uuid::Uuid;
+
+impl TaskService {
+    /// Runs a past execution again with the exact payload it ran with, as a new execution
+    /// linked to the original. The task's schedule is left alone, and tasks that have since
+    /// been deleted can still be replayed as long as they were not purged.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The UUID of the execution to replay.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::NotFound' if the execution or its task no longer exists.
+    /// * Returns 'AppError::ValidationError' if the execution is still running or predates
+    ///   payload recording, so there is nothing exact to replay.
+    /// * Returns 'AppError::Conflict' if the task is running.
+    /// * Returns 'AppError::Database' for any database operation failures.
+    ///
+    /// Returns the new execution.
+    pub async fn replay_execution(&self, id: Uuid) -> Result<Execution, AppError> {
+        let policy = self.config.retry_policy();
+        let original = with_retry(&policy, || self.store.get_execution(id))
+            .await?
+            .ok_or(AppError::NotFound)?;
+        if original.status == ExecutionStatus::Running {
+            return Err(AppError::ValidationError(
+                "Execution is still running".into(),
+            ));
+        }
+        let Some(payload) = original.payload else {
+            return Err(AppError::ValidationError(
+                "Execution has no recorded payload to replay".into(),
+            ));
+        };
+
+        let task = with_retry(&policy, || self.store.get_task(original.task_id))
+            .await?
+            .ok_or(AppError::NotFound)?;
+        let task = Task { payload, ..task };
+
+        let execution = self.run_unscheduled(&task, Some(original.id)).await?;
+        tracing::info!(
+            execution_id = %execution.id,
+            replay_of = %original.id,
+            "Execution replayed"
+        );
+
+        let write = PendingWrite { task, execution };
+        self.notify_outcomes(std::slice::from_ref(&write), None)
+            .await;
+
+        Ok(write.execution)
+    }
+}
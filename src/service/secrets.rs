@@ -0,0 +1,125 @@
+use crate::db::retry::with_retry;
+use crate::domain::StoredSecret;
+use crate::errors::AppError;
+use crate::secrets::{self, SecretCipher};
+use crate::service::TaskService;
+use serde_json::Value;
+use std::collections::HashMap;
+
+impl TaskService {
+    fn cipher(&self) -> Result<&SecretCipher, AppError> {
+        self.config
+            .secrets
+            .as_ref()
+            .ok_or_else(|| AppError::Config("SECRETS_KEY is not set".into()))
+    }
+
+    /// Stores a secret under `name`, replacing its value if it already exists. Tasks pick
+    /// up the new value on their next run.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name payloads refer to it by, as `{{secret:NAME}}`.
+    /// * `value` - The plaintext value; it is encrypted before it is stored.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::ValidationError' if the name is invalid or the value is empty.
+    /// * Returns 'AppError::Config' if `SECRETS_KEY` is not set.
+    /// * Returns 'AppError::Database' for any database operation failures.
+    ///
+    /// Returns the stored secret's metadata.
+    pub async fn put_secret(&self, name: String, value: &str) -> Result<StoredSecret, AppError> {
+        secrets::validate_name(&name).map_err(AppError::ValidationError)?;
+        if value.is_empty() {
+            return Err(AppError::ValidationError(
+                "Secret value must not be empty".into(),
+            ));
+        }
+        let cipher = self.cipher()?;
+
+        let now = self.clock.now();
+        let created_at = self
+            .store
+            .get_secret(&name)
+            .await?
+            .map_or(now, |existing| existing.created_at);
+        let secret = StoredSecret {
+            ciphertext: cipher.encrypt(&name, value),
+            name,
+            created_at,
+            updated_at: now,
+        };
+        with_retry(&self.config.retry_policy(), || {
+            self.store.put_secret(&secret)
+        })
+        .await?;
+
+        tracing::info!(secret = %secret.name, "Secret stored");
+        Ok(secret)
+    }
+
+    /// Lists every secret, ordered by name. Values are never returned.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::Database' for any database operation failures.
+    pub async fn list_secrets(&self) -> Result<Vec<StoredSecret>, AppError> {
+        let secrets = self.store.list_secrets().await?;
+        Ok(secrets)
+    }
+
+    /// Deletes a secret. Tasks that still refer to it fail until it is stored again.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the secret.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::NotFound' if the secret does not exist.
+    /// * Returns 'AppError::Database' for any database operation failures.
+    pub async fn delete_secret(&self, name: &str) -> Result<(), AppError> {
+        let rows_affected = with_retry(&self.config.retry_policy(), || {
+            self.store.delete_secret(name)
+        })
+        .await?;
+        if rows_affected == 0 {
+            return Err(AppError::NotFound);
+        }
+
+        tracing::info!(secret = %name, "Secret deleted");
+        Ok(())
+    }
+
+    /// Replaces the secret placeholders of a payload about to run with their values.
+    ///
+    /// # Errors
+    ///
+    /// * Returns an error message if a referenced secret does not exist or cannot be
+    ///   decrypted, or `SECRETS_KEY` is not set.
+    ///
+    /// Returns the values that were filled in, so they can be kept out of the output.
+    pub(super) async fn resolve_secrets(&self, payload: &mut Value) -> Result<Vec<String>, String> {
+        let names = secrets::placeholders(payload);
+        if names.is_empty() {
+            return Ok(Vec::new());
+        }
+        let cipher = self.cipher().map_err(|e| e.to_string())?;
+
+        let mut values = HashMap::new();
+        for name in names {
+            let secret = self
+                .store
+                .get_secret(&name)
+                .await
+                .map_err(|e| format!("Failed to read secret '{}': {}", name, e))?
+                .ok_or_else(|| format!("Secret '{}' does not exist", name))?;
+            let value = cipher.decrypt(&name, &secret.ciphertext)?;
+            values.insert(name, value);
+        }
+        secrets::interpolate(payload, &values);
+
+        Ok(values.into_values().collect())
+    }
+}
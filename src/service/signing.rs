@@ -0,0 +1,111 @@
+use crate::db::retry::with_retry;
+use crate::domain::Task;
+use crate::errors::AppError;
+use crate::service::TaskService;
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use uuid::Uuid;
+
+/// Header carrying the request signature on outgoing webhook calls.
+pub const SIGNATURE_HEADER: &str = "X-Scheduler-Signature";
+
+/// Prefix of generated signing secrets, so leaked secrets are easy to recognise.
+const SECRET_PREFIX: &str = "whsec_";
+
+/// Generates a new random signing secret.
+pub fn generate_secret() -> String {
+    let bytes: [u8; 32] = rand::random();
+    format!("{}{}", SECRET_PREFIX, URL_SAFE_NO_PAD.encode(bytes))
+}
+
+/// Hex HMAC-SHA256 of `"<timestamp>.<body>"` under `secret`.
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!("{:x}", mac.finalize().into_bytes())
+}
+
+/// Builds the signature header value for a webhook request sent at `now`:
+/// `t=<unix seconds>,v1=<signature>`, with a second `v1` entry from the previous secret
+/// while a rotation overlap is in progress, and a last one from the global secret
+/// (`WEBHOOK_SIGNING_SECRET`) if set. Receivers accept the request if any entry matches a
+/// secret they know.
+///
+/// Returns `None` if there is no secret to sign with.
+pub fn signature_header(
+    task: &Task,
+    global: Option<&str>,
+    now: DateTime<Utc>,
+    body: &[u8],
+) -> Option<String> {
+    let mut secrets = active_secrets(task, now);
+    secrets.extend(global);
+    if secrets.is_empty() {
+        return None;
+    }
+
+    let timestamp = now.timestamp();
+    let signatures: Vec<String> = secrets
+        .iter()
+        .map(|secret| format!("v1={}", sign(secret, timestamp, body)))
+        .collect();
+
+    Some(format!("t={},{}", timestamp, signatures.join(",")))
+}
+
+/// The task's secrets that sign at `now`: the current one, then the previous one while its
+/// rotation overlap lasts.
+pub fn active_secrets(task: &Task, now: DateTime<Utc>) -> Vec<&str> {
+    let mut secrets: Vec<&str> = task.signing_secret.as_deref().into_iter().collect();
+    if let (Some(previous), Some(expires_at)) = (
+        task.previous_signing_secret.as_deref(),
+        task.previous_secret_expires_at,
+    ) && now < expires_at
+    {
+        secrets.push(previous);
+    }
+    secrets
+}
+
+impl TaskService {
+    /// Replaces a task's signing secret. Requests are signed with both the old and the new
+    /// secret until the overlap ends, so receivers can switch over without rejecting any.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The UUID of the task.
+    /// * `overlap` - How long the old secret keeps signing requests.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::NotFound' if the task does not exist or is deleted.
+    /// * Returns 'AppError::Database' for any database operation failures.
+    ///
+    /// Returns the new secret and the end of the overlap window.
+    pub async fn rotate_signing_secret(
+        &self,
+        id: Uuid,
+        overlap: Duration,
+    ) -> Result<(String, DateTime<Utc>), AppError> {
+        let secret = generate_secret();
+        let previous_expires_at = self.clock.now() + overlap;
+
+        let rows_affected = with_retry(&self.config.retry_policy(), || {
+            self.store
+                .rotate_signing_secret(id, &secret, previous_expires_at)
+        })
+        .await?;
+        if rows_affected == 0 {
+            return Err(AppError::NotFound);
+        }
+
+        tracing::info!(task_id = %id, %previous_expires_at, "Signing secret rotated");
+
+        Ok((secret, previous_expires_at))
+    }
+}
@@ -0,0 +1,222 @@
+use crate::db::retry::with_retry;
+use crate::domain::{SNAPSHOT_VERSION, Snapshot, Task};
+use crate::errors::AppError;
+use crate::service::{TaskService, signing};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+impl TaskService {
+    /// Takes a consistent snapshot of every group, task and workflow, for restoring into
+    /// another instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `include_executions` - Add the execution history, which is left out by default.
+    /// * `include_secrets` - Keep the tasks' signing secrets. Without them, restored tasks
+    ///   get fresh secrets and receivers have to be given the new ones.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::Database' for any database operation failures.
+    pub async fn export_snapshot(
+        &self,
+        include_executions: bool,
+        include_secrets: bool,
+    ) -> Result<Snapshot, AppError> {
+        let now = self.clock.now();
+        let mut snapshot = with_retry(&self.config.retry_policy(), || {
+            self.store.export_snapshot(now, include_executions)
+        })
+        .await?;
+
+        if !include_secrets {
+            for task in &mut snapshot.tasks {
+                task.signing_secret = None;
+                task.previous_signing_secret = None;
+                task.previous_secret_expires_at = None;
+            }
+        }
+        tracing::info!(
+            tasks = snapshot.tasks.len(),
+            groups = snapshot.groups.len(),
+            workflows = snapshot.workflows.len(),
+            include_executions,
+            include_secrets,
+            "Snapshot exported"
+        );
+
+        Ok(snapshot)
+    }
+
+    /// Restores a snapshot into this instance, which must not hold any tasks, groups or
+    /// workflows yet. Tasks exported without their signing secrets get new ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `snapshot` - A snapshot taken with [`TaskService::export_snapshot`].
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::ValidationError' if:
+    /// * the snapshot was written in an unsupported format version.
+    /// * an ID appears twice, or a task, workflow or execution refers to something not in
+    ///   the snapshot.
+    /// * the tasks' `depends_on` or `next_task_id` links form a cycle.
+    /// * the instance already holds tasks, groups or workflows.
+    ///
+    /// * Returns 'AppError::Database' for any database operation failures.
+    pub async fn restore_snapshot(&self, mut snapshot: Snapshot) -> Result<(), AppError> {
+        if snapshot.version != SNAPSHOT_VERSION {
+            return Err(AppError::ValidationError(format!(
+                "Unsupported snapshot version {} (expected {})",
+                snapshot.version, SNAPSHOT_VERSION
+            )));
+        }
+        check_references(&snapshot)?;
+
+        for task in &mut snapshot.tasks {
+            if task.signing_secret.is_none() {
+                task.signing_secret = Some(signing::generate_secret());
+                task.previous_signing_secret = None;
+                task.previous_secret_expires_at = None;
+            }
+        }
+
+        let restored = with_retry(&self.config.retry_policy(), || {
+            self.store.restore_snapshot(&snapshot)
+        })
+        .await?;
+        if !restored {
+            return Err(AppError::ValidationError(
+                "Snapshots can only be restored into an empty instance".into(),
+            ));
+        }
+
+        tracing::info!(
+            tasks = snapshot.tasks.len(),
+            groups = snapshot.groups.len(),
+            workflows = snapshot.workflows.len(),
+            taken_at = %snapshot.taken_at,
+            "Snapshot restored"
+        );
+        self.wake_scheduler();
+        Ok(())
+    }
+}
+
+/// Rejects duplicate IDs, references to groups or tasks missing from the snapshot, and
+/// dependency or chain cycles, which the create path cannot produce.
+fn check_references(snapshot: &Snapshot) -> Result<(), AppError> {
+    let mut group_ids = HashSet::new();
+    if let Some(group) = snapshot.groups.iter().find(|g| !group_ids.insert(g.id)) {
+        return Err(AppError::ValidationError(format!(
+            "Group {} appears more than once",
+            group.id
+        )));
+    }
+
+    let mut task_ids = HashSet::new();
+    for task in &snapshot.tasks {
+        if !task_ids.insert(task.id) {
+            return Err(AppError::ValidationError(format!(
+                "Task {} appears more than once",
+                task.id
+            )));
+        }
+        if let Some(group_id) = task.group_id.filter(|id| !group_ids.contains(id)) {
+            return Err(AppError::ValidationError(format!(
+                "Task {} refers to group {}, which is not in the snapshot",
+                task.id, group_id
+            )));
+        }
+    }
+
+    for task in &snapshot.tasks {
+        let links = [
+            ("depends on", task.depends_on),
+            ("chains to", task.next_task_id),
+        ];
+        for (relation, id) in links {
+            if let Some(id) = id.filter(|id| !task_ids.contains(id)) {
+                return Err(AppError::ValidationError(format!(
+                    "Task {} {} task {}, which is not in the snapshot",
+                    task.id, relation, id
+                )));
+            }
+        }
+    }
+    if let Some(id) = find_cycle(&snapshot.tasks, |t| t.depends_on) {
+        return Err(AppError::ValidationError(format!(
+            "Task {} depends on itself through depends_on",
+            id
+        )));
+    }
+    if let Some(id) = find_cycle(&snapshot.tasks, |t| t.next_task_id) {
+        return Err(AppError::ValidationError(format!(
+            "Task {} chains back to itself through next_task_id",
+            id
+        )));
+    }
+
+    let mut workflow_ids = HashSet::new();
+    for workflow in &snapshot.workflows {
+        if !workflow_ids.insert(workflow.id) {
+            return Err(AppError::ValidationError(format!(
+                "Workflow {} appears more than once",
+                workflow.id
+            )));
+        }
+        for node in &workflow.nodes {
+            let task_id = std::iter::once(node.task_id)
+                .chain(node.compensate_with)
+                .find(|id| !task_ids.contains(id));
+            if let Some(task_id) = task_id {
+                return Err(AppError::ValidationError(format!(
+                    "Workflow {} node '{}' refers to task {}, which is not in the snapshot",
+                    workflow.id, node.key, task_id
+                )));
+            }
+        }
+    }
+
+    let mut execution_ids = HashSet::new();
+    for execution in snapshot.executions.iter().flatten() {
+        if !execution_ids.insert(execution.id) {
+            return Err(AppError::ValidationError(format!(
+                "Execution {} appears more than once",
+                execution.id
+            )));
+        }
+        if !task_ids.contains(&execution.task_id) {
+            return Err(AppError::ValidationError(format!(
+                "Execution {} refers to task {}, which is not in the snapshot",
+                execution.id, execution.task_id
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// A task on a cycle of `link` references, if there is one.
+fn find_cycle(tasks: &[Task], link: impl Fn(&Task) -> Option<Uuid>) -> Option<Uuid> {
+    let links: HashMap<Uuid, Uuid> = tasks
+        .iter()
+        .filter_map(|task| Some((task.id, link(task)?)))
+        .collect();
+
+    // Tasks whose links are known to end without returning
+    let mut cleared = HashSet::new();
+    for task in tasks {
+        let mut path = HashSet::new();
+        let mut current = Some(task.id);
+        while let Some(id) = current.filter(|id| !cleared.contains(id)) {
+            if !path.insert(id) {
+                return Some(id);
+            }
+            current = links.get(&id).copied();
+        }
+        cleared.extend(path);
+    }
+    None
+}
@@ -1,13 +1,36 @@
-use crate::{domain::Task, service::TaskService};
-use chrono::Duration;
-use chrono::Utc;
+use crate::api::dto::{
+    CloneTaskReq, CreateTaskReq, PreviewScheduleReq, ReplaceTaskReq, UpdateTaskReq,
+    ValidateScheduleReq,
+};
+use crate::clock::{Clock, TestClock};
+use crate::db::memory::MemoryTaskStore;
+use crate::db::store::{Claim, TaskStore};
+use crate::domain::{
+    BlackoutWindow, DependencyState, Execution, ExecutionRetry, ExecutionStatus, FailureKind,
+    IntervalMode, MisfirePolicy, NodeState, NotificationChannel, NotificationEvent,
+    NotificationRoute, Notifications, RunState, Task, TaskType, WorkflowNode, WorkflowRun,
+    WorkflowRunStatus,
+};
+use crate::errors::AppError;
+use crate::executor::condition::{ConditionOp, RunCondition};
+use crate::notify::{Notification, Notifier};
+use crate::schedule::{ExecutionWindow, IssueKind};
+use crate::scheduler::ScheduleEvent;
+use crate::service::{RequestContext, TaskService};
+use chrono::{DateTime, Duration, NaiveTime, TimeZone, Utc, Weekday};
 use serde_json::json;
 use sqlx::SqlitePool;
+use std::sync::Arc;
 use tokio::sync::mpsc;
 
+/// Fixed starting point for the test clock.
+fn epoch() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap()
+}
+
 fn setup_service(pool: SqlitePool) -> TaskService {
     let (tx, _) = mpsc::channel(1);
-    TaskService::new(pool, tx)
+    TaskService::new(pool, tx).with_clock(Arc::new(TestClock::new(epoch())))
 }
 
 #[sqlx::test]
@@ -16,7 +39,7 @@ async fn test_process_task_reschedules(pool: SqlitePool) -> sqlx::Result<()> {
     let repo = crate::db::queries::TaskRepository::new(&pool);
 
     // Create an interval task
-    let trigger_at = chrono::Utc::now() - chrono::Duration::minutes(1); // in the past to trigger immediately
+    let trigger_at = epoch() - Duration::minutes(1); // in the past to trigger immediately
     let interval_seconds = 60;
     let payload = json!({ "url": "http://example.com" }); // dummy payload
 
@@ -33,16 +56,9 @@ async fn test_process_task_reschedules(pool: SqlitePool) -> sqlx::Result<()> {
     // Fetch the task again to verify it was rescheduled
     let updated_task = repo.get_task(task.id).await?.expect("Task should exist");
 
-    let expected_trigger = Utc::now() + Duration::seconds(interval_seconds);
-
-    let diff = updated_task
-        .trigger_at
-        .signed_duration_since(expected_trigger)
-        .num_milliseconds()
-        .abs();
-
-    assert!(
-        diff < 1000,
+    assert_eq!(
+        updated_task.trigger_at,
+        epoch() + Duration::seconds(interval_seconds),
         "Task should have incremented by interval relevant to now"
     );
 
@@ -62,7 +78,7 @@ async fn test_process_task_once_deletes(pool: SqlitePool) -> sqlx::Result<()> {
     let repo = crate::db::queries::TaskRepository::new(&pool);
 
     // Create a once task
-    let task = Task::new_once("once_task", Utc::now(), json!({}));
+    let task = Task::new_once("once_task", epoch(), json!({}));
     repo.create_task(&task).await?;
 
     service
@@ -94,26 +110,3430 @@ async fn test_interval_calculates_next_trigger_correctly(pool: SqlitePool) -> sq
     let repo = crate::db::queries::TaskRepository::new(&pool);
     let service = setup_service(pool.clone());
 
-    let task = Task::new_interval("test", Utc::now(), 3600, json!({}));
+    let task = Task::new_interval("test", epoch(), 3600, json!({}));
     repo.create_task(&task).await?;
 
+    // Execution happens a little late; the next trigger is relative to when it ran
+    let clock = Arc::new(TestClock::new(epoch() + Duration::seconds(7)));
+    let service = service.with_clock(clock.clone());
     service.process_task(task.clone()).await.unwrap();
 
     let updated_task = repo.get_task(task.id).await?.unwrap();
+    assert_eq!(
+        updated_task.trigger_at,
+        clock.now() + Duration::seconds(3600),
+        "Next trigger should be one hour after the run"
+    );
+
+    Ok(())
+}
+
+struct StubMiddleware;
+
+#[async_trait::async_trait]
+impl crate::executor::ExecutionMiddleware for StubMiddleware {
+    async fn before(&self, task: &mut Task) -> Result<(), String> {
+        task.payload["injected"] = json!(true);
+        Ok(())
+    }
+
+    async fn after(
+        &self,
+        task: &Task,
+        outcome: &mut crate::executor::middleware::ExecutionOutcome,
+    ) {
+        // Replace the (failed) webhook call with the payload the executor saw
+        *outcome = Ok(task.payload.clone());
+    }
+}
+
+#[sqlx::test]
+async fn test_process_task_runs_middleware_chain(pool: SqlitePool) -> sqlx::Result<()> {
+    let chain = crate::executor::MiddlewareChain::new().with(StubMiddleware);
+    let service = setup_service(pool.clone()).with_middleware(chain);
+    let repo = crate::db::queries::TaskRepository::new(&pool);
+
+    let task = Task::new_once("middleware_task", epoch(), json!({}));
+    repo.create_task(&task).await?;
+
+    service
+        .process_task(task.clone())
+        .await
+        .expect("Process task failed");
+
+    let (status, output): (String, String) =
+        sqlx::query_as("SELECT status, output FROM executions WHERE task_id = ?")
+            .bind(task.id)
+            .fetch_one(&pool)
+            .await?;
+
+    assert_eq!(status, "success");
+    let output: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(
+        output["injected"], true,
+        "before hook should mutate the payload"
+    );
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_process_batch_records_all_tasks(pool: SqlitePool) -> sqlx::Result<()> {
+    let service = setup_service(pool.clone());
+    let repo = crate::db::queries::TaskRepository::new(&pool);
+
+    let once = Task::new_once("batch_once", epoch(), json!({}));
+    let interval = Task::new_interval("batch_interval", epoch(), 60, json!({}));
+    repo.create_task(&once).await?;
+    repo.create_task(&interval).await?;
+
+    // Hard delete a third task mid-flight: its write must be skipped, not abort the group
+    let vanished = Task::new_once("batch_vanished", epoch(), json!({}));
+    repo.create_task(&vanished).await?;
+    sqlx::query("DELETE FROM tasks WHERE id = ?")
+        .bind(vanished.id)
+        .execute(&pool)
+        .await?;
+
+    service
+        .process_batch(vec![once.clone(), vanished, interval.clone()])
+        .await
+        .expect("Batch processing failed");
+
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM executions")
+        .fetch_one(&pool)
+        .await?;
+    assert_eq!(count, 2, "Both surviving tasks should have an execution");
+
+    assert!(repo.get_task(once.id).await?.unwrap().deleted_at.is_some());
+    assert_eq!(
+        repo.get_task(interval.id).await?.unwrap().trigger_at,
+        epoch() + Duration::seconds(60)
+    );
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn test_process_batch_failure_leaves_tasks_due(pool: SqlitePool) -> sqlx::Result<()> {
+    let service = setup_service(pool.clone());
+    let repo = crate::db::queries::TaskRepository::new(&pool);
+
+    let first = Task::new_interval("first", epoch() - Duration::minutes(1), 60, json!({}));
+    let second = Task::new_interval("second", epoch() - Duration::minutes(1), 60, json!({}));
+    repo.create_task(&first).await?;
+    repo.create_task(&second).await?;
+
+    // Simulate a crash between executing and committing: the second write blows up
+    sqlx::query(&format!(
+        "CREATE TRIGGER fail_second BEFORE INSERT ON executions \
+         WHEN hex(NEW.task_id) = '{}' BEGIN SELECT RAISE(ABORT, 'boom'); END;",
+        second.id.simple().to_string().to_uppercase()
+    ))
+    .execute(&pool)
+    .await?;
+
+    let result = service
+        .process_batch(vec![first.clone(), second.clone()])
+        .await;
+    assert!(result.is_err(), "Write failure should surface");
+
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM executions")
+        .fetch_one(&pool)
+        .await?;
+    assert_eq!(count, 0, "No partial group should be persisted");
+
+    let due = repo.get_due_tasks(epoch(), 10).await?;
+    assert_eq!(
+        due.len(),
+        2,
+        "Both tasks should still be due and fire again"
+    );
+
+    Ok(())
+}
 
-    let expected = Utc::now() + Duration::seconds(3600);
-    let diff = updated_task
-        .trigger_at
-        .signed_duration_since(expected)
-        .num_seconds()
-        .abs();
+#[sqlx::test]
+async fn test_replay_reruns_the_recorded_payload(pool: SqlitePool) -> sqlx::Result<()> {
+    let service = setup_service(pool.clone());
+    let store = service.store();
+
+    let payload = json!({ "url": "http://127.0.0.1:9/hook", "body": { "batch": 7 } });
+    let task = Task::new_once("one_off", epoch() - Duration::minutes(1), payload.clone());
+    store.create_task(&task).await?;
+    service
+        .process_task(task.clone())
+        .await
+        .expect("Process task failed");
+
+    let original = store.list_executions(task.id, None, 1).await?.remove(0);
+    let replay = service
+        .replay_execution(original.id)
+        .await
+        .expect("Deleted one-off tasks can be replayed");
 
+    assert_ne!(replay.id, original.id);
+    assert_eq!(replay.replay_of, Some(original.id));
+    let stored = store
+        .get_execution(replay.id)
+        .await?
+        .expect("Replay is recorded");
+    assert_eq!(stored.payload, Some(payload));
+    assert_eq!(stored.replay_of, Some(original.id));
     assert!(
-        diff < 5,
-        "Next trigger should be approximately one hour later! Got {}, expected {}",
-        updated_task.trigger_at,
-        expected
+        store.get_task(task.id).await?.unwrap().deleted_at.is_some(),
+        "Replays leave the schedule alone"
+    );
+
+    Ok(())
+}
+
+fn setup_memory_service() -> (TaskService, Arc<MemoryTaskStore>) {
+    let store = Arc::new(MemoryTaskStore::new());
+    let (tx, _) = mpsc::channel(1);
+    let service =
+        TaskService::with_store(store.clone(), tx).with_clock(Arc::new(TestClock::new(epoch())));
+    (service, store)
+}
+
+#[tokio::test]
+async fn test_create_task_surfaces_store_errors() {
+    let (service, store) = setup_memory_service();
+    store.fail_next(sqlx::Error::PoolTimedOut);
+
+    let req = CreateTaskReq {
+        name: "unlucky".into(),
+        task_type: "once".into(),
+        trigger_at: Some(epoch()),
+        schedule: None,
+        interval_seconds: None,
+        cron_expr: None,
+        payload: None,
+        keep_executions: None,
+        keep_days: None,
+        notifications: None,
+        group_id: None,
+        labels: None,
+        retry_policy: None,
+        priority: None,
+        misfire_policy: None,
+        interval_mode: None,
+        ends_at: None,
+        max_executions: None,
+        timezone: None,
+        rrule: None,
+        blackout_windows: None,
+        execution_windows: None,
+        depends_on: None,
+        next_task_id: None,
+        run_if: None,
+    };
+    let result = service.create_task(req, RequestContext::default()).await;
+
+    assert!(matches!(result, Err(AppError::Database(_))));
+    assert!(
+        service
+            .list_tasks(Default::default())
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+struct EchoExecutor;
+
+#[async_trait::async_trait]
+impl crate::executor::Executor for EchoExecutor {
+    async fn execute(
+        &self,
+        task: &Task,
+        ctx: &crate::executor::ExecutionContext<'_>,
+    ) -> Result<serde_json::Value, crate::executor::ExecError> {
+        Ok(json!({ "echo": task.payload["message"], "execution_id": ctx.execution_id }))
+    }
+}
+
+#[tokio::test]
+async fn test_executions_dispatch_on_the_payload_type() {
+    let (service, store) = setup_memory_service();
+    let service = service.with_executor("echo", EchoExecutor);
+
+    let req = |payload| CreateTaskReq {
+        name: "echo".into(),
+        task_type: "once".into(),
+        trigger_at: Some(epoch()),
+        schedule: None,
+        interval_seconds: None,
+        cron_expr: None,
+        payload: Some(payload),
+        keep_executions: None,
+        keep_days: None,
+        notifications: None,
+        group_id: None,
+        labels: None,
+        retry_policy: None,
+        priority: None,
+        misfire_policy: None,
+        interval_mode: None,
+        ends_at: None,
+        max_executions: None,
+        timezone: None,
+        rrule: None,
+        blackout_windows: None,
+        execution_windows: None,
+        depends_on: None,
+        next_task_id: None,
+        run_if: None,
+    };
+    let unknown = req(json!({"type": "carrier-pigeon"}));
+    assert!(matches!(
+        service
+            .create_task(unknown, RequestContext::default())
+            .await,
+        Err(AppError::ValidationError(_))
+    ));
+
+    let task = service
+        .create_task(
+            req(json!({"type": "echo", "message": "hi"})),
+            RequestContext::default(),
+        )
+        .await
+        .unwrap();
+    service.process_task(task.clone()).await.unwrap();
+
+    let executions = store
+        .list_execution_records(task.id, None, 10)
+        .await
+        .unwrap();
+    assert_eq!(executions.len(), 1);
+    assert_eq!(executions[0].status, ExecutionStatus::Success);
+    assert_eq!(executions[0].output["echo"], "hi");
+}
+
+#[tokio::test]
+async fn test_command_tasks_run_allowed_programs_only() {
+    let (service, store) = setup_memory_service();
+    let req = |payload| CreateTaskReq {
+        name: "command".into(),
+        task_type: "once".into(),
+        trigger_at: Some(epoch()),
+        schedule: None,
+        interval_seconds: None,
+        cron_expr: None,
+        payload: Some(payload),
+        keep_executions: None,
+        keep_days: None,
+        notifications: None,
+        group_id: None,
+        labels: None,
+        retry_policy: None,
+        priority: None,
+        misfire_policy: None,
+        interval_mode: None,
+        ends_at: None,
+        max_executions: None,
+        timezone: None,
+        rrule: None,
+        blackout_windows: None,
+        execution_windows: None,
+        depends_on: None,
+        next_task_id: None,
+        run_if: None,
+    };
+    let echo = json!({"type": "command", "program": "echo", "args": ["hello", "world"]});
+
+    // Off unless the config turns it on
+    assert!(matches!(
+        service
+            .create_task(req(echo.clone()), RequestContext::default())
+            .await,
+        Err(AppError::ValidationError(_))
+    ));
+
+    let service = service.with_config(crate::config::Config {
+        command_executor_enabled: true,
+        command_programs: vec!["echo".into(), "sleep".into(), "sh".into()],
+        ..crate::config::Config::default()
+    });
+    assert!(matches!(
+        service
+            .create_task(
+                req(json!({"type": "command", "program": "rm", "args": ["-rf", "/"]})),
+                RequestContext::default()
+            )
+            .await,
+        Err(AppError::ValidationError(_))
+    ));
+
+    let task = service
+        .create_task(req(echo), RequestContext::default())
+        .await
+        .unwrap();
+    let slow = service
+        .create_task(
+            req(
+                json!({"type": "command", "program": "sleep", "args": ["5"], "timeout_seconds": 1}),
+            ),
+            RequestContext::default(),
+        )
+        .await
+        .unwrap();
+    let failing = service
+        .create_task(
+            req(json!({"type": "command", "program": "sh", "args": ["-c", "echo partial; echo broken >&2; exit 3"]})),
+            RequestContext::default(),
+        )
+        .await
+        .unwrap();
+    service.process_task(task.clone()).await.unwrap();
+    service.process_task(slow.clone()).await.unwrap();
+    service.process_task(failing.clone()).await.unwrap();
+
+    let executions = store
+        .list_execution_records(task.id, None, 10)
+        .await
+        .unwrap();
+    assert_eq!(executions[0].status, ExecutionStatus::Success);
+    assert_eq!(executions[0].output["stdout"], "hello world\n");
+    assert_eq!(executions[0].output["exit_code"], 0);
+
+    let executions = store
+        .list_execution_records(slow.id, None, 10)
+        .await
+        .unwrap();
+    assert_eq!(executions[0].status, ExecutionStatus::Failure);
+    assert_eq!(
+        executions[0].output["error"],
+        "Command timed out after 1 seconds"
+    );
+    assert_eq!(executions[0].output["reason"], "timeout");
+
+    // A non-zero exit keeps what the program printed
+    let executions = store
+        .list_execution_records(failing.id, None, 10)
+        .await
+        .unwrap();
+    assert_eq!(executions[0].status, ExecutionStatus::Failure);
+    assert_eq!(
+        executions[0].output["error"],
+        "Command exited with 3: broken\n"
+    );
+    assert_eq!(executions[0].output["exit_code"], 3);
+    assert_eq!(executions[0].output["stdout"], "partial\n");
+    assert_eq!(executions[0].output["stderr"], "broken\n");
+}
+
+#[cfg(feature = "nats")]
+#[tokio::test]
+async fn test_nats_tasks_fail_while_the_server_is_unreachable() {
+    let (service, store) = setup_memory_service();
+    let service = service.with_config(crate::config::Config {
+        nats_url: Some("nats://127.0.0.1:1".into()),
+        ..crate::config::Config::default()
+    });
+    let req = |payload| CreateTaskReq {
+        name: "publish".into(),
+        task_type: "once".into(),
+        trigger_at: Some(epoch()),
+        schedule: None,
+        interval_seconds: None,
+        cron_expr: None,
+        payload: Some(payload),
+        keep_executions: None,
+        keep_days: None,
+        notifications: None,
+        group_id: None,
+        labels: None,
+        retry_policy: None,
+        priority: None,
+        misfire_policy: None,
+        interval_mode: None,
+        ends_at: None,
+        max_executions: None,
+        timezone: None,
+        rrule: None,
+        blackout_windows: None,
+        execution_windows: None,
+        depends_on: None,
+        next_task_id: None,
+        run_if: None,
+    };
+    assert!(matches!(
+        service
+            .create_task(req(json!({"type": "nats"})), RequestContext::default())
+            .await,
+        Err(AppError::ValidationError(_))
+    ));
+
+    let task = service
+        .create_task(
+            req(json!({"type": "nats", "subject": "orders.created", "body": {"id": 1}})),
+            RequestContext::default(),
+        )
+        .await
+        .unwrap();
+    service.process_task(task.clone()).await.unwrap();
+
+    let executions = store
+        .list_execution_records(task.id, None, 10)
+        .await
+        .unwrap();
+    assert_eq!(executions[0].status, ExecutionStatus::Failure);
+    assert!(
+        executions[0].output["error"]
+            .as_str()
+            .unwrap()
+            .starts_with("Failed to connect to NATS")
+    );
+}
+
+#[cfg(feature = "amqp")]
+#[tokio::test]
+async fn test_amqp_tasks_fail_while_the_broker_is_unreachable() {
+    let (service, store) = setup_memory_service();
+    let service = service.with_config(crate::config::Config {
+        amqp_url: Some("amqp://127.0.0.1:1".into()),
+        ..crate::config::Config::default()
+    });
+    let req = |payload| CreateTaskReq {
+        name: "publish".into(),
+        task_type: "once".into(),
+        trigger_at: Some(epoch()),
+        schedule: None,
+        interval_seconds: None,
+        cron_expr: None,
+        payload: Some(payload),
+        keep_executions: None,
+        keep_days: None,
+        notifications: None,
+        group_id: None,
+        labels: None,
+        retry_policy: None,
+        priority: None,
+        misfire_policy: None,
+        interval_mode: None,
+        ends_at: None,
+        max_executions: None,
+        timezone: None,
+        rrule: None,
+        blackout_windows: None,
+        execution_windows: None,
+        depends_on: None,
+        next_task_id: None,
+        run_if: None,
+    };
+    assert!(matches!(
+        service
+            .create_task(
+                req(json!({"type": "amqp", "routing_key": ""})),
+                RequestContext::default()
+            )
+            .await,
+        Err(AppError::ValidationError(_))
+    ));
+
+    let task = service
+        .create_task(
+            req(json!({"type": "amqp", "exchange": "events", "routing_key": "orders.created"})),
+            RequestContext::default(),
+        )
+        .await
+        .unwrap();
+    service.process_task(task.clone()).await.unwrap();
+
+    let executions = store
+        .list_execution_records(task.id, None, 10)
+        .await
+        .unwrap();
+    assert_eq!(executions[0].status, ExecutionStatus::Failure);
+    assert!(
+        executions[0].output["error"]
+            .as_str()
+            .unwrap()
+            .starts_with("Failed to connect to RabbitMQ")
+    );
+}
+
+#[cfg(feature = "redis")]
+#[tokio::test]
+async fn test_redis_tasks_issue_allowed_commands_only() {
+    let (service, store) = setup_memory_service();
+    let service = service.with_config(crate::config::Config {
+        redis_url: Some("redis://127.0.0.1:1".into()),
+        ..crate::config::Config::default()
+    });
+    let req = |payload| CreateTaskReq {
+        name: "invalidate".into(),
+        task_type: "once".into(),
+        trigger_at: Some(epoch()),
+        schedule: None,
+        interval_seconds: None,
+        cron_expr: None,
+        payload: Some(payload),
+        keep_executions: None,
+        keep_days: None,
+        notifications: None,
+        group_id: None,
+        labels: None,
+        retry_policy: None,
+        priority: None,
+        misfire_policy: None,
+        interval_mode: None,
+        ends_at: None,
+        max_executions: None,
+        timezone: None,
+        rrule: None,
+        blackout_windows: None,
+        execution_windows: None,
+        depends_on: None,
+        next_task_id: None,
+        run_if: None,
+    };
+    assert!(matches!(
+        service
+            .create_task(
+                req(json!({"type": "redis", "command": "flushall"})),
+                RequestContext::default()
+            )
+            .await,
+        Err(AppError::ValidationError(_))
+    ));
+
+    let task = service
+        .create_task(
+            req(json!({"type": "redis", "command": "del", "args": ["cache:home"]})),
+            RequestContext::default(),
+        )
+        .await
+        .unwrap();
+    service.process_task(task.clone()).await.unwrap();
+
+    let executions = store
+        .list_execution_records(task.id, None, 10)
+        .await
+        .unwrap();
+    assert_eq!(executions[0].status, ExecutionStatus::Failure);
+    assert!(
+        executions[0].output["error"]
+            .as_str()
+            .unwrap()
+            .starts_with("Failed to connect to Redis")
+    );
+}
+
+#[cfg(feature = "scripting")]
+#[tokio::test]
+async fn test_script_tasks_record_their_result_within_budget() {
+    let (service, store) = setup_memory_service();
+    let service = service.with_config(crate::config::Config {
+        script_max_operations: 10_000,
+        ..crate::config::Config::default()
+    });
+    let req = |payload| CreateTaskReq {
+        name: "glue".into(),
+        task_type: "once".into(),
+        trigger_at: Some(epoch()),
+        schedule: None,
+        interval_seconds: None,
+        cron_expr: None,
+        payload: Some(payload),
+        keep_executions: None,
+        keep_days: None,
+        notifications: None,
+        group_id: None,
+        labels: None,
+        retry_policy: None,
+        priority: None,
+        misfire_policy: None,
+        interval_mode: None,
+        ends_at: None,
+        max_executions: None,
+        timezone: None,
+        rrule: None,
+        blackout_windows: None,
+        execution_windows: None,
+        depends_on: None,
+        next_task_id: None,
+        run_if: None,
+    };
+    assert!(matches!(
+        service
+            .create_task(
+                req(json!({"type": "script", "script": "let = ;"})),
+                RequestContext::default()
+            )
+            .await,
+        Err(AppError::ValidationError(_))
+    ));
+
+    let task = service
+        .create_task(
+            req(json!({"type": "script", "script": "print(\"doubling\"); #{ answer: payload.n * 2 }", "n": 21})),
+            RequestContext::default(),
+        )
+        .await
+        .unwrap();
+    let runaway = service
+        .create_task(
+            req(json!({"type": "script", "script": "loop { }"})),
+            RequestContext::default(),
+        )
+        .await
+        .unwrap();
+    service.process_task(task.clone()).await.unwrap();
+    service.process_task(runaway.clone()).await.unwrap();
+
+    let executions = store
+        .list_execution_records(task.id, None, 10)
+        .await
+        .unwrap();
+    assert_eq!(executions[0].status, ExecutionStatus::Success);
+    assert_eq!(
+        executions[0].output,
+        json!({"result": {"answer": 42}, "log": ["doubling"]})
+    );
+
+    let executions = store
+        .list_execution_records(runaway.id, None, 10)
+        .await
+        .unwrap();
+    assert_eq!(executions[0].status, ExecutionStatus::Failure);
+    assert_eq!(
+        executions[0].output["error"],
+        "Script exceeded 10000 operations"
+    );
+}
+
+#[cfg(feature = "wasm")]
+#[tokio::test]
+async fn test_wasm_plugins_run_from_the_plugins_directory() {
+    let dir = std::env::temp_dir().join(format!("scheduler-plugins-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    // wasmtime also compiles the text format, which keeps the fixtures readable
+    std::fs::write(
+        dir.join("echo.wasm"),
+        r#"(module
+            (memory (export "memory") 1)
+            (func (export "alloc") (param i32) (result i32) i32.const 1024)
+            (func (export "run") (param $ptr i32) (param $len i32) (result i64)
+                local.get $ptr i64.extend_i32_u i64.const 32 i64.shl
+                local.get $len i64.extend_i32_u i64.or))"#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("spin.wasm"),
+        r#"(module
+            (memory (export "memory") 1)
+            (func (export "alloc") (param i32) (result i32) i32.const 0)
+            (func (export "run") (param i32 i32) (result i64) (loop (br 0)) i64.const 0))"#,
+    )
+    .unwrap();
+
+    let (service, store) = setup_memory_service();
+    let service = service.with_config(crate::config::Config {
+        plugins_dir: Some(dir.clone()),
+        wasm_max_fuel: 100_000,
+        ..crate::config::Config::default()
+    });
+    let req = |payload| CreateTaskReq {
+        name: "plugin".into(),
+        task_type: "once".into(),
+        trigger_at: Some(epoch()),
+        schedule: None,
+        interval_seconds: None,
+        cron_expr: None,
+        payload: Some(payload),
+        keep_executions: None,
+        keep_days: None,
+        notifications: None,
+        group_id: None,
+        labels: None,
+        retry_policy: None,
+        priority: None,
+        misfire_policy: None,
+        interval_mode: None,
+        ends_at: None,
+        max_executions: None,
+        timezone: None,
+        rrule: None,
+        blackout_windows: None,
+        execution_windows: None,
+        depends_on: None,
+        next_task_id: None,
+        run_if: None,
+    };
+    assert!(matches!(
+        service
+            .create_task(
+                req(json!({"type": "wasm", "plugin": "../echo"})),
+                RequestContext::default()
+            )
+            .await,
+        Err(AppError::ValidationError(_))
+    ));
+
+    let payload = json!({"type": "wasm", "plugin": "echo", "n": 1});
+    let echo = service
+        .create_task(req(payload.clone()), RequestContext::default())
+        .await
+        .unwrap();
+    let spin = service
+        .create_task(
+            req(json!({"type": "wasm", "plugin": "spin"})),
+            RequestContext::default(),
+        )
+        .await
+        .unwrap();
+    service.process_task(echo.clone()).await.unwrap();
+    service.process_task(spin.clone()).await.unwrap();
+
+    let executions = store
+        .list_execution_records(echo.id, None, 10)
+        .await
+        .unwrap();
+    assert_eq!(executions[0].status, ExecutionStatus::Success);
+    assert_eq!(executions[0].output, payload);
+
+    let executions = store
+        .list_execution_records(spin.id, None, 10)
+        .await
+        .unwrap();
+    assert_eq!(executions[0].status, ExecutionStatus::Failure);
+    assert_eq!(
+        executions[0].output["error"],
+        "Plugin 'spin' ran out of fuel"
+    );
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_clone_task_copies_definition_with_overrides() {
+    let (service, store) = setup_memory_service();
+    let source = Task::new_interval(
+        "report-staging",
+        epoch(),
+        300,
+        json!({ "url": "https://staging.example.com/hook", "retries": 3 }),
+    );
+    store.create_task(&source).await.unwrap();
+
+    let req = CloneTaskReq {
+        name: Some("report-prod".into()),
+        payload: Some(json!({ "url": "https://prod.example.com/hook" })),
+        ..Default::default()
+    };
+    let id = service
+        .clone_task(
+            source.id,
+            req,
+            RequestContext {
+                request_id: Some("req-1".into()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap()
+        .id;
+
+    let copy = store.get_task(id).await.unwrap().unwrap();
+    assert_ne!(copy.id, source.id);
+    assert_eq!(copy.name, "report-prod");
+    assert_eq!(copy.task_type, source.task_type);
+    assert_eq!(copy.interval_seconds, Some(300));
+    assert_eq!(copy.trigger_at, source.trigger_at);
+    assert_eq!(
+        copy.payload,
+        json!({ "url": "https://prod.example.com/hook", "retries": 3 })
+    );
+    assert_eq!(copy.request_id.as_deref(), Some("req-1"));
+    assert!(copy.signing_secret.is_some(), "Clones get their own secret");
+
+    store.delete_task(source.id).await.unwrap();
+    let result = service
+        .clone_task(
+            source.id,
+            CloneTaskReq::default(),
+            RequestContext::default(),
+        )
+        .await;
+    assert!(matches!(result, Err(AppError::NotFound)));
+}
+
+#[tokio::test]
+async fn test_rotated_secrets_sign_alongside_the_new_one_until_expiry() {
+    use crate::service::signing::{sign, signature_header};
+
+    let (service, store) = setup_memory_service();
+    let req = CreateTaskReq {
+        name: "signed".into(),
+        task_type: "once".into(),
+        trigger_at: Some(epoch()),
+        schedule: None,
+        interval_seconds: None,
+        cron_expr: None,
+        payload: None,
+        keep_executions: None,
+        keep_days: None,
+        notifications: None,
+        group_id: None,
+        labels: None,
+        retry_policy: None,
+        priority: None,
+        misfire_policy: None,
+        interval_mode: None,
+        ends_at: None,
+        max_executions: None,
+        timezone: None,
+        rrule: None,
+        blackout_windows: None,
+        execution_windows: None,
+        depends_on: None,
+        next_task_id: None,
+        run_if: None,
+    };
+    let task = service
+        .create_task(req, RequestContext::default())
+        .await
+        .unwrap();
+    let original = task.signing_secret.clone().expect("Secret is generated");
+    let ts = epoch().timestamp();
+
+    assert_eq!(
+        signature_header(&task, None, epoch(), b"{}").unwrap(),
+        format!("t={},v1={}", ts, sign(&original, ts, b"{}"))
+    );
+
+    let (rotated, expires_at) = service
+        .rotate_signing_secret(task.id, Duration::hours(1))
+        .await
+        .unwrap();
+    assert_ne!(rotated, original);
+    assert_eq!(expires_at, epoch() + Duration::hours(1));
+
+    let task = store.get_task(task.id).await.unwrap().unwrap();
+    assert_eq!(
+        signature_header(&task, None, epoch(), b"{}").unwrap(),
+        format!(
+            "t={},v1={},v1={}",
+            ts,
+            sign(&rotated, ts, b"{}"),
+            sign(&original, ts, b"{}")
+        )
+    );
+    let after = expires_at.timestamp();
+    assert_eq!(
+        signature_header(&task, None, expires_at, b"{}").unwrap(),
+        format!("t={},v1={}", after, sign(&rotated, after, b"{}"))
+    );
+
+    let missing = service
+        .rotate_signing_secret(uuid::Uuid::new_v4(), Duration::hours(1))
+        .await;
+    assert!(matches!(missing, Err(AppError::NotFound)));
+}
+
+#[tokio::test]
+async fn test_trigger_links_fire_once_before_expiry() {
+    let store = Arc::new(MemoryTaskStore::new());
+    let clock = Arc::new(TestClock::new(epoch()));
+    let (tx, _) = mpsc::channel(1);
+    let service = TaskService::with_store(store.clone(), tx).with_clock(clock.clone());
+
+    let req = CreateTaskReq {
+        name: "linked".into(),
+        task_type: "interval".into(),
+        trigger_at: Some(epoch() + Duration::days(1)),
+        schedule: None,
+        interval_seconds: Some(3600),
+        cron_expr: None,
+        payload: None,
+        keep_executions: None,
+        keep_days: None,
+        notifications: None,
+        group_id: None,
+        labels: None,
+        retry_policy: None,
+        priority: None,
+        misfire_policy: None,
+        interval_mode: None,
+        ends_at: None,
+        max_executions: None,
+        timezone: None,
+        rrule: None,
+        blackout_windows: None,
+        execution_windows: None,
+        depends_on: None,
+        next_task_id: None,
+        run_if: None,
+    };
+    let task = service
+        .create_task(req, RequestContext::default())
+        .await
+        .unwrap();
+
+    let (link, signature) = service
+        .create_trigger_link(task.id, Duration::hours(1), None)
+        .await
+        .unwrap();
+    let (expiring, expiring_signature) = service
+        .create_trigger_link(task.id, Duration::minutes(1), None)
+        .await
+        .unwrap();
+
+    let forged = service.fire_trigger_link(link.id, "deadbeef").await;
+    assert!(matches!(forged, Err(AppError::NotFound)));
+
+    let execution = service
+        .fire_trigger_link(link.id, &signature)
+        .await
+        .unwrap();
+    assert_eq!(execution.task_id, task.id);
+    assert_eq!(store.executions().len(), 1);
+    assert_eq!(
+        store.get_task(task.id).await.unwrap().unwrap().trigger_at,
+        task.trigger_at,
+        "Triggered runs leave the schedule alone"
+    );
+
+    let reused = service.fire_trigger_link(link.id, &signature).await;
+    assert!(matches!(reused, Err(AppError::NotFound)));
+
+    clock.advance(Duration::minutes(2));
+    let expired = service
+        .fire_trigger_link(expiring.id, &expiring_signature)
+        .await;
+    assert!(matches!(expired, Err(AppError::NotFound)));
+    assert_eq!(store.executions().len(), 1);
+}
+
+#[tokio::test]
+async fn test_process_batch_reports_vanished_tasks_as_removed() {
+    let (service, store) = setup_memory_service();
+
+    let kept = Task::new_interval("kept", epoch(), 60, json!({}));
+    let vanished = Task::new_once("vanished", epoch(), json!({}));
+    store.create_task(&kept).await.unwrap();
+    store.create_task(&vanished).await.unwrap();
+    store.remove_task(vanished.id);
+
+    let changes = service
+        .process_batch(vec![kept.clone(), vanished.clone()])
+        .await
+        .expect("Batch processing failed");
+
+    assert_eq!(
+        changes,
+        vec![
+            ScheduleEvent::Upserted {
+                id: kept.id,
+                trigger_at: epoch() + Duration::seconds(60),
+            },
+            ScheduleEvent::Removed { id: vanished.id },
+        ]
+    );
+    assert_eq!(store.executions().len(), 1);
+}
+
+#[tokio::test]
+async fn test_process_batch_write_failure_keeps_tasks_due() {
+    let (service, store) = setup_memory_service();
+
+    let task = Task::new_once("retry_me", epoch(), json!({}));
+    store.create_task(&task).await.unwrap();
+    store.fail_next(sqlx::Error::PoolTimedOut);
+
+    let result = service.process_batch(vec![task.clone()]).await;
+
+    assert!(matches!(result, Err(AppError::Database(_))));
+    assert!(store.executions().is_empty());
+    assert_eq!(store.get_due_tasks(epoch(), 10).await.unwrap(), vec![task]);
+}
+
+#[tokio::test]
+async fn test_results_buffered_during_outage_are_flushed_once() {
+    let (service, store) = setup_memory_service();
+
+    let task = Task::new_once("outage", epoch(), json!({}));
+    store.create_task(&task).await.unwrap();
+
+    // The running record is written, then the final write fails during the outage and the
+    // first flush attempt too
+    store.pass_next();
+    store.fail_next(sqlx::Error::PoolTimedOut);
+    store.fail_next(sqlx::Error::PoolTimedOut);
+    assert!(service.process_batch(vec![task.clone()]).await.is_err());
+    assert!(service.has_unflushed());
+    assert!(service.flush_unflushed().await.is_err());
+    assert!(service.has_unflushed(), "Results survive a failed flush");
+
+    let changes = service.flush_unflushed().await.unwrap();
+    assert_eq!(changes, vec![ScheduleEvent::Removed { id: task.id }]);
+    assert!(!service.has_unflushed());
+    assert_eq!(store.executions().len(), 1);
+    assert!(store.get_due_tasks(epoch(), 10).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_permanent_write_errors_are_not_buffered() {
+    let (service, store) = setup_memory_service();
+
+    let task = Task::new_once("rejected", epoch(), json!({}));
+    store.create_task(&task).await.unwrap();
+    store.pass_next();
+    store.fail_next(sqlx::Error::RowNotFound);
+
+    assert!(service.process_batch(vec![task]).await.is_err());
+    assert!(!service.has_unflushed());
+}
+
+#[tokio::test]
+async fn test_misfire_policies_catch_up_missed_runs() {
+    let (service, store) = setup_memory_service();
+    let missed_at = epoch() - Duration::minutes(5);
+    let [fire_once, fire_all, skip] = [
+        MisfirePolicy::FireOnce,
+        MisfirePolicy::FireAll,
+        MisfirePolicy::Skip,
+    ]
+    .map(|misfire_policy| Task {
+        misfire_policy,
+        ..Task::new_interval("minutely", missed_at, 60, json!({}))
+    });
+    for task in [&fire_once, &fire_all, &skip] {
+        store.create_task(task).await.unwrap();
+    }
+
+    let changes = service
+        .process_batch(vec![fire_once.clone(), fire_all.clone(), skip.clone()])
+        .await
+        .unwrap();
+
+    let ran: Vec<uuid::Uuid> = store.executions().iter().map(|e| e.task_id).collect();
+    assert_eq!(ran, vec![fire_once.id, fire_all.id]);
+    for (task, trigger_at) in [
+        // Carries on from now
+        (&fire_once, epoch() + Duration::minutes(1)),
+        // The next missed run is due right away
+        (&fire_all, missed_at + Duration::minutes(1)),
+        // Nothing ran; the next run keeps the task's phase
+        (&skip, missed_at + Duration::minutes(6)),
+    ] {
+        assert!(changes.contains(&ScheduleEvent::Upserted {
+            id: task.id,
+            trigger_at
+        }));
+    }
+}
+
+#[tokio::test]
+async fn test_fixed_rate_intervals_keep_their_phase() {
+    let (service, store) = setup_memory_service();
+    let late = epoch() - Duration::seconds(10);
+    let missed = epoch() - Duration::seconds(150);
+    let [fixed_delay, fixed_rate, fixed_rate_missed] = [
+        (late, IntervalMode::FixedDelay),
+        (late, IntervalMode::FixedRate),
+        (missed, IntervalMode::FixedRate),
+    ]
+    .map(|(trigger_at, interval_mode)| Task {
+        interval_mode,
+        ..Task::new_interval("minutely", trigger_at, 60, json!({}))
+    });
+    for task in [&fixed_delay, &fixed_rate, &fixed_rate_missed] {
+        store.create_task(task).await.unwrap();
+    }
+
+    let changes = service
+        .process_batch(vec![
+            fixed_delay.clone(),
+            fixed_rate.clone(),
+            fixed_rate_missed.clone(),
+        ])
+        .await
+        .unwrap();
+
+    assert_eq!(store.executions().len(), 3);
+    for (task, trigger_at) in [
+        // Counts from when the run finished
+        (&fixed_delay, epoch() + Duration::seconds(60)),
+        // Counts from when the run was due
+        (&fixed_rate, late + Duration::seconds(60)),
+        // Runs missed in between fire once, then the phase carries on
+        (&fixed_rate_missed, missed + Duration::seconds(180)),
+    ] {
+        assert!(changes.contains(&ScheduleEvent::Upserted {
+            id: task.id,
+            trigger_at
+        }));
+    }
+}
+
+#[tokio::test]
+async fn test_recurring_tasks_complete_at_their_end_condition() {
+    let (service, store) = setup_memory_service();
+    let service = service.with_executor("echo", EchoExecutor);
+    let echo = json!({"type": "echo", "message": "hi"});
+    let twice = Task {
+        max_executions: Some(2),
+        ..Task::new_interval("twice", epoch(), 60, echo.clone())
+    };
+    let until = Task {
+        ends_at: Some(epoch() + Duration::seconds(30)),
+        ..Task::new_interval("until", epoch(), 60, echo)
+    };
+    store.create_task(&twice).await.unwrap();
+    store.create_task(&until).await.unwrap();
+
+    let changes = service
+        .process_batch(vec![twice.clone(), until.clone()])
+        .await
+        .unwrap();
+    assert!(changes.contains(&ScheduleEvent::Upserted {
+        id: twice.id,
+        trigger_at: epoch() + Duration::seconds(60),
+    }));
+    assert!(changes.contains(&ScheduleEvent::Removed { id: until.id }));
+
+    let twice = store.get_task(twice.id).await.unwrap().unwrap();
+    assert_eq!(twice.run_count, 1);
+    let changes = service.process_batch(vec![twice.clone()]).await.unwrap();
+    assert_eq!(changes, vec![ScheduleEvent::Removed { id: twice.id }]);
+
+    for id in [twice.id, until.id] {
+        let task = store.get_task(id).await.unwrap().unwrap();
+        assert!(task.deleted_at.is_some());
+        assert_eq!(task.run_state, RunState::Completed);
+    }
+}
+
+#[tokio::test]
+async fn test_running_tasks_are_not_started_twice() {
+    let store = Arc::new(MemoryTaskStore::new());
+    let clock = Arc::new(TestClock::new(epoch()));
+    let (tx, _) = mpsc::channel(1);
+    let service = TaskService::with_store(store.clone(), tx).with_clock(clock.clone());
+    let task = Task::new_interval("long_sync", epoch(), 60, json!({}));
+    store.create_task(&task).await.unwrap();
+
+    // Another instance is running the task
+    let running = Execution {
+        executed_at: epoch(),
+        ..Execution::new(task.id, json!({}), ExecutionStatus::Running)
+    };
+    let peer = Claim {
+        owner: "peer".into(),
+        expires_at: epoch() + Duration::minutes(1),
+    };
+    store.begin_executions(&[running], &peer).await.unwrap();
+
+    let changes = service.process_batch(vec![task.clone()]).await.unwrap();
+    assert_eq!(changes, vec![ScheduleEvent::Removed { id: task.id }]);
+    assert!(matches!(
+        service.run_task(task.id, false).await,
+        Err(AppError::Conflict(_))
+    ));
+    assert!(service.reclaim_expired().await.unwrap().is_empty());
+    assert_eq!(store.executions().len(), 1);
+
+    // The peer dies; once its claim lapses, its run counts as interrupted
+    clock.advance(Duration::minutes(2));
+    let changes = service.reclaim_expired().await.unwrap();
+    assert_eq!(
+        changes,
+        vec![ScheduleEvent::Upserted {
+            id: task.id,
+            trigger_at: epoch() + Duration::minutes(3),
+        }]
+    );
+    assert_eq!(store.executions()[0].status, ExecutionStatus::Interrupted);
+    service.run_task(task.id, false).await.unwrap();
+    assert_eq!(store.executions().len(), 2);
+}
+
+#[tokio::test]
+async fn test_interrupted_executions_count_as_runs() {
+    let (service, store) = setup_memory_service();
+
+    let once = Task::new_once("cut_short", epoch(), json!({}));
+    let recurring = Task::new_interval("cut_short_recurring", epoch(), 60, json!({}));
+    store.create_task(&once).await.unwrap();
+    store.create_task(&recurring).await.unwrap();
+
+    // A previous process crashed after writing its running records
+    let running: Vec<Execution> = [&once, &recurring]
+        .iter()
+        .map(|task| Execution::new(task.id, json!({}), ExecutionStatus::Running))
+        .collect();
+    let crashed = Claim {
+        owner: "crashed".into(),
+        expires_at: epoch() - Duration::minutes(1),
+    };
+    store.begin_executions(&running, &crashed).await.unwrap();
+
+    let changes = service.recover_interrupted().await.unwrap();
+
+    assert_eq!(
+        changes,
+        vec![
+            ScheduleEvent::Removed { id: once.id },
+            ScheduleEvent::Upserted {
+                id: recurring.id,
+                trigger_at: epoch() + Duration::seconds(60),
+            },
+        ]
+    );
+    let executions = store.executions();
+    assert_eq!(
+        executions.len(),
+        2,
+        "Running records are finalized in place"
+    );
+    assert!(
+        executions
+            .iter()
+            .all(|e| e.status == ExecutionStatus::Interrupted)
     );
+    assert!(store.get_due_tasks(epoch(), 10).await.unwrap().is_empty());
+    assert!(store.running_executions().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_skip_next_and_cadence_reset_move_the_trigger() {
+    let (service, store) = setup_memory_service();
+
+    let recurring = Task::new_interval("hourly", epoch() + Duration::minutes(30), 3600, json!({}));
+    let once = Task::new_once("one_off", epoch() + Duration::minutes(30), json!({}));
+    store.create_task(&recurring).await.unwrap();
+    store.create_task(&once).await.unwrap();
+
+    let skipped = service.skip_next(recurring.id).await.unwrap();
+    assert_eq!(
+        skipped.trigger_at,
+        recurring.trigger_at + Duration::hours(1)
+    );
+    assert!(matches!(
+        service.skip_next(once.id).await,
+        Err(AppError::ValidationError(_))
+    ));
+
+    // Running early without a reset keeps the schedule
+    service.run_task(recurring.id, false).await.unwrap();
+    let stored = store.get_task(recurring.id).await.unwrap().unwrap();
+    assert_eq!(stored.trigger_at, skipped.trigger_at);
+
+    service.run_task(recurring.id, true).await.unwrap();
+    let stored = store.get_task(recurring.id).await.unwrap().unwrap();
+    assert_eq!(stored.trigger_at, epoch() + Duration::hours(1));
+    assert_eq!(store.executions().len(), 2);
+    assert!(matches!(
+        service.run_task(once.id, true).await,
+        Err(AppError::ValidationError(_))
+    ));
+}
+
+#[derive(Default)]
+struct RecordingNotifier {
+    sent: std::sync::Mutex<Vec<(NotificationChannel, NotificationEvent)>>,
+}
+
+#[async_trait::async_trait]
+impl Notifier for RecordingNotifier {
+    async fn send(
+        &self,
+        channel: &NotificationChannel,
+        notification: &Notification,
+    ) -> Result<(), String> {
+        self.sent
+            .lock()
+            .unwrap()
+            .push((channel.clone(), notification.event));
+        Ok(())
+    }
+}
+
+impl RecordingNotifier {
+    /// Waits for background deliveries to arrive, returning everything sent so far.
+    async fn wait_for(&self, count: usize) -> Vec<(NotificationChannel, NotificationEvent)> {
+        for _ in 0..50 {
+            if self.sent.lock().unwrap().len() >= count {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        self.sent.lock().unwrap().clone()
+    }
+}
+
+#[tokio::test]
+async fn test_notifications_follow_the_task_policy() {
+    let (service, store) = setup_memory_service();
+    let notifier = Arc::new(RecordingNotifier::default());
+    let service = service.with_notifier(notifier.clone());
+
+    let slack = NotificationChannel::Slack {
+        webhook_url: "https://hooks.slack.com/services/T0/B0/x".into(),
+    };
+    let callback = NotificationChannel::Callback {
+        url: "https://example.com/notify".into(),
+    };
+    let task = Task {
+        notifications: Some(Notifications {
+            routes: vec![
+                NotificationRoute {
+                    events: vec![NotificationEvent::Failure],
+                    channel: slack.clone(),
+                },
+                NotificationRoute {
+                    events: vec![NotificationEvent::Recovery, NotificationEvent::MissedRun],
+                    channel: callback.clone(),
+                },
+            ],
+        }),
+        ..Task::new_interval("nightly", epoch() - Duration::minutes(10), 3600, json!({}))
+    };
+    store.create_task(&task).await.unwrap();
+
+    // Without a URL the run fails, and it starts ten minutes after its trigger
+    service.process_task(task.clone()).await.unwrap();
+    let sent = notifier.wait_for(2).await;
+    assert_eq!(sent.len(), 2);
+    assert!(sent.contains(&(slack.clone(), NotificationEvent::Failure)));
+    assert!(sent.contains(&(callback.clone(), NotificationEvent::MissedRun)));
+
+    // The stub turns the next, punctual run into a success
+    let chain = crate::executor::MiddlewareChain::new().with(StubMiddleware);
+    let punctual = Task {
+        trigger_at: epoch(),
+        ..task
+    };
+    service
+        .with_middleware(chain)
+        .process_task(punctual)
+        .await
+        .unwrap();
+    let sent = notifier.wait_for(3).await;
+    assert_eq!(sent.len(), 3, "Only the recovery is sent: {:?}", sent);
+    assert_eq!(sent[2], (callback, NotificationEvent::Recovery));
+}
+
+/// Makes every execution take `by` on the test clock.
+struct SlowMiddleware {
+    clock: Arc<TestClock>,
+    by: Duration,
+}
+
+#[async_trait::async_trait]
+impl crate::executor::ExecutionMiddleware for SlowMiddleware {
+    async fn before(&self, _task: &mut Task) -> Result<(), String> {
+        self.clock.advance(self.by);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_runs_far_slower_than_the_median_are_reported() {
+    let store = Arc::new(MemoryTaskStore::new());
+    let clock = Arc::new(TestClock::new(epoch()));
+    let notifier = Arc::new(RecordingNotifier::default());
+    let (tx, _) = mpsc::channel(1);
+    let service = TaskService::with_store(store.clone(), tx)
+        .with_clock(clock.clone())
+        .with_notifier(notifier.clone());
+
+    let callback = NotificationChannel::Callback {
+        url: "https://example.com/notify".into(),
+    };
+    let task = Task {
+        notifications: Some(Notifications {
+            routes: vec![NotificationRoute {
+                events: vec![NotificationEvent::SlowRun],
+                channel: callback.clone(),
+            }],
+        }),
+        ..Task::new_interval("nightly_export", epoch(), 3600, json!({}))
+    };
+    store.create_task(&task).await.unwrap();
+    let history: Vec<Execution> = (0..5)
+        .map(|i| Execution {
+            executed_at: epoch() - Duration::days(i + 1),
+            duration_ms: Some(100),
+            ..Execution::new(task.id, json!({}), ExecutionStatus::Success)
+        })
+        .collect();
+    let settled = Claim {
+        owner: "earlier".into(),
+        expires_at: epoch() - Duration::days(10),
+    };
+    store.begin_executions(&history, &settled).await.unwrap();
+
+    // Twice the median is within range
+    let chain = crate::executor::MiddlewareChain::new().with(SlowMiddleware {
+        clock: clock.clone(),
+        by: Duration::milliseconds(200),
+    });
+    let execution = service
+        .clone()
+        .with_middleware(chain)
+        .run_task(task.id, false)
+        .await
+        .unwrap();
+    assert_eq!(execution.duration_ms, Some(200));
+    assert!(notifier.wait_for(1).await.is_empty());
+
+    let chain = crate::executor::MiddlewareChain::new().with(SlowMiddleware {
+        clock,
+        by: Duration::seconds(5),
+    });
+    service
+        .with_middleware(chain)
+        .run_task(task.id, false)
+        .await
+        .unwrap();
+    assert_eq!(
+        notifier.wait_for(1).await,
+        vec![(callback, NotificationEvent::SlowRun)]
+    );
+}
+
+#[tokio::test]
+async fn test_webhook_output_is_redacted_before_it_is_recorded() {
+    let hook = axum::Router::new().route(
+        "/hook",
+        axum::routing::post(|| async {
+            axum::Json(json!({ "user": "ada", "session": { "token": "s3cr3t" }, "api_key": "k" }))
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, hook).await.unwrap() });
+
+    let store = Arc::new(MemoryTaskStore::new());
+    let (tx, _) = mpsc::channel(1);
+    let config = crate::config::Config {
+        webhook_log_level: Some(tracing::Level::DEBUG),
+        webhook_redaction: crate::executor::Redaction::new(["x-api-key"], ["api_key"]).unwrap(),
+        ..Default::default()
+    };
+    let service = TaskService::with_store(store.clone(), tx).with_config(config);
+
+    let payload = json!({
+        "url": url,
+        "method": "POST",
+        "debug": { "level": "info", "redact_paths": ["$.session.token"] },
+    });
+    let task = Task::new_once("debugged", epoch(), payload);
+    store.create_task(&task).await.unwrap();
+
+    let execution = service.run_task(task.id, false).await.unwrap();
+    let response: serde_json::Value =
+        serde_json::from_str(execution.output["response"].as_str().unwrap()).unwrap();
+    assert_eq!(
+        response,
+        json!({ "user": "ada", "session": { "token": "[REDACTED]" }, "api_key": "[REDACTED]" })
+    );
+
+    let req = CreateTaskReq {
+        name: "bad debug".into(),
+        task_type: "once".into(),
+        trigger_at: Some(epoch()),
+        schedule: None,
+        interval_seconds: None,
+        cron_expr: None,
+        payload: Some(json!({ "url": url, "debug": { "redact_paths": ["session..token"] } })),
+        keep_executions: None,
+        keep_days: None,
+        notifications: None,
+        group_id: None,
+        labels: None,
+        retry_policy: None,
+        priority: None,
+        misfire_policy: None,
+        interval_mode: None,
+        ends_at: None,
+        max_executions: None,
+        timezone: None,
+        rrule: None,
+        blackout_windows: None,
+        execution_windows: None,
+        depends_on: None,
+        next_task_id: None,
+        run_if: None,
+    };
+    let result = service.create_task(req, RequestContext::default()).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+}
+
+#[tokio::test]
+async fn test_webhook_headers_and_credentials_are_sent_but_not_recorded() {
+    let hook = axum::Router::new().route(
+        "/hook",
+        axum::routing::get(|headers: axum::http::HeaderMap| async move {
+            let header = |name: &str| headers[name].to_str().unwrap().to_string();
+            axum::Json(json!({ "tenant": header("x-tenant"), "auth": header("authorization") }))
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, hook).await.unwrap() });
+
+    let (service, store) = setup_memory_service();
+    let payload = json!({
+        "url": url,
+        "headers": { "X-Tenant": "acme" },
+        "auth": { "type": "bearer", "token": "t0k3n" },
+    });
+    let task = Task::new_once("authenticated", epoch(), payload);
+    store.create_task(&task).await.unwrap();
+
+    let execution = service.run_task(task.id, false).await.unwrap();
+    assert_eq!(execution.status, ExecutionStatus::Success);
+    let response: serde_json::Value =
+        serde_json::from_str(execution.output["response"].as_str().unwrap()).unwrap();
+    assert_eq!(
+        response,
+        json!({ "tenant": "acme", "auth": "Bearer [REDACTED]" })
+    );
+
+    let req = CreateTaskReq {
+        name: "bad auth".into(),
+        task_type: "once".into(),
+        trigger_at: Some(epoch()),
+        schedule: None,
+        interval_seconds: None,
+        cron_expr: None,
+        payload: Some(json!({ "url": url, "auth": { "type": "digest" } })),
+        keep_executions: None,
+        keep_days: None,
+        notifications: None,
+        group_id: None,
+        labels: None,
+        retry_policy: None,
+        priority: None,
+        misfire_policy: None,
+        interval_mode: None,
+        ends_at: None,
+        max_executions: None,
+        timezone: None,
+        rrule: None,
+        blackout_windows: None,
+        execution_windows: None,
+        depends_on: None,
+        next_task_id: None,
+        run_if: None,
+    };
+    let result = service.create_task(req, RequestContext::default()).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+}
+
+#[tokio::test]
+async fn test_oauth2_tokens_are_fetched_once_and_sent_as_bearer() {
+    let issued = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let count = issued.clone();
+    let hook = axum::Router::new()
+        .route(
+            "/token",
+            axum::routing::post(move |headers: axum::http::HeaderMap, form: String| {
+                let count = count.clone();
+                async move {
+                    assert!(headers.contains_key("authorization"));
+                    assert!(form.contains("grant_type=client_credentials"));
+                    count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                    axum::Json(json!({ "access_token": "acc3ss", "expires_in": 3600 }))
+                }
+            }),
+        )
+        .route(
+            "/hook",
+            axum::routing::get(|headers: axum::http::HeaderMap| async move {
+                headers["authorization"].to_str().unwrap().to_string()
+            }),
+        );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, hook).await.unwrap() });
+
+    let (service, store) = setup_memory_service();
+    let payload = json!({
+        "url": format!("{}/hook", base),
+        "auth": {
+            "type": "oauth2",
+            "token_url": format!("{}/token", base),
+            "client_id": "scheduler",
+            "client_secret": "s3cret",
+        },
+    });
+    let task = Task::new_once("oauth", epoch(), payload);
+    store.create_task(&task).await.unwrap();
+
+    for _ in 0..2 {
+        let execution = service.run_task(task.id, false).await.unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Success);
+        assert_eq!(execution.output["response"], "Bearer [REDACTED]");
+    }
+    assert_eq!(issued.load(std::sync::atomic::Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_webhook_urls_and_bodies_are_rendered_with_the_run_context() {
+    let hook = axum::Router::new().route(
+        "/hook/{id}",
+        axum::routing::post(|uri: axum::http::Uri, body: String| async move {
+            format!("{} {}", uri, body)
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, hook).await.unwrap() });
+
+    let (service, store) = setup_memory_service();
+    let payload = json!({
+        "url": format!("{}/hook/{{{{task.id}}}}?name={{{{ task.name }}}}&at={{{{execution.scheduled_at}}}}", base),
+        "method": "POST",
+        "body": { "execution": "{{execution.id}}", "unknown": "{{task.owner}}" },
+    });
+    let task = Task::new_once("nightly report", epoch(), payload);
+    store.create_task(&task).await.unwrap();
+
+    let execution = service.run_task(task.id, false).await.unwrap();
+    assert_eq!(execution.status, ExecutionStatus::Success);
+    assert_eq!(
+        execution.output["response"],
+        format!(
+            "/hook/{}?name=nightly%20report&at=2024-01-01T12%3A00%3A00Z {}",
+            task.id,
+            json!({ "execution": execution.id.to_string(), "unknown": "{{task.owner}}" })
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_secret_placeholders_are_filled_in_but_never_recorded() {
+    let hook = axum::Router::new().route(
+        "/hook",
+        axum::routing::get(|headers: axum::http::HeaderMap| async move {
+            headers["x-api-key"].to_str().unwrap().to_string()
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, hook).await.unwrap() });
+
+    let store = Arc::new(MemoryTaskStore::new());
+    let (tx, _) = mpsc::channel(1);
+    let config = crate::config::Config {
+        secrets: Some(crate::secrets::SecretCipher::new(&[1; 32]).unwrap()),
+        ..Default::default()
+    };
+    let service = TaskService::with_store(store.clone(), tx).with_config(config);
+
+    service
+        .put_secret("billing-key".into(), "s3cr3t")
+        .await
+        .unwrap();
+    let stored = store.get_secret("billing-key").await.unwrap().unwrap();
+    assert!(!String::from_utf8_lossy(&stored.ciphertext).contains("s3cr3t"));
+
+    let payload = json!({ "url": url, "headers": { "X-Api-Key": "{{secret:billing-key}}" } });
+    let task = Task::new_once("billing", epoch(), payload.clone());
+    store.create_task(&task).await.unwrap();
+    let execution = service.run_task(task.id, false).await.unwrap();
+    assert_eq!(execution.status, ExecutionStatus::Success);
+    assert_eq!(execution.output["response"], "[REDACTED]");
+    assert_eq!(execution.payload, Some(payload));
+
+    service.delete_secret("billing-key").await.unwrap();
+    let execution = service.run_task(task.id, false).await.unwrap();
+    assert_eq!(execution.status, ExecutionStatus::Failure);
+    assert_eq!(
+        execution.output["error"],
+        "Secret 'billing-key' does not exist"
+    );
+}
+
+#[tokio::test]
+async fn test_unresponsive_webhooks_time_out() {
+    let hook = axum::Router::new().route(
+        "/hook",
+        axum::routing::get(|| async {
+            tokio::time::sleep(std::time::Duration::from_secs(30)).await;
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, hook).await.unwrap() });
+
+    let (service, store) = setup_memory_service();
+    let task = Task::new_once(
+        "stalled",
+        epoch(),
+        json!({ "url": url, "timeout_seconds": 1 }),
+    );
+    store.create_task(&task).await.unwrap();
+
+    let execution = service.run_task(task.id, false).await.unwrap();
+    assert_eq!(execution.status, ExecutionStatus::Failure);
+    assert_eq!(
+        execution.output,
+        json!({ "error": "Webhook timed out after 1 seconds", "reason": "timeout" })
+    );
+}
+
+#[tokio::test]
+async fn test_webhook_success_criteria_fail_error_responses() {
+    let hook = axum::Router::new().route(
+        "/hook",
+        axum::routing::get(|| async { axum::Json(json!({ "error": true })) }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, hook).await.unwrap() });
+
+    let (service, store) = setup_memory_service();
+    let lenient = Task::new_once("lenient", epoch(), json!({ "url": url }));
+    let strict = Task::new_once(
+        "strict",
+        epoch(),
+        json!({ "url": url, "success": { "json_path": "$.error", "equals": false } }),
+    );
+    store.create_task(&lenient).await.unwrap();
+    store.create_task(&strict).await.unwrap();
+
+    let execution = service.run_task(lenient.id, false).await.unwrap();
+    assert_eq!(execution.status, ExecutionStatus::Success);
+    let execution = service.run_task(strict.id, false).await.unwrap();
+    assert_eq!(execution.status, ExecutionStatus::Failure);
+    assert_eq!(
+        execution.output["error"],
+        "Response field '$.error' is true, expected false: {\"error\":true}"
+    );
+
+    let req = CreateTaskReq {
+        name: "half a check".into(),
+        task_type: "once".into(),
+        trigger_at: Some(epoch()),
+        schedule: None,
+        interval_seconds: None,
+        cron_expr: None,
+        payload: Some(json!({ "url": url, "success": { "json_path": "$.error" } })),
+        keep_executions: None,
+        keep_days: None,
+        notifications: None,
+        group_id: None,
+        labels: None,
+        retry_policy: None,
+        priority: None,
+        misfire_policy: None,
+        interval_mode: None,
+        ends_at: None,
+        max_executions: None,
+        timezone: None,
+        rrule: None,
+        blackout_windows: None,
+        execution_windows: None,
+        depends_on: None,
+        next_task_id: None,
+        run_if: None,
+    };
+    let result = service.create_task(req, RequestContext::default()).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+}
+
+#[tokio::test]
+async fn test_global_secret_signs_every_webhook() {
+    use crate::service::signing::{SIGNATURE_HEADER, sign};
+
+    let received = Arc::new(std::sync::Mutex::new(None));
+    let seen = received.clone();
+    let hook = axum::Router::new().route(
+        "/hook",
+        axum::routing::post(move |headers: axum::http::HeaderMap| async move {
+            *seen.lock().unwrap() = headers
+                .get(SIGNATURE_HEADER)
+                .map(|value| value.to_str().unwrap().to_string());
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, hook).await.unwrap() });
+
+    let store = Arc::new(MemoryTaskStore::new());
+    let (tx, _) = mpsc::channel(1);
+    let config = crate::config::Config {
+        webhook_signing_secret: Some("global".into()),
+        ..Default::default()
+    };
+    let service = TaskService::with_store(store.clone(), tx)
+        .with_config(config)
+        .with_clock(Arc::new(TestClock::new(epoch())));
+
+    // A task without a secret of its own
+    let payload = json!({ "url": url, "method": "POST", "body": { "n": 1 } });
+    let task = Task::new_once("shared", epoch(), payload);
+    store.create_task(&task).await.unwrap();
+    service.run_task(task.id, false).await.unwrap();
+
+    let ts = epoch().timestamp();
+    assert_eq!(
+        received.lock().unwrap().clone(),
+        Some(format!("t={},v1={}", ts, sign("global", ts, br#"{"n":1}"#)))
+    );
+}
+
+#[tokio::test]
+async fn test_webhooks_to_blocked_hosts_are_rejected() {
+    let store = Arc::new(MemoryTaskStore::new());
+    let (tx, _) = mpsc::channel(1);
+    let config = crate::config::Config {
+        webhook_hosts: crate::executor::HostPolicy::new(Vec::<String>::new(), ["*.internal"], true),
+        ..Default::default()
+    };
+    let service = TaskService::with_store(store.clone(), tx).with_config(config);
+
+    let req = |url: &str| CreateTaskReq {
+        name: "probe".into(),
+        task_type: "once".into(),
+        trigger_at: Some(epoch()),
+        schedule: None,
+        interval_seconds: None,
+        cron_expr: None,
+        payload: Some(json!({ "url": url })),
+        keep_executions: None,
+        keep_days: None,
+        notifications: None,
+        group_id: None,
+        labels: None,
+        retry_policy: None,
+        priority: None,
+        misfire_policy: None,
+        interval_mode: None,
+        ends_at: None,
+        max_executions: None,
+        timezone: None,
+        rrule: None,
+        blackout_windows: None,
+        execution_windows: None,
+        depends_on: None,
+        next_task_id: None,
+        run_if: None,
+    };
+    for url in [
+        "http://169.254.169.254/latest/meta-data",
+        "http://[::1]/",
+        "http://billing.internal/charge",
+        "file:///etc/passwd",
+    ] {
+        let result = service
+            .create_task(req(url), RequestContext::default())
+            .await;
+        assert!(
+            matches!(result, Err(AppError::ValidationError(_))),
+            "{} was accepted",
+            url
+        );
+    }
+
+    // Names are checked as they are resolved, when the task runs
+    let task = Task::new_once("loopback", epoch(), json!({ "url": "http://localhost:1/" }));
+    store.create_task(&task).await.unwrap();
+    let execution = service.run_task(task.id, false).await.unwrap();
+    assert_eq!(execution.status, ExecutionStatus::Failure);
+    let error = execution.output["error"].as_str().unwrap();
+    assert!(error.contains("is not public"), "{}", error);
+}
+
+#[tokio::test]
+async fn test_webhook_runs_reuse_pooled_connections() {
+    use axum::extract::ConnectInfo;
+    use std::net::SocketAddr;
+
+    let peers = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let seen = peers.clone();
+    let hook = axum::Router::new().route(
+        "/hook",
+        axum::routing::get(
+            move |ConnectInfo(peer): ConnectInfo<SocketAddr>| async move {
+                seen.lock().unwrap().push(peer);
+            },
+        ),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        axum::serve(
+            listener,
+            hook.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await
+        .unwrap()
+    });
+
+    let (service, store) = setup_memory_service();
+    let task = Task::new_once("pooled", epoch(), json!({ "url": url }));
+    store.create_task(&task).await.unwrap();
+
+    for _ in 0..2 {
+        let execution = service.run_task(task.id, false).await.unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Success);
+    }
+    let peers = peers.lock().unwrap();
+    assert_eq!(peers.len(), 2);
+    assert_eq!(peers[0], peers[1]);
+}
+
+#[sqlx::test]
+async fn test_snapshot_restores_into_an_empty_instance(pool: SqlitePool) -> sqlx::Result<()> {
+    let source = setup_service(pool);
+    let group = source.create_group("nightly".into(), None).await.unwrap();
+    let req = CreateTaskReq {
+        name: "export".into(),
+        task_type: "interval".into(),
+        trigger_at: Some(epoch()),
+        schedule: None,
+        interval_seconds: Some(3600),
+        cron_expr: None,
+        payload: Some(json!({ "method": "GET" })),
+        keep_executions: None,
+        keep_days: None,
+        notifications: None,
+        group_id: Some(group.id),
+        labels: None,
+        retry_policy: None,
+        priority: None,
+        misfire_policy: None,
+        interval_mode: None,
+        ends_at: None,
+        max_executions: None,
+        timezone: None,
+        rrule: None,
+        blackout_windows: None,
+        execution_windows: None,
+        depends_on: None,
+        next_task_id: None,
+        run_if: None,
+    };
+    let task = source
+        .create_task(req, RequestContext::default())
+        .await
+        .unwrap();
+    // Fails without a URL, which still leaves an execution behind
+    source.run_task(task.id, false).await.unwrap();
+    let node = WorkflowNode {
+        key: "export".into(),
+        task_id: task.id,
+        after: vec![],
+        compensate_with: None,
+    };
+    let workflow = source
+        .create_workflow("nightly".into(), vec![node])
+        .await
+        .unwrap();
+
+    let snapshot = source.export_snapshot(true, false).await.unwrap();
+    assert_eq!(snapshot.tasks[0].signing_secret, None);
+    assert_eq!(snapshot.executions.as_ref().map(Vec::len), Some(1));
+    assert_eq!(snapshot.workflows.len(), 1);
+
+    // Workflow nodes may only name tasks that come along
+    let mut dangling = snapshot.clone();
+    dangling.workflows[0].nodes[0].compensate_with = Some(uuid::Uuid::new_v4());
+    let (empty, _) = setup_memory_service();
+    let rejected = empty.restore_snapshot(dangling).await;
+    assert!(matches!(rejected, Err(AppError::ValidationError(_))));
+
+    let target_db = crate::test_util::TempDatabase::new().await.unwrap();
+    let target = setup_service(target_db.pool().clone());
+    target.restore_snapshot(snapshot.clone()).await.unwrap();
+
+    let restored = target.store.get_task(task.id).await?.unwrap();
+    assert_eq!(restored.group_id, Some(group.id));
+    assert_eq!(restored.trigger_at, task.trigger_at);
+    assert!(restored.signing_secret.is_some());
+    assert_ne!(restored.signing_secret, task.signing_secret);
+    assert_eq!(
+        target.store.list_executions(task.id, None, 10).await?.len(),
+        1
+    );
+    let restored = target.store.get_workflow(workflow.id).await?.unwrap();
+    assert_eq!(restored.nodes[0].task_id, task.id);
+
+    let again = target.restore_snapshot(snapshot).await;
+    assert!(matches!(again, Err(AppError::ValidationError(_))));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_snapshot_rejects_dangling_and_cyclic_task_links() {
+    let (source, store) = setup_memory_service();
+    let first = Task::new_once("first", epoch(), json!({}));
+    let second = Task::new_once("second", epoch(), json!({}));
+    store.create_task(&first).await.unwrap();
+    store.create_task(&second).await.unwrap();
+    let snapshot = source.export_snapshot(false, false).await.unwrap();
+    let with_links = |links: &[(Option<uuid::Uuid>, Option<uuid::Uuid>)]| {
+        let mut snapshot = snapshot.clone();
+        for (task, (depends_on, next_task_id)) in snapshot.tasks.iter_mut().zip(links) {
+            task.depends_on = *depends_on;
+            task.next_task_id = *next_task_id;
+        }
+        snapshot
+    };
+
+    let missing = Some(uuid::Uuid::new_v4());
+    let (a, b) = (Some(snapshot.tasks[0].id), Some(snapshot.tasks[1].id));
+    for links in [
+        [(missing, None), (None, None)],
+        [(None, missing), (None, None)],
+        [(b, None), (a, None)],
+        [(None, b), (None, a)],
+        [(None, a), (None, None)],
+    ] {
+        let (target, _) = setup_memory_service();
+        let restored = target.restore_snapshot(with_links(&links)).await;
+        assert!(
+            matches!(restored, Err(AppError::ValidationError(_))),
+            "{links:?}"
+        );
+    }
+
+    let (target, target_store) = setup_memory_service();
+    target
+        .restore_snapshot(with_links(&[(None, b), (a, None)]))
+        .await
+        .unwrap();
+    let chained = target_store.get_task(a.unwrap()).await.unwrap().unwrap();
+    assert_eq!(chained.next_task_id, b);
+}
+
+#[tokio::test]
+async fn test_cron_tasks_follow_their_expression() {
+    let store = Arc::new(MemoryTaskStore::new());
+    let clock = Arc::new(TestClock::new(epoch()));
+    let (tx, _) = mpsc::channel(1);
+    let service = TaskService::with_store(store.clone(), tx).with_clock(clock.clone());
+    let req = |cron_expr: &str| CreateTaskReq {
+        name: "weekday report".into(),
+        task_type: "cron".into(),
+        trigger_at: Some(epoch()),
+        schedule: None,
+        interval_seconds: None,
+        cron_expr: Some(cron_expr.into()),
+        payload: None,
+        keep_executions: None,
+        keep_days: None,
+        notifications: None,
+        group_id: None,
+        labels: None,
+        retry_policy: None,
+        priority: None,
+        misfire_policy: None,
+        interval_mode: None,
+        ends_at: None,
+        max_executions: None,
+        timezone: None,
+        rrule: None,
+        blackout_windows: None,
+        execution_windows: None,
+        depends_on: None,
+        next_task_id: None,
+        run_if: None,
+    };
+
+    let invalid = service
+        .create_task(req("0 9 * *"), RequestContext::default())
+        .await;
+    assert!(matches!(invalid, Err(AppError::ValidationError(_))));
+
+    // Created on Monday at noon, so the first run is Tuesday morning
+    let task = service
+        .create_task(req("0 9 * * 1-5"), RequestContext::default())
+        .await
+        .unwrap();
+    let at_nine = |day: u32| Utc.with_ymd_and_hms(2024, 1, day, 9, 0, 0).unwrap();
+    assert_eq!(task.trigger_at, at_nine(2));
+
+    // A Friday run is followed by Monday's, skipping the weekend
+    clock.set(at_nine(5));
+    service.process_task(task.clone()).await.unwrap();
+    let task = store.get_task(task.id).await.unwrap().unwrap();
+    assert_eq!(task.trigger_at, at_nine(8));
+
+    let skipped = service.skip_next(task.id).await.unwrap();
+    assert_eq!(skipped.trigger_at, at_nine(9));
+}
+
+#[tokio::test]
+async fn test_human_schedules_pick_the_task_type() {
+    let (service, _) = setup_memory_service();
+    let req = |schedule: &str| CreateTaskReq {
+        name: "friendly".into(),
+        task_type: String::new(),
+        trigger_at: None,
+        schedule: Some(schedule.into()),
+        interval_seconds: None,
+        cron_expr: None,
+        payload: None,
+        keep_executions: None,
+        keep_days: None,
+        notifications: None,
+        group_id: None,
+        labels: None,
+        retry_policy: None,
+        priority: None,
+        misfire_policy: None,
+        interval_mode: None,
+        ends_at: None,
+        max_executions: None,
+        timezone: None,
+        rrule: None,
+        blackout_windows: None,
+        execution_windows: None,
+        depends_on: None,
+        next_task_id: None,
+        run_if: None,
+    };
+    let create = |req| service.create_task(req, RequestContext::default());
+
+    let every = create(req("Every 15 minutes")).await.unwrap();
+    assert_eq!(every.task_type, TaskType::Interval);
+    assert_eq!(every.interval_seconds, Some(900));
+    assert_eq!(every.trigger_at, epoch() + Duration::minutes(15));
+
+    // Created at noon, so the first run is the next morning
+    let daily = create(req("daily at 09:30")).await.unwrap();
+    assert_eq!(daily.cron_expr.as_deref(), Some("30 9 * * *"));
+    assert_eq!(
+        daily.trigger_at,
+        epoch() + Duration::hours(21) + Duration::minutes(30)
+    );
+
+    let later = create(req("in 2 hours")).await.unwrap();
+    assert_eq!(later.task_type, TaskType::Once);
+    assert_eq!(later.trigger_at, epoch() + Duration::hours(2));
+
+    let combined = CreateTaskReq {
+        trigger_at: Some(epoch()),
+        ..req("hourly")
+    };
+    // Amounts past what a timestamp can hold are rejected rather than overflowing
+    for invalid in [
+        req("whenever you like"),
+        req("every 0 minutes"),
+        req("in 9999999999999999 seconds"),
+        req("every 9999999999999999 seconds"),
+        req("in 2000000000 weeks"),
+        req("every 600 weeks"),
+        req("in 3651 days"),
+        combined,
+    ] {
+        assert!(matches!(
+            create(invalid).await,
+            Err(AppError::ValidationError(_))
+        ));
+    }
+}
+
+#[tokio::test]
+async fn test_schedule_previews_list_upcoming_runs() {
+    let (service, _) = setup_memory_service();
+
+    let interval = service
+        .preview_schedule(PreviewScheduleReq {
+            task_type: "interval".into(),
+            interval_seconds: Some(3600),
+            count: Some(3),
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(
+        interval,
+        vec![
+            epoch(),
+            epoch() + Duration::hours(1),
+            epoch() + Duration::hours(2)
+        ]
+    );
+
+    // Human schedules resolve as they would on creation
+    let daily = service
+        .preview_schedule(PreviewScheduleReq {
+            schedule: Some("daily at 09:30".into()),
+            count: Some(2),
+            ..Default::default()
+        })
+        .unwrap();
+    let first = Utc.with_ymd_and_hms(2024, 1, 2, 9, 30, 0).unwrap();
+    assert_eq!(daily, vec![first, first + Duration::days(1)]);
+
+    // A rule that ends returns fewer runs than asked for
+    let rrule = service
+        .preview_schedule(PreviewScheduleReq {
+            task_type: "rrule".into(),
+            rrule: Some("FREQ=WEEKLY;BYDAY=MO,FR;COUNT=3".into()),
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(
+        rrule,
+        vec![
+            epoch(),
+            epoch() + Duration::days(4),
+            epoch() + Duration::days(7)
+        ]
+    );
+
+    for invalid in [
+        PreviewScheduleReq {
+            task_type: "cron".into(),
+            cron_expr: Some("not a cron".into()),
+            ..Default::default()
+        },
+        PreviewScheduleReq {
+            task_type: "once".into(),
+            count: Some(101),
+            ..Default::default()
+        },
+        PreviewScheduleReq {
+            schedule: Some("hourly".into()),
+            interval_seconds: Some(60),
+            ..Default::default()
+        },
+        PreviewScheduleReq {
+            schedule: Some("in 2000000000 weeks".into()),
+            ..Default::default()
+        },
+        PreviewScheduleReq {
+            schedule: Some("every 9999999999999999 seconds".into()),
+            ..Default::default()
+        },
+    ] {
+        assert!(matches!(
+            service.preview_schedule(invalid),
+            Err(AppError::ValidationError(_))
+        ));
+    }
+}
+
+#[tokio::test]
+async fn test_schedule_checks_report_each_bad_part() {
+    let (service, _) = setup_memory_service();
+    let check = |req| {
+        service
+            .check_schedule(req)
+            .unwrap()
+            .into_iter()
+            .map(|(field, issue)| (field, issue.part, issue.kind))
+            .collect::<Vec<_>>()
+    };
+
+    assert!(
+        check(ValidateScheduleReq {
+            cron_expr: Some("*/15 9-17 * JAN-MAR MON-FRI".into()),
+            rrule: Some("FREQ=MONTHLY;BYDAY=2TU".into()),
+            timezone: Some("Europe/Oslo".into()),
+        })
+        .is_empty()
+    );
+
+    // Every bad cron field is reported, not just the first
+    assert_eq!(
+        check(ValidateScheduleReq {
+            cron_expr: Some("61 25 * * tomorrow".into()),
+            rrule: Some("FREQ=MONTHLY;BYMONTHDAY=32".into()),
+            timezone: Some("Mars/Olympus".into()),
+        }),
+        vec![
+            ("timezone", None, IssueKind::Unknown),
+            ("cron_expr", Some("minute".into()), IssueKind::OutOfRange),
+            ("cron_expr", Some("hour".into()), IssueKind::OutOfRange),
+            (
+                "cron_expr",
+                Some("day_of_week".into()),
+                IssueKind::Malformed
+            ),
+            ("rrule", Some("BYMONTHDAY".into()), IssueKind::OutOfRange),
+        ]
+    );
+    assert_eq!(
+        check(ValidateScheduleReq {
+            cron_expr: Some("0 0 30 2 *".into()),
+            rrule: Some("FREQ=DAILY;UNTIL=20200101".into()),
+            ..Default::default()
+        }),
+        vec![
+            ("cron_expr", None, IssueKind::NeverMatches),
+            ("rrule", None, IssueKind::NeverMatches),
+        ]
+    );
+
+    assert!(matches!(
+        service.check_schedule(ValidateScheduleReq::default()),
+        Err(AppError::ValidationError(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_rrule_tasks_follow_their_rule() {
+    let store = Arc::new(MemoryTaskStore::new());
+    let clock = Arc::new(TestClock::new(epoch()));
+    let (tx, _) = mpsc::channel(1);
+    let service = TaskService::with_store(store.clone(), tx).with_clock(clock.clone());
+    let req = |rrule: &str| CreateTaskReq {
+        name: "payroll".into(),
+        task_type: "rrule".into(),
+        trigger_at: Some(epoch()),
+        schedule: None,
+        interval_seconds: None,
+        cron_expr: None,
+        payload: None,
+        keep_executions: None,
+        keep_days: None,
+        notifications: None,
+        group_id: None,
+        labels: None,
+        retry_policy: None,
+        priority: None,
+        misfire_policy: None,
+        interval_mode: None,
+        ends_at: None,
+        max_executions: None,
+        timezone: None,
+        rrule: Some(rrule.into()),
+        blackout_windows: None,
+        execution_windows: None,
+        depends_on: None,
+        next_task_id: None,
+        run_if: None,
+    };
+
+    for invalid in [
+        "FREQ=MONTHLY;BYMONTHDAY=32",
+        "FREQ=MONTHLY;BYMONTH=2;BYMONTHDAY=30",
+    ] {
+        let created = service
+            .create_task(req(invalid), RequestContext::default())
+            .await;
+        assert!(matches!(created, Err(AppError::ValidationError(_))));
+    }
+
+    // The last business day of each month, at noon like the rule's start
+    let task = service
+        .create_task(
+            req("FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1"),
+            RequestContext::default(),
+        )
+        .await
+        .unwrap();
+    let at_noon = |month: u32, day: u32| Utc.with_ymd_and_hms(2024, month, day, 12, 0, 0).unwrap();
+    assert_eq!(task.trigger_at, at_noon(1, 31));
+
+    clock.set(at_noon(1, 31));
+    service.process_task(task.clone()).await.unwrap();
+    let task = store.get_task(task.id).await.unwrap().unwrap();
+    assert_eq!(task.trigger_at, at_noon(2, 29));
+
+    // March 31st is a Sunday
+    let skipped = service.skip_next(task.id).await.unwrap();
+    assert_eq!(skipped.trigger_at, at_noon(3, 29));
+}
+
+#[tokio::test]
+async fn test_blackout_windows_defer_runs_to_their_end() {
+    let store = Arc::new(MemoryTaskStore::new());
+    let clock = Arc::new(TestClock::new(epoch()));
+    let (tx, _) = mpsc::channel(1);
+    let window = |from: i64, to: i64| BlackoutWindow {
+        starts_at: epoch() + Duration::hours(from),
+        ends_at: epoch() + Duration::hours(to),
+    };
+    let service = TaskService::with_store(store.clone(), tx)
+        .with_clock(clock.clone())
+        .with_config(crate::config::Config {
+            blackout_windows: vec![window(-1, 2)],
+            ..crate::config::Config::default()
+        });
+
+    // The task's own window adjoins the global one, so the run waits for both
+    let task = Task {
+        blackout_windows: Some(vec![window(2, 3)]),
+        ..Task::new_interval("nightly", epoch(), 3600, json!({}))
+    };
+    store.create_task(&task).await.unwrap();
+
+    service.process_task(task.clone()).await.unwrap();
+    let deferred = store.get_task(task.id).await.unwrap().unwrap();
+    assert_eq!(deferred.trigger_at, epoch() + Duration::hours(3));
+    assert_eq!(deferred.deferred_from, Some(epoch()));
+    assert!(
+        store
+            .list_executions(task.id, None, 10)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    clock.set(epoch() + Duration::hours(3));
+    service.process_task(deferred).await.unwrap();
+    let executions = store
+        .list_execution_records(task.id, None, 10)
+        .await
+        .unwrap();
+    assert_eq!(executions.len(), 1);
+    assert_eq!(executions[0].deferred_from, Some(epoch()));
+    let task = store.get_task(task.id).await.unwrap().unwrap();
+    assert_eq!(task.deferred_from, None);
+    assert_eq!(task.trigger_at, epoch() + Duration::hours(4));
+}
+
+#[tokio::test]
+async fn test_execution_windows_clamp_triggers() {
+    let store = Arc::new(MemoryTaskStore::new());
+    let clock = Arc::new(TestClock::new(epoch()));
+    let (tx, _) = mpsc::channel(1);
+    let service = TaskService::with_store(store.clone(), tx).with_clock(clock.clone());
+
+    // Weekdays from 08:00 to 18:00 in Oslo, 07:00 to 17:00 UTC in January
+    let task = Task {
+        timezone: Some("Europe/Oslo".into()),
+        execution_windows: Some(vec![ExecutionWindow {
+            days: vec![
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+            ],
+            start: NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(18, 0, 0).unwrap(),
+        }]),
+        ..Task::new_interval("business", epoch(), 4 * 3600, json!({}))
+    };
+    store.create_task(&task).await.unwrap();
+
+    service.process_task(task.clone()).await.unwrap();
+    let task = store.get_task(task.id).await.unwrap().unwrap();
+    assert_eq!(task.trigger_at, epoch() + Duration::hours(4));
+
+    // 20:00 UTC is after hours, so the run waits for Tuesday morning
+    clock.set(task.trigger_at);
+    service.process_task(task.clone()).await.unwrap();
+    let task = store.get_task(task.id).await.unwrap().unwrap();
+    assert_eq!(task.trigger_at, epoch() + Duration::hours(19));
+}
+
+#[tokio::test]
+async fn test_dependent_tasks_wait_for_their_dependency() {
+    let (service, store) = setup_memory_service();
+    let service = service.with_executor("echo", EchoExecutor);
+    let echo = json!({"type": "echo", "message": "hi"});
+    let fetch = Task::new_interval("fetch", epoch(), 3600, echo.clone());
+    let report = Task {
+        depends_on: Some(fetch.id),
+        ..Task::new_interval("report", epoch(), 3600, echo)
+    };
+    store.create_task(&fetch).await.unwrap();
+    store.create_task(&report).await.unwrap();
+
+    // The fetch has never succeeded, so the report waits and is checked again later
+    service.process_task(report.clone()).await.unwrap();
+    let waiting = store.get_task(report.id).await.unwrap().unwrap();
+    assert_eq!(waiting.trigger_at, epoch() + Duration::seconds(60));
+    let dependency = service.dependency(&waiting).await.unwrap().unwrap();
+    assert_eq!(dependency.state, DependencyState::Waiting);
+
+    // A successful fetch releases it right away
+    let changes = service.process_batch(vec![fetch.clone()]).await.unwrap();
+    assert!(changes.contains(&ScheduleEvent::Upserted {
+        id: report.id,
+        trigger_at: epoch(),
+    }));
+    let released = store.get_task(report.id).await.unwrap().unwrap();
+    let dependency = service.dependency(&released).await.unwrap().unwrap();
+    assert_eq!(dependency.state, DependencyState::Ready);
+    assert_eq!(dependency.last_succeeded_at, Some(epoch()));
+
+    service.process_task(released).await.unwrap();
+    let executions = store
+        .list_execution_records(report.id, None, 10)
+        .await
+        .unwrap();
+    assert_eq!(executions.len(), 1);
+    assert_eq!(executions[0].deferred_from, Some(epoch()));
+
+    // Its next run needs another successful fetch
+    let report = store.get_task(report.id).await.unwrap().unwrap();
+    let dependency = service.dependency(&report).await.unwrap().unwrap();
+    assert_eq!(dependency.state, DependencyState::Waiting);
+}
+
+#[tokio::test]
+async fn test_workflow_failures_compensate_completed_nodes() {
+    let (service, store) = setup_memory_service();
+    let service = service.with_executor("echo", EchoExecutor);
+    let echo = json!({"type": "echo", "message": "hi"});
+    let reserve = Task::new_interval("reserve", epoch(), 3600, echo.clone());
+    let release = Task::new_interval("release", epoch(), 3600, echo.clone());
+    let audit = Task::new_interval("audit", epoch(), 3600, echo.clone());
+    // Without a URL every run fails
+    let charge = Task::new_interval("charge", epoch(), 3600, json!({}));
+    let ship = Task::new_interval("ship", epoch(), 3600, echo);
+    for task in [&reserve, &release, &audit, &charge, &ship] {
+        store.create_task(task).await.unwrap();
+    }
+    let node = |key: &str, task: &Task, after: &[&str]| WorkflowNode {
+        key: key.into(),
+        task_id: task.id,
+        after: after.iter().map(|key| key.to_string()).collect(),
+        compensate_with: None,
+    };
+
+    let cycle = service
+        .create_workflow(
+            "cycle".into(),
+            vec![node("a", &audit, &["b"]), node("b", &audit, &["a"])],
+        )
+        .await;
+    assert!(matches!(cycle, Err(AppError::ValidationError(_))));
+
+    let workflow = service
+        .create_workflow(
+            "order".into(),
+            vec![
+                node("ship", &ship, &["charge"]),
+                node("charge", &charge, &["reserve"]),
+                WorkflowNode {
+                    compensate_with: Some(release.id),
+                    ..node("reserve", &reserve, &[])
+                },
+                node("audit", &audit, &[]),
+            ],
+        )
+        .await
+        .unwrap();
+    let keys: Vec<&str> = workflow.nodes.iter().map(|n| n.key.as_str()).collect();
+    assert_eq!(keys, ["reserve", "audit", "charge", "ship"]);
+
+    let run = WorkflowRun::new(&workflow, epoch());
+    store.create_workflow_run(&run).await.unwrap();
+    let run = service.advance_workflow_run(&workflow, run).await.unwrap();
+    assert_eq!(run.status, WorkflowRunStatus::Failed);
+    assert_eq!(run.state("audit"), NodeState::Succeeded);
+    assert_eq!(run.state("charge"), NodeState::Failed);
+    // The failure stops the nodes after it and undoes the ones before it
+    assert_eq!(run.state("ship"), NodeState::UpstreamFailed);
+    assert_eq!(run.nodes["ship"].execution_id, None);
+    assert_eq!(run.state("reserve"), NodeState::Compensated);
+    assert!(run.nodes["reserve"].compensation_id.is_some());
+    assert_eq!(service.get_workflow_run(run.id).await.unwrap(), run);
+}
+
+#[tokio::test]
+async fn test_chained_tasks_receive_the_previous_output() {
+    let (service, store) = setup_memory_service();
+    let service = service.with_executor("echo", EchoExecutor);
+    let notify = Task::new_interval(
+        "notify",
+        epoch() + Duration::hours(1),
+        3600,
+        json!({"type": "echo", "message": "fetched {{previous.output.echo}}", "body": "{{previous.output}}"}),
+    );
+    let fetch = Task {
+        next_task_id: Some(notify.id),
+        ..Task::new_interval(
+            "fetch",
+            epoch(),
+            3600,
+            json!({"type": "echo", "message": "42"}),
+        )
+    };
+    store.create_task(&notify).await.unwrap();
+    store.create_task(&fetch).await.unwrap();
+
+    service.process_batch(vec![fetch.clone()]).await.unwrap();
+    let fetched = store
+        .list_execution_records(fetch.id, None, 10)
+        .await
+        .unwrap();
+
+    // The next task runs in the background, outside its own schedule
+    let mut executions = Vec::new();
+    for _ in 0..50 {
+        executions = store
+            .list_execution_records(notify.id, None, 10)
+            .await
+            .unwrap();
+        if !executions.is_empty() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert_eq!(executions.len(), 1);
+    let payload = executions[0].payload.clone().unwrap();
+    assert_eq!(payload["message"], "fetched 42");
+    assert_eq!(payload["body"], fetched[0].output);
+    assert_eq!(executions[0].output["echo"], "fetched 42");
+    let notify = store.get_task(notify.id).await.unwrap().unwrap();
+    assert_eq!(notify.trigger_at, epoch() + Duration::hours(1));
+}
+
+#[tokio::test]
+async fn test_chained_tasks_never_resolve_secrets_named_by_the_previous_output() {
+    let (service, store) = setup_memory_service();
+    let service = service
+        .with_executor("echo", EchoExecutor)
+        .with_config(crate::config::Config {
+            secrets: Some(crate::secrets::SecretCipher::new(&[1; 32]).unwrap()),
+            ..Default::default()
+        });
+    service.put_secret("vault".into(), "s3cr3t").await.unwrap();
+
+    // Neither half is a placeholder on its own, so the first run resolves nothing
+    let notify = Task::new_once(
+        "notify",
+        epoch() + Duration::hours(1),
+        json!({"type": "echo", "message": "{{previous.output.echo.0}}{{previous.output.echo.1}}"}),
+    );
+    let fetch = Task {
+        next_task_id: Some(notify.id),
+        ..Task::new_once(
+            "fetch",
+            epoch(),
+            json!({"type": "echo", "message": ["{{secret:", "vault}}"]}),
+        )
+    };
+    store.create_task(&notify).await.unwrap();
+    store.create_task(&fetch).await.unwrap();
+
+    let previous = service.run_task(fetch.id, false).await.unwrap();
+    assert_eq!(previous.status, ExecutionStatus::Success);
+
+    // The next task is refused instead of running with the secret filled in
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    assert!(
+        store
+            .list_execution_records(notify.id, None, 10)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn test_unmet_run_conditions_skip_the_run() {
+    let (service, store) = setup_memory_service();
+    let clock = Arc::new(TestClock::new(epoch()));
+    let service = service
+        .with_executor("echo", EchoExecutor)
+        .with_clock(clock.clone());
+    let fetch = Task::new_interval(
+        "fetch",
+        epoch(),
+        3600,
+        json!({"type": "echo", "message": "unchanged"}),
+    );
+    let report = Task {
+        depends_on: Some(fetch.id),
+        run_if: Some(RunCondition {
+            path: "$.echo".into(),
+            op: ConditionOp::Eq,
+            value: Some(json!("changed")),
+        }),
+        ..Task::new_interval(
+            "report",
+            epoch(),
+            3600,
+            json!({"type": "echo", "message": "hi"}),
+        )
+    };
+    store.create_task(&fetch).await.unwrap();
+    store.create_task(&report).await.unwrap();
+
+    service.process_batch(vec![fetch.clone()]).await.unwrap();
+    service.process_task(report.clone()).await.unwrap();
+    let executions = store
+        .list_execution_records(report.id, None, 10)
+        .await
+        .unwrap();
+    assert_eq!(executions.len(), 1);
+    assert_eq!(executions[0].status, ExecutionStatus::Skipped);
+    assert_eq!(executions[0].output["skipped"], "run_if condition not met");
+    // A skipped run still moves the task on to its next trigger
+    let skipped = store.get_task(report.id).await.unwrap().unwrap();
+    assert_eq!(skipped.trigger_at, epoch() + Duration::hours(1));
+
+    clock.advance(Duration::hours(1));
+    let changed = Task {
+        payload: json!({"type": "echo", "message": "changed"}),
+        ..fetch
+    };
+    service.process_batch(vec![changed]).await.unwrap();
+    service.process_task(skipped).await.unwrap();
+    let executions = store
+        .list_execution_records(report.id, None, 10)
+        .await
+        .unwrap();
+    assert_eq!(executions.len(), 2);
+    assert_eq!(executions[0].status, ExecutionStatus::Success);
+}
+
+#[tokio::test]
+async fn test_fan_out_tasks_run_a_child_per_item() {
+    let (service, store) = setup_memory_service();
+    let service = service.with_executor("echo", EchoExecutor);
+    let task = Task::new_once(
+        "fan out",
+        epoch(),
+        json!({
+            "type": "fan_out",
+            "items": [{"name": "a"}, {"name": "b"}],
+            "task": {"type": "echo", "message": "{{index}}: {{item.name}}"},
+        }),
+    );
+    store.create_task(&task).await.unwrap();
+    service.process_task(task.clone()).await.unwrap();
+
+    let execution = store.executions().pop().unwrap();
+    assert_eq!(execution.status, ExecutionStatus::Success);
+    assert_eq!(execution.output["succeeded"], 2);
+    let children = execution.output["children"].as_array().unwrap();
+    assert_eq!(children[1]["item"], json!({"name": "b"}));
+    assert_eq!(children[1]["output"]["echo"], "1: b");
+
+    // One failing child fails the run, and every child's outcome is kept
+    let task = Task::new_once(
+        "fan out",
+        epoch(),
+        json!({
+            "type": "fan_out",
+            "items": ["echo", "missing"],
+            "task": {"type": "{{item}}", "message": "hi"},
+        }),
+    );
+    store.create_task(&task).await.unwrap();
+    service.process_task(task.clone()).await.unwrap();
+
+    let execution = store.executions().pop().unwrap();
+    assert_eq!(execution.status, ExecutionStatus::Failure);
+    assert_eq!(execution.output["error"], "1 of 2 children failed");
+    assert_eq!(execution.output["children"][0]["status"], "success");
+    assert_eq!(
+        execution.output["children"][1]["error"],
+        "No executor is registered for type 'missing'"
+    );
+
+    // Children and item sources are checked up front, and cannot fan out again
+    let service = service.with_config(crate::config::Config {
+        webhook_hosts: crate::executor::HostPolicy::new(Vec::<String>::new(), ["*.internal"], true),
+        ..crate::config::Config::default()
+    });
+    let fan_out = |task: serde_json::Value, items_from: Option<serde_json::Value>| {
+        let mut payload = json!({"type": "fan_out", "task": task});
+        match items_from {
+            Some(source) => payload["items_from"] = source,
+            None => payload["items"] = json!([1, 2]),
+        }
+        payload
+    };
+    let source =
+        |payload: serde_json::Value| json!({"payload": payload, "path": "$.response.items"});
+    for invalid in [
+        fan_out(json!({"type": "missing"}), None),
+        fan_out(
+            json!({"type": "webhook", "url": "http://db.internal/{{item}}"}),
+            None,
+        ),
+        fan_out(json!({"url": "https://x.test", "timeout_seconds": 0}), None),
+        fan_out(fan_out(json!({"type": "echo"}), None), None),
+        fan_out(
+            json!({"type": "echo"}),
+            Some(source(json!({"type": "missing"}))),
+        ),
+        fan_out(
+            json!({"type": "echo"}),
+            Some(source(json!({"url": "http://10.0.0.1/items"}))),
+        ),
+    ] {
+        assert!(
+            matches!(
+                service.validate_payload(&invalid),
+                Err(AppError::ValidationError(_))
+            ),
+            "{} should be rejected",
+            invalid
+        );
+    }
+    for valid in [
+        fan_out(json!({"type": "echo", "message": "{{item}}"}), None),
+        fan_out(json!({"type": "{{item}}"}), None),
+        fan_out(
+            json!({"url": "https://x.test/{{item.id}}"}),
+            Some(source(json!({"url": "https://x.test/items"}))),
+        ),
+    ] {
+        service.validate_payload(&valid).unwrap();
+    }
+
+    // A child whose type turns out to be a fan-out fails instead of running
+    let task = Task::new_once(
+        "fan out",
+        epoch(),
+        json!({
+            "type": "fan_out",
+            "items": ["fan_out"],
+            "task": {"type": "{{item}}", "items": [1], "task": {"type": "echo"}},
+        }),
+    );
+    store.create_task(&task).await.unwrap();
+    service.process_task(task.clone()).await.unwrap();
+    let execution = store.executions().pop().unwrap();
+    assert_eq!(execution.status, ExecutionStatus::Failure);
+    assert_eq!(
+        execution.output["children"][0]["error"],
+        "A fan-out cannot run another fan-out"
+    );
+}
+
+#[tokio::test]
+async fn test_running_executions_can_be_cancelled() {
+    let (service, store) = setup_memory_service();
+    let service = service.with_config(crate::config::Config {
+        command_executor_enabled: true,
+        command_programs: vec!["sleep".into()],
+        ..crate::config::Config::default()
+    });
+    let task = Task::new_interval(
+        "slow",
+        epoch(),
+        3600,
+        json!({"type": "command", "program": "sleep", "args": ["30"]}),
+    );
+    store.create_task(&task).await.unwrap();
+    let running = tokio::spawn({
+        let service = service.clone();
+        let task = task.clone();
+        async move { service.process_task(task).await }
+    });
+
+    // The running record is written just before the executor starts
+    let mut cancelled = None;
+    for _ in 0..100 {
+        if let Some(execution) = store.executions().pop()
+            && service.cancel_execution(execution.id).await.is_ok()
+        {
+            cancelled = Some(execution.id);
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    let cancelled = cancelled.unwrap();
+    running.await.unwrap().unwrap();
+
+    let execution = store.get_execution(cancelled).await.unwrap().unwrap();
+    assert_eq!(execution.status, ExecutionStatus::Cancelled);
+    // Not retried; the task moves on to its next run
+    let task = store.get_task(task.id).await.unwrap().unwrap();
+    assert_eq!(task.trigger_at, epoch() + Duration::hours(1));
+    assert!(matches!(
+        service.cancel_execution(cancelled).await,
+        Err(AppError::Conflict(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_shutdown_waits_for_tracked_work_then_aborts_it() {
+    let (service, store) = setup_memory_service();
+    let service = service.with_config(crate::config::Config {
+        command_executor_enabled: true,
+        command_programs: vec!["sleep".into()],
+        shutdown_grace_secs: 1,
+        ..crate::config::Config::default()
+    });
+    let quick = Task::new_once(
+        "quick",
+        epoch(),
+        json!({"type": "command", "program": "sleep", "args": ["0.1"]}),
+    );
+    let slow = Task::new_once(
+        "slow",
+        epoch(),
+        json!({"type": "command", "program": "sleep", "args": ["30"]}),
+    );
+    for task in [&quick, &slow] {
+        store.create_task(task).await.unwrap();
+        let service = service.clone();
+        let id = task.id;
+        service
+            .tracker()
+            .clone()
+            .spawn(async move { service.run_task(id, false).await });
+    }
+
+    service.shutdown().await;
+    let status_of = |id| {
+        store
+            .executions()
+            .into_iter()
+            .find(|e| e.task_id == id)
+            .map(|e| e.status)
+    };
+    assert_eq!(status_of(quick.id), Some(ExecutionStatus::Success));
+    assert_eq!(status_of(slow.id), Some(ExecutionStatus::Interrupted));
+}
+
+/// Collects the fields of `execution` spans, as an exporter would receive them.
+#[derive(Clone, Default)]
+struct ExecutionSpanFields(Arc<std::sync::Mutex<std::collections::HashMap<String, String>>>);
+
+impl tracing::field::Visit for ExecutionSpanFields {
+    fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+        self.0
+            .lock()
+            .unwrap()
+            .insert(field.name().into(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        let value = format!("{:?}", value);
+        self.0.lock().unwrap().insert(field.name().into(), value);
+    }
+}
+
+impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for ExecutionSpanFields {
+    fn on_new_span(
+        &self,
+        attrs: &tracing::span::Attributes<'_>,
+        _id: &tracing::span::Id,
+        _ctx: tracing_subscriber::layer::Context<'_, S>,
+    ) {
+        if attrs.metadata().name() == "execution" {
+            attrs.record(&mut self.clone());
+        }
+    }
+
+    fn on_record(
+        &self,
+        _id: &tracing::span::Id,
+        values: &tracing::span::Record<'_>,
+        _ctx: tracing_subscriber::layer::Context<'_, S>,
+    ) {
+        values.record(&mut self.clone());
+    }
+}
+
+#[tokio::test]
+async fn test_executions_are_traced_with_their_outcome() {
+    use tracing_subscriber::layer::SubscriberExt;
+
+    let fields = ExecutionSpanFields::default();
+    let _guard =
+        tracing::subscriber::set_default(tracing_subscriber::registry().with(fields.clone()));
+    let (service, store) = setup_memory_service();
+    let task = Task::new_once(
+        "disabled",
+        epoch(),
+        json!({"type": "command", "program": "true"}),
+    );
+    store.create_task(&task).await.unwrap();
+
+    service.process_task(task.clone()).await.unwrap();
+
+    let execution = store.executions().pop().unwrap();
+    let fields = fields.0.lock().unwrap();
+    assert_eq!(fields["task_id"], task.id.to_string());
+    assert_eq!(fields["execution_id"], execution.id.to_string());
+    assert_eq!(fields["executor"], "command");
+    assert_eq!(fields["outcome"], "failure");
+    assert!(fields.contains_key("duration_ms"));
+}
+
+#[tokio::test]
+async fn test_scheduling_lag_is_recorded_and_exposed() {
+    let (service, store) = setup_memory_service();
+    let task = Task::new_once(
+        "late",
+        epoch() - Duration::seconds(3),
+        json!({"type": "command", "program": "true"}),
+    );
+    store.create_task(&task).await.unwrap();
+
+    service.process_task(task.clone()).await.unwrap();
+
+    let execution = store.executions().pop().unwrap();
+    assert_eq!(execution.lag_ms, Some(3000));
+    let metrics = service.metrics().render();
+    assert!(metrics.contains("scheduler_lag_seconds_bucket{le=\"1\"} 0\n"));
+    assert!(metrics.contains("scheduler_lag_seconds_bucket{le=\"5\"} 1\n"));
+    assert!(metrics.contains("scheduler_lag_seconds_count 1\n"));
+    assert!(metrics.contains("scheduler_last_lag_seconds 3\n"));
+
+    // Runs started on demand were never due
+    let execution = service.run_now(&task, false).await.unwrap();
+    assert_eq!(execution.lag_ms, None);
+}
+
+#[tokio::test]
+async fn test_executions_record_when_they_were_due_started_and_finished() {
+    let (service, store) = setup_memory_service();
+    let task = Task::new_once(
+        "timed",
+        epoch() - Duration::minutes(1),
+        json!({"type": "command", "program": "true"}),
+    );
+    store.create_task(&task).await.unwrap();
+
+    service.process_task(task.clone()).await.unwrap();
+
+    let execution = store.executions().pop().unwrap();
+    assert_eq!(execution.scheduled_for, Some(task.trigger_at));
+    assert_eq!(execution.started_at, Some(epoch()));
+    assert_eq!(execution.finished_at, Some(epoch()));
+    assert_eq!(execution.duration_ms, Some(0));
+
+    let execution = service.run_now(&task, false).await.unwrap();
+    assert_eq!(execution.scheduled_for, None);
+    assert_eq!(execution.started_at, Some(epoch()));
+    assert_eq!(execution.finished_at, Some(epoch()));
+}
+
+#[tokio::test]
+async fn test_large_outputs_are_truncated() {
+    let (service, store) = setup_memory_service();
+    let service = service.with_config(crate::config::Config {
+        max_output_bytes: 2000,
+        ..crate::config::Config::default()
+    });
+    let body = json!({"id": 7, "items": ["x".repeat(10_000)]}).to_string();
+    let hook = axum::Router::new().route("/", axum::routing::get(move || async move { body }));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, hook).await.unwrap() });
+
+    let whole = Task::new_once("whole", epoch(), json!({"url": url, "method": "GET"}));
+    store.create_task(&whole).await.unwrap();
+    service.process_task(whole.clone()).await.unwrap();
+    let output = store.executions().pop().unwrap().output;
+    assert!(output.to_string().len() <= 2000);
+    assert_eq!(output["truncated"], true);
+    assert!(output["original_bytes"].as_u64().unwrap() > 10_000);
+    assert_eq!(output["status"], 200);
+    let response = output["response"].as_str().unwrap();
+    assert!(response.starts_with("{\"id\":7,"));
+    assert!(response.ends_with(crate::service::output::TRUNCATION_MARKER));
+
+    // Only the fields asked for are kept, which fit
+    let picked = Task::new_once(
+        "picked",
+        epoch(),
+        json!({"url": url, "method": "GET", "response_fields": ["$.id", "$.missing"]}),
+    );
+    store.create_task(&picked).await.unwrap();
+    service.process_task(picked.clone()).await.unwrap();
+    let output = store.executions().pop().unwrap().output;
+    assert_eq!(
+        output,
+        json!({"status": 200, "response": {"$.id": 7, "$.missing": null}})
+    );
+}
+
+/// Every file under an archive directory.
+fn archived_files(dir: &std::path::Path) -> Vec<std::path::PathBuf> {
+    let mut files = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(next) = dirs.pop() {
+        for entry in std::fs::read_dir(next).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    files
+}
+
+#[tokio::test]
+async fn test_pruned_executions_are_archived_before_deletion() {
+    use crate::db::store::{ExecutionWrite, FollowUp};
+    use crate::retention::archive::ArchiveTarget;
+
+    let dir = std::env::temp_dir().join(format!("scheduler-archive-{}", uuid::Uuid::new_v4()));
+    let (service, store) = setup_memory_service();
+    let service = service.with_config(crate::config::Config {
+        execution_retention_days: Some(30),
+        prune_batch_size: 2,
+        execution_archive: Some(ArchiveTarget::Local(dir.clone())),
+        ..crate::config::Config::default()
+    });
+    let task = Task::new_interval("audited", epoch(), 60, json!({}));
+    store.create_task(&task).await.unwrap();
+    let writes: Vec<ExecutionWrite> = [0, 40, 50, 400]
+        .into_iter()
+        .map(|days_ago| ExecutionWrite {
+            execution: Execution {
+                executed_at: epoch() - Duration::days(days_ago),
+                ..Execution::new(
+                    task.id,
+                    json!({"days_ago": days_ago}),
+                    ExecutionStatus::Success,
+                )
+            },
+            follow_up: FollowUp::Keep,
+        })
+        .collect();
+    store.record_executions(&writes).await.unwrap();
+
+    assert_eq!(service.prune_executions().await.unwrap(), 3);
+    assert_eq!(store.executions().len(), 1);
+
+    // One file per batch, under the day it was archived
+    let files = archived_files(&dir);
+    assert_eq!(files.len(), 2);
+    for file in &files {
+        assert!(file.starts_with(dir.join("2024/01/01")));
+    }
+    let mut archived: Vec<Execution> = files
+        .iter()
+        .flat_map(|file| {
+            std::fs::read_to_string(file)
+                .unwrap()
+                .lines()
+                .map(|line| serde_json::from_str(line).unwrap())
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    archived.sort_by_key(|e| e.executed_at);
+    let days_ago: Vec<_> = archived
+        .iter()
+        .map(|e| e.output["days_ago"].clone())
+        .collect();
+    assert_eq!(days_ago, vec![json!(400), json!(50), json!(40)]);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_purged_tasks_have_their_executions_archived() {
+    use crate::db::store::{ExecutionWrite, FollowUp};
+    use crate::retention::archive::ArchiveTarget;
+
+    let dir = std::env::temp_dir().join(format!("scheduler-archive-{}", uuid::Uuid::new_v4()));
+    let (service, store) = setup_memory_service();
+    let service = service.with_config(crate::config::Config {
+        deleted_task_retention_days: Some(30),
+        prune_batch_size: 2,
+        execution_archive: Some(ArchiveTarget::Local(dir.clone())),
+        ..crate::config::Config::default()
+    });
+    let binned = Task {
+        deleted_at: Some(epoch() - Duration::days(40)),
+        ..Task::new_interval("binned", epoch(), 60, json!({}))
+    };
+    let live = Task::new_interval("live", epoch(), 60, json!({}));
+    for (task, runs) in [(&binned, 3), (&live, 2)] {
+        store.create_task(task).await.unwrap();
+        let writes: Vec<ExecutionWrite> = (0..runs)
+            .map(|minutes_ago| ExecutionWrite {
+                execution: Execution {
+                    executed_at: epoch() - Duration::minutes(minutes_ago),
+                    ..Execution::new(task.id, json!({}), ExecutionStatus::Success)
+                },
+                follow_up: FollowUp::Keep,
+            })
+            .collect();
+        store.record_executions(&writes).await.unwrap();
+    }
+
+    assert_eq!(service.purge_deleted_tasks().await.unwrap(), 1);
+    service.purge_task(live.id).await.unwrap();
+    assert!(store.executions().is_empty());
+
+    // Up to a batch of executions per file: two for the binned task, one for the live one
+    let files = archived_files(&dir);
+    assert_eq!(files.len(), 3);
+    let mut archived: Vec<uuid::Uuid> = files
+        .iter()
+        .flat_map(|file| {
+            std::fs::read_to_string(file)
+                .unwrap()
+                .lines()
+                .map(|line| serde_json::from_str::<Execution>(line).unwrap().task_id)
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    archived.sort();
+    let mut expected = vec![binned.id, binned.id, binned.id, live.id, live.id];
+    expected.sort();
+    assert_eq!(archived, expected);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_failed_executions_are_retried_with_backoff() {
+    let store = Arc::new(MemoryTaskStore::new());
+    let clock = Arc::new(TestClock::new(epoch()));
+    let (tx, _) = mpsc::channel(1);
+    let service = TaskService::with_store(store.clone(), tx).with_clock(clock.clone());
+
+    // Without a URL every run fails
+    let task = Task {
+        retry_policy: Some(ExecutionRetry {
+            max_retries: 2,
+            initial_delay_seconds: 10,
+            backoff_multiplier: 2.0,
+        }),
+        ..Task::new_once("flaky", epoch(), json!({}))
+    };
+    store.create_task(&task).await.unwrap();
+
+    service.process_task(task.clone()).await.unwrap();
+    let task = store.get_task(task.id).await.unwrap().unwrap();
+    assert_eq!(task.retry_attempt, 1);
+    assert_eq!(task.trigger_at, epoch() + Duration::seconds(10));
+
+    clock.set(task.trigger_at);
+    service.process_task(task.clone()).await.unwrap();
+    let task = store.get_task(task.id).await.unwrap().unwrap();
+    assert_eq!(task.retry_attempt, 2);
+    assert_eq!(task.trigger_at, epoch() + Duration::seconds(30));
+    assert!(task.deleted_at.is_none());
+
+    // Out of retries, the once task is done with
+    clock.set(task.trigger_at);
+    service.process_task(task.clone()).await.unwrap();
+    let task = store.get_task(task.id).await.unwrap().unwrap();
+    assert!(task.deleted_at.is_some());
+    assert_eq!(store.executions().len(), 3);
+}
+
+#[tokio::test]
+async fn test_executions_record_their_attempt_and_failure_kind() {
+    let store = Arc::new(MemoryTaskStore::new());
+    let clock = Arc::new(TestClock::new(epoch()));
+    let (tx, _) = mpsc::channel(1);
+    let service = TaskService::with_store(store.clone(), tx).with_clock(clock.clone());
+    let unavailable = axum::Router::new().route(
+        "/",
+        axum::routing::get(|| async { axum::http::StatusCode::SERVICE_UNAVAILABLE }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let unavailable_url = format!("http://{}/", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, unavailable).await.unwrap() });
+    // Nothing listens on a port just given back
+    let closed = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let closed_url = format!("http://{}/", closed.local_addr().unwrap());
+    drop(closed);
+
+    let retried = |name, payload| Task {
+        retry_policy: Some(ExecutionRetry {
+            max_retries: 1,
+            initial_delay_seconds: 10,
+            backoff_multiplier: 1.0,
+        }),
+        ..Task::new_once(name, epoch(), payload)
+    };
+    let cases = [
+        (
+            retried(
+                "unavailable",
+                json!({"url": unavailable_url, "method": "GET"}),
+            ),
+            FailureKind::HttpStatus,
+        ),
+        (
+            retried("unreachable", json!({"url": closed_url, "method": "GET"})),
+            FailureKind::Connection,
+        ),
+        (retried("no-url", json!({})), FailureKind::Validation),
+    ];
+    for (task, kind) in cases {
+        store.create_task(&task).await.unwrap();
+        clock.set(epoch());
+        service.process_task(task.clone()).await.unwrap();
+        let task = store.get_task(task.id).await.unwrap().unwrap();
+        clock.set(task.trigger_at);
+        service.process_task(task.clone()).await.unwrap();
+
+        let executions = store
+            .list_execution_records(task.id, None, 10)
+            .await
+            .unwrap();
+        let attempts: Vec<_> = executions.iter().map(|e| e.attempt).collect();
+        assert_eq!(attempts, vec![Some(2), Some(1)], "{}", task.name);
+        assert!(
+            executions.iter().all(|e| e.failure_kind == Some(kind)),
+            "{}",
+            task.name
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_update_task_replaces_the_definition_in_place() {
+    let (service, store) = setup_memory_service();
+    let task = Task::new_interval("sync", epoch(), 60, json!({ "url": "http://a" }));
+    store.create_task(&task).await.unwrap();
+    store.drain_outbox(10).await.unwrap();
+
+    let req = |interval_seconds| ReplaceTaskReq {
+        name: "sync-hourly".into(),
+        trigger_at: epoch() + Duration::minutes(30),
+        interval_seconds,
+        cron_expr: None,
+        payload: Some(json!({ "url": "http://b" })),
+    };
+    let invalid = service.update_task(task.id, req(None)).await;
+    assert!(matches!(invalid, Err(AppError::ValidationError(_))));
+
+    service.update_task(task.id, req(Some(3600))).await.unwrap();
+    let updated = store.get_task(task.id).await.unwrap().unwrap();
+    assert_eq!(updated.name, "sync-hourly");
+    assert_eq!(updated.interval_seconds, Some(3600));
+    assert_eq!(updated.payload, json!({ "url": "http://b" }));
+    assert_eq!(updated.signing_secret, task.signing_secret);
+    assert_eq!(
+        store.drain_outbox(10).await.unwrap(),
+        vec![ScheduleEvent::Upserted {
+            id: task.id,
+            trigger_at: epoch() + Duration::minutes(30),
+        }]
+    );
+
+    store.delete_task(task.id).await.unwrap();
+    let deleted = service.update_task(task.id, req(Some(3600))).await;
+    assert!(matches!(deleted, Err(AppError::NotFound)));
+}
+
+#[sqlx::test]
+async fn test_patch_task_changes_only_the_given_fields(pool: SqlitePool) -> sqlx::Result<()> {
+    let service = setup_service(pool.clone());
+    let repo = crate::db::queries::TaskRepository::new(&pool);
+    let interval = Task::new_interval("poll", epoch(), 60, json!({ "url": "http://a" }));
+    let once = Task::new_once("report", epoch(), json!({}));
+    repo.create_task(&interval).await?;
+    repo.create_task(&once).await?;
+
+    let bump = || UpdateTaskReq {
+        interval_seconds: Some(300),
+        ..Default::default()
+    };
+    service.patch_task(interval.id, bump()).await.unwrap();
+    let patched = repo.get_task(interval.id).await?.unwrap();
+    assert_eq!(patched.interval_seconds, Some(300));
+    assert_eq!(patched.name, interval.name);
+    assert_eq!(patched.trigger_at, interval.trigger_at);
+    assert_eq!(patched.payload, interval.payload);
+
+    let wrong_type = service.patch_task(once.id, bump()).await;
+    assert!(matches!(wrong_type, Err(AppError::ValidationError(_))));
+    let too_small = UpdateTaskReq {
+        interval_seconds: Some(0),
+        ..Default::default()
+    };
+    let too_small = service.patch_task(interval.id, too_small).await;
+    assert!(matches!(too_small, Err(AppError::IntervalTooSmall(1))));
 
     Ok(())
 }
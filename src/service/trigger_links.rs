@@ -0,0 +1,128 @@
+use crate::db::retry::with_retry;
+use crate::domain::{Execution, Task, TriggerLink};
+use crate::errors::AppError;
+use crate::service::api_keys::hash_key;
+use crate::service::signing::{active_secrets, sign};
+use crate::service::{TaskService, merge_payload};
+use chrono::{DateTime, Duration, Utc};
+use serde_json::Value;
+use uuid::Uuid;
+
+/// Signature of a trigger link under a task signing secret. It covers the link ID and its
+/// expiry, so neither can be altered in the URL.
+pub fn link_signature(secret: &str, id: Uuid, expires_at: DateTime<Utc>) -> String {
+    sign(secret, expires_at.timestamp(), id.as_bytes())
+}
+
+/// Path that redeems a link, relative to the API's base URL.
+pub fn link_path(id: Uuid, signature: &str) -> String {
+    format!("/trigger/{}?sig={}", id, signature)
+}
+
+impl TaskService {
+    /// Mints a single-use link that fires a task once, without an API key, until it expires.
+    ///
+    /// The link is signed with the task's signing secret, so rotating the secret also
+    /// invalidates outstanding links once the overlap ends.
+    ///
+    /// # Arguments
+    ///
+    /// * `task_id` - The UUID of the task to fire.
+    /// * `ttl` - How long the link stays valid.
+    /// * `payload` - Fields merged over the task's payload for the triggered run.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::NotFound' if the task does not exist or is deleted.
+    /// * Returns 'AppError::ValidationError' if the task has no signing secret.
+    /// * Returns 'AppError::Database' for any database operation failures.
+    ///
+    /// Returns the link and its signature.
+    pub async fn create_trigger_link(
+        &self,
+        task_id: Uuid,
+        ttl: Duration,
+        payload: Option<Value>,
+    ) -> Result<(TriggerLink, String), AppError> {
+        let policy = self.config.retry_policy();
+        let task = with_retry(&policy, || self.store.get_task(task_id))
+            .await?
+            .filter(|task| task.deleted_at.is_none())
+            .ok_or(AppError::NotFound)?;
+        let secret = task.signing_secret.as_deref().ok_or_else(|| {
+            AppError::ValidationError(
+                "Task has no signing secret; rotate its secret before minting links".into(),
+            )
+        })?;
+
+        let now = self.clock.now();
+        let link = TriggerLink {
+            id: Uuid::new_v4(),
+            task_id,
+            payload,
+            expires_at: now + ttl,
+            used_at: None,
+            created_at: now,
+        };
+        with_retry(&policy, || self.store.create_trigger_link(&link)).await?;
+
+        let signature = link_signature(secret, link.id, link.expires_at);
+        Ok((link, signature))
+    }
+
+    /// Redeems a trigger link and runs its task once (see [`TaskService::run_now`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The UUID of the link.
+    /// * `signature` - The signature from the link's URL.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::NotFound' if the link is unknown, badly signed, expired or already
+    ///   used, or if its task was deleted. The cases are not told apart.
+    /// * Returns 'AppError::Database' for any database operation failures.
+    ///
+    /// Returns the finished execution.
+    pub async fn fire_trigger_link(
+        &self,
+        id: Uuid,
+        signature: &str,
+    ) -> Result<Execution, AppError> {
+        let policy = self.config.retry_policy();
+        let now = self.clock.now();
+
+        let link = with_retry(&policy, || self.store.get_trigger_link(id))
+            .await?
+            .ok_or(AppError::NotFound)?;
+        let task = with_retry(&policy, || self.store.get_task(link.task_id))
+            .await?
+            .filter(|task| task.deleted_at.is_none())
+            .ok_or(AppError::NotFound)?;
+
+        // Comparing hashes keeps the comparison independent of the signature's content
+        let presented = hash_key(signature);
+        let valid = active_secrets(&task, now)
+            .into_iter()
+            .any(|secret| hash_key(&link_signature(secret, link.id, link.expires_at)) == presented);
+        if !valid {
+            tracing::warn!(link_id = %id, "Rejected trigger link with a bad signature");
+            return Err(AppError::NotFound);
+        }
+
+        if !with_retry(&policy, || self.store.redeem_trigger_link(id, now)).await? {
+            return Err(AppError::NotFound);
+        }
+
+        tracing::info!(link_id = %id, task_id = %task.id, "Trigger link redeemed");
+
+        let task = match link.payload {
+            Some(overrides) => Task {
+                payload: merge_payload(task.payload.clone(), overrides),
+                ..task
+            },
+            None => task,
+        };
+        self.run_now(&task, false).await
+    }
+}
@@ -0,0 +1,360 @@
+use crate::db::retry::with_retry;
+use crate::domain::{
+    Execution, ExecutionStatus, NodeState, Workflow, WorkflowNode, WorkflowRun, WorkflowRunStatus,
+};
+use crate::errors::AppError;
+use crate::service::TaskService;
+use futures_util::future;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// Runs of a workflow returned by one listing.
+pub const WORKFLOW_RUNS_PAGE_SIZE: i64 = 50;
+
+/// Orders workflow nodes so that each comes after the nodes it follows, keeping the given
+/// order where the edges allow.
+///
+/// # Errors
+///
+/// * Returns 'AppError::ValidationError' if a key is blank or repeated, a node follows
+///   itself or an unknown key, or the edges form a cycle.
+fn sort_nodes(nodes: Vec<WorkflowNode>) -> Result<Vec<WorkflowNode>, AppError> {
+    let mut keys = HashSet::new();
+    for node in &nodes {
+        if node.key.trim().is_empty() {
+            return Err(AppError::ValidationError(
+                "Node keys must not be blank".into(),
+            ));
+        }
+        if !keys.insert(node.key.as_str()) {
+            return Err(AppError::ValidationError(format!(
+                "Node key '{}' is used more than once",
+                node.key
+            )));
+        }
+    }
+    for node in &nodes {
+        if let Some(after) = node
+            .after
+            .iter()
+            .find(|after| **after == node.key || !keys.contains(after.as_str()))
+        {
+            return Err(AppError::ValidationError(format!(
+                "Node '{}' cannot follow '{}'",
+                node.key, after
+            )));
+        }
+    }
+
+    let mut placed: HashSet<String> = HashSet::new();
+    let mut remaining = nodes;
+    let mut sorted = Vec::with_capacity(remaining.len());
+    while !remaining.is_empty() {
+        let (ready, blocked): (Vec<_>, Vec<_>) = remaining
+            .into_iter()
+            .partition(|node| node.after.iter().all(|after| placed.contains(after)));
+        if ready.is_empty() {
+            return Err(AppError::ValidationError(
+                "Workflow nodes must not form a cycle".into(),
+            ));
+        }
+        placed.extend(ready.iter().map(|node| node.key.clone()));
+        sorted.extend(ready);
+        remaining = blocked;
+    }
+    Ok(sorted)
+}
+
+impl TaskService {
+    /// Creates a workflow: a named graph of existing tasks, each run once the nodes it
+    /// follows have succeeded.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Display name of the workflow.
+    /// * `nodes` - The graph's nodes, with the keys of the nodes each follows.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::ValidationError' if:
+    /// * the name is blank or there are no nodes.
+    /// * a node key is blank or repeated, or a node follows itself or an unknown key.
+    /// * the nodes form a cycle.
+    /// * a node's task or compensating task does not name a live task.
+    ///
+    /// * Returns 'AppError::Database' for any database operation failures.
+    ///
+    /// Returns the new workflow, its nodes in the order they can run in.
+    pub async fn create_workflow(
+        &self,
+        name: String,
+        nodes: Vec<WorkflowNode>,
+    ) -> Result<Workflow, AppError> {
+        if name.trim().is_empty() {
+            return Err(AppError::ValidationError(
+                "Workflow name is required".into(),
+            ));
+        }
+        if nodes.is_empty() {
+            return Err(AppError::ValidationError(
+                "A workflow needs at least one node".into(),
+            ));
+        }
+        let nodes = sort_nodes(nodes)?;
+        for task_id in nodes
+            .iter()
+            .flat_map(|node| std::iter::once(node.task_id).chain(node.compensate_with))
+        {
+            self.check_dependency_exists(Some(task_id)).await?;
+        }
+
+        let workflow = Workflow {
+            id: Uuid::new_v4(),
+            name,
+            nodes,
+            created_at: self.clock.now(),
+        };
+        with_retry(&self.config.retry_policy(), || {
+            self.store.create_workflow(&workflow)
+        })
+        .await?;
+
+        Ok(workflow)
+    }
+
+    /// Lists every workflow, ordered by name.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::Database' for any database operation failures.
+    pub async fn list_workflows(&self) -> Result<Vec<Workflow>, AppError> {
+        let workflows = self.store.list_workflows().await?;
+        Ok(workflows)
+    }
+
+    /// Retrieves a workflow.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::NotFound' if the workflow does not exist.
+    /// * Returns 'AppError::Database' for any database operation failures.
+    pub async fn get_workflow(&self, id: Uuid) -> Result<Workflow, AppError> {
+        with_retry(&self.config.retry_policy(), || self.store.get_workflow(id))
+            .await?
+            .ok_or(AppError::NotFound)
+    }
+
+    /// Retrieves a workflow run.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::NotFound' if the run does not exist.
+    /// * Returns 'AppError::Database' for any database operation failures.
+    pub async fn get_workflow_run(&self, id: Uuid) -> Result<WorkflowRun, AppError> {
+        with_retry(&self.config.retry_policy(), || {
+            self.store.get_workflow_run(id)
+        })
+        .await?
+        .ok_or(AppError::NotFound)
+    }
+
+    /// Lists a workflow's latest runs, newest first.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::NotFound' if the workflow does not exist.
+    /// * Returns 'AppError::Database' for any database operation failures.
+    pub async fn list_workflow_runs(&self, id: Uuid) -> Result<Vec<WorkflowRun>, AppError> {
+        self.get_workflow(id).await?;
+        let runs = self
+            .store
+            .list_workflow_runs(id, WORKFLOW_RUNS_PAGE_SIZE)
+            .await?;
+        Ok(runs)
+    }
+
+    /// Starts a run of a workflow in the background; follow it with
+    /// [`TaskService::get_workflow_run`].
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::NotFound' if the workflow does not exist.
+    /// * Returns 'AppError::Database' for any database operation failures.
+    ///
+    /// Returns the new run, with every node pending.
+    pub async fn start_workflow(&self, id: Uuid) -> Result<WorkflowRun, AppError> {
+        let workflow = self.get_workflow(id).await?;
+        let run = WorkflowRun::new(&workflow, self.clock.now());
+        with_retry(&self.config.retry_policy(), || {
+            self.store.create_workflow_run(&run)
+        })
+        .await?;
+
+        tracing::info!(workflow_id = %id, run_id = %run.id, "Workflow run started");
+        self.spawn_workflow_run(workflow, run.clone());
+        Ok(run)
+    }
+
+    /// Picks up the runs a previous process left unfinished. Nodes that were running are
+    /// run again, since their outcome was never recorded on the run.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::Database' for any database operation failures.
+    pub async fn resume_workflow_runs(&self) -> Result<(), AppError> {
+        let runs = with_retry(&self.config.retry_policy(), || {
+            self.store.unfinished_workflow_runs()
+        })
+        .await?;
+        for mut run in runs {
+            let Some(workflow) = self.store.get_workflow(run.workflow_id).await? else {
+                continue;
+            };
+            run.nodes
+                .values_mut()
+                .filter(|node| node.state == NodeState::Running)
+                .for_each(|node| node.state = NodeState::Pending);
+
+            tracing::info!(run_id = %run.id, "Resuming workflow run");
+            self.spawn_workflow_run(workflow, run);
+        }
+        Ok(())
+    }
+
+    fn spawn_workflow_run(&self, workflow: Workflow, run: WorkflowRun) {
+        let service = self.clone();
+        self.tracker.spawn(async move {
+            let run_id = run.id;
+            if let Err(e) = service.advance_workflow_run(&workflow, run).await {
+                tracing::error!(%run_id, "Workflow run stopped: {:?}", e);
+            }
+        });
+    }
+
+    /// Advances a run through its graph until every node has settled. Each round runs the
+    /// pending nodes whose predecessors all succeeded, side by side, and saves the run. The
+    /// nodes after a failed one fail too without running; once nothing else can run, the
+    /// nodes that succeeded are compensated in reverse order before the run is marked
+    /// failed.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::Database' if the run cannot be saved.
+    ///
+    /// Returns the finished run.
+    pub async fn advance_workflow_run(
+        &self,
+        workflow: &Workflow,
+        mut run: WorkflowRun,
+    ) -> Result<WorkflowRun, AppError> {
+        loop {
+            // Nodes are in dependency order, so failures propagate in a single pass
+            for node in &workflow.nodes {
+                let blocked = node.after.iter().any(|after| {
+                    matches!(
+                        run.state(after),
+                        NodeState::Failed | NodeState::UpstreamFailed
+                    )
+                });
+                if blocked && run.state(&node.key) == NodeState::Pending {
+                    run.nodes.entry(node.key.clone()).or_default().state =
+                        NodeState::UpstreamFailed;
+                }
+            }
+            let ready: Vec<&WorkflowNode> = workflow
+                .nodes
+                .iter()
+                .filter(|node| run.state(&node.key) == NodeState::Pending)
+                .filter(|node| {
+                    node.after
+                        .iter()
+                        .all(|after| run.state(after) == NodeState::Succeeded)
+                })
+                .collect();
+            if ready.is_empty() {
+                break;
+            }
+
+            for node in &ready {
+                run.nodes.entry(node.key.clone()).or_default().state = NodeState::Running;
+            }
+            self.save_workflow_run(&run).await?;
+
+            let results = future::join_all(
+                ready
+                    .iter()
+                    .map(|node| self.run_workflow_task(node.task_id)),
+            )
+            .await;
+            for (node, result) in ready.into_iter().zip(results) {
+                let entry = run.nodes.entry(node.key.clone()).or_default();
+                entry.execution_id = result.as_ref().ok().map(|execution| execution.id);
+                entry.state = match result {
+                    Ok(execution) if execution.status == ExecutionStatus::Success => {
+                        NodeState::Succeeded
+                    }
+                    Ok(_) => NodeState::Failed,
+                    Err(e) => {
+                        tracing::warn!(run_id = %run.id, node = %node.key, "Node could not run: {:?}", e);
+                        NodeState::Failed
+                    }
+                };
+            }
+            self.save_workflow_run(&run).await?;
+        }
+
+        let failed = run
+            .nodes
+            .values()
+            .any(|node| node.state == NodeState::Failed);
+        if failed {
+            for node in workflow.nodes.iter().rev() {
+                let Some(task_id) = node.compensate_with else {
+                    continue;
+                };
+                if run.state(&node.key) != NodeState::Succeeded {
+                    continue;
+                }
+                let result = self.run_workflow_task(task_id).await;
+                let entry = run.nodes.entry(node.key.clone()).or_default();
+                entry.compensation_id = result.as_ref().ok().map(|execution| execution.id);
+                entry.state = match result {
+                    Ok(execution) if execution.status == ExecutionStatus::Success => {
+                        NodeState::Compensated
+                    }
+                    _ => {
+                        tracing::warn!(run_id = %run.id, node = %node.key, "Compensation failed");
+                        NodeState::CompensationFailed
+                    }
+                };
+            }
+        }
+
+        run.status = if failed {
+            WorkflowRunStatus::Failed
+        } else {
+            WorkflowRunStatus::Succeeded
+        };
+        run.finished_at = Some(self.clock.now());
+        self.save_workflow_run(&run).await?;
+        tracing::info!(run_id = %run.id, status = ?run.status, "Workflow run finished");
+        Ok(run)
+    }
+
+    /// Runs a node's task once, outside its schedule.
+    async fn run_workflow_task(&self, task_id: Uuid) -> Result<Execution, AppError> {
+        let task = with_retry(&self.config.retry_policy(), || self.store.get_task(task_id))
+            .await?
+            .filter(|task| task.deleted_at.is_none())
+            .ok_or(AppError::NotFound)?;
+        self.run_now(&task, false).await
+    }
+
+    async fn save_workflow_run(&self, run: &WorkflowRun) -> Result<(), AppError> {
+        with_retry(&self.config.retry_policy(), || {
+            self.store.update_workflow_run(run)
+        })
+        .await?;
+        Ok(())
+    }
+}
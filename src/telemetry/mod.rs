@@ -0,0 +1,119 @@
+//! Export of tracing spans to an OpenTelemetry collector (Jaeger, Tempo, ...) over
+//! OTLP/HTTP, so that the `http_request` spans of the API and the `execution` spans of task
+//! runs can be followed outside the local logs.
+//!
+//! Export needs a build with the `otel` feature and `OTEL_EXPORTER_OTLP_ENDPOINT` set;
+//! otherwise spans only reach the local log output.
+use crate::config::Config;
+use crate::errors::AppError;
+use tracing_subscriber::{Layer, Registry};
+use uuid::Uuid;
+
+/// W3C Trace Context header carrying the trace an outgoing call belongs to.
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+
+/// A tracing layer over the bare registry, so it can sit first in the subscriber stack.
+pub type BoxedLayer = Box<dyn Layer<Registry> + Send + Sync>;
+
+/// Owns the span exporter, whose buffered spans are sent when it is shut down.
+#[derive(Default)]
+pub struct Telemetry {
+    #[cfg(feature = "otel")]
+    provider: Option<opentelemetry_sdk::trace::SdkTracerProvider>,
+}
+
+impl Telemetry {
+    /// Sets up span export if a collector is configured.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Supplies the collector endpoint and the service name spans carry.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::Config' if the endpoint is not a valid URL.
+    ///
+    /// # Returns
+    ///
+    /// The exporter handle, and the layer feeding it if export is on.
+    #[cfg_attr(not(feature = "otel"), allow(unused_variables))]
+    pub fn init(config: &Config) -> Result<(Self, Option<BoxedLayer>), AppError> {
+        #[cfg(feature = "otel")]
+        if let Some(endpoint) = &config.otel_endpoint {
+            return Self::otlp(endpoint, &config.otel_service_name);
+        }
+        Ok((Self::default(), None))
+    }
+
+    #[cfg(feature = "otel")]
+    fn otlp(endpoint: &str, service_name: &str) -> Result<(Self, Option<BoxedLayer>), AppError> {
+        use opentelemetry::trace::TracerProvider;
+        use opentelemetry_otlp::WithExportConfig;
+        use opentelemetry_sdk::{Resource, trace::SdkTracerProvider};
+
+        let exporter = opentelemetry_otlp::SpanExporter::builder()
+            .with_http()
+            .with_endpoint(format!("{}/v1/traces", endpoint.trim_end_matches('/')))
+            .build()
+            .map_err(|e| AppError::Config(format!("Invalid OTLP endpoint: {}", e)))?;
+        let provider = SdkTracerProvider::builder()
+            .with_batch_exporter(exporter)
+            .with_resource(
+                Resource::builder()
+                    .with_service_name(service_name.to_string())
+                    .build(),
+            )
+            .build();
+        opentelemetry::global::set_tracer_provider(provider.clone());
+
+        let layer = tracing_opentelemetry::layer().with_tracer(provider.tracer("task-scheduler"));
+        Ok((
+            Self {
+                provider: Some(provider),
+            },
+            Some(Box::new(layer)),
+        ))
+    }
+
+    /// Sends the spans still buffered and stops exporting. Blocks until the collector has
+    /// received them or the export times out.
+    pub fn shutdown(self) {
+        #[cfg(feature = "otel")]
+        if let Some(provider) = self.provider
+            && let Err(e) = provider.shutdown()
+        {
+            tracing::warn!("Failed to flush exported spans: {}", e);
+        }
+    }
+}
+
+/// The `traceparent` value for a call made by the execution `execution_id`, naming the
+/// current span as the caller.
+///
+/// While spans are exported, this is the span's own trace, so the downstream service's spans
+/// join it in the collector. Otherwise the trace id is the execution id, which downstream
+/// logs can be searched for, and the caller a random span id.
+pub fn traceparent(execution_id: Uuid) -> String {
+    #[cfg(feature = "otel")]
+    {
+        use opentelemetry::trace::TraceContextExt;
+        use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+        let context = tracing::Span::current().context();
+        let span = context.span();
+        let span_context = span.span_context();
+        if span_context.is_valid() {
+            return format!(
+                "00-{}-{}-{:02x}",
+                span_context.trace_id(),
+                span_context.span_id(),
+                span_context.trace_flags().to_u8()
+            );
+        }
+    }
+    format!(
+        "00-{}-{:016x}-01",
+        execution_id.simple(),
+        rand::random::<u64>().max(1)
+    )
+}
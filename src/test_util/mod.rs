@@ -0,0 +1,244 @@
+//! Helpers for integration tests, enabled with the `test-util` feature.
+//!
+//! Downstream crates embedding the scheduler can use these to stand up a migrated SQLite
+//! database, run the full HTTP + scheduler stack on a random port and build fixtures,
+//! without copying our end-to-end boilerplate:
+//!
+//! ```ignore
+//! let db = TempDatabase::new().await?;
+//! let app = spawn_app(db.pool().clone()).await;
+//! // ... create tasks through `app.address` ...
+//! let seen = wait_for_executions(&app.pool, task_id, 1, Duration::from_secs(5)).await;
+//! ```
+use crate::api;
+use crate::clock::Clock;
+use crate::config::Config;
+use crate::db;
+use crate::domain::{Execution, ExecutionStatus, Task};
+use crate::errors::AppError;
+use crate::scheduler;
+use crate::service::TaskService;
+use chrono::{DateTime, Duration, Utc};
+use serde_json::{Value, json};
+use sqlx::SqlitePool;
+use sqlx::sqlite::SqlitePoolOptions;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+#[cfg(test)]
+mod tests;
+
+/// A migrated SQLite database in a temporary file, removed again on drop.
+#[derive(Debug)]
+pub struct TempDatabase {
+    pool: SqlitePool,
+    path: PathBuf,
+}
+
+impl TempDatabase {
+    /// Creates the database file and applies all migrations.
+    ///
+    /// # Errors
+    ///
+    /// * Returns 'AppError::Database' if the database cannot be opened or migrated.
+    pub async fn new() -> Result<Self, AppError> {
+        let path = std::env::temp_dir().join(format!("scheduler-test-{}.db", Uuid::new_v4()));
+        let config = Config {
+            db_url: format!("sqlite:{}", path.display()),
+            ..Config::default()
+        };
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(5)
+            .connect_with(db::connect_options(&config)?)
+            .await?;
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .map_err(|e| AppError::Database(e.into()))?;
+
+        Ok(Self { pool, path })
+    }
+
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+}
+
+impl Drop for TempDatabase {
+    fn drop(&mut self) {
+        for suffix in ["", "-shm", "-wal"] {
+            let _ = std::fs::remove_file(format!("{}{}", self.path.display(), suffix));
+        }
+    }
+}
+
+/// The HTTP API and scheduler running in the background against a test database.
+///
+/// Both are stopped when the app is dropped.
+pub struct TestApp {
+    /// Base URL of the API, e.g. `http://127.0.0.1:41234`.
+    pub address: String,
+    pub pool: SqlitePool,
+    pub service: TaskService,
+    token: CancellationToken,
+}
+
+impl Drop for TestApp {
+    fn drop(&mut self) {
+        self.token.cancel();
+    }
+}
+
+/// Starts the API and scheduler on a random local port using the default configuration.
+pub async fn spawn_app(pool: SqlitePool) -> TestApp {
+    spawn_app_with(pool, |service| service).await
+}
+
+/// Like [`spawn_app`], but lets the caller adjust the service first (config, clock,
+/// middleware) before anything starts.
+pub async fn spawn_app_with(
+    pool: SqlitePool,
+    customize: impl FnOnce(TaskService) -> TaskService,
+) -> TestApp {
+    let (scheduler_tx, scheduler_rx) = mpsc::channel(100);
+    let token = CancellationToken::new();
+
+    let service = customize(TaskService::new(pool.clone(), scheduler_tx));
+
+    let scheduler_service = service.clone();
+    let scheduler_token = token.clone();
+    tokio::spawn(async move {
+        scheduler::run_scheduler(scheduler_service, scheduler_rx, scheduler_token).await;
+    });
+
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind address");
+    let address = format!("http://{}", listener.local_addr().unwrap());
+
+    let app = api::router(service.clone());
+    let server_token = token.clone();
+    tokio::spawn(async move {
+        axum::serve(listener, app)
+            .with_graceful_shutdown(server_token.cancelled_owned())
+            .await
+            .unwrap();
+    });
+
+    TestApp {
+        address,
+        pool,
+        service,
+        token,
+    }
+}
+
+/// Polls until `task_id` has at least `count` finished executions or `timeout` elapses,
+/// returning the number of finished executions seen last.
+pub async fn wait_for_executions(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    count: i64,
+    timeout: std::time::Duration,
+) -> i64 {
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        let seen: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM executions WHERE task_id = ? AND status != 'running'",
+        )
+        .bind(task_id)
+        .fetch_one(pool)
+        .await
+        .expect("Failed to count executions");
+
+        if seen >= count || tokio::time::Instant::now() >= deadline {
+            return seen;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(25)).await;
+    }
+}
+
+/// Builder for [`Task`] fixtures. Tasks are due immediately with an empty payload unless
+/// configured otherwise.
+#[derive(Debug, Clone)]
+pub struct TaskBuilder {
+    task: Task,
+}
+
+impl TaskBuilder {
+    pub fn once(name: impl Into<String>) -> Self {
+        Self {
+            task: Task::new_once(name, Utc::now(), json!({})),
+        }
+    }
+
+    pub fn interval(name: impl Into<String>, seconds: i64) -> Self {
+        Self {
+            task: Task::new_interval(name, Utc::now(), seconds, json!({})),
+        }
+    }
+
+    pub fn trigger_at(mut self, trigger_at: DateTime<Utc>) -> Self {
+        self.task.trigger_at = trigger_at;
+        self
+    }
+
+    /// Triggers `offset` after the given clock's current time (negative for overdue tasks).
+    pub fn due_in(self, clock: &Arc<dyn Clock>, offset: Duration) -> Self {
+        let trigger_at = clock.now() + offset;
+        self.trigger_at(trigger_at)
+    }
+
+    pub fn payload(mut self, payload: Value) -> Self {
+        self.task.payload = payload;
+        self
+    }
+
+    /// Sets a `GET` webhook payload targeting `url`.
+    pub fn webhook(self, url: impl Into<String>) -> Self {
+        self.payload(json!({ "url": url.into(), "method": "GET" }))
+    }
+
+    pub fn build(self) -> Task {
+        self.task
+    }
+}
+
+/// Builder for [`Execution`] fixtures, successful and executed now by default.
+#[derive(Debug, Clone)]
+pub struct ExecutionBuilder {
+    execution: Execution,
+}
+
+impl ExecutionBuilder {
+    pub fn for_task(task: &Task) -> Self {
+        Self {
+            execution: Execution::new(task.id, json!({}), ExecutionStatus::Success),
+        }
+    }
+
+    pub fn failed(mut self, error: impl Into<String>) -> Self {
+        self.execution.status = ExecutionStatus::Failure;
+        self.execution.output = json!({ "error": error.into() });
+        self
+    }
+
+    pub fn executed_at(mut self, executed_at: DateTime<Utc>) -> Self {
+        self.execution.executed_at = executed_at;
+        self
+    }
+
+    pub fn output(mut self, output: Value) -> Self {
+        self.execution.output = output;
+        self
+    }
+
+    pub fn build(self) -> Execution {
+        self.execution
+    }
+}
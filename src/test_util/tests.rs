@@ -0,0 +1,36 @@
+use crate::db::queries::TaskRepository;
+use crate::domain::ExecutionStatus;
+use crate::test_util::{ExecutionBuilder, TaskBuilder, TempDatabase, wait_for_executions};
+use chrono::Duration;
+
+#[tokio::test]
+async fn test_temp_database_is_migrated_and_removed() {
+    let db = TempDatabase::new()
+        .await
+        .expect("Failed to create temp database");
+    let repo = TaskRepository::new(db.pool());
+
+    let task = TaskBuilder::interval("fixture", 30)
+        .trigger_at(chrono::Utc::now() - Duration::minutes(1))
+        .webhook("http://localhost/ping")
+        .build();
+    repo.create_task(&task).await.unwrap();
+
+    let execution = ExecutionBuilder::for_task(&task).failed("boom").build();
+    assert!(matches!(execution.status, ExecutionStatus::Failure));
+    TaskRepository::insert_execution_with_executor(db.pool(), &execution)
+        .await
+        .unwrap();
+
+    let seen = wait_for_executions(db.pool(), task.id, 1, std::time::Duration::ZERO).await;
+    assert_eq!(seen, 1);
+
+    let fetched = repo.get_task(task.id).await.unwrap().unwrap();
+    assert_eq!(fetched.payload["url"], "http://localhost/ping");
+    assert_eq!(fetched.interval_seconds, Some(30));
+
+    let path = db.path.clone();
+    db.pool().close().await;
+    drop(db);
+    assert!(!path.exists(), "Database file should be removed on drop");
+}
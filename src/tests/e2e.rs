@@ -1,40 +1,10 @@
 use reqwest::Client;
 use serde_json::{Value, json};
 use sqlx::SqlitePool;
-use tokio::{net::TcpListener, sync::mpsc};
-use tokio_util::sync::CancellationToken;
 
-use crate::{api, scheduler, service::TaskService};
-
-async fn spawn_app(pool: SqlitePool) -> String {
-    let (scheduler_tx, scheduler_rx) = mpsc::channel::<()>(100);
-    let token = CancellationToken::new();
-
-    let service = TaskService::new(pool.clone(), scheduler_tx);
-    let scheduler_service = service.clone();
-
-    tokio::spawn(async move {
-        scheduler::run_scheduler(scheduler_service, scheduler_rx, token).await;
-    });
-
-    let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("Failed to bind address");
-
-    listener
-        .set_nonblocking(true)
-        .expect("Failed to set non-blocking");
-    let port = listener.local_addr().unwrap().port();
-    let address = format!("http://127.0.0.1:{}", port);
-
-    let app = api::router(service);
-
-    tokio::spawn(async move {
-        axum::serve(TcpListener::from_std(listener).unwrap(), app)
-            .await
-            .unwrap();
-    });
-
-    address
-}
+use crate::service::{CORRELATION_ID_HEADER, EXECUTION_ID_HEADER, REQUEST_ID_HEADER};
+use crate::telemetry::TRACEPARENT_HEADER;
+use crate::test_util::{spawn_app, wait_for_executions};
 
 #[sqlx::test]
 async fn test_e2e_execution(pool: SqlitePool) {
@@ -42,7 +12,8 @@ async fn test_e2e_execution(pool: SqlitePool) {
         .with_env_filter("info,task_scheduler=debug,sqlx=error")
         .try_init();
 
-    let address = spawn_app(pool.clone()).await;
+    let app = spawn_app(pool.clone()).await;
+    let address = app.address.clone();
     let client = Client::new();
 
     let target_url = format!("{}/tasks", address);
@@ -66,14 +37,8 @@ async fn test_e2e_execution(pool: SqlitePool) {
     // Parse task_id as UUID object as it is stored as BLOB in the DB
     let task_uuid = uuid::Uuid::parse_str(task_id).expect("Invalid UUID format");
 
-    // Wait for some time to allow the scheduler to process the task
-    tokio::time::sleep(std::time::Duration::from_secs(3)).await;
-
-    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM executions WHERE task_id = ?")
-        .bind(task_uuid)
-        .fetch_one(&pool)
-        .await
-        .expect("Failed to query DB");
+    // Wait for the scheduler to process the task
+    let count = wait_for_executions(&pool, task_uuid, 1, std::time::Duration::from_secs(3)).await;
     assert_eq!(
         count, 1,
         "There should be one execution record for the task"
@@ -86,7 +51,8 @@ async fn test_e2e_execution(pool: SqlitePool) {
 
 #[sqlx::test]
 async fn test_scheduler_handles_http_failure(pool: SqlitePool) {
-    let address = spawn_app(pool.clone()).await;
+    let app = spawn_app(pool.clone()).await;
+    let address = app.address.clone();
     let client = Client::new();
 
     let response = client
@@ -106,10 +72,11 @@ async fn test_scheduler_handles_http_failure(pool: SqlitePool) {
         .unwrap()
         .to_string();
 
-    tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+    let task_uuid = uuid::Uuid::parse_str(&task_id).unwrap();
+    wait_for_executions(&pool, task_uuid, 1, std::time::Duration::from_secs(3)).await;
 
     let status: String = sqlx::query_scalar("SELECT status FROM executions WHERE task_id = ?")
-        .bind(uuid::Uuid::parse_str(&task_id).unwrap())
+        .bind(task_uuid)
         .fetch_one(&pool)
         .await
         .expect("Execution log missing");
@@ -120,3 +87,69 @@ async fn test_scheduler_handles_http_failure(pool: SqlitePool) {
     let _ = std::fs::remove_file("e2e_test.db-shm");
     let _ = std::fs::remove_file("e2e_test.db-wal");
 }
+
+#[sqlx::test]
+async fn test_request_id_is_propagated_to_webhooks(pool: SqlitePool) {
+    let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::<String>::new()));
+    let capture = seen.clone();
+    let received = std::sync::Arc::new(std::sync::Mutex::new(axum::http::HeaderMap::new()));
+    let received_capture = received.clone();
+    let hook = axum::Router::new().route(
+        "/hook",
+        axum::routing::get(move |headers: axum::http::HeaderMap| async move {
+            if let Some(value) = headers.get(CORRELATION_ID_HEADER) {
+                capture
+                    .lock()
+                    .unwrap()
+                    .push(value.to_str().unwrap().to_string());
+            }
+            *received_capture.lock().unwrap() = headers;
+            "ok"
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let hook_url = format!("http://{}/hook", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, hook).await.unwrap() });
+
+    let app = spawn_app(pool.clone()).await;
+    let response = Client::new()
+        .post(format!("{}/tasks", &app.address))
+        .header("x-request-id", "req-123")
+        .json(&json!({
+            "name": "correlated",
+            "task_type": "once",
+            "trigger_at": chrono::Utc::now().to_rfc3339(),
+            "payload": { "url": hook_url, "method": "GET" }
+        }))
+        .send()
+        .await
+        .unwrap();
+    let task_id = response.json::<Value>().await.unwrap()["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let task_uuid = uuid::Uuid::parse_str(&task_id).unwrap();
+
+    wait_for_executions(&pool, task_uuid, 1, std::time::Duration::from_secs(3)).await;
+
+    let execution_id: uuid::Uuid =
+        sqlx::query_scalar("SELECT id FROM executions WHERE task_id = ?")
+            .bind(task_uuid)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(
+        *seen.lock().unwrap(),
+        vec![format!("req-123:{}", execution_id)]
+    );
+    let headers = received.lock().unwrap();
+    assert_eq!(headers[REQUEST_ID_HEADER], "req-123");
+    assert_eq!(headers[EXECUTION_ID_HEADER], execution_id.to_string());
+    // W3C version-traceid-parentid-flags, traced under the execution id
+    let traceparent = headers[TRACEPARENT_HEADER].to_str().unwrap();
+    let parts: Vec<&str> = traceparent.split('-').collect();
+    assert_eq!(parts.len(), 4);
+    assert_eq!(parts[0], "00");
+    assert_eq!(parts[1], execution_id.simple().to_string());
+    assert_eq!(parts[2].len(), 16);
+}
@@ -0,0 +1,157 @@
+//! Optional HTTPS termination for the API server.
+//!
+//! Certificates are read from PEM files and watched for changes, so a rotated certificate
+//! (e.g. renewed by certbot or cert-manager) is picked up without a restart. With a client
+//! CA configured, clients must present a certificate signed by it (mutual TLS).
+use crate::errors::AppError;
+use axum_server::tls_rustls::RustlsConfig;
+use rustls::ServerConfig;
+use rustls::crypto::ring;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, pem::PemObject};
+use rustls::server::WebPkiClientVerifier;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+#[cfg(test)]
+mod tests;
+
+/// Where to find the server's certificate material.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TlsConfig {
+    /// PEM certificate chain, leaf first.
+    pub cert_path: PathBuf,
+    /// PEM private key (PKCS#8, PKCS#1 or SEC1).
+    pub key_path: PathBuf,
+    /// PEM bundle of CAs trusted to sign client certificates. Enables mutual TLS.
+    pub client_ca_path: Option<PathBuf>,
+    /// How often the files are checked for changes.
+    pub reload_interval: Duration,
+}
+
+impl TlsConfig {
+    fn watched_paths(&self) -> impl Iterator<Item = &Path> {
+        [
+            Some(&self.cert_path),
+            Some(&self.key_path),
+            self.client_ca_path.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        .map(PathBuf::as_path)
+    }
+}
+
+/// Builds a rustls server configuration from the configured files.
+///
+/// # Errors
+///
+/// * Returns 'AppError::Config' if a file cannot be read or parsed, or the key does not
+///   match the certificate.
+pub fn load_server_config(tls: &TlsConfig) -> Result<ServerConfig, AppError> {
+    let certs = read_certs(&tls.cert_path)?;
+    let key = PrivateKeyDer::from_pem_file(&tls.key_path).map_err(|e| {
+        AppError::Config(format!(
+            "Cannot read TLS key {}: {}",
+            tls.key_path.display(),
+            e
+        ))
+    })?;
+
+    let provider = Arc::new(ring::default_provider());
+    let builder = ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| AppError::Config(format!("Invalid TLS protocol setup: {}", e)))?;
+
+    let builder = match &tls.client_ca_path {
+        Some(ca_path) => {
+            let mut roots = rustls::RootCertStore::empty();
+            for cert in read_certs(ca_path)? {
+                roots.add(cert).map_err(|e| {
+                    AppError::Config(format!("Invalid client CA in {}: {}", ca_path.display(), e))
+                })?;
+            }
+            let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider)
+                .build()
+                .map_err(|e| AppError::Config(format!("Invalid client CA setup: {}", e)))?;
+            builder.with_client_cert_verifier(verifier)
+        }
+        None => builder.with_no_client_auth(),
+    };
+
+    let mut config = builder
+        .with_single_cert(certs, key)
+        .map_err(|e| AppError::Config(format!("Invalid TLS certificate or key: {}", e)))?;
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+    Ok(config)
+}
+
+fn read_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>, AppError> {
+    let certs = CertificateDer::pem_file_iter(path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| AppError::Config(format!("Cannot read {}: {}", path.display(), e)))?;
+
+    if certs.is_empty() {
+        return Err(AppError::Config(format!(
+            "No certificates found in {}",
+            path.display()
+        )));
+    }
+    Ok(certs)
+}
+
+/// Latest modification time across the watched files, if all of them can be inspected.
+fn last_modified(tls: &TlsConfig) -> Option<SystemTime> {
+    tls.watched_paths()
+        .map(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+        .collect::<Option<Vec<_>>>()
+        .and_then(|times| times.into_iter().max())
+}
+
+/// Loads the initial configuration and keeps it current as the files change.
+///
+/// A rotated certificate that fails to load (e.g. caught half-written) is logged and the
+/// previous one stays in use until the next successful reload.
+///
+/// # Errors
+///
+/// * Returns 'AppError::Config' if the initial configuration cannot be loaded.
+pub fn watch(
+    tls: TlsConfig,
+    token: CancellationToken,
+) -> Result<(RustlsConfig, JoinHandle<()>), AppError> {
+    let rustls_config = RustlsConfig::from_config(Arc::new(load_server_config(&tls)?));
+    let reloadable = rustls_config.clone();
+
+    let handle = tokio::spawn(async move {
+        let mut seen = last_modified(&tls);
+        let mut ticker = tokio::time::interval(tls.reload_interval);
+        ticker.tick().await;
+
+        loop {
+            tokio::select! {
+                _ = token.cancelled() => return,
+                _ = ticker.tick() => {}
+            }
+
+            let modified = last_modified(&tls);
+            if modified.is_none() || modified == seen {
+                continue;
+            }
+
+            match load_server_config(&tls) {
+                Ok(config) => {
+                    reloadable.reload_from_config(Arc::new(config));
+                    seen = modified;
+                    tracing::info!(cert = %tls.cert_path.display(), "Reloaded TLS certificate");
+                }
+                Err(e) => tracing::warn!("Keeping previous TLS certificate: {}", e),
+            }
+        }
+    });
+
+    Ok((rustls_config, handle))
+}
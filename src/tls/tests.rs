@@ -0,0 +1,158 @@
+use crate::errors::AppError;
+use crate::tls::{TlsConfig, load_server_config, watch};
+use axum::{Router, routing::get};
+use rcgen::{BasicConstraints, CertificateParams, IsCa, KeyPair};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tokio_util::sync::CancellationToken;
+
+/// A CA and a leaf certificate it signed, as PEM.
+struct Pki {
+    ca: String,
+    cert: String,
+    key: String,
+}
+
+fn issue(name: &str) -> Pki {
+    let ca_key = KeyPair::generate().unwrap();
+    let mut ca_params = CertificateParams::new(vec![]).unwrap();
+    ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    let ca = ca_params.self_signed(&ca_key).unwrap();
+
+    let key = KeyPair::generate().unwrap();
+    let cert = CertificateParams::new(vec![name.to_string()])
+        .unwrap()
+        .signed_by(&key, &ca, &ca_key)
+        .unwrap();
+
+    Pki {
+        ca: ca.pem(),
+        cert: cert.pem(),
+        key: key.serialize_pem(),
+    }
+}
+
+/// Writes a file and bumps its modification time, so rewrites are never missed.
+fn write(path: &Path, contents: &str, age: u64) {
+    std::fs::write(path, contents).unwrap();
+    let file = std::fs::File::options().write(true).open(path).unwrap();
+    file.set_modified(SystemTime::now() + Duration::from_secs(age))
+        .unwrap();
+}
+
+fn temp_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("scheduler-tls-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn tls_config(dir: &Path, server: &Pki) -> TlsConfig {
+    write(&dir.join("cert.pem"), &server.cert, 0);
+    write(&dir.join("key.pem"), &server.key, 0);
+    TlsConfig {
+        cert_path: dir.join("cert.pem"),
+        key_path: dir.join("key.pem"),
+        client_ca_path: None,
+        reload_interval: Duration::from_millis(50),
+    }
+}
+
+/// Serves a trivial router over TLS on a random port.
+async fn serve(tls: TlsConfig, token: CancellationToken) -> String {
+    let (rustls_config, _) = watch(tls, token.clone()).unwrap();
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let handle = axum_server::Handle::new();
+    let shutdown = handle.clone();
+    tokio::spawn(async move {
+        token.cancelled().await;
+        shutdown.shutdown();
+    });
+
+    let app = Router::new().route("/", get(|| async { "ok" }));
+    tokio::spawn(
+        axum_server::from_tcp_rustls(listener, rustls_config)
+            .handle(handle)
+            .serve(app.into_make_service()),
+    );
+    format!("https://localhost:{}/", port)
+}
+
+fn client(ca: &str, identity: Option<&Pki>) -> reqwest::Client {
+    let mut builder = reqwest::Client::builder()
+        .add_root_certificate(reqwest::Certificate::from_pem(ca.as_bytes()).unwrap())
+        .resolve("localhost", "127.0.0.1:0".parse().unwrap());
+    if let Some(identity) = identity {
+        let pem = format!("{}{}", identity.cert, identity.key);
+        builder = builder.identity(reqwest::Identity::from_pem(pem.as_bytes()).unwrap());
+    }
+    builder.build().unwrap()
+}
+
+#[test]
+fn test_mismatched_key_is_rejected() {
+    let dir = temp_dir();
+    let mut tls = tls_config(&dir, &issue("localhost"));
+    write(&dir.join("other.pem"), &issue("localhost").key, 0);
+    tls.key_path = dir.join("other.pem");
+
+    assert!(matches!(load_server_config(&tls), Err(AppError::Config(_))));
+
+    tls.key_path = dir.join("missing.pem");
+    assert!(matches!(load_server_config(&tls), Err(AppError::Config(_))));
+}
+
+#[tokio::test]
+async fn test_rotated_certificate_is_picked_up() {
+    let dir = temp_dir();
+    let first = issue("localhost");
+    let tls = tls_config(&dir, &first);
+    let token = CancellationToken::new();
+    let url = serve(tls, token.clone()).await;
+
+    let body = client(&first.ca, None).get(&url).send().await.unwrap();
+    assert_eq!(body.text().await.unwrap(), "ok");
+
+    let second = issue("localhost");
+    write(&dir.join("cert.pem"), &second.cert, 10);
+    write(&dir.join("key.pem"), &second.key, 10);
+
+    let rotated = client(&second.ca, None);
+    let mut served = false;
+    for _ in 0..50 {
+        if rotated.get(&url).send().await.is_ok() {
+            served = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert!(served, "New certificate should be served without a restart");
+
+    token.cancel();
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[tokio::test]
+async fn test_mutual_tls_requires_a_client_certificate() {
+    let dir = temp_dir();
+    let server = issue("localhost");
+    let client_pki = issue("scheduler-client");
+    let mut tls = tls_config(&dir, &server);
+    write(&dir.join("client-ca.pem"), &client_pki.ca, 0);
+    tls.client_ca_path = Some(dir.join("client-ca.pem"));
+
+    let token = CancellationToken::new();
+    let url = serve(tls, token.clone()).await;
+
+    assert!(client(&server.ca, None).get(&url).send().await.is_err());
+    let response = client(&server.ca, Some(&client_pki))
+        .get(&url)
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+
+    token.cancel();
+    let _ = std::fs::remove_dir_all(dir);
+}